@@ -1,6 +1,7 @@
 use std::env;
 use std::fs;
 use std::path::Path;
+use sha2::{Digest, Sha256};
 
 fn main() {
     // 先执行 tauri_build
@@ -16,6 +17,9 @@ fn main() {
     // 注意：这个操作是可选的，如果失败不会阻止构建
     // 注意：VBS 脚本已经通过 include_str! 嵌入到 exe 中，不需要复制
     copy_config_file();
+
+    // 计算嵌入资源的 SHA256，供运行时完整性自检比对
+    embed_resource_hashes();
 }
 
 #[cfg(windows)]
@@ -49,6 +53,21 @@ fn link_cups() {
 #[cfg(not(target_os = "macos"))]
 fn link_cups() {}
 
+/// 嵌入 exe 的资源文件及其哈希对应的环境变量名
+const EMBEDDED_RESOURCES: &[(&str, &str)] = &[
+    ("EPRINTY_PRNPORT_VBS_SHA256", "scripts/prnport.vbs"),
+];
+
+fn embed_resource_hashes() {
+    for (env_name, rel_path) in EMBEDDED_RESOURCES {
+        println!("cargo:rerun-if-changed={}", rel_path);
+        // 资源缺失时 include_bytes! 同样会编译失败，这里直接中止并给出明确提示
+        let bytes = fs::read(rel_path)
+            .unwrap_or_else(|e| panic!("读取嵌入资源 {} 失败: {}", rel_path, e));
+        println!("cargo:rustc-env={}={:x}", env_name, Sha256::digest(&bytes));
+    }
+}
+
 fn copy_config_file() {
     // 获取项目根目录（src-tauri 的父目录）
     let manifest_dir = match env::var("CARGO_MANIFEST_DIR") {
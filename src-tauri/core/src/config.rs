@@ -0,0 +1,609 @@
+// 配置数据模型（printer_config.json）
+// 城市 → 区域 → 打印机 的层级结构（区域可通过 parent 嵌套为 楼宇 → 楼层）、区域 / 打印机级安装选项、driverCatalog 驱动目录，
+// 以及安装时由 driverCatalog 推导出的有效驱动规格（EffectiveDriverSpec）
+//
+// 只包含数据结构与取值常量；加载、合并、校验逻辑在应用侧（需要本地路径与网络）
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrinterConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>, // 配置文件版本号（可选，兼容旧版本）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>, // 继承的基础配置地址（可选，站点 overlay 使用；相对地址基于远程配置地址解析）
+    #[serde(rename = "driverCatalog", skip_serializing_if = "Option::is_none")]
+    pub driver_catalog: Option<std::collections::HashMap<String, DriverCatalogEntry>>,
+    pub cities: Vec<City>,
+    #[serde(rename = "fleetReporting", alias = "fleet_reporting", default, skip_serializing_if = "Option::is_none")]
+    pub fleet_reporting: Option<FleetReportingConfig>, // 机器登记上报（可选，默认关闭）
+    #[serde(rename = "downloadPolicy", alias = "download_policy", default, skip_serializing_if = "Option::is_none")]
+    pub download_policy: Option<DownloadPolicyConfig>, // 驱动包下载限速 / 按流量计费网络策略（可选）
+    #[serde(rename = "rolloutGroups", alias = "rollout_groups", default, skip_serializing_if = "Vec::is_empty")]
+    pub rollout_groups: Vec<String>, // 本机所属的灰度分组（与 version_config.json 的 target_groups 匹配，可选）
+    #[serde(rename = "crashReporting", alias = "crash_reporting", default, skip_serializing_if = "Option::is_none")]
+    pub crash_reporting: Option<CrashReportingConfig>, // 崩溃报告上传地址（可选，仅在用户确认后上传）
+    #[serde(rename = "eventLog", alias = "event_log", default, skip_serializing_if = "Option::is_none")]
+    pub event_log: Option<EventLogConfig>, // Windows 事件日志审计（可选，默认开启）
+    #[serde(rename = "printerWatch", alias = "printer_watch", default, skip_serializing_if = "Option::is_none")]
+    pub printer_watch: Option<PrinterWatchConfig>, // 后台巡检已安装打印机并自动修复（可选，默认关闭）
+    #[serde(rename = "nameTemplate", alias = "name_template", default, skip_serializing_if = "Option::is_none")]
+    pub name_template: Option<String>, // 系统队列命名模板（可选，如 "{area}-{floor}-{model}"；未设置时使用打印机 name）
+    #[serde(rename = "updatePolicy", alias = "update_policy", default, skip_serializing_if = "Option::is_none")]
+    pub update_policy: Option<UpdatePolicyConfig>, // 应用更新下载的主机白名单（可选，内置更新服务器始终允许）
+    #[serde(rename = "delegatedInstall", alias = "delegated_install", default, skip_serializing_if = "Option::is_none")]
+    pub delegated_install: Option<DelegatedInstallConfig>, // 无管理员权限时通过预注册的计划任务代为安装（可选，仅 Windows）
+    #[serde(rename = "installFeedback", alias = "install_feedback", default, skip_serializing_if = "Option::is_none")]
+    pub install_feedback: Option<InstallFeedbackConfig>, // 按区域 / 打印机汇总的匿名安装结果回传（可选，默认关闭）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub kiosk: Option<KioskConfig>, // 自助终端模式：限制可安装的区域与可执行的操作（可选，图书馆 / 公共机房使用）
+}
+
+/// 崩溃报告上传配置（未配置时崩溃报告只保存在本地日志目录）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CrashReportingConfig {
+    #[serde(default)]
+    pub endpoint: String, // 上传地址（http/https；先 POST JSON 摘要，再 POST minidump 二进制）
+}
+
+/// Windows 事件日志审计配置（安装成功 / 失败、驱动添加、打印机删除写入应用程序日志，事件源 ePrinty）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct EventLogConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enabled: Option<bool>, // false 时不写事件日志（未配置时默认开启）
+}
+
+/// 后台巡检配置：定期比对本工具安装的打印机与当前配置，端口地址变化或驱动丢失时修复 / 提示
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrinterWatchConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mode: Option<String>, // "auto"（自动重新安装）| "notify-only"（只提示）| "off"（默认）
+    #[serde(rename = "intervalHours", default, skip_serializing_if = "Option::is_none")]
+    pub interval_hours: Option<u64>, // 巡检间隔（小时，默认 6）
+}
+
+/// 应用更新下载策略
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UpdatePolicyConfig {
+    #[serde(rename = "allowedHosts", alias = "allowed_hosts", default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_hosts: Vec<String>, // 额外允许的下载主机（如 "cdn.example.com"，"*.example.com" 匹配子域名）
+}
+
+/// 委托安装配置：管理员预先注册以最高权限运行 `ePrinty.exe --run-delegated-install` 的计划任务，
+/// 普通用户进程写入请求文件后触发该任务并等待结果文件
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DelegatedInstallConfig {
+    #[serde(rename = "taskName", alias = "task_name", default)]
+    pub task_name: String, // 计划任务名称（如 "\ePrinty\DelegatedInstall"）
+    #[serde(rename = "timeoutSeconds", alias = "timeout_seconds", default, skip_serializing_if = "Option::is_none")]
+    pub timeout_seconds: Option<u64>, // 等待结果文件的超时时间（秒，默认 300）
+}
+
+/// 驱动包下载策略（4G 热点等按流量计费网络使用）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DownloadPolicyConfig {
+    #[serde(rename = "maxKbps", default, skip_serializing_if = "Option::is_none")]
+    pub max_kbps: Option<u64>, // 下载限速（KB/s，未设置或 0 表示不限速）
+    #[serde(rename = "meteredMaxKbps", default, skip_serializing_if = "Option::is_none")]
+    pub metered_max_kbps: Option<u64>, // 按流量计费网络下的限速（KB/s，未设置时沿用 maxKbps）
+    #[serde(rename = "meteredDeferAboveMb", default, skip_serializing_if = "Option::is_none")]
+    pub metered_defer_above_mb: Option<u64>, // 按流量计费网络下超过该大小的驱动包需用户确认后才下载（默认 20 MB，0 表示不推迟）
+}
+
+/// 机器登记上报配置（企业 fleet 看板使用，需显式开启）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct FleetReportingConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub endpoint: String, // 上报地址（http/https，POST JSON）
+    #[serde(rename = "intervalHours", default, skip_serializing_if = "Option::is_none")]
+    pub interval_hours: Option<u64>, // 上报内容不变时的最短重报间隔（默认 24 小时）
+}
+
+/// 安装结果回传配置：按区域 / 打印机汇总安装次数与失败错误码，攒够一批后 POST 给配置服务器（需显式开启）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallFeedbackConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub endpoint: String, // 回传地址（http/https，POST JSON）
+    #[serde(rename = "batchSize", alias = "batch_size", default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<u32>, // 累计多少次安装后自动回传（默认 20，1-1000）
+}
+
+/// 自助终端模式配置：配置了 kiosk 时只允许 allowedActions 中的操作，安装只允许 allowedAreas 中的打印机
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KioskConfig {
+    #[serde(rename = "allowedAreas", alias = "allowed_areas", default, skip_serializing_if = "Vec::is_empty")]
+    pub allowed_areas: Vec<String>, // 允许安装的区域 areaName（包含其下级区域；为空表示不限区域）
+    #[serde(rename = "allowedActions", alias = "allowed_actions", default)]
+    pub allowed_actions: Vec<String>, // 允许的操作（取值见 VALID_KIOSK_ACTIONS；为空表示全部禁止）
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct City {
+    #[serde(rename = "cityId")]
+    pub city_id: String,
+    #[serde(rename = "cityName")]
+    pub city_name: String,
+    pub areas: Vec<Area>,
+}
+
+impl City {
+    /// 区域从顶层到自身的名称路径（如 ["A栋", "3楼"]）
+    ///
+    /// parent 指向不存在的区域或成环时在该处截断（配置校验会拒绝这类配置）
+    pub fn area_path(&self, area_name: &str) -> Vec<String> {
+        let mut path = vec![area_name.to_string()];
+        let mut current = area_name;
+        while let Some(parent) = self
+            .areas
+            .iter()
+            .find(|a| a.area_name == current)
+            .and_then(|a| a.parent.as_deref())
+        {
+            if path.iter().any(|p| p == parent) || !self.areas.iter().any(|a| a.area_name == parent) {
+                break;
+            }
+            path.insert(0, parent.to_string());
+            current = parent;
+        }
+        path
+    }
+
+    /// 检查区域层级：parent 必须指向同城市内的其他区域，且不能成环
+    ///
+    /// # 返回
+    /// - 问题说明列表（为空表示层级合法）
+    pub fn area_hierarchy_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        for area in &self.areas {
+            let Some(parent) = area.parent.as_deref() else {
+                continue;
+            };
+            if !self.areas.iter().any(|a| a.area_name == parent) {
+                problems.push(format!("区域 '{}' 的 parent='{}' 不存在", area.area_name, parent));
+                continue;
+            }
+            // 沿 parent 链向上走，回到自身即成环
+            let mut seen = vec![area.area_name.as_str()];
+            let mut current = Some(parent);
+            while let Some(name) = current {
+                if seen.contains(&name) {
+                    problems.push(format!("区域 '{}' 的 parent 层级成环", area.area_name));
+                    break;
+                }
+                seen.push(name);
+                current = self.areas.iter().find(|a| a.area_name == name).and_then(|a| a.parent.as_deref());
+            }
+        }
+        problems
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Area {
+    #[serde(rename = "areaName")]
+    pub area_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>, // 上级区域的 areaName（可选，同城市内；如楼层的 parent 为所在楼宇，未设置即顶层区域）
+    #[serde(flatten)]
+    pub defaults: InstallOptions, // 区域级默认安装选项（打印机未设置时继承）
+    pub printers: Vec<Printer>,
+}
+
+/// 安装选项（区域级为默认值，打印机级覆盖）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstallOptions {
+    #[serde(rename = "driverInstallPolicy", default, skip_serializing_if = "Option::is_none")]
+    pub driver_install_policy: Option<String>, // "always" | "reuse_if_installed"
+    #[serde(rename = "installMode", default, skip_serializing_if = "Option::is_none")]
+    pub install_mode: Option<String>, // "auto" | "package" | "installer" | "ipp" | "legacy_inf"
+    #[serde(rename = "portProtocol", default, skip_serializing_if = "Option::is_none")]
+    pub port_protocol: Option<String>, // "raw" | "lpr"（Windows TCP/IP 端口协议）
+    #[serde(rename = "installScope", default, skip_serializing_if = "Option::is_none")]
+    pub install_scope: Option<String>, // "auto" | "machine" | "session"（RDS 终端服务器上的安装范围）
+    #[serde(rename = "autoRetry", default, skip_serializing_if = "Option::is_none")]
+    pub auto_retry: Option<bool>, // 驱动类错误时自动换策略重试一次（默认关闭，仅 Windows）
+    #[serde(rename = "printProcessor", default, skip_serializing_if = "Option::is_none")]
+    pub print_processor: Option<String>, // 安装后设置的打印处理器（如 "winprint"，仅 Windows）
+    #[serde(rename = "datatype", default, skip_serializing_if = "Option::is_none")]
+    pub datatype: Option<String>, // 安装后设置的默认数据类型（如 "RAW"，仅 Windows）
+    #[serde(rename = "verifyProbe", default, skip_serializing_if = "Option::is_none")]
+    pub verify_probe: Option<String>, // 安装后校验的额外探测："none" | "zero_page" | "pjl_status"（仅 Windows）
+    #[serde(rename = "ippFallback", default, skip_serializing_if = "Option::is_none")]
+    pub ipp_fallback: Option<bool>, // 未找到匹配的厂商驱动且设备支持 IPP 时改用 Microsoft IPP Class Driver（默认开启，仅 Windows 10 1903+）
+    #[serde(rename = "portProtocolAutoSelect", default, skip_serializing_if = "Option::is_none")]
+    pub port_protocol_auto_select: Option<bool>, // 预检时配置的端口协议不可达但另一协议可达，自动改用可达的协议（默认关闭，只提示，仅 Windows）
+    #[serde(rename = "driverPin", default, skip_serializing_if = "Option::is_none")]
+    pub driver_pin: Option<DriverPin>, // 创建队列前校验所选驱动来自指定的驱动包，不一致时失败（DRIVER_MISMATCH，仅 Windows）
+}
+
+/// 驱动包固定（printers[].driverPin / 区域 defaults.driverPin）
+///
+/// 同名驱动可能来自不同驱动包（厂商通用驱动与型号专用驱动同名等），按名称匹配时可能绑定到错误的驱动包；
+/// 设置后 Add-Printer 前读取所选驱动的 InfPath 校验，两项都设置时都须一致
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPin {
+    /// 驱动包在 DriverStore 中的发布名称（oemNN.inf，机房镜像统一导入驱动时使用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub published_name: Option<String>,
+    /// 驱动 INF 文件的 sha256（`Get-FileHash (Get-PrinterDriver -Name <驱动名>).InfPath`，与导入顺序无关）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inf_sha256: Option<String>,
+}
+
+impl DriverPin {
+    /// 校验配置取值（至少设置一项；publishedName 为 oemNN.inf，infSha256 为 64 位十六进制）
+    pub fn validate(&self) -> Result<(), String> {
+        if self.published_name.is_none() && self.inf_sha256.is_none() {
+            return Err("至少需要设置 publishedName 或 infSha256".to_string());
+        }
+        if let Some(name) = &self.published_name {
+            let lower = name.trim().to_ascii_lowercase();
+            let digits = lower.strip_prefix("oem").and_then(|s| s.strip_suffix(".inf")).unwrap_or_default();
+            if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+                return Err(format!("publishedName=\"{}\" 无效，应为 oemNN.inf", name));
+            }
+        }
+        if let Some(sha256) = &self.inf_sha256 {
+            if sha256.trim().len() != 64 || !sha256.trim().chars().all(|c| c.is_ascii_hexdigit()) {
+                return Err(format!("infSha256=\"{}\" 无效，应为 64 位十六进制", sha256));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl InstallOptions {
+    /// 合并选项：自身已设置的字段优先，未设置的取 `defaults`
+    pub fn or(&self, defaults: &InstallOptions) -> InstallOptions {
+        InstallOptions {
+            driver_install_policy: self.driver_install_policy.clone().or_else(|| defaults.driver_install_policy.clone()),
+            install_mode: self.install_mode.clone().or_else(|| defaults.install_mode.clone()),
+            port_protocol: self.port_protocol.clone().or_else(|| defaults.port_protocol.clone()),
+            install_scope: self.install_scope.clone().or_else(|| defaults.install_scope.clone()),
+            auto_retry: self.auto_retry.or(defaults.auto_retry),
+            print_processor: self.print_processor.clone().or_else(|| defaults.print_processor.clone()),
+            datatype: self.datatype.clone().or_else(|| defaults.datatype.clone()),
+            verify_probe: self.verify_probe.clone().or_else(|| defaults.verify_probe.clone()),
+            ipp_fallback: self.ipp_fallback.or(defaults.ipp_fallback),
+            port_protocol_auto_select: self.port_protocol_auto_select.or(defaults.port_protocol_auto_select),
+            driver_pin: self.driver_pin.clone().or_else(|| defaults.driver_pin.clone()),
+        }
+    }
+}
+
+/// 合法的 installMode 取值
+pub const VALID_INSTALL_MODES: [&str; 5] = ["auto", "package", "installer", "ipp", "legacy_inf"];
+
+/// 合法的 driverInstallPolicy 取值
+pub const VALID_DRIVER_INSTALL_POLICIES: [&str; 2] = ["always", "reuse_if_installed"];
+
+/// 合法的 portProtocol 取值
+pub const VALID_PORT_PROTOCOLS: [&str; 2] = ["raw", "lpr"];
+
+/// 合法的 printerWatch.mode 取值
+pub const VALID_PRINTER_WATCH_MODES: [&str; 3] = ["auto", "notify-only", "off"];
+
+/// nameTemplate 支持的占位符
+pub const VALID_NAME_TEMPLATE_PLACEHOLDERS: [&str; 5] = ["city", "area", "floor", "model", "name"];
+
+/// 合法的 installScope 取值
+pub const VALID_INSTALL_SCOPES: [&str; 3] = ["auto", "machine", "session"];
+
+/// 合法的 verifyProbe 取值
+pub const VALID_VERIFY_PROBES: [&str; 3] = ["none", "zero_page", "pjl_status"];
+
+/// kiosk.allowedActions 合法的操作名称
+pub const VALID_KIOSK_ACTIONS: [&str; 10] = [
+    "install",
+    "delete",
+    "testPage",
+    "printFile",
+    "queueControl",
+    "portConfig",
+    "credentials",
+    "driverMaintenance",
+    "settings",
+    "diagnostics",
+];
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Printer {
+    pub name: String,
+    pub path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>, // 打印机型号（可选）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver_path: Option<String>, // 驱动路径（可选，相对于应用目录）
+    #[serde(default)]
+    pub driver_names: Option<Vec<String>>, // 驱动名称列表（可选，用于 Windows 安装校验）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_mode: Option<String>, // 安装方式（可选）："auto" | "package" | "installer" | "ipp" | "legacy_inf"
+    #[serde(rename = "driverKey", skip_serializing_if = "Option::is_none")]
+    pub driver_key: Option<String>, // 驱动目录键（可选，用于引用 driverCatalog）
+    #[serde(rename = "betaDriverKey", default, skip_serializing_if = "Option::is_none")]
+    pub beta_driver_key: Option<String>, // 测试版驱动目录键（可选，本机驱动通道为 beta 时替代 driverKey）
+    #[serde(rename = "imageUrl", alias = "image_url", default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>, // 打印机图片地址（可选，覆盖 driverCatalog 条目的图片）
+    #[serde(flatten)]
+    pub options: InstallOptions, // 打印机级安装选项（覆盖区域默认值）
+    #[serde(rename = "postInstall", alias = "post_install", default, skip_serializing_if = "Option::is_none")]
+    pub post_install: Option<PostInstallConfig>, // 安装成功后执行的白名单动作（可选，需签名，仅 Windows）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<PrinterPermissionsConfig>, // 安装后设置的打印队列权限（可选，仅 Windows）
+}
+
+/// 打印队列权限：允许 / 拒绝打印的账户（SID、常用组名或 域\账户名）
+/// 配置后只有 allow 中的账户（以及管理员）可以使用该打印机
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PrinterPermissionsConfig {
+    #[serde(default)]
+    pub allow: Vec<String>,
+    #[serde(default)]
+    pub deny: Vec<String>,
+}
+
+/// 安装后动作列表：只允许白名单操作，整个列表（连同打印机名称与驱动包 SHA256）需用管理员私钥签名
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PostInstallConfig {
+    #[serde(default)]
+    pub actions: Vec<PostInstallAction>,
+    #[serde(rename = "packageSha256", default, skip_serializing_if = "Option::is_none")]
+    pub package_sha256: Option<String>, // 厂商工具所在驱动包的 SHA256（含 runVendorUtility 时必填）
+    #[serde(default)]
+    pub signature: String, // Ed25519 签名（128 位十六进制）
+}
+
+/// 安装后动作（不支持任意命令字符串）
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum PostInstallAction {
+    /// 设置打印机配置项（如记账码）
+    #[serde(rename = "setConfig")]
+    SetConfig { key: String, value: String },
+    /// 运行驱动包内的厂商工具（path 相对于驱动包解压目录，参数固定，sha256 为工具文件的哈希）
+    #[serde(rename = "runVendorUtility")]
+    RunVendorUtility {
+        path: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default)]
+        sha256: String,
+    },
+}
+
+/// 驱动目录条目
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverCatalogEntry {
+    #[serde(rename = "installMode", skip_serializing_if = "Option::is_none")]
+    pub install_mode: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub local: Option<DriverLocalSpec>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote: Option<DriverRemoteSpec>, // M1 只解析不使用
+    #[serde(rename = "imageUrl", alias = "image_url", default, skip_serializing_if = "Option::is_none")]
+    pub image_url: Option<String>, // 型号图片地址（可选，引用该驱动的打印机共用）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub compatibility: Option<DriverCompatibility>, // 驱动支持的 Windows 版本范围（可选，安装前检查）
+}
+
+/// 驱动支持的 Windows 版本范围
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverCompatibility {
+    /// 最低支持的 Windows 构建号（含，如 17763）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_build: Option<u32>,
+    /// 最高支持的 Windows 构建号（含，如 22000 表示不支持 Windows 11 22H2 及以上）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_build: Option<u32>,
+    /// 支持的系统类型："client"（桌面版）/ "server"（服务器版）；为空表示都支持
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub editions: Vec<String>,
+}
+
+/// compatibility.editions 允许的取值
+pub const DRIVER_EDITIONS: &[&str] = &["client", "server"];
+
+impl DriverCompatibility {
+    /// 支持范围的说明文字（用于错误提示）
+    pub fn describe(&self) -> String {
+        let builds = match (self.min_build, self.max_build) {
+            (Some(min), Some(max)) => format!("构建号 {} ~ {}", min, max),
+            (Some(min), None) => format!("构建号 {} 及以上", min),
+            (None, Some(max)) => format!("构建号 {} 及以下", max),
+            (None, None) => "所有构建号".to_string(),
+        };
+        let editions: Vec<&str> = self
+            .editions
+            .iter()
+            .map(|e| if e.eq_ignore_ascii_case("server") { "服务器版" } else { "桌面版" })
+            .collect();
+        if editions.is_empty() {
+            format!("Windows {}", builds)
+        } else {
+            format!("Windows {}（{}）", builds, editions.join("、"))
+        }
+    }
+
+    /// 校验配置取值（min_build 不大于 max_build，editions 取值合法）
+    pub fn validate(&self) -> Result<(), String> {
+        if let (Some(min), Some(max)) = (self.min_build, self.max_build) {
+            if min > max {
+                return Err(format!("minBuild={} 大于 maxBuild={}", min, max));
+            }
+        }
+        if let Some(edition) = self.editions.iter().find(|e| !DRIVER_EDITIONS.iter().any(|v| v.eq_ignore_ascii_case(e))) {
+            return Err(format!("editions 包含无效取值 \"{}\"，可选值：{}", edition, DRIVER_EDITIONS.join(" / ")));
+        }
+        Ok(())
+    }
+
+    /// 检查当前系统是否在支持范围内
+    ///
+    /// # 参数
+    /// - `build`: 当前 Windows 构建号
+    /// - `is_server`: 当前系统是否为服务器版
+    ///
+    /// # 返回
+    /// - `Ok(())`: 支持
+    /// - `Err(String)`: 不支持的原因（含支持范围）
+    pub fn check(&self, build: u32, is_server: bool) -> Result<(), String> {
+        let edition = if is_server { "server" } else { "client" };
+        let build_ok = self.min_build.is_none_or(|min| build >= min) && self.max_build.is_none_or(|max| build <= max);
+        let edition_ok = self.editions.is_empty() || self.editions.iter().any(|e| e.eq_ignore_ascii_case(edition));
+        if build_ok && edition_ok {
+            return Ok(());
+        }
+        Err(format!(
+            "当前系统（Windows 构建号 {}，{}）不在驱动支持范围内，支持范围：{}",
+            build,
+            if is_server { "服务器版" } else { "桌面版" },
+            self.describe()
+        ))
+    }
+}
+
+/// 本地驱动规格
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverLocalSpec {
+    #[serde(rename = "infRel", skip_serializing_if = "Option::is_none")]
+    pub inf_rel: Option<String>, // INF 文件相对路径（相对于 driversRoot）
+    #[serde(rename = "driverNames", skip_serializing_if = "Option::is_none")]
+    pub driver_names: Option<Vec<String>>, // 驱动名称列表
+}
+
+/// 远程驱动规格（M1 只解析不使用）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverRemoteSpec {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layout: Option<String>,
+    #[serde(alias = "downloadSize", alias = "download_size", default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>, // 驱动包字节数（可选；下载前与 HEAD 的 Content-Length 比较，不一致时不下载）
+    #[serde(rename = "archivePasswordRef", default, skip_serializing_if = "Option::is_none")]
+    pub archive_password_ref: Option<String>, // 加密 ZIP 的密码引用名（密码本身保存在系统凭据管理器，不写入配置）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub patch: Option<DriverPatchSpec>, // 增量补丁（本机缓存有 baseSha256 对应的旧驱动包时只下载补丁）
+}
+
+/// 驱动包增量补丁规格（补丁由 build_driver_patch 制作，见 zip_patch）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriverPatchSpec {
+    #[serde(rename = "baseSha256", skip_serializing_if = "Option::is_none")]
+    pub base_sha256: Option<String>, // 补丁所基于的旧驱动包 SHA256
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sha256: Option<String>, // 补丁文件本身的 SHA256
+    #[serde(alias = "downloadSize", alias = "download_size", default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+}
+
+/// 远程驱动解析结果（M2.5/M3 使用）
+#[derive(Debug, Clone)]
+pub struct RemoteDriverResolved {
+    pub url: String,
+    pub sha256: String,
+    pub version: Option<String>,
+    pub layout: Option<String>,
+    pub size: Option<u64>,
+    pub archive_password_ref: Option<String>,
+    pub driver_key: String,
+    /// 增量补丁（baseSha256 / url / sha256 同时存在时才为 Some）
+    pub patch: Option<DriverPatchResolved>,
+}
+
+/// 增量补丁解析结果
+#[derive(Debug, Clone)]
+pub struct DriverPatchResolved {
+    pub base_sha256: String,
+    pub url: String,
+    pub sha256: String,
+    pub size: Option<u64>,
+}
+
+/// 有效驱动规格（推导结果）
+#[derive(Debug, Clone)]
+pub struct EffectiveDriverSpec {
+    pub source: String, // "catalog" 或 "legacy"
+    pub effective_install_mode: Option<String>,
+    pub effective_driver_path: Option<String>, // 相对路径（以 driversRoot 为基准）
+    pub effective_driver_names: Vec<String>,
+    pub driver_key_used: Option<String>,
+    /// 远程驱动信息（M2.5：仅解析，M3 使用）
+    /// 只有当 source=catalog 且 catalog_entry.remote.url+sha256 同时存在时才为 Some
+    pub remote_driver: Option<RemoteDriverResolved>,
+    /// 驱动支持的 Windows 版本范围（仅 source=catalog 时可能为 Some）
+    pub compatibility: Option<DriverCompatibility>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn city(areas: &[(&str, Option<&str>)]) -> City {
+        City {
+            city_id: "bj".to_string(),
+            city_name: "北京".to_string(),
+            areas: areas
+                .iter()
+                .map(|(name, parent)| Area {
+                    area_name: name.to_string(),
+                    parent: parent.map(str::to_string),
+                    defaults: InstallOptions::default(),
+                    printers: Vec::new(),
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn resolves_area_paths_and_rejects_broken_parents() {
+        let ok = city(&[("A栋", None), ("3楼", Some("A栋")), ("东区", Some("3楼")), ("旧区域", None)]);
+        assert_eq!(ok.area_path("东区"), vec!["A栋", "3楼", "东区"]);
+        assert_eq!(ok.area_path("旧区域"), vec!["旧区域"]);
+        assert!(ok.area_hierarchy_problems().is_empty());
+
+        let broken = city(&[("1楼", Some("B栋")), ("甲", Some("乙")), ("乙", Some("甲"))]);
+        assert_eq!(broken.area_path("甲"), vec!["乙", "甲"]);
+        assert_eq!(broken.area_hierarchy_problems().len(), 3);
+    }
+
+    #[test]
+    fn checks_driver_compatibility_range() {
+        let compat = DriverCompatibility {
+            min_build: Some(17763),
+            max_build: Some(22000),
+            editions: vec!["client".to_string()],
+        };
+        assert!(compat.check(19045, false).is_ok());
+        assert!(compat.check(22000, false).is_ok());
+        let err = compat.check(22621, false).unwrap_err();
+        assert!(err.contains("22621") && err.contains("构建号 17763 ~ 22000（桌面版）"));
+        assert!(compat.check(20348, true).is_err());
+        assert!(DriverCompatibility::default().check(9600, true).is_ok());
+    }
+
+    #[test]
+    fn validates_driver_pin() {
+        let pin = |published: Option<&str>, sha: Option<&str>| DriverPin {
+            published_name: published.map(str::to_string),
+            inf_sha256: sha.map(str::to_string),
+        };
+        assert!(pin(Some("oem42.inf"), None).validate().is_ok());
+        assert!(pin(Some("OEM7.INF"), Some(&"ab".repeat(32))).validate().is_ok());
+        assert!(pin(Some("hpcu250u.inf"), None).validate().unwrap_err().contains("oemNN.inf"));
+        assert!(pin(None, Some("abc")).validate().is_err());
+        assert!(pin(None, None).validate().is_err());
+    }
+}
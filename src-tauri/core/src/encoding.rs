@@ -0,0 +1,131 @@
+// 文本编码模块（子进程输出、脚本 / 测试页文件、INF 文件共用）
+//
+// 解码顺序（decode_bytes）：
+// 1. UTF-16LE / UTF-16BE BOM → 按 UTF-16 解码
+// 2. UTF-8 BOM → 去掉 BOM 后按 UTF-8 解码
+// 3. 无 BOM 但形如 UTF-16LE（ASCII 字符的高字节全为 0，如 wmic / reg export 的输出）→ 按 UTF-16LE 解码
+// 4. 合法 UTF-8 → 直接使用
+// 5. GBK（中文 Windows 的 ANSI 代码页，PowerShell 未设置 OutputEncoding 时的输出）
+// 6. 以上都失败时 UTF-8 lossy
+//
+// 写入：PowerShell 5.1 读取脚本文件、Out-Printer 读取测试页时依赖 UTF-8 BOM 识别编码，
+// 需要 BOM 的内容统一用 encode_utf8_with_bom 编码
+
+use encoding_rs::GBK;
+
+/// UTF-8 BOM
+pub const UTF8_BOM: [u8; 3] = [0xEF, 0xBB, 0xBF];
+
+/// UTF-16LE BOM
+const UTF16LE_BOM: [u8; 2] = [0xFF, 0xFE];
+
+/// UTF-16BE BOM
+const UTF16BE_BOM: [u8; 2] = [0xFE, 0xFF];
+
+fn decode_utf16(bytes: &[u8], little_endian: bool) -> String {
+    let wide: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|c| if little_endian { u16::from_le_bytes([c[0], c[1]]) } else { u16::from_be_bytes([c[0], c[1]]) })
+        .collect();
+    String::from_utf16_lossy(&wide).trim_end_matches('\0').to_string()
+}
+
+/// 无 BOM 的 UTF-16LE 文本：偶数长度、奇数位上至少一半是 0、偶数位不含 0
+///
+/// GBK / UTF-8 文本中不会出现 0 字节，不会被误判
+fn looks_like_utf16le(bytes: &[u8]) -> bool {
+    if bytes.len() < 4 || !bytes.len().is_multiple_of(2) {
+        return false;
+    }
+    let high_zero = bytes.iter().skip(1).step_by(2).filter(|b| **b == 0).count();
+    let low_zero = bytes.iter().step_by(2).filter(|b| **b == 0).count();
+    low_zero == 0 && high_zero * 2 >= bytes.len() / 2
+}
+
+/// 解码未知编码的文本字节（UTF-16 / UTF-8 / GBK，见模块说明）
+pub fn decode_bytes(bytes: &[u8]) -> String {
+    if let Some(rest) = bytes.strip_prefix(&UTF16LE_BOM) {
+        return decode_utf16(rest, true);
+    }
+    if let Some(rest) = bytes.strip_prefix(&UTF16BE_BOM) {
+        return decode_utf16(rest, false);
+    }
+    let bytes = bytes.strip_prefix(&UTF8_BOM).unwrap_or(bytes);
+    if looks_like_utf16le(bytes) {
+        return decode_utf16(bytes, true);
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        return text.to_string();
+    }
+    let (decoded, had_errors) = GBK.decode_without_bom_handling(bytes);
+    if had_errors {
+        String::from_utf8_lossy(bytes).to_string()
+    } else {
+        decoded.to_string()
+    }
+}
+
+/// 解码子进程（PowerShell / cmd / pnputil 等）的 stdout / stderr
+///
+/// 与 decode_bytes 相同；保留原名称，调用处一眼可知是在处理 Windows 控制台输出
+pub fn decode_windows_string(bytes: &[u8]) -> String {
+    decode_bytes(bytes)
+}
+
+/// 文本编码为带 UTF-8 BOM 的字节
+pub fn encode_utf8_with_bom(text: &str) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(text.len() + UTF8_BOM.len());
+    bytes.extend_from_slice(&UTF8_BOM);
+    bytes.extend_from_slice(text.as_bytes());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 中文 Windows 上 PowerShell 未设置 OutputEncoding 时 Add-Printer 的报错（GBK）："拒绝访问。"
+    const GBK_ACCESS_DENIED: &[u8] = &[0xBE, 0xDC, 0xBE, 0xF8, 0xB7, 0xC3, 0xCE, 0xCA, 0xA1, 0xA3];
+
+    /// pnputil 的 GBK 输出片段："驱动程序包已添加"
+    const GBK_PNPUTIL: &[u8] = &[
+        0xC7, 0xFD, 0xB6, 0xAF, 0xB3, 0xCC, 0xD0, 0xF2, 0xB0, 0xFC, 0xD2, 0xD1, 0xCC, 0xED, 0xBC, 0xD3,
+    ];
+
+    /// UTF-16LE（带 BOM）的 INF 片段："[Version]\r\n"
+    const UTF16LE_INF: &[u8] = &[
+        0xFF, 0xFE, b'[', 0, b'V', 0, b'e', 0, b'r', 0, b's', 0, b'i', 0, b'o', 0, b'n', 0, b']', 0, b'\r', 0, b'\n', 0,
+    ];
+
+    /// wmic 输出（UTF-16LE，无 BOM）："打印 OK"
+    const UTF16LE_NO_BOM: &[u8] = &[0x53, 0x62, 0x70, 0x53, b' ', 0, b'O', 0, b'K', 0];
+
+    #[test]
+    fn decodes_gbk_console_output() {
+        assert_eq!(decode_windows_string(GBK_ACCESS_DENIED), "拒绝访问。");
+        assert_eq!(decode_bytes(GBK_PNPUTIL), "驱动程序包已添加");
+    }
+
+    #[test]
+    fn decodes_utf8_with_and_without_bom() {
+        let text = "打印机 HP LaserJet 已安装";
+        assert_eq!(decode_bytes(text.as_bytes()), text);
+        assert_eq!(decode_bytes(&encode_utf8_with_bom(text)), text);
+        // 只有 BOM 的输出（PowerShell 设置 UTF8 OutputEncoding 后的空输出）
+        assert_eq!(decode_bytes(&UTF8_BOM), "");
+    }
+
+    #[test]
+    fn decodes_utf16_with_and_without_bom() {
+        assert_eq!(decode_bytes(UTF16LE_INF), "[Version]\r\n");
+        assert_eq!(decode_bytes(&[0xFE, 0xFF, 0x62, 0x53, 0x53, 0x70]), "打印");
+        assert_eq!(decode_bytes(UTF16LE_NO_BOM), "打印 OK");
+        // 奇数长度 / 没有 0 字节的内容不会按 UTF-16 解码
+        assert_eq!(decode_bytes(b"OK\n"), "OK\n");
+    }
+
+    #[test]
+    fn falls_back_to_lossy_for_invalid_bytes() {
+        assert_eq!(decode_bytes(&[b'a', 0xFF, b'b']), "a\u{FFFD}b");
+    }
+}
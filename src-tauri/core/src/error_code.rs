@@ -0,0 +1,31 @@
+// 稳定错误码标记：安装结果的 stderr 与命令错误信息以 [EASYPRINTER_CODE=XXX] 携带错误码，
+// 安装历史、自动重试与 api_* 命令统一按此提取
+
+/// 错误码标记前缀
+pub const CODE_TAG_PREFIX: &str = "[EASYPRINTER_CODE=";
+
+/// 提取文本中第一个错误码标记
+///
+/// # 返回
+/// - `Some(code)`: 标记中的错误码
+/// - `None`: 没有标记、标记未闭合或错误码为空
+pub fn error_code(text: &str) -> Option<&str> {
+    let rest = text.split(CODE_TAG_PREFIX).nth(1)?;
+    let code = &rest[..rest.find(']')?];
+    Some(code).filter(|c| !c.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn extracts_first_error_code_tag() {
+        assert_eq!(error_code("[EASYPRINTER_CODE=WIN_DRIVER_NOT_FOUND] 未找到驱动"), Some("WIN_DRIVER_NOT_FOUND"));
+        assert_eq!(error_code("stderr\n[EASYPRINTER_CODE=WIN_INF_INSTALL_FAILED]"), Some("WIN_INF_INSTALL_FAILED"));
+        assert_eq!(error_code("[EASYPRINTER_CODE=A] [EASYPRINTER_CODE=B]"), Some("A"));
+        assert_eq!(error_code("[EASYPRINTER_CODE=] 空错误码"), None);
+        assert_eq!(error_code("[EASYPRINTER_CODE=UNTERMINATED"), None);
+        assert_eq!(error_code("Add-Printer 失败"), None);
+    }
+}
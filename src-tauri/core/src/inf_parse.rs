@@ -0,0 +1,224 @@
+/// INF 文件解析工具模块
+///
+/// 只解析打印机驱动安装需要的少量信息：
+/// - [Version] 段的 Class / DriverVer
+/// - [Manufacturer] 段引用的型号段中的驱动名称（即 Add-PrinterDriver -Name 使用的名称）
+/// - [Strings] 段的 %token% 替换
+///
+/// 兼容 UTF-16LE BOM、UTF-8 BOM 与 ANSI（GBK）编码
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// INF 解析结果
+#[derive(Debug, Clone, Default)]
+pub struct InfSummary {
+    /// [Version] Class（打印机驱动为 "Printer"）
+    pub class: Option<String>,
+    /// DriverVer 的日期部分（如 "06/21/2023"）
+    pub driver_date: Option<String>,
+    /// DriverVer 的版本号部分（如 "3.2.1.0"）
+    pub driver_version: Option<String>,
+    /// 型号段中声明的驱动名称（去重，保持出现顺序）
+    pub model_names: Vec<String>,
+}
+
+impl InfSummary {
+    /// 是否为打印机类驱动
+    pub fn is_printer_class(&self) -> bool {
+        self.class
+            .as_deref()
+            .map(|c| c.eq_ignore_ascii_case("Printer"))
+            .unwrap_or(false)
+    }
+}
+
+/// 读取 INF 文件文本（兼容 UTF-16LE BOM 与 ANSI/UTF-8）
+pub fn read_inf_text(inf_path: &Path) -> Result<String, String> {
+    let bytes = std::fs::read(inf_path)
+        .map_err(|e| format!("读取 INF 文件失败 {}: {}", inf_path.display(), e))?;
+    Ok(decode_inf_bytes(&bytes))
+}
+
+/// 解码 INF 字节内容（ZIP 内条目等不落盘的场景使用）
+pub fn decode_inf_bytes(bytes: &[u8]) -> String {
+    crate::encoding::decode_bytes(bytes)
+}
+
+/// 去掉行尾注释（引号内的 ';' 不视为注释）
+fn strip_comment(line: &str) -> &str {
+    let mut in_quotes = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ';' if !in_quotes => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// 按段拆分 INF 文本：段名（小写）→ 段内有效行
+fn split_sections(inf_text: &str) -> HashMap<String, Vec<String>> {
+    let mut sections: HashMap<String, Vec<String>> = HashMap::new();
+    let mut current: Option<String> = None;
+    for raw in inf_text.lines() {
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        if line.starts_with('[') {
+            current = line
+                .strip_prefix('[')
+                .and_then(|l| l.split(']').next())
+                .map(|name| name.trim().to_lowercase());
+            if let Some(name) = &current {
+                sections.entry(name.clone()).or_default();
+            }
+            continue;
+        }
+        if let Some(name) = &current {
+            sections.entry(name.clone()).or_default().push(line.to_string());
+        }
+    }
+    sections
+}
+
+fn split_key_value(line: &str) -> Option<(&str, &str)> {
+    line.split_once('=').map(|(k, v)| (k.trim(), v.trim()))
+}
+
+/// 解析值：去引号并替换 %token%（[Strings] 中找不到时保留原样）
+fn resolve_value(value: &str, strings: &HashMap<String, String>) -> String {
+    let value = value.trim().trim_matches('"');
+    if value.len() > 2 && value.starts_with('%') && value.ends_with('%') {
+        let key = value[1..value.len() - 1].to_lowercase();
+        if let Some(resolved) = strings.get(&key) {
+            return resolved.clone();
+        }
+    }
+    value.to_string()
+}
+
+/// 从 INF 文本中解析 [Version] 段的 DriverVer 版本号
+///
+/// 格式：`DriverVer = 06/21/2023,3.2.1.0`（版本号部分可省略）
+pub fn parse_driver_ver(inf_text: &str) -> Option<String> {
+    parse_inf(inf_text).driver_version
+}
+
+/// 解析 INF 文本
+pub fn parse_inf(inf_text: &str) -> InfSummary {
+    let sections = split_sections(inf_text);
+
+    let strings: HashMap<String, String> = sections
+        .get("strings")
+        .map(|lines| {
+            lines
+                .iter()
+                .filter_map(|l| split_key_value(l))
+                .map(|(k, v)| (k.to_lowercase(), v.trim_matches('"').to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    let mut summary = InfSummary::default();
+    for (key, value) in sections
+        .get("version")
+        .map(|lines| lines.iter().filter_map(|l| split_key_value(l)).collect::<Vec<_>>())
+        .unwrap_or_default()
+    {
+        if key.eq_ignore_ascii_case("Class") {
+            summary.class = Some(resolve_value(value, &strings));
+        } else if key.eq_ignore_ascii_case("DriverVer") {
+            let mut parts = value.split(',').map(|p| p.trim().trim_matches('"'));
+            summary.driver_date = parts.next().filter(|p| !p.is_empty()).map(|p| p.to_string());
+            summary.driver_version = parts.next().filter(|p| !p.is_empty()).map(|p| p.to_string());
+        }
+    }
+
+    // [Manufacturer]：%MFG% = Models, NTamd64, NTx86 → 型号段 Models / Models.NTamd64 / Models.NTx86
+    let mut model_sections = Vec::new();
+    for line in sections.get("manufacturer").cloned().unwrap_or_default() {
+        let value = match split_key_value(&line) {
+            Some((_, v)) => v.to_string(),
+            None => line.clone(),
+        };
+        let mut parts = value.split(',').map(|p| p.trim().to_lowercase());
+        let base = match parts.next().filter(|p| !p.is_empty()) {
+            Some(b) => b,
+            None => continue,
+        };
+        model_sections.push(base.clone());
+        model_sections.extend(parts.filter(|p| !p.is_empty()).map(|decoration| format!("{}.{}", base, decoration)));
+    }
+
+    for section in model_sections {
+        for line in sections.get(&section).map(|l| l.as_slice()).unwrap_or_default() {
+            let name = match split_key_value(line) {
+                Some((k, _)) => resolve_value(k, &strings),
+                None => continue,
+            };
+            if !name.is_empty() && !summary.model_names.contains(&name) {
+                summary.model_names.push(name);
+            }
+        }
+    }
+
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE_INF: &str = r#"
+[Version]
+Signature="$Windows NT$"
+Class=Printer
+ClassGUID={4D36E979-E325-11CE-BFC1-08002BE10318}
+DriverVer=06/21/2023,3.2.1.0 ; comment
+
+[Manufacturer]
+%HP% = HP, NTamd64
+
+[HP.NTamd64]
+"HP LaserJet MFP M227-M231 PCL-6 (V4)" = HPInstall, USBPRINT\HPLaserJet1234
+%MODEL_ALT% = HPInstall, WSDPRINT\HPLaserJet1234
+"HP LaserJet MFP M227-M231 PCL-6 (V4)" = HPInstall, LPTENUM\HPLaserJet1234
+
+[Strings]
+HP = "HP"
+MODEL_ALT = "HP LaserJet MFP M227 (WSD)"
+"#;
+
+    #[test]
+    fn parses_version_and_models() {
+        let summary = parse_inf(SAMPLE_INF);
+        assert!(summary.is_printer_class());
+        assert_eq!(summary.driver_date.as_deref(), Some("06/21/2023"));
+        assert_eq!(summary.driver_version.as_deref(), Some("3.2.1.0"));
+        assert_eq!(
+            summary.model_names,
+            vec![
+                "HP LaserJet MFP M227-M231 PCL-6 (V4)".to_string(),
+                "HP LaserJet MFP M227 (WSD)".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn driver_ver_without_version_part() {
+        assert_eq!(parse_driver_ver("[Version]\nDriverVer=06/21/2023\n"), None);
+        assert_eq!(parse_driver_ver("[Strings]\nDriverVer=06/21/2023,1.0\n"), None);
+    }
+
+    #[test]
+    fn decodes_utf16le_with_bom() {
+        let mut bytes = vec![0xFF, 0xFE];
+        for unit in "[Version]\r\nClass=Printer\r\n".encode_utf16() {
+            bytes.extend_from_slice(&unit.to_le_bytes());
+        }
+        assert!(parse_inf(&decode_inf_bytes(&bytes)).is_printer_class());
+    }
+}
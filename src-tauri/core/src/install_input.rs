@@ -0,0 +1,242 @@
+// 安装参数校验模块（平台无关）
+// 在启动任何 PowerShell / lpadmin 进程之前校验 install_printer 的 name / path / model，
+// 返回字段级结构化错误（field + code），前端按 code 本地化提示，message 仅作默认中文文案
+//
+// 命名规则：
+// - Windows：打印机名称最长 220 个 UTF-16 字符（MAX_PRINTER_NAME）；不得包含 \ , "（Spooler / PrintUI 拒绝）
+//   及 * ? [ ]（Get-Printer -Name 按通配符解释）；不得以 "." 结尾
+// - macOS：名称作为 lpadmin -D（printer-info，IPP text(127)）写入，最长 127 字节；
+//   CUPS 队列名由名称清洗生成（仅保留 [A-Za-z0-9._-]），因此名称本身不限制字符
+// - 所有平台：不得包含控制字符；路径必须能解析为 PrinterAddress
+
+use serde::{Deserialize, Serialize};
+
+use super::printer_address::PrinterAddress;
+
+/// 字段级校验错误
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InputFieldError {
+    /// 出错字段："name" | "path" | "model"
+    pub field: String,
+    /// 错误码："required" | "too_long" | "reserved_character" | "control_character" | "trailing_dot" | "invalid_format" | "unsupported_scheme"
+    pub code: String,
+    /// 默认中文提示
+    pub message: String,
+    /// too_long 时的长度上限（单位见 lengthUnit）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_length: Option<usize>,
+    /// too_long 时的长度单位："utf16" | "bytes"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub length_unit: Option<String>,
+    /// reserved_character 时命中的字符
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
+}
+
+/// 长度计量单位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LengthUnit {
+    Utf16,
+    Bytes,
+}
+
+impl LengthUnit {
+    fn measure(self, value: &str) -> usize {
+        match self {
+            LengthUnit::Utf16 => value.encode_utf16().count(),
+            LengthUnit::Bytes => value.len(),
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            LengthUnit::Utf16 => "utf16",
+            LengthUnit::Bytes => "bytes",
+        }
+    }
+}
+
+/// 平台命名规则
+struct NamingRules {
+    max_name_length: usize,
+    max_model_length: usize,
+    length_unit: LengthUnit,
+    reserved_name_chars: &'static [char],
+    reject_trailing_dot: bool,
+    /// 是否支持设备 URI（ipp:// 等）
+    allow_device_uri: bool,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+const WINDOWS_RULES: NamingRules = NamingRules {
+    max_name_length: 220,
+    max_model_length: 220,
+    length_unit: LengthUnit::Utf16,
+    reserved_name_chars: &['\\', ',', '"', '*', '?', '[', ']'],
+    reject_trailing_dot: true,
+    allow_device_uri: false,
+};
+
+#[cfg_attr(not(target_os = "macos"), allow(dead_code))]
+const MACOS_RULES: NamingRules = NamingRules {
+    max_name_length: 127,
+    max_model_length: 127,
+    length_unit: LengthUnit::Bytes,
+    reserved_name_chars: &[],
+    reject_trailing_dot: false,
+    allow_device_uri: true,
+};
+
+fn field_error(field: &str, code: &str, message: String) -> InputFieldError {
+    InputFieldError {
+        field: field.to_string(),
+        code: code.to_string(),
+        message,
+        max_length: None,
+        length_unit: None,
+        character: None,
+    }
+}
+
+fn field_label(field: &str) -> &'static str {
+    match field {
+        "name" => "打印机名称",
+        "path" => "打印机路径",
+        _ => "打印机型号",
+    }
+}
+
+/// 校验长度与控制字符（name / model 共用）
+fn check_text(field: &str, value: &str, max_length: usize, unit: LengthUnit, errors: &mut Vec<InputFieldError>) {
+    if value.chars().any(|c| c.is_control()) {
+        errors.push(field_error(
+            field,
+            "control_character",
+            format!("{}不能包含换行、制表符等控制字符", field_label(field)),
+        ));
+    }
+    if unit.measure(value) > max_length {
+        let mut error = field_error(
+            field,
+            "too_long",
+            format!(
+                "{}过长（{} {}，最多 {}）",
+                field_label(field),
+                unit.measure(value),
+                if unit == LengthUnit::Utf16 { "个字符" } else { "字节" },
+                max_length
+            ),
+        );
+        error.max_length = Some(max_length);
+        error.length_unit = Some(unit.as_str().to_string());
+        errors.push(error);
+    }
+}
+
+fn validate_with(rules: &NamingRules, name: &str, path: &str, model: Option<&str>) -> Vec<InputFieldError> {
+    let mut errors = Vec::new();
+
+    if name.trim().is_empty() {
+        errors.push(field_error("name", "required", "打印机名称不能为空".to_string()));
+    } else {
+        check_text("name", name, rules.max_name_length, rules.length_unit, &mut errors);
+        if let Some(c) = name.chars().find(|c| rules.reserved_name_chars.contains(c)) {
+            let mut error = field_error(
+                "name",
+                "reserved_character",
+                format!("打印机名称不能包含字符 \"{}\"", c),
+            );
+            error.character = Some(c.to_string());
+            errors.push(error);
+        }
+        if rules.reject_trailing_dot && name.trim_end().ends_with('.') {
+            errors.push(field_error("name", "trailing_dot", "打印机名称不能以 \".\" 结尾".to_string()));
+        }
+    }
+
+    if path.trim().is_empty() {
+        errors.push(field_error("path", "required", "打印机路径不能为空".to_string()));
+    } else if path.chars().any(|c| c.is_control()) {
+        errors.push(field_error("path", "control_character", "打印机路径不能包含换行、制表符等控制字符".to_string()));
+    } else {
+        match PrinterAddress::parse(path) {
+            Ok(PrinterAddress::IppUrl(url)) if !rules.allow_device_uri => errors.push(field_error(
+                "path",
+                "unsupported_scheme",
+                format!("当前平台不支持 {}:// 设备 URI，请使用 IP 地址、主机名或 \\\\server\\queue", url.scheme()),
+            )),
+            Ok(_) => {}
+            Err(e) => errors.push(field_error("path", "invalid_format", format!("打印机路径无效: {}", e))),
+        }
+    }
+
+    if let Some(model) = model.filter(|m| !m.trim().is_empty()) {
+        check_text("model", model, rules.max_model_length, rules.length_unit, &mut errors);
+    }
+
+    errors
+}
+
+/// 按当前平台规则校验安装参数（不启动任何外部进程）
+///
+/// # 参数
+/// - `name`: 打印机名称
+/// - `path`: 打印机路径
+/// - `model`: 打印机型号（可选）
+///
+/// # 返回
+/// - `Ok(())`: 全部通过
+/// - `Err(Vec<InputFieldError>)`: 每个字段的全部错误
+pub fn validate_install_input(name: &str, path: &str, model: Option<&str>) -> Result<(), Vec<InputFieldError>> {
+    #[cfg(target_os = "macos")]
+    let rules = &MACOS_RULES;
+    #[cfg(not(target_os = "macos"))]
+    let rules = &WINDOWS_RULES;
+
+    let errors = validate_with(rules, name, path, model);
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codes(errors: &[InputFieldError]) -> Vec<(&str, &str)> {
+        errors.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect()
+    }
+
+    #[test]
+    fn windows_rules() {
+        assert!(validate_with(&WINDOWS_RULES, "大厦A座一楼 前台", "\\\\192.168.20.11", Some("HP M227")).is_empty());
+
+        let errors = validate_with(&WINDOWS_RULES, "前台,2F.", "ipp://10.0.0.5/ipp/print", None);
+        assert_eq!(
+            codes(&errors),
+            vec![("name", "reserved_character"), ("name", "trailing_dot"), ("path", "unsupported_scheme")]
+        );
+        assert_eq!(errors[0].character.as_deref(), Some(","));
+
+        let long_name = "打".repeat(221);
+        let errors = validate_with(&WINDOWS_RULES, &long_name, "  ", Some("a\nb"));
+        assert_eq!(
+            codes(&errors),
+            vec![("name", "too_long"), ("path", "required"), ("model", "control_character")]
+        );
+        assert_eq!(errors[0].max_length, Some(220));
+    }
+
+    #[test]
+    fn macos_rules() {
+        assert!(validate_with(&MACOS_RULES, "前台.", "ipp://10.0.0.5/ipp/print", None).is_empty());
+
+        // 127 字节上限：43 个汉字 = 129 字节
+        let errors = validate_with(&MACOS_RULES, &"打".repeat(43), "not a host!", None);
+        assert_eq!(codes(&errors), vec![("name", "too_long"), ("path", "invalid_format")]);
+        assert_eq!(errors[0].length_unit.as_deref(), Some("bytes"));
+    }
+}
@@ -0,0 +1,125 @@
+// IPP 探测报文模块
+//
+// 构造最小的 Get-Printer-Attributes 请求（IPP/2.0，只请求型号与支持的文档格式），并从应答中取出
+// status-code 与 printer-make-and-model：
+// - 应答 status-code 为 successful-ok 系列（0x0000–0x00FF）即认为设备支持 IPP
+// - 只解析一层 attribute，不支持 collection（探测用不到）
+
+/// IPP 标准端口
+pub const IPP_PORT: u16 = 631;
+
+/// Get-Printer-Attributes 操作码
+const OP_GET_PRINTER_ATTRIBUTES: u16 = 0x000B;
+
+/// 探测结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IppProbeReply {
+    pub status_code: u16,
+    pub make_and_model: Option<String>,
+}
+
+impl IppProbeReply {
+    /// status-code 是否为 successful-ok 系列
+    pub fn is_success(&self) -> bool {
+        self.status_code <= 0x00FF
+    }
+}
+
+/// 设备的 IPP 打印地址（http://host:631/ipp/print，Add-Printer -IppURL 使用）
+///
+/// # 参数
+/// - `uri_host`: URI 主机部分（IPv6 带方括号）
+pub fn printer_url(uri_host: &str) -> String {
+    format!("http://{}:{}/ipp/print", uri_host, IPP_PORT)
+}
+
+fn push_attribute(body: &mut Vec<u8>, tag: u8, name: &str, value: &str) {
+    body.push(tag);
+    body.extend_from_slice(&(name.len() as u16).to_be_bytes());
+    body.extend_from_slice(name.as_bytes());
+    body.extend_from_slice(&(value.len() as u16).to_be_bytes());
+    body.extend_from_slice(value.as_bytes());
+}
+
+/// 构造 Get-Printer-Attributes 请求
+///
+/// # 参数
+/// - `printer_uri`: 请求中的 printer-uri（ipp:// 或 http:// 地址）
+pub fn build_get_printer_attributes(printer_uri: &str) -> Vec<u8> {
+    let mut body = vec![0x02, 0x00];
+    body.extend_from_slice(&OP_GET_PRINTER_ATTRIBUTES.to_be_bytes());
+    body.extend_from_slice(&1u32.to_be_bytes());
+    body.push(0x01); // operation-attributes-tag
+    push_attribute(&mut body, 0x47, "attributes-charset", "utf-8");
+    push_attribute(&mut body, 0x48, "attributes-natural-language", "en");
+    push_attribute(&mut body, 0x45, "printer-uri", printer_uri);
+    push_attribute(&mut body, 0x44, "requested-attributes", "printer-make-and-model");
+    push_attribute(&mut body, 0x44, "", "document-format-supported");
+    body.push(0x03); // end-of-attributes-tag
+    body
+}
+
+/// 解析 Get-Printer-Attributes 应答
+///
+/// # 返回
+/// - `Ok(IppProbeReply)`: 应答的 status-code 与型号
+/// - `Err(String)`: 不是 IPP 应答（长度不足或结构损坏）
+pub fn parse_probe_reply(bytes: &[u8]) -> Result<IppProbeReply, String> {
+    if bytes.len() < 8 {
+        return Err(format!("IPP 应答过短（{} 字节）", bytes.len()));
+    }
+    let status_code = u16::from_be_bytes([bytes[2], bytes[3]]);
+    let mut make_and_model = None;
+    let mut pos = 8;
+    while pos < bytes.len() {
+        let tag = bytes[pos];
+        pos += 1;
+        if tag == 0x03 {
+            break;
+        }
+        if tag < 0x10 {
+            continue; // 分组标签
+        }
+        let read_len = |at: usize| -> Result<usize, String> {
+            bytes
+                .get(at..at + 2)
+                .map(|b| u16::from_be_bytes([b[0], b[1]]) as usize)
+                .ok_or_else(|| "IPP 应答结构损坏".to_string())
+        };
+        let name_len = read_len(pos)?;
+        let name = bytes.get(pos + 2..pos + 2 + name_len).ok_or("IPP 应答结构损坏")?;
+        pos += 2 + name_len;
+        let value_len = read_len(pos)?;
+        let value = bytes.get(pos + 2..pos + 2 + value_len).ok_or("IPP 应答结构损坏")?;
+        pos += 2 + value_len;
+        if name == b"printer-make-and-model" && make_and_model.is_none() {
+            make_and_model = Some(String::from_utf8_lossy(value).trim().to_string()).filter(|s| !s.is_empty());
+        }
+    }
+    Ok(IppProbeReply { status_code, make_and_model })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_request_and_parses_reply() {
+        let request = build_get_printer_attributes(&printer_url("192.168.20.5"));
+        assert_eq!(&request[..4], &[0x02, 0x00, 0x00, 0x0B]);
+        assert_eq!(request.last(), Some(&0x03));
+
+        let mut reply = vec![0x02, 0x00, 0x00, 0x00, 0, 0, 0, 1, 0x01];
+        push_attribute(&mut reply, 0x47, "attributes-charset", "utf-8");
+        reply.push(0x04);
+        push_attribute(&mut reply, 0x41, "printer-make-and-model", "HP LaserJet M227 ");
+        reply.push(0x03);
+        let parsed = parse_probe_reply(&reply).unwrap();
+        assert!(parsed.is_success());
+        assert_eq!(parsed.make_and_model.as_deref(), Some("HP LaserJet M227"));
+
+        assert!(!parse_probe_reply(&[0x02, 0x00, 0x04, 0x06, 0, 0, 0, 1, 0x03]).unwrap().is_success());
+        assert!(parse_probe_reply(b"<html>").is_err());
+        assert!(parse_probe_reply(&[0x02, 0x00, 0x00, 0x00, 0, 0, 0, 1, 0x04, 0x41, 0x00, 0x30]).is_err());
+    }
+}
@@ -0,0 +1,37 @@
+// ePrinty 核心库（不依赖 Tauri，可供配置校验工具等其他程序复用）
+//
+// - config：printer_config.json 数据模型与取值常量
+// - printer_address：打印机路径解析（IPv4 / IPv6 / 主机名 / 共享打印机 / 设备 URI）
+// - install_input：安装参数校验（字段级结构化错误）
+// - encoding：文本解码（UTF-16 / UTF-8 BOM / GBK）与带 BOM 的写入
+// - error_code：[EASYPRINTER_CODE=...] 稳定错误码标记的提取
+// - ps_script：PowerShell 脚本构造（参数经 param() 块传入，不拼接进脚本文本）
+// - spooler_errors：Add-Printer 等打印错误码（HRESULT / Win32）映射为结构化错误码与处理建议
+// - lpadmin_errors：macOS lpadmin 错误文本映射为结构化错误码与处理建议
+// - inf_parse：INF 驱动名称 / 版本解析
+// - printer_acl：打印队列权限（SDDL）构造与解析
+// - ipp_probe：IPP Get-Printer-Attributes 探测报文构造与应答解析
+// - snmp：SNMPv2c GetNext 报文构造与应答解析、Printer-MIB 耗材余量汇总
+// - zip_pack / zip_extract：确定性驱动包打包与防 Zip Slip 解压
+// - zip_patch：驱动包增量补丁（按文件粒度）制作与应用
+// - command_trace：安装任务外部命令记录（脱敏、写入 / 提取 job 日志、重放清单）
+//
+// 应用（src-tauri）以原路径重新导出这些模块：crate::PrinterConfig、crate::utils::inf_parse、crate::utils::encoding、
+// crate::platform::printer_address 等
+
+pub mod command_trace;
+pub mod config;
+pub mod encoding;
+pub mod error_code;
+pub mod inf_parse;
+pub mod install_input;
+pub mod ipp_probe;
+pub mod lpadmin_errors;
+pub mod printer_acl;
+pub mod printer_address;
+pub mod ps_script;
+pub mod snmp;
+pub mod spooler_errors;
+pub mod zip_extract;
+pub mod zip_pack;
+pub mod zip_patch;
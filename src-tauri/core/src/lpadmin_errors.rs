@@ -0,0 +1,165 @@
+// macOS lpadmin 错误映射模块
+//
+// lpadmin 失败时只输出一行英文（或本地化）错误文本，退出码恒为 1，本模块按错误文本映射为稳定的错误码与处理建议：
+// - CUPS 调度程序（cupsd）未运行 / 无法连接
+// - 权限不足（不在 lpadmin 组、未授权）
+// - 队列名包含不允许的字符或过长
+// - 指定的 PPD / 驱动模型不存在
+// - 未收录的错误返回 None，调用方保留原始错误信息
+//
+// 匹配不区分大小写；同时收录 macOS 简体中文系统下的常见本地化文本
+
+use serde::Serialize;
+
+/// 已知的 lpadmin 错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LpadminErrorInfo {
+    /// 稳定错误码（写入 [EASYPRINTER_CODE=...]）
+    pub code: &'static str,
+    pub message_zh: &'static str,
+    pub message_en: &'static str,
+    pub remediation_zh: &'static str,
+    pub remediation_en: &'static str,
+    /// 换用其他驱动（-m）重试也不会成功（调用方应立即停止尝试）
+    pub fatal: bool,
+}
+
+/// CUPS 调度程序不可用
+pub const MAC_CUPS_UNAVAILABLE: &str = "MAC_CUPS_UNAVAILABLE";
+
+/// 启动 cupsd 的命令（处理建议中给出）
+pub const CUPSD_KICKSTART_COMMAND: &str = "sudo launchctl kickstart -k system/org.cups.cupsd";
+
+/// 已收录的错误（按匹配优先级排序）与对应的错误文本片段（小写）
+const KNOWN_LPADMIN_ERRORS: [(LpadminErrorInfo, &[&str]); 4] = [
+    (
+        LpadminErrorInfo {
+            code: MAC_CUPS_UNAVAILABLE,
+            message_zh: "CUPS 打印服务（cupsd）未运行或无法连接",
+            message_en: "The CUPS scheduler (cupsd) is not running or cannot be reached",
+            remediation_zh: "请在终端执行 sudo launchctl kickstart -k system/org.cups.cupsd 启动打印服务后重试",
+            remediation_en: "Run `sudo launchctl kickstart -k system/org.cups.cupsd` in Terminal to start the print service, then try again",
+            fatal: true,
+        },
+        &[
+            "unable to connect to server",
+            "connection refused",
+            "scheduler is not running",
+            "scheduler not responding",
+            "无法连接到服务器",
+            "调度程序未运行",
+        ],
+    ),
+    (
+        LpadminErrorInfo {
+            code: "MAC_PERMISSION_DENIED",
+            message_zh: "没有管理打印机的权限",
+            message_en: "Not authorized to manage printers",
+            remediation_zh: "请使用管理员账户（lpadmin 组成员）运行 ePrinty，或在授权提示中输入管理员密码后重试",
+            remediation_en: "Run ePrinty as an administrator (a member of the lpadmin group), or enter an administrator password when prompted",
+            fatal: true,
+        },
+        &[
+            "forbidden",
+            "not authorized",
+            "unauthorized",
+            "not permitted",
+            "permission denied",
+            "privilege",
+            "authentication required",
+            "authentication failed",
+            "sudo: a password is required",
+            "sudo: a terminal is required",
+            "is not in the sudoers file",
+            "未授权",
+            "被禁止",
+            "权限不足",
+            "没有权限",
+        ],
+    ),
+    (
+        LpadminErrorInfo {
+            code: "MAC_INVALID_QUEUE_NAME",
+            message_zh: "打印队列名称无效",
+            message_en: "The printer queue name is invalid",
+            remediation_zh: "队列名只能包含字母、数字、- _ . 且不超过 127 个字符，请检查配置中的 nameTemplate 或打印机名称",
+            remediation_en: "Queue names may only contain letters, digits and - _ . (at most 127 characters); check nameTemplate or the printer name in the configuration",
+            fatal: true,
+        },
+        &[
+            "printer name can only contain printable characters",
+            "printer name is too long",
+            "bad printer name",
+            "invalid printer name",
+            "name must be",
+            "打印机名称只能包含",
+            "打印机名称太长",
+        ],
+    ),
+    (
+        LpadminErrorInfo {
+            code: "MAC_PPD_NOT_FOUND",
+            message_zh: "找不到指定的 PPD / 驱动模型",
+            message_en: "The specified PPD or driver model was not found",
+            remediation_zh: "系统中没有该驱动：请安装厂商提供的 macOS 驱动，或确认打印机支持 AirPrint（IPP Everywhere）免驱安装",
+            remediation_en: "The driver is not installed: install the vendor's macOS driver, or make sure the printer supports AirPrint (IPP Everywhere)",
+            fatal: false,
+        },
+        &[
+            "unable to copy ppd file",
+            "unable to open ppd",
+            "unable to find ppd",
+            "ppd file not found",
+            "bad ppd",
+            "unknown model",
+            "无法拷贝 ppd 文件",
+            "无法复制 ppd 文件",
+        ],
+    ),
+];
+
+/// 按 lpadmin 错误输出查找已知错误
+///
+/// # 参数
+/// - `stderr`: lpadmin 的错误输出
+///
+/// # 返回
+/// - `Some(&LpadminErrorInfo)`: 第一个匹配的已收录错误
+/// - `None`: 无法识别
+pub fn classify_lpadmin_error(stderr: &str) -> Option<&'static LpadminErrorInfo> {
+    let lower = stderr.to_lowercase();
+    KNOWN_LPADMIN_ERRORS
+        .iter()
+        .find(|(_, patterns)| patterns.iter().any(|p| lower.contains(p)))
+        .map(|(info, _)| info)
+}
+
+/// 按错误码查找已收录的错误（如安装前检查 cupsd 时直接使用 MAC_CUPS_UNAVAILABLE）
+pub fn lpadmin_error_by_code(code: &str) -> Option<&'static LpadminErrorInfo> {
+    KNOWN_LPADMIN_ERRORS.iter().map(|(info, _)| info).find(|info| info.code == code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_common_lpadmin_failures() {
+        let code = |stderr: &str| classify_lpadmin_error(stderr).map(|info| info.code);
+        assert_eq!(code("lpadmin: Unable to connect to server: Connection refused"), Some(MAC_CUPS_UNAVAILABLE));
+        assert_eq!(code("lpadmin: Forbidden"), Some("MAC_PERMISSION_DENIED"));
+        assert_eq!(code("lpadmin: Printer name can only contain printable characters."), Some("MAC_INVALID_QUEUE_NAME"));
+        assert_eq!(code("lpadmin: Unable to copy PPD file."), Some("MAC_PPD_NOT_FOUND"));
+        assert_eq!(code("lpadmin: 无法连接到服务器"), Some(MAC_CUPS_UNAVAILABLE));
+        assert_eq!(code("lpadmin: Bad device-uri scheme \"foo\"."), None);
+        assert_eq!(code("sudo: a password is required"), Some("MAC_PERMISSION_DENIED"));
+        assert_eq!(code("lpadmin: Authentication required"), Some("MAC_PERMISSION_DENIED"));
+        // 只是提到 sudo / authentication / 权限 的输出不算权限错误
+        assert_eq!(code("lpadmin: Unable to open PPD \"/tmp/sudo-helper.ppd\"."), Some("MAC_PPD_NOT_FOUND"));
+        assert_eq!(code("lpadmin: Bad device-uri \"ipp://authentication.example.com\"."), None);
+        assert_eq!(code("lpadmin: 警告：已设置打印机权限默认值"), None);
+        assert!(!lpadmin_error_by_code("MAC_PPD_NOT_FOUND").unwrap().fatal);
+        assert!(lpadmin_error_by_code(MAC_CUPS_UNAVAILABLE).unwrap().remediation_zh.contains(CUPSD_KICKSTART_COMMAND));
+    }
+}
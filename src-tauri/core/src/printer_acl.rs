@@ -0,0 +1,189 @@
+// 打印队列权限（SDDL）构造与解析模块
+//
+// 配置 permissions.allow / deny 中的账户（SID、SDDL 别名或常用组名）转换为 Set-Printer -PermissionSDDL 使用的
+// 安全描述符，并把 Get-Printer -Full 返回的 PermissionSDDL 解析为可读的 ACE 列表：
+// - 常用组名（Everyone / Administrators / Users 等，可带 BUILTIN\ / NT AUTHORITY\ 前缀）直接映射为 SDDL 别名
+// - 其他账户名需由平台层翻译为 SID（NTAccount.Translate）后再构造
+// - 生成的 SDDL 始终保留 Administrators / SYSTEM 的管理权限与 CREATOR OWNER 对自己文档的权限，
+//   拒绝项排在允许项之前（规范顺序）
+// - 只配置 deny（allow 为空）时保留系统默认的 Everyone 打印权限，只拒绝列出的账户
+
+use serde::{Deserialize, Serialize};
+
+/// 常用组名 → SDDL 别名（名称不区分大小写）
+const WELL_KNOWN_ALIASES: [(&str, &str); 10] = [
+    ("everyone", "WD"),
+    ("administrators", "BA"),
+    ("users", "BU"),
+    ("authenticated users", "AU"),
+    ("interactive", "IU"),
+    ("system", "SY"),
+    ("power users", "PU"),
+    ("print operators", "PO"),
+    ("guests", "BG"),
+    ("creator owner", "CO"),
+];
+
+/// 账户名可带的域前缀（内置组）
+const BUILTIN_PREFIXES: [&str; 2] = ["builtin\\", "nt authority\\"];
+
+/// 始终保留的管理项：Administrators / SYSTEM 管理打印机与全部文档，CREATOR OWNER 管理自己的文档
+const MANAGEMENT_ACES: &str = "(A;;LCSWDTSDRCWDWO;;;BA)(A;OICIIO;GA;;;BA)(A;;LCSWDTSDRCWDWO;;;SY)(A;OICIIO;GA;;;SY)(A;OIIO;GA;;;CO)";
+
+/// allow 为空时默认允许打印的账户（Everyone）
+const DEFAULT_PRINT_TRUSTEE: &str = "WD";
+
+/// 解析后的 ACE（只列打印机本身的项，不含仅继承给文档的项）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AclEntry {
+    /// SDDL 中的账户（别名如 "WD" 或 SID）
+    pub trustee: String,
+    /// 常用组名（别名可识别时）
+    pub name: Option<String>,
+    /// "allow" | "deny"
+    pub access: String,
+    /// "manage" | "print" | "other"
+    pub rights: String,
+}
+
+/// 常用组名对应的 SDDL 别名
+pub fn well_known_alias(name: &str) -> Option<&'static str> {
+    let lower = name.trim().to_lowercase();
+    let bare = BUILTIN_PREFIXES
+        .iter()
+        .find_map(|prefix| lower.strip_prefix(prefix))
+        .unwrap_or(&lower);
+    WELL_KNOWN_ALIASES.iter().find(|(n, _)| *n == bare).map(|(_, alias)| *alias)
+}
+
+/// 是否为 SID 字符串（S-1-5-21-...）
+pub fn is_sid(value: &str) -> bool {
+    let upper = value.trim().to_uppercase();
+    upper
+        .strip_prefix("S-1-")
+        .map(|rest| !rest.is_empty() && rest.split('-').all(|part| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit())))
+        .unwrap_or(false)
+}
+
+/// 不经平台翻译即可写入 SDDL 的账户（常用组名 / 两位 SDDL 别名 / SID），其他返回 None
+pub fn direct_trustee(value: &str) -> Option<String> {
+    let trimmed = value.trim();
+    if let Some(alias) = well_known_alias(trimmed) {
+        return Some(alias.to_string());
+    }
+    if is_sid(trimmed) {
+        return Some(trimmed.to_uppercase());
+    }
+    let upper = trimmed.to_uppercase();
+    WELL_KNOWN_ALIASES.iter().any(|(_, alias)| *alias == upper).then_some(upper)
+}
+
+/// 构造打印机安全描述符
+///
+/// # 参数
+/// - `allow`: 允许打印的账户（已转换为别名或 SID；为空时允许 Everyone 打印）
+/// - `deny`: 拒绝打印的账户（已转换为别名或 SID）
+pub fn build_printer_sddl(allow: &[String], deny: &[String]) -> String {
+    let mut sddl = String::from("G:SYD:");
+    for trustee in deny {
+        sddl.push_str(&format!("(D;;SW;;;{})", trustee));
+    }
+    sddl.push_str(MANAGEMENT_ACES);
+    let default_allow = [DEFAULT_PRINT_TRUSTEE.to_string()];
+    let allow = if allow.is_empty() { &default_allow[..] } else { allow };
+    for trustee in allow {
+        sddl.push_str(&format!("(A;;SWRC;;;{})(A;CIIO;GX;;;{})", trustee, trustee));
+    }
+    sddl
+}
+
+/// 解析 PermissionSDDL 中的 DACL
+pub fn parse_sddl_entries(sddl: &str) -> Vec<AclEntry> {
+    let Some(dacl) = sddl.split("D:").nth(1) else {
+        return Vec::new();
+    };
+    let dacl = dacl.split("S:").next().unwrap_or_default();
+    dacl.split('(')
+        .filter_map(|ace| ace.split(')').next())
+        .filter_map(|ace| {
+            let fields: Vec<&str> = ace.split(';').collect();
+            if fields.len() < 6 || fields[1].contains("IO") {
+                return None;
+            }
+            let access = match fields[0] {
+                "A" => "allow",
+                "D" => "deny",
+                _ => return None,
+            };
+            let rights = fields[2];
+            let rights = if rights.contains("GA") || rights.contains("WD") {
+                "manage"
+            } else if rights.contains("SW") {
+                "print"
+            } else {
+                "other"
+            };
+            let trustee = fields[5].to_string();
+            let name = WELL_KNOWN_ALIASES
+                .iter()
+                .find(|(_, alias)| *alias == trustee)
+                .map(|(name, _)| title_case(name));
+            Some(AclEntry { trustee, name, access: access.to_string(), rights: rights.to_string() })
+        })
+        .collect()
+}
+
+/// "authenticated users" → "Authenticated Users"
+fn title_case(name: &str) -> String {
+    name.split(' ')
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map(|c| c.to_uppercase().chain(chars).collect::<String>()).unwrap_or_default()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_and_parses_printer_sddl() {
+        assert_eq!(direct_trustee("BUILTIN\\Users").as_deref(), Some("BU"));
+        assert_eq!(direct_trustee(" everyone ").as_deref(), Some("WD"));
+        assert_eq!(direct_trustee("s-1-5-21-1004-513").as_deref(), Some("S-1-5-21-1004-513"));
+        assert_eq!(direct_trustee("CONTOSO\\Finance"), None);
+        assert!(!is_sid("S-1-"));
+
+        let sddl = build_printer_sddl(&["S-1-5-21-1004-513".to_string()], &["BG".to_string()]);
+        assert!(sddl.starts_with("G:SYD:(D;;SW;;;BG)(A;;LCSWDTSDRCWDWO;;;BA)"));
+
+        let entries = parse_sddl_entries(&sddl);
+        let summary: Vec<(&str, &str, &str)> =
+            entries.iter().map(|e| (e.trustee.as_str(), e.access.as_str(), e.rights.as_str())).collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("BG", "deny", "print"),
+                ("BA", "allow", "manage"),
+                ("SY", "allow", "manage"),
+                ("S-1-5-21-1004-513", "allow", "print"),
+            ]
+        );
+        assert_eq!(entries[0].name.as_deref(), Some("Guests"));
+    }
+
+    #[test]
+    fn deny_only_config_keeps_everyone_print_access() {
+        let sddl = build_printer_sddl(&[], &["BG".to_string()]);
+        assert!(sddl.starts_with("G:SYD:(D;;SW;;;BG)"));
+        assert!(sddl.ends_with("(A;;SWRC;;;WD)(A;CIIO;GX;;;WD)"));
+
+        let entries = parse_sddl_entries(&sddl);
+        let last = entries.last().unwrap();
+        assert_eq!((last.trustee.as_str(), last.access.as_str(), last.rights.as_str()), ("WD", "allow", "print"));
+        assert_eq!(last.name.as_deref(), Some("Everyone"));
+    }
+}
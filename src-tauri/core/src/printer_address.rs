@@ -0,0 +1,399 @@
+// 打印机地址解析模块（平台无关）
+// 将配置中的打印机路径（\\192.168.1.5、\\fd00::12、\\printer-3f.corp.local、\\server\queue、ipp://... 等）
+// 统一解析为 PrinterAddress，各平台后端只消费解析结果，不再各自裁剪反斜杠 / 拆分路径
+//
+// 支持的地址形态：
+// - IPv4：192.168.1.5、192.168.1.5:9100
+// - IPv6：fd00::12、[fd00::12]、[fd00::12]:9100、fe80::1%12、fd00--12.ipv6-literal.net（Windows UNC 写法）
+// - hostname：printer-3f.corp.local、PRN01、PRN01:9100
+// - 共享打印机：\\server\queue、smb://server/queue
+// - 设备 URI：ipp:// / ipps:// / socket:// / lpd://（也接受省略 // 的 ipp:host 写法）
+//
+// 主机类路径允许 `\\` 前缀（\\192.168.1.5 与 192.168.1.5 等价）
+
+use std::fmt;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use url::Url;
+
+/// 设备 URI 支持的协议
+const DEVICE_URI_SCHEMES: &[&str] = &["ipp", "ipps", "socket", "lpd"];
+
+/// 主机部分的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HostKind {
+    Ipv4,
+    Ipv6,
+    Hostname,
+}
+
+/// 已解析的打印机地址
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrinterAddress {
+    Ipv4 { addr: Ipv4Addr, port: Option<u16> },
+    /// zone id（fe80::1%12 中的 12）单独保存
+    Ipv6 { addr: Ipv6Addr, zone: Option<String>, port: Option<u16> },
+    Hostname { name: String, port: Option<u16> },
+    /// \\server\queue（server 保留原始写法，UNC 连接需要 ipv6-literal.net 形式）
+    SmbShare { server: String, share: String },
+    /// 设备 URI（ipp / ipps / socket / lpd）
+    IppUrl(Url),
+}
+
+impl PrinterAddress {
+    /// 解析打印机路径
+    ///
+    /// # 参数
+    /// - `raw`: 配置中的 path（主机、`\\` 前缀主机、共享路径或设备 URI）
+    ///
+    /// # 返回
+    /// - `Ok(PrinterAddress)`: 解析成功
+    /// - `Err(String)`: 无法识别的地址
+    pub fn parse(raw: &str) -> Result<Self, String> {
+        let trimmed = raw.trim();
+        if trimmed.is_empty() {
+            return Err("打印机地址为空".to_string());
+        }
+
+        if let Some(uri) = normalize_uri_input(trimmed) {
+            return parse_uri(&uri);
+        }
+
+        if trimmed.starts_with('\\') {
+            let parts: Vec<&str> = trimmed.split('\\').filter(|s| !s.is_empty()).collect();
+            return match parts.as_slice() {
+                [] => Err(format!("打印机地址为空: \"{}\"", raw)),
+                [host] => parse_host(host, None).map_err(|_| unrecognized(raw)),
+                [server, share @ ..] => {
+                    parse_host(server, None).map_err(|_| unrecognized(raw))?;
+                    Ok(Self::SmbShare { server: server.to_string(), share: share.join("\\") })
+                }
+            };
+        }
+
+        // 兼容 host/ipp/print 这类带路径的写法：只取主机段
+        let host_port = trimmed.trim_start_matches('/').split('/').next().unwrap_or_default();
+        let (host, port) = split_host_port(host_port).map_err(|e| format!("{}: \"{}\"", e, raw))?;
+        parse_host(host, port).map_err(|_| unrecognized(raw))
+    }
+
+    /// 主机部分的类型（共享路径 / URI 取其中的主机）
+    pub fn host_kind(&self) -> HostKind {
+        match self {
+            Self::Ipv4 { .. } => HostKind::Ipv4,
+            Self::Ipv6 { .. } => HostKind::Ipv6,
+            Self::Hostname { .. } => HostKind::Hostname,
+            Self::SmbShare { .. } | Self::IppUrl(_) => parse_host(&self.host_address(), None)
+                .map(|address| address.host_kind())
+                .unwrap_or(HostKind::Hostname),
+        }
+    }
+
+    /// 路径中显式指定的端口（URI 未写端口时为 None）
+    pub fn port(&self) -> Option<u16> {
+        match self {
+            Self::Ipv4 { port, .. } | Self::Ipv6 { port, .. } | Self::Hostname { port, .. } => *port,
+            Self::SmbShare { .. } => None,
+            Self::IppUrl(url) => url.port(),
+        }
+    }
+
+    /// 主机地址（用于 Add-PrinterPort -PrinterHostAddress / prnport.vbs -h / 连通性探测）
+    ///
+    /// IPv6 使用不带方括号的字面量（保留 %zone）；共享路径返回服务器名
+    pub fn host_address(&self) -> String {
+        match self {
+            Self::Ipv4 { addr, .. } => addr.to_string(),
+            Self::Ipv6 { addr, zone: Some(zone), .. } => format!("{}%{}", addr, zone),
+            Self::Ipv6 { addr, zone: None, .. } => addr.to_string(),
+            Self::Hostname { name, .. } => name.clone(),
+            Self::SmbShare { server, .. } => server.clone(),
+            Self::IppUrl(url) => {
+                let host = url.host_str().unwrap_or_default().trim_start_matches('[').trim_end_matches(']');
+                // URI 中的 zone id 以 %25 编码
+                host.replacen("%25", "%", 1)
+            }
+        }
+    }
+
+    /// 用于 URI（ipp:// / socket://）的主机部分，IPv6 带方括号
+    pub fn uri_host(&self) -> String {
+        match self.host_kind() {
+            HostKind::Ipv6 => format!("[{}]", self.host_address().replace('%', "%25")),
+            _ => self.host_address(),
+        }
+    }
+
+    /// 生成标准 TCP/IP 端口名
+    ///
+    /// # 规则
+    /// - IPv4：`IP_192_168_1_5`（与旧规则一致）
+    /// - IPv6：`IP_fd00__12`（`:` 和 `%` 替换为 `_`）
+    /// - hostname：`IP_printer-3f_corp_local`（非法字符替换为 `_`）
+    pub fn port_name(&self) -> String {
+        let sanitized = self
+            .host_address()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect::<String>();
+        format!("IP_{}", sanitized)
+    }
+
+    /// 端口名是否对应该地址（兼容旧端口名与包含地址的自定义端口名）
+    pub fn matches_port_name(&self, port_name: &str) -> bool {
+        port_name.eq_ignore_ascii_case(&self.port_name()) || port_name.contains(&self.host_address())
+    }
+
+    /// CUPS 设备 URI（lpadmin -v）
+    ///
+    /// 主机类地址默认生成 IPP Everywhere 地址（ipp://host/ipp/print，非 631 端口时带端口）
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))] // 目前仅 macOS 构造设备 URI
+    pub fn device_uri(&self) -> String {
+        match self {
+            Self::IppUrl(url) => url.to_string(),
+            Self::SmbShare { server, share } => format!("smb://{}/{}", server, share.replace('\\', "/")),
+            _ => match self.port() {
+                None | Some(631) => format!("ipp://{}/ipp/print", self.uri_host()),
+                Some(port) => format!("ipp://{}:{}/ipp/print", self.uri_host(), port),
+            },
+        }
+    }
+
+    /// 连通性探测使用的端口（未显式指定时按协议取默认端口）
+    #[cfg_attr(not(target_os = "macos"), allow(dead_code))] // 目前仅 macOS 安装前探测
+    pub fn device_port(&self) -> u16 {
+        if let Some(port) = self.port() {
+            return port;
+        }
+        match self {
+            Self::SmbShare { .. } => 445,
+            Self::IppUrl(url) => match url.scheme() {
+                "socket" => 9100,
+                "lpd" => 515,
+                _ => 631,
+            },
+            _ => 631,
+        }
+    }
+}
+
+impl fmt::Display for PrinterAddress {
+    /// 规范化写法：主机类地址不带 `\\` 前缀，共享路径为 \\server\queue
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::SmbShare { server, share } => write!(f, "\\\\{}\\{}", server, share),
+            Self::IppUrl(url) => write!(f, "{}", url),
+            _ => match (self.host_kind(), self.port()) {
+                (_, None) => write!(f, "{}", self.host_address()),
+                (HostKind::Ipv6, Some(port)) => write!(f, "[{}]:{}", self.host_address(), port),
+                (_, Some(port)) => write!(f, "{}:{}", self.host_address(), port),
+            },
+        }
+    }
+}
+
+/// 去掉路径开头的反斜杠（原样保留其余部分）
+///
+/// 仅用于 stable_id 等需要与旧版本逐字节一致的场景，解析地址请使用 PrinterAddress::parse
+pub fn strip_unc_prefix(raw: &str) -> &str {
+    raw.trim_start_matches('\\')
+}
+
+fn unrecognized(raw: &str) -> String {
+    format!(
+        "无法识别的打印机地址: \"{}\"（支持 IPv4、IPv6、hostname、\\\\server\\queue 或 ipp:// 设备 URI）",
+        raw
+    )
+}
+
+/// 识别 URI 写法并补全 `//`（ipp:host → ipp://host）；不是 URI 时返回 None
+fn normalize_uri_input(trimmed: &str) -> Option<String> {
+    if trimmed.contains("://") {
+        return Some(trimmed.to_string());
+    }
+    let (scheme, rest) = trimmed.split_once(':')?;
+    if DEVICE_URI_SCHEMES.iter().any(|s| s.eq_ignore_ascii_case(scheme)) {
+        return Some(format!("{}://{}", scheme, rest));
+    }
+    None
+}
+
+fn parse_uri(uri: &str) -> Result<PrinterAddress, String> {
+    let url = Url::parse(uri).map_err(|e| format!("无法解析设备 URI \"{}\": {}", uri, e))?;
+    let host = url.host_str().filter(|h| !h.is_empty()).ok_or_else(|| format!("设备 URI 缺少主机: \"{}\"", uri))?;
+
+    if url.scheme() == "smb" {
+        let share = url.path().trim_matches('/').replace('/', "\\");
+        if share.is_empty() {
+            return Err(format!("共享打印机 URI 缺少队列名: \"{}\"", uri));
+        }
+        return Ok(PrinterAddress::SmbShare { server: host.to_string(), share });
+    }
+    if !DEVICE_URI_SCHEMES.contains(&url.scheme()) {
+        return Err(format!("不支持的设备 URI 协议 \"{}\"（支持 ipp / ipps / socket / lpd / smb）", url.scheme()));
+    }
+    Ok(PrinterAddress::IppUrl(url))
+}
+
+/// 拆分 host[:port]（IPv6 带端口时必须使用 [addr]:port）
+fn split_host_port(raw: &str) -> Result<(&str, Option<u16>), String> {
+    let parse_port = |s: &str| s.parse::<u16>().map(Some).map_err(|_| "端口号无效".to_string());
+
+    if let Some(rest) = raw.strip_prefix('[') {
+        let (host, after) = rest.split_once(']').ok_or_else(|| "IPv6 地址缺少 ']'".to_string())?;
+        return match after.strip_prefix(':') {
+            Some(port) => Ok((host, parse_port(port)?)),
+            None if after.is_empty() => Ok((host, None)),
+            None => Err("IPv6 地址格式无效".to_string()),
+        };
+    }
+    // 裸 IPv6 含多个冒号，整体视为主机
+    if raw.matches(':').count() > 1 {
+        return Ok((raw, None));
+    }
+    match raw.rsplit_once(':') {
+        Some((host, port)) => Ok((host, parse_port(port)?)),
+        None => Ok((raw, None)),
+    }
+}
+
+/// 解析主机：IPv4 / IPv6 / hostname
+fn parse_host(host: &str, port: Option<u16>) -> Result<PrinterAddress, String> {
+    let host = host.trim();
+    if let Some((addr, zone)) = parse_ipv6(host) {
+        return Ok(PrinterAddress::Ipv6 { addr, zone, port });
+    }
+    if let Ok(addr) = host.parse::<Ipv4Addr>() {
+        return Ok(PrinterAddress::Ipv4 { addr, port });
+    }
+    if is_valid_hostname(host) {
+        return Ok(PrinterAddress::Hostname { name: host.to_string(), port });
+    }
+    Err(unrecognized(host))
+}
+
+/// 解析 IPv6 字面量（保留 zone id）
+fn parse_ipv6(host: &str) -> Option<(Ipv6Addr, Option<String>)> {
+    let lower = host.to_ascii_lowercase();
+
+    // Windows UNC 写法：fd00--12.ipv6-literal.net（`-` 代替 `:`，`s` 代替 `%`）
+    let candidate = if let Some(prefix) = lower.strip_suffix(".ipv6-literal.net") {
+        prefix.replace('-', ":").replacen('s', "%", 1)
+    } else {
+        lower.trim_start_matches('[').trim_end_matches(']').to_string()
+    };
+
+    if !candidate.contains(':') {
+        return None;
+    }
+
+    let (addr, zone) = match candidate.split_once('%') {
+        Some((addr, zone)) => (addr, Some(zone)),
+        None => (candidate.as_str(), None),
+    };
+    let parsed: Ipv6Addr = addr.parse().ok()?;
+
+    match zone {
+        Some(zone) if !zone.is_empty() && zone.chars().all(|c| c.is_ascii_alphanumeric()) => {
+            Some((parsed, Some(zone.to_string())))
+        }
+        Some(_) => None,
+        None => Some((parsed, None)),
+    }
+}
+
+/// 校验 hostname（RFC 1123 标签规则，额外允许 `_` 以兼容 NetBIOS 名称）
+fn is_valid_hostname(host: &str) -> bool {
+    if host.is_empty() || host.len() > 253 {
+        return false;
+    }
+    // 全数字点分但不是合法 IPv4（如 192.168.1.300）视为无效而非 hostname
+    if host.chars().all(|c| c.is_ascii_digit() || c == '.') {
+        return false;
+    }
+    host.split('.').all(|label| {
+        !label.is_empty()
+            && label.len() <= 63
+            && !label.starts_with('-')
+            && !label.ends_with('-')
+            && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_ipv4_paths() {
+        let addr = PrinterAddress::parse("\\\\192.168.20.5").unwrap();
+        assert_eq!(addr.host_kind(), HostKind::Ipv4);
+        assert_eq!(addr.host_address(), "192.168.20.5");
+        assert_eq!(addr.port_name(), "IP_192_168_20_5");
+        assert_eq!(addr.uri_host(), "192.168.20.5");
+        assert!(PrinterAddress::parse("192.168.1.300").is_err());
+
+        let with_port = PrinterAddress::parse("192.168.20.5:9100").unwrap();
+        assert_eq!(with_port.port(), Some(9100));
+        assert_eq!(with_port.to_string(), "192.168.20.5:9100");
+        assert_eq!(with_port.device_uri(), "ipp://192.168.20.5:9100/ipp/print");
+    }
+
+    #[test]
+    fn parses_ipv6_paths() {
+        for raw in ["\\\\fd00::12", "fd00::12", "[fd00::12]", "\\\\fd00--12.ipv6-literal.net"] {
+            let addr = PrinterAddress::parse(raw).unwrap();
+            assert_eq!(addr.host_kind(), HostKind::Ipv6, "raw={}", raw);
+            assert_eq!(addr.host_address(), "fd00::12");
+            assert_eq!(addr.port_name(), "IP_fd00__12");
+            assert_eq!(addr.uri_host(), "[fd00::12]");
+        }
+
+        let scoped = PrinterAddress::parse("fe80::1%12").unwrap();
+        assert_eq!(scoped.host_address(), "fe80::1%12");
+        assert_eq!(scoped.port_name(), "IP_fe80__1_12");
+        assert_eq!(scoped.uri_host(), "[fe80::1%2512]");
+
+        let with_port = PrinterAddress::parse("[fd00::12]:9100").unwrap();
+        assert_eq!(with_port.port(), Some(9100));
+        assert_eq!(with_port.to_string(), "[fd00::12]:9100");
+    }
+
+    #[test]
+    fn parses_hostname_paths() {
+        let addr = PrinterAddress::parse("\\\\printer-3f.corp.local").unwrap();
+        assert_eq!(addr.host_kind(), HostKind::Hostname);
+        assert_eq!(addr.host_address(), "printer-3f.corp.local");
+        assert_eq!(addr.port_name(), "IP_printer-3f_corp_local");
+        assert!(addr.matches_port_name("IP_printer-3f_corp_local"));
+
+        assert_eq!(PrinterAddress::parse("PRN01").unwrap().host_kind(), HostKind::Hostname);
+        assert!(PrinterAddress::parse("bad host").is_err());
+        assert!(PrinterAddress::parse("\\\\").is_err());
+    }
+
+    #[test]
+    fn parses_shares_and_uris() {
+        let share = PrinterAddress::parse("\\\\PRINTSRV01\\3F-Color").unwrap();
+        assert_eq!(
+            share,
+            PrinterAddress::SmbShare { server: "PRINTSRV01".to_string(), share: "3F-Color".to_string() }
+        );
+        assert_eq!(share.to_string(), "\\\\PRINTSRV01\\3F-Color");
+        assert_eq!(share.host_address(), "PRINTSRV01");
+        assert_eq!(share.device_uri(), "smb://PRINTSRV01/3F-Color");
+        assert!(PrinterAddress::parse("\\\\bad host\\queue").is_err());
+
+        let ipp = PrinterAddress::parse("ipp:192.168.1.5/ipp/print").unwrap();
+        assert!(matches!(ipp, PrinterAddress::IppUrl(_)));
+        assert_eq!(ipp.device_uri(), "ipp://192.168.1.5/ipp/print");
+        assert_eq!(ipp.device_port(), 631);
+
+        let socket = PrinterAddress::parse("socket://[fd00::12]").unwrap();
+        assert_eq!(socket.host_address(), "fd00::12");
+        assert_eq!(socket.host_kind(), HostKind::Ipv6);
+        assert_eq!(socket.device_port(), 9100);
+
+        assert!(PrinterAddress::parse("http://192.168.1.5").is_err());
+        assert_eq!(strip_unc_prefix("\\\\192.168.1.5"), "192.168.1.5");
+    }
+}
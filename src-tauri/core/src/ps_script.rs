@@ -0,0 +1,112 @@
+// PowerShell 脚本构造模块（平台无关，只生成脚本文本）
+//
+// 脚本正文与参数分开传入：正文中以 $Name 引用参数，参数值不拼入脚本文本，
+// 而是 Base64（UTF-8）编码后作为 param() 块的实参传入：
+//
+//   & {
+//   param([string]$Name, [string]$Port)
+//   Add-Printer -Name $Name -PortName $Port
+//   } -Name ([System.Text.Encoding]::UTF8.GetString([System.Convert]::FromBase64String('...'))) -Port (...)
+//
+// Base64 字母表不含引号 / 反引号 / $ / 换行，打印机名称等用户输入无论包含什么字符都无法破坏脚本语法，
+// 调用方也不再需要手写 '' 转义。生成结果可直接交给 run_powershell（-Command）执行
+
+use std::fmt::Write;
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// 带参数的 PowerShell 脚本
+#[derive(Debug, Clone)]
+pub struct PsScript {
+    body: String,
+    params: Vec<(&'static str, String)>,
+}
+
+impl PsScript {
+    /// 创建脚本
+    ///
+    /// # 参数
+    /// - `body`: 脚本正文（以 $参数名 引用参数，不要再把用户输入拼进正文）
+    pub fn new(body: impl Into<String>) -> Self {
+        PsScript { body: body.into(), params: Vec::new() }
+    }
+
+    /// 添加字符串参数
+    ///
+    /// # 参数
+    /// - `name`: 参数名（字母开头，仅字母 / 数字 / 下划线；正文中以 $name 引用）
+    /// - `value`: 参数值（任意文本）
+    pub fn param(mut self, name: &'static str, value: impl AsRef<str>) -> Self {
+        debug_assert!(is_valid_param_name(name), "无效的 PowerShell 参数名: {}", name);
+        self.params.push((name, value.as_ref().to_string()));
+        self
+    }
+
+    /// 生成可直接执行的脚本文本
+    pub fn build(&self) -> String {
+        if self.params.is_empty() {
+            return self.body.clone();
+        }
+        let declarations = self
+            .params
+            .iter()
+            .map(|(name, _)| format!("[string]${}", name))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let mut script = format!("& {{\nparam({})\n{}\n}}", declarations, self.body);
+        for (name, value) in &self.params {
+            let _ = write!(
+                script,
+                " -{} ([System.Text.Encoding]::UTF8.GetString([System.Convert]::FromBase64String('{}')))",
+                name,
+                base64_encode(value.as_bytes())
+            );
+        }
+        script
+    }
+}
+
+fn is_valid_param_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// 标准 Base64 编码（带 = 填充）
+fn base64_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(BASE64_ALPHABET[((n >> (18 - 6 * i)) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn passes_params_without_splicing_values_into_script() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode("品牌".as_bytes()), "5ZOB54mM");
+
+        let hostile = "3F'; Remove-Item C:\\ -Recurse; '$(x)`\"";
+        let script = PsScript::new("Get-Printer -Name $Name").param("Name", hostile).build();
+        assert!(script.starts_with("& {\nparam([string]$Name)\nGet-Printer -Name $Name\n}"));
+        assert!(!script.contains(hostile));
+        assert!(!script.contains("Remove-Item"));
+        assert!(script.ends_with(&format!("FromBase64String('{}')))", base64_encode(hostile.as_bytes()))));
+
+        assert_eq!(PsScript::new("Get-Printer").build(), "Get-Printer");
+    }
+}
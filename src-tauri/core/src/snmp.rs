@@ -0,0 +1,414 @@
+// SNMP 报文模块（打印机耗材查询）
+//
+// 构造 SNMPv2c GetNextRequest 并解析 Response，按 Printer-MIB（RFC 3805）的 prtMarkerSuppliesTable
+// 汇总墨粉 / 墨水 / 硒鼓等耗材余量：
+// - 只实现查询耗材用到的 BER 子集（INTEGER / OCTET STRING / NULL / OID / 计数器类无符号整数）
+// - prtMarkerSuppliesLevel 的负值：-1 其他、-2 未知、-3 尚有余量但无法计量
+// - 废粉盒等"被填满"的耗材（prtMarkerSuppliesClass = 4）余量表示已填充比例，不参与"余量不足"判断
+
+use serde::Serialize;
+
+/// SNMP 标准端口
+pub const SNMP_PORT: u16 = 161;
+
+/// 余量不足的百分比阈值（含）
+pub const LOW_SUPPLY_PERCENT: u8 = 10;
+
+/// prtMarkerSuppliesEntry（1.3.6.1.2.1.43.11.1.1）
+const SUPPLIES_ENTRY: &[u32] = &[1, 3, 6, 1, 2, 1, 43, 11, 1, 1];
+
+/// prtMarkerSuppliesEntry 的列号
+const COL_CLASS: u32 = 4;
+const COL_TYPE: u32 = 5;
+const COL_DESCRIPTION: u32 = 6;
+const COL_MAX_CAPACITY: u32 = 8;
+const COL_LEVEL: u32 = 9;
+
+/// 查询耗材需要遍历的列
+pub const SUPPLY_COLUMNS: &[u32] = &[COL_CLASS, COL_TYPE, COL_DESCRIPTION, COL_MAX_CAPACITY, COL_LEVEL];
+
+/// prtMarkerSuppliesClass: receptacleThatIsFilled
+const CLASS_RECEPTACLE: i64 = 4;
+
+const TAG_INTEGER: u8 = 0x02;
+const TAG_OCTET_STRING: u8 = 0x04;
+const TAG_NULL: u8 = 0x05;
+const TAG_OID: u8 = 0x06;
+const TAG_SEQUENCE: u8 = 0x30;
+const TAG_GET_NEXT_REQUEST: u8 = 0xA1;
+const TAG_RESPONSE: u8 = 0xA2;
+
+/// 变量值
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnmpValue {
+    Integer(i64),
+    OctetString(Vec<u8>),
+    ObjectId(Vec<u32>),
+    Null,
+    /// noSuchObject / noSuchInstance / endOfMibView
+    EndOfMib,
+    /// 其他类型（保留标签）
+    Other(u8),
+}
+
+/// Response 报文
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnmpResponse {
+    pub request_id: i32,
+    pub error_status: i64,
+    pub varbinds: Vec<(Vec<u32>, SnmpValue)>,
+}
+
+/// 单个耗材
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterSupply {
+    /// 表索引（hrDeviceIndex.prtMarkerSuppliesIndex，如 "1.1"）
+    pub index: String,
+    /// 设备上报的名称（如 "Black Toner Cartridge"）
+    pub description: String,
+    /// 耗材类型：toner / ink / drum / waste / other
+    pub kind: String,
+    /// 原始余量（负值含义见模块说明）
+    pub level: i64,
+    pub max_capacity: i64,
+    /// 余量百分比（无法计量时为 None）
+    pub percent: Option<u8>,
+    /// 余量不足（百分比不超过 LOW_SUPPLY_PERCENT）
+    pub low: bool,
+}
+
+/// 列 OID（prtMarkerSuppliesEntry.column）
+pub fn column_oid(column: u32) -> Vec<u32> {
+    let mut oid = SUPPLIES_ENTRY.to_vec();
+    oid.push(column);
+    oid
+}
+
+fn push_length(out: &mut Vec<u8>, len: usize) {
+    if len < 0x80 {
+        out.push(len as u8);
+    } else {
+        let bytes: Vec<u8> = len.to_be_bytes().into_iter().skip_while(|b| *b == 0).collect();
+        out.push(0x80 | bytes.len() as u8);
+        out.extend_from_slice(&bytes);
+    }
+}
+
+fn push_tlv(out: &mut Vec<u8>, tag: u8, content: &[u8]) {
+    out.push(tag);
+    push_length(out, content.len());
+    out.extend_from_slice(content);
+}
+
+fn encode_integer(value: i64) -> Vec<u8> {
+    let bytes = value.to_be_bytes();
+    // 去掉不影响符号的前导 0x00 / 0xFF
+    let mut start = 0;
+    while start < bytes.len() - 1
+        && ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0) || (bytes[start] == 0xFF && bytes[start + 1] & 0x80 != 0))
+    {
+        start += 1;
+    }
+    bytes[start..].to_vec()
+}
+
+fn encode_oid(oid: &[u32]) -> Vec<u8> {
+    let mut out = Vec::new();
+    if oid.len() >= 2 {
+        out.push((oid[0] * 40 + oid[1]) as u8);
+    }
+    for &arc in oid.iter().skip(2) {
+        let mut chunk = vec![(arc & 0x7F) as u8];
+        let mut rest = arc >> 7;
+        while rest > 0 {
+            chunk.push(0x80 | (rest & 0x7F) as u8);
+            rest >>= 7;
+        }
+        out.extend(chunk.into_iter().rev());
+    }
+    out
+}
+
+/// 构造 SNMPv2c GetNextRequest
+///
+/// # 参数
+/// - `community`: 团体名
+/// - `request_id`: 请求 ID（应答原样带回）
+/// - `oids`: 请求的 OID 列表（应答按相同顺序返回各自的下一个变量）
+pub fn build_get_next(community: &str, request_id: i32, oids: &[Vec<u32>]) -> Vec<u8> {
+    let mut varbinds = Vec::new();
+    for oid in oids {
+        let mut varbind = Vec::new();
+        push_tlv(&mut varbind, TAG_OID, &encode_oid(oid));
+        push_tlv(&mut varbind, TAG_NULL, &[]);
+        push_tlv(&mut varbinds, TAG_SEQUENCE, &varbind);
+    }
+    let mut pdu = Vec::new();
+    push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(request_id as i64));
+    push_tlv(&mut pdu, TAG_INTEGER, &[0]); // error-status
+    push_tlv(&mut pdu, TAG_INTEGER, &[0]); // error-index
+    push_tlv(&mut pdu, TAG_SEQUENCE, &varbinds);
+
+    let mut message = Vec::new();
+    push_tlv(&mut message, TAG_INTEGER, &[1]); // version: v2c
+    push_tlv(&mut message, TAG_OCTET_STRING, community.as_bytes());
+    push_tlv(&mut message, TAG_GET_NEXT_REQUEST, &pdu);
+    let mut out = Vec::new();
+    push_tlv(&mut out, TAG_SEQUENCE, &message);
+    out
+}
+
+/// BER 读取游标
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.bytes.len()
+    }
+
+    /// 读取一个 TLV，返回 (tag, content)
+    fn read(&mut self) -> Result<(u8, &'a [u8]), String> {
+        let damaged = || "SNMP 应答结构损坏".to_string();
+        let tag = *self.bytes.get(self.pos).ok_or_else(damaged)?;
+        let first = *self.bytes.get(self.pos + 1).ok_or_else(damaged)?;
+        self.pos += 2;
+        let len = if first & 0x80 == 0 {
+            first as usize
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count == 0 || count > 4 {
+                return Err(damaged());
+            }
+            let len_bytes = self.bytes.get(self.pos..self.pos + count).ok_or_else(damaged)?;
+            self.pos += count;
+            len_bytes.iter().fold(0usize, |acc, b| (acc << 8) | *b as usize)
+        };
+        let content = self.bytes.get(self.pos..self.pos + len).ok_or_else(damaged)?;
+        self.pos += len;
+        Ok((tag, content))
+    }
+
+    fn expect(&mut self, tag: u8) -> Result<&'a [u8], String> {
+        let (actual, content) = self.read()?;
+        if actual != tag {
+            return Err(format!("SNMP 应答结构损坏（期望标签 0x{:02X}，实际 0x{:02X}）", tag, actual));
+        }
+        Ok(content)
+    }
+}
+
+fn decode_integer(content: &[u8]) -> i64 {
+    let mut value: i64 = if content.first().is_some_and(|b| b & 0x80 != 0) { -1 } else { 0 };
+    for b in content.iter().take(8) {
+        value = (value << 8) | *b as i64;
+    }
+    value
+}
+
+fn decode_unsigned(content: &[u8]) -> i64 {
+    content.iter().take(8).fold(0i64, |acc, b| (acc << 8) | *b as i64)
+}
+
+fn decode_oid(content: &[u8]) -> Result<Vec<u32>, String> {
+    let first = *content.first().ok_or("SNMP 应答中的 OID 为空")?;
+    let top = (first / 40).min(2) as u32;
+    let mut oid = vec![top, first as u32 - top * 40];
+    let mut arc: u32 = 0;
+    for b in &content[1..] {
+        if arc > u32::MAX >> 7 {
+            return Err("SNMP 应答中的 OID 分量过大".to_string());
+        }
+        arc = (arc << 7) | (b & 0x7F) as u32;
+        if b & 0x80 == 0 {
+            oid.push(arc);
+            arc = 0;
+        }
+    }
+    Ok(oid)
+}
+
+fn decode_value(tag: u8, content: &[u8]) -> Result<SnmpValue, String> {
+    Ok(match tag {
+        TAG_INTEGER => SnmpValue::Integer(decode_integer(content)),
+        TAG_OCTET_STRING => SnmpValue::OctetString(content.to_vec()),
+        TAG_NULL => SnmpValue::Null,
+        TAG_OID => SnmpValue::ObjectId(decode_oid(content)?),
+        // Counter32 / Gauge32 / TimeTicks / Counter64
+        0x41 | 0x42 | 0x43 | 0x46 => SnmpValue::Integer(decode_unsigned(content)),
+        0x80..=0x82 => SnmpValue::EndOfMib,
+        other => SnmpValue::Other(other),
+    })
+}
+
+/// 解析 Response 报文
+///
+/// # 返回
+/// - `Ok(SnmpResponse)`: 请求 ID、错误状态与变量列表
+/// - `Err(String)`: 不是 SNMP 应答（结构损坏或 PDU 类型不符）
+pub fn parse_response(bytes: &[u8]) -> Result<SnmpResponse, String> {
+    let mut outer = Reader::new(bytes);
+    let mut message = Reader::new(outer.expect(TAG_SEQUENCE)?);
+    message.expect(TAG_INTEGER)?; // version
+    message.expect(TAG_OCTET_STRING)?; // community
+    let mut pdu = Reader::new(message.expect(TAG_RESPONSE)?);
+    let request_id = decode_integer(pdu.expect(TAG_INTEGER)?) as i32;
+    let error_status = decode_integer(pdu.expect(TAG_INTEGER)?);
+    pdu.expect(TAG_INTEGER)?; // error-index
+    let mut list = Reader::new(pdu.expect(TAG_SEQUENCE)?);
+    let mut varbinds = Vec::new();
+    while !list.is_empty() {
+        let mut varbind = Reader::new(list.expect(TAG_SEQUENCE)?);
+        let oid = decode_oid(varbind.expect(TAG_OID)?)?;
+        let (tag, content) = varbind.read()?;
+        varbinds.push((oid, decode_value(tag, content)?));
+    }
+    Ok(SnmpResponse { request_id, error_status, varbinds })
+}
+
+/// 耗材类型（PrtMarkerSuppliesTypeTC）归类
+fn supply_kind(type_code: i64) -> &'static str {
+    match type_code {
+        3 | 21 => "toner",
+        5..=7 => "ink",
+        9 => "drum",
+        4 | 8 | 14 => "waste",
+        _ => "other",
+    }
+}
+
+/// 余量百分比（无法计量时为 None）
+fn level_percent(level: i64, max_capacity: i64) -> Option<u8> {
+    if level < 0 || max_capacity <= 0 {
+        return None;
+    }
+    Some((level.min(max_capacity) * 100 / max_capacity) as u8)
+}
+
+/// 由遍历 prtMarkerSuppliesTable 得到的变量汇总耗材列表（按表索引排序）
+///
+/// # 参数
+/// - `varbinds`: 遍历 SUPPLY_COLUMNS 各列得到的 (OID, 值)，不属于耗材表的变量被忽略
+pub fn supplies_from_varbinds(varbinds: &[(Vec<u32>, SnmpValue)]) -> Vec<PrinterSupply> {
+    use std::collections::BTreeMap;
+
+    let mut rows: BTreeMap<Vec<u32>, BTreeMap<u32, &SnmpValue>> = BTreeMap::new();
+    for (oid, value) in varbinds {
+        let Some(rest) = oid.strip_prefix(SUPPLIES_ENTRY) else {
+            continue;
+        };
+        let Some((column, index)) = rest.split_first() else {
+            continue;
+        };
+        if SUPPLY_COLUMNS.contains(column) && !index.is_empty() {
+            rows.entry(index.to_vec()).or_default().insert(*column, value);
+        }
+    }
+
+    rows.into_iter()
+        .map(|(index, columns)| {
+            let integer = |column: u32| match columns.get(&column) {
+                Some(SnmpValue::Integer(v)) => *v,
+                _ => -2,
+            };
+            let description = match columns.get(&COL_DESCRIPTION) {
+                Some(SnmpValue::OctetString(bytes)) => {
+                    crate::encoding::decode_bytes(bytes).trim_end_matches('\0').trim().to_string()
+                }
+                _ => String::new(),
+            };
+            let level = integer(COL_LEVEL);
+            let max_capacity = integer(COL_MAX_CAPACITY);
+            let percent = level_percent(level, max_capacity);
+            let low = integer(COL_CLASS) != CLASS_RECEPTACLE && percent.is_some_and(|p| p <= LOW_SUPPLY_PERCENT);
+            PrinterSupply {
+                index: index.iter().map(u32::to_string).collect::<Vec<_>>().join("."),
+                description,
+                kind: supply_kind(integer(COL_TYPE)).to_string(),
+                level,
+                max_capacity,
+                percent,
+                low,
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(request_id: i32, varbinds: &[(Vec<u32>, u8, Vec<u8>)]) -> Vec<u8> {
+        let mut list = Vec::new();
+        for (oid, tag, content) in varbinds {
+            let mut varbind = Vec::new();
+            push_tlv(&mut varbind, TAG_OID, &encode_oid(oid));
+            push_tlv(&mut varbind, *tag, content);
+            push_tlv(&mut list, TAG_SEQUENCE, &varbind);
+        }
+        let mut pdu = Vec::new();
+        push_tlv(&mut pdu, TAG_INTEGER, &encode_integer(request_id as i64));
+        push_tlv(&mut pdu, TAG_INTEGER, &[0]);
+        push_tlv(&mut pdu, TAG_INTEGER, &[0]);
+        push_tlv(&mut pdu, TAG_SEQUENCE, &list);
+        let mut message = Vec::new();
+        push_tlv(&mut message, TAG_INTEGER, &[1]);
+        push_tlv(&mut message, TAG_OCTET_STRING, b"public");
+        push_tlv(&mut message, TAG_RESPONSE, &pdu);
+        let mut out = Vec::new();
+        push_tlv(&mut out, TAG_SEQUENCE, &message);
+        out
+    }
+
+    fn cell(column: u32, row: u32) -> Vec<u32> {
+        let mut oid = column_oid(column);
+        oid.extend([1, row]);
+        oid
+    }
+
+    #[test]
+    fn builds_get_next_and_summarizes_supplies() {
+        let request = build_get_next("public", 300, &[column_oid(COL_LEVEL)]);
+        assert_eq!(request[0], TAG_SEQUENCE);
+        let oid_bytes = [0x06, 0x0A, 0x2B, 6, 1, 2, 1, 43, 11, 1, 1, 9];
+        assert!(request.windows(oid_bytes.len()).any(|w| w == oid_bytes));
+        assert_eq!(encode_integer(-3), vec![0xFD]);
+        assert_eq!(encode_integer(300), vec![0x01, 0x2C]);
+        assert_eq!(encode_oid(&[1, 3, 6, 1, 4, 1, 2699]), vec![0x2B, 6, 1, 4, 1, 0x95, 0x0B]);
+
+        let reply = response(300, &[
+            (cell(COL_DESCRIPTION, 1), TAG_OCTET_STRING, b"Black Toner\0".to_vec()),
+            (cell(COL_TYPE, 1), TAG_INTEGER, vec![3]),
+            (cell(COL_MAX_CAPACITY, 1), TAG_INTEGER, encode_integer(10000)),
+            (cell(COL_LEVEL, 1), TAG_INTEGER, encode_integer(800)),
+            (cell(COL_DESCRIPTION, 2), TAG_OCTET_STRING, b"Drum Unit".to_vec()),
+            (cell(COL_TYPE, 2), TAG_INTEGER, vec![9]),
+            (cell(COL_MAX_CAPACITY, 2), TAG_INTEGER, encode_integer(100)),
+            (cell(COL_LEVEL, 2), TAG_INTEGER, encode_integer(-3)),
+            (cell(COL_CLASS, 3), TAG_INTEGER, vec![4]),
+            (cell(COL_TYPE, 3), TAG_INTEGER, vec![4]),
+            (cell(COL_MAX_CAPACITY, 3), TAG_INTEGER, encode_integer(100)),
+            (cell(COL_LEVEL, 3), TAG_INTEGER, encode_integer(5)),
+            (vec![1, 3, 6, 1, 2, 1, 43, 12, 1, 1, 4, 1, 1], 0x82, vec![]),
+        ]);
+        let parsed = parse_response(&reply).unwrap();
+        assert_eq!(parsed.request_id, 300);
+        assert_eq!(parsed.varbinds.last().unwrap().1, SnmpValue::EndOfMib);
+
+        let supplies = supplies_from_varbinds(&parsed.varbinds);
+        assert_eq!(supplies.len(), 3);
+        assert_eq!((supplies[0].index.as_str(), supplies[0].description.as_str()), ("1.1", "Black Toner"));
+        assert_eq!((supplies[0].kind.as_str(), supplies[0].percent, supplies[0].low), ("toner", Some(8), true));
+        assert_eq!((supplies[1].kind.as_str(), supplies[1].percent, supplies[1].low), ("drum", None, false));
+        assert_eq!((supplies[2].kind.as_str(), supplies[2].low), ("waste", false));
+
+        assert!(parse_response(b"\x30\x05\x02\x01").is_err());
+    }
+}
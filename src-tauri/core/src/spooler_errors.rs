@@ -0,0 +1,229 @@
+// 打印后台处理程序（Spooler）错误码映射模块
+//
+// Add-Printer / PrintUIEntry 失败时只给出原始异常文本（如 "0x80070705 unknown driver"），
+// 本模块从文本中取出 HRESULT / Win32 错误码，映射为稳定的错误码、中英文说明与处理建议：
+// - HRESULT 0x8007xxxx（FACILITY_WIN32）取低 16 位作为 Win32 错误码
+// - 其他 8 位十六进制值（如 0x00000709）直接视为 Win32 错误码
+// - 未收录的错误码返回 None，调用方保留原始错误信息
+//
+// 另外识别 "cmdlet 不存在" 类错误（PrintManagement 模块缺失，或 32 位 PowerShell 看不到 64 位模块），
+// 这类错误没有 Win32 错误码，由 missing_print_cmdlet() 单独判断
+
+use serde::Serialize;
+
+/// 已知的打印错误
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpoolerErrorInfo {
+    /// 稳定错误码（写入 [EASYPRINTER_CODE=...]）
+    pub code: &'static str,
+    /// Win32 错误码
+    pub win32_code: u32,
+    pub message_zh: &'static str,
+    pub message_en: &'static str,
+    pub remediation_zh: &'static str,
+    pub remediation_en: &'static str,
+}
+
+const fn info(
+    code: &'static str,
+    win32_code: u32,
+    message_zh: &'static str,
+    message_en: &'static str,
+    remediation_zh: &'static str,
+    remediation_en: &'static str,
+) -> SpoolerErrorInfo {
+    SpoolerErrorInfo { code, win32_code, message_zh, message_en, remediation_zh, remediation_en }
+}
+
+/// 已收录的错误码（按 Win32 错误码排序）
+pub const KNOWN_SPOOLER_ERRORS: [SpoolerErrorInfo; 12] = [
+    info(
+        "WIN_ACCESS_DENIED",
+        5,
+        "没有权限执行该操作",
+        "Access is denied",
+        "请以管理员身份运行 ePrinty，或联系 IT 授予安装打印机的权限",
+        "Run ePrinty as administrator, or ask IT to grant printer installation rights",
+    ),
+    info(
+        "WIN_DRIVER_MODULE_NOT_FOUND",
+        126,
+        "驱动依赖的模块（DLL）缺失",
+        "A module required by the driver was not found",
+        "驱动包可能不完整，请使用 driverInstallPolicy=always 重新安装驱动",
+        "The driver package may be incomplete; reinstall it with driverInstallPolicy=always",
+    ),
+    info(
+        "WIN_SPOOLER_UNAVAILABLE",
+        1722,
+        "打印后台处理程序（Print Spooler）服务不可用",
+        "The Print Spooler service is unavailable",
+        "请在「服务」中启动 Print Spooler 服务后重试",
+        "Start the Print Spooler service and try again",
+    ),
+    info(
+        "WIN_UNKNOWN_PORT",
+        1796,
+        "指定的打印机端口不存在",
+        "The specified printer port is unknown",
+        "端口可能创建失败或已被删除，请重新安装（会重新创建端口）",
+        "The port may have failed to create or was removed; reinstall to recreate it",
+    ),
+    info(
+        "WIN_UNKNOWN_PRINTER_DRIVER",
+        1797,
+        "系统中没有该名称的打印机驱动",
+        "The printer driver is unknown",
+        "请确认 driverCatalog 中的驱动名称与 INF 中声明的型号完全一致，或使用 driverInstallPolicy=always 重新安装驱动",
+        "Check that the driver name in driverCatalog exactly matches the model declared in the INF, or reinstall the driver with driverInstallPolicy=always",
+    ),
+    info(
+        "WIN_UNKNOWN_PRINT_PROCESSOR",
+        1798,
+        "指定的打印处理器不存在",
+        "The print processor is unknown",
+        "请检查配置中的 printProcessor，或删除该设置使用驱动默认的处理器",
+        "Check printProcessor in the config, or remove it to use the driver default",
+    ),
+    info(
+        "WIN_INVALID_PRINTER_NAME",
+        1801,
+        "打印机名称无效或无法访问",
+        "The printer name is invalid",
+        "请检查打印机名称是否包含不支持的字符；若为共享打印机，请确认打印服务器可访问",
+        "Check the printer name for unsupported characters; for shared printers, make sure the print server is reachable",
+    ),
+    info(
+        "WIN_PRINTER_ALREADY_EXISTS",
+        1802,
+        "同名打印机已存在",
+        "The printer already exists",
+        "请先删除同名打印机，或使用重新安装",
+        "Delete the existing printer first, or use reinstall",
+    ),
+    info(
+        "WIN_PRINTER_NOT_FOUND",
+        3012,
+        "找不到打印机",
+        "The printer was not found",
+        "请确认打印服务器上的共享名或队列名是否正确",
+        "Check the share or queue name on the print server",
+    ),
+    info(
+        "WIN_PRINTER_DRIVER_BLOCKED",
+        3014,
+        "驱动被系统策略阻止安装",
+        "The printer driver is blocked",
+        "该驱动被组策略或系统兼容性列表阻止，请联系 IT 提供受支持的驱动版本",
+        "The driver is blocked by policy or the compatibility list; ask IT for a supported driver version",
+    ),
+    info(
+        "WIN_CORE_DRIVER_PACKAGE_NOT_FOUND",
+        3016,
+        "驱动依赖的系统核心驱动包缺失",
+        "A core driver package required by the driver was not found",
+        "请通过 Windows 更新安装缺失的核心驱动（如 Microsoft PCL6 / PS Class Driver）后重试",
+        "Install the missing core driver (e.g. Microsoft PCL6 / PS Class Driver) via Windows Update and try again",
+    ),
+    info(
+        "WIN_PRINTER_DRIVER_DOWNLOAD_NEEDED",
+        3019,
+        "需要从打印服务器下载驱动，但被系统阻止（Point and Print 限制）",
+        "A printer driver must be downloaded from the print server (Point and Print restriction)",
+        "请以管理员身份安装，或由 IT 预先在本机安装该驱动 / 调整 Point and Print 策略",
+        "Install as administrator, or ask IT to preinstall the driver or adjust the Point and Print policy",
+    ),
+];
+
+/// 从错误文本中取出 Win32 错误码（"0x80070705" → 1797，"0x00000709" → 1801）
+fn extract_win32_codes(text: &str) -> Vec<u32> {
+    let lower = text.to_ascii_lowercase();
+    let mut codes = Vec::new();
+    let mut rest = lower.as_str();
+    while let Some(pos) = rest.find("0x") {
+        let digits: String = rest[pos + 2..].chars().take_while(|c| c.is_ascii_hexdigit()).collect();
+        rest = &rest[pos + 2 + digits.len()..];
+        let Ok(value) = u32::from_str_radix(&digits, 16) else {
+            continue;
+        };
+        if digits.len() > 8 {
+            continue;
+        }
+        let code = if value & 0xFFFF_0000 == 0x8007_0000 { value & 0xFFFF } else { value };
+        codes.push(code);
+    }
+    codes
+}
+
+/// 按错误文本中的错误码查找已知错误
+///
+/// # 参数
+/// - `text`: Add-Printer 等命令的错误输出
+///
+/// # 返回
+/// - `Some(&SpoolerErrorInfo)`: 文本中第一个已收录的错误码
+/// - `None`: 没有可识别的错误码
+pub fn classify_spooler_error(text: &str) -> Option<&'static SpoolerErrorInfo> {
+    extract_win32_codes(text)
+        .into_iter()
+        .find_map(|code| KNOWN_SPOOLER_ERRORS.iter().find(|e| e.win32_code == code))
+}
+
+/// 打印管理相关的 cmdlet（PrintManagement 模块）
+const PRINT_CMDLETS: [&str; 10] = [
+    "Add-PrinterPort",
+    "Add-PrinterDriver",
+    "Add-Printer",
+    "Get-PrinterPort",
+    "Get-PrinterDriver",
+    "Get-Printer",
+    "Remove-PrinterPort",
+    "Remove-PrinterDriver",
+    "Remove-Printer",
+    "Set-Printer",
+];
+
+/// 判断 PowerShell 错误输出是否为打印 cmdlet 不存在
+///
+/// # 返回
+/// - `Some(cmdlet)`: 找不到的打印 cmdlet 名称
+/// - `None`: 不是 "cmdlet 不存在" 错误，或缺失的不是打印 cmdlet
+pub fn missing_print_cmdlet(stderr: &str) -> Option<&'static str> {
+    let not_found = stderr.contains("CommandNotFoundException")
+        || stderr.contains("is not recognized as the name of a cmdlet")
+        || stderr.contains("识别为 cmdlet");
+    if !not_found {
+        return None;
+    }
+    // PRINT_CMDLETS 中同前缀的长名称在前，避免 Add-PrinterPort 被识别成 Add-Printer
+    PRINT_CMDLETS.iter().copied().find(|cmdlet| {
+        ["'", "\u{201C}", "\""].iter().any(|quote| stderr.contains(&format!("{}{}", quote, cmdlet)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_hresult_and_win32_codes() {
+        let unknown_driver = classify_spooler_error("Add-Printer : 指定的打印机驱动程序未知。 HRESULT 0x80070705").unwrap();
+        assert_eq!(unknown_driver.code, "WIN_UNKNOWN_PRINTER_DRIVER");
+        assert_eq!(classify_spooler_error("操作无法完成(错误 0x00000709)。").unwrap().code, "WIN_INVALID_PRINTER_NAME");
+        assert_eq!(classify_spooler_error("0x00000BCB").unwrap().code, "WIN_PRINTER_DRIVER_DOWNLOAD_NEEDED");
+        assert_eq!(classify_spooler_error("HRESULT 0x80070005 Access denied").unwrap().code, "WIN_ACCESS_DENIED");
+        assert_eq!(classify_spooler_error("0x80004005 未指定的错误"), None);
+        assert_eq!(classify_spooler_error("没有错误码"), None);
+    }
+
+    #[test]
+    fn detects_missing_print_cmdlets() {
+        let en = "Add-PrinterPort : The term 'Add-PrinterPort' is not recognized as the name of a cmdlet, function, script file, or operable program.";
+        let zh = "Add-Printer : 无法将\u{201C}Add-Printer\u{201D}项识别为 cmdlet、函数、脚本文件或可运行程序的名称。";
+        assert_eq!(missing_print_cmdlet(en), Some("Add-PrinterPort"));
+        assert_eq!(missing_print_cmdlet(zh), Some("Add-Printer"));
+        assert_eq!(missing_print_cmdlet("The term 'Foo-Bar' is not recognized as the name of a cmdlet"), None);
+        assert_eq!(missing_print_cmdlet("Add-Printer : 指定的打印机驱动程序未知。"), None);
+    }
+}
@@ -3,6 +3,7 @@
 /// 用于替代 PowerShell Expand-Archive，提供：
 /// - 防 Zip Slip 的路径验证
 /// - 可观测性（日志记录、进度回调）
+/// - 加密 ZIP（ZipCrypto）解压：由调用方提供密码，密码错误时返回 BadPassword
 /// - 取消支持（AtomicBool）
 /// - 详细的错误信息
 
@@ -49,6 +50,14 @@ pub enum ExtractError {
         path: String,
         reason: String,
     },
+    /// ZIP 条目已加密，但未提供密码
+    PasswordRequired {
+        entry_name: String,
+    },
+    /// 密码错误（或加密条目已损坏）
+    BadPassword {
+        entry_name: String,
+    },
     /// 用户取消操作
     Cancelled,
     /// 其他错误
@@ -73,6 +82,12 @@ impl std::fmt::Display for ExtractError {
             ExtractError::PermissionDenied { path, reason } => {
                 write!(f, "权限被拒绝 | 路径: '{}' | 原因: {}", path, reason)
             }
+            ExtractError::PasswordRequired { entry_name } => {
+                write!(f, "ZIP 已加密，需要密码才能解压 | Entry: '{}'", entry_name)
+            }
+            ExtractError::BadPassword { entry_name } => {
+                write!(f, "ZIP 密码错误（或加密内容已损坏） | Entry: '{}'", entry_name)
+            }
             ExtractError::Cancelled => {
                 write!(f, "用户取消解压操作")
             }
@@ -176,6 +191,30 @@ pub fn extract_zip_to_dir(
     dest_dir: &Path,
     cancel: Option<&AtomicBool>,
     progress_cb: Option<&dyn Fn(usize, usize)>,
+) -> Result<ExtractReport, ExtractError> {
+    extract_zip_to_dir_with_password(zip_path, dest_dir, None, cancel, progress_cb)
+}
+
+/// 提取 ZIP 到指定目录（支持加密 ZIP）
+///
+/// # 参数
+/// - `zip_path`: ZIP 文件路径
+/// - `dest_dir`: 目标目录（不需提前存在，会自动创建）
+/// - `password`: 解压密码（None 表示不加密；未加密的条目忽略密码）
+/// - `cancel`: 可选的取消标志（原子操作）
+/// - `progress_cb`: 可选的进度回调：`fn(done: usize, total: usize)`
+///
+/// # 返回
+/// - `Ok(ExtractReport)`: 解压成功的统计信息
+/// - `Err(ExtractError::PasswordRequired)`: 条目已加密但未提供密码
+/// - `Err(ExtractError::BadPassword)`: 密码错误（校验字节不符，或解密后 CRC 校验失败）
+/// - `Err(ExtractError)`: 其他解压失败
+pub fn extract_zip_to_dir_with_password(
+    zip_path: &Path,
+    dest_dir: &Path,
+    password: Option<&str>,
+    cancel: Option<&AtomicBool>,
+    progress_cb: Option<&dyn Fn(usize, usize)>,
 ) -> Result<ExtractReport, ExtractError> {
     let start_time = Instant::now();
     
@@ -255,12 +294,36 @@ pub fn extract_zip_to_dir(
             cb(i, total_entries);
         }
         
-        let mut entry = archive.by_index(i).map_err(|e| {
-            ExtractError::ZipFormatError {
-                reason: format!("无法读取 ZIP 条目 #{}: {}", i, e),
+        let opened = match password {
+            Some(pw) => archive.by_index_decrypt(i, pw.as_bytes()),
+            None => archive.by_index(i).map(Ok),
+        };
+        // 密码相关的失败：Some(true) = 条目已加密但未提供密码，Some(false) = 密码错误
+        let password_failure = match &opened {
+            Ok(Ok(_)) => None,
+            Ok(Err(zip::result::InvalidPassword)) => Some(false),
+            Err(zip::result::ZipError::UnsupportedArchive(zip::result::ZipError::PASSWORD_REQUIRED)) => Some(true),
+            Err(e) => {
+                return Err(ExtractError::ZipFormatError {
+                    reason: format!("无法读取 ZIP 条目 #{}: {}", i, e),
+                });
             }
-        })?;
-        
+        };
+        if let Some(password_required) = password_failure {
+            // 释放对 archive 的借用后再读取条目名
+            drop(opened);
+            let entry_name = archive.by_index_raw(i).map(|e| e.name().to_string()).unwrap_or_default();
+            if password_required {
+                eprintln!("[ZipExtract] error=password_required entry=\"{}\"", entry_name);
+                return Err(ExtractError::PasswordRequired { entry_name });
+            }
+            eprintln!("[ZipExtract] error=bad_password entry=\"{}\"", entry_name);
+            return Err(ExtractError::BadPassword { entry_name });
+        }
+        let Ok(Ok(mut entry)) = opened else {
+            unreachable!("打开失败的条目已在上面返回");
+        };
+
         let entry_name = entry.name().to_string();
         
         // 验证路径安全性（防 Zip Slip）
@@ -324,6 +387,13 @@ pub fn extract_zip_to_dir(
             let mut buffer = vec![0u8; buffer_size];
             loop {
                 let bytes_read = entry.read(&mut buffer).map_err(|e| {
+                    // ZipCrypto 的校验字节只有 1/256 的区分度，错误密码也可能通过校验，
+                    // 此时解密出的内容 CRC 不符，同样按密码错误处理
+                    if password.is_some() && e.to_string().contains("Invalid checksum") {
+                        return ExtractError::BadPassword {
+                            entry_name: entry_name.clone(),
+                        };
+                    }
                     ExtractError::IoError {
                         operation: "读取 ZIP 条目".to_string(),
                         path: entry_name.clone(),
@@ -413,7 +483,7 @@ mod tests {
         
         // 验证
         assert_eq!(report.files_extracted, 3, "应该提取 3 个文件");
-        assert_eq!(report.directories_created, 3, "应该创建 3 个目录");
+        assert_eq!(report.directories_created, 2, "应该创建 2 个目录（dir1/ 与 dir1/subdir/ 条目）");
         assert!(extract_dir.join("dir1/file1.txt").exists());
         assert!(extract_dir.join("dir1/subdir/file2.txt").exists());
         assert!(extract_dir.join("root.txt").exists());
@@ -455,6 +525,42 @@ mod tests {
         Ok(())
     }
     
+    /// `zip -0 -P secret` 生成的加密 ZIP（ZipCrypto）：a.inf = "driver"
+    const ENCRYPTED_ZIP: &[u8] = &[
+        0x50, 0x4b, 0x03, 0x04, 0x0a, 0x00, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x21, 0x58, 0xd9, 0x7c,
+        0x66, 0x11, 0x12, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00, 0x00, 0x05, 0x00, 0x00, 0x00, 0x61, 0x2e,
+        0x69, 0x6e, 0x66, 0x42, 0x07, 0x15, 0xb4, 0xad, 0x2a, 0x38, 0x7d, 0x94, 0x84, 0xdd, 0x9b, 0x42,
+        0x02, 0x17, 0x42, 0xad, 0xf3, 0x50, 0x4b, 0x07, 0x08, 0xd9, 0x7c, 0x66, 0x11, 0x12, 0x00, 0x00,
+        0x00, 0x06, 0x00, 0x00, 0x00, 0x50, 0x4b, 0x01, 0x02, 0x1e, 0x03, 0x0a, 0x00, 0x09, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x21, 0x58, 0xd9, 0x7c, 0x66, 0x11, 0x12, 0x00, 0x00, 0x00, 0x06, 0x00, 0x00,
+        0x00, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0xa4, 0x81, 0x00,
+        0x00, 0x00, 0x00, 0x61, 0x2e, 0x69, 0x6e, 0x66, 0x50, 0x4b, 0x05, 0x06, 0x00, 0x00, 0x00, 0x00,
+        0x01, 0x00, 0x01, 0x00, 0x33, 0x00, 0x00, 0x00, 0x45, 0x00, 0x00, 0x00, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_encrypted_extraction() -> Result<(), Box<dyn std::error::Error>> {
+        let temp_dir = TempDir::new()?;
+        let zip_path = temp_dir.path().join("encrypted.zip");
+        fs::write(&zip_path, ENCRYPTED_ZIP)?;
+
+        // 正确密码
+        let extract_dir = temp_dir.path().join("ok");
+        let report = extract_zip_to_dir_with_password(&zip_path, &extract_dir, Some("secret"), None, None)?;
+        assert_eq!(report.files_extracted, 1);
+        assert_eq!(fs::read_to_string(extract_dir.join("a.inf"))?, "driver");
+
+        // 未提供密码
+        let result = extract_zip_to_dir(&zip_path, &temp_dir.path().join("none"), None, None);
+        assert!(matches!(result, Err(ExtractError::PasswordRequired { .. })), "应该返回 PasswordRequired 错误");
+
+        // 密码错误
+        let result = extract_zip_to_dir_with_password(&zip_path, &temp_dir.path().join("bad"), Some("wrong"), None, None);
+        assert!(matches!(result, Err(ExtractError::BadPassword { .. })), "应该返回 BadPassword 错误");
+
+        Ok(())
+    }
+
     #[test]
     fn test_cancellation() -> Result<(), Box<dyn std::error::Error>> {
         let temp_dir = TempDir::new()?;
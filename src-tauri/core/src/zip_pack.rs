@@ -0,0 +1,133 @@
+/// 确定性 ZIP 打包工具模块
+///
+/// 用于制作驱动包（与 zip_extract 的 drivers_root 布局对应）：
+/// - 条目按相对路径排序，时间戳固定为 1980-01-01，权限固定（文件 0644 / 目录 0755）
+/// - 同一目录内容重复打包得到字节完全相同的 ZIP（sha256 稳定，便于写入配置）
+/// - ZIP 内以源目录名为顶层目录
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use walkdir::WalkDir;
+use zip::write::FileOptions;
+
+/// 打包结果信息
+#[derive(Debug, Clone)]
+pub struct PackReport {
+    pub files_packed: usize,
+    pub directories_packed: usize,
+    /// 源文件总字节数（未压缩）
+    pub bytes_read: u64,
+}
+
+/// 将目录确定性地打包为 ZIP
+///
+/// # 参数
+/// - `source_dir`: 要打包的目录（其目录名作为 ZIP 内顶层目录）
+/// - `output_zip`: 输出 ZIP 路径（已存在时覆盖，不能位于 source_dir 内）
+///
+/// # 返回
+/// - `Ok(PackReport)`: 打包成功
+/// - `Err(String)`: 错误信息
+pub fn pack_dir_deterministic(source_dir: &Path, output_zip: &Path) -> Result<PackReport, String> {
+    let root_name = source_dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .filter(|n| !n.is_empty())
+        .ok_or_else(|| format!("无法确定源目录名称: {}", source_dir.display()))?;
+
+    let entries: Vec<walkdir::DirEntry> = WalkDir::new(source_dir)
+        .follow_links(false)
+        .sort_by_file_name()
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("遍历源目录失败 {}: {}", source_dir.display(), e))?;
+
+    if let Some(parent) = output_zip.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("创建输出目录失败 {}: {}", parent.display(), e))?;
+    }
+    let file = fs::File::create(output_zip)
+        .map_err(|e| format!("创建 ZIP 文件失败 {}: {}", output_zip.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+
+    let base_options = FileOptions::default().last_modified_time(zip::DateTime::default());
+    let dir_options = base_options.unix_permissions(0o755);
+    let file_options = base_options
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+
+    let mut report = PackReport {
+        files_packed: 0,
+        directories_packed: 0,
+        bytes_read: 0,
+    };
+
+    for entry in entries {
+        let relative = entry
+            .path()
+            .strip_prefix(source_dir)
+            .map_err(|e| format!("计算相对路径失败 {}: {}", entry.path().display(), e))?;
+        // ZIP 内统一使用 '/' 分隔
+        let mut name = root_name.clone();
+        for component in relative.components() {
+            name.push('/');
+            name.push_str(&component.as_os_str().to_string_lossy());
+        }
+
+        let file_type = entry.file_type();
+        if file_type.is_dir() {
+            zip.add_directory(name.as_str(), dir_options)
+                .map_err(|e| format!("写入 ZIP 目录条目失败 {}: {}", name, e))?;
+            report.directories_packed += 1;
+        } else if file_type.is_file() {
+            zip.start_file(name.as_str(), file_options)
+                .map_err(|e| format!("写入 ZIP 文件条目失败 {}: {}", name, e))?;
+            let mut source = fs::File::open(entry.path())
+                .map_err(|e| format!("打开源文件失败 {}: {}", entry.path().display(), e))?;
+            let copied = io::copy(&mut source, &mut zip)
+                .map_err(|e| format!("写入 ZIP 文件内容失败 {}: {}", name, e))?;
+            report.files_packed += 1;
+            report.bytes_read += copied;
+        } else {
+            // 符号链接等特殊条目不打包（解压端也不会创建它们）
+            eprintln!("[ZipPack] skip non-regular entry path=\"{}\"", entry.path().display());
+        }
+    }
+
+    let mut file = zip
+        .finish()
+        .map_err(|e| format!("完成 ZIP 写入失败 {}: {}", output_zip.display(), e))?;
+    file.flush()
+        .map_err(|e| format!("刷新 ZIP 文件失败 {}: {}", output_zip.display(), e))?;
+
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_pack_is_deterministic() {
+        let temp = TempDir::new().unwrap();
+        let source = temp.path().join("DriverPack");
+        fs::create_dir_all(source.join("x64")).unwrap();
+        fs::write(source.join("x64").join("driver.inf"), b"[Version]\r\nClass=Printer\r\n").unwrap();
+        fs::write(source.join("readme.txt"), b"hello").unwrap();
+
+        let first = temp.path().join("first.zip");
+        let second = temp.path().join("second.zip");
+        let report = pack_dir_deterministic(&source, &first).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        pack_dir_deterministic(&source, &second).unwrap();
+
+        assert_eq!(report.files_packed, 2);
+        assert_eq!(fs::read(&first).unwrap(), fs::read(&second).unwrap());
+
+        let archive = zip::ZipArchive::new(fs::File::open(&first).unwrap()).unwrap();
+        let names: Vec<&str> = archive.file_names().collect();
+        assert!(names.contains(&"DriverPack/x64/driver.inf"));
+    }
+}
@@ -0,0 +1,229 @@
+// 驱动包增量补丁模块
+//
+// 补丁本身也是 ZIP（按文件粒度，不做二进制差分）：
+// - `files/<包内路径>`：新版本中新增或内容有变化的文件（及新增的目录条目）
+// - `removed.txt`：新版本中已删除的条目路径，每行一个
+//
+// 应用补丁：把旧驱动包解压到工作目录，按 removed.txt 删除、写入 files/ 下的文件，再用 zip_pack 确定性重新打包。
+// 新旧驱动包都由 zip_pack 制作时，重建结果与新驱动包字节完全相同（sha256 一致）；调用方仍需校验 sha256。
+
+use crate::zip_extract::extract_zip_to_dir;
+use crate::zip_pack::pack_dir_deterministic;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::FileOptions;
+
+/// 补丁内已删除条目列表的文件名
+pub const REMOVED_LIST: &str = "removed.txt";
+
+/// 补丁内变化文件的目录前缀
+pub const FILES_PREFIX: &str = "files/";
+
+/// 补丁制作结果信息
+#[derive(Debug, Clone)]
+pub struct PatchReport {
+    /// 新增或变化的文件数
+    pub files_changed: usize,
+    /// 已删除的条目数
+    pub entries_removed: usize,
+    /// 未变化的文件数
+    pub files_unchanged: usize,
+}
+
+/// ZIP 条目摘要（用于比较新旧驱动包）
+struct EntrySummary {
+    is_dir: bool,
+    crc32: u32,
+    size: u64,
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<fs::File>, String> {
+    let file = fs::File::open(path).map_err(|e| format!("打开 ZIP 文件失败 {}: {}", path.display(), e))?;
+    zip::ZipArchive::new(file).map_err(|e| format!("读取 ZIP 文件失败 {}: {}", path.display(), e))
+}
+
+fn summarize(archive: &mut zip::ZipArchive<fs::File>) -> Result<BTreeMap<String, EntrySummary>, String> {
+    let mut entries = BTreeMap::new();
+    for index in 0..archive.len() {
+        let entry = archive.by_index_raw(index).map_err(|e| format!("读取 ZIP 条目失败 index={}: {}", index, e))?;
+        entries.insert(
+            entry.name().to_string(),
+            EntrySummary { is_dir: entry.is_dir(), crc32: entry.crc32(), size: entry.size() },
+        );
+    }
+    Ok(entries)
+}
+
+/// 把补丁中的包内路径转换为相对路径（拒绝绝对路径与 ".."，防止写到工作目录以外）
+fn safe_relative_path(name: &str) -> Result<PathBuf, String> {
+    let path = Path::new(name.trim_end_matches('/'));
+    if name.trim().is_empty() || !path.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(format!("补丁中的路径无效: \"{}\"", name));
+    }
+    Ok(path.to_path_buf())
+}
+
+/// 比较新旧驱动包，制作增量补丁
+///
+/// # 参数
+/// - `base_zip`: 旧驱动包（客户端缓存中已有的版本）
+/// - `new_zip`: 新驱动包
+/// - `output_patch`: 输出补丁路径（已存在时覆盖）
+///
+/// # 返回
+/// - `Ok(PatchReport)`: 制作成功
+/// - `Err(String)`: 错误信息（驱动包无法读取、包含加密条目等）
+pub fn build_patch(base_zip: &Path, new_zip: &Path, output_patch: &Path) -> Result<PatchReport, String> {
+    let base = summarize(&mut open_archive(base_zip)?)?;
+    let mut new_archive = open_archive(new_zip)?;
+    let new = summarize(&mut new_archive)?;
+
+    if let Some(parent) = output_patch.parent().filter(|p| !p.as_os_str().is_empty()) {
+        fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败 {}: {}", parent.display(), e))?;
+    }
+    let file = fs::File::create(output_patch)
+        .map_err(|e| format!("创建补丁文件失败 {}: {}", output_patch.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    let base_options = FileOptions::default().last_modified_time(zip::DateTime::default());
+    let dir_options = base_options.unix_permissions(0o755);
+    let file_options = base_options
+        .compression_method(zip::CompressionMethod::Deflated)
+        .unix_permissions(0o644);
+
+    let mut report = PatchReport { files_changed: 0, entries_removed: 0, files_unchanged: 0 };
+    for (name, summary) in &new {
+        let unchanged = base
+            .get(name)
+            .map(|b| b.is_dir == summary.is_dir && b.crc32 == summary.crc32 && b.size == summary.size)
+            .unwrap_or(false);
+        if unchanged {
+            if !summary.is_dir {
+                report.files_unchanged += 1;
+            }
+            continue;
+        }
+        let patch_name = format!("{}{}", FILES_PREFIX, name);
+        if summary.is_dir {
+            zip.add_directory(patch_name.as_str(), dir_options)
+                .map_err(|e| format!("写入补丁目录条目失败 {}: {}", patch_name, e))?;
+            continue;
+        }
+        let mut source = new_archive
+            .by_name(name)
+            .map_err(|e| format!("读取新驱动包条目失败 {}（不支持加密驱动包）: {}", name, e))?;
+        zip.start_file(patch_name.as_str(), file_options)
+            .map_err(|e| format!("写入补丁文件条目失败 {}: {}", patch_name, e))?;
+        io::copy(&mut source, &mut zip).map_err(|e| format!("写入补丁文件内容失败 {}: {}", patch_name, e))?;
+        report.files_changed += 1;
+    }
+
+    let removed: Vec<&str> = base.keys().filter(|name| !new.contains_key(*name)).map(String::as_str).collect();
+    report.entries_removed = removed.len();
+    zip.start_file(REMOVED_LIST, file_options)
+        .map_err(|e| format!("写入 {} 失败: {}", REMOVED_LIST, e))?;
+    zip.write_all(removed.join("\n").as_bytes())
+        .map_err(|e| format!("写入 {} 失败: {}", REMOVED_LIST, e))?;
+
+    let mut file = zip
+        .finish()
+        .map_err(|e| format!("完成补丁写入失败 {}: {}", output_patch.display(), e))?;
+    file.flush()
+        .map_err(|e| format!("刷新补丁文件失败 {}: {}", output_patch.display(), e))?;
+    Ok(report)
+}
+
+/// 在旧驱动包上应用补丁，重建新驱动包
+///
+/// # 参数
+/// - `base_zip`: 旧驱动包
+/// - `patch_zip`: build_patch 制作的补丁
+/// - `work_dir`: 工作目录（必须不存在或为空，调用方负责清理）
+/// - `output_zip`: 重建的新驱动包路径（已存在时覆盖）
+///
+/// # 返回
+/// - `Ok(())`: 重建完成（sha256 由调用方校验）
+/// - `Err(String)`: 错误信息
+pub fn apply_patch(base_zip: &Path, patch_zip: &Path, work_dir: &Path, output_zip: &Path) -> Result<(), String> {
+    extract_zip_to_dir(base_zip, work_dir, None, None).map_err(|e| format!("解压旧驱动包失败: {}", e))?;
+
+    let mut patch = open_archive(patch_zip)?;
+    let mut removed = String::new();
+    patch
+        .by_name(REMOVED_LIST)
+        .map_err(|e| format!("补丁缺少 {}: {}", REMOVED_LIST, e))?
+        .read_to_string(&mut removed)
+        .map_err(|e| format!("读取 {} 失败: {}", REMOVED_LIST, e))?;
+    for name in removed.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        let target = work_dir.join(safe_relative_path(name)?);
+        let result = if target.is_dir() { fs::remove_dir_all(&target) } else { fs::remove_file(&target) };
+        if let Err(e) = result.or_else(|e| if e.kind() == io::ErrorKind::NotFound { Ok(()) } else { Err(e) }) {
+            return Err(format!("删除条目失败 {}: {}", target.display(), e));
+        }
+    }
+
+    for index in 0..patch.len() {
+        let mut entry = patch.by_index(index).map_err(|e| format!("读取补丁条目失败 index={}: {}", index, e))?;
+        let Some(name) = entry.name().strip_prefix(FILES_PREFIX).map(str::to_string) else {
+            continue;
+        };
+        let target = work_dir.join(safe_relative_path(&name)?);
+        if entry.is_dir() {
+            fs::create_dir_all(&target).map_err(|e| format!("创建目录失败 {}: {}", target.display(), e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败 {}: {}", parent.display(), e))?;
+        }
+        let mut dest = fs::File::create(&target).map_err(|e| format!("写入文件失败 {}: {}", target.display(), e))?;
+        io::copy(&mut entry, &mut dest).map_err(|e| format!("写入文件失败 {}: {}", target.display(), e))?;
+    }
+
+    // 驱动包以单个顶层目录为根（zip_pack 的布局）
+    let roots: Vec<PathBuf> = fs::read_dir(work_dir)
+        .map_err(|e| format!("读取工作目录失败 {}: {}", work_dir.display(), e))?
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    match roots.as_slice() {
+        [root] if root.is_dir() => pack_dir_deterministic(root, output_zip).map(|_| ()),
+        _ => Err(format!("补丁应用后顶层条目数为 {}，不是单个目录，无法重建驱动包", roots.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_patch_rebuilds_identical_pack() {
+        let temp = TempDir::new().unwrap();
+        let old = temp.path().join("v1").join("DriverPack");
+        fs::create_dir_all(old.join("x64")).unwrap();
+        fs::write(old.join("x64").join("driver.inf"), b"DriverVer=1.0").unwrap();
+        fs::write(old.join("x64").join("driver.dll"), vec![7u8; 4096]).unwrap();
+        fs::write(old.join("obsolete.txt"), b"old").unwrap();
+        let new = temp.path().join("v2").join("DriverPack");
+        fs::create_dir_all(new.join("x64")).unwrap();
+        fs::create_dir_all(new.join("arm64")).unwrap();
+        fs::write(new.join("x64").join("driver.inf"), b"DriverVer=2.0").unwrap();
+        fs::write(new.join("x64").join("driver.dll"), vec![7u8; 4096]).unwrap();
+        fs::write(new.join("arm64").join("driver.inf"), b"DriverVer=2.0").unwrap();
+
+        let (base_zip, new_zip) = (temp.path().join("v1.zip"), temp.path().join("v2.zip"));
+        pack_dir_deterministic(&old, &base_zip).unwrap();
+        pack_dir_deterministic(&new, &new_zip).unwrap();
+
+        let patch_zip = temp.path().join("v1-v2.patch.zip");
+        let report = build_patch(&base_zip, &new_zip, &patch_zip).unwrap();
+        assert_eq!((report.files_changed, report.entries_removed, report.files_unchanged), (2, 1, 1));
+
+        let rebuilt = temp.path().join("rebuilt.zip");
+        apply_patch(&base_zip, &patch_zip, &temp.path().join("work"), &rebuilt).unwrap();
+        assert_eq!(fs::read(&rebuilt).unwrap(), fs::read(&new_zip).unwrap());
+
+        assert!(safe_relative_path("../evil.dll").is_err());
+    }
+}
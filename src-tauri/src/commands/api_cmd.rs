@@ -0,0 +1,171 @@
+/**
+ * 统一返回结构的命令（ApiResponse 信封）
+ * 职责：
+ * - 所有 api_* 命令都返回 { ok, data, error: { code, message, details } }，不再通过 Err(String) 抛错
+ * - 成功载荷中内嵌的失败（InstallResult.success=false、DeletePrinterResult.success=false）同样转为 ok=false，
+ *   原始载荷仍放在 data 中，前端可以继续读取 jobId / logPath 等字段
+ * - 错误码优先取消息中的 [EASYPRINTER_CODE=...]，否则使用各命令的默认错误码；消息中的 step=... 放入 details.step
+ *
+ * 兼容：原有命令（load_config / install_printer / delete_printer / print_test_page / check_version_update）
+ * 保持原返回结构不变；api_* 命令用 api_command! 宏包装它们，参数与原命令完全一致。
+ * 前端经 services/apiClient.ts 的 invokeApi 调用 api_* 命令
+ */
+
+use serde::Serialize;
+
+/// 错误详情
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiError {
+    /// 错误码（如 WIN_UNKNOWN_PRINTER_DRIVER / INSTALL_FAILED）
+    pub code: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<serde_json::Value>,
+}
+
+/// 命令统一返回结构
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiResponse<T> {
+    pub ok: bool,
+    pub data: Option<T>,
+    pub error: Option<ApiError>,
+}
+
+impl ApiError {
+    /// 由错误消息构造（default_code 在消息不含 [EASYPRINTER_CODE=...] 时使用）
+    pub fn from_message(default_code: &str, message: &str) -> Self {
+        let code = crate::platform::error_code::error_code(message).unwrap_or(default_code).to_string();
+        let details = step_value(message).map(|step| serde_json::json!({ "step": step }));
+        ApiError {
+            code,
+            message: message.to_string(),
+            details,
+        }
+    }
+}
+
+/// 取出消息中 "step=值" 的值（到下一个空格为止）
+fn step_value(text: &str) -> Option<&str> {
+    let rest = text.split("step=").nth(1)?;
+    let value = rest.split(' ').next().unwrap_or_default().trim();
+    Some(value).filter(|v| !v.is_empty())
+}
+
+/// 载荷中内嵌的失败（默认没有）
+pub trait ApiPayload {
+    fn embedded_error(&self) -> Option<ApiError> {
+        None
+    }
+}
+
+impl ApiPayload for String {}
+impl ApiPayload for crate::LoadConfigResult {}
+impl ApiPayload for crate::VersionCheckResult {}
+
+impl ApiPayload for crate::InstallResult {
+    fn embedded_error(&self) -> Option<ApiError> {
+        if self.success {
+            return None;
+        }
+        if let Some(errors) = &self.validation_errors {
+            return Some(ApiError {
+                code: "INVALID_INPUT".to_string(),
+                message: self.message.clone(),
+                details: serde_json::to_value(errors).ok(),
+            });
+        }
+        let mut error = ApiError::from_message("INSTALL_FAILED", self.stderr.as_deref().unwrap_or_default());
+        error.message = self.message.clone();
+        error.details = Some(serde_json::json!({ "jobId": self.job_id, "logPath": self.log_path }));
+        Some(error)
+    }
+}
+
+impl ApiPayload for crate::platform::DeletePrinterResult {
+    fn embedded_error(&self) -> Option<ApiError> {
+        (!self.success).then(|| ApiError {
+            code: "DELETE_FAILED".to_string(),
+            message: self.message.clone(),
+            details: self.evidence.clone().map(|evidence| serde_json::json!({ "evidence": evidence })),
+        })
+    }
+}
+
+impl<T: ApiPayload> ApiResponse<T> {
+    /// 把命令结果转换为统一结构
+    pub fn from_result(default_code: &str, result: Result<T, String>) -> Self {
+        match result {
+            Ok(data) => {
+                let error = data.embedded_error();
+                ApiResponse { ok: error.is_none(), data: Some(data), error }
+            }
+            Err(message) => ApiResponse {
+                ok: false,
+                data: None,
+                error: Some(ApiError::from_message(default_code, &message)),
+            },
+        }
+    }
+}
+
+/// 用统一结构包装已有命令：api_command!(新命令名 => 原命令(参数: 类型, ...) -> 载荷类型, "默认错误码");
+macro_rules! api_command {
+    ($name:ident => $($inner:ident)::+ ($($arg:ident: $ty:ty),* $(,)?) -> $ret:ty, $code:literal) => {
+        #[tauri::command]
+        #[allow(non_snake_case, clippy::too_many_arguments)]
+        pub async fn $name($($arg: $ty),*) -> ApiResponse<$ret> {
+            ApiResponse::from_result($code, $($inner)::+($($arg),*).await)
+        }
+    };
+}
+
+api_command!(api_load_config => crate::load_config(app: tauri::AppHandle) -> crate::LoadConfigResult, "CONFIG_LOAD_FAILED");
+
+api_command!(api_install_printer => crate::install_printer(
+    app: tauri::AppHandle,
+    name: String,
+    path: String,
+    driverKey: Option<String>,
+    driverPath: Option<String>,
+    model: Option<String>,
+    driverInstallPolicy: Option<String>,
+    installMode: Option<String>,
+    dryRun: Option<bool>,
+    queueConflict: Option<String>,
+) -> crate::InstallResult, "INSTALL_FAILED");
+
+api_command!(api_delete_printer => crate::delete_printer(
+    printer_name: String,
+    remove_port: Option<bool>,
+    remove_driver: Option<bool>,
+) -> crate::platform::DeletePrinterResult, "DELETE_FAILED");
+
+api_command!(api_print_test_page => crate::print_test_page(
+    app: tauri::AppHandle,
+    payload: crate::PrintTestPageRequest,
+) -> String, "TEST_PAGE_FAILED");
+
+api_command!(api_check_version_update => crate::check_version_update() -> crate::VersionCheckResult, "VERSION_CHECK_FAILED");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn derives_error_code_and_step_from_message() {
+        let error = ApiError::from_message("INSTALL_FAILED", "[EASYPRINTER_CODE=WIN_UNKNOWN_PRINTER_DRIVER] 驱动未知");
+        assert_eq!(error.code, "WIN_UNKNOWN_PRINTER_DRIVER");
+        assert!(error.details.is_none());
+
+        let error = ApiError::from_message("TEST_PAGE_FAILED", "[PrintTestPage] ERROR step=VALIDATE message=打印机名称不能为空");
+        assert_eq!(error.code, "TEST_PAGE_FAILED");
+        assert_eq!(error.details, Some(serde_json::json!({ "step": "VALIDATE" })));
+
+        let failed: ApiResponse<String> = ApiResponse::from_result("X", Err("网络错误".to_string()));
+        assert!(!failed.ok && failed.data.is_none());
+        assert_eq!(failed.error.map(|e| e.code).as_deref(), Some("X"));
+        assert!(ApiResponse::from_result("X", Ok("已发送".to_string())).ok);
+    }
+}
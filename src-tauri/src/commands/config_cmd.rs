@@ -13,3 +13,130 @@ pub fn get_cached_config(app: tauri::AppHandle) -> Result<CachedConfigResult, St
 pub async fn refresh_remote_config(app: tauri::AppHandle) -> Result<RefreshConfigResult, String> {
     crate::services::config_service::refresh_remote_config(&app).await
 }
+
+#[tauri::command]
+pub fn reveal_path(app: tauri::AppHandle, kind: String) -> Result<crate::services::fs_paths::RevealPathResult, String> {
+    crate::services::fs_paths::reveal_path(&app, &kind)
+}
+
+#[tauri::command]
+pub async fn get_effective_config() -> Result<crate::services::config_service::EffectiveConfigResult, String> {
+    crate::services::config_service::get_effective_config().await
+}
+
+#[tauri::command]
+pub fn get_interaction_mode() -> crate::services::interaction_mode::InteractionMode {
+    crate::services::interaction_mode::current()
+}
+
+#[tauri::command]
+pub fn get_pending_crash_report() -> Result<Option<crate::services::crash_report::CrashReport>, String> {
+    crate::services::crash_report::pending_report()
+}
+
+#[tauri::command]
+pub async fn resolve_crash_report(id: String, upload: bool) -> Result<crate::services::crash_report::CrashReportResolution, String> {
+    crate::services::crash_report::resolve_report(&id, upload).await
+}
+
+#[tauri::command]
+pub fn shutdown_app(app: tauri::AppHandle, cancel_jobs: Option<bool>) {
+    tauri::async_runtime::spawn(crate::services::shutdown_service::shutdown(app, cancel_jobs.unwrap_or(false)));
+}
+
+#[tauri::command]
+pub async fn get_unfinished_jobs() -> Result<Vec<crate::services::shutdown_service::UnfinishedJob>, String> {
+    crate::services::shutdown_service::get_unfinished_jobs().await
+}
+
+#[tauri::command]
+pub async fn resolve_unfinished_jobs(
+    app: tauri::AppHandle,
+    resume: bool,
+) -> Result<Vec<crate::services::shutdown_service::ResumedJob>, String> {
+    crate::services::shutdown_service::resolve_unfinished_jobs(&app, resume).await
+}
+
+#[tauri::command]
+pub async fn relaunch_elevated(app: tauri::AppHandle) -> Result<(), String> {
+    crate::services::kiosk::check_action("install")?;
+    crate::services::elevation_resume::relaunch_elevated(app).await
+}
+
+#[tauri::command]
+pub async fn get_elevation_resume_jobs() -> Result<Vec<crate::services::elevation_resume::ElevationResumeJob>, String> {
+    crate::services::elevation_resume::get_pending_jobs().await
+}
+
+#[tauri::command]
+pub async fn get_webview_recovery() -> Result<Option<crate::services::webview_recovery::RecoveryEvent>, String> {
+    crate::services::webview_recovery::get_webview_recovery().await
+}
+
+/// 端到端自检（界面不使用，供 CI 与现场工程师调用；命令行等价入口为 --self-test）
+#[tauri::command]
+pub async fn self_test(app: tauri::AppHandle) -> crate::services::self_test::SelfTestReport {
+    crate::services::self_test::run_self_test(&app).await
+}
+
+/// 清理本机残留（卸载前调用；命令行等价入口为 --cleanup-machine-artifacts）
+#[tauri::command]
+pub async fn cleanup_machine_artifacts(app: tauri::AppHandle) -> Result<crate::services::machine_cleanup::MachineCleanupReport, String> {
+    crate::services::kiosk::check_action("delete")?;
+    tokio::task::spawn_blocking(move || crate::services::machine_cleanup::cleanup_machine_artifacts(&app))
+        .await
+        .map_err(|e| format!("清理本机残留任务异常: {}", e))
+}
+
+#[tauri::command]
+pub fn get_settings() -> crate::services::user_settings::UserSettings {
+    crate::services::user_settings::current()
+}
+
+#[tauri::command]
+pub async fn update_settings(
+    app: tauri::AppHandle,
+    patch: serde_json::Value,
+) -> Result<crate::services::user_settings::SettingsUpdate, String> {
+    crate::services::kiosk::check_action("settings")?;
+    let update = tokio::task::spawn_blocking(move || crate::services::user_settings::update(patch))
+        .await
+        .map_err(|e| format!("保存设置任务异常: {}", e))??;
+    if !update.changed.is_empty() {
+        crate::services::events::emit_settings_changed(&app, &update);
+    }
+    Ok(update)
+}
+
+#[tauri::command]
+pub async fn export_app_state(
+    app: tauri::AppHandle,
+    output_path: String,
+) -> Result<crate::services::app_state::ExportAppStateResult, String> {
+    crate::services::kiosk::check_action("settings")?;
+    crate::services::app_state::export_app_state(&app, &output_path).await
+}
+
+#[tauri::command]
+pub async fn import_app_state(
+    app: tauri::AppHandle,
+    archive_path: String,
+    replay_installs: Option<bool>,
+) -> Result<crate::services::app_state::ImportAppStateResult, String> {
+    crate::services::kiosk::check_action("settings")?;
+    crate::services::app_state::import_app_state(&app, &archive_path, replay_installs.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn list_remote_config_versions() -> Result<crate::services::config_versions::RemoteConfigVersionList, String> {
+    crate::services::config_versions::list_remote_config_versions().await
+}
+
+#[tauri::command]
+pub async fn apply_remote_config_version(
+    app: tauri::AppHandle,
+    version: String,
+) -> Result<crate::services::config_versions::AppliedConfigVersion, String> {
+    crate::services::kiosk::check_action("settings")?;
+    crate::services::config_versions::apply_remote_config_version(&app, &version).await
+}
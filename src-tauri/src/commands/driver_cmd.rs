@@ -0,0 +1,104 @@
+/**
+ * 驱动维护命令处理（维护页使用）
+ */
+
+#[tauri::command]
+pub fn list_staged_printer_drivers() -> Result<Vec<crate::platform::StagedDriverInfo>, String> {
+    crate::services::driver_service::list_staged_printer_drivers()
+}
+
+#[tauri::command]
+pub fn remove_staged_driver(published_name: String) -> Result<crate::platform::RemoveStagedDriverResult, String> {
+    crate::services::kiosk::check_action("driverMaintenance")?;
+    eprintln!("[DriverStore][Command] ENTER cmd=remove_staged_driver published_name=\"{}\"", published_name);
+    let result = crate::services::driver_service::remove_staged_driver(&published_name)?;
+    eprintln!("[DriverStore][Command] EXIT cmd=remove_staged_driver success={}", result.success);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn check_driver_conflicts(printer_name: String) -> Result<crate::platform::DriverConflictCheckResult, String> {
+    eprintln!("[DriverConflict][Command] ENTER cmd=check_driver_conflicts printer=\"{}\"", printer_name);
+    let result = tokio::task::spawn_blocking(move || crate::services::driver_service::check_driver_conflicts(&printer_name))
+        .await
+        .map_err(|e| format!("驱动冲突检测任务异常: {}", e))??;
+    eprintln!("[DriverConflict][Command] EXIT cmd=check_driver_conflicts status={}", result.status);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn build_driver_pack(source_dir: String, output_zip: String) -> Result<crate::services::driver_service::DriverPackResult, String> {
+    crate::services::kiosk::check_action("driverMaintenance")?;
+    eprintln!("[DriverPack][Command] ENTER cmd=build_driver_pack source_dir=\"{}\" output_zip=\"{}\"", source_dir, output_zip);
+    let result = tokio::task::spawn_blocking(move || crate::services::driver_service::build_driver_pack(&source_dir, &output_zip))
+        .await
+        .map_err(|e| format!("驱动包制作任务异常: {}", e))??;
+    eprintln!("[DriverPack][Command] EXIT cmd=build_driver_pack sha256={}", result.sha256);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn build_driver_patch(base_zip: String, new_zip: String, output_patch: String) -> Result<crate::services::driver_service::DriverPatchResult, String> {
+    crate::services::kiosk::check_action("driverMaintenance")?;
+    eprintln!("[DriverPack][Command] ENTER cmd=build_driver_patch base_zip=\"{}\" new_zip=\"{}\" output_patch=\"{}\"", base_zip, new_zip, output_patch);
+    let result = tokio::task::spawn_blocking(move || crate::services::driver_service::build_driver_patch(&base_zip, &new_zip, &output_patch))
+        .await
+        .map_err(|e| format!("增量补丁制作任务异常: {}", e))??;
+    eprintln!("[DriverPack][Command] EXIT cmd=build_driver_patch sha256={} size={}", result.sha256, result.size_bytes);
+    Ok(result)
+}
+
+#[tauri::command]
+pub async fn inspect_driver_pack(zip_path_or_url: String) -> Result<crate::services::driver_service::DriverPackInspection, String> {
+    eprintln!("[DriverPack][Command] ENTER cmd=inspect_driver_pack source=\"{}\"", zip_path_or_url);
+    let result = crate::services::driver_service::inspect_driver_pack(&zip_path_or_url).await?;
+    eprintln!("[DriverPack][Command] EXIT cmd=inspect_driver_pack infs={} warnings={}", result.infs.len(), result.warnings.len());
+    Ok(result)
+}
+
+#[tauri::command]
+pub fn set_metered_download_allowed(allowed: bool) -> Result<(), String> {
+    crate::services::driver_service::set_metered_download_allowed(allowed)
+}
+
+#[tauri::command]
+pub fn set_driver_archive_password(reference: String, password: String) -> Result<(), String> {
+    crate::services::kiosk::check_action("credentials")?;
+    crate::services::driver_service::set_driver_archive_password(&reference, &password)
+}
+
+#[tauri::command]
+pub fn clear_driver_archive_password(reference: String) -> Result<bool, String> {
+    crate::services::kiosk::check_action("credentials")?;
+    crate::services::driver_service::clear_driver_archive_password(&reference)
+}
+
+#[tauri::command]
+pub async fn gc_driver_cache(remove_unreferenced: Option<bool>) -> Result<crate::services::driver_cache::DriverCacheGcReport, String> {
+    let remove_unreferenced = remove_unreferenced.unwrap_or(false);
+    if remove_unreferenced {
+        crate::services::kiosk::check_action("driverMaintenance")?;
+    }
+    eprintln!("[DriverCache][Command] ENTER cmd=gc_driver_cache remove_unreferenced={}", remove_unreferenced);
+    let report = crate::services::driver_cache::gc_driver_cache(remove_unreferenced).await?;
+    eprintln!("[DriverCache][Command] EXIT cmd=gc_driver_cache reclaimed_bytes={}", report.reclaimed_bytes);
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn switch_driver_channel(
+    app: tauri::AppHandle,
+    printer: String,
+    channel: String,
+) -> Result<crate::services::driver_channel::DriverChannelSwitchResult, String> {
+    crate::services::kiosk::check_config_printer(&printer)?;
+    eprintln!("[DriverChannel][Command] ENTER cmd=switch_driver_channel printer=\"{}\" channel=\"{}\"", printer, channel);
+    let result = crate::services::driver_channel::switch_driver_channel(app, &printer, &channel).await?;
+    eprintln!(
+        "[DriverChannel][Command] EXIT cmd=switch_driver_channel channel={} driverKey=\"{}\" success={}",
+        result.channel.as_str(),
+        result.driver_key,
+        result.install.success
+    );
+    Ok(result)
+}
@@ -3,5 +3,7 @@
  * 集中管理所有 Tauri 命令的注册
  */
 
+pub mod api_cmd;
 pub mod config_cmd;
+pub mod driver_cmd;
 pub mod printer_cmd;
@@ -3,11 +3,257 @@
  */
 
 #[tauri::command]
-pub fn list_printers() -> Result<Vec<crate::platform::PrinterDetectEntry>, String> {
-    crate::services::printer_service::list_printers()
+pub async fn list_printers() -> Result<Vec<crate::platform::PrinterDetectEntry>, String> {
+    crate::services::printer_service::list_printers().await
 }
 
 #[tauri::command]
-pub fn list_printers_detailed() -> Result<Vec<crate::platform::DetailedPrinterInfo>, String> {
-    crate::services::printer_service::list_printers_detailed()
+pub async fn list_printers_detailed() -> Result<Vec<crate::platform::DetailedPrinterInfo>, String> {
+    crate::services::printer_service::list_printers_detailed().await
+}
+
+#[tauri::command]
+pub fn get_job_log(job_id: String) -> Result<String, String> {
+    crate::services::printer_service::get_job_log(&job_id)
+}
+
+#[tauri::command]
+pub fn get_job_command_trace(job_id: String) -> Result<Vec<crate::utils::command_trace::CommandTraceEntry>, String> {
+    crate::services::printer_service::get_job_command_trace(&job_id)
+}
+
+#[tauri::command]
+pub async fn export_job_diagnostics(
+    job_id: String,
+    output_path: String,
+) -> Result<crate::services::printer_service::JobDiagnosticsExport, String> {
+    crate::services::kiosk::check_action("diagnostics")?;
+    crate::services::printer_service::export_job_diagnostics(&job_id, &output_path).await
+}
+
+#[tauri::command]
+pub fn cancel_install_job(job_id: String) -> Result<bool, String> {
+    eprintln!("[InstallJob][Command] cmd=cancel_install_job job_id=\"{}\"", job_id);
+    crate::services::printer_service::cancel_install_job(&job_id)
+}
+
+#[tauri::command]
+pub async fn get_install_history(limit: Option<usize>) -> Result<Vec<crate::services::install_history::InstallRecord>, String> {
+    crate::services::printer_service::get_install_history(limit).await
+}
+
+#[tauri::command]
+pub async fn get_metrics_summary(period: Option<String>) -> Result<crate::services::metrics_service::MetricsSummary, String> {
+    crate::services::metrics_service::get_metrics_summary(period).await
+}
+
+#[tauri::command]
+pub async fn get_install_estimate(
+    printer_name: String,
+    driver_key: Option<String>,
+) -> Result<crate::services::install_estimate::InstallEstimate, String> {
+    crate::services::install_estimate::estimate_for_printer(&printer_name, driver_key).await
+}
+
+#[tauri::command]
+pub async fn flush_feedback() -> Result<crate::services::feedback_service::FeedbackFlushResult, String> {
+    crate::services::feedback_service::flush_feedback().await
+}
+
+#[tauri::command]
+pub async fn reconcile_printers() -> Result<crate::services::printer_watch::ReconcileReport, String> {
+    crate::services::printer_watch::reconcile_printers().await
+}
+
+#[tauri::command]
+pub async fn get_printer_watch_history(limit: Option<usize>) -> Result<Vec<crate::services::install_history::WatchActionRecord>, String> {
+    crate::services::install_history::list_watch_actions(limit).await
+}
+
+#[tauri::command]
+pub async fn get_test_page_history(
+    printer: String,
+    limit: Option<usize>,
+) -> Result<Vec<crate::services::install_history::TestPageRecord>, String> {
+    crate::services::printer_service::get_test_page_history(&printer, limit).await
+}
+
+#[tauri::command]
+pub fn pause_printer(name: String) -> Result<crate::platform::QueueControlResult, String> {
+    crate::services::kiosk::check_action("queueControl")?;
+    crate::services::printer_service::set_printer_paused(&name, true)
+}
+
+#[tauri::command]
+pub fn resume_printer(name: String) -> Result<crate::platform::QueueControlResult, String> {
+    crate::services::kiosk::check_action("queueControl")?;
+    crate::services::printer_service::set_printer_paused(&name, false)
+}
+
+#[tauri::command]
+pub fn open_print_queue(name: String) -> Result<crate::platform::OpenQueueResult, String> {
+    crate::services::printer_service::open_print_queue(&name)
+}
+
+#[tauri::command]
+pub async fn print_file(
+    printer: String,
+    path: String,
+    options: Option<crate::services::printer_service::PrintFileOptions>,
+) -> Result<crate::platform::PrintFileResult, String> {
+    crate::services::kiosk::check_action("printFile")?;
+    crate::services::printer_service::print_file(&printer, &path, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+pub async fn check_printer_connectivity(
+    name: String,
+    path: String,
+    trace_route: Option<bool>,
+) -> Result<crate::services::host_resolution::ConnectivityCheckResult, String> {
+    crate::services::printer_service::check_printer_connectivity(&name, &path, trace_route.unwrap_or(false)).await
+}
+
+#[tauri::command]
+pub async fn uninstall_all_managed_printers(
+    remove_ports: Option<bool>,
+    remove_drivers: Option<bool>,
+) -> Result<crate::services::cleanup_service::UninstallAllReport, String> {
+    crate::services::kiosk::check_action("delete")?;
+    let remove_ports = remove_ports.unwrap_or(false);
+    let remove_drivers = remove_drivers.unwrap_or(false);
+    eprintln!(
+        "[UninstallAll][Command] ENTER cmd=uninstall_all_managed_printers remove_ports={} remove_drivers={}",
+        remove_ports, remove_drivers
+    );
+    let report = crate::services::cleanup_service::uninstall_all_managed_printers(remove_ports, remove_drivers).await?;
+    eprintln!(
+        "[UninstallAll][Command] EXIT cmd=uninstall_all_managed_printers removed={} failed={}",
+        report.removed, report.failed
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn delete_printers_by_area(
+    area: String,
+    remove_ports: Option<bool>,
+    remove_drivers: Option<bool>,
+) -> Result<crate::services::cleanup_service::AreaDeleteReport, String> {
+    crate::services::kiosk::check_action("delete")?;
+    let remove_ports = remove_ports.unwrap_or(false);
+    let remove_drivers = remove_drivers.unwrap_or(false);
+    eprintln!(
+        "[DeleteByArea][Command] ENTER cmd=delete_printers_by_area area=\"{}\" remove_ports={} remove_drivers={}",
+        area, remove_ports, remove_drivers
+    );
+    let report = crate::services::cleanup_service::delete_printers_by_area(&area, remove_ports, remove_drivers).await?;
+    eprintln!(
+        "[DeleteByArea][Command] EXIT cmd=delete_printers_by_area removed={} failed={}",
+        report.removed, report.failed
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn render_test_page_preview(printer: String) -> Result<crate::platform::test_page_content::TestPagePreview, String> {
+    crate::services::printer_service::render_test_page_preview(&printer)
+}
+
+#[tauri::command]
+pub async fn get_printer_catalog(
+    area: Option<String>,
+    page: Option<usize>,
+    page_size: Option<usize>,
+    sort: Option<String>,
+) -> Result<crate::services::catalog_service::PrinterCatalogPage, String> {
+    let query = crate::services::catalog_service::PrinterCatalogQuery { area, page, page_size, sort };
+    crate::services::catalog_service::get_printer_catalog(query).await
+}
+
+#[tauri::command]
+pub fn set_printer_credentials(printer_name: String, username: String, password: String) -> Result<String, String> {
+    crate::services::kiosk::check_action("credentials")?;
+    crate::services::printer_service::set_printer_credentials(&printer_name, &username, &password)
+}
+
+#[tauri::command]
+pub fn clear_printer_credentials(printer_name: String) -> Result<bool, String> {
+    crate::services::kiosk::check_action("credentials")?;
+    crate::services::printer_service::clear_printer_credentials(&printer_name)
+}
+
+#[tauri::command]
+pub async fn dump_print_environment(output_path: Option<String>) -> Result<crate::platform::PrintEnvironmentReport, String> {
+    eprintln!("[PrintEnvironment][Command] ENTER cmd=dump_print_environment output_path={:?}", output_path);
+    let report = crate::services::printer_service::dump_print_environment(output_path).await?;
+    eprintln!(
+        "[PrintEnvironment][Command] EXIT cmd=dump_print_environment queues={} drivers={} ports={}",
+        report.queues.len(),
+        report.drivers.len(),
+        report.ports.len()
+    );
+    Ok(report)
+}
+
+#[tauri::command]
+pub async fn probe_permissions() -> Result<crate::platform::PermissionReport, String> {
+    crate::services::printer_service::probe_permissions().await
+}
+
+#[tauri::command]
+pub async fn get_port_config(port: String) -> Result<crate::platform::PortConfig, String> {
+    crate::services::printer_service::get_port_config(&port).await
+}
+
+#[tauri::command]
+pub async fn set_port_config(
+    port: String,
+    options: crate::platform::PortConfigUpdate,
+) -> Result<crate::platform::PortConfigChange, String> {
+    crate::services::kiosk::check_action("portConfig")?;
+    eprintln!("[PortConfig][Command] ENTER cmd=set_port_config port=\"{}\"", port);
+    let change = crate::services::printer_service::set_port_config(&port, options).await?;
+    eprintln!(
+        "[PortConfig][Command] EXIT cmd=set_port_config port=\"{}\" method={} restart_required={}",
+        change.port.port_name, change.method, change.requires_spooler_restart
+    );
+    Ok(change)
+}
+
+#[tauri::command]
+pub async fn update_printer_port(printer_name: String) -> Result<crate::platform::PortRetargetResult, String> {
+    crate::services::kiosk::check_action("portConfig")?;
+    eprintln!("[PortConfig][Command] ENTER cmd=update_printer_port printer=\"{}\"", printer_name);
+    crate::services::printer_service::update_printer_port(&printer_name).await
+}
+
+#[tauri::command]
+pub async fn get_printer_image(key: String) -> Result<crate::services::printer_image::PrinterImage, String> {
+    crate::services::printer_image::get_printer_image(&key).await
+}
+
+#[tauri::command]
+pub async fn get_printer_permissions(printer_name: String) -> Result<crate::services::printer_permissions::PrinterPermissionsReport, String> {
+    crate::services::printer_permissions::get_printer_permissions(printer_name).await
+}
+
+#[tauri::command]
+pub async fn preview_printer_permissions(
+    permissions: crate::PrinterPermissionsConfig,
+) -> Result<crate::services::printer_permissions::PermissionsPreview, String> {
+    crate::services::printer_permissions::preview_printer_permissions(permissions).await
+}
+
+#[tauri::command]
+pub async fn get_printer_supplies(
+    ip: String,
+    community: Option<String>,
+) -> Result<crate::services::snmp_service::PrinterSuppliesReport, String> {
+    crate::services::snmp_service::get_printer_supplies(&ip, community.as_deref()).await
+}
+
+#[tauri::command]
+pub fn list_printer_operation_locks() -> Vec<crate::services::printer_locks::PrinterLockHolder> {
+    crate::services::printer_locks::held_locks()
 }
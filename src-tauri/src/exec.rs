@@ -18,17 +18,31 @@ pub struct ExecOutput {
 /// 执行外部命令（隐藏窗口，收集输出）
 #[cfg(windows)]
 pub fn run_hidden(program: &str, args: &[&str]) -> Result<ExecOutput, String> {
-    let output = Command::new(program)
+    use crate::platform::windows::job_log;
+
+    let command_line = crate::utils::command_trace::format_command_line(program, args);
+    let started = std::time::Instant::now();
+    let child = crate::platform::windows::process_watchdog::mark(&mut Command::new(program))
         .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .creation_flags(CREATE_NO_WINDOW)
-        .output()
+        .spawn()
+        .map_err(|e| {
+            job_log::append_command_trace(&command_line, None, started, Some("spawn_failed"));
+            format!("执行命令失败: program={}, args={:?}, error={}", program, args, e)
+        })?;
+    let _tracked = crate::platform::windows::process_watchdog::track(&child, program);
+    let output = child
+        .wait_with_output()
         .map_err(|e| format!("执行命令失败: program={}, args={:?}, error={}", program, args, e))?;
     
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    job_log::append_command_trace(&command_line, output.status.code(), started, None);
+    job_log::append_process_output(&command_line, &output);
+    
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
     let status_code = output.status.code();
     
     Ok(ExecOutput {
@@ -58,11 +72,13 @@ pub fn run_hidden_with_timeout(_program: &str, _args: &[&str], _timeout_secs: u6
 }
 
 /// 启动外部命令（隐藏窗口，返回 Child 句柄，用于异步等待）
+///
+/// 子进程带看门狗环境标记；调用方需用 process_watchdog::track 登记返回的 Child
 #[cfg(windows)]
 pub fn spawn_hidden_piped(program: &str, args: &[&str]) -> Result<std::process::Child, String> {
     use std::process::Stdio;
     
-    Command::new(program)
+    crate::platform::windows::process_watchdog::mark(&mut Command::new(program))
         .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
@@ -9,6 +9,8 @@ const ALLOWED_STEP_IDS: &[&str] = &[
     "job.init",
     "job.done",
     "job.failed",
+    "job.retry",
+    "job.ippFallback",
     "device.probe",
     "driver.download",
     "driver.verify",
@@ -24,6 +26,24 @@ const ALLOWED_STATES: &[&str] = &["pending", "running", "success", "failed", "sk
 
 static INSTALL_MODE_REGISTRY: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
 
+/// 每个安装任务的步骤计时：进行中步骤的开始时间 + 已结束步骤的耗时 + 出错处登记的失败步骤（供安装历史记录）
+static STEP_TIMINGS: OnceLock<Mutex<HashMap<String, JobStepTimings>>> = OnceLock::new();
+
+/// 下一次安装的预计耗时（key = 打印机名称，job.init 事件发送时取出写入 meta.estimate）
+static INSTALL_ESTIMATES: OnceLock<Mutex<HashMap<String, Value>>> = OnceLock::new();
+
+/// 超过该时长（毫秒）未更新的步骤计时视为任务已提前结束、不会再写入安装历史，下一个任务开始时清除
+const STALE_STEP_TIMINGS_MS: i64 = 60 * 60 * 1000;
+
+#[derive(Default)]
+struct JobStepTimings {
+    running_since: HashMap<String, i64>,
+    durations: Vec<(String, i64)>,
+    failed_step: Option<String>,
+    /// 最后一次更新的时间（毫秒时间戳）
+    touched_at: i64,
+}
+
 fn now_ts_ms() -> i64 {
     std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -58,6 +78,100 @@ fn resolve_install_mode(job_id: &str, explicit: Option<String>) -> String {
     "auto".to_string()
 }
 
+/// 记录步骤耗时（running 记开始时间，success/failed 时结算；job.* 步骤不计时）
+fn track_step_timing(event: &InstallProgressEvent) {
+    if event.job_id.trim().is_empty() || event.step_id.starts_with("job.") {
+        return;
+    }
+    let timings = STEP_TIMINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    let Ok(mut map) = timings.lock() else {
+        return;
+    };
+    let job = map.entry(event.job_id.clone()).or_default();
+    job.touched_at = now_ts_ms();
+    match event.state.as_str() {
+        "running" => {
+            job.running_since.entry(event.step_id.clone()).or_insert(event.ts_ms);
+        }
+        "success" | "failed" => {
+            if let Some(started) = job.running_since.remove(&event.step_id) {
+                job.durations.push((event.step_id.clone(), (event.ts_ms - started).max(0)));
+            }
+        }
+        _ => {}
+    }
+}
+
+/// 登记安装任务失败的步骤（在出错处调用；同一任务以最后一次登记为准）
+///
+/// # 参数
+/// - `job_id`: 安装任务 ID
+/// - `step_id`: 失败的步骤 ID（如 "driver.stageDriver"）
+pub fn record_failed_step(job_id: &str, step_id: &str) {
+    if job_id.trim().is_empty() {
+        return;
+    }
+    let timings = STEP_TIMINGS.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut map) = timings.lock() {
+        let job = map.entry(job_id.to_string()).or_default();
+        job.failed_step = Some(step_id.to_string());
+        job.touched_at = now_ts_ms();
+    }
+}
+
+/// 清除提前结束的任务遗留的步骤计时（安装参数错误、平台层返回 Err 等未写入安装历史的任务）
+fn prune_stale_step_timings(now: i64) {
+    let Some(Ok(mut map)) = STEP_TIMINGS.get().map(|timings| timings.lock()) else {
+        return;
+    };
+    map.retain(|_, job| now - job.touched_at < STALE_STEP_TIMINGS_MS);
+}
+
+/// 取出并清除某个安装任务的步骤计时
+///
+/// # 返回
+/// - 已结算的步骤耗时（步骤 ID, 毫秒，按结束顺序）与登记的失败步骤（未登记时为 None）
+pub fn take_step_timings(job_id: &str) -> (Vec<(String, i64)>, Option<String>) {
+    STEP_TIMINGS
+        .get()
+        .and_then(|timings| timings.lock().ok())
+        .and_then(|mut map| map.remove(job_id))
+        .map(|job| (job.durations, job.failed_step))
+        .unwrap_or_default()
+}
+
+/// 登记打印机下一次安装的预计耗时（该打印机的下一个 job.init 事件发送时写入 meta.estimate）
+pub fn register_install_estimate(printer_name: &str, estimate: Value) {
+    let estimates = INSTALL_ESTIMATES.get_or_init(|| Mutex::new(HashMap::new()));
+    if let Ok(mut map) = estimates.lock() {
+        map.insert(printer_name.to_string(), estimate);
+    }
+}
+
+/// 清除打印机已登记但未被 job.init 取走的预计耗时（安装在 job.init 之前返回时调用）
+pub fn clear_install_estimate(printer_name: &str) {
+    if let Some(mut map) = INSTALL_ESTIMATES.get().and_then(|estimates| estimates.lock().ok()) {
+        map.remove(printer_name);
+    }
+}
+
+/// 把已登记的预计耗时写入 job.init 事件的 meta（取出后清除）
+fn attach_install_estimate(event: &mut InstallProgressEvent) {
+    let Some(estimate) = INSTALL_ESTIMATES
+        .get()
+        .and_then(|estimates| estimates.lock().ok())
+        .and_then(|mut map| map.remove(&event.printer_name))
+    else {
+        return;
+    };
+    match event.meta.get_or_insert_with(|| Value::Object(Default::default())) {
+        Value::Object(meta) => {
+            meta.insert("estimate".to_string(), estimate);
+        }
+        _ => eprintln!("[ProgressEmit] job.init meta is not an object, estimate skipped jobId={}", event.job_id),
+    }
+}
+
 fn validate_step_id(step_id: &str) -> Result<(), String> {
     if ALLOWED_STEP_IDS.contains(&step_id) {
         Ok(())
@@ -85,8 +199,11 @@ pub fn emit_install_progress(app: &AppHandle, mut event: InstallProgressEvent) -
     event.install_mode = Some(resolved_install_mode.clone());
 
     if event.step_id == "job.init" {
+        prune_stale_step_timings(now_ts_ms());
         register_install_mode(&event.job_id, &resolved_install_mode);
+        attach_install_estimate(&mut event);
     }
+    track_step_timing(&event);
 
     app.emit_all("install_progress", &event)
         .map_err(|e| format!("emit install_progress failed: {}", e))
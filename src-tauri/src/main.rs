@@ -55,102 +55,7 @@ const BRAND_KEYWORDS: &[(&str, &[&str])] = &[
     ("BROTHER", &["BROTHER", "兄弟"]),
 ];
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct PrinterConfig {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    version: Option<String>, // 配置文件版本号（可选，兼容旧版本）
-    #[serde(rename = "driverCatalog", skip_serializing_if = "Option::is_none")]
-    pub driver_catalog: Option<std::collections::HashMap<String, DriverCatalogEntry>>,
-    pub cities: Vec<City>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct City {
-    #[serde(rename = "cityId")]
-    pub city_id: String,
-    #[serde(rename = "cityName")]
-    pub city_name: String,
-    pub areas: Vec<Area>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Area {
-    #[serde(rename = "areaName")]
-    pub area_name: String,
-    pub printers: Vec<Printer>,
-}
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Printer {
-    pub name: String,
-    pub path: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub model: Option<String>, // 打印机型号（可选）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub driver_path: Option<String>, // 驱动路径（可选，相对于应用目录）
-    #[serde(default)]
-    pub driver_names: Option<Vec<String>>, // 驱动名称列表（可选，用于 Windows 安装校验）
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub install_mode: Option<String>, // 安装方式（可选）："auto" | "package" | "installer" | "ipp" | "legacy_inf"
-    #[serde(rename = "driverKey", skip_serializing_if = "Option::is_none")]
-    pub driver_key: Option<String>, // 驱动目录键（可选，用于引用 driverCatalog）
-}
-
-/// 驱动目录条目
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DriverCatalogEntry {
-    #[serde(rename = "installMode", skip_serializing_if = "Option::is_none")]
-    pub install_mode: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub local: Option<DriverLocalSpec>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub remote: Option<DriverRemoteSpec>, // M1 只解析不使用
-}
-
-/// 本地驱动规格
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DriverLocalSpec {
-    #[serde(rename = "infRel", skip_serializing_if = "Option::is_none")]
-    pub inf_rel: Option<String>, // INF 文件相对路径（相对于 driversRoot）
-    #[serde(rename = "driverNames", skip_serializing_if = "Option::is_none")]
-    pub driver_names: Option<Vec<String>>, // 驱动名称列表
-}
-
-/// 远程驱动规格（M1 只解析不使用）
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct DriverRemoteSpec {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub url: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub sha256: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub version: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub layout: Option<String>,
-}
-
-/// 远程驱动解析结果（M2.5/M3 使用）
-#[derive(Debug, Clone)]
-pub struct RemoteDriverResolved {
-    pub url: String,
-    pub sha256: String,
-    pub version: Option<String>,
-    pub layout: Option<String>,
-    pub driver_key: String,
-}
-
-/// 有效驱动规格（推导结果）
-#[derive(Debug, Clone)]
-pub struct EffectiveDriverSpec {
-    pub source: String, // "catalog" 或 "legacy"
-    pub effective_install_mode: Option<String>,
-    pub effective_driver_path: Option<String>, // 相对路径（以 driversRoot 为基准）
-    pub effective_driver_names: Vec<String>,
-    pub driver_key_used: Option<String>,
-    /// 远程驱动信息（M2.5：仅解析，M3 使用）
-    /// 只有当 source=catalog 且 catalog_entry.remote.url+sha256 同时存在时才为 Some
-    pub remote_driver: Option<RemoteDriverResolved>,
-}
+pub use eprinty_core::config::*;
 
 /// 系统信息响应
 #[derive(Debug, Serialize, Deserialize)]
@@ -173,6 +78,10 @@ pub struct SystemInfo {
     pub ubr: Option<u32>, // 7171 (Update Build Revision)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub os_display: Option<String>, // "Windows 11 24H2 (Build 26100.7171)"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<services::integrity_service::IntegrityReport>, // 最近一次完整性自检结果
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub terminal_server: Option<platform::TerminalServerStatus>, // 远程桌面（RDS）环境（仅 Windows）
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -188,6 +97,62 @@ struct InstallResult {
     /// 安装任务 ID（与后端发出的进度事件中的 jobId 一致）
     #[serde(rename = "jobId")]
     job_id: String,
+    /// 本次任务的详细日志文件路径（可通过 get_job_log 读取）
+    #[serde(rename = "logPath", skip_serializing_if = "Option::is_none")]
+    log_path: Option<String>,
+    /// 安装参数校验失败时的字段级错误（此时未创建安装任务，jobId 为空）
+    #[serde(rename = "validationErrors", skip_serializing_if = "Option::is_none")]
+    validation_errors: Option<Vec<platform::install_input::InputFieldError>>,
+    /// 安装尝试记录（autoRetry 换策略重试时包含多条，仅 Windows）
+    #[serde(rename = "attempts", skip_serializing_if = "Option::is_none")]
+    attempts: Option<Vec<platform::InstallAttempt>>,
+    /// 安装后校验是否全部通过（未执行校验时为 false，仅 Windows 执行校验）
+    #[serde(rename = "verified")]
+    verified: bool,
+    /// 安装后校验详情（队列 / 端口 / 驱动 / 探测）
+    #[serde(rename = "verification", skip_serializing_if = "Option::is_none")]
+    verification: Option<platform::InstallVerification>,
+}
+
+impl InstallResult {
+    /// 安装参数校验失败的结果（未启动任何外部进程）
+    fn validation_failed(errors: Vec<platform::install_input::InputFieldError>, dry_run: bool) -> Self {
+        let message = errors.iter().map(|e| e.message.as_str()).collect::<Vec<_>>().join("\n");
+        eprintln!("[InstallRequest] validation failed: {}", message.replace('\n', "; "));
+        InstallResult {
+            success: false,
+            message,
+            method: Some("Validation".to_string()),
+            stdout: None,
+            stderr: None,
+            effective_dry_run: dry_run,
+            job_id: String::new(),
+            log_path: None,
+            validation_errors: Some(errors),
+            attempts: None,
+            verified: false,
+            verification: None,
+        }
+    }
+
+    /// 队列名冲突按 queueConflict 不安装的结果（错误信息已带 QUEUE_CONFLICT 错误码）
+    fn queue_conflict(message: String, dry_run: bool) -> Self {
+        eprintln!("[InstallRequest] queue conflict: {}", message.replace('\n', "; "));
+        InstallResult {
+            success: false,
+            message,
+            method: Some("QueueConflict".to_string()),
+            stdout: None,
+            stderr: None,
+            effective_dry_run: dry_run,
+            job_id: String::new(),
+            log_path: None,
+            validation_errors: None,
+            attempts: None,
+            verified: false,
+            verification: None,
+        }
+    }
 }
 
 // ============================================================================
@@ -200,7 +165,7 @@ struct InstallResult {
 pub struct ProgressPayload {
     pub current: Option<u64>,
     pub total: Option<u64>,
-    pub unit: Option<String>, // "bytes" | "files" | "percent"
+    pub unit: Option<String>, // "bytes" | "files" | "percent" | "attempts"
     pub percent: Option<f64>,
 }
 
@@ -272,6 +237,8 @@ struct LoadConfigResult {
     remote_config: Option<PrinterConfig>, // 远程配置（如果有更新）
     local_version: Option<String>, // 本地版本号
     remote_version: Option<String>, // 远程版本号
+    update_plan: Option<services::config_update_plan::ConfigUpdatePlan>, // 确认更新后的逐台打印机行动计划（仅 confirm_update_config）
+    remote_source: Option<services::config_sources::ConfigSourceReport>, // 实际提供远程配置的来源（含此前失败的来源）
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -326,6 +293,10 @@ pub struct VersionConfig {
     pub download_size: Option<String>,
     pub checksum: Option<Checksum>,
     pub printer_config: Option<PrinterConfigInfo>,
+    #[serde(default)]
+    pub rollout_percentage: Option<u8>, // 灰度比例 0-100（未设置表示全量）
+    #[serde(default)]
+    pub target_groups: Option<Vec<String>>, // 仅向这些分组推送（未设置或为空表示不限分组）
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -359,13 +330,20 @@ pub struct VersionCheckResult {
     pub force_update: bool,
     pub changelog: Option<Vec<ChangelogEntry>>,
     pub download_size: Option<String>,
+    pub update_available: bool, // 存在更高版本（不论是否已向本机推送）
+    pub rollout_status: String, // "up_to_date" | "offered" | "not_offered"（有新版本但本机不在灰度范围内）
+    pub rollout_percentage: Option<u8>,
+    pub rollout_bucket: Option<u32>, // 本机分桶（0-99，仅按比例灰度时计算）
 }
 
 /// 【强校验】验证配置的完整性和一致性
 /// 检查项：
 /// 1. driverCatalog 必须存在
-/// 2. 每个 printer 的 driverKey 必须存在且在 catalog 中有对应条目
+/// 2. 每个 printer 的 driverKey 必须存在且在 catalog 中有对应条目（betaDriverKey 可选，存在时同样须在 catalog 中）
 /// 3. printer 节点中不允许出现 driver_path/driver_names/install_mode 等字段（已迁移至 driverCatalog）
+/// 4. 区域 / 打印机级安装选项取值合法
+/// 5. fleetReporting 开启时 endpoint 必须是 http/https 地址
+/// 6. driverCatalog 条目的 compatibility 范围合法
 pub fn validate_printer_config_v2(config: &PrinterConfig) -> Result<(), String> {
     // 1. 检查 driverCatalog 存在
     let catalog = config.driver_catalog.as_ref()
@@ -394,6 +372,18 @@ pub fn validate_printer_config_v2(config: &PrinterConfig) -> Result<(), String>
                         catalog.keys().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(", ")
                     ));
                 }
+
+                // 测试版驱动同样必须引用 catalog 中的条目
+                if let Some(beta_key) = &printer.beta_driver_key {
+                    if !catalog.contains_key(beta_key) {
+                        return Err(format!(
+                            "【配置校验失败】打印机 '{}' 引用的 betaDriverKey='{}' 在 driverCatalog 中不存在。\n\n已定义的 driverKey：[{}]",
+                            printer.name,
+                            beta_key,
+                            catalog.keys().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(", ")
+                        ));
+                    }
+                }
             }
         }
     }
@@ -426,6 +416,155 @@ pub fn validate_printer_config_v2(config: &PrinterConfig) -> Result<(), String>
         }
     }
     
+    // 4. 检查区域 / 打印机级安装选项取值
+    for city in &config.cities {
+        for area in &city.areas {
+            validate_install_options(&area.defaults, &format!("区域 '{}'", area.area_name))?;
+            for printer in &area.printers {
+                validate_install_options(&printer.options, &format!("打印机 '{}'", printer.name))?;
+            }
+        }
+    }
+    
+    // 5. 检查区域层级（parent 必须指向同城市内的其他区域，且不能成环）
+    for city in &config.cities {
+        let problems = city.area_hierarchy_problems();
+        if !problems.is_empty() {
+            return Err(format!(
+                "【配置校验失败】城市 '{}' 的区域层级无效：{}",
+                city.city_name,
+                problems.join("；")
+            ));
+        }
+    }
+    
+    // 6. 检查 fleetReporting 上报地址
+    if let Some(fleet) = config.fleet_reporting.as_ref().filter(|f| f.enabled) {
+        let valid = url::Url::parse(fleet.endpoint.trim())
+            .map(|u| u.scheme() == "http" || u.scheme() == "https")
+            .unwrap_or(false);
+        if !valid {
+            return Err(format!(
+                "【配置校验失败】fleetReporting.endpoint=\"{}\" 无效。开启上报时必须填写 http/https 地址",
+                fleet.endpoint
+            ));
+        }
+    }
+    
+    // 7. 检查 crashReporting 上传地址
+    if let Some(crash) = config.crash_reporting.as_ref().filter(|c| !c.endpoint.trim().is_empty()) {
+        let valid = url::Url::parse(crash.endpoint.trim())
+            .map(|u| u.scheme() == "http" || u.scheme() == "https")
+            .unwrap_or(false);
+        if !valid {
+            return Err(format!(
+                "【配置校验失败】crashReporting.endpoint=\"{}\" 无效。必须为 http/https 地址",
+                crash.endpoint
+            ));
+        }
+    }
+    
+    // 8. 检查 printerWatch 巡检模式
+    if let Some(mode) = config.printer_watch.as_ref().and_then(|w| w.mode.as_deref()) {
+        if !VALID_PRINTER_WATCH_MODES.contains(&mode) {
+            return Err(format!(
+                "【配置校验失败】printerWatch.mode=\"{}\" 无效。允许值：{}",
+                mode,
+                VALID_PRINTER_WATCH_MODES.join(" | ")
+            ));
+        }
+    }
+    
+    // 9. 检查 nameTemplate 队列命名模板
+    if let Some(template) = config.name_template.as_deref() {
+        services::printer_naming::validate_template(template).map_err(|e| format!("【配置校验失败】{}", e))?;
+    }
+    
+    // 10. 检查 updatePolicy 下载主机白名单
+    if let Some(policy) = config.update_policy.as_ref() {
+        for host in &policy.allowed_hosts {
+            services::update_download::validate_allowed_host(host)
+                .map_err(|e| format!("【配置校验失败】updatePolicy.allowedHosts: {}", e))?;
+        }
+    }
+
+    // 11. 检查 delegatedInstall 计划任务名称与超时时间
+    if let Some(delegated) = config.delegated_install.as_ref() {
+        services::delegated_install::validate_config(delegated)
+            .map_err(|e| format!("【配置校验失败】delegatedInstall: {}", e))?;
+    }
+
+    // 12. 检查 installFeedback 回传地址与批量大小
+    if let Some(feedback) = config.install_feedback.as_ref().filter(|f| f.enabled) {
+        services::feedback_service::validate_config(feedback)
+            .map_err(|e| format!("【配置校验失败】installFeedback: {}", e))?;
+    }
+
+    // 13. 检查 postInstall 动作格式（签名在执行时用本机密钥校验）
+    // 14. 检查 permissions 账户列表格式（账户是否存在在应用时检查）
+    for city in &config.cities {
+        for area in &city.areas {
+            for printer in &area.printers {
+                if let Some(post_install) = printer.post_install.as_ref() {
+                    services::post_install::validate_config(post_install)
+                        .map_err(|e| format!("【配置校验失败】打印机 '{}' 的 postInstall: {}", printer.name, e))?;
+                }
+                if let Some(permissions) = printer.permissions.as_ref() {
+                    services::printer_permissions::validate_config(permissions)
+                        .map_err(|e| format!("【配置校验失败】打印机 '{}' 的 permissions: {}", printer.name, e))?;
+                }
+            }
+        }
+    }
+
+    // 15. 检查 driverCatalog 的 compatibility 支持范围
+    for (driver_key, entry) in catalog {
+        if let Some(compat) = entry.compatibility.as_ref() {
+            compat.validate()
+                .map_err(|e| format!("【配置校验失败】driverCatalog['{}'].compatibility: {}", driver_key, e))?;
+        }
+    }
+
+    // 16. 检查 kiosk 自助终端策略
+    if let Some(kiosk) = config.kiosk.as_ref() {
+        services::kiosk::validate_config(kiosk, config)
+            .map_err(|e| format!("【配置校验失败】kiosk: {}", e))?;
+    }
+
+    Ok(())
+}
+
+/// 校验安装选项取值（owner 用于错误提示，如 "区域 '3F'"）
+fn validate_install_options(options: &InstallOptions, owner: &str) -> Result<(), String> {
+    let checks: [(&str, &Option<String>, &[&str]); 5] = [
+        ("driverInstallPolicy", &options.driver_install_policy, &VALID_DRIVER_INSTALL_POLICIES),
+        ("installMode", &options.install_mode, &VALID_INSTALL_MODES),
+        ("portProtocol", &options.port_protocol, &VALID_PORT_PROTOCOLS),
+        ("installScope", &options.install_scope, &VALID_INSTALL_SCOPES),
+        ("verifyProbe", &options.verify_probe, &VALID_VERIFY_PROBES),
+    ];
+    for (field, value, allowed) in checks {
+        if let Some(value) = value {
+            if !allowed.contains(&value.as_str()) {
+                return Err(format!(
+                    "【配置校验失败】{} 的 {}=\"{}\" 无效。允许的取值：{}",
+                    owner,
+                    field,
+                    value,
+                    allowed.join("|")
+                ));
+            }
+        }
+    }
+    // printProcessor / datatype 取值由驱动决定，只拒绝空白值
+    for (field, value) in [("printProcessor", &options.print_processor), ("datatype", &options.datatype)] {
+        if value.as_ref().map(|v| v.trim().is_empty()).unwrap_or(false) {
+            return Err(format!("【配置校验失败】{} 的 {} 不能为空字符串", owner, field));
+        }
+    }
+    if let Some(pin) = &options.driver_pin {
+        pin.validate().map_err(|e| format!("【配置校验失败】{} 的 driverPin: {}", owner, e))?;
+    }
     Ok(())
 }
 
@@ -546,7 +685,7 @@ pub fn load_local_config() -> Result<(PrinterConfig, std::path::PathBuf), String
             return Ok((config, config_path));
         }
         
-        return Err(format!("未找到本地配置文件: {}。Windows 要求配置文件与 exe 同目录。", config_path.display()));
+        Err(format!("未找到本地配置文件: {}。Windows 要求配置文件与 exe 同目录。", config_path.display()))
     }
     
     #[cfg(not(target_os = "windows"))]
@@ -595,6 +734,49 @@ pub fn load_local_config() -> Result<(PrinterConfig, std::path::PathBuf), String
     }
 }
 
+/// 查找配置中的打印机条目（按名称或路径匹配），返回原始条目与所在区域
+pub fn find_config_entry<'a>(config: &'a PrinterConfig, name: &str, path: &str) -> Option<(&'a Area, &'a Printer)> {
+    config
+        .cities
+        .iter()
+        .flat_map(|city| city.areas.iter())
+        .flat_map(|area| area.printers.iter().map(move |printer| (area, printer)))
+        .find(|(_, printer)| printer.name == name || printer.path == path)
+}
+
+/// 合并区域默认安装选项并按本机驱动通道选择驱动
+fn resolve_config_printer(area: &Area, printer: &Printer) -> Printer {
+    let mut resolved = printer.clone();
+    resolved.options = printer.options.or(&area.defaults);
+    services::driver_channel::apply_channel(&mut resolved);
+    resolved
+}
+
+/// 查找配置中的打印机（按名称或路径匹配），并合并所在区域的默认安装选项
+/// 
+/// 返回的 Printer.options 为生效值：打印机级设置优先，未设置的字段继承区域默认值；
+/// Printer.driver_key 为本机驱动通道选中的驱动（beta 通道且配置了 betaDriverKey 时为测试版）
+pub fn find_printer_with_defaults(config: &PrinterConfig, name: &str, path: &str) -> Option<Printer> {
+    find_config_entry(config, name, path).map(|(area, printer)| resolve_config_printer(area, printer))
+}
+
+/// 查找名称与路径都匹配的配置打印机（合并方式同 find_printer_with_defaults）
+///
+/// 委托安装、自助终端等权限检查使用：只按名称或只按路径匹配时，
+/// 已配置打印机的名称搭配任意路径（或反之）也能通过检查
+pub fn find_configured_printer(config: &PrinterConfig, name: &str, path: &str) -> Option<Printer> {
+    config
+        .cities
+        .iter()
+        .flat_map(|city| city.areas.iter())
+        .find_map(|area| {
+            area.printers
+                .iter()
+                .find(|printer| printer.name == name && printer.path == path)
+                .map(|printer| resolve_config_printer(area, printer))
+        })
+}
+
 /// 推导有效驱动规格
 /// 
 /// 根据 printer 的 driver_key 和 catalog 推导出 effective_* 字段
@@ -659,7 +841,24 @@ pub fn resolve_effective_driver_spec(
                         sha256: sha256.clone(),
                         version: remote.version.clone(),
                         layout: remote.layout.clone(),
+                        size: remote.size,
+                        archive_password_ref: remote.archive_password_ref.clone(),
                         driver_key: driver_key.clone(),
+                        patch: remote.patch.as_ref().and_then(|patch| {
+                            match (&patch.base_sha256, &patch.url, &patch.sha256) {
+                                (Some(base), Some(url), Some(sha256))
+                                    if !base.trim().is_empty() && !url.trim().is_empty() && !sha256.trim().is_empty() =>
+                                {
+                                    Some(DriverPatchResolved {
+                                        base_sha256: base.trim().to_string(),
+                                        url: url.clone(),
+                                        sha256: sha256.clone(),
+                                        size: patch.size,
+                                    })
+                                }
+                                _ => None,
+                            }
+                        }),
                     })
                 } else {
                     None
@@ -678,6 +877,7 @@ pub fn resolve_effective_driver_spec(
             effective_driver_names,
             driver_key_used: Some(driver_key.clone()),
             remote_driver,
+            compatibility: catalog_entry.compatibility.clone(),
         }
     } else {
         // 使用 legacy 字段
@@ -688,41 +888,20 @@ pub fn resolve_effective_driver_spec(
             effective_driver_names: printer.driver_names.clone().unwrap_or_default(),
             driver_key_used: None,
             remote_driver: None, // legacy 模式没有 remote_driver
+            compatibility: None,
         }
     }
 }
 
-// 保存配置到本地文件（使用原子写：temp -> rename）
+// 保存配置到本地文件（持有文件锁 + 原子写：temp -> rename）
+// 文件锁避免多开实例 / 后台刷新与前端确认更新同时写入导致配置损坏
 fn save_config_to_local(config: &PrinterConfig, config_path: &std::path::Path) -> Result<(), String> {
-    use std::io::Write;
-    
     let json_content = serde_json::to_string_pretty(config)
         .map_err(|e| format!("序列化配置失败: {}", e))?;
     
-    // 确保目录存在
-    if let Some(parent) = config_path.parent() {
-        fs::create_dir_all(parent)
-            .map_err(|e| format!("创建目录失败: {}", e))?;
-    }
-    
-    // 原子写：先写入临时文件，然后重命名
-    let temp_path = config_path.with_extension("json.tmp");
-    
-    // 写入临时文件
-    {
-        let mut file = fs::File::create(&temp_path)
-            .map_err(|e| format!("创建临时配置文件失败: {}", e))?;
-        
-        file.write_all(json_content.as_bytes())
-            .map_err(|e| format!("写入临时配置文件失败: {}", e))?;
-        
-        file.sync_all()
-            .map_err(|e| format!("同步临时文件失败: {}", e))?;
-    }
-    
-    // 原子重命名（在大多数文件系统上是原子操作）
-    fs::rename(&temp_path, config_path)
-        .map_err(|e| format!("重命名临时文件失败: {}", e))?;
+    let _lock = services::meta_store::FileLock::acquire(config_path)?;
+    services::meta_store::write_atomic(config_path, json_content.as_bytes())?;
+    services::config_http_cache::commit(config, config_path);
     
     // macOS/Linux: 确保目录同步（Windows 上 rename 已经同步）
     #[cfg(not(windows))]
@@ -755,14 +934,16 @@ fn config_different(local: &PrinterConfig, remote: &PrinterConfig) -> bool {
         return true;
     }
     
-    // 比较每个 city, area 和 printer
+    // 比较每个 city, area（含区域层级 parent）和 printer
     for (local_city, remote_city) in local.cities.iter().zip(remote.cities.iter()) {
         if local_city.city_id != remote_city.city_id || local_city.areas.len() != remote_city.areas.len() {
             return true;
         }
         
         for (local_area, remote_area) in local_city.areas.iter().zip(remote_city.areas.iter()) {
-            if local_area.area_name != remote_area.area_name || local_area.printers.len() != remote_area.printers.len() {
+            if local_area.area_name != remote_area.area_name
+                || local_area.parent != remote_area.parent
+                || local_area.printers.len() != remote_area.printers.len() {
                 return true;
             }
             
@@ -829,12 +1010,12 @@ async fn load_config(app: tauri::AppHandle) -> Result<LoadConfigResult, String>
             tauri::async_runtime::spawn(async move {
                 // 使用较短的 timeout（1500ms）避免后台任务长期占用
                 let remote_result = tokio::time::timeout(
-                    std::time::Duration::from_millis(1500),
+                    services::config_sources::overall_timeout(std::time::Duration::from_millis(1500)),
                     load_remote_config()
                 ).await;
                 
                 match remote_result {
-                    Ok(Ok(remote_config)) => {
+                    Ok(Ok(services::config_sources::RemoteConfig { config: remote_config, source })) => {
                         let remote_version = remote_config.version.clone();
                         
                         // 比较配置是否不同
@@ -849,6 +1030,7 @@ async fn load_config(app: tauri::AppHandle) -> Result<LoadConfigResult, String>
                                 "has_update": true,
                                 "local_version": local_version_clone,
                                 "remote_version": remote_version,
+                                "remote_source": source,
                             });
                             
                             if let Err(e) = app_clone.emit_all("config_remote_update", payload) {
@@ -894,17 +1076,19 @@ async fn load_config(app: tauri::AppHandle) -> Result<LoadConfigResult, String>
                 remote_config: None, // 初始为 None，后续通过事件通知
                 local_version,
                 remote_version: None, // 初始为 None，后续通过事件通知
+                update_plan: None,
+                remote_source: None,
             })
         }
         Err(local_err) => {
             // 本地配置不存在，尝试加载远程配置
             let remote_result = tokio::time::timeout(
-                std::time::Duration::from_secs(6),
+                services::config_sources::overall_timeout(std::time::Duration::from_secs(6)),
                 load_remote_config()
             ).await;
             
             match remote_result {
-                Ok(Ok(remote_config)) => {
+                Ok(Ok(services::config_sources::RemoteConfig { config: remote_config, source })) => {
                     let remote_version = remote_config.version.clone();
                     
                     // 远程配置加载成功，保存到本地配置路径
@@ -913,6 +1097,7 @@ async fn load_config(app: tauri::AppHandle) -> Result<LoadConfigResult, String>
                     // 尝试保存远程配置到本地（可选，失败不影响使用）
                     let remote_error = match save_config_to_local(&remote_config, &save_path) {
                         Ok(_) => {
+                            services::config_sources::remember_saved_origin(Some(&source.url));
                             eprintln!("[INFO] 本地配置不存在，已将远程配置保存到本地");
                             None
                         }
@@ -930,6 +1115,8 @@ async fn load_config(app: tauri::AppHandle) -> Result<LoadConfigResult, String>
                         remote_config: None,
                         local_version: None,
                         remote_version,
+                        update_plan: None,
+                        remote_source: Some(source),
                     })
                 }
                 Ok(Err(remote_err)) => {
@@ -968,19 +1155,29 @@ async fn confirm_update_config(app: tauri::AppHandle) -> Result<LoadConfigResult
             
             // 加载远程配置
             let remote_result = tokio::time::timeout(
-                std::time::Duration::from_secs(6),
+                services::config_sources::overall_timeout(std::time::Duration::from_secs(6)),
                 load_remote_config()
             ).await;
             
             match remote_result {
-                Ok(Ok(remote_config)) => {
+                Ok(Ok(services::config_sources::RemoteConfig { config: remote_config, source })) => {
                     let remote_version = remote_config.version.clone();
                     
                     // 保存远程配置到本地（使用原子写）
                     match save_config_to_local(&remote_config, &local_config_path) {
                         Ok(_) => {
+                            services::config_sources::remember_saved_origin(Some(&source.url));
                             eprintln!("[INFO] 已确认更新，远程配置已保存到本地");
                             
+                            // 逐台比较新旧配置，给出已安装打印机的修复计划（不整体失效重装）
+                            let (old_config, new_config) = (local_config, remote_config.clone());
+                            let update_plan = tokio::task::spawn_blocking(move || {
+                                services::config_update_plan::plan_with_installed_state(&old_config, &new_config)
+                            })
+                            .await
+                            .map_err(|e| eprintln!("[WARN] 计算配置更新计划任务异常: {}", e))
+                            .ok();
+                            
                             Ok(LoadConfigResult {
                                 config: remote_config,
                                 source: "remote_updated".to_string(),
@@ -989,6 +1186,8 @@ async fn confirm_update_config(app: tauri::AppHandle) -> Result<LoadConfigResult
                                 remote_config: None,
                                 local_version,
                                 remote_version,
+                                update_plan,
+                                remote_source: Some(source),
                             })
                         }
                         Err(save_err) => {
@@ -1010,18 +1209,43 @@ async fn confirm_update_config(app: tauri::AppHandle) -> Result<LoadConfigResult
     }
 }
 
-// 加载远程配置
-async fn load_remote_config() -> Result<PrinterConfig, String> {
-    // 创建带超时的 HTTP 客户端（5秒超时）
+// 加载远程配置（经请求协调器去重与限频）
+async fn load_remote_config() -> Result<services::config_sources::RemoteConfig, String> {
+    services::request_coordinator::REMOTE_CONFIG
+        .run(fetch_remote_config)
+        .await
+}
+
+// 按优先级依次尝试各配置源
+async fn fetch_remote_config() -> Result<services::config_sources::RemoteConfig, String> {
+    let sources = services::user_settings::config_sources();
+    let (config, source) = services::config_sources::first_available(&sources, fetch_remote_config_from).await?;
+    Ok(services::config_sources::RemoteConfig { config, source })
+}
+
+// 实际发起远程配置 HTTP 请求（单个配置源）
+async fn fetch_remote_config_from(source: services::config_sources::ConfigSource) -> Result<PrinterConfig, String> {
+    // 创建带超时的 HTTP 客户端（按配置源超时，默认 5 秒）
     let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(HTTP_TIMEOUT_SECS))
+        .timeout(source.timeout())
         .build()
         .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
     
-    let url = CONFIG_REMOTE_URL;
+    let url = source.url.as_str();
     
-    let response = client
-        .get(url)
+    // 本地配置仍是上次下载的版本时发送条件请求（304 = 无更新）
+    let conditional = services::config_http_cache::conditional_request(url);
+    let mut request = client.get(url);
+    if let Some(cond) = &conditional {
+        if let Some(etag) = &cond.etag {
+            request = request.header(reqwest::header::IF_NONE_MATCH, etag.as_str());
+        }
+        if let Some(last_modified) = &cond.last_modified {
+            request = request.header(reqwest::header::IF_MODIFIED_SINCE, last_modified.as_str());
+        }
+    }
+    
+    let response = request
         .send()
         .await
         .map_err(|e| {
@@ -1034,6 +1258,30 @@ async fn load_remote_config() -> Result<PrinterConfig, String> {
             }
         })?;
     
+    let header_value = |name: reqwest::header::HeaderName| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|v| v.to_str().ok())
+            .map(|v| v.to_string())
+    };
+    let etag = header_value(reqwest::header::ETAG);
+    let last_modified = header_value(reqwest::header::LAST_MODIFIED);
+    
+    if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+        let cond = conditional.ok_or_else(|| "服务器返回 304，但本地没有可复用的配置".to_string())?;
+        let config = services::config_http_cache::load_cached_config(&cond)?;
+        eprintln!("[CONFIG_HTTP_CACHE] 304 Not Modified，复用本地配置 version={:?}", config.version);
+        // 304 可能携带新的验证器；未携带时沿用原值
+        services::config_http_cache::remember_response(
+            url,
+            config.version.clone(),
+            etag.or(cond.etag),
+            last_modified.or(cond.last_modified),
+        );
+        return Ok(config);
+    }
+    
     if !response.status().is_success() {
         return Err(format!("服务器返回错误: {}", response.status()));
     }
@@ -1043,6 +1291,11 @@ async fn load_remote_config() -> Result<PrinterConfig, String> {
         .await
         .map_err(|e| format!("解析JSON失败: {}", e))?;
     
+    // 站点 overlay：拉取 extends 指向的基础配置并合并
+    let config = services::config_service::resolve_extends(config, url).await?;
+    
+    services::config_http_cache::remember_response(url, config.version.clone(), etag, last_modified);
+    
     Ok(config)
 }
 
@@ -1064,6 +1317,7 @@ async fn load_remote_config() -> Result<PrinterConfig, String> {
 fn debug_extract_zip(zip_path: String, driver_uuid: Option<String>) -> Result<DebugExtractZipResponse, String> {
     use std::path::Path;
     
+    services::kiosk::check_action("driverMaintenance")?;
     eprintln!("[DebugExtractZip] start zip_path=\"{}\" driver_uuid={:?}", zip_path, driver_uuid);
     
     // 解析 zip_path
@@ -1101,7 +1355,7 @@ fn debug_extract_zip(zip_path: String, driver_uuid: Option<String>) -> Result<De
         zip_path, final_driver_uuid, drivers_root.display());
     
     // 调用 extract_zip_for_driver（debug 命令不需要进度事件）
-    match crate::platform::windows::archive::extract_zip_for_driver(zip_path_buf, &drivers_root, &final_driver_uuid, None, None, "debug_job") {
+    match crate::platform::windows::archive::extract_zip_for_driver(zip_path_buf, &drivers_root, &final_driver_uuid, None, None, None, "debug_job") {
         Ok(result) => {
             eprintln!("[DebugExtractZip] success driver_uuid=\"{}\" uuid_root=\"{}\" extracted_root=\"{}\" file_count={}", 
                 result.driver_uuid, result.uuid_root.display(), result.extracted_root.display(), result.file_count);
@@ -1145,6 +1399,7 @@ fn debug_extract_zip(_zip_path: String, _dest_rel: Option<String>) -> Result<Deb
 #[tauri::command]
 #[cfg(windows)]
 async fn debug_fetch_driver_payload(remote_url: String, sha256: String) -> Result<DebugFetchDriverPayloadResponse, String> {
+    services::kiosk::check_action("driverMaintenance")?;
     eprintln!("[DebugFetchDriverPayload] start remote_url=\"{}\" sha256=\"{}\"", remote_url, sha256);
     
     // 获取 AppDir 和 drivers_root
@@ -1161,7 +1416,14 @@ async fn debug_fetch_driver_payload(remote_url: String, sha256: String) -> Resul
         remote_url, sha256, drivers_root.display());
     
     // 调用 ensure_payload_zip（debug 命令不需要进度事件）
-    match crate::platform::windows::driver_fetch::ensure_payload_zip(&drivers_root, &remote_url, &sha256, None, None, "debug_job").await {
+    match crate::platform::windows::driver_fetch::ensure_payload_zip(
+        &drivers_root,
+        &remote_url,
+        &sha256,
+        None,
+        None,
+        crate::platform::windows::driver_fetch::FetchProgress { app: None, printer_name: None, job_id: "debug_job" },
+    ).await {
         Ok(result) => {
             eprintln!("[DebugFetchDriverPayload] success driver_uuid=\"{}\" uuid_root=\"{}\" payload_zip=\"{}\" source_used=\"{}\" bytes={} sha256_actual=\"{}\"", 
                 result.driver_uuid, result.uuid_root.display(), result.payload_zip.display(), 
@@ -1197,7 +1459,7 @@ async fn debug_fetch_driver_payload(_remote_url: String, _sha256: String) -> Res
 
 // 删除打印机
 #[tauri::command]
-fn delete_printer(
+async fn delete_printer(
     printer_name: String, 
     remove_port: Option<bool>,
     remove_driver: Option<bool>
@@ -1205,6 +1467,8 @@ fn delete_printer(
     eprintln!("[DeletePrinter][Command] ENTER cmd=delete_printer printer_name=\"{}\" remove_port={:?} remove_driver={:?}", 
         printer_name, remove_port, remove_driver);
     
+    services::kiosk::check_action("delete")?;
+    let _printer_lock = services::printer_locks::acquire(&[&printer_name], "delete")?;
     let remove_port_flag = remove_port.unwrap_or(false); // 默认 false 更安全
     let remove_driver_flag = remove_driver.unwrap_or(false); // 默认 false
     
@@ -1213,11 +1477,26 @@ fn delete_printer(
         eprintln!("[DeletePrinter][Command] WARN remove_driver=true (高级操作)");
     }
     
-    let result = crate::platform::delete_printer(&printer_name, remove_port_flag, remove_driver_flag)?;
+    let result = tokio::task::spawn_blocking(move || {
+        crate::platform::delete_printer(&printer_name, remove_port_flag, remove_driver_flag)
+    })
+    .await
+    .map_err(|e| format!("删除打印机任务异常: {}", e))??;
     
     eprintln!("[DeletePrinter][Command] EXIT cmd=delete_printer success={} removed_queue={} removed_port={} removed_driver={}", 
         result.success, result.removed_queue, result.removed_port, result.removed_driver);
     
+    services::audit_log::record_delete(&result).await;
+    
+    if result.success {
+        let queue_names = vec![result.printer_name.clone()];
+        match tokio::task::spawn_blocking(move || services::driver_cache::release_queues(&queue_names)).await {
+            Ok(Err(e)) => eprintln!("[DeletePrinter][Command] 释放驱动包引用失败: {}", e),
+            Err(e) => eprintln!("[DeletePrinter][Command] 释放驱动包引用任务异常: {}", e),
+            Ok(Ok(())) => {}
+        }
+    }
+    
     Ok(result)
 }
 
@@ -1234,11 +1513,16 @@ async fn install_printer(
     model: Option<String>,
     driverInstallPolicy: Option<String>,  // 驱动安装策略："always" | "reuse_if_installed"
     installMode: Option<String>,  // 安装方式："auto" | "package" | "installer" | "ipp" | "legacy_inf"
-    dryRun: Option<bool>  // 测试模式
+    dryRun: Option<bool>,  // 测试模式
+    queueConflict: Option<String>  // 同名队列指向其他地址时的处理："fail" | "overwrite" | "rename-new"
 ) -> Result<InstallResult, String> {
-    // 参数校验
-    if name.trim().is_empty() {
-        return Err("打印机名称不能为空".to_string());
+    // 参数校验（名称 / 路径 / 型号按 Windows 命名规则，失败时返回字段级错误）
+    if let Err(errors) = platform::install_input::validate_install_input(&name, &path, model.as_deref()) {
+        return Ok(InstallResult::validation_failed(errors, dryRun.unwrap_or(true)));
+    }
+    // 自助终端模式：真实安装只允许策略中的区域（dryRun 不改动本机，不受限制）
+    if !dryRun.unwrap_or(true) {
+        services::kiosk::check_install(&name, &path)?;
     }
     
     // v2.0.0+ 强制要求 driverKey
@@ -1254,16 +1538,79 @@ async fn install_printer(
     let catalog = config.driver_catalog.as_ref()
         .ok_or_else(|| "配置缺少 driverCatalog".to_string())?;
     
+    // 驱动通道：beta 通道的打印机改用 betaDriverKey（前端传入的是配置中的稳定版 driverKey）
+    let effective_driver_key = services::driver_channel::driver_key_for_request(&config, &name, &path, effective_driver_key);
+    
     let _catalog_entry = catalog.get(&effective_driver_key)
         .ok_or_else(|| format!(
             "driverKey='{}' 在 driverCatalog 中不存在。已定义的 driverKey: [{}]",
             effective_driver_key,
             catalog.keys().map(|s| format!("'{}'", s)).collect::<Vec<_>>().join(", ")
         ))?;
+
+    // 配置中的打印机 / 区域级安装选项（打印机级覆盖区域默认值）、安装后动作与队列权限
+    let config_printer = find_printer_with_defaults(&config, &name, &path);
+    let post_install = config_printer.as_ref().and_then(|p| p.post_install.clone());
+    // 安装后动作的 vendor 工具按本次实际安装的驱动包定位（安装过程中配置可能被刷新）
+    let post_install_package = services::driver_cache::package_uuid(&config, &effective_driver_key);
+    let permissions = config_printer.as_ref().and_then(|p| p.permissions.clone());
+    let config_options = config_printer.map(|p| p.options).unwrap_or_default();
+    
+    // nameTemplate：按模板生成系统队列名称（配置中的原始名称作为 displayName 记入安装历史）
+    let queue_name = services::printer_naming::queue_name_for_install(&config, &name, model.as_deref()).await;
+    // 队列名冲突：同名队列指向其他地址时按 queueConflict 处理（fail 返回 QUEUE_CONFLICT 安装结果，rename-new 改用追加序号的名称）
+    let queue_conflict = services::queue_conflict::strategy_for_request(queueConflict.as_deref())?;
+    let queue_name = match services::queue_conflict::resolve_queue_name(&name, queue_name, &path, queue_conflict).await? {
+        services::queue_conflict::QueueNameResolution::Use(queue_name) => queue_name,
+        services::queue_conflict::QueueNameResolution::Conflict(message) => {
+            return Ok(InstallResult::queue_conflict(message, dryRun.unwrap_or(true)));
+        }
+    };
+    if let Some(queue_name) = &queue_name {
+        if let Err(errors) = platform::install_input::validate_install_input(queue_name, &path, model.as_deref()) {
+            return Ok(InstallResult::validation_failed(errors, dryRun.unwrap_or(true)));
+        }
+        eprintln!("[InstallRequest] nameTemplate queue_name=\"{}\" display_name=\"{}\"", queue_name, name);
+    }
+    // 操作锁：同一台打印机正在删除 / 修复 / 安装时返回 PRINTER_BUSY（dryRun 不改动本机，不加锁）
+    let _printer_lock = match dryRun.unwrap_or(true) {
+        true => None,
+        false => Some(services::printer_locks::acquire(&[&name, queue_name.as_deref().unwrap_or_default()], "install")?),
+    };
+
+    // 委托安装：未提权且配置了 delegatedInstall 时，交给管理员预先注册的计划任务执行（worker 以提权身份重新走本流程）
+    if let Some(delegated) = config.delegated_install.as_ref().filter(|_| !dryRun.unwrap_or(true) && !is_elevated()) {
+        eprintln!("[InstallRequest] not elevated, delegating to scheduled task=\"{}\"", delegated.task_name);
+        let started_at = chrono::Utc::now().timestamp_millis();
+        let request = services::delegated_install::DelegatedInstallRequest {
+            request_id: String::new(),
+            requested_by: String::new(),
+            created_at: 0,
+            name: name.clone(),
+            path,
+            queue_conflict: Some(queue_conflict.as_str().to_string()),
+        };
+        let result = services::delegated_install::install_via_task(delegated, request).await?;
+        let mut record = services::install_history::InstallRecord::from_result(
+            queue_name.clone().unwrap_or_else(|| name.clone()),
+            Some(effective_driver_key),
+            installMode,
+            started_at,
+            &result,
+        );
+        record.display_name = queue_name.map(|_| name);
+        if let Some(driver_key) = record.driver_key.clone().filter(|_| record.success && !record.dry_run) {
+            services::driver_cache::record_queue(record.printer_name.clone(), driver_key).await;
+        }
+        services::audit_log::record_install(&record).await;
+        services::feedback_service::record_install(&record).await;
+        services::install_history::record_install(record).await;
+        return Ok(result);
+    }
     
     // ========== installMode 决策逻辑（新） ==========
-    // 规范化 installMode（遵循 "前端 → driverCatalog → auto" 优先级）
-    let valid_modes = ["auto", "package", "installer", "ipp", "legacy_inf"];
+    // 规范化 installMode（遵循 "前端 → 打印机/区域配置 → driverCatalog → auto" 优先级）
+    let valid_modes = VALID_INSTALL_MODES;
     
     let install_mode = if let Some(frontend_mode) = installMode {
         // 1. 前端明确传入 → 直接使用（经过校验）
@@ -1275,8 +1622,12 @@ async fn install_printer(
         }
         eprintln!("[InstallRequest] using frontend installMode=\"{}\"", frontend_mode);
         frontend_mode
+    } else if let Some(config_mode) = &config_options.install_mode {
+        // 2. 前端未传 → 使用打印机 / 区域配置（加载配置时已校验）
+        eprintln!("[InstallRequest] using printer/area installMode=\"{}\"", config_mode);
+        config_mode.clone()
     } else if let Some(catalog_mode) = &_catalog_entry.install_mode {
-        // 2. 前端未传 → 尝试从 driverCatalog 读取
+        // 3. 尝试从 driverCatalog 读取
         if !valid_modes.contains(&catalog_mode.as_str()) {
             eprintln!("[InstallRequest] WARNING: invalid driverCatalog.installMode=\"{}\", fallback to auto", catalog_mode);
             "auto".to_string()
@@ -1285,14 +1636,14 @@ async fn install_printer(
             catalog_mode.clone()
         }
     } else {
-        // 3. 两者都没有 → 默认 auto
+        // 4. 均未指定 → 默认 auto
         eprintln!("[InstallRequest] no installMode specified, default to auto");
         "auto".to_string()
     };
 
-    // 验证 driverInstallPolicy（禁止静默回退）
-    let valid_policies = ["always", "reuse_if_installed"];
-    let driver_install_policy = match &driverInstallPolicy {
+    // 验证 driverInstallPolicy（禁止静默回退；前端未传时使用打印机 / 区域配置）
+    let valid_policies = VALID_DRIVER_INSTALL_POLICIES;
+    let driver_install_policy = match driverInstallPolicy.as_ref().or(config_options.driver_install_policy.as_ref()) {
         Some(policy) if valid_policies.contains(&policy.as_str()) => policy.clone(),
         Some(invalid_policy) => {
             return Err(format!(
@@ -1321,6 +1672,11 @@ async fn install_printer(
         driver_names: None, // v2.0.0+ 从 driverCatalog 获取
         install_mode: None, // v2.0.0+ 从 driverCatalog 获取
         driver_key: Some(effective_driver_key.clone()),
+        beta_driver_key: None,
+        image_url: None,
+        options: config_options,
+        post_install: None,
+        permissions: None,
     };
     
     // 推导有效驱动规格
@@ -1333,10 +1689,30 @@ async fn install_printer(
         effective_spec.effective_driver_names
     );
     
+    // hostname 型路径：安装前解析，解析失败直接返回错误
+    let resolved_host = services::host_resolution::resolve_install_host(&path).await?;
+    
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let history_name = name.clone();
+    let history_driver_key = Some(effective_driver_key.clone());
+    let history_install_mode = Some(install_mode.clone());
+    // 因未提权失败时保存原请求，提权后继续安装
+    let resume_path = path.clone();
+    let resume_model = model.clone();
+    let resume_policy = driverInstallPolicy.clone();
+    // 预估安装耗时，随 job.init 事件 meta.estimate 发送
+    services::install_estimate::attach_to_next_job(
+        queue_name.as_deref().unwrap_or(&name),
+        Some(&config),
+        &name,
+        Some(&effective_driver_key),
+    )
+    .await;
+    
     // 调用平台统一的安装入口（使用 resolved 字段）
-    crate::platform::install_printer(
+    let result = crate::platform::install_printer(
         app,
-        name,
+        queue_name.clone().unwrap_or_else(|| name.clone()),
         path,
         effective_spec.effective_driver_path,  // 从 driverCatalog 解析
         model,
@@ -1345,7 +1721,65 @@ async fn install_printer(
         Some(install_mode),
         dry_run_value,
     )
-    .await
+    .await;
+    // 安装在 job.init 之前返回（锁占用 / 校验失败）时预估未被取走，清除以免附到下一次安装
+    install_event_emitter::clear_install_estimate(queue_name.as_deref().unwrap_or(&name));
+    let result = result?;
+    
+    if let Some(resolved) = resolved_host {
+        if result.success && !result.effective_dry_run {
+            services::host_resolution::record_install_host(history_name.clone(), resolved).await;
+        }
+    }
+    
+    // 队列权限与安装后动作：仅在真实安装成功后执行，结果记入安装历史
+    let installed = result.success && !result.effective_dry_run;
+    let permissions_report = match permissions.filter(|_| installed) {
+        Some(permissions) => Some(
+            services::printer_permissions::apply_permissions(queue_name.clone().unwrap_or_else(|| history_name.clone()), permissions).await,
+        ),
+        None => None,
+    };
+    let post_install_results = match post_install.filter(|_| installed) {
+        Some(post_install) => {
+            services::post_install::run_post_install(
+                history_name.clone(),
+                queue_name.clone().unwrap_or_else(|| history_name.clone()),
+                post_install_package,
+                post_install,
+            )
+            .await
+        }
+        None => Vec::new(),
+    };
+    
+    let mut record = services::install_history::InstallRecord::from_result(
+        queue_name.clone().unwrap_or_else(|| history_name.clone()),
+        history_driver_key,
+        history_install_mode,
+        started_at,
+        &result,
+    );
+    services::elevation_resume::record_install(
+        &history_name,
+        &resume_path,
+        resume_model.as_deref(),
+        resume_policy.as_deref(),
+        Some(queue_conflict.as_str()),
+        &record,
+    )
+    .await;
+    record.display_name = queue_name.map(|_| history_name);
+    record.post_install = post_install_results;
+    record.permissions = permissions_report;
+    if let Some(driver_key) = record.driver_key.clone().filter(|_| record.success && !record.dry_run) {
+        services::driver_cache::record_queue(record.printer_name.clone(), driver_key).await;
+    }
+    services::audit_log::record_install(&record).await;
+    services::feedback_service::record_install(&record).await;
+    services::install_history::record_install(record).await;
+    
+    Ok(result)
 }
 
 #[cfg(target_os = "macos")]
@@ -1360,20 +1794,40 @@ async fn install_printer(
     model: Option<String>,
     _driverInstallPolicy: Option<String>,
     installMode: Option<String>,  // macOS 会自动降级为 driverless
-    dryRun: Option<bool>  // 测试模式
+    dryRun: Option<bool>,  // 测试模式
+    _queueConflict: Option<String>  // macOS 队列名由设备 URI 生成，不存在同名异址
 ) -> Result<InstallResult, String> {
-    if name.trim().is_empty() {
-        return Err("打印机名称不能为空".to_string());
+    let dry_run_value = dryRun.unwrap_or(true);
+    // 参数校验（名称 / 路径 / 型号按 lpadmin 规则，失败时返回字段级错误）
+    if let Err(errors) = platform::install_input::validate_install_input(&name, &path, model.as_deref()) {
+        return Ok(InstallResult::validation_failed(errors, dry_run_value));
     }
-    if path.trim().is_empty() {
-        return Err("打印机路径不能为空".to_string());
+    if !dry_run_value {
+        services::kiosk::check_install(&name, &path)?;
     }
+    // nameTemplate：按模板生成系统队列名称（配置读取失败时使用原始名称）
+    let queue_name = match load_local_config() {
+        Ok((config, _)) => services::printer_naming::queue_name_for_install(&config, &name, model.as_deref()).await,
+        Err(_) => None,
+    };
+    if let Some(queue_name) = &queue_name {
+        if let Err(errors) = platform::install_input::validate_install_input(queue_name, &path, model.as_deref()) {
+            return Ok(InstallResult::validation_failed(errors, dry_run_value));
+        }
+    }
+    let _printer_lock = match dry_run_value {
+        true => None,
+        false => Some(services::printer_locks::acquire(&[&name, queue_name.as_deref().unwrap_or_default()], "install")?),
+    };
+    let resolved_host = services::host_resolution::resolve_install_host(&path).await?;
+    services::install_estimate::attach_to_next_job(queue_name.as_deref().unwrap_or(&name), None, &name, None).await;
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let history_name = name.clone();
+    let history_install_mode = installMode.clone();
 
-    let dry_run_value = dryRun.unwrap_or(true);
-
-    crate::platform::install_printer(
+    let result = crate::platform::install_printer(
         app,
-        name,
+        queue_name.clone().unwrap_or_else(|| name.clone()),
         path,
         None,
         model,
@@ -1382,7 +1836,29 @@ async fn install_printer(
         installMode,
         dry_run_value,
     )
-    .await
+    .await;
+    install_event_emitter::clear_install_estimate(queue_name.as_deref().unwrap_or(&name));
+    let result = result?;
+
+    if let Some(resolved) = resolved_host {
+        if result.success && !result.effective_dry_run {
+            services::host_resolution::record_install_host(history_name.clone(), resolved).await;
+        }
+    }
+
+    let mut record = services::install_history::InstallRecord::from_result(
+        queue_name.clone().unwrap_or_else(|| history_name.clone()),
+        None,
+        history_install_mode,
+        started_at,
+        &result,
+    );
+    record.display_name = queue_name.map(|_| history_name);
+    services::audit_log::record_install(&record).await;
+    services::feedback_service::record_install(&record).await;
+    services::install_history::record_install(record).await;
+
+    Ok(result)
 }
 
 #[cfg(not(any(windows, target_os = "macos")))]
@@ -1397,7 +1873,8 @@ async fn install_printer(
     _model: Option<String>,
     _driverInstallPolicy: Option<String>,
     _installMode: Option<String>,
-    _dryRun: Option<bool>
+    _dryRun: Option<bool>,
+    _queueConflict: Option<String>
 ) -> Result<InstallResult, String> {
     Err("当前仅支持 Windows 和 macOS 平台安装".to_string())
 }
@@ -1417,7 +1894,8 @@ struct PrintTestPageRequest {
 
 // 打印测试页
 #[tauri::command]
-fn print_test_page(app: tauri::AppHandle, payload: PrintTestPageRequest) -> Result<String, String> {
+async fn print_test_page(app: tauri::AppHandle, payload: PrintTestPageRequest) -> Result<String, String> {
+    services::kiosk::check_action("testPage")?;
     let destination = payload
         .queue_name
         .or(payload.printer_name)
@@ -1426,6 +1904,7 @@ fn print_test_page(app: tauri::AppHandle, payload: PrintTestPageRequest) -> Resu
     if destination.trim().is_empty() {
         return Err("[PrintTestPage] ERROR step=VALIDATE message=打印机名称不能为空".to_string());
     }
+    let _printer_lock = services::printer_locks::acquire(&[&destination], "testPage")?;
 
     let destination_json = destination
         .chars()
@@ -1448,7 +1927,12 @@ fn print_test_page(app: tauri::AppHandle, payload: PrintTestPageRequest) -> Resu
         destination.as_bytes().len()
     );
 
-    crate::platform::print_test_page(app, destination)
+    let history_name = destination.clone();
+    let result = tokio::task::spawn_blocking(move || crate::platform::print_test_page(app, destination))
+        .await
+        .map_err(|e| format!("[PrintTestPage] ERROR step=TASK message=打印任务异常: {}", e))?;
+    services::printer_service::record_test_page(&history_name, &result).await;
+    result.map(|submission| submission.message)
 }
 
 // 重装打印机
@@ -1468,9 +1952,11 @@ async fn reinstall_printer(
     if configPrinterKey.trim().is_empty() {
         return Err("配置打印机标识不能为空".to_string());
     }
+    services::kiosk::check_install(&configPrinterName, &configPrinterPath)?;
     if configPrinterName.trim().is_empty() {
         return Err("配置打印机名称不能为空".to_string());
     }
+    let _printer_lock = services::printer_locks::acquire(&[&configPrinterName], "reinstall")?;
     crate::platform::reinstall_printer(
         app,
         configPrinterKey, configPrinterPath, configPrinterName,
@@ -1484,8 +1970,41 @@ async fn check_version_update() -> Result<VersionCheckResult, String> {
     // 获取当前版本
     let current_version = env!("CARGO_PKG_VERSION");
     
-    // 加载远程版本配置
-    let version_config_url = VERSION_CONFIG_REMOTE_URL;
+    // 加载远程版本配置（经请求协调器去重与限频）
+    let version_config = services::request_coordinator::VERSION_CONFIG
+        .run(fetch_version_config)
+        .await?;
+    
+    // 比较版本
+    let update_available = compare_versions(current_version, &version_config.app_version);
+    
+    // 灰度推送：有新版本时判断本机是否在推送范围内（不在范围内时 has_update=false）
+    let rollout = if update_available {
+        services::update_rollout::evaluate(&version_config, current_version).await
+    } else {
+        services::update_rollout::RolloutDecision::up_to_date()
+    };
+    
+    Ok(VersionCheckResult {
+        has_update: rollout.offered,
+        current_version: current_version.to_string(),
+        latest_version: version_config.app_version.clone(),
+        update_url: version_config.update_url.clone(),
+        update_type: version_config.update_type.clone(),
+        update_description: version_config.update_description.clone(),
+        force_update: version_config.force_update,
+        changelog: version_config.changelog.clone(),
+        download_size: version_config.download_size.clone(),
+        update_available,
+        rollout_status: rollout.status.to_string(),
+        rollout_percentage: version_config.rollout_percentage,
+        rollout_bucket: rollout.bucket,
+    })
+}
+
+// 实际发起远程版本配置 HTTP 请求
+async fn fetch_version_config() -> Result<VersionConfig, String> {
+    let version_config_url = services::user_settings::version_config_url();
     
     let client = reqwest::Client::builder()
         .timeout(std::time::Duration::from_secs(HTTP_TIMEOUT_SECS))
@@ -1514,20 +2033,7 @@ async fn check_version_update() -> Result<VersionCheckResult, String> {
         .await
         .map_err(|e| format!("解析版本配置失败: {}", e))?;
     
-    // 比较版本
-    let has_update = compare_versions(current_version, &version_config.app_version);
-    
-    Ok(VersionCheckResult {
-        has_update,
-        current_version: current_version.to_string(),
-        latest_version: version_config.app_version.clone(),
-        update_url: version_config.update_url.clone(),
-        update_type: version_config.update_type.clone(),
-        update_description: version_config.update_description.clone(),
-        force_update: version_config.force_update,
-        changelog: version_config.changelog.clone(),
-        download_size: version_config.download_size.clone(),
-    })
+    Ok(version_config)
 }
 
 // 比较版本号（简单版本，支持语义化版本）
@@ -1567,52 +2073,10 @@ fn compare_versions(current: &str, latest: &str) -> bool {
     false
 }
 
-// 下载并更新软件
+// 下载并更新软件（白名单主机 + TLS 1.2 + SHA256 校验，见 services::update_download）
 #[tauri::command]
-async fn download_update(update_url: String) -> Result<String, String> {
-    use std::fs;
-    use std::io::Write;
-    
-    // 创建 HTTP 客户端
-    let client = reqwest::Client::builder()
-        .timeout(std::time::Duration::from_secs(HTTP_TIMEOUT_DOWNLOAD_SECS))
-        .build()
-        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
-    
-    // 下载更新文件
-    let response = client
-        .get(&update_url)
-        .send()
-        .await
-        .map_err(|e| format!("下载更新文件失败: {}", e))?;
-    
-    if !response.status().is_success() {
-        return Err(format!("下载失败: {}", response.status()));
-    }
-    
-    // 获取文件内容
-    let bytes = response.bytes().await
-        .map_err(|e| format!("读取更新文件失败: {}", e))?;
-    
-    // 保存到临时目录
-    let temp_dir = std::env::temp_dir();
-    let _exe_name = std::path::Path::new(&update_url)
-        .file_name()
-        .and_then(|n| n.to_str())
-        .unwrap_or("update.exe");
-    let temp_file = temp_dir.join(format!("easyPrinter_update_{}.exe", std::process::id()));
-    
-    {
-        let mut file = fs::File::create(&temp_file)
-            .map_err(|e| format!("创建临时文件失败: {}", e))?;
-        
-        file.write_all(&bytes)
-            .map_err(|e| format!("写入更新文件失败: {}", e))?;
-        
-        file.sync_all()
-            .map_err(|e| format!("同步文件失败: {}", e))?;
-    }
-    
+async fn download_update(app: tauri::AppHandle, update_url: String) -> Result<String, String> {
+    let temp_file = services::update_download::download_update(&app, &update_url).await?;
     Ok(format!("更新文件已下载到: {}", temp_file.to_string_lossy()))
 }
 
@@ -1659,6 +2123,8 @@ fn get_system_info() -> Result<SystemInfo, String> {
             build_number,
             ubr,
             os_display,
+            integrity: services::integrity_service::last_report(),
+            terminal_server: platform::terminal_server_status(),
         })
     }
     
@@ -1681,6 +2147,8 @@ fn get_system_info() -> Result<SystemInfo, String> {
             build_number: None,
             ubr: None,
             os_display: None,
+            integrity: services::integrity_service::last_report(),
+            terminal_server: platform::terminal_server_status(),
         })
     }
     
@@ -1703,6 +2171,8 @@ fn get_system_info() -> Result<SystemInfo, String> {
             build_number: None,
             ubr: None,
             os_display: None,
+            integrity: services::integrity_service::last_report(),
+            terminal_server: platform::terminal_server_status(),
         })
     }
     
@@ -1719,10 +2189,18 @@ fn get_system_info() -> Result<SystemInfo, String> {
             build_number: None,
             ubr: None,
             os_display: None,
+            integrity: services::integrity_service::last_report(),
+            terminal_server: platform::terminal_server_status(),
         })
     }
 }
 
+/// 校验安装完整性（内嵌资源哈希 + 可执行文件签名）
+#[tauri::command]
+async fn verify_installation() -> Result<services::integrity_service::IntegrityReport, String> {
+    services::integrity_service::verify_installation().await
+}
+
 #[cfg(windows)]
 fn get_windows_version() -> Result<(String, Option<String>, Option<String>, Option<String>, Option<u32>, Option<String>), String> {
     use winapi::um::winreg::*;
@@ -1880,7 +2358,7 @@ fn get_macos_version() -> Result<String, String> {
         .map_err(|e| format!("执行 sw_vers 失败: {}", e))?;
     
     if output.status.success() {
-        let mut version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        let mut version = crate::utils::encoding::decode_bytes(&output.stdout).trim().to_string();
         
         // 获取版本号
         let version_output = Command::new("sw_vers")
@@ -1889,7 +2367,7 @@ fn get_macos_version() -> Result<String, String> {
             .map_err(|e| format!("获取 macOS 版本号失败: {}", e))?;
         
         if version_output.status.success() {
-            let version_num = String::from_utf8_lossy(&version_output.stdout).trim().to_string();
+            let version_num = crate::utils::encoding::decode_bytes(&version_output.stdout).trim().to_string();
             version.push(' ');
             version.push_str(&version_num);
         }
@@ -1933,7 +2411,7 @@ fn get_linux_version() -> Result<String, String> {
     
     if let Ok(output) = output {
         if output.status.success() {
-            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stdout = crate::utils::encoding::decode_bytes(&output.stdout);
             // 从输出中提取版本信息，例如 "Description:    Ubuntu 22.04.1 LTS"
             if let Some(line) = stdout.lines().next() {
                 let parts: Vec<&str> = line.split(':').collect();
@@ -2122,6 +2600,15 @@ fn check_webview2_installed() -> bool {
 }
 
 fn main() {
+    // 非交互模式（--silent / --non-interactive / EPRINTY_NON_INTERACTIVE=1）：不弹出任何原生对话框
+    let non_interactive = services::interaction_mode::init_from_process();
+    
+    // 崩溃捕获：panic / 未处理异常时把摘要与 minidump 写入 logs/crashes，下次启动时提示是否发送
+    services::crash_report::install();
+    
+    // 加载用户设置（同步调试开关、地址覆盖到各子系统）
+    services::user_settings::current();
+    
     // 在 Windows 上检查是否有管理员权限
     #[cfg(windows)]
     {
@@ -2151,8 +2638,70 @@ fn main() {
         .invoke_handler(tauri::generate_handler![
             commands::config_cmd::get_cached_config,
             commands::config_cmd::refresh_remote_config,
+            commands::config_cmd::reveal_path,
+            commands::config_cmd::get_effective_config,
+            commands::config_cmd::get_interaction_mode,
+            commands::config_cmd::get_pending_crash_report,
+            commands::config_cmd::resolve_crash_report,
+            commands::config_cmd::shutdown_app,
+            commands::config_cmd::get_unfinished_jobs,
+            commands::config_cmd::resolve_unfinished_jobs,
+            commands::config_cmd::relaunch_elevated,
+            commands::config_cmd::get_elevation_resume_jobs,
+            commands::config_cmd::get_webview_recovery,
+            commands::config_cmd::self_test,
+            commands::config_cmd::cleanup_machine_artifacts,
+            commands::config_cmd::get_settings,
+            commands::config_cmd::update_settings,
+            commands::config_cmd::export_app_state,
+            commands::config_cmd::import_app_state,
+            commands::config_cmd::list_remote_config_versions,
+            commands::config_cmd::apply_remote_config_version,
             commands::printer_cmd::list_printers,
             commands::printer_cmd::list_printers_detailed,
+            commands::printer_cmd::get_job_log,
+            commands::printer_cmd::get_job_command_trace,
+            commands::printer_cmd::export_job_diagnostics,
+            commands::printer_cmd::cancel_install_job,
+            commands::printer_cmd::get_install_history,
+            commands::printer_cmd::get_metrics_summary,
+            commands::printer_cmd::get_install_estimate,
+            commands::printer_cmd::flush_feedback,
+            commands::printer_cmd::reconcile_printers,
+            commands::printer_cmd::get_printer_watch_history,
+            commands::printer_cmd::get_test_page_history,
+            commands::printer_cmd::pause_printer,
+            commands::printer_cmd::resume_printer,
+            commands::printer_cmd::open_print_queue,
+            commands::printer_cmd::print_file,
+            commands::printer_cmd::check_printer_connectivity,
+            commands::printer_cmd::uninstall_all_managed_printers,
+            commands::printer_cmd::delete_printers_by_area,
+            commands::printer_cmd::render_test_page_preview,
+            commands::printer_cmd::get_printer_catalog,
+            commands::printer_cmd::set_printer_credentials,
+            commands::printer_cmd::clear_printer_credentials,
+            commands::printer_cmd::dump_print_environment,
+            commands::printer_cmd::probe_permissions,
+            commands::printer_cmd::get_port_config,
+            commands::printer_cmd::set_port_config,
+            commands::printer_cmd::update_printer_port,
+            commands::printer_cmd::get_printer_image,
+            commands::printer_cmd::get_printer_permissions,
+            commands::printer_cmd::preview_printer_permissions,
+            commands::printer_cmd::get_printer_supplies,
+            commands::printer_cmd::list_printer_operation_locks,
+            commands::driver_cmd::list_staged_printer_drivers,
+            commands::driver_cmd::remove_staged_driver,
+            commands::driver_cmd::check_driver_conflicts,
+            commands::driver_cmd::build_driver_pack,
+            commands::driver_cmd::build_driver_patch,
+            commands::driver_cmd::inspect_driver_pack,
+            commands::driver_cmd::set_metered_download_allowed,
+            commands::driver_cmd::set_driver_archive_password,
+            commands::driver_cmd::clear_driver_archive_password,
+            commands::driver_cmd::gc_driver_cache,
+            commands::driver_cmd::switch_driver_channel,
             load_config,
             install_printer,
             open_url,
@@ -2161,11 +2710,25 @@ fn main() {
             check_version_update,
             download_update,
             get_system_info,
+            verify_installation,
             reinstall_printer,
             delete_printer,
             debug_extract_zip,
-            debug_fetch_driver_payload
+            debug_fetch_driver_payload,
+            commands::api_cmd::api_load_config,
+            commands::api_cmd::api_install_printer,
+            commands::api_cmd::api_delete_printer,
+            commands::api_cmd::api_print_test_page,
+            commands::api_cmd::api_check_version_update
         ])
+        .on_window_event(|event| {
+            // 关闭窗口时有进行中的安装任务：阻止关闭，等待 / 取消任务后再退出
+            if let tauri::WindowEvent::CloseRequested { api, .. } = event.event() {
+                if services::shutdown_service::handle_close_requested(&event.window().app_handle()) {
+                    api.prevent_close();
+                }
+            }
+        })
         .setup(|app| {
             // Windows 平台：设置窗口圆角策略
             #[cfg(windows)]
@@ -2192,6 +2755,74 @@ fn main() {
                 }
             }
             
+            // 卸载清理模式（卸载前以 --cleanup-machine-artifacts 启动）：在迁移之前执行，避免重新创建数据目录
+            if services::machine_cleanup::cleanup_requested() {
+                if let Some(window) = app.get_window("main") {
+                    let _ = window.hide();
+                }
+                let code = services::machine_cleanup::run_from_command_line(&app.handle());
+                app.handle().exit(code);
+                return Ok(());
+            }
+
+            // 本地数据格式迁移（需在任何后台任务读取 meta 状态之前同步完成）
+            match services::migrations::run_startup_migrations() {
+                Ok(report) if !report.applied.is_empty() => {
+                    eprintln!("[Migrations] data format v{} -> v{} steps={}", report.from_version, report.to_version, report.applied.len());
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("[Migrations] startup migration failed: {}", e),
+            }
+
+            // 自检模式（CI / 现场验证以 --self-test 启动）：不显示窗口、不启动后台任务，写出报告后以退出码表示结果
+            if services::self_test::self_test_requested() {
+                if let Some(window) = app.get_window("main") {
+                    let _ = window.hide();
+                }
+                let app_handle = app.handle();
+                tauri::async_runtime::spawn(async move {
+                    let code = services::self_test::run_from_command_line(&app_handle).await;
+                    app_handle.exit(code);
+                });
+                return Ok(());
+            }
+
+            // 委托安装 worker（计划任务以 --run-delegated-install 启动）：不显示窗口、不启动后台任务，处理完请求后退出
+            if services::delegated_install::worker_requested() {
+                if let Some(window) = app.get_window("main") {
+                    let _ = window.hide();
+                }
+                let app_handle = app.handle();
+                tauri::async_runtime::spawn(async move {
+                    services::delegated_install::run_worker(app_handle.clone()).await;
+                    app_handle.exit(0);
+                });
+                return Ok(());
+            }
+
+            // 启动完整性自检（内嵌资源哈希），结果随 get_system_info 返回
+            std::thread::spawn(|| {
+                services::integrity_service::run_startup_check();
+            });
+            
+            // 机器登记上报（仅 fleetReporting.enabled 时发送）
+            tauri::async_runtime::spawn(services::fleet_service::register_on_startup());
+            
+            // 回传上次遗留的安装统计（仅 installFeedback.enabled 时发送）
+            tauri::async_runtime::spawn(services::feedback_service::flush_on_startup());
+            
+            // 子进程看门狗：清理崩溃遗留 / 挂起的 PowerShell 等子进程
+            tauri::async_runtime::spawn(services::watchdog_service::run_watchdog());
+            
+            // WebView 进程监控：界面进程崩溃 / WebView2 运行时更新后自动重新加载或重启
+            services::webview_recovery::install(&app.handle());
+            
+            // 打印机后台巡检（printerWatch.mode 为 auto / notify-only 时定期比对并修复 / 提示）
+            tauri::async_runtime::spawn(services::printer_watch::run_printer_watch(app.handle()));
+            
+            // 以管理员身份启动时，继续上次因未提权失败的安装任务
+            tauri::async_runtime::spawn(services::elevation_resume::resume_on_startup(app.handle()));
+            
             // 启动后延迟 800ms 发送进度事件自检
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -2253,6 +2884,12 @@ fn main() {
               || error_str.contains("failed to create webview")
               || error_str.contains("cannot find the file specified");
 
+          // 缺少 WebView2 时先提供自动下载安装（成功后重新启动应用，不再返回）；非交互模式不询问
+          #[cfg(windows)]
+          if is_webview2_error && !non_interactive {
+              platform::windows::webview2_bootstrap::offer_install_and_relaunch(WEBVIEW2_DOWNLOAD_URL);
+          }
+
           if is_webview2_error {
             error_msg = format!(
                 "WebView2 运行时未安装或版本不兼容\n\n\
@@ -2285,9 +2922,9 @@ fn main() {
             );
         }
         
-        // 在 Windows 上使用消息框显示错误
+        // 在 Windows 上使用消息框显示错误（非交互模式只写 stderr / error.log）
         #[cfg(windows)]
-        {
+        if !non_interactive {
             use winapi::um::winuser::{MessageBoxW, MB_OK, MB_ICONERROR};
             use std::ffi::OsStr;
             use std::os::windows::ffi::OsStrExt;
@@ -2312,9 +2949,8 @@ fn main() {
             }
         }
         
-        // 非 Windows 平台或备用方式：输出到 stderr
-        #[cfg(not(windows))]
-        {
+        // 非交互模式、非 Windows 平台或备用方式：输出到 stderr
+        if non_interactive || cfg!(not(windows)) {
             eprintln!("{}", error_msg);
         }
         
@@ -2329,7 +2965,11 @@ fn main() {
             }
         }
         
-        // 退出程序
-        std::process::exit(1);
+        // 退出程序（退出码区分失败原因，供 CLI / 远程命令判断）
+        std::process::exit(if is_webview2_error {
+            services::interaction_mode::EXIT_WEBVIEW2_MISSING
+        } else {
+            services::interaction_mode::EXIT_STARTUP_FAILED
+        });
     }
 }
@@ -0,0 +1,260 @@
+// macOS 平台 AirPrint（IPP Everywhere）能力检测模块
+// 通过系统自带的 dns-sd 命令浏览 Bonjour _ipp._tcp 服务，找到目标地址对应的广播并读取 TXT 记录：
+//
+// - detect_airprint(): 浏览 → 逐个解析（dns-sd -L）→ 按解析出的主机 IP 匹配目标 → 判断 TXT
+// - TXT 中含 URF（且不为 none），或 pdl 含 image/urf / image/pwg-raster，视为支持 AirPrint，
+//   可直接 lpadmin -m everywhere 免驱安装
+// - dns-sd 无法执行或整体检测超时返回 Unavailable（能力未知），由调用方沿用默认的安装顺序
+
+use std::collections::HashSet;
+use std::io::{BufRead, BufReader};
+use std::net::{IpAddr, ToSocketAddrs};
+use std::process::{Command, Stdio};
+use std::sync::mpsc;
+use std::time::{Duration, Instant};
+
+const IPP_SERVICE_TYPE: &str = "_ipp._tcp";
+
+/// 浏览服务的时长（dns-sd -B 不会自行退出）
+const BROWSE_DURATION_MS: u64 = 2_000;
+
+/// 单个服务解析的超时
+const LOOKUP_TIMEOUT_MS: u64 = 1_500;
+
+/// 整体检测超时（服务较多的网络中不无限解析下去）
+const DETECT_TIMEOUT_MS: u64 = 8_000;
+
+/// 已解析并匹配目标地址的 AirPrint 服务
+#[derive(Debug, Clone)]
+pub struct AirPrintService {
+    /// Bonjour 服务实例名
+    pub instance_name: String,
+    /// 广播的主机名（如 HP1A2B3C.local.）
+    pub host_name: String,
+    pub port: u16,
+    /// TXT rp（IPP 资源路径，如 ipp/print）
+    pub resource_path: Option<String>,
+}
+
+/// AirPrint 检测结果
+#[derive(Debug, Clone)]
+pub enum AirPrintStatus {
+    /// 目标地址广播了 AirPrint 能力
+    Capable(AirPrintService),
+    /// 浏览完成，但目标地址没有广播或 TXT 不含 AirPrint 能力（字符串为说明）
+    NotAdvertised(String),
+    /// dns-sd 不可用或浏览失败（字符串为原因）
+    Unavailable(String),
+}
+
+/// 执行 dns-sd 并在截止时间到达或 stop 返回 true 时结束进程，返回已读取的输出行
+fn run_dns_sd(args: &[&str], duration: Duration, stop: impl Fn(&[String]) -> bool) -> Result<Vec<String>, String> {
+    let mut child = Command::new("dns-sd")
+        .args(args)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .map_err(|e| format!("执行 dns-sd 失败: {}", e))?;
+
+    let stdout = child.stdout.take().ok_or_else(|| "无法读取 dns-sd 输出".to_string())?;
+    let (tx, rx) = mpsc::channel();
+    std::thread::spawn(move || {
+        for line in BufReader::new(stdout).lines().map_while(Result::ok) {
+            if tx.send(line).is_err() {
+                break;
+            }
+        }
+    });
+
+    let deadline = Instant::now() + duration;
+    let mut lines = Vec::new();
+    loop {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            break;
+        }
+        match rx.recv_timeout(remaining) {
+            Ok(line) => {
+                lines.push(line);
+                if stop(&lines) {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    let _ = child.kill();
+    let _ = child.wait();
+    Ok(lines)
+}
+
+/// 从 dns-sd -B 输出中取出新增的服务实例名（去重，保持出现顺序）
+fn parse_browse_instances(lines: &[String]) -> Vec<String> {
+    let mut seen = HashSet::new();
+    let mut instances = Vec::new();
+    for line in lines {
+        if line.split_whitespace().nth(1) != Some("Add") {
+            continue;
+        }
+        let marker = format!("{}.", IPP_SERVICE_TYPE);
+        let Some(pos) = line.find(&marker) else {
+            continue;
+        };
+        let name = line[pos + marker.len()..].trim().to_string();
+        if !name.is_empty() && seen.insert(name.clone()) {
+            instances.push(name);
+        }
+    }
+    instances
+}
+
+/// 按未转义的空格拆分 TXT 行（dns-sd 以 `\ ` 表示值中的空格）
+fn split_txt_tokens(line: &str) -> Vec<String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut chars = line.chars();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    current.push(next);
+                }
+            }
+            c if c.is_whitespace() => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+/// 解析 dns-sd -L 输出，返回 (主机名, 端口, TXT 键值)；TXT 键统一转为小写
+fn parse_lookup(lines: &[String]) -> Option<(String, u16, Vec<(String, String)>)> {
+    const REACHED: &str = "can be reached at ";
+    let index = lines.iter().position(|line| line.contains(REACHED))?;
+    let line = &lines[index];
+    let target = line[line.find(REACHED)? + REACHED.len()..]
+        .split_whitespace()
+        .next()?;
+    let (host, port) = target.rsplit_once(':')?;
+    let port = port.parse::<u16>().ok()?;
+
+    let txt = lines[index + 1..]
+        .iter()
+        .take_while(|l| l.starts_with(' ') || l.starts_with('\t'))
+        .flat_map(|l| split_txt_tokens(l))
+        .filter_map(|token| {
+            token
+                .split_once('=')
+                .map(|(k, v)| (k.to_ascii_lowercase(), v.to_string()))
+        })
+        .collect();
+    Some((host.to_string(), port, txt))
+}
+
+/// TXT 记录是否表明支持 AirPrint（URF 栅格或 PWG 栅格）
+fn txt_has_airprint(txt: &[(String, String)]) -> bool {
+    let value = |key: &str| txt.iter().find(|(k, _)| k == key).map(|(_, v)| v.trim().to_ascii_lowercase());
+    let urf = value("urf").is_some_and(|v| !v.is_empty() && v != "none");
+    let pdl = value("pdl").is_some_and(|v| v.contains("image/urf") || v.contains("image/pwg-raster"));
+    urf || pdl
+}
+
+/// 解析主机名得到的全部 IP（.local 由系统 mDNSResponder 解析）
+fn resolve_ips(host: &str, port: u16) -> HashSet<IpAddr> {
+    let host = host.trim_end_matches('.');
+    if let Ok(ip) = host.split('%').next().unwrap_or_default().parse::<IpAddr>() {
+        return HashSet::from([ip]);
+    }
+    (host, port)
+        .to_socket_addrs()
+        .map(|addrs| addrs.map(|a| a.ip()).collect())
+        .unwrap_or_default()
+}
+
+/// 检测目标地址是否广播了 AirPrint 能力（阻塞调用，最长约 DETECT_TIMEOUT_MS）
+///
+/// # 参数
+/// - `host`: 打印机主机地址（IP 或主机名，与安装时探测的地址一致）
+/// - `port`: 打印机 IPP 端口（用于解析主机名，不参与匹配）
+///
+/// # 返回
+/// - `AirPrintStatus::Capable`: 找到目标的广播且 TXT 含 AirPrint 能力
+/// - `AirPrintStatus::NotAdvertised`: 浏览完成但未匹配到 / TXT 不含 AirPrint 能力
+/// - `AirPrintStatus::Unavailable`: dns-sd 无法执行或检测超时
+pub fn detect_airprint(host: &str, port: u16) -> AirPrintStatus {
+    let started = Instant::now();
+    let target_ips = resolve_ips(host, port);
+    let target_name = host.trim_end_matches('.').to_ascii_lowercase();
+
+    let browse = match run_dns_sd(
+        &["-B", IPP_SERVICE_TYPE, "local."],
+        Duration::from_millis(BROWSE_DURATION_MS),
+        |_| false,
+    ) {
+        Ok(lines) => lines,
+        Err(e) => return AirPrintStatus::Unavailable(e),
+    };
+    let instances = parse_browse_instances(&browse);
+    eprintln!(
+        "[AirPrint] browse host=\"{}\" target_ips={:?} instances={}",
+        host,
+        target_ips,
+        instances.len()
+    );
+
+    for (index, instance) in instances.iter().enumerate() {
+        if started.elapsed() > Duration::from_millis(DETECT_TIMEOUT_MS) {
+            return AirPrintStatus::Unavailable(format!(
+                "检测超时（已解析 {} / {} 个服务）",
+                index,
+                instances.len()
+            ));
+        }
+
+        let lines = match run_dns_sd(
+            &["-L", instance, IPP_SERVICE_TYPE, "local."],
+            Duration::from_millis(LOOKUP_TIMEOUT_MS),
+            |lines| lines.last().is_some_and(|l| l.starts_with(' ') || l.starts_with('\t')),
+        ) {
+            Ok(lines) => lines,
+            Err(e) => return AirPrintStatus::Unavailable(e),
+        };
+        let Some((host_name, service_port, txt)) = parse_lookup(&lines) else {
+            continue;
+        };
+
+        let same_name = host_name.trim_end_matches('.').eq_ignore_ascii_case(&target_name);
+        let same_ip = !target_ips.is_empty() && !resolve_ips(&host_name, service_port).is_disjoint(&target_ips);
+        if !same_name && !same_ip {
+            continue;
+        }
+
+        eprintln!(
+            "[AirPrint] matched instance=\"{}\" host_name=\"{}\" port={} txt={:?}",
+            instance, host_name, service_port, txt
+        );
+        if !txt_has_airprint(&txt) {
+            return AirPrintStatus::NotAdvertised(format!("服务 \"{}\" 的 TXT 记录不含 URF / PWG 栅格能力", instance));
+        }
+        let resource_path = txt
+            .iter()
+            .find(|(k, _)| k == "rp")
+            .map(|(_, v)| v.trim().trim_start_matches('/').to_string());
+        return AirPrintStatus::Capable(AirPrintService {
+            instance_name: instance.clone(),
+            host_name,
+            port: service_port,
+            resource_path,
+        });
+    }
+
+    AirPrintStatus::NotAdvertised(format!("未发现 {} 的 Bonjour 打印服务广播", host))
+}
@@ -1,5 +1,12 @@
-use std::ffi::{CStr, CString};
-use std::os::raw::{c_char, c_int};
+// macOS 平台 CUPS C API 绑定模块
+// 通过 libcups 的 cupsEnumDests 枚举已添加的打印队列，直接读取结构化的目的地与选项：
+//
+// - 不解析 lpstat 文本输出（本地化输出、含空格的队列名都会导致解析错误）
+// - 只枚举本机已添加的队列（mask 排除 Bonjour 发现但未添加的打印机）；lpoptions 实例（name/instance）与队列共用同一个系统队列，跳过
+// - 回调中的 cups_dest_t 只在回调期间有效，字段在回调内复制
+
+use std::ffi::{c_void, CStr, CString};
+use std::os::raw::{c_char, c_int, c_uint};
 
 #[derive(Debug, Clone)]
 pub struct SystemPrinter {
@@ -25,14 +32,35 @@ struct cups_dest_t {
     options: *mut cups_option_t,
 }
 
+type cups_dest_cb_t = extern "C" fn(user_data: *mut c_void, flags: c_uint, dest: *mut cups_dest_t) -> c_int;
+
+/// cups_dest_flags_t
+const CUPS_DEST_FLAGS_NONE: c_uint = 0x00;
+const CUPS_DEST_FLAGS_REMOVED: c_uint = 0x04;
+const CUPS_DEST_FLAGS_ERROR: c_uint = 0x08;
+
+/// cups_ptype_t：网络发现但未添加到本机的打印机
+const CUPS_PRINTER_DISCOVERED: c_uint = 0x0100_0000;
+
+/// 枚举超时（本机队列立即返回，超时只限制 Bonjour 浏览的等待时间）
+const ENUM_TIMEOUT_MS: c_int = 1000;
+
 extern "C" {
-    fn cupsGetDests(dests: *mut *mut cups_dest_t) -> c_int;
-    fn cupsFreeDests(num_dests: c_int, dests: *mut cups_dest_t);
+    fn cupsEnumDests(
+        flags: c_uint,
+        msec: c_int,
+        cancel: *mut c_int,
+        type_: c_uint,
+        mask: c_uint,
+        cb: cups_dest_cb_t,
+        user_data: *mut c_void,
+    ) -> c_int;
     fn cupsGetOption(
         name: *const c_char,
         num_options: c_int,
         options: *const cups_option_t,
     ) -> *const c_char;
+    fn cupsLastErrorString() -> *const c_char;
 }
 
 fn cstr_to_string(ptr: *const c_char) -> Option<String> {
@@ -68,44 +96,68 @@ unsafe fn get_option(
     cstr_to_string(value_ptr)
 }
 
-pub fn list_printers_via_cups() -> Result<Vec<SystemPrinter>, String> {
-    let mut dests_ptr: *mut cups_dest_t = std::ptr::null_mut();
-    let num_dests = unsafe { cupsGetDests(&mut dests_ptr) };
-    if num_dests < 0 {
-        return Err(format!("cupsGetDests failed count={}", num_dests));
-    }
-    if dests_ptr.is_null() || num_dests == 0 {
-        return Ok(Vec::new());
+/// 复制单个目的地的字段（实例与无效名称返回 None）
+unsafe fn read_dest(dest: &cups_dest_t) -> Option<SystemPrinter> {
+    if !dest.instance.is_null() {
+        return None;
     }
+    let name = match cstr_to_string(dest.name) {
+        Some(name) if !name.is_empty() => name,
+        _ => {
+            eprintln!("[MacOS][CupsFfi] WARN skip destination with empty or non-UTF-8 name");
+            return None;
+        }
+    };
+    let display_name = get_option("printer-info", dest.num_options, dest.options)
+        .filter(|info| !info.trim().is_empty())
+        .or_else(|| Some(name.clone()));
+    let device_uri = get_option("device-uri", dest.num_options, dest.options);
+    let is_accepting_jobs = get_option("printer-is-accepting-jobs", dest.num_options, dest.options)
+        .and_then(|v| parse_bool(&v));
+    let state = get_option("printer-state", dest.num_options, dest.options).and_then(|v| parse_i32(&v));
+    Some(SystemPrinter {
+        system_queue_name: name,
+        display_name,
+        device_uri,
+        is_accepting_jobs,
+        state,
+    })
+}
 
-    let dests = unsafe { std::slice::from_raw_parts(dests_ptr, num_dests as usize) };
-    let mut printers = Vec::new();
-
-    for dest in dests {
-        let name = cstr_to_string(dest.name).unwrap_or_default();
-        if name.is_empty() {
-            continue;
+extern "C" fn collect_dest(user_data: *mut c_void, flags: c_uint, dest: *mut cups_dest_t) -> c_int {
+    if user_data.is_null() || dest.is_null() || flags & (CUPS_DEST_FLAGS_REMOVED | CUPS_DEST_FLAGS_ERROR) != 0 {
+        return 1;
+    }
+    let printers = unsafe { &mut *(user_data as *mut Vec<SystemPrinter>) };
+    if let Some(printer) = unsafe { read_dest(&*dest) } {
+        if !printers.iter().any(|p| p.system_queue_name == printer.system_queue_name) {
+            printers.push(printer);
         }
-        let display_name = unsafe { get_option("printer-info", dest.num_options, dest.options) }
-            .or_else(|| Some(name.clone()));
-        let device_uri = unsafe { get_option("device-uri", dest.num_options, dest.options) };
-        let is_accepting_jobs = unsafe {
-            get_option("printer-is-accepting-jobs", dest.num_options, dest.options)
-                .and_then(|v| parse_bool(&v))
-        };
-        let state = unsafe {
-            get_option("printer-state", dest.num_options, dest.options).and_then(|v| parse_i32(&v))
-        };
-
-        printers.push(SystemPrinter {
-            system_queue_name: name,
-            display_name,
-            device_uri,
-            is_accepting_jobs,
-            state,
-        });
     }
+    1
+}
 
-    unsafe { cupsFreeDests(num_dests, dests_ptr) };
+/// 枚举本机已添加的打印队列
+///
+/// # 返回
+/// - `Ok(Vec<SystemPrinter>)`: 队列列表（无队列时为空）
+/// - `Err(String)`: cupsEnumDests 失败（如 CUPS 调度程序未运行）
+pub fn list_printers_via_cups() -> Result<Vec<SystemPrinter>, String> {
+    let mut printers: Vec<SystemPrinter> = Vec::new();
+    let ok = unsafe {
+        cupsEnumDests(
+            CUPS_DEST_FLAGS_NONE,
+            ENUM_TIMEOUT_MS,
+            std::ptr::null_mut(),
+            0,
+            CUPS_PRINTER_DISCOVERED,
+            collect_dest,
+            &mut printers as *mut Vec<SystemPrinter> as *mut c_void,
+        )
+    };
+    if ok == 0 && printers.is_empty() {
+        let error = cstr_to_string(unsafe { cupsLastErrorString() }).unwrap_or_default();
+        return Err(format!("cupsEnumDests failed error=\"{}\"", error));
+    }
     Ok(printers)
 }
@@ -134,7 +134,16 @@ pub fn delete_printer_macos(printer_name: &str, _remove_port: bool) -> Result<De
                 evidence: Some(format!("lpadmin -x failed (not found): stderr={}", stderr.chars().take(200).collect::<String>())),
             })
         } else {
-            let error_msg = if !stderr.is_empty() {
+            let classified = crate::platform::lpadmin_errors::classify_lpadmin_error(&stderr);
+            let error_msg = if let Some(info) = classified {
+                format!(
+                    "[EASYPRINTER_CODE={}] 删除打印机失败：{}\n\n{}\n\nlpadmin: {}",
+                    info.code,
+                    info.message_zh,
+                    info.remediation_zh,
+                    stderr.trim().chars().take(200).collect::<String>()
+                )
+            } else if !stderr.is_empty() {
                 format!("lpadmin 执行失败: {}", stderr.chars().take(200).collect::<String>())
             } else {
                 format!("lpadmin 执行失败: exit_code={}", output.status.code().unwrap_or(-1))
@@ -1,6 +1,7 @@
 use serde_json::Value;
 use std::{
-    net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs},
+    net::{IpAddr, Ipv4Addr, SocketAddr, TcpStream, ToSocketAddrs},
+    os::unix::net::UnixStream,
     process::Command,
     sync::Once,
     time::{Duration, Instant},
@@ -8,8 +9,9 @@ use std::{
 use sha2::{Digest, Sha256};
 use tauri::AppHandle;
 use tokio::time::{sleep, timeout, Duration as TokioDuration};
-use url::Url;
 
+use super::airprint::{self, AirPrintStatus};
+use crate::platform::lpadmin_errors::{classify_lpadmin_error, lpadmin_error_by_code, MAC_CUPS_UNAVAILABLE};
 use crate::install_event_emitter::StepReporter;
 
 /// Generate a monotonic-ish timestamp in milliseconds
@@ -31,6 +33,10 @@ struct DeviceTarget {
     uri: String,
     host: String,
     port: u16,
+    /// URI 中的主机部分（IPv6 带方括号）
+    uri_host: String,
+    /// URI 是否由主机地址推导（ipp://host/ipp/print），而非配置中显式写出
+    derived_uri: bool,
 }
 
 struct StepError {
@@ -53,6 +59,13 @@ const LPADMIN_TIMEOUT_MS: u64 = 20_000;
 const FINAL_VERIFY_TIMEOUT_SECS: u64 = 10;
 const FINAL_VERIFY_RETRY_MS: u64 = 500;
 
+/// cupsd 的本地套接字（launchd 按需启动 cupsd，连接该套接字即会拉起调度程序）
+const CUPSD_SOCKET_PATH: &str = "/private/var/run/cupsd";
+const CUPSD_PORT: u16 = 631;
+
+/// CUPS 自带的 Generic PostScript PPD：目标未广播 AirPrint 时的 PPD 安装回退
+const GENERIC_PPD_MODEL: &str = "drv:///sample.drv/generic.ppd";
+
 async fn run_cmd(cmd: &str, args: &[&str], timeout_ms: u64) -> Result<CmdOutput, String> {
     let cmd_owned = cmd.to_string();
     let args_owned: Vec<String> = args.iter().map(|s| s.to_string()).collect();
@@ -109,15 +122,41 @@ fn truncate_text(input: &str, max_len: usize) -> String {
     truncated
 }
 
-fn is_privilege_error(stderr: &str) -> bool {
-    let lower = stderr.to_lowercase();
-    lower.contains("not authorized")
-        || lower.contains("not permitted")
-        || lower.contains("permission denied")
-        || lower.contains("privilege")
-        || lower.contains("operation not permitted")
-        || lower.contains("authentication")
-        || lower.contains("sudo")
+/// 创建队列前检查 CUPS 调度程序是否可用（连接可能阻塞最多 2 秒，放到阻塞线程执行）
+async fn check_cupsd() -> Result<(), StepError> {
+    tokio::task::spawn_blocking(check_cupsd_blocking)
+        .await
+        .unwrap_or_else(|e| {
+            Err(StepError {
+                code: MAC_CUPS_UNAVAILABLE,
+                detail: format!("cupsd 检测任务异常: {}", e),
+            })
+        })
+}
+
+/// 本地套接字或 localhost:631 任一可连接即认为 cupsd 可用
+fn check_cupsd_blocking() -> Result<(), StepError> {
+    if UnixStream::connect(CUPSD_SOCKET_PATH).is_ok() {
+        return Ok(());
+    }
+    let local = SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), CUPSD_PORT);
+    TcpStream::connect_timeout(&local, Duration::from_secs(2))
+        .map(|_| ())
+        .map_err(|e| StepError {
+            code: MAC_CUPS_UNAVAILABLE,
+            detail: format!(
+                "cupsd 不可用：无法连接 {} 与 localhost:{} ({})",
+                CUPSD_SOCKET_PATH, CUPSD_PORT, e
+            ),
+        })
+}
+
+/// 创建队列失败时给用户的提示（已收录的错误附带处理建议）
+fn queue_failure_message(code: &str) -> String {
+    match lpadmin_error_by_code(code) {
+        Some(info) => format!("创建队列失败：{}\n\n{}", info.message_zh, info.remediation_zh),
+        None => "创建队列失败".to_string(),
+    }
 }
 
 fn sanitize_queue_name(input: &str) -> String {
@@ -204,80 +243,76 @@ fn is_queue_not_found(stderr: &str) -> bool {
     zh_patterns.iter().any(|p| stderr.contains(p))
 }
 
-fn default_port_for_scheme(scheme: &str) -> u16 {
-    match scheme {
-        "ipp" | "ipps" => 631,
-        "socket" => 9100,
-        "lpd" => 515,
-        _ => 631,
-    }
+fn parse_device_target(path: &str, fallback_name: &str) -> Result<DeviceTarget, String> {
+    let raw = if path.trim().is_empty() { fallback_name } else { path };
+    let address = crate::platform::printer_address::PrinterAddress::parse(raw)?;
+
+    Ok(DeviceTarget {
+        uri: address.device_uri(),
+        host: address.host_address(),
+        port: address.device_port(),
+        uri_host: address.uri_host(),
+        derived_uri: !matches!(address, crate::platform::printer_address::PrinterAddress::IppUrl(_)),
+    })
 }
 
-fn normalize_uri_input(raw: &str) -> String {
-    let trimmed = raw.trim();
-    if trimmed.starts_with("ipp:") && !trimmed.starts_with("ipp://") {
-        return format!("ipp://{}", trimmed.trim_start_matches("ipp:"));
-    }
-    if trimmed.starts_with("ipps:") && !trimmed.starts_with("ipps://") {
-        return format!("ipps://{}", trimmed.trim_start_matches("ipps:"));
-    }
-    if trimmed.starts_with("socket:") && !trimmed.starts_with("socket://") {
-        return format!("socket://{}", trimmed.trim_start_matches("socket:"));
-    }
-    if trimmed.starts_with("lpd:") && !trimmed.starts_with("lpd://") {
-        return format!("lpd://{}", trimmed.trim_start_matches("lpd:"));
+/// 检测目标的 AirPrint 能力（仅 ipp / ipps 设备 URI 可使用 IPP Everywhere）
+async fn detect_airprint_for(target: &DeviceTarget) -> AirPrintStatus {
+    let scheme = target.uri.split("://").next().unwrap_or_default().to_ascii_lowercase();
+    if scheme != "ipp" && scheme != "ipps" {
+        return AirPrintStatus::NotAdvertised(format!("{}:// 设备 URI 不支持 IPP Everywhere", scheme));
     }
-    trimmed.to_string()
+
+    let (host, port) = (target.host.clone(), target.port);
+    tokio::task::spawn_blocking(move || airprint::detect_airprint(&host, port))
+        .await
+        .unwrap_or_else(|e| AirPrintStatus::Unavailable(format!("检测任务异常: {}", e)))
 }
 
-fn parse_host_port(raw: &str) -> Result<(String, Option<u16>), String> {
-    let cleaned = raw
-        .trim()
-        .trim_start_matches('\\')
-        .trim_start_matches('/')
-        .split('/')
-        .next()
-        .unwrap_or("")
-        .to_string();
-
-    if cleaned.is_empty() {
-        return Err("设备地址为空".to_string());
-    }
+/// 按 AirPrint 广播修正由主机地址推导的 URI（资源路径 / 端口以 TXT rp 与服务端口为准）
+fn airprint_device_uri(target: &DeviceTarget, status: &AirPrintStatus) -> String {
+    let service = match status {
+        AirPrintStatus::Capable(service) if target.derived_uri => service,
+        _ => return target.uri.clone(),
+    };
+    let Some(resource_path) = service.resource_path.as_deref() else {
+        return target.uri.clone();
+    };
+    let scheme = if target.uri.starts_with("ipps://") { "ipps" } else { "ipp" };
+    let port = if service.port == 631 { String::new() } else { format!(":{}", service.port) };
+    format!("{}://{}{}/{}", scheme, target.uri_host, port, resource_path)
+}
 
-    if let Some((host, port_str)) = cleaned.rsplit_once(':') {
-        if port_str.chars().all(|c| c.is_ascii_digit()) {
-            let port = port_str.parse::<u16>().map_err(|_| "端口号无效".to_string())?;
-            return Ok((host.to_string(), Some(port)));
-        }
+/// lpadmin 的驱动尝试顺序
+///
+/// - 广播了 AirPrint：优先 IPP Everywhere，失败再回退 PPD
+/// - 未广播：直接使用 PPD（IPP Everywhere 必然无法获取打印机属性）
+/// - 检测不可用：沿用 IPP Everywhere → raw 的默认顺序
+fn model_attempts(status: &AirPrintStatus) -> Vec<Vec<&'static str>> {
+    match status {
+        AirPrintStatus::Capable(_) => vec![
+            vec!["-m", "everywhere"],
+            vec!["-m", GENERIC_PPD_MODEL],
+            vec!["-m", "raw"],
+            vec![],
+        ],
+        AirPrintStatus::NotAdvertised(_) => vec![vec!["-m", GENERIC_PPD_MODEL], vec!["-m", "raw"], vec![]],
+        AirPrintStatus::Unavailable(_) => vec![vec!["-m", "everywhere"], vec!["-m", "raw"], vec![]],
     }
-
-    Ok((cleaned, None))
 }
 
-fn parse_device_target(path: &str, fallback_name: &str) -> Result<DeviceTarget, String> {
-    let raw = if path.trim().is_empty() { fallback_name } else { path };
-    let normalized = normalize_uri_input(raw);
-
-    if normalized.contains("://") {
-        let url = Url::parse(&normalized).map_err(|e| format!("无法解析设备 URI: {}", e))?;
-        let host = url.host_str().ok_or_else(|| "URI 缺少 host".to_string())?.to_string();
-        let port = url.port().unwrap_or(default_port_for_scheme(url.scheme()));
-        return Ok(DeviceTarget {
-            uri: normalized,
-            host,
-            port,
-        });
+fn airprint_meta_value(status: &AirPrintStatus) -> Value {
+    match status {
+        AirPrintStatus::Capable(service) => serde_json::json!({
+            "status": "capable",
+            "instanceName": service.instance_name,
+            "hostName": service.host_name,
+            "port": service.port,
+            "resourcePath": service.resource_path,
+        }),
+        AirPrintStatus::NotAdvertised(reason) => serde_json::json!({ "status": "not_advertised", "reason": reason }),
+        AirPrintStatus::Unavailable(reason) => serde_json::json!({ "status": "unavailable", "reason": reason }),
     }
-
-    let (host, port_opt) = parse_host_port(&normalized)?;
-    let port = port_opt.unwrap_or(631);
-    let uri = if port == 631 {
-        format!("ipp://{}/ipp/print", host)
-    } else {
-        format!("ipp://{}:{}/ipp/print", host, port)
-    };
-
-    Ok(DeviceTarget { uri, host, port })
 }
 
 fn build_meta(
@@ -372,13 +407,14 @@ fn build_queue_name(printer_name: &str, device_uri: &str) -> String {
     }
 }
 
-async fn ensure_queue(queue: &str, uri: &str, display_name: &str) -> Result<(), StepError> {
+/// 创建 / 更新队列，依次尝试 attempts 中的驱动参数，返回成功时使用的驱动（未指定 -m 时为 "default"）
+async fn ensure_queue(
+    queue: &str,
+    uri: &str,
+    display_name: &str,
+    attempts: &[Vec<&'static str>],
+) -> Result<&'static str, StepError> {
     let base_args = ["-p", queue, "-E", "-v", uri, "-D", display_name];
-    let attempts = [
-        vec!["-m", "everywhere"],
-        vec!["-m", "raw"],
-        vec![],
-    ];
     let mut last_stderr = None;
     let mut last_exit_code = None;
 
@@ -400,14 +436,15 @@ async fn ensure_queue(queue: &str, uri: &str, display_name: &str) -> Result<(),
                 detail: e,
             })?;
         if output.success {
-            return Ok(());
+            return Ok(extra.get(1).copied().unwrap_or("default"));
         }
 
         last_stderr = Some(output.stderr.clone());
         last_exit_code = output.exit_code;
-        if is_privilege_error(&output.stderr) {
+        // 权限不足、cupsd 不可用、队列名无效时换驱动重试也不会成功
+        if let Some(info) = classify_lpadmin_error(&output.stderr).filter(|info| info.fatal) {
             return Err(StepError {
-                code: "E_NO_PRIVILEGE",
+                code: info.code,
                 detail: format!(
                     "queue_name={} exit_code={:?} stderr={}",
                     queue,
@@ -418,8 +455,13 @@ async fn ensure_queue(queue: &str, uri: &str, display_name: &str) -> Result<(),
         }
     }
 
+    let code = last_stderr
+        .as_deref()
+        .and_then(classify_lpadmin_error)
+        .map(|info| info.code)
+        .unwrap_or("E_QUEUE_CREATE_FAILED");
     Err(StepError {
-        code: "E_QUEUE_CREATE_FAILED",
+        code,
         detail: format!(
             "queue_name={} exit_code={:?} stderr={}",
             queue,
@@ -548,6 +590,11 @@ pub async fn install_printer_macos(
                 stderr: None,
                 effective_dry_run: dry_run,
                 job_id,
+                log_path: None,
+                validation_errors: None,
+                attempts: None,
+                verified: false,
+                verification: None,
             });
         }
     };
@@ -629,6 +676,11 @@ pub async fn install_printer_macos(
             stderr: None,
             effective_dry_run: dry_run,
             job_id,
+            log_path: None,
+            validation_errors: None,
+            attempts: None,
+            verified: false,
+            verification: None,
         });
     }
 
@@ -637,45 +689,73 @@ pub async fn install_printer_macos(
         eprintln!("[InstallPrinterMacOS] device.probe success emit failed: {}", err);
     }
 
-    reporter.set_default_meta(Some(build_meta(
+    if let Err(err) = reporter.emit_step_running("device.ensureQueue", "正在检测 AirPrint 能力并创建打印队列 (需要管理员权限)", None) {
+        eprintln!("[InstallPrinterMacOS] device.ensureQueue running emit failed: {}", err);
+    }
+
+    // cupsd 不可用时 lpadmin 只会报 "Unable to connect to server"，提前检查并给出启动建议
+    let cupsd_status = if dry_run { Ok(()) } else { check_cupsd().await };
+
+    // 广播 AirPrint 的设备直接使用 IPP Everywhere（免驱），否则回退 PPD 安装
+    let airprint_status = if dry_run {
+        AirPrintStatus::Unavailable("dry run 跳过检测".to_string())
+    } else if cupsd_status.is_err() {
+        AirPrintStatus::Unavailable("cupsd 不可用，跳过检测".to_string())
+    } else {
+        detect_airprint_for(&target).await
+    };
+    let device_uri = airprint_device_uri(&target, &airprint_status);
+    eprintln!(
+        "[InstallPrinterMacOS] airprint status={:?} device_uri=\"{}\"",
+        airprint_status, device_uri
+    );
+
+    let mut ensure_meta = build_meta(
         &queue_name,
         &target.host,
-        Some(&target.uri),
+        Some(&device_uri),
         &requested_mode_lower,
         &effective_mode,
-    )));
-
-    if let Err(err) = reporter.emit_step_running("device.ensureQueue", "正在创建打印队列 (需要管理员权限)", None) {
-        eprintln!("[InstallPrinterMacOS] device.ensureQueue running emit failed: {}", err);
+    );
+    if let Value::Object(map) = &mut ensure_meta {
+        map.insert("airprint".into(), airprint_meta_value(&airprint_status));
     }
+    reporter.set_default_meta(Some(ensure_meta));
 
     let ensure_result = if dry_run {
-        Ok(())
+        Ok("dry_run")
+    } else if let Err(err) = cupsd_status {
+        Err(err)
     } else {
-        ensure_queue(&queue_name, &target.uri, &name).await
+        ensure_queue(&queue_name, &device_uri, &name, &model_attempts(&airprint_status)).await
     };
 
-    if let Err(err) = ensure_result {
-        let message = match err.code {
-            "E_NO_PRIVILEGE" => "创建队列失败：需要管理员权限".to_string(),
-            _ => "创建队列失败".to_string(),
-        };
-        let _ = reporter.emit_step_fail("device.ensureQueue", err.code, &message, Some(&err.detail));
-        let _ = reporter.emit_job_failed(err.code, &message, Some(&err.detail));
-        let _ = reporter.emit_job_done(false, Some(&message));
+    let model = match ensure_result {
+        Ok(model) => model,
+        Err(err) => {
+            let message = queue_failure_message(err.code);
+            let _ = reporter.emit_step_fail("device.ensureQueue", err.code, &message, Some(&err.detail));
+            let _ = reporter.emit_job_failed(err.code, &message, Some(&err.detail));
+            let _ = reporter.emit_job_done(false, Some(&message));
 
-        return Ok(crate::InstallResult {
-            success: false,
-            message,
-            method: Some("driverless".into()),
-            stdout: None,
-            stderr: None,
-            effective_dry_run: dry_run,
-            job_id,
-        });
-    }
+            return Ok(crate::InstallResult {
+                success: false,
+                message,
+                method: Some("driverless".into()),
+                stdout: None,
+                stderr: None,
+                effective_dry_run: dry_run,
+                job_id,
+                log_path: None,
+                validation_errors: None,
+                attempts: None,
+                verified: false,
+                verification: None,
+            });
+        }
+    };
 
-    let ensure_message = format!("队列已创建/更新：{}", queue_name);
+    let ensure_message = format!("队列已创建/更新：{}（驱动：{}）", queue_name, model);
     if let Err(err) = reporter.emit_step_success("device.ensureQueue", Some(&ensure_message)) {
         eprintln!("[InstallPrinterMacOS] device.ensureQueue success emit failed: {}", err);
     }
@@ -687,7 +767,7 @@ pub async fn install_printer_macos(
     eprintln!(
         "[InstallPrinterMacOS] finalVerify start queue_name=\"{}\" device_uri=\"{}\" cmd=\"lpoptions\" args=[\"-p\",\"{}\"]",
         queue_name,
-        target.uri,
+        device_uri,
         queue_name
     );
 
@@ -708,17 +788,22 @@ pub async fn install_printer_macos(
                 stderr: None,
                 effective_dry_run: dry_run,
                 job_id,
+                log_path: None,
+                validation_errors: None,
+                attempts: None,
+                verified: false,
+                verification: None,
             });
         }
 
         match verify_queue_once(&queue_name, attempt).await {
             VerifyStatus::Exists(actual_uri) => {
                 if let Some(actual_uri) = actual_uri {
-                    if normalize_uri_for_compare(&actual_uri) != normalize_uri_for_compare(&target.uri) {
+                    if normalize_uri_for_compare(&actual_uri) != normalize_uri_for_compare(&device_uri) {
                         eprintln!(
                             "[InstallPrinterMacOS] finalVerify warn: uri mismatch queue_name=\"{}\" expected=\"{}\" actual=\"{}\"",
                             queue_name,
-                            target.uri,
+                            device_uri,
                             actual_uri
                         );
                     }
@@ -735,6 +820,11 @@ pub async fn install_printer_macos(
                     stderr: None,
                     effective_dry_run: dry_run,
                     job_id,
+                    log_path: None,
+                    validation_errors: None,
+                    attempts: None,
+                    verified: false,
+                    verification: None,
                 });
             }
             VerifyStatus::NotFound(detail) => {
@@ -779,5 +869,10 @@ pub async fn install_printer_macos(
         stderr: None,
         effective_dry_run: dry_run,
         job_id,
+        log_path: None,
+        validation_errors: None,
+        attempts: None,
+        verified: false,
+        verification: None,
     })
 }
@@ -0,0 +1,79 @@
+// macOS 平台完整性自检模块
+// - 嵌入资源：macOS 构建目前不内嵌需落盘的脚本 / PPD（prnport.vbs 仅 Windows 使用）
+// - App 签名：codesign --verify --deep --strict 校验 .app 包（未打包时校验可执行文件本身）
+
+use crate::platform::ExecutableSignature;
+use std::path::PathBuf;
+use std::process::Command;
+
+/// 获取需要校验的签名目标（可执行文件所在的 .app 包，找不到时退回可执行文件）
+fn signature_target() -> Result<PathBuf, String> {
+    let exe_path = std::env::current_exe().map_err(|e| format!("获取可执行文件路径失败: {}", e))?;
+    let bundle = exe_path
+        .ancestors()
+        .find(|p| p.extension().map(|ext| ext == "app").unwrap_or(false))
+        .map(|p| p.to_path_buf());
+    Ok(bundle.unwrap_or(exe_path))
+}
+
+/// 查询当前 App 的代码签名状态
+pub fn verify_executable_signature_macos() -> ExecutableSignature {
+    let target = match signature_target() {
+        Ok(p) => p,
+        Err(e) => {
+            return ExecutableSignature {
+                status: "unknown".to_string(),
+                path: None,
+                signer: None,
+                message: e,
+            };
+        }
+    };
+    let path = Some(target.display().to_string());
+
+    let verify = match Command::new("codesign")
+        .args(["--verify", "--deep", "--strict"])
+        .arg(&target)
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => {
+            return ExecutableSignature {
+                status: "unknown".to_string(),
+                path,
+                signer: None,
+                message: format!("执行 codesign 失败: {}", e),
+            };
+        }
+    };
+    let verify_stderr = String::from_utf8_lossy(&verify.stderr).trim().to_string();
+
+    if !verify.status.success() {
+        let status = if verify_stderr.contains("not signed at all") { "unsigned" } else { "invalid" };
+        return ExecutableSignature {
+            status: status.to_string(),
+            path,
+            signer: None,
+            message: verify_stderr,
+        };
+    }
+
+    // codesign -dvv 输出写在 stderr，Authority= 第一行为签名者
+    let signer = Command::new("codesign")
+        .args(["-dvv"])
+        .arg(&target)
+        .output()
+        .ok()
+        .and_then(|o| {
+            String::from_utf8_lossy(&o.stderr)
+                .lines()
+                .find_map(|line| line.strip_prefix("Authority=").map(|s| s.trim().to_string()))
+        });
+
+    ExecutableSignature {
+        status: "valid".to_string(),
+        path,
+        signer,
+        message: "签名有效".to_string(),
+    }
+}
@@ -0,0 +1,38 @@
+// macOS 平台机器标识模块（供 fleet 上报计算机器指纹）
+// - 主机名：scutil --get LocalHostName（失败时退回 hostname）
+// - MAC：networksetup -listallhardwareports 中的硬件端口 Ethernet Address
+
+use crate::platform::MachineIdentity;
+use std::process::Command;
+
+fn command_stdout(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// 获取本机主机名与硬件端口 MAC 地址
+pub fn get_machine_identity_macos() -> Result<MachineIdentity, String> {
+    let hostname = command_stdout("scutil", &["--get", "LocalHostName"])
+        .or_else(|| command_stdout("hostname", &[]))
+        .ok_or_else(|| "无法获取主机名".to_string())?;
+
+    let mac_addresses = command_stdout("networksetup", &["-listallhardwareports"])
+        .map(|text| {
+            text.lines()
+                .filter_map(|line| line.trim().strip_prefix("Ethernet Address:"))
+                .map(|mac| mac.trim().to_string())
+                .filter(|mac| !mac.is_empty() && mac != "N/A")
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Ok(MachineIdentity { hostname, mac_addresses })
+}
@@ -0,0 +1,81 @@
+// macOS 平台打印文件模块
+// 通过 lp 提交 PDF / 文本文件，返回 CUPS request id 便于跟踪
+
+use std::path::Path;
+use std::process::Command;
+
+use crate::platform::{PrintFileKind, PrintFileResult};
+
+/// macOS 平台打印文件实现
+/// 
+/// # 参数
+/// - `printer_name`: 目标打印队列名称
+/// - `file_path`: 已通过类型校验的文件路径
+/// - `kind`: 文件类型
+/// - `copies`: 份数（lp -n）
+/// 
+/// # 返回
+/// - `Ok(PrintFileResult)`: 执行结果（失败时 success=false 且带 error_code）
+/// - `Err(String)`: lp 无法执行
+pub fn print_file_macos(printer_name: &str, file_path: &Path, kind: PrintFileKind, copies: u32) -> Result<PrintFileResult, String> {
+    eprintln!(
+        "[PrintFile] START printer=\"{}\" file=\"{}\" kind={} copies={}",
+        printer_name, file_path.display(), kind.as_str(), copies
+    );
+
+    let output = Command::new("/usr/bin/lp")
+        .arg("-d")
+        .arg(printer_name)
+        .arg("-n")
+        .arg(copies.to_string())
+        .arg("--")
+        .arg(file_path)
+        .output()
+        .map_err(|e| format!("执行 lp 失败: {}", e))?;
+
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let evidence = format!(
+        "exit_code={:?} stdout=\"{}\" stderr=\"{}\"",
+        output.status.code(),
+        stdout.trim(),
+        stderr.trim()
+    );
+
+    let mut result = PrintFileResult {
+        success: output.status.success(),
+        printer_name: printer_name.to_string(),
+        file_path: file_path.display().to_string(),
+        file_kind: kind.as_str().to_string(),
+        copies,
+        spool_job_id: None,
+        error_code: None,
+        message: String::new(),
+        evidence: Some(evidence),
+    };
+
+    if output.status.success() {
+        result.spool_job_id = super::test_page::parse_job_id(&stdout);
+        result.message = match &result.spool_job_id {
+            Some(id) => format!("文件已提交到打印机: {}（作业 {}）", printer_name, id),
+            None => format!("文件已提交到打印机: {}", printer_name),
+        };
+        eprintln!("[PrintFile] SUCCESS printer=\"{}\" spool_job_id={:?}", printer_name, result.spool_job_id);
+        return Ok(result);
+    }
+
+    let lower = stderr.to_lowercase();
+    let (code, message) = if lower.contains("does not exist") || lower.contains("unknown destination") {
+        ("MAC_PRINTER_NOT_FOUND", format!("打印机不存在: {}", printer_name))
+    } else if lower.contains("forbidden") || lower.contains("not authorized") || lower.contains("permission") {
+        ("MAC_PERMISSION_DENIED", "没有权限向该打印机提交作业".to_string())
+    } else if lower.contains("not accepting") {
+        ("MAC_QUEUE_NOT_ACCEPTING", format!("打印队列当前不接受作业: {}", printer_name))
+    } else {
+        ("MAC_PRINT_FILE_FAILED", format!("提交打印作业失败: {}", stderr.trim()))
+    };
+    eprintln!("[PrintFile] FAILURE code={} stderr=\"{}\"", code, stderr.trim());
+    result.error_code = Some(code.to_string());
+    result.message = message;
+    Ok(result)
+}
@@ -0,0 +1,146 @@
+// macOS 平台打印队列暂停/恢复模块
+// 使用 cupsdisable / cupsenable 控制队列
+// 另提供打开打印机队列应用（~/Library/Printers/<name>.app），不存在时回退为 lpq 输出
+
+use std::process::Command;
+
+/// macOS 平台暂停/恢复打印队列
+/// 
+/// # 参数
+/// - `printer_name`: 打印机队列名称
+/// - `pause`: true 表示暂停（cupsdisable），false 表示恢复（cupsenable）
+/// 
+/// # 返回
+/// - `Ok(QueueControlResult)`: 执行结果（失败时 success=false 且带 error_code）
+/// - `Err(String)`: 命令无法执行
+pub fn set_queue_state_macos(printer_name: &str, pause: bool) -> Result<crate::platform::QueueControlResult, String> {
+    let (program, action, label) = if pause {
+        ("cupsdisable", "pause", "暂停")
+    } else {
+        ("cupsenable", "resume", "恢复")
+    };
+
+    eprintln!("[QueueControl] START printer_name=\"{}\" action={}", printer_name, action);
+
+    let output = Command::new(program)
+        .arg(printer_name)
+        .output()
+        .map_err(|e| format!("执行 {} 失败: {}", program, e))?;
+
+    if output.status.success() {
+        eprintln!("[QueueControl] OK printer_name=\"{}\" action={}", printer_name, action);
+        return Ok(crate::platform::QueueControlResult {
+            success: true,
+            printer_name: printer_name.to_string(),
+            action: action.to_string(),
+            error_code: None,
+            message: format!("已{}打印机: {}", label, printer_name),
+            evidence: Some(format!("{} ok", program)),
+        });
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let lower = stderr.to_lowercase();
+
+    let (code, message) = if lower.contains("forbidden") || lower.contains("not authorized") || lower.contains("unauthorized") {
+        (
+            "MAC_PERMISSION_DENIED",
+            format!("{}打印机需要管理员权限（lpadmin 组），请授权后重试。\n\n打印机: {}", label, printer_name),
+        )
+    } else if lower.contains("unknown printer") || lower.contains("does not exist") || lower.contains("not found") {
+        ("MAC_PRINTER_NOT_FOUND", format!("打印机 \"{}\" 不存在", printer_name))
+    } else {
+        ("MAC_QUEUE_CONTROL_FAILED", format!("{}打印机失败: {}", label, stderr.trim()))
+    };
+
+    eprintln!("[QueueControl] FAIL printer_name=\"{}\" action={} code={} stderr={}", printer_name, action, code, stderr.trim());
+    Ok(crate::platform::QueueControlResult {
+        success: false,
+        printer_name: printer_name.to_string(),
+        action: action.to_string(),
+        error_code: Some(code.to_string()),
+        message,
+        evidence: Some(format!("{} exit_code={:?} stderr={}", program, output.status.code(), stderr.chars().take(200).collect::<String>())),
+    })
+}
+
+/// macOS 平台打开打印队列（查看排队中的作业）
+/// 
+/// 系统为每台打印机生成队列应用 ~/Library/Printers/<name>.app（首次从系统设置打开队列时创建），
+/// 存在时用 open 打开；否则回退为 `lpq -P <name>`，把排队作业文本返回给前端显示
+/// 
+/// # 参数
+/// - `printer_name`: CUPS 队列名称
+/// 
+/// # 返回
+/// - `Ok(OpenQueueResult)`: 执行结果（打印机不存在时 success=false）
+/// - `Err(String)`: open / lpq 无法执行
+pub fn open_queue_window_macos(printer_name: &str) -> Result<crate::platform::OpenQueueResult, String> {
+    eprintln!("[QueueControl] START printer_name=\"{}\" action=open_queue", printer_name);
+
+    let printer_app = std::env::var("HOME")
+        .ok()
+        .map(|home| std::path::PathBuf::from(home).join("Library").join("Printers").join(format!("{}.app", printer_name)))
+        .filter(|path| path.exists());
+    if let Some(app) = printer_app {
+        let output = Command::new("open")
+            .arg(&app)
+            .output()
+            .map_err(|e| format!("执行 open 失败: {}", e))?;
+        if output.status.success() {
+            eprintln!("[QueueControl] OK printer_name=\"{}\" action=open_queue method=printer_app", printer_name);
+            return Ok(crate::platform::OpenQueueResult {
+                success: true,
+                printer_name: printer_name.to_string(),
+                method: "printer_app".to_string(),
+                error_code: None,
+                message: format!("已打开打印队列: {}", printer_name),
+                jobs_output: None,
+            });
+        }
+        eprintln!(
+            "[QueueControl] WARN open printer_app failed, fallback to lpq stderr={}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+
+    let output = Command::new("lpq")
+        .args(["-P", printer_name])
+        .output()
+        .map_err(|e| format!("执行 lpq 失败: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+
+    if !output.status.success() {
+        let lower = stderr.to_lowercase();
+        let code = if lower.contains("unknown") || lower.contains("does not exist") || lower.contains("not found") {
+            "MAC_PRINTER_NOT_FOUND"
+        } else {
+            "MAC_QUEUE_CONTROL_FAILED"
+        };
+        eprintln!("[QueueControl] FAIL printer_name=\"{}\" action=open_queue code={} stderr={}", printer_name, code, stderr);
+        let message = if code == "MAC_PRINTER_NOT_FOUND" {
+            format!("打印机 \"{}\" 不存在", printer_name)
+        } else {
+            format!("读取打印队列失败: {}", stderr)
+        };
+        return Ok(crate::platform::OpenQueueResult {
+            success: false,
+            printer_name: printer_name.to_string(),
+            method: "lpq".to_string(),
+            error_code: Some(code.to_string()),
+            message,
+            jobs_output: None,
+        });
+    }
+
+    eprintln!("[QueueControl] OK printer_name=\"{}\" action=open_queue method=lpq", printer_name);
+    Ok(crate::platform::OpenQueueResult {
+        success: true,
+        printer_name: printer_name.to_string(),
+        method: "lpq".to_string(),
+        error_code: None,
+        message: format!("打印机 {} 的排队作业", printer_name),
+        jobs_output: Some(stdout),
+    })
+}
@@ -0,0 +1,28 @@
+// macOS 平台 TTL 受限 ping 模块
+// 连通性检查的逐跳诊断：ping -c 1 -m <ttl> -t <秒> 发送单个 ICMP 回显请求，
+// TTL 耗尽时由中途路由器回复 "Time to live exceeded"
+//
+// 只返回原始输出，响应地址的解析由服务层完成
+
+use std::net::Ipv4Addr;
+use std::process::Command;
+
+use crate::platform::PingProbeOutput;
+
+/// 发送一个 TTL 受限的 ICMP 回显请求
+///
+/// # 参数
+/// - `target`: 目标地址
+/// - `ttl`: 生存时间（跳数）
+/// - `timeout_ms`: 等待回复的超时时间（ping -t 以秒为单位，向上取整）
+pub fn ping_with_ttl(target: Ipv4Addr, ttl: u8, timeout_ms: u32) -> Result<PingProbeOutput, String> {
+    let timeout_secs = timeout_ms.div_ceil(1000).max(1);
+    let output = Command::new("ping")
+        .args(["-c", "1", "-m", &ttl.to_string(), "-t", &timeout_secs.to_string(), &target.to_string()])
+        .output()
+        .map_err(|e| format!("执行 ping 失败: {}", e))?;
+    Ok(PingProbeOutput {
+        success: output.status.success(),
+        output: String::from_utf8_lossy(&output.stdout).to_string(),
+    })
+}
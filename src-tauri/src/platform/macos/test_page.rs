@@ -6,7 +6,11 @@ use std::time::{Duration, Instant};
 use serde_json::json;
 use tauri::{AppHandle, Manager};
 
-use crate::platform::test_page_content::build_test_page_content;
+use crate::platform::test_page_content::{build_test_page_bytes, build_test_page_content, TEST_PAGE_TIME_FORMAT};
+use crate::platform::TestPageSubmission;
+
+/// 等待测试页任务完成的最长时间
+const JOB_WAIT_TIMEOUT: Duration = Duration::from_secs(20);
 
 fn stderr_snip(stderr: &str) -> String {
     let snip = if stderr.len() > 200 {
@@ -17,7 +21,7 @@ fn stderr_snip(stderr: &str) -> String {
     snip.replace('\n', " ").trim().to_string()
 }
 
-fn parse_job_id(stdout: &str) -> Option<String> {
+pub(crate) fn parse_job_id(stdout: &str) -> Option<String> {
     for line in stdout.lines() {
         let lower = line.to_lowercase();
         if let Some(pos) = lower.find("request id is") {
@@ -52,7 +56,39 @@ fn emit_print_progress(
     let _ = app.emit_all("print_progress", payload);
 }
 
-pub fn print_test_page_macos(app: AppHandle, printer_name: String) -> Result<String, String> {
+/// lpstat -W not-completed -o 输出中的任务 ID（每行首列，如 "HP_M404-42"）
+fn queued_job_ids(stdout: &str) -> Vec<&str> {
+    stdout.lines().filter_map(|line| line.split_whitespace().next()).collect()
+}
+
+/// 等待打印任务离开未完成队列
+///
+/// # 返回
+/// - "completed": 任务已不在未完成队列中
+/// - "pending": 超时仍在队列中（打印机脱机 / 暂停等）
+/// - "unknown": lpstat 查询失败
+fn wait_for_job(printer_name: &str, job_id: &str) -> &'static str {
+    let deadline = Instant::now() + JOB_WAIT_TIMEOUT;
+    let mut queried = false;
+    while Instant::now() < deadline {
+        let output = Command::new("/usr/bin/lpstat")
+            .args(["-W", "not-completed", "-o", printer_name])
+            .output();
+        if let Ok(out) = output {
+            if out.status.success() {
+                queried = true;
+                let stdout = String::from_utf8_lossy(&out.stdout);
+                if !queued_job_ids(&stdout).contains(&job_id) {
+                    return "completed";
+                }
+            }
+        }
+        std::thread::sleep(Duration::from_millis(500));
+    }
+    if queried { "pending" } else { "unknown" }
+}
+
+pub fn print_test_page_macos(app: AppHandle, printer_name: String) -> Result<TestPageSubmission, String> {
     eprintln!("[PrintTestPage] START printer_name=\"{}\"", printer_name);
     let job_id = format!("print_{}_{}", chrono::Utc::now().timestamp_millis(), std::process::id());
 
@@ -60,7 +96,7 @@ pub fn print_test_page_macos(app: AppHandle, printer_name: String) -> Result<Str
     emit_print_progress(&app, &job_id, &printer_name, "print.prepare", "running", "准备测试页内容");
     let content = build_test_page_content(
         &printer_name,
-        &now.format("%Y年%m月%d日 %H:%M:%S").to_string(),
+        &now.format(TEST_PAGE_TIME_FORMAT).to_string(),
     );
     emit_print_progress(&app, &job_id, &printer_name, "print.prepare", "success", "测试页内容已生成");
 
@@ -71,7 +107,7 @@ pub fn print_test_page_macos(app: AppHandle, printer_name: String) -> Result<Str
     {
         let mut file = fs::File::create(&temp_file)
             .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=创建临时文件失败: {}", e))?;
-        file.write_all(content.as_bytes())
+        file.write_all(&build_test_page_bytes(&content))
             .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=写入测试内容失败: {}", e))?;
         file.sync_all()
             .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=同步文件失败: {}", e))?;
@@ -99,41 +135,42 @@ pub fn print_test_page_macos(app: AppHandle, printer_name: String) -> Result<Str
 
     if output.status.success() {
         emit_print_progress(&app, &job_id, &printer_name, "print.submit", "success", "打印任务已提交");
-        let submitted_job_id = parse_job_id(&stdout);
-        emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "running", "等待打印完成");
+        let Some(submitted_job_id) = parse_job_id(&stdout) else {
+            eprintln!("[PrintTestPage] SUCCESS printer_name=\"{}\" job_id=unknown", printer_name);
+            emit_print_progress(&app, &job_id, &printer_name, "print.done", "success", "测试页已发送到打印机");
+            return Ok(TestPageSubmission {
+                message: format!("测试页已发送到打印机: {}", printer_name),
+                job_id: None,
+                job_status: None,
+            });
+        };
 
-        let deadline = Instant::now() + Duration::from_secs(20);
-        let mut completed = false;
-        if let Some(submitted_job_id) = &submitted_job_id {
-            while Instant::now() < deadline {
-                let output = Command::new("/usr/bin/lpstat")
-                    .args(["-W", "not-completed", "-o"])
-                    .output();
-                if let Ok(out) = output {
-                    if out.status.success() {
-                        let stdout = String::from_utf8_lossy(&out.stdout).to_string();
-                        if !stdout.contains(submitted_job_id) {
-                            completed = true;
-                            break;
-                        }
-                    }
-                }
-                std::thread::sleep(Duration::from_millis(500));
+        emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "running", "等待打印完成");
+        let job_status = wait_for_job(&printer_name, &submitted_job_id);
+        let done_message = match job_status {
+            "completed" => {
+                emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "success", "打印任务已完成");
+                "测试页打印完成"
             }
-        }
-
-        emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "success", "打印任务已完成");
-        emit_print_progress(&app, &job_id, &printer_name, "print.done", "success", "测试页打印完成");
-
-        if let Some(submitted_job_id) = submitted_job_id {
-            eprintln!(
-                "[PrintTestPage] SUCCESS printer_name=\"{}\" job_id=\"{}\" completed={}",
-                printer_name, submitted_job_id, completed
-            );
-            return Ok(format!("测试页已提交: {} ({})", printer_name, submitted_job_id));
-        }
-        eprintln!("[PrintTestPage] SUCCESS printer_name=\"{}\"", printer_name);
-        return Ok(format!("测试页已发送到打印机: {}", printer_name));
+            "pending" => {
+                emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "skipped", "打印任务仍在队列中");
+                "测试页已提交，仍在打印队列中"
+            }
+            _ => {
+                emit_print_progress(&app, &job_id, &printer_name, "print.monitor", "skipped", "无法查询打印任务状态");
+                "测试页已提交"
+            }
+        };
+        emit_print_progress(&app, &job_id, &printer_name, "print.done", "success", done_message);
+        eprintln!(
+            "[PrintTestPage] SUCCESS printer_name=\"{}\" job_id=\"{}\" job_status={}",
+            printer_name, submitted_job_id, job_status
+        );
+        return Ok(TestPageSubmission {
+            message: format!("{}: {} ({})", done_message, printer_name, submitted_job_id),
+            job_id: Some(submitted_job_id),
+            job_status: Some(job_status.to_string()),
+        });
     }
 
     emit_print_progress(&app, &job_id, &printer_name, "print.failed", "failed", "打印任务提交失败");
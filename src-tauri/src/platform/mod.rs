@@ -5,6 +5,9 @@ pub mod windows;
 pub mod macos;
 
 pub mod test_page_content;
+pub use eprinty_core::{error_code, install_input, ipp_probe, printer_acl, printer_address, ps_script, spooler_errors};
+#[cfg(target_os = "macos")]
+pub use eprinty_core::lpadmin_errors;
 
 use serde::{Deserialize, Serialize};
 
@@ -32,6 +35,12 @@ pub struct PrinterDetectEntry {
     pub is_accepting_jobs: Option<bool>,
     pub state: Option<i32>,
     pub platform: String,
+    /// 是否由本工具安装（安装历史或平台标记；由 printer_service 填充）
+    #[serde(default)]
+    pub managed: bool,
+    /// 对应的配置打印机名称（仅 managed 且能在当前配置中找到时有值）
+    #[serde(default)]
+    pub config_key: Option<String>,
 }
 
 /// 平台统一的打印机列表获取入口
@@ -54,6 +63,8 @@ pub fn list_printers() -> Result<Vec<PrinterDetectEntry>, String> {
                 is_accepting_jobs: None,
                 state: None,
                 platform: "windows".to_string(),
+                managed: false,
+                config_key: None,
             })
             .collect())
     }
@@ -71,6 +82,8 @@ pub fn list_printers() -> Result<Vec<PrinterDetectEntry>, String> {
                 is_accepting_jobs: dest.is_accepting_jobs,
                 state: dest.state,
                 platform: "macos".to_string(),
+                managed: false,
+                config_key: None,
             })
             .collect())
     }
@@ -105,6 +118,53 @@ pub fn list_printers_detailed() -> Result<Vec<DetailedPrinterInfo>, String> {
     }
 }
 
+/// 以 Microsoft IPP Class Driver 创建免驱队列时 InstallResult.method 的取值（仅 Windows）
+pub const IPP_CLASS_DRIVER_METHOD: &str = "IppClassDriver";
+
+/// 一次安装尝试的记录（autoRetry 换策略重试时每次尝试一条）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct InstallAttempt {
+    /// 第几次尝试（从 1 开始）
+    pub attempt: u32,
+    /// 本次实际使用的 installMode
+    pub install_mode: String,
+    /// 本次实际使用的 driverInstallPolicy
+    pub driver_install_policy: String,
+    pub success: bool,
+    /// 失败时的稳定错误码（如 WIN_DRIVER_NOT_FOUND）
+    pub error_code: Option<String>,
+    pub message: String,
+    /// 本次相对上一次调整了什么策略（首次尝试为 None）
+    pub retry_reason: Option<String>,
+}
+
+/// 安装后校验结果（队列存在、端口指向的地址、驱动名称，以及可选的作业 / PJL 探测）
+///
+/// 各 `*_matches` / `probe_ok` 为 None 表示该项未校验（如共享连接不校验端口、配置未列出驱动名称）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub struct InstallVerification {
+    /// 所有已校验的项目均通过
+    pub verified: bool,
+    pub queue_exists: bool,
+    pub port_name: Option<String>,
+    /// 端口的 PrinterHostAddress（非 TCP/IP 端口为 None）
+    pub port_host: Option<String>,
+    pub expected_host: Option<String>,
+    pub port_matches: Option<bool>,
+    pub driver_name: Option<String>,
+    pub expected_driver_names: Vec<String>,
+    pub driver_matches: Option<bool>,
+    /// 执行的探测："zero_page" | "pjl_status"（未配置 verifyProbe 时为 None）
+    pub probe: Option<String>,
+    pub probe_ok: Option<bool>,
+    /// 校验结论说明（失败时列出不一致的项目）
+    pub detail: String,
+}
+
 /// 平台统一的打印机安装入口
 /// 
 /// 根据当前平台调用相应的实现：
@@ -125,7 +185,8 @@ pub async fn install_printer(
     #[cfg(windows)]
     {
         // Windows 平台：调用 Windows 实现
-        let result = crate::platform::windows::install::install_printer_windows(app, name, path, driverPath, model, driverInstallPolicy, driverKey, installMode, dry_run).await?;
+        let outcome = crate::platform::windows::install::install_printer_windows(app, name, path, driverPath, model, driverInstallPolicy, driverKey, installMode, dry_run).await?;
+        let result = outcome.result;
         // 转换 InstallResult 类型（从 platform/windows/install::InstallResult 到 crate::InstallResult）
         Ok(crate::InstallResult {
             success: result.success,
@@ -134,7 +195,12 @@ pub async fn install_printer(
             stdout: result.stdout,
             stderr: result.stderr,
             effective_dry_run: result.effective_dry_run, // 从平台结果中获取
+            log_path: crate::platform::windows::job_log::existing_job_log_path(&result.job_id),
             job_id: result.job_id, // 传递 jobId 给前端
+            validation_errors: None,
+            attempts: Some(outcome.attempts).filter(|a| !a.is_empty()),
+            verified: outcome.verification.as_ref().is_some_and(|v| v.verified),
+            verification: outcome.verification,
         })
     }
     
@@ -156,16 +222,37 @@ pub async fn install_printer(
     }
 }
 
+/// 测试页提交结果
+#[derive(Debug, Clone)]
+pub struct TestPageSubmission {
+    pub message: String,
+    /// 系统打印任务 ID（仅 macOS lp 返回）
+    pub job_id: Option<String>,
+    /// 任务状态（仅 macOS，lpstat -W not-completed 跟踪）："completed" | "pending" | "unknown"
+    pub job_status: Option<String>,
+}
+
+/// 测试页提交方式（记入测试页历史）
+pub const TEST_PAGE_METHOD: &str = if cfg!(windows) {
+    "out-printer"
+} else if cfg!(target_os = "macos") {
+    "lp"
+} else {
+    "unsupported"
+};
+
 /// 平台统一的打印测试页入口
 /// 
 /// 根据当前平台调用相应的实现：
 /// - Windows: 调用 Windows 实现
-/// - macOS: 调用 macOS 实现（暂未实现）
-pub fn print_test_page(app: tauri::AppHandle, printer_name: String) -> Result<String, String> {
+/// - macOS: 调用 macOS 实现
+pub fn print_test_page(app: tauri::AppHandle, printer_name: String) -> Result<TestPageSubmission, String> {
     #[cfg(windows)]
     {
-        // Windows 平台：调用 Windows 实现
+        // Windows 平台：调用 Windows 实现（Out-Printer 不返回任务 ID）
+        let _ = app;
         crate::platform::windows::test_page::print_test_page_windows(printer_name)
+            .map(|message| TestPageSubmission { message, job_id: None, job_status: None })
     }
     
     #[cfg(target_os = "macos")]
@@ -176,6 +263,7 @@ pub fn print_test_page(app: tauri::AppHandle, printer_name: String) -> Result<St
     
     #[cfg(not(any(windows, target_os = "macos")))]
     {
+        let _ = (app, printer_name);
         Err("当前仅支持 Windows 和 macOS 平台".to_string())
     }
 }
@@ -249,62 +337,1622 @@ pub fn open_url(url: &str) -> Result<(), String> {
     }
 }
 
-/// 删除打印机结果（统一结构）
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
-pub struct DeletePrinterResult {
-    pub success: bool,
-    pub printer_name: String,
-    pub removed_queue: bool,
-    pub removed_port: bool,
-    pub removed_driver: bool,
-    pub driver_name: Option<String>,
-    pub port_name: Option<String>,
-    pub message: String,
-    pub evidence: Option<String>,
+/// 平台统一的"在文件管理器中打开"入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: explorer.exe（select=true 时 /select 选中文件）
+/// - macOS: open（select=true 时 open -R）
+pub fn reveal_path(path: &std::path::Path, select: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::open::reveal_path_windows(path, select)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::reveal_path_macos(path, select)
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (path, select);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
 }
 
-/// 平台统一的删除打印机入口
+/// 平台统一的安装任务日志读取入口
 /// 
 /// 根据当前平台调用相应的实现：
-/// - Windows: 调用 Windows 实现
-/// - macOS: 调用 macOS 实现
-pub fn delete_printer(printer_name: &str, remove_port: bool, remove_driver: bool) -> Result<DeletePrinterResult, String> {
+/// - Windows: 读取 %LOCALAPPDATA%\ePrinty\logs\jobs\{jobId}.log
+/// - macOS: 暂不支持
+pub fn get_job_log(job_id: &str) -> Result<String, String> {
     #[cfg(windows)]
     {
-        // Windows 平台：调用 Windows 实现
-        let result = crate::platform::windows::delete::delete_printer_windows(printer_name, remove_port, remove_driver)?;
-        Ok(DeletePrinterResult {
-            success: result.success,
-            printer_name: printer_name.to_string(),
-            removed_queue: result.removed_queue,
-            removed_port: result.removed_port,
-            removed_driver: result.removed_driver,
-            driver_name: result.driver_name,
-            port_name: result.port_name,
-            message: result.message,
-            evidence: result.evidence,
-        })
+        crate::platform::windows::job_log::read_job_log(job_id)
     }
     
     #[cfg(target_os = "macos")]
     {
-        // macOS 平台：调用 macOS 实现（不支持 remove_driver）
-        let result = crate::platform::macos::delete::delete_printer_macos(printer_name, remove_port)?;
-        Ok(DeletePrinterResult {
-            success: result.success,
-            printer_name: printer_name.to_string(),
-            removed_queue: result.removed_queue,
-            removed_port: result.removed_port,
-            removed_driver: false, // macOS 不支持驱动删除
-            driver_name: None,
-            port_name: None,
-            message: result.message,
-            evidence: result.evidence,
-        })
+        let _ = job_id;
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = job_id;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的安装任务取消入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 置位任务取消标志，正在执行的 pnputil 驱动安装会被终止
+/// - macOS: 暂不支持
+/// 
+/// # 返回
+/// - `Ok(true)`: 已请求取消
+/// - `Ok(false)`: 任务不存在或已结束
+pub fn cancel_install_job(job_id: &str) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        Ok(crate::platform::windows::install_jobs::cancel(job_id))
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = job_id;
+        Err("macOS 平台暂不支持该功能".to_string())
     }
     
     #[cfg(not(any(windows, target_os = "macos")))]
     {
+        let _ = job_id;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 进行中的安装任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveInstallJob {
+    pub job_id: String,
+    /// 安装请求中的打印机名称（配置名称）
+    pub printer_name: String,
+    pub path: String,
+    pub driver_key: Option<String>,
+    pub dry_run: bool,
+    /// 开始时间（Unix 毫秒）
+    pub started_at: i64,
+}
+
+/// 平台统一的取消全部安装任务入口（退出时使用）
+///
+/// # 返回
+/// - 已请求取消的任务数（macOS 不登记安装任务，返回 0）
+pub fn cancel_all_install_jobs() -> usize {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_jobs::cancel_all()
+    }
+
+    #[cfg(not(windows))]
+    {
+        0
+    }
+}
+
+/// 平台统一的进行中安装任务列表入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: install_jobs 中已登记的任务
+/// - macOS: 不登记安装任务，返回空列表
+pub fn active_install_jobs() -> Vec<ActiveInstallJob> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_jobs::active_jobs()
+    }
+
+    #[cfg(not(windows))]
+    {
+        Vec::new()
+    }
+}
+
+/// 平台统一的已结束安装任务结果入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: install_jobs 中记录的最近结束任务的结果
+/// - macOS: 不登记安装任务，返回 None
+///
+/// # 返回
+/// - `Some(success)`: 任务已结束及其是否成功；`None`: 未找到结果
+pub fn install_job_outcome(job_id: &str) -> Option<bool> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_jobs::outcome(job_id)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = job_id;
+        None
+    }
+}
+
+/// 平台统一的进行中安装任务数入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: install_jobs 中已登记的任务数
+/// - macOS: 不登记安装任务，返回 0
+pub fn active_install_job_count() -> usize {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_jobs::active_count()
+    }
+
+    #[cfg(not(windows))]
+    {
+        0
+    }
+}
+
+/// 提权后继续安装的任务信息（沿用原任务的 jobId 与 job 日志）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallResumeHint {
+    pub job_id: String,
+    /// 原任务失败的步骤（如 "driver.stageDriver"；步骤开始前就失败时为 "preflight"）
+    pub failed_step: String,
+    /// 原任务已完成的步骤（按完成顺序）
+    pub completed_steps: Vec<String>,
+}
+
+/// 平台统一的安装任务继续入口（提权后继续因未提权失败的任务）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 下一次安装该路径的打印机时沿用 hint 中的 jobId，job.init 事件 meta.resumedFrom 带有原任务的步骤
+/// - macOS: 安装不区分提权，忽略
+///
+/// # 参数
+/// - `path`: 打印机路径（与安装请求中的 path 一致）
+/// - `hint`: None 时清除未被使用的 hint
+pub fn set_install_resume_hint(path: &str, hint: Option<InstallResumeHint>) {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_jobs::set_resume_hint(path, hint);
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (path, hint);
+    }
+}
+
+/// 平台统一的进程提权状态入口
+///
+/// # 返回
+/// - Windows: 进程令牌是否已提升
+/// - macOS: 始终为 false（安装时按需授权，不区分进程权限）
+pub fn is_elevated() -> bool {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::permission_probe::is_elevated()
+    }
+
+    #[cfg(not(windows))]
+    {
+        false
+    }
+}
+
+/// 平台统一的以管理员身份重新启动入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: ShellExecuteExW "runas" 启动当前可执行文件（UAC 提示），用户拒绝时返回 ELEVATION_CANCELLED
+/// - macOS: 暂不支持
+///
+/// # 参数
+/// - `args`: 新进程的命令行参数
+pub fn relaunch_elevated(args: &[&str]) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::elevation::relaunch_elevated(args)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let _ = args;
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = args;
         Err("当前仅支持 Windows 和 macOS 平台".to_string())
     }
 }
+
+/// 平台统一的计划任务触发入口（委托安装）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: schtasks /Run /TN <任务名称>
+/// - macOS: 不支持（委托安装仅用于 Windows 无 UAC 授权的环境）
+///
+/// # 参数
+/// - `task_name`: 计划任务名称
+pub fn run_scheduled_task(task_name: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::scheduled_task::run_scheduled_task(task_name)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = task_name;
+        Err("委托安装（计划任务）仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 安装前同名队列的状态
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueNameStatus {
+    /// 不存在同名队列
+    Free,
+    /// 同名队列已指向目标地址（重新安装）
+    SameTarget,
+    /// 同名队列指向其他地址（端口地址或端口名）
+    Conflict { current_target: String },
+}
+
+/// 平台统一的队列名冲突检查入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 读取同名队列的端口，与配置地址比较
+/// - macOS: 队列名由设备 URI 生成、同一 URI 复用已有队列，不会出现同名异址，始终返回 Free
+pub fn check_queue_name(queue_name: &str, path: &str) -> Result<QueueNameStatus, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::install_verify::check_queue_name(queue_name, path)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (queue_name, path);
+        Ok(QueueNameStatus::Free)
+    }
+}
+
+/// 清理本机残留时单项的处理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArtifactRemoval {
+    /// "scheduledTask" | "eventSource" | "credential" | "directory" | "file"
+    pub kind: String,
+    /// 任务名称、注册表路径、凭据目标名或文件路径
+    pub target: String,
+    /// "removed" | "notFound" | "failed"
+    pub status: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl ArtifactRemoval {
+    pub fn removed(kind: &str, target: &str) -> Self {
+        Self { kind: kind.to_string(), target: target.to_string(), status: "removed".to_string(), message: None }
+    }
+
+    pub fn not_found(kind: &str, target: &str) -> Self {
+        Self { kind: kind.to_string(), target: target.to_string(), status: "notFound".to_string(), message: None }
+    }
+
+    pub fn failed(kind: &str, target: &str, message: String) -> Self {
+        Self { kind: kind.to_string(), target: target.to_string(), status: "failed".to_string(), message: Some(message) }
+    }
+}
+
+/// 平台统一的系统对象清理入口（卸载前清理本机残留）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 删除计划任务、事件日志事件源、凭据管理器中 ePrinty/ 开头的凭据
+/// - macOS: 应用不创建此类系统对象，返回空列表
+///
+/// # 参数
+/// - `task_names`: 要删除的计划任务名称（委托安装）
+pub fn remove_system_artifacts(task_names: &[String]) -> Vec<ArtifactRemoval> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::machine_cleanup::remove_system_artifacts(task_names)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = task_names;
+        Vec::new()
+    }
+}
+
+/// 平台统一的打印机配置项设置入口（安装后动作 setConfig）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: Set-PrinterProperty
+/// - macOS: 不支持（安装后动作依赖 Windows 驱动包）
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+/// - `key`: 属性名
+/// - `value`: 属性值
+pub fn set_printer_property(printer_name: &str, key: &str, value: &str) -> Result<String, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::post_install::set_printer_property(printer_name, key, value)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (printer_name, key, value);
+        Err("安装后动作仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的安装后动作签名公钥读取入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 读取 HKLM\SOFTWARE\ePrinty 的 PostInstallPublicKey（管理员部署）
+/// - 其他平台: 返回 None（安装后动作仅支持 Windows）
+pub fn read_post_install_public_key() -> Result<Option<String>, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::post_install::read_public_key()
+    }
+
+    #[cfg(not(windows))]
+    {
+        Ok(None)
+    }
+}
+
+/// 平台统一的厂商工具执行入口（安装后动作 runVendorUtility）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 隐藏窗口直接启动进程（不经过 shell），120 秒超时
+/// - macOS: 不支持
+///
+/// # 参数
+/// - `program`: 工具可执行文件的完整路径
+/// - `args`: 固定参数
+pub fn run_vendor_utility(program: &std::path::Path, args: &[String]) -> Result<String, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::post_install::run_vendor_utility(program, args)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (program, args);
+        Err("安装后动作仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的账户 SID 解析入口（打印队列权限）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: NTAccount.Translate
+/// - macOS: 不支持（打印队列权限仅用于 Windows）
+///
+/// # 参数
+/// - `names`: 账户名（域\账户 / 本地组名）
+pub fn resolve_account_sids(names: &[String]) -> Result<Vec<String>, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::printer_acl::resolve_account_sids(names)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = names;
+        Err("打印队列权限仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的打印机权限读取入口（返回 PermissionSDDL）
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+pub fn get_printer_sddl(printer_name: &str) -> Result<String, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::printer_acl::get_printer_sddl(printer_name)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = printer_name;
+        Err("打印队列权限仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的打印机权限设置入口（Set-Printer -PermissionSDDL，需要管理员权限）
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+/// - `sddl`: 安全描述符
+pub fn set_printer_sddl(printer_name: &str, sddl: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::printer_acl::set_printer_sddl(printer_name, sddl)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (printer_name, sddl);
+        Err("打印队列权限仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 远程桌面（RDS / 终端服务器）环境状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TerminalServerStatus {
+    /// 是否为多用户远程桌面会话主机（普通 Windows 开启远程桌面不算）
+    pub is_terminal_server: bool,
+    /// 当前进程是否运行在远程桌面会话中
+    pub is_remote_session: bool,
+    /// 当前进程所在的会话 ID
+    pub session_id: Option<u32>,
+    /// 是否启用了远程桌面打印机重定向（Easy Print）
+    pub printer_redirection_enabled: bool,
+    /// 需要提示用户的注意事项
+    pub warnings: Vec<String>,
+}
+
+/// 检测远程桌面（RDS / 终端服务器）环境
+///
+/// # 返回
+/// - `Some(TerminalServerStatus)`: Windows 平台的检测结果（会话内缓存）
+/// - `None`: 其他平台不适用
+pub fn terminal_server_status() -> Option<TerminalServerStatus> {
+    #[cfg(windows)]
+    {
+        Some(crate::platform::windows::terminal_server::status())
+    }
+    
+    #[cfg(not(windows))]
+    {
+        None
+    }
+}
+
+/// 打印队列暂停/恢复结果（统一结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueControlResult {
+    pub success: bool,
+    pub printer_name: String,
+    /// "pause" | "resume"
+    pub action: String,
+    /// 失败时的错误码（如 WIN_PERMISSION_DENIED / MAC_PERMISSION_DENIED）
+    pub error_code: Option<String>,
+    pub message: String,
+    pub evidence: Option<String>,
+}
+
+/// 平台统一的暂停/恢复打印队列入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: SetPrinterW(PRINTER_CONTROL_PAUSE / PRINTER_CONTROL_RESUME)
+/// - macOS: cupsdisable / cupsenable
+pub fn set_printer_paused(printer_name: &str, pause: bool) -> Result<QueueControlResult, String> {
+    #[cfg(windows)]
+    {
+        use crate::platform::windows::queue_control::{set_queue_state_windows, QueueControlAction};
+        let action = if pause { QueueControlAction::Pause } else { QueueControlAction::Resume };
+        set_queue_state_windows(printer_name, action)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::queue_control::set_queue_state_macos(printer_name, pause)
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (printer_name, pause);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 打印机端口（Standard TCP/IP 端口）配置
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConfig {
+    pub port_name: String,
+    pub host_address: Option<String>,
+    pub port_number: Option<u32>,
+    /// "raw" | "lpr"
+    pub protocol: Option<String>,
+    /// SNMP 状态查询（驱动据此显示"脱机"；打印机 SNMP 不可用时会误报脱机）
+    pub snmp_enabled: Option<bool>,
+    pub snmp_community: Option<String>,
+    /// 读取方式："powershell"（Get-PrinterPort）| "registry"（旧系统注册表）
+    pub source: String,
+}
+
+/// set_port_config 的修改项（None 表示不修改）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConfigUpdate {
+    pub port_number: Option<u32>,
+    pub snmp_enabled: Option<bool>,
+    pub snmp_community: Option<String>,
+}
+
+/// set_port_config 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortConfigChange {
+    /// 修改后读回的配置
+    pub port: PortConfig,
+    /// 修改方式："powershell" | "registry"
+    pub method: String,
+    /// 通过注册表修改时需重启 Print Spooler 服务才会生效
+    pub requires_spooler_restart: bool,
+    pub message: String,
+}
+
+/// retarget_printer_port 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PortRetargetResult {
+    pub queue_name: String,
+    /// 改挂前的端口（与新端口相同时表示无需改挂）
+    pub old_port: String,
+    pub new_port: String,
+    pub host_address: String,
+    /// 旧端口已无队列使用并被删除
+    pub old_port_removed: bool,
+}
+
+/// 平台统一的读取端口配置入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows 10+: Get-PrinterPort + Win32_TCPIPPrinterPort
+/// - Windows 7/8: 注册表 Monitors\Standard TCP/IP Port\Ports\<端口>
+/// - macOS: 不支持（CUPS 队列没有独立的端口对象）
+pub fn get_port_config(port_name: &str) -> Result<PortConfig, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::port_config::get_port_config_windows(port_name)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = port_name;
+        Err("打印机端口配置仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的修改端口配置入口（需要管理员权限）
+///
+/// # 参数
+/// - `port_name`: 端口名称（如 "IP_192_168_20_65"）
+/// - `update`: 修改项（已由服务层校验）
+pub fn set_port_config(port_name: &str, update: &PortConfigUpdate) -> Result<PortConfigChange, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::port_config::set_port_config_windows(port_name, update)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (port_name, update);
+        Err("打印机端口配置仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 平台统一的改挂打印机端口入口（打印机 IP 变化时原地更新，不重装驱动；需要管理员权限）
+///
+/// # 参数
+/// - `queue_name`: 系统队列名称
+/// - `host_address`: 新的打印机地址
+/// - `port_name`: 新端口名称（如 "IP_192_168_20_66"）
+/// - `port_number`: 配置中指定的端口号（None 时沿用旧端口）
+pub fn retarget_printer_port(
+    queue_name: &str,
+    host_address: &str,
+    port_name: &str,
+    port_number: Option<u16>,
+) -> Result<PortRetargetResult, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::port_config::retarget_printer_port_windows(queue_name, host_address, port_name, port_number)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (queue_name, host_address, port_name, port_number);
+        Err("打印机端口配置仅支持 Windows 平台".to_string())
+    }
+}
+
+/// 打开打印队列窗口结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OpenQueueResult {
+    pub success: bool,
+    pub printer_name: String,
+    /// 打开方式："printui"（Windows 队列窗口）| "printer_app"（macOS 打印机队列应用）| "lpq"（仅返回排队作业文本）
+    pub method: String,
+    /// 失败时的错误码（如 WIN_PRINTER_NOT_FOUND / MAC_PRINTER_NOT_FOUND）
+    pub error_code: Option<String>,
+    pub message: String,
+    /// 回退到 lpq 时的排队作业输出（队列窗口已打开时为 None）
+    pub jobs_output: Option<String>,
+}
+
+/// 平台统一的打开打印队列窗口入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: rundll32 printui.dll,PrintUIEntry /o /n "<name>"
+/// - macOS: 打开 ~/Library/Printers 下该打印机的队列应用，不存在时回退为 lpq -P 输出
+pub fn open_print_queue(printer_name: &str) -> Result<OpenQueueResult, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::queue_control::open_queue_window_windows(printer_name)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::queue_control::open_queue_window_macos(printer_name)
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = printer_name;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 可直接提交打印的文件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PrintFileKind {
+    Pdf,
+    Text,
+}
+
+impl PrintFileKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PrintFileKind::Pdf => "pdf",
+            PrintFileKind::Text => "text",
+        }
+    }
+}
+
+/// 打印文件结果（统一结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintFileResult {
+    pub success: bool,
+    pub printer_name: String,
+    pub file_path: String,
+    /// "pdf" | "text"
+    pub file_kind: String,
+    pub copies: u32,
+    /// 假脱机作业 ID（Windows: Get-PrintJob Id；macOS: lp 返回的 request id）
+    /// 作业在轮询前已完成时可能为空
+    pub spool_job_id: Option<String>,
+    pub error_code: Option<String>,
+    pub message: String,
+    pub evidence: Option<String>,
+}
+
+/// 平台统一的打印文件入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 文本走 Out-Printer，PDF 走 ShellExecute "printto"（依赖系统关联的 PDF 阅读器）
+/// - macOS: lp -d <printer> -n <copies>
+pub fn print_file(printer_name: &str, file_path: &std::path::Path, kind: PrintFileKind, copies: u32) -> Result<PrintFileResult, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::print_file::print_file_windows(printer_name, file_path, kind, copies)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::print_file::print_file_macos(printer_name, file_path, kind, copies)
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (printer_name, file_path, kind, copies);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 删除打印机结果（统一结构）
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct DeletePrinterResult {
+    pub success: bool,
+    pub printer_name: String,
+    pub removed_queue: bool,
+    pub removed_port: bool,
+    pub removed_driver: bool,
+    pub driver_name: Option<String>,
+    pub port_name: Option<String>,
+    pub message: String,
+    pub evidence: Option<String>,
+}
+
+/// 平台统一的删除打印机入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 调用 Windows 实现
+/// - macOS: 调用 macOS 实现
+pub fn delete_printer(printer_name: &str, remove_port: bool, remove_driver: bool) -> Result<DeletePrinterResult, String> {
+    #[cfg(windows)]
+    {
+        // Windows 平台：调用 Windows 实现
+        let result = crate::platform::windows::delete::delete_printer_windows(printer_name, remove_port, remove_driver)?;
+        Ok(DeletePrinterResult {
+            success: result.success,
+            printer_name: printer_name.to_string(),
+            removed_queue: result.removed_queue,
+            removed_port: result.removed_port,
+            removed_driver: result.removed_driver,
+            driver_name: result.driver_name,
+            port_name: result.port_name,
+            message: result.message,
+            evidence: result.evidence,
+        })
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        // macOS 平台：调用 macOS 实现（不支持 remove_driver）
+        let result = crate::platform::macos::delete::delete_printer_macos(printer_name, remove_port)?;
+        Ok(DeletePrinterResult {
+            success: result.success,
+            printer_name: printer_name.to_string(),
+            removed_queue: result.removed_queue,
+            removed_port: result.removed_port,
+            removed_driver: false, // macOS 不支持驱动删除
+            driver_name: None,
+            port_name: None,
+            message: result.message,
+            evidence: result.evidence,
+        })
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 列出由本工具创建的打印机队列名称
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: Comment/Location 中带 ePrinty tag 的队列
+/// - macOS: 以 "eprinty-" 前缀命名的 CUPS 队列
+pub fn list_managed_printer_names() -> Result<Vec<String>, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::remove::list_tagged_printer_names()
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let destinations = crate::platform::macos::list_destinations()?;
+        Ok(destinations
+            .into_iter()
+            .map(|dest| dest.name)
+            .filter(|name| name.starts_with("eprinty-"))
+            .collect())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// DriverStore 中已导入的打印机驱动包信息（统一结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StagedDriverInfo {
+    /// 发布名称（oemNN.inf）
+    pub published_name: String,
+    /// 原始 INF 名称
+    pub original_name: Option<String>,
+    pub provider_name: Option<String>,
+    pub class_name: Option<String>,
+    pub driver_version: Option<String>,
+    pub signer_name: Option<String>,
+    /// 由该驱动包注册的打印机驱动名称
+    pub registered_drivers: Vec<String>,
+    /// 正在使用该驱动包的打印队列
+    pub in_use_by_queues: Vec<String>,
+}
+
+/// 删除 staged 驱动包结果（统一结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveStagedDriverResult {
+    pub success: bool,
+    pub published_name: String,
+    pub message: String,
+    pub evidence: Option<String>,
+}
+
+/// 平台统一的 staged 驱动包列表入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 调用 Windows 实现（pnputil /enum-drivers）
+/// - macOS: 不支持（无 DriverStore 概念）
+pub fn list_staged_printer_drivers() -> Result<Vec<StagedDriverInfo>, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::driver_store::list_staged_printer_drivers_windows()
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的 staged 驱动包删除入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 调用 Windows 实现（pnputil /delete-driver，使用中时拒绝）
+/// - macOS: 不支持（无 DriverStore 概念）
+pub fn remove_staged_driver(published_name: &str) -> Result<RemoveStagedDriverResult, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::driver_store::remove_staged_driver_windows(published_name)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = published_name;
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = published_name;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 单个驱动的冲突信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverConflictInfo {
+    /// 已注册的驱动名称
+    pub driver_name: String,
+    pub installed_version: Option<String>,
+    /// 目标 INF 中声明的版本（DriverVer）
+    pub target_version: Option<String>,
+    /// 使用该驱动的其他队列
+    pub affected_queues: Vec<String>,
+    /// 其中由本工具管理的队列
+    pub affected_managed_queues: Vec<String>,
+}
+
+/// 驱动冲突检测结果（统一结构）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverConflictCheckResult {
+    /// "OK" | "CONFLICT" | "UNKNOWN"
+    pub status: String,
+    /// 安装会原地更新驱动并影响其他受管队列
+    pub has_conflict: bool,
+    pub printer_name: String,
+    pub inf_path: Option<String>,
+    pub target_version: Option<String>,
+    pub conflicts: Vec<DriverConflictInfo>,
+    pub message: String,
+}
+
+/// 平台统一的驱动冲突检测入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 比较目标 INF 的 DriverVer 与已注册驱动版本
+/// - macOS: 不支持（PPD 按队列独立，不存在共享驱动原地升级）
+pub fn check_driver_conflicts(printer_name: &str, driver_path: &str, driver_names: &[String]) -> Result<DriverConflictCheckResult, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::driver_conflict::check_driver_conflicts_windows(printer_name, driver_path, driver_names)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = (printer_name, driver_path, driver_names);
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (printer_name, driver_path, driver_names);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 单个资源的完整性校验结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceIntegrity {
+    pub name: String,
+    /// "embedded"（exe 内嵌字节）| "disk"（运行时写出的副本）
+    pub location: String,
+    pub path: Option<String>,
+    pub ok: bool,
+    /// 编译期计算的 SHA256
+    pub expected_sha256: String,
+    pub actual_sha256: Option<String>,
+    pub message: Option<String>,
+}
+
+/// 可执行文件签名状态
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExecutableSignature {
+    /// "valid" | "unsigned" | "invalid" | "unknown"
+    pub status: String,
+    pub path: Option<String>,
+    pub signer: Option<String>,
+    pub message: String,
+}
+
+/// 平台统一的嵌入资源完整性校验入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 校验 prnport.vbs（内嵌字节 + %TEMP% 副本）
+/// - macOS: 当前无需落盘的内嵌资源，返回空列表
+pub fn verify_embedded_resources() -> Vec<ResourceIntegrity> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::integrity::verify_embedded_resources_windows()
+    }
+    
+    #[cfg(not(windows))]
+    {
+        Vec::new()
+    }
+}
+
+/// 平台统一的可执行文件签名校验入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: Get-AuthenticodeSignature
+/// - macOS: codesign --verify --deep --strict
+pub fn verify_executable_signature() -> ExecutableSignature {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::integrity::verify_executable_signature_windows()
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::integrity::verify_executable_signature_macos()
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        ExecutableSignature {
+            status: "unknown".to_string(),
+            path: None,
+            signer: None,
+            message: "当前仅支持 Windows 和 macOS 平台".to_string(),
+        }
+    }
+}
+
+/// 本机标识（主机名 + 物理网卡 MAC，用于计算 fleet 上报的机器指纹）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineIdentity {
+    pub hostname: String,
+    pub mac_addresses: Vec<String>,
+}
+
+/// 平台统一的本机标识获取入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: COMPUTERNAME + Win32_NetworkAdapter
+/// - macOS: scutil + networksetup
+pub fn get_machine_identity() -> Result<MachineIdentity, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::machine_id::get_machine_identity_windows()
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::machine_id::get_machine_identity_macos()
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的残留子进程清理入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 按子进程登记文件清理已退出实例遗留、或超过 `max_age` 仍未结束的子进程
+/// - macOS: 子进程（lpadmin / lpstat 等）均为短命令，暂不需要清理，返回 0
+/// 
+/// # 返回
+/// - `Ok(usize)`: 本次终止的子进程数量
+pub fn sweep_orphan_processes(max_age: std::time::Duration) -> Result<usize, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::process_watchdog::sweep(max_age).map(|report| report.killed.len())
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = max_age;
+        Ok(0)
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = max_age;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的共享打印服务器凭据保存入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 写入 Windows 凭据管理器（按服务器保存，安装 \\server\queue 前用于建立 SMB 会话）
+/// - macOS: 暂不支持
+pub fn set_smb_credentials(server: &str, username: &str, password: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::smb_credentials::store_credentials(server, username, password)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = (server, username, password);
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (server, username, password);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的共享打印服务器凭据删除入口
+/// 
+/// # 返回
+/// - `Ok(bool)`: 是否删除了已保存的凭据（未保存时为 false）
+pub fn clear_smb_credentials(server: &str) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::smb_credentials::delete_credentials(server)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = server;
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = server;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的加密驱动包密码保存入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 按引用名（driverCatalog remote.archivePasswordRef）写入 Windows 凭据管理器
+/// - macOS: 暂不支持（macOS 不使用远程驱动包）
+pub fn set_driver_archive_password(reference: &str, password: &str) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::archive_password::store_password(reference, password)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = (reference, password);
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (reference, password);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的加密驱动包密码删除入口
+/// 
+/// # 返回
+/// - `Ok(bool)`: 是否删除了已保存的密码（未保存时为 false）
+pub fn clear_driver_archive_password(reference: &str) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::archive_password::delete_password(reference)
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = reference;
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = reference;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 打印环境报告中的打印队列
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnvQueueInfo {
+    pub name: String,
+    pub driver_name: Option<String>,
+    pub port_name: Option<String>,
+    pub shared: Option<bool>,
+    pub share_name: Option<String>,
+    /// "Local" | "Connection"
+    pub queue_type: Option<String>,
+    pub status: Option<String>,
+    pub location: Option<String>,
+    pub comment: Option<String>,
+}
+
+/// 打印环境报告中的打印驱动
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnvDriverInfo {
+    pub name: String,
+    /// a.b.c.d
+    pub version: Option<String>,
+    pub inf_path: Option<String>,
+    pub manufacturer: Option<String>,
+    /// 驱动架构（如 "Windows x64"）
+    pub environment: Option<String>,
+    /// 3 = V3, 4 = V4
+    pub major_version: Option<u32>,
+}
+
+/// 打印环境报告中的打印端口
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnvPortInfo {
+    pub name: String,
+    pub description: Option<String>,
+    /// TCP/IP 端口的主机地址
+    pub host_address: Option<String>,
+    pub port_number: Option<u32>,
+    /// "RAW" | "LPR"
+    pub protocol: Option<String>,
+    pub snmp_enabled: Option<bool>,
+}
+
+/// 打印后台处理服务状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnvSpoolerInfo {
+    pub status: Option<String>,
+    pub start_type: Option<String>,
+    pub spool_directory: Option<String>,
+}
+
+/// 打印相关策略值（注册表）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EnvPolicyValue {
+    /// 注册表键路径
+    pub path: String,
+    pub name: String,
+    pub value: Option<String>,
+}
+
+/// 打印系统环境报告（统一结构，供技术支持排查）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintEnvironmentReport {
+    /// "windows" | "macos"
+    pub platform: String,
+    pub generated_at: String,
+    pub os_version: Option<String>,
+    pub queues: Vec<EnvQueueInfo>,
+    pub drivers: Vec<EnvDriverInfo>,
+    pub ports: Vec<EnvPortInfo>,
+    pub spooler: Option<EnvSpoolerInfo>,
+    pub policies: Vec<EnvPolicyValue>,
+    /// 单项采集失败的说明（不影响其他部分）
+    pub errors: Vec<String>,
+    /// 导出的 JSON 文件路径（未导出时为空）
+    pub exported_path: Option<String>,
+}
+
+/// 平台统一的打印环境报告入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 一次 PowerShell 采集 Get-Printer / Get-PrinterDriver / Get-PrinterPort / Spooler 服务 / 打印策略注册表
+/// - macOS: 暂不支持
+pub fn dump_print_environment() -> Result<PrintEnvironmentReport, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::print_environment::dump_print_environment_windows()
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 单项操作所需权限的判断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OperationPermission {
+    /// "stage_driver" | "add_tcpip_printer" | "connect_shared_printer" | "download_driver"
+    pub operation: String,
+    pub allowed: bool,
+    /// 缺少的权限说明（allowed=true 时为空）
+    pub missing: Vec<String>,
+}
+
+/// 按操作细分的权限探测结果（替代笼统的是否管理员判断）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionReport {
+    /// 进程令牌是否已提升（仅作参考，不再单独决定能否执行操作）
+    pub is_elevated: bool,
+    /// SeLoadDriverPrivilege："enabled" | "present"（存在但未启用，可按需启用）| "absent" | "unknown"
+    pub load_driver_privilege: String,
+    /// 能否打开 \\.\pipe\spoolss（Spooler 运行且允许连接）
+    pub spooler_pipe_accessible: bool,
+    /// 能否以 SERVER_ACCESS_ADMINISTER 打开本机打印服务器（添加端口 / 队列 / 驱动需要）
+    pub print_server_admin: bool,
+    pub drivers_root: Option<String>,
+    pub drivers_root_writable: bool,
+    pub operations: Vec<OperationPermission>,
+    /// 探测过程中的说明（失败原因等）
+    pub notes: Vec<String>,
+}
+
+impl PermissionReport {
+    /// 查找某项操作的判断结果
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub fn operation(&self, operation: &str) -> Option<&OperationPermission> {
+        self.operations.iter().find(|o| o.operation == operation)
+    }
+}
+
+/// 平台统一的权限探测入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 逐项探测 SeLoadDriverPrivilege、\\.\pipe\spoolss、打印服务器管理权限与 drivers_root 写权限
+/// - macOS: 暂不支持
+pub fn probe_permissions() -> Result<PermissionReport, String> {
+    #[cfg(windows)]
+    {
+        Ok(crate::platform::windows::permission_probe::probe())
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        Err("macOS 平台暂不支持该功能".to_string())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 审计事件类型（写入系统事件日志）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(not(windows), allow(dead_code))]
+pub enum AuditEventKind {
+    InstallSucceeded,
+    InstallFailed,
+    PrinterDeleted,
+    /// 删除未完成（队列仍存在或清理失败）
+    PrinterDeleteFailed,
+}
+
+/// 平台统一的审计事件写入入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 写入应用程序日志（事件源 ePrinty，首次写入时注册；配置 eventLog.enabled=false 时跳过）
+/// - macOS: 无 Windows 事件日志，忽略
+/// 
+/// # 返回
+/// - `Ok(true)`: 已写入
+/// - `Ok(false)`: 未写入（已关闭或平台不支持）
+#[cfg_attr(not(windows), allow(unused_variables))]
+pub fn report_audit_event(kind: AuditEventKind, summary: &str, fields: &[(&str, String)]) -> Result<bool, String> {
+    #[cfg(windows)]
+    {
+        use crate::platform::windows::event_log::{self, EventLevel};
+        let (event_id, level) = match kind {
+            AuditEventKind::InstallSucceeded => (event_log::EVENT_INSTALL_SUCCEEDED, EventLevel::Information),
+            AuditEventKind::InstallFailed => (event_log::EVENT_INSTALL_FAILED, EventLevel::Error),
+            AuditEventKind::PrinterDeleted => (event_log::EVENT_PRINTER_DELETED, EventLevel::Information),
+            AuditEventKind::PrinterDeleteFailed => (event_log::EVENT_PRINTER_DELETED, EventLevel::Warning),
+        };
+        event_log::report_event(event_id, level, summary, fields)
+    }
+    
+    #[cfg(not(windows))]
+    {
+        Ok(false)
+    }
+}
+
+/// 平台统一的调试开关设置入口（由用户设置同步）
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 是否保留驱动包解压 staging 目录、是否保留测试页临时文件
+/// - macOS: 无对应流程，忽略
+pub fn set_diagnostic_options(keep_staging: bool, keep_test_page_file: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::archive::set_keep_staging(keep_staging);
+        crate::platform::windows::test_page::set_keep_test_page_file(keep_test_page_file);
+        Ok(())
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = (keep_staging, keep_test_page_file);
+        Ok(())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (keep_staging, keep_test_page_file);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// 平台统一的按流量计费网络下载确认入口
+/// 
+/// 根据当前平台调用相应的实现：
+/// - Windows: 设置会话内是否允许在按流量计费网络上下载超过阈值的驱动包
+/// - macOS: 不下载驱动包，忽略
+pub fn set_metered_download_allowed(allowed: bool) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::driver_fetch::set_metered_download_allowed(allowed);
+        Ok(())
+    }
+    
+    #[cfg(target_os = "macos")]
+    {
+        let _ = allowed;
+        Ok(())
+    }
+    
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = allowed;
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// TTL 受限 ping 的原始结果
+#[derive(Debug, Clone)]
+pub struct PingProbeOutput {
+    /// ping 退出码为 0（收到回复）
+    pub success: bool,
+    /// ping 标准输出（已解码）
+    pub output: String,
+}
+
+/// 平台统一的 TTL 受限 ping 入口（连通性检查的逐跳诊断）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: ping -n 1 -i <ttl> -w <毫秒>
+/// - macOS: ping -c 1 -m <ttl> -t <秒>
+///
+/// # 参数
+/// - `target`: 目标地址（仅 IPv4）
+/// - `ttl`: 生存时间（跳数）
+/// - `timeout_ms`: 等待回复的超时时间
+pub fn ping_with_ttl(target: std::net::Ipv4Addr, ttl: u8, timeout_ms: u32) -> Result<PingProbeOutput, String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::route_probe::ping_with_ttl(target, ttl, timeout_ms)
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        crate::platform::macos::route_probe::ping_with_ttl(target, ttl, timeout_ms)
+    }
+
+    #[cfg(not(any(windows, target_os = "macos")))]
+    {
+        let _ = (target, ttl, timeout_ms);
+        Err("当前仅支持 Windows 和 macOS 平台".to_string())
+    }
+}
+
+/// WebView 进程失败类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum WebviewFailure {
+    /// 浏览器主进程退出（WebView2 运行时更新 / 崩溃），当前窗口无法恢复
+    BrowserExited,
+    /// 渲染进程退出，重新加载页面可恢复
+    RendererExited,
+    /// 渲染进程无响应，重新加载页面可恢复
+    RendererUnresponsive,
+    /// 其他子进程退出（GPU / 工具进程等，WebView 会自行重建），值为原始类型码
+    Other(i32),
+}
+
+/// 平台统一的 WebView 进程监控入口
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: 注册 WebView2 ProcessFailed 回调
+/// - macOS: WKWebView 内容进程退出由系统自动重新加载，不注册
+///
+/// # 参数
+/// - `window`: 主窗口
+/// - `on_failed`: 进程失败时调用（主线程，应尽快返回）
+pub fn watch_webview_process<F>(window: &tauri::Window, on_failed: F) -> Result<(), String>
+where
+    F: Fn(WebviewFailure) + Send + 'static,
+{
+    #[cfg(windows)]
+    {
+        crate::platform::windows::webview_watchdog::watch(window, on_failed)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (window, on_failed);
+        Ok(())
+    }
+}
+
+/// 平台统一的 WebView 重新加载入口
+///
+/// # 参数
+/// - `window`: 主窗口
+pub fn reload_webview(window: &tauri::Window) -> Result<(), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::webview_watchdog::reload(window)
+    }
+
+    #[cfg(not(windows))]
+    {
+        window.eval("window.location.reload()").map_err(|e| format!("重新加载页面失败: {}", e))
+    }
+}
+
+/// 平台统一的原生确认对话框入口（WebView 不可用时使用，阻塞直到用户选择）
+///
+/// # 返回
+/// - `true`: 用户确认（非 Windows 平台没有原生对话框实现，始终返回 true）
+pub fn confirm_native_dialog(message: &str) -> bool {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::webview_watchdog::confirm_dialog(message)
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = message;
+        true
+    }
+}
+
+/// 已获取的驱动包（自检使用）
+#[derive(Debug, Clone)]
+pub struct FetchedDriverPayload {
+    pub driver_uuid: String,
+    pub payload_zip: std::path::PathBuf,
+    /// "cache" | "download" | "share" | "patch"
+    pub source_used: String,
+    pub bytes: u64,
+}
+
+/// 平台统一的驱动包获取入口（与安装流程使用同一下载 / 校验实现，不发送进度事件）
+///
+/// 根据当前平台调用相应的实现：
+/// - Windows: driver_fetch::ensure_payload_zip
+/// - macOS: 不使用驱动包，返回错误
+///
+/// # 参数
+/// - `drivers_root`: 驱动根目录（自检时为临时目录）
+/// - `remote_url`: 驱动包地址
+/// - `expected_sha256` / `expected_size`: 期望的哈希与大小
+/// - `job_id`: 任务 ID（仅用于日志）
+pub async fn fetch_driver_payload(
+    drivers_root: &std::path::Path,
+    remote_url: &str,
+    expected_sha256: &str,
+    expected_size: Option<u64>,
+    job_id: &str,
+) -> Result<FetchedDriverPayload, String> {
+    #[cfg(windows)]
+    {
+        let result = crate::platform::windows::driver_fetch::ensure_payload_zip(
+            drivers_root,
+            remote_url,
+            expected_sha256,
+            expected_size,
+            None,
+            crate::platform::windows::driver_fetch::FetchProgress { app: None, printer_name: None, job_id },
+        )
+        .await
+        .map_err(|e| format!("[{}] {}", e.code(), e))?;
+        Ok(FetchedDriverPayload {
+            driver_uuid: result.driver_uuid,
+            payload_zip: result.payload_zip,
+            source_used: result.source_used,
+            bytes: result.bytes,
+        })
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (drivers_root, remote_url, expected_sha256, expected_size, job_id);
+        Err("当前平台不使用驱动包".to_string())
+    }
+}
+
+/// 平台统一的驱动包解压入口（与安装流程使用同一解压实现，不发送进度事件）
+///
+/// # 参数
+/// - `payload_zip`: 驱动包 ZIP
+/// - `drivers_root`: 驱动根目录
+/// - `driver_uuid`: 驱动 UUID（fetch_driver_payload 的返回值）
+/// - `job_id`: 任务 ID（仅用于日志）
+///
+/// # 返回
+/// - `Ok((PathBuf, usize))`: 解压目录与文件数
+pub fn extract_driver_payload(
+    payload_zip: &std::path::Path,
+    drivers_root: &std::path::Path,
+    driver_uuid: &str,
+    job_id: &str,
+) -> Result<(std::path::PathBuf, usize), String> {
+    #[cfg(windows)]
+    {
+        crate::platform::windows::archive::extract_zip_for_driver(payload_zip, drivers_root, driver_uuid, None, None, None, job_id)
+            .map(|result| (result.extracted_root, result.file_count))
+            .map_err(|e| format!("[{}] {}", e.code(), e))
+    }
+
+    #[cfg(not(windows))]
+    {
+        let _ = (payload_zip, drivers_root, driver_uuid, job_id);
+        Err("当前平台不使用驱动包".to_string())
+    }
+}
@@ -1,3 +1,28 @@
+// 测试页内容生成（print_test_page 与 render_test_page_preview 共用）
+// - Windows：Out-Printer 读取带 UTF-8 BOM 的文本文件
+// - macOS：lp 直接提交 UTF-8 文本文件（无 BOM）
+
+use serde::{Deserialize, Serialize};
+
+/// 测试页中的时间格式
+pub const TEST_PAGE_TIME_FORMAT: &str = "%Y年%m月%d日 %H:%M:%S";
+
+/// 测试页预览（与实际提交给打印机的字节完全一致）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestPagePreview {
+    pub printer_name: String,
+    /// 目前测试页只有文本格式："text"
+    pub format: String,
+    pub mime_type: String,
+    /// 测试页文本（不含 BOM，供预览面板直接渲染）
+    pub content: String,
+    /// 写有完整字节（含平台前缀）的临时文件路径
+    pub file_path: String,
+    pub size_bytes: u64,
+    pub generated_at: String,
+}
+
 pub fn build_test_page_content(printer_name: &str, test_time: &str) -> String {
     format!(
 r#"
@@ -61,3 +86,47 @@ English: Hello World!
         test_time = test_time
     )
 }
+
+/// 将测试页文本编码为实际提交给打印机的字节
+///
+/// Windows 平台在前面加 UTF-8 BOM（Out-Printer 读取时依赖 BOM 识别编码）
+pub fn build_test_page_bytes(content: &str) -> Vec<u8> {
+    if cfg!(windows) {
+        crate::utils::encoding::encode_utf8_with_bom(content)
+    } else {
+        content.as_bytes().to_vec()
+    }
+}
+
+/// 生成测试页预览：内容与 print_test_page 相同，字节写入临时文件
+///
+/// # 参数
+/// - `printer_name`: 测试页上显示的打印机名称
+///
+/// # 返回
+/// - `Ok(TestPagePreview)`: 预览内容与临时文件路径
+/// - `Err(String)`: 临时文件写入失败
+pub fn render_test_page_preview(printer_name: &str) -> Result<TestPagePreview, String> {
+    let now = chrono::Local::now();
+    let generated_at = now.format(TEST_PAGE_TIME_FORMAT).to_string();
+    let content = build_test_page_content(printer_name, &generated_at);
+    let bytes = build_test_page_bytes(&content);
+
+    let temp_file = std::env::temp_dir().join(format!(
+        "eprinty_testpage_preview_{}_{}.txt",
+        std::process::id(),
+        now.timestamp_millis()
+    ));
+    std::fs::write(&temp_file, &bytes)
+        .map_err(|e| format!("写入测试页预览文件失败 {}: {}", temp_file.display(), e))?;
+
+    Ok(TestPagePreview {
+        printer_name: printer_name.to_string(),
+        format: "text".to_string(),
+        mime_type: "text/plain; charset=utf-8".to_string(),
+        content,
+        file_path: temp_file.display().to_string(),
+        size_bytes: bytes.len() as u64,
+        generated_at,
+    })
+}
@@ -5,7 +5,24 @@
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::Manager;
+use crate::platform::ps_script::PsScript;
+
+/// 是否保留驱动包解压失败时的临时目录（用户设置 keepStaging，便于排查）
+static KEEP_STAGING: AtomicBool = AtomicBool::new(false);
+
+/// extract_zip_for_driver 的临时解压目录 / 旧 extracted 备份目录前缀（uuid_root 下与 extracted 同级）
+const EXTRACT_TEMP_PREFIX: &str = ".extracted-";
+const EXTRACT_BACKUP_PREFIX: &str = ".extracted-old-";
+
+/// 旧版本 extract_zip_for_driver 使用的 staging 目录（启动解压时顺带清理）
+const LEGACY_STAGING_DIR: &str = "_staging";
+
+/// 设置是否保留 staging 目录（由用户设置同步）
+pub fn set_keep_staging(keep: bool) {
+    KEEP_STAGING.store(keep, Ordering::SeqCst);
+}
 
 /// 解压结果（旧版，保留用于兼容）
 #[derive(Debug, Clone)]
@@ -67,6 +84,28 @@ pub enum ExtractError {
         operation: &'static str,
         error: String,
     },
+    /// 驱动包已加密，但未配置或未保存解压密码
+    ArchivePasswordRequired {
+        zip_path: String,
+        entry_name: String,
+    },
+    /// 驱动包解压密码错误
+    BadArchivePassword {
+        zip_path: String,
+        entry_name: String,
+    },
+}
+
+impl ExtractError {
+    /// 错误码（用于安装结果中的 [EASYPRINTER_CODE=...] 与步骤事件）
+    pub fn code(&self) -> &'static str {
+        match self {
+            ExtractError::ArchivePasswordRequired { .. } => "ARCHIVE_PASSWORD_REQUIRED",
+            ExtractError::BadArchivePassword { .. } => "BAD_ARCHIVE_PASSWORD",
+            ExtractError::ZipSlipDetected { .. } => "ZIP_SLIP_DETECTED",
+            _ => "EXTRACT_FAILED",
+        }
+    }
 }
 
 impl std::fmt::Display for ExtractError {
@@ -97,6 +136,14 @@ impl std::fmt::Display for ExtractError {
             ExtractError::IoError { step, operation, error } => {
                 write!(f, "IO 错误 (step={}, operation={}): {}", step, operation, error)
             }
+            ExtractError::ArchivePasswordRequired { zip_path, entry_name } => {
+                write!(f, "驱动包已加密，需要解压密码（请在配置中设置 archivePasswordRef 并保存对应的密码）\nZIP: {}\n条目: {}",
+                    zip_path, entry_name)
+            }
+            ExtractError::BadArchivePassword { zip_path, entry_name } => {
+                write!(f, "驱动包解压密码错误（请重新保存 archivePasswordRef 对应的密码）\nZIP: {}\n条目: {}",
+                    zip_path, entry_name)
+            }
         }
     }
 }
@@ -404,14 +451,12 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path, drivers_root: &Path) -> Res
     let zip_path_str = zip_path.to_string_lossy();
     let staging_dir_str = staging_dir.to_string_lossy();
     
-    // 转义路径中的单引号（PowerShell 需要）
-    let zip_path_escaped = zip_path_str.replace("'", "''");
-    let staging_dir_escaped = staging_dir_str.replace("'", "''");
-    
-    let script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Expand-Archive -LiteralPath '{}' -DestinationPath '{}' -Force",
-        zip_path_escaped, staging_dir_escaped
-    );
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Expand-Archive -LiteralPath $ZipPath -DestinationPath $StagingDir -Force",
+    )
+    .param("ZipPath", &zip_path_str)
+    .param("StagingDir", &staging_dir_str)
+    .build();
     
     eprintln!("[ExtractZip] step=expand_archive inputs=zip_path=\"{}\" staging_dir=\"{}\"", 
         zip_path.display(), staging_dir.display());
@@ -430,8 +475,8 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path, drivers_root: &Path) -> Res
         }
     };
     
-    let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
-    let stderr = crate::platform::windows::encoding::decode_windows_string(&output.stderr);
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
     let exit_code = output.status.code();
     
     if !output.status.success() {
@@ -530,7 +575,7 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path, drivers_root: &Path) -> Res
                 }
             };
             
-            if !canonical_path.starts_with(&canonical_staging_dir) {
+            if !canonical_path.starts_with(canonical_staging_dir) {
                 *offending_path = Some(path.clone());
                 let evidence = format!(
                     "step=zip_slip_check offending_path=\"{}\" canonical_staging=\"{}\" canonical_offending=\"{}\"",
@@ -742,29 +787,30 @@ pub fn extract_zip(zip_path: &Path, dest_dir: &Path, drivers_root: &Path) -> Res
 /// - `zip_path`: ZIP 文件路径
 /// - `drivers_root`: 驱动根目录（AppDir/drivers）
 /// - `driver_uuid`: 驱动 UUID（必须满足格式：^[a-zA-Z0-9_-]{8,64}$）
+/// - `archive_password`: 加密驱动包的解压密码（None 表示未加密）
 /// 
 /// # 返回
 /// - `Ok(ExtractForDriverResult)`: 解压成功
+/// - `Err(ExtractError::ArchivePasswordRequired / BadArchivePassword)`: 驱动包已加密但无密码 / 密码错误
 /// - `Err(ExtractError)`: 解压失败
 /// 
 /// # 目录结构
 /// - uuid_root = drivers_root.join(driver_uuid)
-/// - staging_root = uuid_root.join("_staging")
-/// - staging_dir = staging_root.join(<run_id_uuid>)
+/// - temp_dir = uuid_root.join(".extracted-<run_id>")（与 extracted_root 同卷的兄弟目录）
 /// - extracted_root = uuid_root.join("extracted")
 /// 
 /// # 步骤
 /// 1. compute_roots: 计算所有根目录
-/// 2. prepare_dirs: 创建必要的目录
-/// 3. expand_archive: 解压到 staging
-/// 4. zip_slip_check: 检测路径越界（相对于 uuid_root）
-/// 5. materialize: 将 staging 内容复制到 extracted_root
-/// 6. cleanup_staging: 清理 staging 目录
-/// 7. summary: 统计并输出摘要
+/// 2. prepare_dirs: 创建 uuid_root，清理上次中断遗留的临时目录，创建 temp_dir
+/// 3. expand_archive: 直接解压到 temp_dir（只写一遍磁盘）
+/// 4. zip_slip_check: 检测路径越界（相对于 temp_dir）
+/// 5. materialize: 旧 extracted_root 改名为备份，temp_dir 原子改名为 extracted_root（失败时还原备份）
+/// 6. summary: 统计并输出摘要
 pub fn extract_zip_for_driver(
     zip_path: &Path,
     drivers_root: &Path,
     driver_uuid: &str,
+    archive_password: Option<&str>,
     app: Option<&tauri::AppHandle>,
     printer_name: Option<&str>,
     job_id: &str,  // 安装任务 ID
@@ -785,101 +831,80 @@ pub fn extract_zip_for_driver(
     // Step 1: compute_roots - 计算所有根目录
     // ============================================================================
     let uuid_root = drivers_root.join(driver_uuid);
-    let staging_root = uuid_root.join("_staging");
     let run_id = generate_driver_uuid();
-    let staging_dir = staging_root.join(&run_id);
+    let temp_dir = uuid_root.join(format!("{}{}", EXTRACT_TEMP_PREFIX, run_id));
+    let backup_dir = uuid_root.join(format!("{}{}", EXTRACT_BACKUP_PREFIX, run_id));
     let extracted_root = uuid_root.join("extracted");
     
     eprintln!("[ExtractZipForDriver] step=compute_roots inputs=drivers_root=\"{}\" driver_uuid=\"{}\" run_id=\"{}\"", 
         drivers_root.display(), driver_uuid, run_id);
-    eprintln!("[ExtractZipForDriver] step=compute_roots outputs=uuid_root=\"{}\" staging_root=\"{}\" staging_dir=\"{}\" extracted_root=\"{}\"", 
-        uuid_root.display(), staging_root.display(), staging_dir.display(), extracted_root.display());
+    eprintln!("[ExtractZipForDriver] step=compute_roots outputs=uuid_root=\"{}\" temp_dir=\"{}\" extracted_root=\"{}\"", 
+        uuid_root.display(), temp_dir.display(), extracted_root.display());
     
     // ============================================================================
     // Step 2: prepare_dirs - 创建必要的目录
     // ============================================================================
-    eprintln!("[ExtractZipForDriver] step=prepare_dirs inputs=staging_dir=\"{}\" extracted_root=\"{}\"", 
-        staging_dir.display(), extracted_root.display());
+    eprintln!("[ExtractZipForDriver] step=prepare_dirs inputs=uuid_root=\"{}\" temp_dir=\"{}\"", 
+        uuid_root.display(), temp_dir.display());
     
-    // 创建 staging_root（如果不存在）
-    if let Err(e) = fs::create_dir_all(&staging_root) {
+    if let Err(e) = fs::create_dir_all(&uuid_root) {
         return Err(ExtractError::IoError {
             step: "prepare_dirs",
-            operation: "创建 staging 根目录",
-            error: format!("无法创建 staging 根目录 {}: {}", staging_root.display(), e),
+            operation: "创建 uuid 根目录",
+            error: format!("无法创建 uuid 根目录 {}: {}", uuid_root.display(), e),
         });
     }
     
-    // 如果 staging_dir 已存在，删除它（只删除 staging_dir 自身，不删除 uuid_root）
-    if staging_dir.exists() {
-        eprintln!("[ExtractZipForDriver] step=prepare_dirs action=remove_existing_staging staging_dir=\"{}\"", staging_dir.display());
-        if let Err(e) = fs::remove_dir_all(&staging_dir) {
-            return Err(ExtractError::IoError {
-                step: "prepare_dirs",
-                operation: "删除现有 staging 目录",
-                error: format!("无法删除现有 staging 目录 {}: {}", staging_dir.display(), e),
-            });
-        }
-    }
-    
-    // 创建 staging_dir
-    if let Err(e) = fs::create_dir_all(&staging_dir) {
-        return Err(ExtractError::IoError {
-            step: "prepare_dirs",
-            operation: "创建 staging 目录",
-            error: format!("无法创建 staging 目录 {}: {}", staging_dir.display(), e),
-        });
-    }
+    // 清理上次中断遗留的临时 / 备份目录，以及旧版本的 _staging 目录（失败只记录日志）
+    remove_stale_extract_dirs(&uuid_root);
     
-    // 创建 extracted_root（如果不存在，但不删除现有内容）
-    if let Err(e) = fs::create_dir_all(&extracted_root) {
+    if let Err(e) = fs::create_dir(&temp_dir) {
         return Err(ExtractError::IoError {
             step: "prepare_dirs",
-            operation: "创建 extracted 根目录",
-            error: format!("无法创建 extracted 根目录 {}: {}", extracted_root.display(), e),
+            operation: "创建临时解压目录",
+            error: format!("无法创建临时解压目录 {}: {}", temp_dir.display(), e),
         });
     }
     
-    eprintln!("[ExtractZipForDriver] step=prepare_dirs result=created staging_dir=\"{}\" extracted_root=\"{}\"", 
-        staging_dir.display(), extracted_root.display());
+    eprintln!("[ExtractZipForDriver] step=prepare_dirs result=created temp_dir=\"{}\"", temp_dir.display());
     
-    // 确保在函数返回前清理 staging_dir（使用 Drop trait）
-    // 支持环境变量 EPRINTY_KEEP_STAGING 来保留 staging 以便排查
-    struct StagingCleanup {
-        staging_dir: PathBuf,
+    // 函数返回前仍存在的 temp_dir 说明解压未完成（成功时已改名为 extracted_root），直接删除
+    // 用户设置 keepStaging=true 时保留以便排查
+    struct TempDirCleanup {
+        temp_dir: PathBuf,
         should_cleanup: bool,
     }
     
-    impl Drop for StagingCleanup {
+    impl Drop for TempDirCleanup {
         fn drop(&mut self) {
-            if self.staging_dir.exists() {
+            if self.temp_dir.exists() {
                 if self.should_cleanup {
-                    if let Err(e) = std::fs::remove_dir_all(&self.staging_dir) {
-                        eprintln!("[ExtractZipForDriver] cleanup_failed staging_dir=\"{}\" error=\"{}\"", 
-                            self.staging_dir.display(), e);
+                    if let Err(e) = std::fs::remove_dir_all(&self.temp_dir) {
+                        eprintln!("[ExtractZipForDriver] cleanup_failed temp_dir=\"{}\" error=\"{}\"", 
+                            self.temp_dir.display(), e);
                     } else {
-                        eprintln!("[ExtractZipForDriver] cleanup_success staging_dir=\"{}\"", 
-                            self.staging_dir.display());
+                        eprintln!("[ExtractZipForDriver] cleanup_success temp_dir=\"{}\"", 
+                            self.temp_dir.display());
                     }
                 } else {
-                    eprintln!("[ExtractZipForDriver] cleanup_skipped staging_dir=\"{}\" reason=\"EPRINTY_KEEP_STAGING 已设置\"", 
-                        self.staging_dir.display());
+                    eprintln!("[ExtractZipForDriver] cleanup_skipped temp_dir=\"{}\" reason=\"keepStaging 已开启\"", 
+                        self.temp_dir.display());
                 }
             }
         }
     }
     
-    // 检查环境变量决定是否清理 staging
-    let keep_staging = std::env::var("EPRINTY_KEEP_STAGING").is_ok();
+    // 检查用户设置决定是否清理临时目录
+    let keep_staging = KEEP_STAGING.load(Ordering::SeqCst);
     let should_cleanup = !keep_staging;
     
-    let _cleanup = StagingCleanup {
-        staging_dir: staging_dir.clone(),
+    let _cleanup = TempDirCleanup {
+        temp_dir: temp_dir.clone(),
         should_cleanup,
     };
     
     // ============================================================================
-    // Step 3: expand_archive - 解压到 staging（使用 Rust 原生 zip crate）
+    // Step 3: expand_archive - 直接解压到 temp_dir（使用 Rust 原生 zip crate）
     // ============================================================================
     // 检查 ZIP 文件是否存在
     if !zip_path.exists() {
@@ -889,10 +914,10 @@ pub fn extract_zip_for_driver(
     }
     
     let zip_path_str = zip_path.to_string_lossy().to_string();
-    let staging_dir_str = staging_dir.to_string_lossy().to_string();
+    let temp_dir_str = temp_dir.to_string_lossy().to_string();
     
-    eprintln!("[ExtractZipForDriver] step=expand_archive inputs=zip_path=\"{}\" staging_dir=\"{}\"", 
-        zip_path.display(), staging_dir.display());
+    eprintln!("[ExtractZipForDriver] step=expand_archive inputs=zip_path=\"{}\" temp_dir=\"{}\"", 
+        zip_path.display(), temp_dir.display());
     
     // 创建 StepReporter（用于整个解压流程）
     let mut step_reporter_opt: Option<crate::platform::windows::step_reporter::StepReporter> = if let (Some(app_handle), Some(printer)) = (app, printer_name) {
@@ -908,37 +933,56 @@ pub fn extract_zip_for_driver(
     };
     
     // 使用 Rust 原生 zip 解压器
-    let extract_result = match crate::utils::zip_extract::extract_zip_to_dir(
+    let extract_result = match crate::utils::zip_extract::extract_zip_to_dir_with_password(
         zip_path,
-        &staging_dir,
+        &temp_dir,
+        archive_password,
         None, // cancel flag (暂不支持)
         None, // progress callback (暂不集成，后续可加)
     ) {
         Ok(report) => report,
         Err(e) => {
             let error_msg = format!("{}", e);
-            let staging_hint = if should_cleanup {
-                format!("（staging 目录将在函数返回时自动清理）")
+            let temp_hint = if should_cleanup {
+                "（临时解压目录将在函数返回时自动清理）".to_string()
             } else {
-                format!("（staging 目录已保留供排查：{}）", staging_dir.display())
+                format!("（临时解压目录已保留供排查：{}）", temp_dir.display())
             };
-            eprintln!("[ExtractZipForDriver] step=expand_archive result=failed error=\"{}\" {}", error_msg, staging_hint);
+            eprintln!("[ExtractZipForDriver] step=expand_archive result=failed error=\"{}\" {}", error_msg, temp_hint);
+            
+            // 加密相关的失败单独给出错误码
+            use crate::utils::zip_extract::ExtractError as ZipError;
+            let password_error = match e {
+                ZipError::PasswordRequired { entry_name } => Some(ExtractError::ArchivePasswordRequired {
+                    zip_path: zip_path_str.clone(),
+                    entry_name,
+                }),
+                ZipError::BadPassword { entry_name } => Some(ExtractError::BadArchivePassword {
+                    zip_path: zip_path_str.clone(),
+                    entry_name,
+                }),
+                _ => None,
+            };
+            let code = password_error.as_ref().map(|e| e.code()).unwrap_or("EXTRACT_FAILED");
             
             // 发送失败事件
             if let Some(reporter) = step_reporter_opt.take() {
                 let _ = reporter.failed(
-                    "EXTRACT_FAILED".to_string(),
-                    format!("解压失败: {} | ZIP: {} | Dest: {} | {}", error_msg, zip_path.display(), staging_dir.display(), staging_hint),
+                    code.to_string(),
+                    format!("解压失败: {} | ZIP: {} | Dest: {} | {}", error_msg, zip_path.display(), temp_dir.display(), temp_hint),
                     None,
                     Some(error_msg.clone()),
                     None,
                 );
             }
             
+            if let Some(password_error) = password_error {
+                return Err(password_error);
+            }
             return Err(ExtractError::ExtractFailed {
                 step: "expand_archive",
                 zip_path: zip_path_str,
-                dest_dir: staging_dir_str,
+                dest_dir: temp_dir_str,
                 stdout: String::new(),
                 stderr: error_msg,
                 exit_code: None,
@@ -955,21 +999,21 @@ pub fn extract_zip_for_driver(
     );
     
     // ============================================================================
-    // Step 4: zip_slip_check - 检测路径越界（相对于 uuid_root）
+    // Step 4: zip_slip_check - 检测路径越界（相对于 temp_dir，改名前完成）
     // ============================================================================
-    let canonical_uuid_root = match uuid_root.canonicalize() {
+    let canonical_temp_dir = match temp_dir.canonicalize() {
         Ok(path) => path,
         Err(e) => {
             return Err(ExtractError::IoError {
                 step: "zip_slip_check",
-                operation: "规范化 uuid 根目录",
-                error: format!("无法规范化 uuid 根目录 {}: {}", uuid_root.display(), e),
+                operation: "规范化临时解压目录",
+                error: format!("无法规范化临时解压目录 {}: {}", temp_dir.display(), e),
             });
         }
     };
     
-    eprintln!("[ExtractZipForDriver] step=zip_slip_check inputs=uuid_root=\"{}\" canonical_uuid_root=\"{}\" staging_dir=\"{}\"", 
-        uuid_root.display(), canonical_uuid_root.display(), staging_dir.display());
+    eprintln!("[ExtractZipForDriver] step=zip_slip_check inputs=temp_dir=\"{}\" canonical_temp_dir=\"{}\"", 
+        temp_dir.display(), canonical_temp_dir.display());
     
     // 递归遍历所有文件
     let mut file_count = 0;
@@ -978,7 +1022,7 @@ pub fn extract_zip_for_driver(
     
     fn walk_dir_for_uuid(
         dir: &Path,
-        canonical_uuid_root: &Path,
+        canonical_root: &Path,
         file_count: &mut usize,
         dir_count: &mut usize,
         offending_path: &mut Option<PathBuf>,
@@ -1018,7 +1062,7 @@ pub fn extract_zip_for_driver(
                 }
             };
             
-            // 规范化路径并检查是否在 uuid_root 内（必须在递归之前检查）
+            // 规范化路径并检查是否在解压目录内（必须在递归之前检查）
             let canonical_path = match path.canonicalize() {
                 Ok(canonical) => canonical,
                 Err(e) => {
@@ -1030,17 +1074,17 @@ pub fn extract_zip_for_driver(
                 }
             };
             
-            if !canonical_path.starts_with(canonical_uuid_root) {
+            if !canonical_path.starts_with(canonical_root) {
                 *offending_path = Some(path.clone());
                 let evidence = format!(
-                    "step=zip_slip_check offending_path=\"{}\" canonical_uuid_root=\"{}\" canonical_offending=\"{}\"",
-                    path.display(), canonical_uuid_root.display(), canonical_path.display()
+                    "step=zip_slip_check offending_path=\"{}\" canonical_root=\"{}\" canonical_offending=\"{}\"",
+                    path.display(), canonical_root.display(), canonical_path.display()
                 );
                 eprintln!("[ExtractZipForDriver] step=zip_slip_check result=failed evidence=\"{}\"", evidence);
                 
                 return Err(ExtractError::ZipSlipDetected {
                     offending_path: path.display().to_string(),
-                    canonical_dest_dir: canonical_uuid_root.display().to_string(),
+                    canonical_dest_dir: canonical_root.display().to_string(),
                     canonical_offending_path: canonical_path.display().to_string(),
                 });
             }
@@ -1048,7 +1092,7 @@ pub fn extract_zip_for_driver(
             if metadata.is_dir() {
                 *dir_count += 1;
                 // 递归检查子目录（只有在路径检查通过后才递归）
-                walk_dir_for_uuid(&path, canonical_uuid_root, file_count, dir_count, offending_path)?;
+                walk_dir_for_uuid(&path, canonical_root, file_count, dir_count, offending_path)?;
             } else {
                 *file_count += 1;
             }
@@ -1057,11 +1101,10 @@ pub fn extract_zip_for_driver(
         Ok(())
     }
     
-    match walk_dir_for_uuid(&staging_dir, &canonical_uuid_root, &mut file_count, &mut dir_count, &mut offending_path) {
+    match walk_dir_for_uuid(&temp_dir, &canonical_temp_dir, &mut file_count, &mut dir_count, &mut offending_path) {
         Ok(_) => {
             eprintln!("[ExtractZipForDriver] step=zip_slip_check result=passed files={} dirs={}", file_count, dir_count);
             
-            // 发送 zip-slip 检查通过事件
             // 更新进度：zip-slip 检查通过
             if let Some(ref mut reporter) = step_reporter_opt {
                 reporter.update_progress(
@@ -1089,115 +1132,23 @@ pub fn extract_zip_for_driver(
     }
     
     // ============================================================================
-    // Step 5: materialize - 将 staging 内容复制到 extracted_root
+    // Step 5: materialize - temp_dir 原子改名为 extracted_root
     // ============================================================================
-    eprintln!("[ExtractZipForDriver] step=materialize inputs=staging_dir=\"{}\" extracted_root=\"{}\"", 
-        staging_dir.display(), extracted_root.display());
-    
-    // 发送 materialize 开始事件
-    // 更新进度：开始合并文件
-    if let Some(ref mut reporter) = step_reporter_opt {
-        reporter.update_progress(
-            Some(0),
-            Some(file_count as u64),
-            Some("files".to_string()),
-            Some(0.0),
-            Some("正在合并文件".to_string()),
-        );
-    }
-    
-    let mut copied_files = 0;
-    
-    fn copy_tree_to_extracted(
-        src: &Path,
-        dest: &Path,
-        staging_base: &Path,
-        extracted_base: &Path,
-        copied_files: &mut usize,
-    ) -> Result<(), ExtractError> {
-        let entries = match fs::read_dir(src) {
-            Ok(entries) => entries,
-            Err(e) => {
-                return Err(ExtractError::IoError {
-                    step: "materialize",
-                    operation: "读取 staging 目录",
-                    error: format!("无法读取 staging 目录 {}: {}", src.display(), e),
-                });
-            }
-        };
-        
-        for entry in entries {
-            let entry = match entry {
-                Ok(entry) => entry,
-                Err(e) => {
-                    return Err(ExtractError::IoError {
-                        step: "materialize",
-                        operation: "读取目录项",
-                        error: format!("无法读取目录项: {}", e),
-                    });
-                }
-            };
-            
-            let src_path = entry.path();
-            let relative_path = src_path.strip_prefix(staging_base)
-                .map_err(|e| ExtractError::IoError {
-                    step: "materialize",
-                    operation: "计算相对路径",
-                    error: format!("无法计算相对路径: {}", e),
-                })?;
-            
-            let dest_path = extracted_base.join(relative_path);
-            
-            let metadata = match entry.metadata() {
-                Ok(meta) => meta,
-                Err(e) => {
-                    return Err(ExtractError::IoError {
-                        step: "materialize",
-                        operation: "获取文件元数据",
-                        error: format!("无法获取文件元数据 {}: {}", src_path.display(), e),
-                    });
-                }
-            };
-            
-            if metadata.is_dir() {
-                // 创建目标目录
-                if let Err(e) = fs::create_dir_all(&dest_path) {
-                    return Err(ExtractError::IoError {
-                        step: "materialize",
-                        operation: "创建目标目录",
-                        error: format!("无法创建目标目录 {}: {}", dest_path.display(), e),
-                    });
-                }
-                // 递归复制子目录
-                copy_tree_to_extracted(&src_path, &dest_path, staging_base, extracted_base, copied_files)?;
-            } else {
-                // 复制文件（允许覆盖）
-                if let Err(e) = fs::copy(&src_path, &dest_path) {
-                    return Err(ExtractError::IoError {
-                        step: "materialize",
-                        operation: "复制文件",
-                        error: format!("无法复制文件 {} -> {}: {}", src_path.display(), dest_path.display(), e),
-                    });
-                }
-                *copied_files += 1;
-            }
-        }
-        
-        Ok(())
-    }
+    eprintln!("[ExtractZipForDriver] step=materialize inputs=temp_dir=\"{}\" extracted_root=\"{}\" backup_dir=\"{}\"", 
+        temp_dir.display(), extracted_root.display(), backup_dir.display());
     
-    match copy_tree_to_extracted(&staging_dir, &extracted_root, &staging_dir, &extracted_root, &mut copied_files) {
-        Ok(_) => {
-            eprintln!("[ExtractZipForDriver] step=materialize result=success copied_files={}", copied_files);
+    match swap_into_place(&temp_dir, &extracted_root, &backup_dir) {
+        Ok(replaced) => {
+            eprintln!("[ExtractZipForDriver] step=materialize result=success replaced_existing={}", replaced);
             
             // 发送解压成功事件
             if let Some(reporter) = step_reporter_opt.take() {
                 let meta = serde_json::json!({
-                    "copied_files": copied_files,
                     "file_count": file_count,
+                    "replaced_existing": replaced,
                 });
                 let _ = reporter.success(
-                    format!("解压完成：已合并 {} 个文件", copied_files),
+                    format!("解压完成：共 {} 个文件", file_count),
                     Some(meta),
                 );
             }
@@ -1207,7 +1158,7 @@ pub fn extract_zip_for_driver(
             if let Some(reporter) = step_reporter_opt.take() {
                 let _ = reporter.failed(
                     "MATERIALIZE_FAILED".to_string(),
-                    format!("合并文件失败: {}", e),
+                    format!("替换解压目录失败: {}", e),
                     None,
                     None,
                     None,
@@ -1218,22 +1169,7 @@ pub fn extract_zip_for_driver(
     }
     
     // ============================================================================
-    // Step 6: cleanup_staging - 清理 staging 目录
-    // ============================================================================
-    eprintln!("[ExtractZipForDriver] step=cleanup_staging inputs=staging_dir=\"{}\"", staging_dir.display());
-    
-    // 注意：StagingCleanup 会在函数返回时自动清理，但我们可以显式清理
-    if staging_dir.exists() {
-        if let Err(e) = fs::remove_dir_all(&staging_dir) {
-            eprintln!("[ExtractZipForDriver] step=cleanup_staging warning=failed error=\"{}\"", e);
-            // 不返回错误，因为解压已经成功
-        } else {
-            eprintln!("[ExtractZipForDriver] step=cleanup_staging result=removed staging_dir=\"{}\"", staging_dir.display());
-        }
-    }
-    
-    // ============================================================================
-    // Step 7: summary - 统计并输出摘要
+    // Step 6: summary - 统计并输出摘要
     // ============================================================================
     let mut top_entries = Vec::new();
     let entries = match fs::read_dir(&extracted_root) {
@@ -1268,8 +1204,8 @@ pub fn extract_zip_for_driver(
     };
     
     let evidence = format!(
-        "step=summary driver_uuid=\"{}\" uuid_root=\"{}\" extracted_root=\"{}\" files={} dirs={} copied_files={} top_entries={}",
-        driver_uuid, uuid_root.display(), extracted_root.display(), file_count, dir_count, copied_files, top_entries_display
+        "step=summary driver_uuid=\"{}\" uuid_root=\"{}\" extracted_root=\"{}\" files={} dirs={} top_entries={}",
+        driver_uuid, uuid_root.display(), extracted_root.display(), file_count, dir_count, top_entries_display
     );
     
     eprintln!("[ExtractZipForDriver] step=summary result=success evidence=\"{}\"", evidence);
@@ -1282,3 +1218,68 @@ pub fn extract_zip_for_driver(
         top_entries,
     })
 }
+
+/// 删除 uuid_root 下上次中断遗留的临时 / 备份目录，以及旧版本使用的 _staging 目录（失败只记录日志）
+fn remove_stale_extract_dirs(uuid_root: &Path) {
+    let Ok(entries) = fs::read_dir(uuid_root) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let stale = name == LEGACY_STAGING_DIR
+            || name.starts_with(EXTRACT_TEMP_PREFIX)
+            || name.starts_with(EXTRACT_BACKUP_PREFIX);
+        if !stale || !entry.path().is_dir() {
+            continue;
+        }
+        match fs::remove_dir_all(entry.path()) {
+            Ok(()) => eprintln!("[ExtractZipForDriver] step=prepare_dirs action=remove_stale dir=\"{}\"", entry.path().display()),
+            Err(e) => eprintln!("[ExtractZipForDriver] step=prepare_dirs warning=remove_stale_failed dir=\"{}\" error=\"{}\"", entry.path().display(), e),
+        }
+    }
+}
+
+/// 把已解压完成的 temp_dir 原子改名为 extracted_root
+///
+/// 已有 extracted_root 时先改名为 backup_dir；temp_dir 改名失败时把备份改回原名，
+/// 保证任何时刻 extracted_root 要么是旧版本、要么是完整的新版本。成功后删除备份（失败只记录日志）
+///
+/// # 返回
+/// - `Ok(bool)`: 是否替换了已有的 extracted_root
+fn swap_into_place(temp_dir: &Path, extracted_root: &Path, backup_dir: &Path) -> Result<bool, ExtractError> {
+    let replaced = extracted_root.exists();
+    if replaced {
+        if let Err(e) = fs::rename(extracted_root, backup_dir) {
+            return Err(ExtractError::IoError {
+                step: "materialize",
+                operation: "备份已有 extracted 目录",
+                error: format!(
+                    "无法将 {} 改名为 {}（目录中的文件可能正被占用）: {}",
+                    extracted_root.display(), backup_dir.display(), e
+                ),
+            });
+        }
+    }
+    
+    if let Err(e) = fs::rename(temp_dir, extracted_root) {
+        if replaced {
+            if let Err(restore_error) = fs::rename(backup_dir, extracted_root) {
+                eprintln!("[ExtractZipForDriver] step=materialize warning=restore_backup_failed backup_dir=\"{}\" error=\"{}\"", 
+                    backup_dir.display(), restore_error);
+            }
+        }
+        return Err(ExtractError::IoError {
+            step: "materialize",
+            operation: "改名临时解压目录",
+            error: format!("无法将 {} 改名为 {}: {}", temp_dir.display(), extracted_root.display(), e),
+        });
+    }
+    
+    if replaced {
+        if let Err(e) = fs::remove_dir_all(backup_dir) {
+            eprintln!("[ExtractZipForDriver] step=materialize warning=remove_backup_failed backup_dir=\"{}\" error=\"{}\"", 
+                backup_dir.display(), e);
+        }
+    }
+    Ok(replaced)
+}
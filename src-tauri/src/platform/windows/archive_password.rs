@@ -0,0 +1,103 @@
+// Windows 平台加密驱动包密码模块
+// 部分厂商以加密 ZIP 分发驱动包，解压密码不写入配置文件：
+// - 配置 driverCatalog.<key>.remote.archivePasswordRef 只给出引用名
+// - 密码按引用名保存在 Windows 凭据管理器，目标名 ePrinty/DriverArchive/<ref>，类型 GENERIC
+// - 解压远程驱动包前按引用名读取密码，交给 zip 解压器
+//
+// 封装入口：
+// - store_password() / delete_password()
+// - read_password(): 未保存时返回 Ok(None)
+
+use std::ptr;
+
+use winapi::shared::minwindef::{DWORD, FALSE};
+use winapi::shared::winerror::ERROR_NOT_FOUND;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::wincred::{
+    CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    PCREDENTIALW,
+};
+
+/// 凭据目标名前缀
+const TARGET_PREFIX: &str = "ePrinty/DriverArchive/";
+
+/// 凭据中记录的用户名（仅用于在凭据管理器中辨认）
+const CREDENTIAL_USER: &str = "ePrinty";
+
+/// 将 Rust String 转换为 UTF-16 宽字符串（以 null 结尾）
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+fn target_name(reference: &str) -> String {
+    format!("{}{}", TARGET_PREFIX, reference.to_lowercase())
+}
+
+/// 保存驱动包解压密码到 Windows 凭据管理器（已存在时覆盖）
+pub fn store_password(reference: &str, password: &str) -> Result<(), String> {
+    let mut target = to_wide(&target_name(reference));
+    let mut user = to_wide(CREDENTIAL_USER);
+    // 密码以 UTF-16（不含结尾 null）保存，与系统凭据的约定一致
+    let mut blob: Vec<u8> = password.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+
+    let mut credential: CREDENTIALW = unsafe { std::mem::zeroed() };
+    credential.Type = CRED_TYPE_GENERIC;
+    credential.TargetName = target.as_mut_ptr();
+    credential.UserName = user.as_mut_ptr();
+    credential.CredentialBlobSize = blob.len() as DWORD;
+    credential.CredentialBlob = if blob.is_empty() { ptr::null_mut() } else { blob.as_mut_ptr() };
+    credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+
+    let ok = unsafe { CredWriteW(&mut credential, 0) };
+    // 尽快清掉内存中的密码副本
+    blob.iter_mut().for_each(|b| *b = 0);
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        return Err(format!("保存驱动包密码失败（ref={}，错误码 {}）", reference, code));
+    }
+    eprintln!("[ArchivePassword] stored ref=\"{}\"", reference);
+    Ok(())
+}
+
+/// 删除驱动包解压密码（不存在时视为成功）
+pub fn delete_password(reference: &str) -> Result<bool, String> {
+    let target = to_wide(&target_name(reference));
+    let ok = unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) };
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        if code == ERROR_NOT_FOUND {
+            return Ok(false);
+        }
+        return Err(format!("删除驱动包密码失败（ref={}，错误码 {}）", reference, code));
+    }
+    eprintln!("[ArchivePassword] deleted ref=\"{}\"", reference);
+    Ok(true)
+}
+
+/// 读取驱动包解压密码：Ok(None) 表示未保存
+pub fn read_password(reference: &str) -> Result<Option<String>, String> {
+    let target = to_wide(&target_name(reference));
+    let mut credential: PCREDENTIALW = ptr::null_mut();
+    let ok = unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) };
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        if code == ERROR_NOT_FOUND {
+            return Ok(None);
+        }
+        return Err(format!("读取驱动包密码失败（ref={}，错误码 {}）", reference, code));
+    }
+
+    let password = unsafe {
+        let cred = &*credential;
+        let password = if cred.CredentialBlob.is_null() || cred.CredentialBlobSize == 0 {
+            String::new()
+        } else {
+            let bytes = std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize);
+            let wide: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&wide)
+        };
+        CredFree(credential as *mut _);
+        password
+    };
+    Ok(Some(password))
+}
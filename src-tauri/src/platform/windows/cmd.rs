@@ -2,8 +2,10 @@
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
+use std::io::Read;
 use std::process::Command;
 use std::process::Stdio;
+use std::thread::JoinHandle;
 use std::time::{Duration, Instant};
 
 #[cfg(windows)]
@@ -15,6 +17,43 @@ const COMMAND_TIMEOUT_SECS: u64 = 120;
 // 超时检查轮询间隔（毫秒）
 const POLL_INTERVAL_MS: u64 = 100;
 
+/// 子进程 stdout / stderr 的后台读取线程
+///
+/// 轮询 try_wait 期间必须持续读取管道：输出超过管道缓冲区（约 4 KB）后子进程会阻塞在写入上，永远不会退出
+pub(super) struct PipeDrain {
+    stdout: JoinHandle<Vec<u8>>,
+    stderr: JoinHandle<Vec<u8>>,
+}
+
+impl PipeDrain {
+    /// 取走子进程的 stdout / stderr 管道并开始读取
+    pub(super) fn start(child: &mut std::process::Child) -> Self {
+        PipeDrain {
+            stdout: spawn_reader(child.stdout.take()),
+            stderr: spawn_reader(child.stderr.take()),
+        }
+    }
+
+    /// 进程结束后取回完整输出（超时 / 出错时直接丢弃，子进程被终止后读取线程自行结束）
+    pub(super) fn finish(self, status: std::process::ExitStatus) -> std::process::Output {
+        std::process::Output {
+            status,
+            stdout: self.stdout.join().unwrap_or_default(),
+            stderr: self.stderr.join().unwrap_or_default(),
+        }
+    }
+}
+
+fn spawn_reader(pipe: Option<impl Read + Send + 'static>) -> JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut buf);
+        }
+        buf
+    })
+}
+
 /// 统一执行外部命令的封装函数
 /// 
 /// # 参数
@@ -27,39 +66,46 @@ const POLL_INTERVAL_MS: u64 = 100;
 /// 
 /// # 特性
 /// - Windows 下统一隐藏窗口（creation_flags 0x08000000）
-/// - stdout/stderr 管道
+/// - stdout/stderr 管道（后台线程持续读取，输出再多也不会阻塞子进程）
 /// - 不做编码解码，只返回 Output
 /// - 120 秒超时控制
 pub fn run_command(program: &str, args: &[&str]) -> Result<std::process::Output, String> {
     #[cfg(windows)]
     {
-        let mut child = Command::new(program)
+        let command_line = crate::utils::command_trace::format_command_line(program, args);
+        let start_time = Instant::now();
+        let mut child = super::process_watchdog::mark(&mut Command::new(program))
             .args(args)
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .creation_flags(CREATE_NO_WINDOW)
             .spawn()
-            .map_err(|e| format!("执行命令失败: {}", e))?;
+            .map_err(|e| {
+                super::job_log::append_command_trace(&command_line, None, start_time, Some("spawn_failed"));
+                format!("执行命令失败: {}", e)
+            })?;
+        let _tracked = super::process_watchdog::track(&child, program);
+        let drain = PipeDrain::start(&mut child);
         
-        let start_time = Instant::now();
         let timeout = Duration::from_secs(COMMAND_TIMEOUT_SECS);
         
         // 轮询检查进程是否完成
-        loop {
+        let status = loop {
             // 先检查超时
             if start_time.elapsed() >= timeout {
                 // 超时，杀死进程
                 let _ = child.kill();
                 // 等待进程结束，避免僵尸进程
                 let _ = child.wait();
+                super::job_log::append_command_trace(&command_line, None, start_time, Some("timeout"));
                 return Err(format!("执行命令超时: {}s", COMMAND_TIMEOUT_SECS));
             }
             
             match child.try_wait() {
-                Ok(Some(_status)) => {
+                Ok(Some(status)) => {
                     // 进程已完成，退出循环
-                    break;
+                    break status;
                 }
                 Ok(None) => {
                     // 进程仍在运行，等待后继续
@@ -72,11 +118,12 @@ pub fn run_command(program: &str, args: &[&str]) -> Result<std::process::Output,
                     return Err(format!("检查命令进程状态失败: {}", e));
                 }
             }
-        }
+        };
         
         // 循环结束后，获取输出
-        let output = child.wait_with_output()
-            .map_err(|e| format!("获取命令输出失败: {}", e))?;
+        let output = drain.finish(status);
+        super::job_log::append_command_trace(&command_line, output.status.code(), start_time, None);
+        super::job_log::append_process_output(&command_line, &output);
         Ok(output)
     }
     
@@ -89,12 +136,13 @@ pub fn run_command(program: &str, args: &[&str]) -> Result<std::process::Output,
             .stderr(Stdio::piped())
             .spawn()
             .map_err(|e| format!("执行命令失败: {}", e))?;
+        let drain = PipeDrain::start(&mut child);
         
         let start_time = Instant::now();
         let timeout = Duration::from_secs(COMMAND_TIMEOUT_SECS);
         
         // 轮询检查进程是否完成
-        loop {
+        let status = loop {
             // 先检查超时
             if start_time.elapsed() >= timeout {
                 // 超时，杀死进程
@@ -105,9 +153,9 @@ pub fn run_command(program: &str, args: &[&str]) -> Result<std::process::Output,
             }
             
             match child.try_wait() {
-                Ok(Some(_status)) => {
+                Ok(Some(status)) => {
                     // 进程已完成，退出循环
-                    break;
+                    break status;
                 }
                 Ok(None) => {
                     // 进程仍在运行，等待后继续
@@ -120,12 +168,128 @@ pub fn run_command(program: &str, args: &[&str]) -> Result<std::process::Output,
                     return Err(format!("检查命令进程状态失败: {}", e));
                 }
             }
-        }
+        };
         
         // 循环结束后，获取输出
-        let output = child.wait_with_output()
-            .map_err(|e| format!("获取命令输出失败: {}", e))?;
-        Ok(output)
+        Ok(drain.finish(status))
     }
 }
 
+
+/// 执行外部命令并逐行回调 stdout（用于 pnputil 等长时间运行命令的进度展示）
+/// 
+/// # 参数
+/// - `program`: 要执行的程序名称
+/// - `args`: 命令参数数组
+/// - `cancel`: 可选的取消标志，置位后终止子进程
+/// - `on_line`: 每读到一行非空 stdout（已解码、去除首尾空白）时调用
+/// 
+/// # 返回
+/// - `Ok(Some(Output))`: 进程已结束，stdout 为完整输出
+/// - `Ok(None)`: 已取消（子进程已终止）
+/// - `Err(String)`: 启动失败或超时
+/// 
+/// # 特性
+/// 与 run_command 一致：隐藏窗口、子进程登记、120 秒超时、输出写入 job 日志
+#[cfg(windows)]
+pub fn run_command_streaming(
+    program: &str,
+    args: &[&str],
+    cancel: Option<&std::sync::atomic::AtomicBool>,
+    mut on_line: impl FnMut(&str),
+) -> Result<Option<std::process::Output>, String> {
+    use std::io::{BufRead, BufReader};
+    use std::sync::atomic::Ordering;
+    use std::sync::mpsc;
+
+    let command_line = crate::utils::command_trace::format_command_line(program, args);
+    let start_time = Instant::now();
+    let mut child = super::process_watchdog::mark(&mut Command::new(program))
+        .args(args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map_err(|e| {
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("spawn_failed"));
+            format!("执行命令失败: {}", e)
+        })?;
+    let _tracked = super::process_watchdog::track(&child, program);
+
+    // stdout 按行转发；stderr 整体读取（两个管道都必须持续读取，避免子进程写满缓冲区阻塞）
+    let stdout_pipe = child.stdout.take().ok_or_else(|| "获取命令输出管道失败".to_string())?;
+    let mut stderr_pipe = child.stderr.take().ok_or_else(|| "获取命令错误输出管道失败".to_string())?;
+    let (line_tx, line_rx) = mpsc::channel::<Vec<u8>>();
+    let stdout_reader = std::thread::spawn(move || {
+        let mut reader = BufReader::new(stdout_pipe);
+        loop {
+            let mut line = Vec::new();
+            match reader.read_until(b'\n', &mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if line_tx.send(line).is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+    let stderr_reader = std::thread::spawn(move || {
+        let mut buf = Vec::new();
+        let _ = stderr_pipe.read_to_end(&mut buf);
+        buf
+    });
+
+    let mut stdout = Vec::new();
+    let mut forward = |line: Vec<u8>, stdout: &mut Vec<u8>| {
+        let text = crate::utils::encoding::decode_windows_string(&line);
+        if !text.trim().is_empty() {
+            on_line(text.trim());
+        }
+        stdout.extend_from_slice(&line);
+    };
+
+    let timeout = Duration::from_secs(COMMAND_TIMEOUT_SECS);
+    let status = loop {
+        while let Ok(line) = line_rx.try_recv() {
+            forward(line, &mut stdout);
+        }
+
+        if cancel.map(|flag| flag.load(Ordering::Relaxed)).unwrap_or(false) {
+            let _ = child.kill();
+            let _ = child.wait();
+            super::job_log::append_line(&format!("[Cmd] cancelled program={}", program));
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("cancelled"));
+            return Ok(None);
+        }
+        if start_time.elapsed() >= timeout {
+            let _ = child.kill();
+            let _ = child.wait();
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("timeout"));
+            return Err(format!("执行命令超时: {}s", COMMAND_TIMEOUT_SECS));
+        }
+
+        match child.try_wait() {
+            Ok(Some(status)) => break status,
+            Ok(None) => std::thread::sleep(Duration::from_millis(POLL_INTERVAL_MS)),
+            Err(e) => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err(format!("检查命令进程状态失败: {}", e));
+            }
+        }
+    };
+
+    // 进程结束后读完剩余输出
+    let _ = stdout_reader.join();
+    for line in line_rx.try_iter() {
+        forward(line, &mut stdout);
+    }
+    let stderr = stderr_reader.join().unwrap_or_default();
+
+    let output = std::process::Output { status, stdout, stderr };
+    super::job_log::append_command_trace(&command_line, output.status.code(), start_time, None);
+    super::job_log::append_process_output(&command_line, &output);
+    Ok(Some(output))
+}
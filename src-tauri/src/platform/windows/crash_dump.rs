@@ -0,0 +1,99 @@
+// Windows 平台崩溃转储（minidump）模块
+// 进程因未处理的结构化异常（访问冲突等）崩溃，或 Rust 侧 panic 时，把 minidump 写入崩溃报告目录：
+//
+// - install_exception_filter(): 注册 SetUnhandledExceptionFilter，崩溃时写入 <id>.dmp 与异常摘要后交由系统结束进程
+// - write_current_process_dump(): panic hook 中调用，为当前进程写入 minidump（不含异常上下文）
+// - 摘要由 services::crash_report 统一格式化，下次启动时提示用户是否上传
+
+use std::os::windows::io::AsRawHandle;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use windows::Win32::Foundation::{BOOL, HANDLE};
+use windows::Win32::System::Diagnostics::Debug::{
+    MiniDumpWithDataSegs, MiniDumpWithThreadInfo, MiniDumpWriteDump, SetUnhandledExceptionFilter,
+    EXCEPTION_POINTERS, MINIDUMP_EXCEPTION_INFORMATION,
+};
+use windows::Win32::System::Threading::{GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId};
+
+/// 交给系统默认处理（结束进程 / WER）
+const EXCEPTION_CONTINUE_SEARCH: i32 = 0;
+
+/// 崩溃时的回调：写完 minidump 后由调用方记录摘要（参数：报告 id、dump 路径、异常码）
+type CrashCallback = fn(&str, Option<&Path>, u32);
+
+/// 异常过滤器的状态（install_exception_filter 时设置一次）
+struct FilterState {
+    dir: PathBuf,
+    new_report_id: fn() -> String,
+    on_crash: CrashCallback,
+}
+
+static FILTER_STATE: OnceLock<FilterState> = OnceLock::new();
+
+/// 写入 minidump
+fn write_dump(path: &Path, exception: Option<&MINIDUMP_EXCEPTION_INFORMATION>) -> Result<(), String> {
+    let file = std::fs::File::create(path).map_err(|e| format!("创建 minidump 文件失败 {}: {}", path.display(), e))?;
+    let result = unsafe {
+        MiniDumpWriteDump(
+            GetCurrentProcess(),
+            GetCurrentProcessId(),
+            HANDLE(file.as_raw_handle() as isize),
+            MiniDumpWithDataSegs | MiniDumpWithThreadInfo,
+            exception.map(|e| e as *const _),
+            None,
+            None,
+        )
+    };
+    if let Err(e) = result {
+        drop(file);
+        let _ = std::fs::remove_file(path);
+        return Err(format!("MiniDumpWriteDump 失败: {}", e));
+    }
+    Ok(())
+}
+
+/// 为当前进程写入 minidump（panic hook 使用，无异常上下文）
+///
+/// # 参数
+/// - `path`: dump 文件路径
+pub fn write_current_process_dump(path: &Path) -> Result<(), String> {
+    write_dump(path, None)
+}
+
+unsafe extern "system" fn unhandled_exception_filter(pointers: *const EXCEPTION_POINTERS) -> i32 {
+    let Some(state) = FILTER_STATE.get() else {
+        return EXCEPTION_CONTINUE_SEARCH;
+    };
+    let code = pointers
+        .as_ref()
+        .and_then(|p| p.ExceptionRecord.as_ref())
+        .map(|r| r.ExceptionCode.0 as u32)
+        .unwrap_or(0);
+
+    let id = (state.new_report_id)();
+    let dump_path = state.dir.join(format!("{}.dmp", id));
+    let exception = MINIDUMP_EXCEPTION_INFORMATION {
+        ThreadId: GetCurrentThreadId(),
+        ExceptionPointers: pointers as *mut _,
+        ClientPointers: BOOL(0),
+    };
+    let written = write_dump(&dump_path, Some(&exception)).is_ok();
+    (state.on_crash)(&id, written.then_some(dump_path.as_path()), code);
+    EXCEPTION_CONTINUE_SEARCH
+}
+
+/// 注册未处理异常过滤器（进程启动时调用一次）
+///
+/// # 参数
+/// - `dir`: 崩溃报告目录
+/// - `new_report_id`: 生成报告 id（dump 文件名为 <id>.dmp）
+/// - `on_crash`: 写完 minidump 后调用，用于记录崩溃摘要
+pub fn install_exception_filter(dir: PathBuf, new_report_id: fn() -> String, on_crash: CrashCallback) {
+    let state = FilterState { dir, new_report_id, on_crash };
+    if FILTER_STATE.set(state).is_ok() {
+        unsafe {
+            SetUnhandledExceptionFilter(Some(unhandled_exception_filter));
+        }
+    }
+}
@@ -9,6 +9,7 @@ use winapi::um::winnt::LPWSTR;
 use winapi::um::errhandlingapi::GetLastError;
 use winapi::ctypes::c_void;
 use std::ptr;
+use crate::platform::ps_script::PsScript;
 
 /// 删除打印机结果（内部使用）
 #[derive(Debug)]
@@ -142,20 +143,21 @@ fn delete_printer_port(port_name: &str) -> Result<bool, String> {
     // 注意：这是"尽力而为"的实现，失败不影响队列删除的成功
     use super::cmd;
     
-    let ps_command = format!(
-        "Remove-PrinterPort -Name '{}' -ErrorAction SilentlyContinue; if ($?) {{ Write-Output 'SUCCESS' }} else {{ Write-Output 'FAILED' }}",
-        port_name.replace("'", "''") // 转义单引号
-    );
+    let ps_command = PsScript::new(
+        "Remove-PrinterPort -Name $Name -ErrorAction SilentlyContinue; if ($?) { Write-Output 'SUCCESS' } else { Write-Output 'FAILED' }",
+    )
+    .param("Name", port_name)
+    .build();
     
-    match cmd::run_command("powershell.exe", &[
+    match cmd::run_command(&super::ps::powershell_program().program, &[
         "-NoProfile",
         "-NonInteractive",
         "-Command",
         &ps_command
     ]) {
         Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            let stdout = crate::utils::encoding::decode_windows_string(&output.stdout).trim().to_string();
+            let stderr = crate::utils::encoding::decode_windows_string(&output.stderr).trim().to_string();
             
             if output.status.success() && stdout.contains("SUCCESS") {
                 log::write_log(&format!("[DeletePrinter] DELETE_PORT_OK port_name=\"{}\"", port_name));
@@ -246,20 +248,22 @@ fn delete_printer_driver(driver_name: &str) -> Result<bool, String> {
     // 注意：这是"尽力而为"的实现，失败不影响队列删除的成功
     use super::cmd;
     
-    let ps_command = format!(
-        "Remove-PrinterDriver -Name '{}' -ErrorAction SilentlyContinue; if ($?) {{ Write-Output 'SUCCESS' }} else {{ Write-Output 'FAILED' }}",
-        driver_name.replace("'", "''") // 转义单引号
-    );
+    let ps_command = PsScript::new(
+        "Remove-PrinterDriver -Name $Name -ErrorAction SilentlyContinue; if ($?) { Write-Output 'SUCCESS' } else { Write-Output 'FAILED' }",
+    )
+    .param("Name", driver_name)
+    .build();
     
-    match cmd::run_command("powershell.exe", &[
+    match cmd::run_command(&super::ps::powershell_program().program, &[
         "-NoProfile",
         "-NonInteractive",
         "-Command",
         &ps_command
     ]) {
         Ok(output) => {
-            let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            super::session_cache::invalidate_driver_inventory();
+            let stdout = crate::utils::encoding::decode_windows_string(&output.stdout).trim().to_string();
+            let stderr = crate::utils::encoding::decode_windows_string(&output.stderr).trim().to_string();
             
             if output.status.success() && stdout.contains("SUCCESS") {
                 log::write_log(&format!("[DeletePrinter] DELETE_DRIVER_OK driver_name=\"{}\"", driver_name));
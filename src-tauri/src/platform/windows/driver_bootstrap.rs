@@ -228,10 +228,12 @@ pub enum BootstrapError {
     },
     /// 下载失败
     FetchFailed {
+        code: &'static str,
         error: String,
     },
     /// 解压失败
     ExtractFailed {
+        code: &'static str,
         error: String,
     },
     /// Materialize 失败
@@ -246,16 +248,27 @@ pub enum BootstrapError {
     },
 }
 
+impl BootstrapError {
+    /// 错误码（下载与解压阶段失败时携带细分错误码，如 SHARE_ACCESS_DENIED / BAD_ARCHIVE_PASSWORD）
+    pub fn code(&self) -> Option<&'static str> {
+        match self {
+            BootstrapError::FetchFailed { code, .. } => Some(code),
+            BootstrapError::ExtractFailed { code, .. } => Some(code),
+            _ => None,
+        }
+    }
+}
+
 impl std::fmt::Display for BootstrapError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             BootstrapError::RemoteDriverMissing { reason } => {
                 write!(f, "远程驱动信息缺失: {}", reason)
             }
-            BootstrapError::FetchFailed { error } => {
+            BootstrapError::FetchFailed { error, .. } => {
                 write!(f, "下载失败: {}", error)
             }
-            BootstrapError::ExtractFailed { error } => {
+            BootstrapError::ExtractFailed { error, .. } => {
                 write!(f, "解压失败: {}", error)
             }
             BootstrapError::MaterializeFailed { error } => {
@@ -302,10 +315,11 @@ pub async fn bootstrap_driver_from_remote(
         drivers_root,
         &remote_driver.url,
         &remote_driver.sha256,
-        app,
-        printer_name,
-        job_id,
+        remote_driver.size,
+        remote_driver.patch.as_ref(),
+        crate::platform::windows::driver_fetch::FetchProgress { app, printer_name, job_id },
     ).await.map_err(|e| BootstrapError::FetchFailed {
+        code: e.code(),
         error: format!("{}", e),
     })?;
     
@@ -318,14 +332,38 @@ pub async fn bootstrap_driver_from_remote(
     eprintln!("[DriverBootstrap] step=extract_payload inputs=payload_zip=\"{}\" driver_uuid=\"{}\"", 
         fetch_result.payload_zip.display(), fetch_result.driver_uuid);
     
+    // 加密驱动包：按 archivePasswordRef 从凭据管理器读取密码（密码不写入配置与日志）
+    let archive_password = match remote_driver.archive_password_ref.as_deref() {
+        Some(reference) => {
+            let password = crate::platform::windows::archive_password::read_password(reference)
+                .map_err(|e| BootstrapError::ExtractFailed {
+                    code: "ARCHIVE_PASSWORD_REQUIRED",
+                    error: e,
+                })?
+                .ok_or_else(|| BootstrapError::ExtractFailed {
+                    code: "ARCHIVE_PASSWORD_REQUIRED",
+                    error: format!(
+                        "驱动包需要解压密码，但本机未保存 archivePasswordRef=\"{}\" 对应的密码（请先通过 set_driver_archive_password 保存）",
+                        reference
+                    ),
+                })?;
+            eprintln!("[DriverBootstrap] step=extract_payload archive_password_ref=\"{}\" password=found", reference);
+            Some(password)
+        }
+        None => None,
+    };
+    
+    let _extraction_slot = super::install_jobs::acquire_extraction_slot(job_id).await;
     let extract_result = crate::platform::windows::archive::extract_zip_for_driver(
         &fetch_result.payload_zip,
         drivers_root,
         &fetch_result.driver_uuid,
+        archive_password.as_deref(),
         app,
         printer_name,
         job_id,
     ).map_err(|e| BootstrapError::ExtractFailed {
+        code: e.code(),
         error: format!("{}", e),
     })?;
     
@@ -0,0 +1,216 @@
+// Windows 平台驱动冲突检测模块
+// 安装前比较目标 INF 的驱动名称 / 版本与系统中已注册的打印机驱动：
+// 同名驱动版本不同时，安装会原地升级（或降级）该驱动，所有使用它的队列都会受影响
+//
+// - 目标版本：INF [Version] 段的 DriverVer（日期,版本号）
+// - 已安装版本：Get-PrinterDriver 的 DriverVersion（UInt64，按 16 位拆分为 a.b.c.d）
+// - 受影响队列：使用同名驱动、且不是本次安装目标的队列；其中带 ePrinty tag 的视为受管队列
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::{DriverConflictCheckResult, DriverConflictInfo};
+use crate::utils::inf_parse::{parse_driver_ver, read_inf_text};
+use std::path::Path;
+
+/// 已注册的打印机驱动（含版本与使用它的队列）
+#[derive(Debug)]
+struct InstalledDriver {
+    name: String,
+    version: Option<String>,
+    queues: Vec<String>,
+}
+
+/// 规范化版本号用于比较（补齐为 4 段数字，"3.2" == "3.2.0.0"）
+fn normalize_version(version: &str) -> Vec<u64> {
+    let mut parts: Vec<u64> = version
+        .split('.')
+        .map(|p| p.trim().parse::<u64>().unwrap_or(0))
+        .collect();
+    parts.resize(4, 0);
+    parts
+}
+
+/// 查询已注册的打印机驱动版本及使用它们的队列
+fn query_installed_drivers() -> Result<Vec<InstalledDriver>, String> {
+    let script = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+        $drivers = @(Get-PrinterDriver -ErrorAction SilentlyContinue | ForEach-Object { \
+            $v = [UInt64]$_.DriverVersion; \
+            $ver = if ($v -gt 0) { '{0}.{1}.{2}.{3}' -f (($v -shr 48) -band 0xFFFF), (($v -shr 32) -band 0xFFFF), (($v -shr 16) -band 0xFFFF), ($v -band 0xFFFF) } else { $null }; \
+            [PSCustomObject]@{ Name = $_.Name; Version = $ver } }); \
+        $queues = @(Get-Printer -ErrorAction SilentlyContinue | ForEach-Object { [PSCustomObject]@{ Name = $_.Name; DriverName = $_.DriverName } }); \
+        [PSCustomObject]@{ drivers = $drivers; queues = $queues } | ConvertTo-Json -Compress -Depth 4";
+
+    let output = super::ps::run_powershell(script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("查询已安装打印机驱动失败: {}", log::truncate(stderr.trim(), 300)));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析已安装打印机驱动失败: {}", e))?;
+
+    // ConvertTo-Json 对单元素数组可能输出对象，这里统一为数组
+    let as_array = |v: Option<&serde_json::Value>| -> Vec<serde_json::Value> {
+        match v {
+            Some(serde_json::Value::Array(items)) => items.clone(),
+            Some(serde_json::Value::Null) | None => Vec::new(),
+            Some(other) => vec![other.clone()],
+        }
+    };
+
+    let queues = as_array(json.get("queues"));
+    let drivers = as_array(json.get("drivers"))
+        .iter()
+        .filter_map(|d| {
+            let name = d.get("Name")?.as_str()?.to_string();
+            let version = d.get("Version").and_then(|v| v.as_str()).map(|v| v.to_string());
+            let queues = queues
+                .iter()
+                .filter(|q| q.get("DriverName").and_then(|n| n.as_str()) == Some(name.as_str()))
+                .filter_map(|q| q.get("Name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+                .collect();
+            Some(InstalledDriver { name, version, queues })
+        })
+        .collect();
+
+    Ok(drivers)
+}
+
+/// 检测安装目标 INF 是否会影响其他队列
+///
+/// # 参数
+/// - `inf_path`: 目标 INF 绝对路径
+/// - `driver_names`: 本次安装使用的驱动名称候选（来自 driver_names 配置）
+/// - `target_queue`: 本次安装的队列名称（不计入受影响队列）
+///
+/// # 返回
+/// - `Ok(DriverConflictCheckResult)`: 检测结果（status = "OK" | "CONFLICT" | "UNKNOWN"）
+/// - `Err(String)`: INF 无法读取或驱动查询失败
+pub fn detect_driver_conflicts(
+    inf_path: &Path,
+    driver_names: &[String],
+    target_queue: &str,
+) -> Result<DriverConflictCheckResult, String> {
+    let target_version = parse_driver_ver(&read_inf_text(inf_path)?);
+    let installed = query_installed_drivers()?;
+    let managed_queues = super::remove::list_tagged_printer_names().unwrap_or_else(|e| {
+        log::write_log(&format!("[DriverConflict] 读取受管队列失败: {}", e));
+        Vec::new()
+    });
+
+    let mut conflicts = Vec::new();
+    for driver_name in driver_names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        let existing = match installed.iter().find(|d| d.name.eq_ignore_ascii_case(driver_name)) {
+            Some(d) => d,
+            None => continue,
+        };
+
+        let version_differs = match (&existing.version, &target_version) {
+            (Some(installed_ver), Some(target_ver)) => normalize_version(installed_ver) != normalize_version(target_ver),
+            // 任一版本未知时无法判断，不视为冲突
+            _ => false,
+        };
+        if !version_differs {
+            continue;
+        }
+
+        let affected_queues: Vec<String> = existing
+            .queues
+            .iter()
+            .filter(|q| !q.eq_ignore_ascii_case(target_queue))
+            .cloned()
+            .collect();
+        let affected_managed_queues: Vec<String> = affected_queues
+            .iter()
+            .filter(|q| managed_queues.iter().any(|m| m.eq_ignore_ascii_case(q)))
+            .cloned()
+            .collect();
+
+        conflicts.push(DriverConflictInfo {
+            driver_name: existing.name.clone(),
+            installed_version: existing.version.clone(),
+            target_version: target_version.clone(),
+            affected_queues,
+            affected_managed_queues,
+        });
+    }
+
+    let has_conflict = conflicts.iter().any(|c| !c.affected_managed_queues.is_empty());
+    let status = if has_conflict {
+        "CONFLICT"
+    } else if target_version.is_none() {
+        "UNKNOWN"
+    } else {
+        "OK"
+    };
+
+    let message = if has_conflict {
+        let details: Vec<String> = conflicts
+            .iter()
+            .filter(|c| !c.affected_managed_queues.is_empty())
+            .map(|c| {
+                format!(
+                    "{}（已安装 {} → 目标 {}，影响：{}）",
+                    c.driver_name,
+                    c.installed_version.as_deref().unwrap_or("未知"),
+                    c.target_version.as_deref().unwrap_or("未知"),
+                    c.affected_managed_queues.join("、")
+                )
+            })
+            .collect();
+        format!(
+            "安装将原地更新已存在的驱动，其他已安装的打印机可能受影响：{}",
+            details.join("；")
+        )
+    } else if target_version.is_none() {
+        "目标 INF 未声明 DriverVer，无法判断是否与已安装驱动冲突".to_string()
+    } else {
+        "未发现驱动版本冲突".to_string()
+    };
+
+    log::write_log(&format!(
+        "[DriverConflict] inf=\"{}\" target_version={:?} status={} conflicts={}",
+        log::sanitize_path(inf_path),
+        target_version,
+        status,
+        conflicts.len()
+    ));
+
+    Ok(DriverConflictCheckResult {
+        status: status.to_string(),
+        has_conflict,
+        printer_name: target_queue.to_string(),
+        inf_path: Some(inf_path.display().to_string()),
+        target_version,
+        conflicts,
+        message,
+    })
+}
+
+/// 按配置中的 driver_path 检测驱动冲突（维护页 / 安装前确认使用）
+///
+/// # 参数
+/// - `printer_name`: 目标队列名称
+/// - `driver_path`: 配置中的 effective_driver_path（相对 drivers 目录）
+/// - `driver_names`: 驱动名称候选
+pub fn check_driver_conflicts_windows(
+    printer_name: &str,
+    driver_path: &str,
+    driver_names: &[String],
+) -> Result<DriverConflictCheckResult, String> {
+    let inf_path = super::install::resolve_local_inf_path(driver_path)?;
+    if !inf_path.exists() {
+        // 驱动包尚未下载：安装时才会获取，暂无法比较
+        return Ok(DriverConflictCheckResult {
+            status: "UNKNOWN".to_string(),
+            has_conflict: false,
+            printer_name: printer_name.to_string(),
+            inf_path: Some(inf_path.display().to_string()),
+            target_version: None,
+            conflicts: Vec::new(),
+            message: "驱动包尚未下载到本机，安装时将重新检测".to_string(),
+        });
+    }
+    detect_driver_conflicts(&inf_path, driver_names, printer_name)
+}
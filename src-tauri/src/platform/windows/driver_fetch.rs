@@ -1,21 +1,104 @@
 // Windows 平台驱动包下载模块
 // 
 // 提供远程驱动包下载、sha256 校验和缓存功能
+// 下载限速与按流量计费网络策略见配置 downloadPolicy：
+// - 按流量计费网络上超过阈值的驱动包默认推迟（DOWNLOAD_DEFERRED_METERED），用户确认后调用 set_metered_download_allowed(true) 再重试
+// 下载前先发 HEAD 预检（服务器不支持 HEAD 时跳过，直接下载）：
+// - Content-Type 为 text/html：多为酒店 / 访客网络的认证页（captive portal），立即失败（CAPTIVE_PORTAL_SUSPECTED）
+// - Content-Length 与 driverCatalog remote.size 不一致：立即失败（DOWNLOAD_SIZE_MISMATCH），不下载几百 MB 的错误文件
+// - 服务器提供 SHA256 响应头（X-Checksum-Sha256 / x-amz-meta-sha256）且与配置不一致：立即失败（SHA256_MISMATCH）
+// 增量补丁（driverCatalog remote.patch）：缓存未命中且本机有 baseSha256 对应的旧驱动包时，只下载补丁并重建新驱动包；
+// 旧驱动包不存在、补丁下载 / 应用失败或重建结果 sha256 不符时回退到完整下载
 
 use std::path::{Path, PathBuf};
 use std::fs;
 use std::io::Read;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use url::Url;
 use tauri::Manager;
 
+/// 按流量计费网络上推迟下载的默认阈值（MB）
+const DEFAULT_METERED_DEFER_ABOVE_MB: u64 = 20;
+
+/// 用户是否已确认在按流量计费网络上下载（会话内有效）
+static METERED_DOWNLOAD_ALLOWED: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否允许在按流量计费网络上下载大驱动包（用户确认后调用，会话内有效）
+pub fn set_metered_download_allowed(allowed: bool) {
+    METERED_DOWNLOAD_ALLOWED.store(allowed, Ordering::SeqCst);
+    eprintln!("[DriverFetch] metered_download_allowed={}", allowed);
+}
+
+/// 本次下载生效的限速与推迟策略
+#[derive(Debug, Clone)]
+struct DownloadLimits {
+    /// 限速（字节/秒），None 表示不限速
+    bytes_per_sec: Option<u64>,
+    /// 超过该大小时推迟下载（仅按流量计费网络且用户未确认时），None 表示不推迟
+    defer_above_bytes: Option<u64>,
+    /// 当前连接的计费类型（日志 / 错误信息使用）
+    cost_type: Option<&'static str>,
+}
+
+/// 根据配置 downloadPolicy 与当前网络计费状态计算下载策略
+fn resolve_download_limits() -> DownloadLimits {
+    let policy = crate::load_local_config()
+        .ok()
+        .and_then(|(config, _)| config.download_policy)
+        .unwrap_or_default();
+    let cost = super::network_cost::current_connection_cost();
+    let metered = cost.as_ref().map(|c| c.metered).unwrap_or(false);
+
+    let kbps = if metered {
+        policy.metered_max_kbps.or(policy.max_kbps)
+    } else {
+        policy.max_kbps
+    };
+    let defer_above_mb = policy.metered_defer_above_mb.unwrap_or(DEFAULT_METERED_DEFER_ABOVE_MB);
+    let defer_above_bytes = if metered && defer_above_mb > 0 && !METERED_DOWNLOAD_ALLOWED.load(Ordering::SeqCst) {
+        Some(defer_above_mb * 1024 * 1024)
+    } else {
+        None
+    };
+
+    DownloadLimits {
+        bytes_per_sec: kbps.filter(|k| *k > 0).map(|k| k * 1024),
+        defer_above_bytes,
+        cost_type: cost.map(|c| c.cost_type),
+    }
+}
+
+/// 进度事件上下文（app 与 printer_name 均提供时才发送步骤事件）
+#[derive(Clone, Copy)]
+pub struct FetchProgress<'a> {
+    pub app: Option<&'a tauri::AppHandle>,
+    pub printer_name: Option<&'a str>,
+    /// 安装任务 ID
+    pub job_id: &'a str,
+}
+
+impl FetchProgress<'_> {
+    /// 开始一个步骤事件（未提供 app / printer_name 时返回 None）
+    fn start_step(&self, step: &str, message: &str) -> Option<crate::platform::windows::step_reporter::StepReporter> {
+        let (app_handle, printer) = (self.app?, self.printer_name?);
+        Some(crate::platform::windows::step_reporter::StepReporter::start(
+            std::sync::Arc::new(app_handle.clone()),
+            self.job_id.to_string(),
+            printer.to_string(),
+            step.to_string(),
+            message.to_string(),
+        ))
+    }
+}
+
 /// 下载结果
 #[derive(Debug, Clone)]
 pub struct FetchResult {
     pub driver_uuid: String,
     pub uuid_root: PathBuf,
     pub payload_zip: PathBuf,
-    pub source_used: String, // "cache" | "download"
+    pub source_used: String, // "cache" | "download" | "share" | "patch"
     pub bytes: u64,
     pub sha256_actual: String,
 }
@@ -65,6 +148,37 @@ pub enum FetchError {
         status: u16,
         url: String,
     },
+    /// 按流量计费网络上推迟下载（需用户确认）
+    DeferredMeteredConnection {
+        url: String,
+        content_length: u64,
+        threshold_bytes: u64,
+        cost_type: String,
+    },
+    /// 服务器返回 HTML 页面而不是 ZIP（疑似网络认证页拦截）
+    CaptivePortalSuspected {
+        url: String,
+        final_url: String,
+        content_type: String,
+    },
+    /// 服务器声明的大小与配置不一致
+    DownloadSizeMismatch {
+        url: String,
+        expected: u64,
+        actual: u64,
+    },
+    /// 共享路径访问被拒绝（凭据缺失/错误、无读取权限）
+    ShareAccessDenied {
+        path: String,
+        os_error: Option<i32>,
+        error: String,
+    },
+    /// 共享路径不可用（文件不存在、网络路径无法访问）
+    ShareUnavailable {
+        path: String,
+        os_error: Option<i32>,
+        error: String,
+    },
 }
 
 impl std::fmt::Display for FetchError {
@@ -98,6 +212,46 @@ impl std::fmt::Display for FetchError {
             FetchError::DownloadFailedStatus { status, url } => {
                 write!(f, "下载失败（HTTP 状态码错误）\n状态码: {}\nURL: {}", status, url)
             }
+            FetchError::DeferredMeteredConnection { url, content_length, threshold_bytes, cost_type } => {
+                write!(f, "[{}] 当前网络按流量计费（{}），驱动包 {:.1} MB 超过 {} MB，已推迟下载；确认后可继续下载\nURL: {}",
+                    self.code(), cost_type, *content_length as f64 / 1024.0 / 1024.0, threshold_bytes / 1024 / 1024, url)
+            }
+            FetchError::CaptivePortalSuspected { url, final_url, content_type } => {
+                write!(f, "[{}] 服务器返回了网页（Content-Type: {}）而不是驱动包，当前网络可能需要先在浏览器中登录认证（酒店 / 访客 Wi-Fi 等）\nURL: {}\n最终地址: {}",
+                    self.code(), content_type, url, final_url)
+            }
+            FetchError::DownloadSizeMismatch { url, expected, actual } => {
+                write!(f, "[{}] 驱动包大小与配置不一致（配置 {} 字节，服务器 {} 字节），可能是文件已被替换或下载地址错误\nURL: {}",
+                    self.code(), expected, actual, url)
+            }
+            FetchError::ShareAccessDenied { path, os_error, error } => {
+                write!(f, "[{}] 无法访问共享驱动包（访问被拒绝，请确认已登录该共享或当前账户具有读取权限）\n路径: {}\n系统错误码: {:?}\n{}",
+                    self.code(), path, os_error, error)
+            }
+            FetchError::ShareUnavailable { path, os_error, error } => {
+                write!(f, "[{}] 共享驱动包不可用（文件不存在或网络路径无法访问）\n路径: {}\n系统错误码: {:?}\n{}",
+                    self.code(), path, os_error, error)
+            }
+        }
+    }
+}
+
+impl FetchError {
+    /// 错误码（用于前端/日志区分失败类型）
+    pub fn code(&self) -> &'static str {
+        match self {
+            FetchError::DownloadFailed { .. } => "DOWNLOAD_FAILED",
+            FetchError::Sha256Mismatch { .. } => "SHA256_MISMATCH",
+            FetchError::InvalidSha256 { .. } => "INVALID_SHA256",
+            FetchError::IoError { .. } => "IO_ERROR",
+            FetchError::InvalidRemoteUrl { .. } => "INVALID_REMOTE_URL",
+            FetchError::DownloadFailedEmptyBody { .. } => "DOWNLOAD_EMPTY_BODY",
+            FetchError::DownloadFailedStatus { .. } => "DOWNLOAD_HTTP_STATUS",
+            FetchError::DeferredMeteredConnection { .. } => "DOWNLOAD_DEFERRED_METERED",
+            FetchError::CaptivePortalSuspected { .. } => "CAPTIVE_PORTAL_SUSPECTED",
+            FetchError::DownloadSizeMismatch { .. } => "DOWNLOAD_SIZE_MISMATCH",
+            FetchError::ShareAccessDenied { .. } => "SHARE_ACCESS_DENIED",
+            FetchError::ShareUnavailable { .. } => "SHARE_UNAVAILABLE",
         }
     }
 }
@@ -146,6 +300,69 @@ fn validate_remote_url(url: &str) -> Result<Url, FetchError> {
     Ok(parsed_url)
 }
 
+/// 驱动包来源
+#[derive(Debug, Clone)]
+enum PayloadSource {
+    /// HTTP/HTTPS 远程地址（已规范化）
+    Http(String),
+    /// 本地或 UNC 共享路径（来自 file:// URL 或 \\server\share\... 形式）
+    LocalFile(PathBuf),
+}
+
+/// 解析驱动包来源
+///
+/// # 支持的格式
+/// - `http://` / `https://`：走 validate_remote_url 校验后 HTTP 下载
+/// - `file://server/share/x.zip`、`file:///C:/x.zip`：转换为本地/UNC 路径
+/// - `\\server\share\x.zip`：UNC 路径，直接使用
+fn resolve_payload_source(url: &str) -> Result<PayloadSource, FetchError> {
+    let trimmed = url.trim();
+
+    if trimmed.starts_with("\\\\") {
+        return Ok(PayloadSource::LocalFile(PathBuf::from(trimmed)));
+    }
+
+    if trimmed.len() >= 7 && trimmed[..7].eq_ignore_ascii_case("file://") {
+        let parsed_url = Url::parse(trimmed).map_err(|e| FetchError::InvalidRemoteUrl {
+            url: url.to_string(),
+            reason: format!("file URL 解析失败: {}", e),
+        })?;
+        let path = parsed_url.to_file_path().map_err(|_| FetchError::InvalidRemoteUrl {
+            url: url.to_string(),
+            reason: "file URL 无法转换为本地或 UNC 路径".to_string(),
+        })?;
+        return Ok(PayloadSource::LocalFile(path));
+    }
+
+    Ok(PayloadSource::Http(validate_remote_url(url)?.to_string()))
+}
+
+/// 将共享路径访问错误映射为 FetchError
+///
+/// - 5 (ERROR_ACCESS_DENIED)、86 (ERROR_INVALID_PASSWORD)、1244 (ERROR_NOT_AUTHENTICATED)、
+///   1326 (ERROR_LOGON_FAILURE)、1327 (ERROR_ACCOUNT_RESTRICTION)、1330 (ERROR_PASSWORD_EXPIRED)
+///   → ShareAccessDenied
+/// - 2/3 (文件/路径不存在)、53 (ERROR_BAD_NETPATH)、67 (ERROR_BAD_NET_NAME) 等 → ShareUnavailable
+fn map_share_error(path: &Path, e: &std::io::Error) -> FetchError {
+    let os_error = e.raw_os_error();
+    let access_denied = matches!(os_error, Some(5) | Some(86) | Some(1244) | Some(1326) | Some(1327) | Some(1330))
+        || e.kind() == std::io::ErrorKind::PermissionDenied;
+
+    if access_denied {
+        FetchError::ShareAccessDenied {
+            path: path.display().to_string(),
+            os_error,
+            error: e.to_string(),
+        }
+    } else {
+        FetchError::ShareUnavailable {
+            path: path.display().to_string(),
+            os_error,
+            error: e.to_string(),
+        }
+    }
+}
+
 /// 校验 SHA256 格式
 fn validate_sha256(sha256: &str) -> Result<(), FetchError> {
     if sha256.is_empty() {
@@ -205,12 +422,19 @@ pub fn sha256_file(path: &Path) -> Result<String, FetchError> {
     Ok(format!("{:x}", hash))
 }
 
+/// 由驱动包 SHA256 计算 driver_uuid（"drv_" + sha256 前 12 位小写，即 drivers_root 下的目录名）
+pub fn driver_uuid_for_sha256(sha256: &str) -> String {
+    format!("drv_{}", sha256.chars().take(12).collect::<String>().to_lowercase())
+}
+
 /// 确保驱动包 ZIP 文件存在（下载或从缓存获取）
 /// 
 /// # 参数
 /// - `drivers_root`: 驱动根目录
-/// - `remote_url`: 远程 ZIP 文件 URL
+/// - `remote_url`: 远程 ZIP 文件 URL（http/https），或共享路径（file:// URL / UNC 路径）
 /// - `expected_sha256`: 期望的 SHA256 哈希值（64 字符十六进制）
+/// - `expected_size`: 期望的字节数（HTTP 下载前与 HEAD 的 Content-Length 比较）
+/// - `patch`: 增量补丁（可选；应用失败时回退到完整下载）
 /// 
 /// # 返回
 /// - `Ok(FetchResult)`: 下载/缓存成功
@@ -218,17 +442,17 @@ pub fn sha256_file(path: &Path) -> Result<String, FetchError> {
 /// 
 /// # 步骤
 /// 1. compute_paths: 计算路径（基于 sha256 前缀生成 driver_uuid）
-/// 2. cache_check: 检查缓存
-/// 3. download: 下载（如果需要）
+/// 2. cache_check: 检查缓存（未命中且配置了补丁时，先基于本机旧驱动包应用增量补丁）
+/// 3. download / share_copy: 下载或从共享路径复制（如果需要）
 /// 4. sha256_verify: 校验 SHA256
 /// 5. summary: 输出摘要
 pub async fn ensure_payload_zip(
     drivers_root: &Path,
     remote_url: &str,
     expected_sha256: &str,
-    app: Option<&tauri::AppHandle>,  // 用于发送进度事件（可选）
-    printer_name: Option<&str>,  // 打印机名称（用于进度事件）
-    job_id: &str,  // 安装任务 ID
+    expected_size: Option<u64>,  // 配置声明的大小（driverCatalog remote.size，可选，用于 HEAD 预检）
+    patch: Option<&crate::DriverPatchResolved>,  // 增量补丁（driverCatalog remote.patch，可选）
+    progress: FetchProgress<'_>,  // 进度事件上下文
 ) -> Result<FetchResult, FetchError> {
     eprintln!("[EnsurePayloadZip] start remote_url=\"{}\" expected_sha256=\"{}\" drivers_root=\"{}\"", 
         remote_url, expected_sha256, drivers_root.display());
@@ -238,7 +462,11 @@ pub async fn ensure_payload_zip(
     // ============================================================================
     eprintln!("[EnsurePayloadZip] step=validate_remote_url inputs=remote_url=\"{}\"", remote_url);
     
-    let canonical_url = validate_remote_url(remote_url)?.to_string();
+    let source = resolve_payload_source(remote_url)?;
+    let canonical_url = match &source {
+        PayloadSource::Http(url) => url.clone(),
+        PayloadSource::LocalFile(path) => path.display().to_string(),
+    };
     
     eprintln!("[EnsurePayloadZip] step=validate_remote_url result=passed canonical_url=\"{}\"", canonical_url);
     
@@ -255,11 +483,15 @@ pub async fn ensure_payload_zip(
     // Step 1: compute_paths - 计算路径
     // ============================================================================
     // driver_uuid = "drv_" + sha256[0..12]
-    let driver_uuid = format!("drv_{}", &expected_sha256[..12].to_lowercase());
+    let driver_uuid = driver_uuid_for_sha256(expected_sha256);
     let uuid_root = drivers_root.join(&driver_uuid);
     let payload_dir = uuid_root.join("payload");
     let payload_zip = payload_dir.join("payload.zip");
-    let payload_tmp = payload_dir.join("payload.zip.part");
+    // 下载中的临时文件放在当前任务的工作目录（不在任务中时放在 payload 目录）
+    let payload_tmp = match super::job_workdir::current_dir() {
+        Some(dir) => dir.join(format!("{}.payload.zip.part", driver_uuid)),
+        None => payload_dir.join("payload.zip.part"),
+    };
     
     eprintln!("[EnsurePayloadZip] step=compute_paths inputs=expected_sha256=\"{}\" drivers_root=\"{}\"", 
         expected_sha256, drivers_root.display());
@@ -334,37 +566,447 @@ pub async fn ensure_payload_zip(
     };
     
     // ============================================================================
-    // Step 3: download - 下载 ZIP 文件（应用内下载，禁止系统下载）
+    // Step 2.5: patch - 基于本机旧驱动包应用增量补丁（失败时回退到完整下载）
+    // ============================================================================
+    let patched = match patch {
+        Some(patch) => {
+            eprintln!("[EnsurePayloadZip] step=patch inputs=base_sha256=\"{}\" patch_url=\"{}\"", patch.base_sha256, patch.url);
+            match apply_remote_patch(drivers_root, patch, &payload_zip, expected_sha256, progress).await {
+                Ok(patch_bytes) => {
+                    eprintln!("[EnsurePayloadZip] step=patch result=success patch_bytes={}", patch_bytes);
+                    true
+                }
+                Err(e) => {
+                    eprintln!("[EnsurePayloadZip] step=patch result=fallback_full_download reason=\"{}\"", e);
+                    false
+                }
+            }
+        }
+        None => false,
+    };
+    
+    // ============================================================================
+    // Step 3: download / share_copy - 获取 ZIP 文件（HTTP 下载或从共享路径复制）
+    // ============================================================================
+    let source_used = match &source {
+        _ if patched => "patch".to_string(),
+        PayloadSource::Http(_) => {
+            download_payload_http(canonical_url.clone(), expected_sha256, expected_size, &payload_tmp, &payload_zip, &source_used, progress).await?;
+            source_used
+        }
+        PayloadSource::LocalFile(src_path) => {
+            copy_payload_from_share(src_path, &payload_tmp, &payload_zip, progress)?;
+            "share".to_string()
+        }
+    };
+    
+    // ============================================================================
+    // Step 4: sha256_verify - 校验 SHA256
+    // ============================================================================
+    eprintln!("[EnsurePayloadZip] step=sha256_verify inputs=payload_zip=\"{}\" expected_sha256=\"{}\"", 
+        payload_zip.display(), expected_sha256);
+    
+    // 发送 Verify 开始事件
+    let mut verify_reporter_opt: Option<crate::platform::windows::step_reporter::StepReporter> = progress.start_step("driver.verify", "正在校验驱动包");
+    
+    let sha256_actual = sha256_file(&payload_zip)?;
+    let sha256_actual_lower = sha256_actual.to_lowercase();
+    let expected_lower = expected_sha256.to_lowercase();
+    
+    if sha256_actual_lower != expected_lower {
+        // SHA256 不匹配，删除文件
+        let _ = fs::remove_file(&payload_zip);
+        
+        let evidence = format!(
+            "step=sha256_verify expected=\"{}\" actual=\"{}\" payload_zip=\"{}\"",
+            expected_sha256, sha256_actual, payload_zip.display()
+        );
+        eprintln!("[EnsurePayloadZip] step=sha256_verify result=failed evidence=\"{}\"", evidence);
+        
+        // 发送 Verify 失败事件
+        if let Some(reporter) = verify_reporter_opt.take() {
+            let _ = reporter.failed(
+                "SHA256_MISMATCH".to_string(),
+                format!("SHA256 校验失败：期望 {}，实际 {}", expected_sha256, sha256_actual),
+                None,
+                Some(evidence.clone()),
+                None,
+            );
+        }
+        
+        return Err(FetchError::Sha256Mismatch {
+            expected: expected_sha256.to_string(),
+            actual: sha256_actual,
+            payload_zip: payload_zip.display().to_string(),
+        });
+    }
+    
+    eprintln!("[EnsurePayloadZip] step=sha256_verify result=passed sha256_actual=\"{}\"", sha256_actual);
+    
+    // 发送 Verify 成功事件
+    if let Some(reporter) = verify_reporter_opt.take() {
+        let meta = serde_json::json!({
+            "sha256": sha256_actual,
+        });
+        let _ = reporter.success(
+            "驱动包校验通过".to_string(),
+            Some(meta),
+        );
+    }
+    
+    // ============================================================================
+    // Step 5: summary - 输出摘要
     // ============================================================================
+    let file_size = fs::metadata(&payload_zip)
+        .map_err(|e| FetchError::IoError {
+            step: "summary",
+            operation: "获取文件大小",
+            error: format!("无法获取文件大小 {}: {}", payload_zip.display(), e),
+        })?
+        .len();
+    
+    let evidence = format!(
+        "step=summary driver_uuid=\"{}\" uuid_root=\"{}\" payload_zip=\"{}\" source_used=\"{}\" bytes={} sha256_actual=\"{}\"",
+        driver_uuid, uuid_root.display(), payload_zip.display(), source_used, file_size, sha256_actual
+    );
+    
+    eprintln!("[EnsurePayloadZip] step=summary result=success evidence=\"{}\"", evidence);
+    
+    Ok(FetchResult {
+        driver_uuid,
+        uuid_root,
+        payload_zip,
+        source_used,
+        bytes: file_size,
+        sha256_actual,
+    })
+}
+
+/// 下载增量补丁并在本机旧驱动包上重建新驱动包（成功时新驱动包已放到 payload_zip）
+///
+/// 补丁下载到 payload_zip 所在目录；sha256 计算、补丁应用与改名在阻塞线程中执行
+///
+/// # 返回
+/// - `Ok(u64)`: 补丁字节数
+/// - `Err(String)`: 不能使用补丁的原因（调用方回退到完整下载）
+async fn apply_remote_patch(
+    drivers_root: &Path,
+    patch: &crate::DriverPatchResolved,
+    payload_zip: &Path,
+    expected_sha256: &str,
+    progress: FetchProgress<'_>,
+) -> Result<u64, String> {
+    validate_sha256(&patch.base_sha256).map_err(|e| format!("baseSha256 无效: {}", e))?;
+    validate_sha256(&patch.sha256).map_err(|e| format!("补丁 sha256 无效: {}", e))?;
+    let base_zip = drivers_root
+        .join(driver_uuid_for_sha256(&patch.base_sha256))
+        .join("payload")
+        .join("payload.zip");
+    if !base_zip.exists() {
+        return Err(format!("本机没有旧驱动包缓存 {}", base_zip.display()));
+    }
+    let base_actual = {
+        let base_zip = base_zip.clone();
+        tokio::task::spawn_blocking(move || sha256_file(&base_zip).map_err(|e| e.to_string()))
+            .await
+            .map_err(|e| format!("旧驱动包校验任务异常: {}", e))??
+    };
+    if !base_actual.eq_ignore_ascii_case(&patch.base_sha256) {
+        return Err(format!("旧驱动包 sha256 不符（期望 {}，实际 {}）", patch.base_sha256, base_actual));
+    }
+
+    let payload_dir = payload_zip.parent().unwrap_or(drivers_root);
+    let workdir = super::job_workdir::current_dir();
+    let patch_zip = payload_dir.join("patch.zip");
+    let patch_tmp = match &workdir {
+        Some(dir) => dir.join("patch.zip.part"),
+        None => payload_dir.join("patch.zip.part"),
+    };
+    let work_dir = match &workdir {
+        Some(dir) => dir.join("patch_work"),
+        None => payload_dir.join("patch_work"),
+    };
+    let rebuilt_zip = payload_dir.join("payload.zip.patched");
+
+    let result = async {
+        match resolve_payload_source(&patch.url).map_err(|e| e.to_string())? {
+            PayloadSource::Http(url) => {
+                download_payload_http(url, &patch.sha256, patch.size, &patch_tmp, &patch_zip, "download", progress)
+                    .await
+                    .map_err(|e| e.to_string())?
+            }
+            PayloadSource::LocalFile(src_path) => {
+                copy_payload_from_share(&src_path, &patch_tmp, &patch_zip, progress).map_err(|e| e.to_string())?
+            }
+        }
+        let (patch_sha256, expected_sha256) = (patch.sha256.clone(), expected_sha256.to_string());
+        let (base_zip, patch_zip, work_dir, rebuilt_zip, payload_zip) =
+            (base_zip.clone(), patch_zip.clone(), work_dir.clone(), rebuilt_zip.clone(), payload_zip.to_path_buf());
+        let job_scope = super::job_log::current_job_id();
+        tokio::task::spawn_blocking(move || {
+            super::job_log::sync_scope(job_scope, || {
+                let patch_actual = sha256_file(&patch_zip).map_err(|e| e.to_string())?;
+                if !patch_actual.eq_ignore_ascii_case(&patch_sha256) {
+                    return Err(format!("补丁 sha256 不符（期望 {}，实际 {}）", patch_sha256, patch_actual));
+                }
+                let patch_bytes = fs::metadata(&patch_zip).map(|m| m.len()).unwrap_or(0);
+
+                let _ = fs::remove_dir_all(&work_dir);
+                crate::utils::zip_patch::apply_patch(&base_zip, &patch_zip, &work_dir, &rebuilt_zip)?;
+                let rebuilt_actual = sha256_file(&rebuilt_zip).map_err(|e| e.to_string())?;
+                if !rebuilt_actual.eq_ignore_ascii_case(&expected_sha256) {
+                    return Err(format!("补丁重建结果 sha256 不符（期望 {}，实际 {}）", expected_sha256, rebuilt_actual));
+                }
+                super::job_workdir::move_into_place(&rebuilt_zip, &payload_zip)
+                    .map_err(|e| format!("无法重命名 {} -> {}: {}", rebuilt_zip.display(), payload_zip.display(), e))?;
+                Ok(patch_bytes)
+            })
+        })
+        .await
+        .map_err(|e| format!("补丁应用任务异常: {}", e))?
+    }
+    .await;
+
+    let _ = fs::remove_file(&patch_zip);
+    let _ = fs::remove_file(&rebuilt_zip);
+    let _ = fs::remove_dir_all(&work_dir);
+    result
+}
+
+/// 从本地/UNC 共享路径复制驱动包 ZIP，写入 payload_tmp 后重命名为 payload_zip
+///
+/// 复制完成后仍由 ensure_payload_zip 的 sha256_verify 步骤统一校验
+fn copy_payload_from_share(
+    src_path: &Path,
+    payload_tmp: &Path,
+    payload_zip: &Path,
+    progress: FetchProgress<'_>,
+) -> Result<(), FetchError> {
+    use std::io::Write;
+
+    eprintln!("[DriverFetch] step=share_copy start src=\"{}\" dest_tmp=\"{}\" dest_final=\"{}\"",
+        src_path.display(), payload_tmp.display(), payload_zip.display());
+
+    let reporter_opt: Option<crate::platform::windows::step_reporter::StepReporter> = progress.start_step("driver.download", "正在从共享路径复制驱动包");
+
+    if payload_tmp.exists() {
+        let _ = fs::remove_file(payload_tmp);
+    }
+
+    let copy_result = (|| -> Result<u64, FetchError> {
+        let mut src = fs::File::open(src_path).map_err(|e| map_share_error(src_path, &e))?;
+        let total = src.metadata().ok().map(|m| m.len());
+        let mut dest = fs::File::create(payload_tmp).map_err(|e| FetchError::IoError {
+            step: "share_copy",
+            operation: "创建临时文件",
+            error: format!("无法创建临时文件 {}: {}", payload_tmp.display(), e),
+        })?;
+
+        let mut buffer = vec![0u8; 64 * 1024];
+        let mut copied: u64 = 0;
+        let mut last_progress_mb: u64 = 0;
+        loop {
+            let n = src.read(&mut buffer).map_err(|e| map_share_error(src_path, &e))?;
+            if n == 0 {
+                break;
+            }
+            dest.write_all(&buffer[..n]).map_err(|e| FetchError::IoError {
+                step: "share_copy",
+                operation: "写入临时文件",
+                error: format!("无法写入临时文件 {}: {}", payload_tmp.display(), e),
+            })?;
+            copied += n as u64;
+
+            // 每复制 1MB 更新一次进度
+            let copied_mb = copied / 1024 / 1024;
+            if copied_mb > last_progress_mb {
+                last_progress_mb = copied_mb;
+                if let Some(reporter) = reporter_opt.as_ref() {
+                    let percent = total.filter(|t| *t > 0).map(|t| (copied as f64 / t as f64) * 100.0);
+                    reporter.update_progress(
+                        Some(copied),
+                        total,
+                        Some("bytes".to_string()),
+                        percent,
+                        Some(format!("已复制 {} / {} MB", copied_mb, total.map(|t| t / 1024 / 1024).unwrap_or(0))),
+                    );
+                }
+            }
+        }
+
+        dest.flush().map_err(|e| FetchError::IoError {
+            step: "share_copy",
+            operation: "刷新临时文件",
+            error: format!("无法刷新临时文件 {}: {}", payload_tmp.display(), e),
+        })?;
+        drop(dest);
+
+        if copied == 0 {
+            return Err(FetchError::ShareUnavailable {
+                path: src_path.display().to_string(),
+                os_error: None,
+                error: "共享路径上的驱动包为空文件".to_string(),
+            });
+        }
+
+        super::job_workdir::move_into_place(payload_tmp, payload_zip).map_err(|e| FetchError::IoError {
+            step: "share_copy",
+            operation: "重命名临时文件",
+            error: format!("无法重命名 {} -> {}: {}", payload_tmp.display(), payload_zip.display(), e),
+        })?;
+
+        Ok(copied)
+    })();
+
+    match copy_result {
+        Ok(bytes) => {
+            eprintln!("[DriverFetch] step=share_copy result=success bytes={} src=\"{}\"", bytes, src_path.display());
+            if let Some(reporter) = reporter_opt {
+                let meta = serde_json::json!({
+                    "bytes": bytes,
+                    "source": src_path.display().to_string(),
+                });
+                let _ = reporter.success(
+                    format!("复制完成：{} MB", bytes / 1024 / 1024),
+                    Some(meta),
+                );
+            }
+            Ok(())
+        }
+        Err(error) => {
+            eprintln!("[DriverFetch] step=share_copy result=failed code={} error=\"{}\"", error.code(), error);
+            if payload_tmp.exists() {
+                let _ = fs::remove_file(payload_tmp);
+            }
+            if let Some(reporter) = reporter_opt {
+                let _ = reporter.failed(
+                    error.code().to_string(),
+                    format!("{}", error),
+                    None,
+                    None,
+                    None,
+                );
+            }
+            Err(error)
+        }
+    }
+}
+
+/// 响应头是否表明返回的是网页（认证页 / 错误页）而不是二进制包
+fn is_html_content_type(content_type: Option<&str>) -> bool {
+    content_type
+        .map(|ct| {
+            let lower = ct.trim().to_ascii_lowercase();
+            lower.starts_with("text/html") || lower.starts_with("application/xhtml")
+        })
+        .unwrap_or(false)
+}
+
+/// 检查响应头：HTML 页面、声明大小、服务器提供的 SHA256
+fn check_response_headers(
+    response: &reqwest::Response,
+    url: &str,
+    expected_sha256: &str,
+    expected_size: Option<u64>,
+) -> Result<(), FetchError> {
+    let header = |name: &str| response.headers().get(name).and_then(|v| v.to_str().ok()).map(str::trim);
+
+    let content_type = header("content-type");
+    if is_html_content_type(content_type) {
+        return Err(FetchError::CaptivePortalSuspected {
+            url: url.to_string(),
+            final_url: response.url().to_string(),
+            content_type: content_type.unwrap_or_default().to_string(),
+        });
+    }
+
+    if let (Some(expected), Some(actual)) = (expected_size, response.content_length()) {
+        if actual > 0 && actual != expected {
+            return Err(FetchError::DownloadSizeMismatch {
+                url: url.to_string(),
+                expected,
+                actual,
+            });
+        }
+    }
+
+    let server_sha256 = ["x-checksum-sha256", "x-amz-meta-sha256"]
+        .iter()
+        .find_map(|name| header(name))
+        .filter(|v| v.len() == 64 && v.chars().all(|c| c.is_ascii_hexdigit()));
+    if let Some(actual) = server_sha256 {
+        if !actual.eq_ignore_ascii_case(expected_sha256) {
+            return Err(FetchError::Sha256Mismatch {
+                expected: expected_sha256.to_lowercase(),
+                actual: actual.to_lowercase(),
+                payload_zip: format!("{}（服务器响应头声明）", url),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// 下载前的 HEAD 预检（请求失败或服务器不支持 HEAD 时返回 Ok，交给 GET 流程处理）
+async fn preflight_head(
+    client: &reqwest::Client,
+    url: &str,
+    url_display: &str,
+    expected_sha256: &str,
+    expected_size: Option<u64>,
+) -> Result<(), FetchError> {
+    let response = match client.head(url).send().await {
+        Ok(response) => response,
+        Err(e) => {
+            eprintln!("[DriverFetch] step=preflight_head result=skipped reason=request_failed error=\"{}\" url=\"{}\"", e, url_display);
+            return Ok(());
+        }
+    };
+    if !response.status().is_success() {
+        eprintln!("[DriverFetch] step=preflight_head result=skipped reason=status_{} url=\"{}\"", response.status().as_u16(), url_display);
+        return Ok(());
+    }
+    check_response_headers(&response, url, expected_sha256, expected_size)?;
+    eprintln!(
+        "[DriverFetch] step=preflight_head result=passed content_length={:?} expected_size={:?} url=\"{}\"",
+        response.content_length(), expected_size, url_display
+    );
+    Ok(())
+}
+
+/// 通过 HTTP 下载驱动包 ZIP（应用内下载，带重试），写入 payload_tmp 后重命名为 payload_zip
+async fn download_payload_http(
+    canonical_url: String,
+    expected_sha256: &str,
+    expected_size: Option<u64>,
+    payload_tmp: &Path,
+    payload_zip: &Path,
+    source_used: &str,
+    progress: FetchProgress<'_>,
+) -> Result<(), FetchError> {
     // 注意：使用 canonical_url，不再使用 remote_url，避免二次拼接
     eprintln!("[DriverFetch] step=download_internal start url=\"{}\" dest_tmp=\"{}\" dest_final=\"{}\"", 
         canonical_url, payload_tmp.display(), payload_zip.display());
     
     // 创建 StepReporter（仅在需要下载时）
     let mut step_reporter_opt: Option<crate::platform::windows::step_reporter::StepReporter> = if source_used == "download" {
-        if let (Some(app_handle), Some(printer)) = (app, printer_name) {
-            Some(crate::platform::windows::step_reporter::StepReporter::start(
-                std::sync::Arc::new(app_handle.clone()),
-                job_id.to_string(),
-                printer.to_string(),
-                "driver.download".to_string(),
-                "正在下载驱动包".to_string(),
-            ))
-        } else {
-            None
-        }
+        progress.start_step("driver.download", "正在下载驱动包")
     } else {
         None
     };
     
     // 删除临时文件（如果存在）
     if payload_tmp.exists() {
-        let _ = fs::remove_file(&payload_tmp);
+        let _ = fs::remove_file(payload_tmp);
     }
     
     // 下载配置
     const MAX_ATTEMPTS: u32 = 3;
     const TIMEOUT_SECS: u64 = 120;
+    const PREFLIGHT_TIMEOUT_SECS: u64 = 15;
+    
+    let limits = resolve_download_limits();
+    eprintln!("[DriverFetch] step=download_internal limits bytes_per_sec={:?} defer_above_bytes={:?} cost_type={:?}",
+        limits.bytes_per_sec, limits.defer_above_bytes, limits.cost_type);
     
     // 脱敏 URL（用于日志）- 基于 canonical_url，不再拼接 scheme
     let url_display = if let Some(domain_end) = canonical_url.find("://") {
@@ -388,6 +1030,26 @@ pub async fn ensure_payload_zip(
         canonical_url.clone()
     };
     
+    // HEAD 预检：认证页 / 大小不符 / 服务器声明的 SHA256 不符时立即失败，不开始下载
+    let preflight_client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(PREFLIGHT_TIMEOUT_SECS))
+        .user_agent("ePrinty/1.4.1")
+        .build()
+        .map_err(|e| FetchError::DownloadFailed {
+            step: "preflight_head",
+            url: canonical_url.clone(),
+            attempt: 0,
+            http_status: None,
+            error: format!("无法创建 HTTP 客户端: {}", e),
+        })?;
+    if let Err(error) = preflight_head(&preflight_client, &canonical_url, &url_display, expected_sha256, expected_size).await {
+        eprintln!("[DriverFetch] step=preflight_head result=failed code={} url=\"{}\"", error.code(), url_display);
+        if let Some(reporter) = step_reporter_opt.take() {
+            let _ = reporter.failed(error.code().to_string(), format!("{}", error), None, None, None);
+        }
+        return Err(error);
+    }
+    
     let mut last_error: Option<FetchError> = None;
     
     for attempt in 1..=MAX_ATTEMPTS {
@@ -467,9 +1129,9 @@ pub async fn ensure_payload_zip(
                     }
                     
                     // 清理临时文件
-                    let _ = fs::remove_file(&payload_tmp);
+                    let _ = fs::remove_file(payload_tmp);
                     // 如果最终文件已存在，也删除（避免 cache_check 命中空包）
-                    let _ = fs::remove_file(&payload_zip);
+                    let _ = fs::remove_file(payload_zip);
                     
                     last_error = Some(error);
                     break;
@@ -510,6 +1172,18 @@ pub async fn ensure_payload_zip(
                     break;
                 }
                 
+                // ============================================================================
+                // 严格成功判据 1.5: 响应头检查（HEAD 未拦截到时，GET 仍可能被认证页替换；不重试）
+                // ============================================================================
+                if let Err(error) = check_response_headers(&response, &canonical_url, expected_sha256, expected_size) {
+                    eprintln!("[DriverFetch] step=download_internal result=failed code={} status={} url=\"{}\"",
+                        error.code(), http_status_code, url_display);
+                    if let Some(reporter) = step_reporter_opt.take() {
+                        let _ = reporter.failed(error.code().to_string(), format!("{}", error), None, None, None);
+                    }
+                    return Err(error);
+                }
+                
                 // ============================================================================
                 // 严格成功判据 2: Content-Length 检查
                 // ============================================================================
@@ -525,15 +1199,40 @@ pub async fn ensure_payload_zip(
                             http_status_code, url_display);
                         
                         // 清理临时文件
-                        let _ = fs::remove_file(&payload_tmp);
+                        let _ = fs::remove_file(payload_tmp);
                         // 如果最终文件已存在，也删除
-                        let _ = fs::remove_file(&payload_zip);
+                        let _ = fs::remove_file(payload_zip);
                         
                         last_error = Some(error);
                         break;
                     }
                 }
                 
+                // 按流量计费网络：大驱动包推迟下载（大小未知时不推迟，仅限速）
+                if let (Some(threshold), Some(cl)) = (limits.defer_above_bytes, content_length) {
+                    if cl > threshold {
+                        let error = FetchError::DeferredMeteredConnection {
+                            url: canonical_url.clone(),
+                            content_length: cl,
+                            threshold_bytes: threshold,
+                            cost_type: limits.cost_type.unwrap_or("unknown").to_string(),
+                        };
+                        eprintln!("[DriverFetch] step=download_internal result=deferred reason=metered_connection content_length={} threshold={} url=\"{}\"", 
+                            cl, threshold, url_display);
+                        
+                        if let Some(reporter) = step_reporter_opt.take() {
+                            let _ = reporter.failed(
+                                error.code().to_string(),
+                                format!("{}", error),
+                                None,
+                                None,
+                                None,
+                            );
+                        }
+                        return Err(error);
+                    }
+                }
+                
                 // 确保父目录存在
                 if let Some(parent) = payload_tmp.parent() {
                     if let Err(e) = fs::create_dir_all(parent) {
@@ -550,7 +1249,7 @@ pub async fn ensure_payload_zip(
                     .create(true)
                     .truncate(true)
                     .write(true)
-                    .open(&payload_tmp)
+                    .open(payload_tmp)
                     .map_err(|e| FetchError::IoError {
                         step: "download_internal",
                         operation: "创建临时文件",
@@ -560,6 +1259,7 @@ pub async fn ensure_payload_zip(
                 let mut stream = response.bytes_stream();
                 let mut total_bytes = 0u64;
                 let mut last_progress_log = std::time::Instant::now();
+                let stream_start = std::time::Instant::now();
                 
                 use futures_util::StreamExt;
                 while let Some(chunk_result) = stream.next().await {
@@ -581,6 +1281,15 @@ pub async fn ensure_payload_zip(
                     
                     total_bytes += chunk.len() as u64;
                     
+                    // 限速：实际速度超过限制时等待到应达时间点
+                    if let Some(bytes_per_sec) = limits.bytes_per_sec {
+                        let target = Duration::from_secs_f64(total_bytes as f64 / bytes_per_sec as f64);
+                        let elapsed = stream_start.elapsed();
+                        if target > elapsed {
+                            tokio::time::sleep(target - elapsed).await;
+                        }
+                    }
+                    
                     // 每 256KB 或每 300ms 发送一次进度事件
                     let should_emit_progress = last_progress_log.elapsed().as_millis() >= 300 || 
                         (total_bytes % 262144 == 0 && total_bytes > 0);  // 每 256KB
@@ -659,9 +1368,9 @@ pub async fn ensure_payload_zip(
                     }
                     
                     // 清理临时文件
-                    let _ = fs::remove_file(&payload_tmp);
+                    let _ = fs::remove_file(payload_tmp);
                     // 如果最终文件已存在，也删除
-                    let _ = fs::remove_file(&payload_zip);
+                    let _ = fs::remove_file(payload_zip);
                     
                     last_error = Some(error);
                     break;
@@ -670,8 +1379,8 @@ pub async fn ensure_payload_zip(
                 // ============================================================================
                 // 所有判据通过，执行原子重命名
                 // ============================================================================
-                // 原子重命名：dest_tmp -> dest_final（保证原子性）
-                fs::rename(&payload_tmp, &payload_zip)
+                // 原子重命名：dest_tmp -> dest_final（跨卷时先复制到目标旁再重命名，仍保证原子性）
+                super::job_workdir::move_into_place(payload_tmp, payload_zip)
                     .map_err(|e| FetchError::IoError {
                         step: "download_internal",
                         operation: "重命名文件",
@@ -749,99 +1458,10 @@ pub async fn ensure_payload_zip(
     if let Some(error) = last_error {
         // 清理临时文件（避免下次误命中）
         if payload_tmp.exists() {
-            let _ = fs::remove_file(&payload_tmp);
+            let _ = fs::remove_file(payload_tmp);
         }
         return Err(error);
     }
     
-    // ============================================================================
-    // Step 4: sha256_verify - 校验 SHA256
-    // ============================================================================
-    eprintln!("[EnsurePayloadZip] step=sha256_verify inputs=payload_zip=\"{}\" expected_sha256=\"{}\"", 
-        payload_zip.display(), expected_sha256);
-    
-    // 发送 Verify 开始事件
-    let mut verify_reporter_opt: Option<crate::platform::windows::step_reporter::StepReporter> = if let (Some(app_handle), Some(printer)) = (app, printer_name) {
-        Some(crate::platform::windows::step_reporter::StepReporter::start(
-            std::sync::Arc::new(app_handle.clone()),
-            job_id.to_string(),
-            printer.to_string(),
-            "driver.verify".to_string(),
-            "正在校验驱动包".to_string(),
-        ))
-    } else {
-        None
-    };
-    
-    let sha256_actual = sha256_file(&payload_zip)?;
-    let sha256_actual_lower = sha256_actual.to_lowercase();
-    let expected_lower = expected_sha256.to_lowercase();
-    
-    if sha256_actual_lower != expected_lower {
-        // SHA256 不匹配，删除文件
-        let _ = fs::remove_file(&payload_zip);
-        
-        let evidence = format!(
-            "step=sha256_verify expected=\"{}\" actual=\"{}\" payload_zip=\"{}\"",
-            expected_sha256, sha256_actual, payload_zip.display()
-        );
-        eprintln!("[EnsurePayloadZip] step=sha256_verify result=failed evidence=\"{}\"", evidence);
-        
-        // 发送 Verify 失败事件
-        if let Some(reporter) = verify_reporter_opt.take() {
-            let _ = reporter.failed(
-                "SHA256_MISMATCH".to_string(),
-                format!("SHA256 校验失败：期望 {}，实际 {}", expected_sha256, sha256_actual),
-                None,
-                Some(evidence.clone()),
-                None,
-            );
-        }
-        
-        return Err(FetchError::Sha256Mismatch {
-            expected: expected_sha256.to_string(),
-            actual: sha256_actual,
-            payload_zip: payload_zip.display().to_string(),
-        });
-    }
-    
-    eprintln!("[EnsurePayloadZip] step=sha256_verify result=passed sha256_actual=\"{}\"", sha256_actual);
-    
-    // 发送 Verify 成功事件
-    if let Some(reporter) = verify_reporter_opt.take() {
-        let meta = serde_json::json!({
-            "sha256": sha256_actual,
-        });
-        let _ = reporter.success(
-            "驱动包校验通过".to_string(),
-            Some(meta),
-        );
-    }
-    
-    // ============================================================================
-    // Step 5: summary - 输出摘要
-    // ============================================================================
-    let file_size = fs::metadata(&payload_zip)
-        .map_err(|e| FetchError::IoError {
-            step: "summary",
-            operation: "获取文件大小",
-            error: format!("无法获取文件大小 {}: {}", payload_zip.display(), e),
-        })?
-        .len();
-    
-    let evidence = format!(
-        "step=summary driver_uuid=\"{}\" uuid_root=\"{}\" payload_zip=\"{}\" source_used=\"{}\" bytes={} sha256_actual=\"{}\"",
-        driver_uuid, uuid_root.display(), payload_zip.display(), source_used, file_size, sha256_actual
-    );
-    
-    eprintln!("[EnsurePayloadZip] step=summary result=success evidence=\"{}\"", evidence);
-    
-    Ok(FetchResult {
-        driver_uuid,
-        uuid_root,
-        payload_zip,
-        source_used,
-        bytes: file_size,
-        sha256_actual,
-    })
+    Ok(())
 }
@@ -0,0 +1,48 @@
+// Windows 平台驱动目录并发保护模块
+// 共用同一驱动包（同一 driver_uuid）的打印机同时安装时，下载 / 解压 / materialize / pnputil 暂存
+// 都在 drivers_root/<driver_uuid>/ 与 drivers_root 下读写，清理 staging 目录会删除另一个任务正在使用的文件
+//
+// - lock(): 获取 driver_uuid 对应的异步互斥锁，同一驱动串行、不同驱动并行
+// - 锁按 driver_uuid 懒创建并在会话内保留（数量上限为配置中的驱动数）
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use tokio::sync::OwnedMutexGuard;
+
+/// 每个 driver_uuid 一把锁
+static LOCKS: Mutex<Option<HashMap<String, Arc<tokio::sync::Mutex<()>>>>> = Mutex::new(None);
+
+fn lock_for(driver_uuid: &str) -> Arc<tokio::sync::Mutex<()>> {
+    let mut guard = LOCKS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    guard
+        .get_or_insert_with(HashMap::new)
+        .entry(driver_uuid.to_string())
+        .or_default()
+        .clone()
+}
+
+/// 获取驱动目录锁（返回的守卫 Drop 时释放）
+///
+/// # 参数
+/// - `driver_uuid`: 驱动包目录名（driver_fetch::driver_uuid_for_sha256）
+pub async fn lock(driver_uuid: &str) -> OwnedMutexGuard<()> {
+    let mutex = lock_for(driver_uuid);
+    if let Ok(guard) = mutex.clone().try_lock_owned() {
+        return guard;
+    }
+
+    let started = Instant::now();
+    super::log::write_log(&format!(
+        "[DriverLock] waiting driver_uuid={} (another install is using the same driver package)",
+        driver_uuid
+    ));
+    let guard = mutex.lock_owned().await;
+    super::log::write_log(&format!(
+        "[DriverLock] acquired driver_uuid={} waited_ms={}",
+        driver_uuid,
+        started.elapsed().as_millis()
+    ));
+    guard
+}
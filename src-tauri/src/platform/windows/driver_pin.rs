@@ -0,0 +1,124 @@
+// Windows 平台驱动包固定（driverPin）校验模块
+// 同名打印驱动可能来自不同驱动包，Add-Printer -DriverName 只按名称绑定；配置了 driverPin 时，
+// 创建 / 改绑队列前读取所选驱动的 InfPath，校验发布名称（oemNN.inf）与 INF 文件 sha256：
+//
+// - 发布名称：InfPath 本身为 %SystemRoot%\INF\oemNN.inf 时直接取文件名，否则（DriverStore FileRepository 路径）
+//   在 %SystemRoot%\INF 中查找内容一致的 oemNN.inf
+// - 无法读取 InfPath 或找不到发布名称时同样视为不一致（不确定时不安装）
+// - 不一致时返回 [EASYPRINTER_CODE=DRIVER_MISMATCH] 错误
+//
+// 创建安装任务时按打印机名称与路径（两者都须一致）解析 driverPin 并随任务登记；
+// 读取配置失败时登记为错误，任务内的校验一律按不一致处理（不确定时不安装）
+
+use std::path::Path;
+
+use crate::platform::ps_script::PsScript;
+use crate::utils::encoding::decode_windows_string;
+use crate::DriverPin;
+
+/// 驱动包与 driverPin 不一致时的错误码
+pub const DRIVER_MISMATCH: &str = "DRIVER_MISMATCH";
+
+/// 解析打印机配置的 driverPin（创建安装任务时调用一次）
+///
+/// # 参数
+/// - `printer_name`: 打印机名称
+/// - `path`: 打印机路径
+///
+/// # 返回
+/// - `Ok(Some(pin))`: 已配置 driverPin
+/// - `Ok(None)`: 未配置，或配置中没有名称与路径都一致的打印机
+/// - `Err(String)`: 读取配置失败，无法确认是否配置了 driverPin
+pub fn resolve_driver_pin(printer_name: &str, path: &str) -> Result<Option<DriverPin>, String> {
+    let (config, _) = crate::load_local_config().map_err(|e| format!("读取配置失败，无法确认 driverPin: {}", e))?;
+    Ok(crate::find_configured_printer(&config, printer_name, path).and_then(|printer| printer.options.driver_pin))
+}
+
+/// 读取已注册驱动的 InfPath
+fn query_driver_inf_path(driver_name: &str) -> Result<String, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; (Get-PrinterDriver -Name $DriverName -ErrorAction Stop | Select-Object -First 1).InfPath",
+    )
+    .param("DriverName", driver_name)
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    let inf_path = stdout.trim();
+    if !output.status.success() || inf_path.is_empty() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("读取驱动 \"{}\" 的 InfPath 失败: {}", driver_name, super::log::truncate(stderr.trim(), 300)));
+    }
+    Ok(inf_path.to_string())
+}
+
+/// InfPath 对应的发布名称（oemNN.inf）
+fn published_name_for(inf_path: &Path) -> Option<String> {
+    let file_name = inf_path.file_name()?.to_string_lossy().to_lowercase();
+    if file_name.starts_with("oem") && file_name.ends_with(".inf") {
+        return Some(file_name);
+    }
+    let copy = super::install::find_published_inf_copy(inf_path)?;
+    copy.file_name().map(|n| n.to_string_lossy().to_lowercase())
+}
+
+fn mismatch(driver_name: &str, reason: String) -> String {
+    super::log::write_log(&format!("[DriverPin] MISMATCH driver=\"{}\" reason=\"{}\"", driver_name, reason));
+    format!(
+        "[EASYPRINTER_CODE={}] 驱动 \"{}\" 与配置的 driverPin 不一致：{}。本机可能存在来自其他驱动包的同名驱动，请删除错误的驱动包后重试，或更新配置中的 driverPin",
+        DRIVER_MISMATCH, driver_name, reason
+    )
+}
+
+/// 校验驱动是否来自 driverPin 指定的驱动包
+///
+/// # 参数
+/// - `driver_name`: Add-Printer 将使用的驱动名称
+/// - `pin`: 配置的 driverPin
+///
+/// # 返回
+/// - `Ok(())`: 一致
+/// - `Err(String)`: 不一致或无法确认（含 DRIVER_MISMATCH 错误码）
+pub fn verify_driver_pin(driver_name: &str, pin: &DriverPin) -> Result<(), String> {
+    let inf_path = query_driver_inf_path(driver_name).map_err(|e| mismatch(driver_name, e))?;
+    let inf = Path::new(&inf_path);
+
+    if let Some(expected) = &pin.published_name {
+        match published_name_for(inf) {
+            Some(actual) if actual.eq_ignore_ascii_case(expected.trim()) => {}
+            Some(actual) => {
+                return Err(mismatch(
+                    driver_name,
+                    format!("期望发布名称 {}，实际为 {}（InfPath: {}）", expected, actual, inf_path),
+                ))
+            }
+            None => {
+                return Err(mismatch(
+                    driver_name,
+                    format!("期望发布名称 {}，未找到 InfPath 对应的 oemNN.inf（InfPath: {}）", expected, inf_path),
+                ))
+            }
+        }
+    }
+    if let Some(expected) = &pin.inf_sha256 {
+        let actual = super::driver_fetch::sha256_file(inf)
+            .map_err(|e| mismatch(driver_name, format!("计算 INF sha256 失败（InfPath: {}）: {}", inf_path, e)))?;
+        if !actual.eq_ignore_ascii_case(expected.trim()) {
+            return Err(mismatch(
+                driver_name,
+                format!("期望 INF sha256 {}，实际为 {}（InfPath: {}）", expected.trim().to_lowercase(), actual, inf_path),
+            ));
+        }
+    }
+    super::log::write_log(&format!("[DriverPin] OK driver=\"{}\" inf_path=\"{}\"", driver_name, inf_path));
+    Ok(())
+}
+
+/// 按当前安装任务登记的 driverPin 校验驱动（未配置 driverPin 或不在安装任务中时直接通过，
+/// 登记时无法确认配置则视为不一致）
+pub fn check_current_job(driver_name: &str) -> Result<(), String> {
+    match super::install_jobs::current_driver_pin() {
+        Ok(Some(pin)) => verify_driver_pin(driver_name, &pin),
+        Ok(None) => Ok(()),
+        Err(e) => Err(mismatch(driver_name, e)),
+    }
+}
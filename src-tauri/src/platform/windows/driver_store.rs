@@ -0,0 +1,323 @@
+// Windows 平台 DriverStore 维护模块
+// 使用 pnputil /enum-drivers 与 /delete-driver 管理已导入（staged）的打印机驱动包
+//
+// 安全约束：
+// - 仅允许删除 oemNN.inf 形式的 published name
+// - 仅允许删除打印机类（Class GUID = {4d36e979-e325-11ce-bfc1-08002be10318}）驱动包
+// - 驱动包仍被打印队列使用时拒绝删除（不使用 /force）
+
+use super::cmd;
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::{RemoveStagedDriverResult, StagedDriverInfo};
+
+/// 打印机设备类 GUID（与系统语言无关，用于过滤 pnputil 输出）
+const PRINTER_CLASS_GUID: &str = "{4d36e979-e325-11ce-bfc1-08002be10318}";
+
+/// pnputil 输出中的单个驱动包块（尚未关联打印队列）
+#[derive(Debug, Default)]
+struct PnputilDriverBlock {
+    published_name: Option<String>,
+    original_name: Option<String>,
+    provider_name: Option<String>,
+    class_name: Option<String>,
+    class_guid: Option<String>,
+    driver_version: Option<String>,
+    signer_name: Option<String>,
+}
+
+/// 已注册的打印机驱动及使用它的队列
+#[derive(Debug, Default)]
+struct PrinterDriverUsage {
+    driver_name: String,
+    inf_file_name: Option<String>,
+    queues: Vec<String>,
+}
+
+/// 校验 published name 是否为 oemNN.inf 形式
+fn is_valid_published_name(name: &str) -> bool {
+    let lower = name.trim().to_lowercase();
+    match lower.strip_prefix("oem").and_then(|s| s.strip_suffix(".inf")) {
+        Some(digits) => !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()),
+        None => false,
+    }
+}
+
+/// 解析 pnputil /enum-drivers 输出（兼容中英文系统）
+///
+/// 输出按空行分块，每块为 "键: 值" 行；键名随系统语言变化，
+/// 因此同时匹配中文与英文标签
+fn parse_enum_drivers_output(output: &str) -> Vec<PnputilDriverBlock> {
+    let mut blocks = Vec::new();
+    let mut current = PnputilDriverBlock::default();
+
+    for line in output.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            if current.published_name.is_some() {
+                blocks.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+
+        // 同时兼容半角与全角冒号
+        let (key, value) = match line.split_once(':').or_else(|| line.split_once('：')) {
+            Some((k, v)) => (k.trim().to_lowercase(), v.trim().to_string()),
+            None => continue,
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        match key.as_str() {
+            "published name" | "发布名称" => {
+                // 新块开始（部分版本块之间无空行）
+                if current.published_name.is_some() {
+                    blocks.push(std::mem::take(&mut current));
+                }
+                current.published_name = Some(value);
+            }
+            "original name" | "原始名称" => current.original_name = Some(value),
+            "provider name" | "提供程序名称" => current.provider_name = Some(value),
+            "class name" | "类名" | "类名称" => current.class_name = Some(value),
+            "class guid" | "类 guid" | "类guid" => current.class_guid = Some(value.to_lowercase()),
+            "driver version" | "驱动程序版本" => current.driver_version = Some(value),
+            "signer name" | "签名者姓名" | "签名者名称" => current.signer_name = Some(value),
+            _ => {}
+        }
+    }
+
+    if current.published_name.is_some() {
+        blocks.push(current);
+    }
+
+    blocks
+}
+
+/// 执行 pnputil /enum-drivers 并返回打印机类驱动包
+fn enum_printer_driver_packages() -> Result<Vec<PnputilDriverBlock>, String> {
+    let output = cmd::run_command("pnputil.exe", &["/enum-drivers"])
+        .map_err(|e| format!("执行 pnputil /enum-drivers 失败: {}", e))?;
+
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!(
+            "pnputil /enum-drivers 返回失败: exit_code={:?} stderr={}",
+            output.status.code(),
+            log::truncate(stderr.trim(), 300)
+        ));
+    }
+
+    let blocks = parse_enum_drivers_output(&stdout)
+        .into_iter()
+        .filter(|b| {
+            b.class_guid
+                .as_deref()
+                .map(|g| g == PRINTER_CLASS_GUID)
+                .unwrap_or_else(|| {
+                    // 无 Class GUID 时退回到类名判断
+                    b.class_name
+                        .as_deref()
+                        .map(|c| c.eq_ignore_ascii_case("Printer") || c == "打印机")
+                        .unwrap_or(false)
+                })
+        })
+        .collect();
+
+    Ok(blocks)
+}
+
+/// 查询已注册的打印机驱动（含 InfPath）及使用它们的打印队列
+fn query_printer_driver_usage() -> Result<Vec<PrinterDriverUsage>, String> {
+    let script = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+        $drivers = @(Get-PrinterDriver -ErrorAction SilentlyContinue | ForEach-Object { [PSCustomObject]@{ Name = $_.Name; InfPath = $_.InfPath } }); \
+        $queues = @(Get-Printer -ErrorAction SilentlyContinue | ForEach-Object { [PSCustomObject]@{ Name = $_.Name; DriverName = $_.DriverName } }); \
+        [PSCustomObject]@{ drivers = $drivers; queues = $queues } | ConvertTo-Json -Compress -Depth 4";
+
+    let output = super::ps::run_powershell(script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("查询打印机驱动使用情况失败: {}", log::truncate(stderr.trim(), 300)));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析打印机驱动使用情况失败: {}", e))?;
+
+    // ConvertTo-Json 对单元素数组可能输出对象，这里统一为数组
+    let as_array = |v: Option<&serde_json::Value>| -> Vec<serde_json::Value> {
+        match v {
+            Some(serde_json::Value::Array(items)) => items.clone(),
+            Some(serde_json::Value::Null) | None => Vec::new(),
+            Some(other) => vec![other.clone()],
+        }
+    };
+
+    let queues = as_array(json.get("queues"));
+    let usages = as_array(json.get("drivers"))
+        .iter()
+        .filter_map(|d| {
+            let driver_name = d.get("Name")?.as_str()?.to_string();
+            let inf_file_name = d
+                .get("InfPath")
+                .and_then(|p| p.as_str())
+                .and_then(|p| std::path::Path::new(p).file_name().map(|f| f.to_string_lossy().to_lowercase()));
+            let queues = queues
+                .iter()
+                .filter(|q| q.get("DriverName").and_then(|n| n.as_str()) == Some(driver_name.as_str()))
+                .filter_map(|q| q.get("Name").and_then(|n| n.as_str()).map(|s| s.to_string()))
+                .collect();
+            Some(PrinterDriverUsage { driver_name, inf_file_name, queues })
+        })
+        .collect();
+
+    Ok(usages)
+}
+
+/// 将驱动包与已注册驱动/队列关联
+///
+/// 注意：通过原始 INF 文件名匹配 InfPath；同一原始 INF 存在多个版本时，
+/// 所有版本都会被视为"使用中"（宁可拒绝删除也不误删）
+fn build_staged_driver_info(block: PnputilDriverBlock, usages: &[PrinterDriverUsage]) -> StagedDriverInfo {
+    let original_lower = block.original_name.as_deref().map(|n| n.to_lowercase());
+    let mut registered_drivers = Vec::new();
+    let mut in_use_by_queues = Vec::new();
+
+    if let Some(original) = &original_lower {
+        for usage in usages {
+            if usage.inf_file_name.as_deref() == Some(original.as_str()) {
+                registered_drivers.push(usage.driver_name.clone());
+                in_use_by_queues.extend(usage.queues.iter().cloned());
+            }
+        }
+    }
+
+    StagedDriverInfo {
+        published_name: block.published_name.unwrap_or_default(),
+        original_name: block.original_name,
+        provider_name: block.provider_name,
+        class_name: block.class_name,
+        driver_version: block.driver_version,
+        signer_name: block.signer_name,
+        registered_drivers,
+        in_use_by_queues,
+    }
+}
+
+/// 列出 DriverStore 中已导入的打印机驱动包
+///
+/// # 返回
+/// - `Ok(Vec<StagedDriverInfo>)`: 打印机类驱动包列表（含使用情况）
+/// - `Err(String)`: pnputil 执行失败
+pub fn list_staged_printer_drivers_windows() -> Result<Vec<StagedDriverInfo>, String> {
+    log::write_log("[DriverStore] LIST_START");
+
+    let blocks = enum_printer_driver_packages()?;
+    let usages = match query_printer_driver_usage() {
+        Ok(u) => u,
+        Err(e) => {
+            // 使用情况查询失败不影响列表返回，但删除时会重新检查
+            log::write_log(&format!("[DriverStore] QUERY_USAGE_FAIL error={}", e));
+            Vec::new()
+        }
+    };
+
+    let drivers: Vec<StagedDriverInfo> = blocks
+        .into_iter()
+        .map(|b| build_staged_driver_info(b, &usages))
+        .collect();
+
+    log::write_log(&format!("[DriverStore] LIST_OK count={}", drivers.len()));
+    Ok(drivers)
+}
+
+/// 从 DriverStore 删除已导入的打印机驱动包
+///
+/// # 参数
+/// - `published_name`: 发布名称（oemNN.inf）
+///
+/// # 返回
+/// - `Ok(RemoveStagedDriverResult)`: 删除结果（被拒绝时 success=false）
+/// - `Err(String)`: 参数非法或命令执行失败
+pub fn remove_staged_driver_windows(published_name: &str) -> Result<RemoveStagedDriverResult, String> {
+    let published_name = published_name.trim();
+    log::write_log(&format!("[DriverStore] REMOVE_START published_name=\"{}\"", published_name));
+
+    if !is_valid_published_name(published_name) {
+        return Err(format!("无效的驱动包发布名称: \"{}\"（应为 oemNN.inf）", published_name));
+    }
+
+    // 删除前重新检查：必须是打印机类驱动包，且未被任何队列使用
+    let blocks = enum_printer_driver_packages()?;
+    let block = match blocks
+        .into_iter()
+        .find(|b| b.published_name.as_deref().map(|n| n.eq_ignore_ascii_case(published_name)).unwrap_or(false))
+    {
+        Some(b) => b,
+        None => {
+            return Ok(RemoveStagedDriverResult {
+                success: false,
+                published_name: published_name.to_string(),
+                message: format!("未在 DriverStore 中找到打印机驱动包: {}", published_name),
+                evidence: None,
+            });
+        }
+    };
+
+    // 使用情况查询失败时，为安全起见拒绝删除
+    let usages = query_printer_driver_usage()
+        .map_err(|e| format!("无法检查驱动包使用情况，已拒绝删除: {}", e))?;
+    let info = build_staged_driver_info(block, &usages);
+
+    if !info.in_use_by_queues.is_empty() {
+        log::write_log(&format!(
+            "[DriverStore] REMOVE_SKIP published_name=\"{}\" reason=in_use queues={:?}",
+            published_name, info.in_use_by_queues
+        ));
+        return Ok(RemoveStagedDriverResult {
+            success: false,
+            published_name: published_name.to_string(),
+            message: format!(
+                "驱动包 {} 仍被 {} 个打印机使用（{}），已拒绝删除。请先删除相关打印机。",
+                published_name,
+                info.in_use_by_queues.len(),
+                info.in_use_by_queues.join("、")
+            ),
+            evidence: Some(format!("registered_drivers={:?}", info.registered_drivers)),
+        });
+    }
+
+    let output = cmd::run_command("pnputil.exe", &["/delete-driver", published_name])
+        .map_err(|e| format!("执行 pnputil /delete-driver 失败: {}", e))?;
+    super::session_cache::invalidate_driver_inventory();
+    let stdout = decode_windows_string(&output.stdout);
+    let stderr = decode_windows_string(&output.stderr);
+    let evidence = format!(
+        "pnputil /delete-driver exit_code={:?} stdout=\"{}\" stderr=\"{}\"",
+        output.status.code(),
+        log::truncate(stdout.trim(), 300),
+        log::truncate(stderr.trim(), 300)
+    );
+
+    if output.status.success() {
+        log::write_log(&format!("[DriverStore] REMOVE_OK published_name=\"{}\"", published_name));
+        Ok(RemoveStagedDriverResult {
+            success: true,
+            published_name: published_name.to_string(),
+            message: format!("已从 DriverStore 删除驱动包: {}", published_name),
+            evidence: Some(evidence),
+        })
+    } else {
+        log::write_log(&format!("[DriverStore] REMOVE_FAIL published_name=\"{}\" {}", published_name, evidence));
+        Ok(RemoveStagedDriverResult {
+            success: false,
+            published_name: published_name.to_string(),
+            message: format!(
+                "删除驱动包失败: {}。驱动包可能仍被系统使用，或需要管理员权限。",
+                published_name
+            ),
+            evidence: Some(evidence),
+        })
+    }
+}
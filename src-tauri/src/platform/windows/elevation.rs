@@ -0,0 +1,74 @@
+// Windows 平台以管理员身份重新启动模块
+// 安装因未提权失败后，用户可选择以管理员身份重新启动应用（提权后自动继续失败的安装任务）：
+//
+// - relaunch_elevated(): ShellExecuteExW 以 "runas" 启动当前可执行文件，系统弹出 UAC 提示
+// - 用户在 UAC 提示中拒绝时返回 [EASYPRINTER_CODE=ELEVATION_CANCELLED] 错误，当前进程继续运行
+// - 只负责启动新进程，当前进程的退出由调用方处理（等待 / 保存进行中的任务后退出）
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::shellapi::{ShellExecuteExW, SHELLEXECUTEINFOW};
+use winapi::um::winuser::SW_SHOWNORMAL;
+
+/// 用户在 UAC 提示中拒绝授权（ERROR_CANCELLED）
+const ERROR_CANCELLED: u32 = 1223;
+
+/// 用户拒绝提权时的错误码
+pub const ELEVATION_CANCELLED: &str = "ELEVATION_CANCELLED";
+
+fn to_wide(s: &OsStr) -> Vec<u16> {
+    s.encode_wide().chain(Some(0)).collect()
+}
+
+/// 拼接命令行参数（含空白或引号的参数加双引号）
+fn join_args(args: &[&str]) -> String {
+    args.iter()
+        .map(|arg| {
+            if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"') {
+                format!("\"{}\"", arg.replace('"', "\\\""))
+            } else {
+                arg.to_string()
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 以管理员身份启动当前可执行文件
+///
+/// # 参数
+/// - `args`: 新进程的命令行参数
+///
+/// # 返回
+/// - `Ok(())`: 用户已授权，新进程已启动
+/// - `Err(String)`: 用户拒绝授权（ELEVATION_CANCELLED）或启动失败
+pub fn relaunch_elevated(args: &[&str]) -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("获取可执行文件路径失败: {}", e))?;
+    let verb = to_wide(OsStr::new("runas"));
+    let file = to_wide(exe.as_os_str());
+    let parameters = to_wide(OsStr::new(&join_args(args)));
+
+    let mut info: SHELLEXECUTEINFOW = unsafe { std::mem::zeroed() };
+    info.cbSize = std::mem::size_of::<SHELLEXECUTEINFOW>() as u32;
+    info.lpVerb = verb.as_ptr();
+    info.lpFile = file.as_ptr();
+    info.lpParameters = parameters.as_ptr();
+    info.nShow = SW_SHOWNORMAL;
+
+    let ok = unsafe { ShellExecuteExW(&mut info) };
+    if ok != 0 {
+        super::log::write_log(&format!("[Elevation] relaunched elevated exe=\"{}\"", exe.display()));
+        return Ok(());
+    }
+    let error_code = unsafe { GetLastError() };
+    super::log::write_log(&format!("[Elevation] relaunch failed error_code={}", error_code));
+    if error_code == ERROR_CANCELLED {
+        return Err(format!(
+            "[EASYPRINTER_CODE={}] 已取消管理员权限授权，应用仍以当前权限运行",
+            ELEVATION_CANCELLED
+        ));
+    }
+    Err(format!("以管理员身份重新启动应用失败（错误码 {}）", error_code))
+}
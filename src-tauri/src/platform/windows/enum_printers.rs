@@ -217,7 +217,7 @@ pub fn enum_printers_w() -> Result<Vec<PrinterInfo>, String> {
 
 /// Fallback：使用 PowerShell Get-Printer 枚举打印机
 fn enum_printers_fallback_powershell() -> Result<Vec<PrinterInfo>, String> {
-    use crate::platform::windows::encoding::decode_windows_string;
+    use crate::utils::encoding::decode_windows_string;
     
     // 使用简单的 PowerShell 命令，直接获取 Name 列表（避免 JSON 解析复杂性）
     let fallback_script = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Get-Printer | Select-Object -ExpandProperty Name";
@@ -0,0 +1,264 @@
+// Windows 平台事件日志（审计）模块
+// 把可审计的操作写入 Windows 应用程序日志，供 SIEM / 事件转发采集：
+//
+// - 事件源 "ePrinty"：首次写事件时（通常为以管理员身份安装打印机时）注册到
+//   HKLM\SYSTEM\CurrentControlSet\Services\EventLog\Application\ePrinty，
+//   EventMessageFile 使用 .NET 自带的 EventLogMessages.dll（消息模板为 "%1"，事件查看器直接显示正文）
+// - 未提升的进程无法注册事件源，此时仍然写入（事件查看器会提示找不到描述，但正文字段完整）
+// - 事件正文为 key=value 行，事件 ID 固定（见 EVENT_* 常量），便于按 ID 建立采集规则
+// - 配置 eventLog.enabled=false 时不写入任何事件
+// - unregister_source(): 卸载清理时删除事件源注册表项（已写入的事件保留在应用程序日志中）
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+use std::ptr;
+use std::sync::OnceLock;
+
+use winapi::shared::minwindef::HKEY;
+use winapi::um::winbase::{DeregisterEventSource, RegisterEventSourceW, ReportEventW};
+use winapi::um::winnt::{
+    EVENTLOG_ERROR_TYPE, EVENTLOG_INFORMATION_TYPE, EVENTLOG_WARNING_TYPE, KEY_READ, KEY_SET_VALUE, REG_DWORD,
+    REG_EXPAND_SZ, REG_OPTION_NON_VOLATILE,
+};
+use winapi::um::winreg::{RegCloseKey, RegCreateKeyExW, RegDeleteKeyW, RegOpenKeyExW, RegSetValueExW, HKEY_LOCAL_MACHINE};
+
+/// 事件源名称
+pub const EVENT_SOURCE: &str = "ePrinty";
+
+/// 事件 ID：打印机安装成功
+pub const EVENT_INSTALL_SUCCEEDED: u32 = 1000;
+/// 事件 ID：打印机安装失败
+pub const EVENT_INSTALL_FAILED: u32 = 1001;
+/// 事件 ID：驱动包已添加到驱动库
+pub const EVENT_DRIVER_ADDED: u32 = 1002;
+/// 事件 ID：打印机已删除
+pub const EVENT_PRINTER_DELETED: u32 = 1003;
+
+const SOURCE_KEY: &str = r"SYSTEM\CurrentControlSet\Services\EventLog\Application\ePrinty";
+
+/// .NET Framework 自带的通用消息文件（所有事件 ID 的模板均为 "%1"）
+const EVENT_MESSAGE_FILE: &str = r"%SystemRoot%\Microsoft.NET\Framework\v4.0.30319\EventLogMessages.dll";
+
+/// 事件级别
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLevel {
+    Information,
+    Warning,
+    Error,
+}
+
+impl EventLevel {
+    fn event_type(self) -> u16 {
+        match self {
+            EventLevel::Information => EVENTLOG_INFORMATION_TYPE,
+            EventLevel::Warning => EVENTLOG_WARNING_TYPE,
+            EventLevel::Error => EVENTLOG_ERROR_TYPE,
+        }
+    }
+}
+
+/// 本进程内事件源注册的结论（只尝试一次）
+static SOURCE_REGISTERED: OnceLock<bool> = OnceLock::new();
+
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+}
+
+/// 配置是否允许写事件日志（读取失败时按默认开启处理）
+fn enabled() -> bool {
+    crate::load_local_config()
+        .ok()
+        .and_then(|(config, _)| config.event_log)
+        .and_then(|c| c.enabled)
+        .unwrap_or(true)
+}
+
+/// 事件源注册表项是否已存在
+fn source_key_exists() -> bool {
+    let key_path = to_wide(SOURCE_KEY);
+    unsafe {
+        let mut key: HKEY = ptr::null_mut();
+        if RegOpenKeyExW(HKEY_LOCAL_MACHINE, key_path.as_ptr(), 0, KEY_READ, &mut key) != 0 {
+            return false;
+        }
+        RegCloseKey(key);
+        true
+    }
+}
+
+/// 创建事件源注册表项（需要管理员权限）
+fn create_source_key() -> Result<(), String> {
+    let key_path = to_wide(SOURCE_KEY);
+    unsafe {
+        let mut key: HKEY = ptr::null_mut();
+        let status = RegCreateKeyExW(
+            HKEY_LOCAL_MACHINE,
+            key_path.as_ptr(),
+            0,
+            ptr::null_mut(),
+            REG_OPTION_NON_VOLATILE,
+            KEY_SET_VALUE,
+            ptr::null_mut(),
+            &mut key,
+            ptr::null_mut(),
+        );
+        if status != 0 {
+            return Err(format!("创建事件源注册表项失败 error_code={}", status));
+        }
+
+        let message_file = to_wide(EVENT_MESSAGE_FILE);
+        let message_file_name = to_wide("EventMessageFile");
+        let types_supported: u32 = (EVENTLOG_INFORMATION_TYPE | EVENTLOG_WARNING_TYPE | EVENTLOG_ERROR_TYPE) as u32;
+        let types_name = to_wide("TypesSupported");
+
+        let mut status = RegSetValueExW(
+            key,
+            message_file_name.as_ptr(),
+            0,
+            REG_EXPAND_SZ,
+            message_file.as_ptr() as *const u8,
+            (message_file.len() * 2) as u32,
+        );
+        if status == 0 {
+            status = RegSetValueExW(
+                key,
+                types_name.as_ptr(),
+                0,
+                REG_DWORD,
+                &types_supported as *const u32 as *const u8,
+                std::mem::size_of::<u32>() as u32,
+            );
+        }
+        RegCloseKey(key);
+        if status != 0 {
+            return Err(format!("写入事件源注册表值失败 error_code={}", status));
+        }
+    }
+    Ok(())
+}
+
+/// 删除事件源注册表项（卸载清理；需要管理员权限）
+///
+/// # 返回
+/// - `Ok(true)`: 已删除
+/// - `Ok(false)`: 事件源未注册
+/// - `Err(String)`: 删除失败
+pub fn unregister_source() -> Result<bool, String> {
+    if !source_key_exists() {
+        return Ok(false);
+    }
+    let subkey = to_wide(SOURCE_KEY);
+    let status = unsafe { RegDeleteKeyW(HKEY_LOCAL_MACHINE, subkey.as_ptr()) };
+    if status != 0 {
+        return Err(format!("删除事件源注册表项失败 error_code={}（需要管理员权限）", status));
+    }
+    Ok(true)
+}
+
+/// 确保事件源已注册（失败不影响写入，只记录日志）
+fn ensure_source_registered() -> bool {
+    *SOURCE_REGISTERED.get_or_init(|| {
+        if source_key_exists() {
+            return true;
+        }
+        match create_source_key() {
+            Ok(()) => {
+                super::log::write_log(&format!("[EventLog] 已注册事件源 \"{}\"", EVENT_SOURCE));
+                true
+            }
+            Err(e) => {
+                super::log::write_log(&format!(
+                    "[EventLog] 事件源 \"{}\" 未注册且无法注册（需要管理员权限）: {}",
+                    EVENT_SOURCE, e
+                ));
+                false
+            }
+        }
+    })
+}
+
+/// 组装事件正文：摘要行 + key=value 行（空值字段省略，值中的换行替换为空格）
+fn format_message(summary: &str, fields: &[(&str, String)]) -> String {
+    let mut lines = vec![summary.to_string()];
+    lines.extend(
+        fields
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .map(|(key, value)| format!("{}={}", key, value.replace(['\r', '\n'], " "))),
+    );
+    lines.join("\r\n")
+}
+
+/// 写入一条审计事件
+///
+/// # 参数
+/// - `event_id`: 事件 ID（EVENT_* 常量）
+/// - `level`: 事件级别
+/// - `summary`: 摘要行
+/// - `fields`: 结构化字段（按顺序写为 key=value 行）
+///
+/// # 返回
+/// - `Ok(true)`: 已写入
+/// - `Ok(false)`: 配置已关闭事件日志，未写入
+/// - `Err(String)`: 写入失败
+pub fn report_event(event_id: u32, level: EventLevel, summary: &str, fields: &[(&str, String)]) -> Result<bool, String> {
+    if !enabled() {
+        return Ok(false);
+    }
+    let registered = ensure_source_registered();
+
+    let source = to_wide(EVENT_SOURCE);
+    let message = to_wide(&format_message(summary, fields));
+    unsafe {
+        let handle = RegisterEventSourceW(ptr::null(), source.as_ptr());
+        if handle.is_null() {
+            return Err(format!(
+                "RegisterEventSourceW 失败 error_code={}",
+                winapi::um::errhandlingapi::GetLastError()
+            ));
+        }
+        let mut strings = [message.as_ptr()];
+        let ok = ReportEventW(
+            handle,
+            level.event_type(),
+            0,
+            event_id,
+            ptr::null_mut(),
+            1,
+            0,
+            strings.as_mut_ptr(),
+            ptr::null_mut(),
+        );
+        let error_code = winapi::um::errhandlingapi::GetLastError();
+        DeregisterEventSource(handle);
+        if ok == 0 {
+            return Err(format!("ReportEventW 失败 event_id={} error_code={}", event_id, error_code));
+        }
+    }
+    super::log::write_log(&format!(
+        "[EventLog] 已写入事件 event_id={} source_registered={}",
+        event_id, registered
+    ));
+    Ok(true)
+}
+
+/// 记录驱动包已添加（安装流程 stage 驱动成功后调用，失败只写日志）
+///
+/// # 参数
+/// - `printer_name`: 触发安装的打印机
+/// - `driver_name`: 目标驱动名称
+/// - `published_name`: 驱动库中的 oemXX.inf
+/// - `mechanism`: 注册方式（setupapi / pnputil）
+pub fn report_driver_added(printer_name: &str, driver_name: &str, published_name: &str, mechanism: &str) {
+    let fields = [
+        ("action", "driver_added".to_string()),
+        ("printer", printer_name.to_string()),
+        ("driver", driver_name.to_string()),
+        ("publishedName", published_name.to_string()),
+        ("mechanism", mechanism.to_string()),
+        ("user", std::env::var("USERNAME").unwrap_or_default()),
+    ];
+    if let Err(e) = report_event(EVENT_DRIVER_ADDED, EventLevel::Information, "ePrinty 已添加打印机驱动", &fields) {
+        super::log::write_log(&format!("[EventLog] {}", e));
+    }
+}
@@ -6,6 +6,8 @@ use serde_json;
 use std::fs;
 use std::io::Write;
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::sync::Mutex;
 use std::hash::{Hash, Hasher};
 use tauri::Manager;
 
@@ -13,6 +15,8 @@ use crate::install_event_emitter::emit_install_progress;
 
 #[cfg(windows)]
 use crate::platform::windows::driver_store_setupapi::stage_driver_and_get_published_name;
+use crate::platform::ps_script::PsScript;
+use crate::platform::spooler_errors::{classify_spooler_error, missing_print_cmdlet, SpoolerErrorInfo};
 
 
 // ============================================================================
@@ -27,7 +31,8 @@ const POWERSHELL_TIMEOUT_SECS: u64 = 120; // 2分钟，用于打印机安装相
 // 嵌入的 prnport.vbs 脚本内容（在编译时打包进 exe）
 // 注意：VBS 文件可能是 GBK/ANSI 编码，使用 include_bytes! 直接嵌入原始字节
 // 写入文件时保持原始编码，因为 VBScript 需要 ANSI/GBK 编码才能正确解析
-const PRNPORT_VBS_BYTES: &[u8] = include_bytes!("../../../scripts/prnport.vbs");
+// 完整性：build.rs 计算其 SHA256（EPRINTY_PRNPORT_VBS_SHA256），由 integrity 模块校验
+pub(crate) const PRNPORT_VBS_BYTES: &[u8] = include_bytes!("../../../scripts/prnport.vbs");
 
 // ============================================================================
 // 数据结构
@@ -46,6 +51,88 @@ pub struct InstallResult {
     pub job_id: String,
 }
 
+/// install_printer_windows 的返回：安装结果及其附带的尝试记录、安装后校验
+#[derive(Debug)]
+pub struct InstallOutcome {
+    pub result: InstallResult,
+    /// 每次尝试的记录（autoRetry 换策略重试时包含多条）
+    pub attempts: Vec<crate::platform::InstallAttempt>,
+    /// 安装后校验结果（安装失败 / dry run 时为 None）
+    pub verification: Option<crate::platform::InstallVerification>,
+}
+
+/// LPR 端口使用的队列名（多数网络打印机接受任意队列名）
+const LPR_QUEUE_NAME: &str = "lp";
+
+/// TCP/IP 端口协议（来自配置 portProtocol，未设置时为 RAW）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PortProtocol {
+    Raw,
+    Lpr,
+}
+
+impl PortProtocol {
+    fn from_config(value: Option<&str>) -> Self {
+        match value {
+            Some("lpr") => PortProtocol::Lpr,
+            _ => PortProtocol::Raw,
+        }
+    }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            PortProtocol::Raw => "raw",
+            PortProtocol::Lpr => "lpr",
+        }
+    }
+
+    fn default_port(self) -> u16 {
+        match self {
+            PortProtocol::Raw => 9100,
+            PortProtocol::Lpr => 515,
+        }
+    }
+
+    /// 另一种协议（端口预检时作为备选）
+    fn alternate(self) -> Self {
+        match self {
+            PortProtocol::Raw => PortProtocol::Lpr,
+            PortProtocol::Lpr => PortProtocol::Raw,
+        }
+    }
+}
+
+/// 安装范围（来自配置 installScope，未设置时为 auto）
+///
+/// - Machine：始终按本机安装（队列 / 端口 / 驱动对所有用户生效）
+/// - Session：共享打印机仅为当前用户建立连接（Add-Printer -ConnectionName，不安装驱动、无需管理员）
+/// - Auto：在 RDS 终端服务器上等同 Session，其余情况等同 Machine
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InstallScope {
+    Auto,
+    Machine,
+    Session,
+}
+
+impl InstallScope {
+    fn from_config(value: Option<&str>) -> Self {
+        match value {
+            Some("machine") => InstallScope::Machine,
+            Some("session") => InstallScope::Session,
+            _ => InstallScope::Auto,
+        }
+    }
+
+    /// 是否按当前用户会话安装
+    fn is_session(self, is_terminal_server: bool) -> bool {
+        match self {
+            InstallScope::Auto => is_terminal_server,
+            InstallScope::Machine => false,
+            InstallScope::Session => true,
+        }
+    }
+}
+
 // ============================================================================
 // Package 安装分支
 // ============================================================================
@@ -69,7 +156,7 @@ fn register_printer_driver(
     published_inf_path: &str,
     dry_run: bool,
 ) -> Result<(), String> {
-    use crate::platform::windows::encoding::decode_windows_string;
+    use crate::utils::encoding::decode_windows_string;
     
     eprintln!("[RegisterPrinterDriver] start name=\"{}\" inf=\"{}\" dryRun={}", 
         driver_name, published_inf_path, dry_run);
@@ -80,34 +167,24 @@ fn register_printer_driver(
         return Ok(());
     }
     
-    // 先验证驱动是否已存在（幂等检查）
-    let verify_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Get-PrinterDriver -Name '{}' -ErrorAction SilentlyContinue | Select-Object -First 1 -ExpandProperty Name",
-        driver_name.replace("'", "''")
-    );
-    
-    match super::ps::run_powershell(&verify_script) {
-        Ok(output) => {
-            let stdout = decode_windows_string(&output.stdout);
-            let trimmed_stdout = stdout.trim();
-            
-            // 如果驱动已存在，视为成功（幂等）
-            if !trimmed_stdout.is_empty() && trimmed_stdout == driver_name {
-                eprintln!("[RegisterPrinterDriver] success (driver already exists)");
-                return Ok(());
-            }
+    // 先验证驱动是否已存在（幂等检查，使用会话缓存的驱动清单）
+    match super::session_cache::find_installed_driver(driver_name) {
+        Ok(Some(_)) => {
+            eprintln!("[RegisterPrinterDriver] success (driver already exists)");
+            return Ok(());
         }
-        Err(_) => {
-            // 验证失败，继续尝试注册
+        Ok(None) | Err(_) => {
+            // 未找到或查询失败，继续尝试注册
         }
     }
     
     // 执行 Add-PrinterDriver
-    let add_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-PrinterDriver -Name '{}' -InfPath '{}' -ErrorAction Stop",
-        driver_name.replace("'", "''"),
-        published_inf_path.replace("'", "''")
-    );
+    let add_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-PrinterDriver -Name $DriverName -InfPath $InfPath -ErrorAction Stop",
+    )
+    .param("DriverName", driver_name)
+    .param("InfPath", published_inf_path)
+    .build();
     
     match super::ps::run_powershell(&add_script) {
         Ok(output) => {
@@ -115,17 +192,14 @@ fn register_printer_driver(
             let stderr = decode_windows_string(&output.stderr);
             let exit_code = output.status.code();
             
-            // 再次验证驱动是否已注册
-            match super::ps::run_powershell(&verify_script) {
-                Ok(verify_output) => {
-                    let verify_stdout = decode_windows_string(&verify_output.stdout);
-                    let trimmed_verify = verify_stdout.trim();
-                    
-                    if !trimmed_verify.is_empty() && trimmed_verify == driver_name {
-                        eprintln!("[RegisterPrinterDriver] success");
-                        return Ok(());
-                    }
+            // 驱动清单已变化，丢弃缓存后再次验证驱动是否已注册
+            super::session_cache::invalidate_driver_inventory();
+            match super::session_cache::find_installed_driver(driver_name) {
+                Ok(Some(_)) => {
+                    eprintln!("[RegisterPrinterDriver] success");
+                    return Ok(());
                 }
+                Ok(None) => {}
                 Err(e) => {
                     eprintln!("[RegisterPrinterDriver] failed (verification failed): {}", e);
                 }
@@ -196,30 +270,40 @@ async fn install_printer_package_branch(
     _model: Option<String>,
     dry_run: bool,
     driver_names: Option<Vec<String>>, // 使用传入的 driver_names（来自 effective_*）
+    port_protocol: PortProtocol, // 端口协议（来自打印机 / 区域配置）
 ) -> Result<InstallResult, String> {
     eprintln!("[PackageBranch] start printer=\"{}\" dryRun={}", name, dry_run);
     
     // ============================================================================
-    // Preflight Gate: 检查管理员权限
+    // Preflight Gate: 探测导入驱动包实际需要的权限（而非笼统的是否管理员）
     // ============================================================================
-    let is_admin = is_running_as_admin();
-    eprintln!("[Preflight] is_admin={} printer=\"{}\" path=\"{}\"", is_admin, name, path);
+    let permissions = super::permission_probe::probe();
+    let is_admin = permissions.is_elevated;
+    let stage_permission = permissions.operation(super::permission_probe::OP_STAGE_DRIVER);
+    eprintln!("[Preflight] is_admin={} stage_driver={:?} printer=\"{}\" path=\"{}\"", is_admin, stage_permission, name, path);
     
-    if !is_admin {
+    if let Some(permission) = stage_permission.filter(|p| !p.allowed) {
         let inf_path_str = inf_abs_path.as_ref()
             .map(|p| p.display().to_string())
             .unwrap_or_else(|| "N/A".to_string());
         
         let evidence = format!(
-            "is_admin=false printer_name=\"{}\" path=\"{}\" inf_abs_path=\"{}\"",
+            "is_admin={} load_driver_privilege={} spooler_pipe={} server_admin={} drivers_root_writable={} missing={:?} printer_name=\"{}\" path=\"{}\" inf_abs_path=\"{}\"",
+            is_admin,
+            permissions.load_driver_privilege,
+            permissions.spooler_pipe_accessible,
+            permissions.print_server_admin,
+            permissions.drivers_root_writable,
+            permission.missing,
             name, path, inf_path_str
         );
         
-        eprintln!("[Preflight] gate_failed step=check_admin_privilege evidence=\"{}\"", evidence);
+        eprintln!("[Preflight] gate_failed step=check_stage_driver_permission evidence=\"{}\"", evidence);
+        crate::install_event_emitter::record_failed_step(job_id, "driver.stageDriver");
         
         let error = InstallError::PermissionDenied {
             step: "install_printer_package",
-            reason: "需要管理员权限安装打印机驱动（pnputil /add-driver）".to_string(),
+            reason: format!("当前权限不足以安装打印机驱动（pnputil /add-driver）：{}", permission.missing.join("；")),
             evidence,
         };
         
@@ -234,7 +318,7 @@ async fn install_printer_package_branch(
         });
     }
     
-    eprintln!("[Preflight] gate_passed step=check_admin_privilege is_admin=true");
+    eprintln!("[Preflight] gate_passed step=check_stage_driver_permission is_admin={}", is_admin);
     
     let target_driver_name = match &driver_names {
         Some(names) if !names.is_empty() => {
@@ -288,13 +372,9 @@ async fn install_printer_package_branch(
     eprintln!("[PackageBranch] dryRun=false，执行驱动包注册（SetupAPI）");
     eprintln!("[PackageBranch] inf_path=\"{}\"", inf_path.display());
     
-    match stage_driver_with_setupapi_fallback(&inf_path) {
-        Ok((published_name, published_inf_path, used_fallback, evidence)) => {
-            if used_fallback {
-                eprintln!("[PackageBranch] 驱动包注册成功（已回退到 pnputil）published_name=\"{}\" is_admin={}", published_name, is_admin);
-            } else {
-                eprintln!("[PackageBranch] 驱动包注册成功（SetupAPI）published_name=\"{}\" is_admin={}", published_name, is_admin);
-            }
+    match stage_driver_with_setupapi_fallback(&inf_path, std::slice::from_ref(&target_driver_name)) {
+        Ok((published_name, published_inf_path, mechanism, evidence)) => {
+            eprintln!("[PackageBranch] 驱动包注册成功 mechanism={} published_name=\"{}\" is_admin={}", mechanism.as_str(), published_name, is_admin);
             
             // 发送 StageDriver 成功事件
             emit_progress_event(
@@ -303,11 +383,7 @@ async fn install_printer_package_branch(
                 printer_name,
                 "driver.stageDriver",
                 "success",
-                if used_fallback {
-                    "驱动包注册成功（已回退到 pnputil）".to_string()
-                } else {
-                    "驱动包注册成功（SetupAPI）".to_string()
-                },
+                mechanism.success_message(),
                 None,
                 None,
                 Some("stageDriver".to_string()),
@@ -315,6 +391,7 @@ async fn install_printer_package_branch(
             
             eprintln!("[PackageBranch] published_name=\"{}\" published_inf_path=\"{}\" evidence=\"{}\"", 
                 published_name, published_inf_path, evidence);
+            super::event_log::report_driver_added(printer_name, &target_driver_name, &published_name, mechanism.as_str());
             
             // 保存 evidence 以便后续错误处理使用
             let stage_evidence = evidence.clone();
@@ -389,7 +466,10 @@ async fn install_printer_package_branch(
                             let is_legacy = windows_build > 0 && windows_build < 10240;
                             
                             // 确保端口存在
-                            let port_name = match ensure_printer_port(&host, 9100, is_legacy, job_id) {
+                            let report_port_phase = |phase: PortPhase, attempt: Option<(u32, u32)>| {
+                                emit_port_phase_event(app, job_id, printer_name, phase, &host, attempt);
+                            };
+                            let port_name = match ensure_printer_port(&host, port_protocol, is_legacy, job_id, &report_port_phase) {
                                 Ok(port) => {
                                     eprintln!("[PackageBranch] EnsurePrinterPort step=success port_name=\"{}\"", port);
                                     
@@ -533,9 +613,12 @@ async fn install_printer_package_branch(
                         TargetType::SharedConnection { path: conn_path } => {
                             // 共享连接：直接使用 Add-Printer -ConnectionName
                             // 防御性检查：确保 connection_name 格式正确（至少包含两段）
-                            let parts: Vec<&str> = conn_path.split('\\').filter(|s| !s.is_empty()).collect();
-                            if parts.len() < 2 {
-                                let evidence = format!("InvalidSharedConnectionName connection_name=\"{}\" parts_count={}", conn_path, parts.len());
+                            let is_share = matches!(
+                                crate::platform::printer_address::PrinterAddress::parse(&conn_path),
+                                Ok(crate::platform::printer_address::PrinterAddress::SmbShare { .. })
+                            );
+                            if !is_share {
+                                let evidence = format!("InvalidSharedConnectionName connection_name=\"{}\"", conn_path);
                                 eprintln!("[PackageBranch] EnsurePrinterQueue step=failed evidence=\"{}\"", evidence);
                                 return Ok(InstallResult {
                                     success: false,
@@ -555,13 +638,13 @@ async fn install_printer_package_branch(
                                 conn_path, target_driver_name);
                             
                             // 修复：使用 Where-Object 精确过滤，避免 Get-Printer -Name 的通配符匹配导致误判
-                            let escaped_conn_path = conn_path.replace("'", "''");
-                            let check_shared_script = format!(
-                                "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $q = '{}'; $printer = Get-Printer -Name $q -ErrorAction SilentlyContinue | Where-Object {{ $_.Name -eq $q }} | Select-Object -ExpandProperty Name",
-                                escaped_conn_path
-                            );
+                            let check_shared_script = PsScript::new(
+                                "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $printer = Get-Printer -Name $Connection -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $Connection } | Select-Object -ExpandProperty Name",
+                            )
+                            .param("Connection", &conn_path)
+                            .build();
                             
-                            let queue_exists = match super::ps::run_powershell(&check_shared_script) {
+                            let queue_exists = match super::ps::run_powershell_async(check_shared_script).await {
                                 Ok(output) => {
                                     let stdout = decode_windows_string(&output.stdout);
                                     // 二次确认：验证返回的名称是否完全等于 conn_path
@@ -591,12 +674,30 @@ async fn install_printer_package_branch(
                                 })
                             } else {
                                 eprintln!("[PackageBranch] EnsurePrinterQueue step=create_shared inputs=connection_name=\"{}\"", conn_path);
-                                let add_shared_script = format!(
-                                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-Printer -ConnectionName '{}' -ErrorAction Stop",
-                                    conn_path.replace("'", "''")
-                                );
+                                // 已保存服务器凭据时先建立 SMB 会话（持有到 Add-Printer 完成后自动断开）
+                                let _smb_session = match super::smb_credentials::connect_for_path(&conn_path) {
+                                    Ok(session) => session,
+                                    Err(e) => {
+                                        let evidence = format!("smb_connect_failed error=\"{}\" connection_name=\"{}\"", e, conn_path);
+                                        eprintln!("[PackageBranch] EnsurePrinterQueue step=failed evidence=\"{}\"", evidence);
+                                        return Ok(InstallResult {
+                                            success: false,
+                                            message: format!("连接共享打印服务器失败: {}\n\n连接名称: {}\n\nEvidence: {}", e, conn_path, evidence),
+                                            method: Some("Package".to_string()),
+                                            stdout: Some(stage_evidence.clone()),
+                                            stderr: Some(evidence),
+                                            effective_dry_run: dry_run,
+                                            job_id: job_id.to_string(),
+                                        });
+                                    }
+                                };
+                                let add_shared_script = PsScript::new(
+                                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-Printer -ConnectionName $Connection -ErrorAction Stop",
+                                )
+                                .param("Connection", &conn_path)
+                                .build();
                                 
-                                match super::ps::run_powershell(&add_shared_script) {
+                                match super::ps::run_powershell_async(add_shared_script).await {
                                     Ok(output) => {
                                         let stdout = decode_windows_string(&output.stdout);
                                         let stderr = decode_windows_string(&output.stderr);
@@ -716,6 +817,7 @@ async fn install_printer_package_branch(
                 );
                 
                 eprintln!("[PackageBranch] failed step=permission_denied evidence=\"{}\"", evidence);
+                crate::install_event_emitter::record_failed_step(job_id, "driver.stageDriver");
                 
                 let error = InstallError::PermissionDenied {
                     step: "pnputil_stage",
@@ -878,10 +980,13 @@ enum InstallError {
     /// 打印机安装失败（现代方式）
     PrinterInstallFailedModern {
         stderr: String,
+        /// 按输出中的 HRESULT / Win32 错误码识别出的已知错误
+        classified: Option<&'static SpoolerErrorInfo>,
     },
     /// 打印机安装失败（VBS 方式）
     PrinterInstallFailedVbs {
         stderr: String,
+        classified: Option<&'static SpoolerErrorInfo>,
     },
     /// INF 驱动安装失败
     InfInstallFailed {
@@ -892,10 +997,8 @@ enum InstallError {
     },
     /// PrintUIEntry /if 安装失败
     PrintUIInfInstallFailed {
-        printer_name: String,
-        inf_path: String,
-        port_name: String,
-        model: String,
+        /// 装箱的命令参数（保持 InstallError 体积，避免 Result 的 Err 过大）
+        command: Box<PrintUiInstallCommand>,
         exit_code: Option<i32>,
         stdout: String,
         stderr: String,
@@ -906,6 +1009,36 @@ enum InstallError {
         reason: String,
         evidence: String,
     },
+    /// 用户取消安装
+    Cancelled {
+        step: &'static str,
+    },
+    /// 安装成功但打印处理器 / 数据类型设置失败
+    PrintProcessorFailed {
+        reason: String,
+    },
+    /// 找不到打印管理 cmdlet（PrintManagement 模块不可用，或 32 位 PowerShell 看不到 64 位模块）
+    PrintCmdletsUnavailable {
+        cmdlet: &'static str,
+        stderr: String,
+    },
+    /// 驱动不支持当前 Windows 版本（driverCatalog[].compatibility）
+    UnsupportedOs {
+        reason: String,
+    },
+    /// 所选驱动与配置的 driverPin 不一致
+    DriverMismatch {
+        reason: String,
+    },
+}
+
+/// PrintUIEntry /if 的命令参数（错误信息使用）
+#[derive(Debug)]
+struct PrintUiInstallCommand {
+    printer_name: String,
+    inf_path: String,
+    port_name: String,
+    model: String,
 }
 
 impl InstallError {
@@ -922,11 +1055,18 @@ impl InstallError {
             InstallError::PortAddFailedModern { .. } => "WIN_PORT_FAILED",
             InstallError::PortAddFailedVbs { .. } => "WIN_PORT_FAILED",
             InstallError::VbsScriptFailed { .. } => "WIN_VBS_FAILED",
-            InstallError::PrinterInstallFailedModern { .. } => "WIN_PRINTER_INSTALL_FAILED",
-            InstallError::PrinterInstallFailedVbs { .. } => "WIN_PRINTER_INSTALL_FAILED",
+            InstallError::PrinterInstallFailedModern { classified, .. }
+            | InstallError::PrinterInstallFailedVbs { classified, .. } => {
+                classified.map(|info| info.code).unwrap_or("WIN_PRINTER_INSTALL_FAILED")
+            }
             InstallError::InfInstallFailed { .. } => "WIN_INF_INSTALL_FAILED",
             InstallError::PrintUIInfInstallFailed { .. } => "WIN_PRINTUI_INF_INSTALL_FAILED",
             InstallError::PermissionDenied { .. } => "WIN_PERMISSION_DENIED",
+            InstallError::Cancelled { .. } => "WIN_INSTALL_CANCELLED",
+            InstallError::PrintProcessorFailed { .. } => "WIN_PRINT_PROCESSOR_FAILED",
+            InstallError::PrintCmdletsUnavailable { .. } => "WIN_PRINT_CMDLETS_UNAVAILABLE",
+            InstallError::UnsupportedOs { .. } => "UNSUPPORTED_OS",
+            InstallError::DriverMismatch { .. } => super::driver_pin::DRIVER_MISMATCH,
         }
     }
 
@@ -1007,10 +1147,15 @@ impl InstallError {
             InstallError::VbsScriptFailed { error, script_info } => {
                 format!("执行 prnport.vbs 脚本失败: {} | {}", error, script_info)
             }
-            InstallError::PrinterInstallFailedModern { stderr } => {
-                format!("端口添加成功，但打印机安装失败。错误信息: {}。请确保系统中已安装打印机驱动，或联系管理员安装驱动。", stderr)
+            InstallError::PrinterInstallFailedModern { stderr, classified: Some(info) }
+            | InstallError::PrinterInstallFailedVbs { stderr, classified: Some(info) } => {
+                format!(
+                    "端口添加成功，但打印机安装失败：{}（{}，错误码 0x{:08X}）。建议：{}（{}）。错误信息: {}",
+                    info.message_zh, info.message_en, info.win32_code, info.remediation_zh, info.remediation_en, stderr
+                )
             }
-            InstallError::PrinterInstallFailedVbs { stderr } => {
+            InstallError::PrinterInstallFailedModern { stderr, classified: None }
+            | InstallError::PrinterInstallFailedVbs { stderr, classified: None } => {
                 format!("端口添加成功，但打印机安装失败。错误信息: {}。请确保系统中已安装打印机驱动，或联系管理员安装驱动。", stderr)
             }
             InstallError::InfInstallFailed { inf_path, exit_code, stdout, stderr } => {
@@ -1021,17 +1166,36 @@ impl InstallError {
                 format!("从配置文件安装 INF 驱动失败。文件: {}。{}。标准输出: {}。错误输出: {}", 
                     inf_path, exit_msg, stdout, stderr)
             }
-            InstallError::PrintUIInfInstallFailed { printer_name, inf_path, port_name, model, exit_code, stdout, stderr } => {
+            InstallError::PrintUIInfInstallFailed { command, exit_code, stdout, stderr } => {
                 let exit_msg = match exit_code {
                     Some(code) => format!("退出代码: {}", code),
                     None => "无法获取退出代码".to_string(),
                 };
                 format!("使用 PrintUIEntry 安装打印机失败。打印机: {}，驱动: {}，端口: {}，型号: {}。{}。标准输出: {}。错误输出: {}", 
-                    printer_name, inf_path, port_name, model, exit_msg, stdout, stderr)
+                    command.printer_name, command.inf_path, command.port_name, command.model, exit_msg, stdout, stderr)
             }
             InstallError::PermissionDenied { step, reason, evidence } => {
                 format!("需要管理员权限才能执行 {}。{}\n\n诊断信息: {}", step, reason, evidence)
             }
+            InstallError::Cancelled { step } => {
+                format!("安装已取消（{}）", step)
+            }
+            InstallError::PrintProcessorFailed { reason } => {
+                format!("打印机已安装，但{}", reason)
+            }
+            InstallError::PrintCmdletsUnavailable { cmdlet, stderr } => {
+                let program = super::ps::powershell_program();
+                let hint = if program.wow64 && !program.sysnative {
+                    "当前为 32 位进程，且无法访问 64 位 PowerShell（%SystemRoot%\\Sysnative），请改用 64 位版本的 ePrinty"
+                } else {
+                    "系统未提供 PrintManagement 模块：需要 Windows 8 / Server 2012 及以上版本；Server Core 或精简版系统请先启用\"打印和文件服务\"功能"
+                };
+                format!("系统中找不到 {} 命令（打印管理 cmdlet 不可用）。{}。错误信息: {}", cmdlet, hint, stderr)
+            }
+            InstallError::UnsupportedOs { reason } => {
+                format!("驱动不支持当前 Windows 版本：{}。请联系管理员提供适用于本机系统的驱动", reason)
+            }
+            InstallError::DriverMismatch { reason } => reason.clone(),
         }
     }
 }
@@ -1040,59 +1204,16 @@ impl InstallError {
 // 辅助函数
 // ============================================================================
 
-// 使用 encoding 模块的 decode_windows_string
-use crate::platform::windows::encoding::decode_windows_string;
+// 使用 utils::encoding 模块的 decode_windows_string
+use crate::utils::encoding::decode_windows_string;
 
 // 注意：以下辅助函数在当前实现中未使用，但保留以备将来需要时使用
 // format_command_error 和 run_cscript_hidden 函数已移除，因为当前实现直接使用 Command 和 decode_windows_string
 
 /// 检测 Windows 版本（返回构建号，用于判断是否支持 Add-PrinterPort）
-/// 注意：GetVersionExW API 在 Windows 10+ 可能返回兼容版本信息（如 9200），不准确
-/// 因此优先使用 PowerShell 获取真实版本信息
+/// 会话内只检测一次，见 session_cache::windows_build_number
 fn get_windows_build_number() -> Result<u32, String> {
-    // 优先使用 PowerShell 检测真实构建号（更可靠）
-    // 使用 Get-CimInstance 获取真实的操作系统版本信息
-    match super::ps::run_powershell("(Get-CimInstance Win32_OperatingSystem).BuildNumber") {
-        Ok(output) => {
-            let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            
-            if !version_str.is_empty() {
-                if let Ok(build_number) = version_str.parse::<u32>() {
-                    return Ok(build_number);
-                }
-            }
-        }
-        Err(_) => {}
-    }
-    
-    // 备用方案：使用 Environment.OSVersion
-    match super::ps::run_powershell("[System.Environment]::OSVersion.Version.Build") {
-        Ok(output) => {
-            let version_str = String::from_utf8_lossy(&output.stdout).trim().to_string();
-            
-            if !version_str.is_empty() {
-                if let Ok(build_number) = version_str.parse::<u32>() {
-                    return Ok(build_number);
-                }
-            }
-        }
-        Err(_) => {}
-    }
-    
-    // 最后的备用方案：使用 GetVersionExW（但可能不准确）
-    use winapi::um::sysinfoapi::GetVersionExW;
-    use winapi::um::winnt::OSVERSIONINFOW;
-    
-    unsafe {
-        let mut os_info: OSVERSIONINFOW = std::mem::zeroed();
-        os_info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOW>() as u32;
-        
-        if GetVersionExW(&mut os_info) != 0 {
-            Ok(os_info.dwBuildNumber)
-        } else {
-            Err("无法检测 Windows 构建号".to_string())
-        }
-    }
+    super::session_cache::windows_build_number()
 }
 
 // ============================================================================
@@ -1112,6 +1233,21 @@ enum PortAddOutcome {
 // 私有辅助函数
 // ============================================================================
 
+/// driverPin 校验失败时的安装结果（错误文本已带 DRIVER_MISMATCH 错误码）
+fn driver_mismatch_result(error: &str, job_id: &str) -> InstallResult {
+    let prefix = format!("[EASYPRINTER_CODE={}] ", super::driver_pin::DRIVER_MISMATCH);
+    let error = InstallError::DriverMismatch { reason: error.trim_start_matches(prefix.as_str()).to_string() };
+    InstallResult {
+        success: false,
+        message: error.to_user_message(),
+        method: None,
+        stdout: None,
+        stderr: error.format_stderr_with_code(None),
+        effective_dry_run: false,
+        job_id: job_id.to_string(),
+    }
+}
+
 /// 检查已存在的打印机（如果存在）
 /// 注意：不再执行删除操作，仅用于检查存在性
 /// 如果打印机已存在，安装过程可能会失败，需要用户手动删除
@@ -1226,9 +1362,23 @@ fn install_printer_with_printui(
             
             eprintln!("[DEBUG] PrintUIEntry exit code: {:?}, stdout length: {}, stderr length: {}", 
                 exit_code, stdout.len(), stderr.len());
+            // PrintUIEntry /if 会同时安装驱动
+            super::session_cache::invalidate_driver_inventory();
             
             if output.status.success() {
                 eprintln!("[DEBUG] PrintUIEntry 执行成功，打印机已安装");
+                // PrintUIEntry 导入驱动与创建队列是一步完成的，只能在安装后按 /m 的驱动名称校验 driverPin；
+                // 不一致时删除刚创建的队列，不留下绑定错误驱动包的打印机
+                if let Err(e) = super::driver_pin::check_current_job(model) {
+                    if let Err(del_err) = super::delete::delete_printer_windows(printer_name, false, false) {
+                        super::log::write_log(&format!(
+                            "[DriverPin] PRINTUI_ROLLBACK_FAIL printer=\"{}\" error=\"{}\"",
+                            printer_name, del_err
+                        ));
+                    }
+                    let prefix = format!("[EASYPRINTER_CODE={}] ", super::driver_pin::DRIVER_MISMATCH);
+                    return Err(InstallError::DriverMismatch { reason: e.trim_start_matches(prefix.as_str()).to_string() });
+                }
                 Ok(InstallResult {
                     success: true,
                     message: format!("打印机 {} 安装成功（使用 PrintUIEntry）", printer_name),
@@ -1241,10 +1391,12 @@ fn install_printer_with_printui(
             } else {
                 eprintln!("[ERROR] PrintUIEntry 执行失败，exit code: {:?}", exit_code);
                 let error = InstallError::PrintUIInfInstallFailed {
-                    printer_name: printer_name.to_string(),
-                    inf_path: inf_path_final.clone(),
-                    port_name: port_name.to_string(),
-                    model: model.to_string(),
+                    command: Box::new(PrintUiInstallCommand {
+                        printer_name: printer_name.to_string(),
+                        inf_path: inf_path_final.clone(),
+                        port_name: port_name.to_string(),
+                        model: model.to_string(),
+                    }),
                     exit_code,
                     stdout,
                     stderr,
@@ -1327,79 +1479,135 @@ fn has_failure_keywords(output: &str) -> bool {
         || output_lower.contains("no such file")
 }
 
-/// 检查当前进程是否以管理员权限运行
-/// 
-/// 使用 CheckTokenMembership（advapi32）检查管理员权限
-/// 这是更可靠的方法，比 IsUserAnAdmin 更准确
-#[cfg(windows)]
-fn is_running_as_admin() -> bool {
-    use winapi::um::winnt::TOKEN_ELEVATION;
-    use winapi::um::winnt::HANDLE;
-    use winapi::um::processthreadsapi::GetCurrentProcess;
-    use winapi::um::processthreadsapi::OpenProcessToken;
-    use winapi::um::winnt::TOKEN_QUERY;
-    use winapi::um::handleapi::CloseHandle;
-    use std::mem;
-    use std::ptr;
-    
-    unsafe {
-        let mut token: HANDLE = ptr::null_mut();
-        let process = GetCurrentProcess();
-        
-        if OpenProcessToken(process, TOKEN_QUERY, &mut token) == 0 {
-            return false;
+/// 驱动包导入方式（写入 evidence / 进度消息，便于排查受限环境）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StageMechanism {
+    SetupApi,
+    Pnputil,
+    /// pnputil 被 AppLocker / 组策略阻止时的回退
+    AddPrinterDriver,
+}
+
+impl StageMechanism {
+    fn as_str(&self) -> &'static str {
+        match self {
+            StageMechanism::SetupApi => "SetupCopyOEMInfW",
+            StageMechanism::Pnputil => "pnputil",
+            StageMechanism::AddPrinterDriver => "Add-PrinterDriver",
         }
-        
-        let mut elevation: TOKEN_ELEVATION = mem::zeroed();
-        let mut ret_size: u32 = 0;
-        
-        use winapi::um::securitybaseapi::GetTokenInformation;
-        use winapi::um::winnt::TokenElevation;
-        
-        let result = GetTokenInformation(
-            token,
-            TokenElevation,
-            &mut elevation as *mut _ as *mut _,
-            mem::size_of::<TOKEN_ELEVATION>() as u32,
-            &mut ret_size,
-        );
-        
-        CloseHandle(token);
-        
-        if result != 0 {
-            elevation.TokenIsElevated != 0
-        } else {
-            false
+    }
+
+    /// stageDriver 成功事件的提示文案
+    fn success_message(&self) -> String {
+        match self {
+            StageMechanism::SetupApi => "驱动包注册成功（SetupAPI）".to_string(),
+            StageMechanism::Pnputil => "驱动包注册成功（已回退到 pnputil）".to_string(),
+            StageMechanism::AddPrinterDriver => "驱动包注册成功（pnputil 被组策略阻止，已改用 Add-PrinterDriver）".to_string(),
         }
     }
 }
 
-#[cfg(not(windows))]
-fn is_running_as_admin() -> bool {
-    false // 非 Windows 平台不需要权限提升
+/// 检查 pnputil 是否被 AppLocker / 软件限制策略阻止
+///
+/// 被阻止时进程无法启动（ERROR_ACCESS_DISABLED_BY_POLICY = 1260 / 0x800704EC），
+/// 或输出"此程序被组策略阻止"
+fn is_blocked_by_policy(output: &str) -> bool {
+    let output_lower = output.to_lowercase();
+    output_lower.contains("blocked by group policy")
+        || output_lower.contains("组策略阻止")
+        || output_lower.contains("os error 1260")
+        || output_lower.contains("0x800704ec")
+        || output_lower.contains("applocker")
+}
+
+/// 在 %SystemRoot%\INF 中查找与源 INF 内容一致的 oemXXX.inf（Add-PrinterDriver 不返回 published name）
+pub(super) fn find_published_inf_copy(inf_path: &std::path::Path) -> Option<std::path::PathBuf> {
+    let source = fs::read(inf_path).ok()?;
+    let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+    let entries = fs::read_dir(std::path::Path::new(&system_root).join("INF")).ok()?;
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| e.path())
+        .filter(|p| {
+            p.file_name()
+                .and_then(|n| n.to_str())
+                .map(|n| {
+                    let n = n.to_lowercase();
+                    n.starts_with("oem") && n.ends_with(".inf")
+                })
+                .unwrap_or(false)
+        })
+        .filter(|p| fs::metadata(p).map(|m| m.len() == source.len() as u64).unwrap_or(false))
+        .find(|p| fs::read(p).map(|bytes| bytes == source).unwrap_or(false))
+}
+
+/// pnputil 被阻止时改用 Add-PrinterDriver -InfPath 导入并注册驱动
+///
+/// # 参数
+/// - `inf_path`: 源 INF 文件路径
+/// - `driver_names`: 驱动名称候选（Add-PrinterDriver 必须指定名称，依次尝试）
+///
+/// # 返回
+/// - `Ok((published_name, published_inf_path))`: 导入成功；找不到 oem 副本时返回源 INF 路径
+/// - `Err(String)`: 所有候选名称均注册失败
+fn stage_driver_via_add_printer_driver(
+    inf_path: &std::path::Path,
+    driver_names: &[String],
+) -> Result<(String, String), String> {
+    let inf_path_str = inf_path.to_string_lossy().to_string();
+    let mut errors = Vec::new();
+    for driver_name in driver_names.iter().map(|n| n.trim()).filter(|n| !n.is_empty()) {
+        match register_printer_driver(driver_name, &inf_path_str, false) {
+            Ok(()) => {
+                let published = find_published_inf_copy(inf_path);
+                let published_name = published
+                    .as_ref()
+                    .and_then(|p| p.file_name())
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| "unknown".to_string());
+                let published_inf_path = published
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_else(|| inf_path_str.clone());
+                eprintln!(
+                    "[StageDriver] fallback=Add-PrinterDriver success driver_name=\"{}\" published_name=\"{}\"",
+                    driver_name, published_name
+                );
+                return Ok((published_name, published_inf_path));
+            }
+            Err(e) => errors.push(format!("{}: {}", driver_name, e)),
+        }
+    }
+    if errors.is_empty() {
+        return Err("Add-PrinterDriver 回退失败：未配置驱动名称".to_string());
+    }
+    Err(format!("Add-PrinterDriver 回退失败：\n{}", errors.join("\n")))
 }
 
 /// 使用 SetupAPI 或 fallback 到 pnputil 来 stage 驱动包并获取 published name
 /// 
 /// # 参数
 /// - `inf_path`: INF 文件的完整路径
+/// - `driver_names`: 驱动名称候选（pnputil 被组策略阻止时用于 Add-PrinterDriver 回退）
 /// 
 /// # 返回
-/// - `Ok((published_name, published_inf_path, used_fallback, evidence))`: 成功
+/// - `Ok((published_name, published_inf_path, mechanism, evidence))`: 成功
 /// - `Err(String)`: 失败
 #[cfg(windows)]
-fn stage_driver_with_setupapi_fallback(inf_path: &std::path::Path) -> Result<(String, String, bool, String), String> {
+fn stage_driver_with_setupapi_fallback(
+    inf_path: &std::path::Path,
+    driver_names: &[String],
+) -> Result<(String, String, StageMechanism, String), String> {
     // 首先尝试使用 SetupAPI
     match stage_driver_and_get_published_name(inf_path) {
         Ok(result) => {
             eprintln!("[StageDriver] method=SetupCopyOEMInfW success published_name=\"{}\" published_inf_path=\"{}\"", 
                 result.published_name, result.published_inf_path.display());
-            return Ok((
+            Ok((
                 result.published_name,
                 result.published_inf_path.to_string_lossy().to_string(),
-                false,
+                StageMechanism::SetupApi,
                 result.evidence,
-            ));
+            ))
         }
         Err(setupapi_error) => {
             eprintln!("[StageDriver] method=SetupCopyOEMInfW failed error=\"{}\" win32_error=0x{:08X} evidence=\"{}\"", 
@@ -1422,18 +1630,39 @@ fn stage_driver_with_setupapi_fallback(inf_path: &std::path::Path) -> Result<(St
                                 published_inf_path
                             );
                             eprintln!("[StageDriver] fallback=pnputil success published_name=\"{}\"", published_name);
-                            return Ok((published_name, published_inf_path, true, evidence));
+                            Ok((published_name, published_inf_path, StageMechanism::Pnputil, evidence))
                         } else {
-                            return Err(format!(
+                            Err(format!(
                                 "pnputil fallback 成功但 published INF 文件不存在: {}\n\nSetupAPI 错误: {}\n\npnputil 输出:\n{}",
                                 published_inf_path, setupapi_error, pnputil_result.output_text
-                            ));
+                            ))
                         }
                     } else {
-                        return Err(format!(
+                        Err(format!(
                             "pnputil fallback 成功但无法从输出中提取 published name\n\nSetupAPI 错误: {}\n\npnputil 输出:\n{}",
                             setupapi_error, pnputil_result.output_text
-                        ));
+                        ))
+                    }
+                }
+                Err(pnputil_error) if is_blocked_by_policy(&pnputil_error) => {
+                    // pnputil 被 AppLocker / 组策略阻止：改用 Add-PrinterDriver（PowerShell cmdlet 通常仍被允许）
+                    eprintln!("[StageDriver] pnputil blocked by policy, fallback=Add-PrinterDriver start error=\"{}\"", pnputil_error);
+                    match stage_driver_via_add_printer_driver(inf_path, driver_names) {
+                        Ok((published_name, published_inf_path)) => {
+                            let evidence = format!(
+                                "method=add_printer_driver_fallback setupapi_error=0x{:08X} pnputil=blocked_by_policy published_name=\"{}\" published_inf_path=\"{}\"",
+                                setupapi_error.win32_error,
+                                published_name,
+                                published_inf_path
+                            );
+                            Ok((published_name, published_inf_path, StageMechanism::AddPrinterDriver, evidence))
+                        }
+                        Err(add_error) => {
+                            Err(format!(
+                                "导入驱动包失败（pnputil 被组策略阻止，Add-PrinterDriver 回退也失败）\n\nSetupAPI 错误: {}\n\npnputil 错误: {}\n\n{}",
+                                setupapi_error, pnputil_error, add_error
+                            ))
+                        }
                     }
                 }
                 Err(pnputil_error) => {
@@ -1445,10 +1674,10 @@ fn stage_driver_with_setupapi_fallback(inf_path: &std::path::Path) -> Result<(St
                         pnputil_error
                     );
                     eprintln!("[StageDriver] fallback=pnputil failed evidence=\"{}\"", evidence);
-                    return Err(format!(
+                    Err(format!(
                         "导入驱动包失败（SetupAPI 和 pnputil 都失败）\n\nSetupAPI 错误: {}\n\npnputil 错误: {}\n\n证据: {}",
                         setupapi_error, pnputil_error, evidence
-                    ));
+                    ))
                 }
             }
         }
@@ -1542,7 +1771,7 @@ fn stage_driver_package_windows(inf_path: &std::path::Path) -> Result<StageResul
     
     // 构建 pnputil 命令
     let mut cmd = Command::new("pnputil.exe");
-    cmd.args(&["/add-driver", &inf_path_str, "/install", "/subdirs"])
+    cmd.args(["/add-driver", &inf_path_str, "/install", "/subdirs"])
        .current_dir(&inf_dir)
        .stdin(Stdio::null())
        .stdout(Stdio::piped())
@@ -1554,18 +1783,30 @@ fn stage_driver_package_windows(inf_path: &std::path::Path) -> Result<StageResul
     }
     
     // 执行命令
-    let output = match cmd.output() {
+    super::process_watchdog::mark(&mut cmd);
+    let command_line = crate::utils::command_trace::format_command_line("pnputil.exe", &["/add-driver", &inf_path_str, "/install", "/subdirs"]);
+    let started = std::time::Instant::now();
+    let output = match cmd.spawn().and_then(|child| {
+        let _tracked = super::process_watchdog::track(&child, "pnputil.exe");
+        child.wait_with_output()
+    }) {
         Ok(output) => output,
         Err(e) => {
             let err_msg = format!("pnputil 命令执行失败: {}", e);
             eprintln!("[StageDriverPackage] failed error=\"{}\"", err_msg);
+            super::job_log::append_command_trace(&command_line, None, started, Some("spawn_failed"));
             return Err(err_msg);
         }
     };
     
+    super::job_log::append_command_trace(&command_line, output.status.code(), started, None);
+    super::job_log::append_process_output(&command_line, &output);
+    // /install 可能已注册驱动，丢弃会话缓存的驱动清单
+    super::session_cache::invalidate_driver_inventory();
+    
     let exit_code = output.status.code();
-    let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
-    let stderr = crate::platform::windows::encoding::decode_windows_string(&output.stderr);
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
     
     // 保存长度（在移动之前）
     let stdout_len = stdout.len();
@@ -1665,20 +1906,136 @@ struct StageResult {
     inf_path: String,
 }
 
+/// INF 驱动安装的子阶段（根据 pnputil 输出行判断）
+#[derive(Debug, Clone, Copy)]
+enum InfDriverPhase {
+    StagingPackage,
+    InstallingDriver,
+}
+
+impl InfDriverPhase {
+    /// pnputil 输出行对应的阶段（英文 / 中文系统输出均可识别）
+    fn from_pnputil_line(line: &str) -> Self {
+        if line.to_ascii_lowercase().contains("install") || line.contains("安装") {
+            InfDriverPhase::InstallingDriver
+        } else {
+            InfDriverPhase::StagingPackage
+        }
+    }
+
+    fn as_str(&self) -> &'static str {
+        match self {
+            InfDriverPhase::StagingPackage => "staging_package",
+            InfDriverPhase::InstallingDriver => "installing_driver",
+        }
+    }
+
+    fn step_id(&self) -> &'static str {
+        match self {
+            InfDriverPhase::StagingPackage => "driver.stageDriver",
+            InfDriverPhase::InstallingDriver => "driver.registerDriver",
+        }
+    }
+
+    fn legacy_phase(&self) -> &'static str {
+        match self {
+            InfDriverPhase::StagingPackage => "stageDriver",
+            InfDriverPhase::InstallingDriver => "registerDriver",
+        }
+    }
+}
+
+/// 发送 INF 驱动安装进度事件（state=running，meta.phase 标识子阶段，meta.line 为 pnputil 原始输出行）
+fn emit_inf_driver_progress(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    phase: InfDriverPhase,
+    line: &str,
+) {
+    let message = match phase {
+        InfDriverPhase::StagingPackage => format!("正在暂存驱动包: {}", line),
+        InfDriverPhase::InstallingDriver => format!("正在安装驱动: {}", line),
+    };
+    let ts_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    let event = crate::InstallProgressEvent {
+        job_id: job_id.to_string(),
+        printer_name: printer_name.to_string(),
+        step_id: phase.step_id().to_string(),
+        state: "running".to_string(),
+        message,
+        ts_ms,
+        progress: None,
+        error: None,
+        meta: Some(serde_json::json!({ "phase": phase.as_str(), "line": line })),
+        install_mode: None,
+        legacy_phase: Some(phase.legacy_phase().to_string()),
+    };
+    if let Err(e) = emit_install_progress(app, event) {
+        eprintln!(
+            "[ProgressEmit] jobId={} printer={} stepId={} phase={} result=Err error=\"{}\"",
+            job_id, printer_name, phase.step_id(), phase.as_str(), e
+        );
+    }
+}
+
 /// 安装 INF 驱动文件
 /// 
 /// # 参数
+/// - `app`: 用于发送进度事件
+/// - `job_id` / `printer_name`: 进度事件归属的安装任务
 /// - `inf_path`: INF 文件的完整路径
 /// - `driver_names`: 驱动名称候选列表，用于验证安装是否成功
 /// 
 /// # 返回
 /// - `Ok(())`: 安装成功且驱动已注册
-/// - `Err(InstallError)`: 安装失败或驱动未注册
+/// - `Err(InstallError)`: 安装失败、驱动未注册或已取消
 /// 
 /// # 实现说明
-/// 使用 pnputil.exe 安装 INF 驱动
-/// pnputil 是 Windows 推荐的驱动安装工具，比 Add-PrinterDriver 更可靠
-fn install_inf_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Result<(), InstallError> {
+/// 使用 pnputil.exe 安装 INF 驱动（可能持续一分钟左右），放入 spawn_blocking 执行：
+/// - pnputil 的每行输出作为 driver.stageDriver / driver.registerDriver 的 running 事件发送
+/// - 安装任务被取消时终止 pnputil 并返回 InstallError::Cancelled
+async fn install_inf_driver(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    inf_path: &std::path::Path,
+    driver_names: &[String],
+) -> Result<(), InstallError> {
+    let app = app.clone();
+    let job_id = job_id.to_string();
+    let printer_name = printer_name.to_string();
+    let inf_path = inf_path.to_path_buf();
+    let driver_names = driver_names.to_vec();
+    let log_job_id = super::job_log::current_job_id();
+
+    let task = tokio::task::spawn_blocking(move || {
+        super::job_log::sync_scope(log_job_id, || {
+            install_inf_driver_blocking(&inf_path, &driver_names, |phase, line| {
+                emit_inf_driver_progress(&app, &job_id, &printer_name, phase, line)
+            })
+        })
+    });
+    match task.await {
+        Ok(result) => result,
+        Err(e) => Err(InstallError::CommandFailed {
+            step: "install_inf_driver",
+            command: "pnputil.exe /add-driver".to_string(),
+            stderr: format!("INF 驱动安装任务异常: {}", e),
+        }),
+    }
+}
+
+/// install_inf_driver 的同步实现（在阻塞线程中执行）
+fn install_inf_driver_blocking(
+    inf_path: &std::path::Path,
+    driver_names: &[String],
+    mut on_progress: impl FnMut(InfDriverPhase, &str),
+) -> Result<(), InstallError> {
     eprintln!("[DEBUG] 开始安装 INF 驱动: {}", inf_path.display());
     
     // 检查 INF 文件是否存在
@@ -1710,18 +2067,28 @@ fn install_inf_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Re
     
     // 使用 pnputil.exe 安装 INF 驱动
     // pnputil.exe /add-driver "<inf_path>" /install
-    match super::cmd::run_command("pnputil.exe", &[
-        "/add-driver",
-        &inf_path_quoted,
-        "/install"
-    ]) {
-        Ok(output) => {
+    let cancel_flag = super::install_jobs::current_cancel_flag();
+    on_progress(InfDriverPhase::StagingPackage, &inf_path_str);
+    match super::cmd::run_command_streaming(
+        "pnputil.exe",
+        &["/add-driver", &inf_path_quoted, "/install"],
+        cancel_flag.as_deref(),
+        |line| on_progress(InfDriverPhase::from_pnputil_line(line), line),
+    ) {
+        Ok(None) => {
+            eprintln!("[WARN] pnputil 已取消: {}", inf_path_str);
+            // 已暂存的部分可能已写入驱动库，清空缓存以便后续步骤重新扫描
+            super::session_cache::invalidate_driver_inventory();
+            Err(InstallError::Cancelled { step: "install_inf_driver" })
+        }
+        Ok(Some(output)) => {
             let stdout = decode_windows_string(&output.stdout);
             let stderr = decode_windows_string(&output.stderr);
             let exit_code = output.status.code();
             
             eprintln!("[DEBUG] pnputil exit code: {:?}, stdout length: {}, stderr length: {}", 
                 exit_code, stdout.len(), stderr.len());
+            super::session_cache::invalidate_driver_inventory();
             
             // pnputil 成功时 exit code 为 0
             if output.status.success() {
@@ -1746,6 +2113,9 @@ fn install_inf_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Re
                         })
                     }
                 }
+            } else if is_blocked_by_policy(&format!("{}\n{}", stdout, stderr)) {
+                eprintln!("[WARN] pnputil 被组策略阻止，改用 Add-PrinterDriver");
+                install_inf_driver_via_add_printer_driver(&inf_path_abs, driver_names)
             } else {
                 // pnputil 执行失败
                 eprintln!("[ERROR] pnputil 执行失败，exit code: {:?}", exit_code);
@@ -1757,6 +2127,11 @@ fn install_inf_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Re
                 })
             }
         }
+        Err(e) if is_blocked_by_policy(&e) => {
+            // AppLocker 阻止 pnputil 启动
+            eprintln!("[WARN] pnputil 被组策略阻止（{}），改用 Add-PrinterDriver", e);
+            install_inf_driver_via_add_printer_driver(&inf_path_abs, driver_names)
+        }
         Err(e) => {
             // 命令执行失败（如进程启动失败）
             eprintln!("[ERROR] pnputil 命令执行失败: {}", e);
@@ -1769,6 +2144,35 @@ fn install_inf_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Re
     }
 }
 
+/// install_inf_driver 的 Add-PrinterDriver 回退（pnputil 被组策略阻止时）
+fn install_inf_driver_via_add_printer_driver(inf_path: &std::path::Path, driver_names: &[String]) -> Result<(), InstallError> {
+    let inf_path_str = inf_path.to_string_lossy().to_string();
+    if let Err(e) = stage_driver_via_add_printer_driver(inf_path, driver_names) {
+        return Err(InstallError::InfInstallFailed {
+            inf_path: inf_path_str,
+            exit_code: None,
+            stdout: String::new(),
+            stderr: format!("pnputil 被组策略阻止，{}", e),
+        });
+    }
+    match select_installed_driver_name(driver_names) {
+        Ok(driver_name) => {
+            super::log::write_log(&format!(
+                "[InstallInfDriver] stage_mechanism={} driver_name=\"{}\"",
+                StageMechanism::AddPrinterDriver.as_str(),
+                driver_name
+            ));
+            Ok(())
+        }
+        Err(_) => Err(InstallError::InfInstallFailed {
+            inf_path: inf_path_str,
+            exit_code: None,
+            stdout: "Add-PrinterDriver 执行成功，但 driver_names 中未找到已安装驱动".to_string(),
+            stderr: format!("请检查 driver_names 是否与 INF 中的驱动名称一致。候选: {}", driver_names.join(", ")),
+        }),
+    }
+}
+
 /// 获取应用目录（可执行文件所在目录）
 /// 
 /// 返回可执行文件所在的目录路径
@@ -2033,6 +2437,15 @@ fn resolve_inf_abs_path(
     Ok(inf_abs_to_check)
 }
 
+/// 将配置中的 effective_driver_path 解析为本地 INF 绝对路径（不要求文件存在）
+///
+/// 供安装流程以外的模块（如驱动冲突检测）复用同一套路径规则与越界防护
+pub(crate) fn resolve_local_inf_path(effective_driver_path: &str) -> Result<std::path::PathBuf, String> {
+    let app_dir = get_app_dir()?;
+    let drivers_root = get_drivers_root(&app_dir);
+    resolve_inf_abs_path(effective_driver_path, &drivers_root).map_err(|e| e.to_string())
+}
+
 /// 解析 driver_path（相对于应用目录）- 保留用于向后兼容
 /// 新代码应使用 resolve_inf_abs_path
 #[deprecated(note = "使用 resolve_inf_abs_path 代替")]
@@ -2051,7 +2464,7 @@ fn resolve_driver_path(driver_path: &str) -> Result<std::path::PathBuf, InstallE
 }
 
 /// 按候选列表选择已安装驱动名
-/// 对每个候选驱动名执行 PowerShell 查询，返回第一个已安装的驱动名
+/// 在会话缓存的驱动清单中依次查找候选驱动名，返回第一个已安装的驱动名（一次 Get-PrinterDriver 扫描）
 fn select_installed_driver_name(candidates: &[String]) -> Result<String, (InstallError, Option<String>)> {
     // 过滤并 trim 候选列表
     let filtered_candidates: Vec<String> = candidates
@@ -2069,38 +2482,21 @@ fn select_installed_driver_name(candidates: &[String]) -> Result<String, (Instal
         ));
     }
     
-    // 收集所有检查失败的 stderr 信息（用于诊断）
+    // 查询失败时记录错误信息（用于诊断）
     let mut last_stderr: Option<String> = None;
     
     // 逐个检查候选驱动是否已安装
     for candidate in &filtered_candidates {
-        // 使用 PowerShell 精确匹配驱动名
-        let check_script = format!(
-            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Get-PrinterDriver -Name '{}' -ErrorAction SilentlyContinue | Select-Object -First 1 -ExpandProperty Name",
-            candidate.replace("'", "''")
-        );
-        
-        match super::ps::run_powershell(&check_script) {
-            Ok(output) => {
-                let stdout = decode_windows_string(&output.stdout);
-                let trimmed_stdout = stdout.trim();
-                
-                // 如果 stdout 非空，说明驱动已安装
-                if !trimmed_stdout.is_empty() {
-                    eprintln!("[DEBUG] 找到已安装的驱动: {}", trimmed_stdout);
-                    return Ok(trimmed_stdout.to_string());
-                }
-                
-                // 记录 stderr（即使 stdout 为空，也可能有错误信息）
-                let stderr = decode_windows_string(&output.stderr);
-                if !stderr.trim().is_empty() {
-                    last_stderr = Some(stderr);
-                }
+        match super::session_cache::find_installed_driver(candidate) {
+            Ok(Some(installed_name)) => {
+                eprintln!("[DEBUG] 找到已安装的驱动: {}", installed_name);
+                return Ok(installed_name);
             }
+            Ok(None) => {}
             Err(e) => {
-                // PowerShell 执行失败，记录错误信息
+                // 驱动清单查询失败，后续候选也无法判断
                 last_stderr = Some(e);
-                // 继续检查下一个候选
+                break;
             }
         }
     }
@@ -2135,71 +2531,51 @@ enum TargetType {
 }
 
 fn detect_target_type(target_path: &str) -> Result<TargetType, String> {
-    let trimmed = target_path.trim();
-    
-    // 优先级 1：检查是否是合法的共享路径（\\server\share）
-    // 必须匹配：^\\[^\\]+\\[^\\]+ 且 split 后路径段数 >= 2
-    if trimmed.starts_with("\\\\") {
-        // 按反斜杠分割路径
-        let parts: Vec<&str> = trimmed.split('\\').filter(|s| !s.is_empty()).collect();
-        
-        if parts.len() >= 2 {
-            // 合法的共享连接：\\server\share
-            return Ok(TargetType::SharedConnection {
-                path: trimmed.to_string(),
-            });
-        } else if parts.len() == 1 {
-            // 只有 host 的 UNC 写法：\\192.168.20.5 或 \\server
-            // 提取 host = 去掉开头两个反斜杠后的内容
-            let host = parts[0].to_string();
-            eprintln!("[DetectTargetType] HostOnlyUNC detected: host=\"{}\"", host);
-            return Ok(TargetType::TcpIpHost { host });
-        } else {
-            // 空路径或只有反斜杠
-            return Err(format!(
-                "无效的 UNC 路径格式: \"{}\"。\n\nTCP/IP 打印机：填写 \"192.168.20.5\" 或 \"printer.company.local\"\n共享打印机：必须填写 \"\\\\server\\\\share\"",
-                trimmed
-            ));
+    use crate::platform::printer_address::PrinterAddress;
+
+    // 共享连接必须为 \\server\share；TCP/IP 主机允许 \\ 前缀（\\192.168.20.5 或 \\server）
+    match PrinterAddress::parse(target_path) {
+        Ok(address @ PrinterAddress::SmbShare { .. }) => Ok(TargetType::SharedConnection {
+            path: address.to_string(),
+        }),
+        Ok(PrinterAddress::IppUrl(url)) => Err(format!(
+            "Windows 暂不支持设备 URI 形式的打印机路径: \"{}\"。\n\nTCP/IP 打印机：填写 \"192.168.20.5\" 或 \"printer.company.local\"\n共享打印机：必须填写 \"\\\\server\\\\share\"",
+            url
+        )),
+        Ok(address) => {
+            if let Some(port) = address.port() {
+                // 端口协议与端口号由配置中的 protocol 决定，路径中的端口号忽略
+                eprintln!("[DetectTargetType] ignore port in path port={} path=\"{}\"", port, target_path);
+            }
+            let host = address.host_address();
+            eprintln!("[DetectTargetType] TcpIpHost detected: host=\"{}\"", host);
+            Ok(TargetType::TcpIpHost { host })
         }
-    }
-    
-    // 优先级 2：Plain TcpIpHost（不含反斜杠）
-    // 检查是否是 IP 地址或 hostname
-    if trimmed.contains('.') || trimmed.contains(':') {
-        // 可能是 IP 地址（IPv4 或 IPv6）
-        Ok(TargetType::TcpIpHost {
-            host: trimmed.to_string(),
-        })
-    } else if trimmed.chars().all(|c| c.is_alphanumeric() || c == '-' || c == '_') {
-        // 可能是 hostname
-        Ok(TargetType::TcpIpHost {
-            host: trimmed.to_string(),
-        })
-    } else {
-        // 其他格式：不支持
-        Err(format!(
-            "无法识别的目标路径格式: \"{}\"。\n\nTCP/IP 打印机：填写 \"192.168.20.5\" 或 \"printer.company.local\"\n共享打印机：必须填写 \"\\\\server\\\\share\"",
-            trimmed
-        ))
+        Err(e) => Err(format!(
+            "无法识别的目标路径格式: \"{}\"（{}）。\n\nTCP/IP 打印机：填写 \"192.168.20.5\" 或 \"printer.company.local\"\n共享打印机：必须填写 \"\\\\server\\\\share\"",
+            target_path.trim(),
+            e
+        )),
     }
 }
 
-/// 生成端口名（沿用旧规则）
+/// 生成端口名（沿用旧规则，由 PrinterAddress 统一处理）
 /// 
 /// # 规则
-/// - IP 地址：`IP_{ip.replace(".", "_")}`
+/// - IPv4：`IP_{ip.replace(".", "_")}`
+/// - IPv6：`IP_{ip.replace(":", "_")}`
 /// - hostname：`IP_{hostname.replace(非法字符, "_")}`
 fn generate_port_name(host: &str) -> String {
-    // 如果是 IP 地址，用下划线替换点
-    if host.contains('.') {
-        format!("IP_{}", host.replace(".", "_"))
-    } else {
-        // hostname：将非法字符替换为下划线
-        let sanitized = host
-            .chars()
-            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
-            .collect::<String>();
-        format!("IP_{}", sanitized)
+    match crate::platform::printer_address::PrinterAddress::parse(host) {
+        Ok(address) => address.port_name(),
+        Err(_) => {
+            // 无法解析时退化为旧规则：将非法字符替换为下划线
+            let sanitized = host
+                .chars()
+                .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+                .collect::<String>();
+            format!("IP_{}", sanitized)
+        }
     }
 }
 
@@ -2207,25 +2583,35 @@ fn generate_port_name(host: &str) -> String {
 /// 
 /// # 参数
 /// - `ip_or_host`: IP 地址或 hostname
-/// - `port_number`: 端口号（默认 9100）
+/// - `protocol`: 端口协议（RAW 9100 / LPR 515）
 /// - `is_legacy`: 是否使用 VBS 方式（Windows 7/8）
+/// - `report_phase`: 子阶段回调（检查已有端口 / 创建端口 / 验证端口及尝试次数），用于发送进度事件
 /// 
 /// # 返回
 /// - `Ok(port_name)`: 端口名
 /// - `Err(String)`: 错误信息（包含 evidence）
-fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_id: &str) -> Result<String, String> {
-    use crate::platform::windows::encoding::decode_windows_string;
+fn ensure_printer_port(
+    ip_or_host: &str,
+    protocol: PortProtocol,
+    is_legacy: bool,
+    job_id: &str,
+    report_phase: &dyn Fn(PortPhase, Option<(u32, u32)>),
+) -> Result<String, String> {
+    use crate::utils::encoding::decode_windows_string;
     
+    let port_number = protocol.default_port();
     let port_name = generate_port_name(ip_or_host);
-    eprintln!("[EnsurePrinterPort] step=start inputs=host=\"{}\" port={} port_name=\"{}\" is_legacy={}", 
-        ip_or_host, port_number, port_name, is_legacy);
+    eprintln!("[EnsurePrinterPort] step=start inputs=host=\"{}\" port={} protocol={} port_name=\"{}\" is_legacy={}", 
+        ip_or_host, port_number, protocol.as_str(), port_name, is_legacy);
     
     // 步骤 1：检查端口是否存在
     // 确保 exit_code=0，通过 JSON exists 字段表达是否存在
-    let check_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $port = Get-PrinterPort -Name '{}' -ErrorAction SilentlyContinue; if ($null -eq $port) {{ @{{ exists=$false }} | ConvertTo-Json -Compress | Write-Output }} else {{ @{{ exists=$true; Name=$port.Name; PrinterHostAddress=$port.PrinterHostAddress; PortNumber=$port.PortNumber; Protocol=$port.Protocol }} | ConvertTo-Json -Compress | Write-Output }}",
-        port_name.replace("'", "''")
-    );
+    report_phase(PortPhase::CheckExisting, None);
+    let check_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $port = Get-PrinterPort -Name $PortName -ErrorAction SilentlyContinue; if ($null -eq $port) { @{ exists=$false } | ConvertTo-Json -Compress | Write-Output } else { @{ exists=$true; Name=$port.Name; PrinterHostAddress=$port.PrinterHostAddress; PortNumber=$port.PortNumber; Protocol=$port.Protocol } | ConvertTo-Json -Compress | Write-Output }",
+    )
+    .param("PortName", &port_name)
+    .build();
     
     let check_result = match super::ps::run_powershell(&check_script) {
         Ok(output) => {
@@ -2321,6 +2707,7 @@ fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_
     // 端口不存在，需要创建
     eprintln!("[EnsurePrinterPort] step=create_port inputs=port_name=\"{}\" host=\"{}\" port={}", 
         port_name, ip_or_host, port_number);
+    report_phase(PortPhase::Create, None);
     
     let create_result = if is_legacy {
         // 使用 VBS 方式（Windows 7/8）
@@ -2333,7 +2720,7 @@ fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_
             }
         };
         
-        match add_printer_port_vbs(&script_path, &port_name, ip_or_host, job_id) {
+        match add_printer_port_vbs(&script_path, &port_name, ip_or_host, protocol, job_id) {
             Ok(_) => {
                 eprintln!("[EnsurePrinterPort] step=create_port result=success action=create method=vbs port_name=\"{}\"", port_name);
                 Ok(())
@@ -2348,7 +2735,7 @@ fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_
         }
     } else {
         // 使用现代方式（Windows 10+）
-        match add_printer_port_modern(&port_name, ip_or_host) {
+        match add_printer_port_modern(&port_name, ip_or_host, protocol) {
             Ok(outcome) => {
                 match outcome {
                     PortAddOutcome::Created => {
@@ -2374,7 +2761,9 @@ fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_
     // 创建后必须验证
     match create_result {
         Ok(_) => {
-            match verify_printer_port(&port_name) {
+            match verify_printer_port(&port_name, &|attempt, max_attempts| {
+                report_phase(PortPhase::Verify, Some((attempt, max_attempts)))
+            }) {
                 Ok(verified) => {
                     if verified {
                         eprintln!("[EnsurePrinterPort] step=verify_port result=success port_name=\"{}\"", port_name);
@@ -2407,19 +2796,22 @@ fn ensure_printer_port(ip_or_host: &str, port_number: u16, is_legacy: bool, job_
 /// - `Ok(())`: 成功
 /// - `Err(String)`: 错误信息（包含 evidence）
 fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) -> Result<(), String> {
-    use crate::platform::windows::encoding::decode_windows_string;
+    use crate::utils::encoding::decode_windows_string;
     
     eprintln!("[EnsurePrinterQueue] step=start inputs=queue_name=\"{}\" driver_name=\"{}\" port_name=\"{}\"", 
         queue_name, driver_name, port_name);
+
+    // 创建或改绑队列前校验 driverPin
+    super::driver_pin::check_current_job(driver_name)?;
     
     // 步骤 1：检查队列是否存在
     // 确保 exit_code=0，通过 JSON exists 字段表达是否存在
     // 修复：使用 Where-Object 精确过滤，避免 Get-Printer -Name 的通配符匹配导致误判
-    let escaped_queue_name = queue_name.replace("'", "''");
-    let check_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $q = '{}'; $printer = Get-Printer -Name $q -ErrorAction SilentlyContinue | Where-Object {{ $_.Name -eq $q }}; if ($null -eq $printer) {{ @{{ exists=$false }} | ConvertTo-Json -Compress | Write-Output }} else {{ @{{ exists=$true; Name=$printer.Name; DriverName=$printer.DriverName; PortName=$printer.PortName }} | ConvertTo-Json -Compress | Write-Output }}",
-        escaped_queue_name
-    );
+    let check_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $printer = Get-Printer -Name $QueueName -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $QueueName }; if ($null -eq $printer) { @{ exists=$false } | ConvertTo-Json -Compress | Write-Output } else { @{ exists=$true; Name=$printer.Name; DriverName=$printer.DriverName; PortName=$printer.PortName } | ConvertTo-Json -Compress | Write-Output }",
+    )
+    .param("QueueName", queue_name)
+    .build();
     
     let check_result = match super::ps::run_powershell(&check_script) {
         Ok(output) => {
@@ -2532,11 +2924,12 @@ fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) ->
             
             // 修正驱动绑定
             if driver_needs_fix {
-                let fix_driver_script = format!(
-                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Set-Printer -Name '{}' -DriverName '{}' -ErrorAction Stop; Write-Output 'FixDriverSuccess' }} catch {{ Write-Error $_.Exception.Message; exit 1 }}",
-                    queue_name.replace("'", "''"),
-                    driver_name.replace("'", "''")
-                );
+                let fix_driver_script = PsScript::new(
+                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Set-Printer -Name $QueueName -DriverName $DriverName -ErrorAction Stop; Write-Output 'FixDriverSuccess' } catch { Write-Error $_.Exception.Message; exit 1 }",
+                )
+                .param("QueueName", queue_name)
+                .param("DriverName", driver_name)
+                .build();
                 
                 match super::ps::run_powershell(&fix_driver_script) {
                     Ok(output) => {
@@ -2564,11 +2957,12 @@ fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) ->
             
             // 修正端口绑定
             if port_needs_fix {
-                let fix_port_script = format!(
-                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Set-Printer -Name '{}' -PortName '{}' -ErrorAction Stop; Write-Output 'FixPortSuccess' }} catch {{ Write-Error $_.Exception.Message; exit 1 }}",
-                    queue_name.replace("'", "''"),
-                    port_name.replace("'", "''")
-                );
+                let fix_port_script = PsScript::new(
+                    "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Set-Printer -Name $QueueName -PortName $PortName -ErrorAction Stop; Write-Output 'FixPortSuccess' } catch { Write-Error $_.Exception.Message; exit 1 }",
+                )
+                .param("QueueName", queue_name)
+                .param("PortName", port_name)
+                .build();
                 
                 match super::ps::run_powershell(&fix_port_script) {
                     Ok(output) => {
@@ -2603,12 +2997,13 @@ fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) ->
             queue_name, driver_name, port_name);
         
         // 改造脚本：所有幂等逻辑必须 swallow 异常并保持 exit 0
-        let create_script = format!(
-            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Add-Printer -Name '{}' -DriverName '{}' -PortName '{}' -ErrorAction Stop; Write-Output 'QueueSuccess' }} catch {{ if ($_.Exception.Message -like '*already exists*' -or $_.Exception.Message -like '*已存在*') {{ Write-Output 'QueueExists' }} else {{ Write-Error $_.Exception.Message; exit 1 }} }}",
-            queue_name.replace("'", "''"),
-            driver_name.replace("'", "''"),
-            port_name.replace("'", "''")
-        );
+        let create_script = PsScript::new(
+            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Add-Printer -Name $QueueName -DriverName $DriverName -PortName $PortName -ErrorAction Stop; Write-Output 'QueueSuccess' } catch { if ($_.Exception.Message -like '*already exists*' -or $_.Exception.Message -like '*已存在*') { Write-Output 'QueueExists' } else { Write-Error $_.Exception.Message; exit 1 } }",
+        )
+        .param("QueueName", queue_name)
+        .param("DriverName", driver_name)
+        .param("PortName", port_name)
+        .build();
         
         match super::ps::run_powershell(&create_script) {
             Ok(output) => {
@@ -2656,11 +3051,11 @@ fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) ->
     
     // final_verify 必须输出 JSON（即使不存在）
     // 修复：使用 Where-Object 精确过滤，避免 Get-Printer -Name 的通配符匹配导致误判
-    let escaped_queue_name = queue_name.replace("'", "''");
-    let verify_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $q = '{}'; $printer = Get-Printer -Name $q -ErrorAction SilentlyContinue | Where-Object {{ $_.Name -eq $q }}; if ($null -eq $printer) {{ @{{ exists=$false }} | ConvertTo-Json -Compress | Write-Output }} else {{ @{{ exists=$true; Name=$printer.Name; DriverName=$printer.DriverName; PortName=$printer.PortName }} | ConvertTo-Json -Compress | Write-Output }}",
-        escaped_queue_name
-    );
+    let verify_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $printer = Get-Printer -Name $QueueName -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $QueueName }; if ($null -eq $printer) { @{ exists=$false } | ConvertTo-Json -Compress | Write-Output } else { @{ exists=$true; Name=$printer.Name; DriverName=$printer.DriverName; PortName=$printer.PortName } | ConvertTo-Json -Compress | Write-Output }",
+    )
+    .param("QueueName", queue_name)
+    .build();
     
     match super::ps::run_powershell(&verify_script) {
         Ok(output) => {
@@ -2788,18 +3183,25 @@ fn ensure_printer_queue(queue_name: &str, driver_name: &str, port_name: &str) ->
     }
 }
 
+/// 端口验证最大尝试次数
+const VERIFY_PORT_ATTEMPTS: u32 = 3;
+
 /// 重试几次，因为端口创建可能需要时间
-fn verify_printer_port(port_name: &str) -> Result<bool, InstallError> {
+///
+/// `on_attempt(attempt, max_attempts)` 在每次尝试开始前调用（用于发送进度事件）
+fn verify_printer_port(port_name: &str, on_attempt: &dyn Fn(u32, u32)) -> Result<bool, InstallError> {
     let mut port_verified = false;
-    for attempt in 1..=3 {
-        eprintln!("[DEBUG] 验证端口存在（尝试 {}/3）", attempt);
+    for attempt in 1..=VERIFY_PORT_ATTEMPTS {
+        eprintln!("[DEBUG] 验证端口存在（尝试 {}/{}）", attempt, VERIFY_PORT_ATTEMPTS);
+        on_attempt(attempt, VERIFY_PORT_ATTEMPTS);
         
         // Get-PrinterPort 查询脚本必须保证 exit_code=0（通过 -ErrorAction SilentlyContinue 且不 throw）
         // 通过 JSON exists 字段表达是否存在
-        let verify_port_script = format!(
-            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $port = Get-PrinterPort -Name '{}' -ErrorAction SilentlyContinue; if ($null -eq $port) {{ @{{ exists=$false }} | ConvertTo-Json -Compress | Write-Output }} else {{ @{{ exists=$true }} | ConvertTo-Json -Compress | Write-Output }}",
-            port_name.replace("'", "''")
-        );
+        let verify_port_script = PsScript::new(
+            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $port = Get-PrinterPort -Name $PortName -ErrorAction SilentlyContinue; if ($null -eq $port) { @{ exists=$false } | ConvertTo-Json -Compress | Write-Output } else { @{ exists=$true } | ConvertTo-Json -Compress | Write-Output }",
+        )
+        .param("PortName", port_name)
+        .build();
         let verify_port = super::ps::run_powershell(&verify_port_script);
         
         match verify_port {
@@ -2811,7 +3213,7 @@ fn verify_printer_port(port_name: &str) -> Result<bool, InstallError> {
                 // 统一成功判据：exit_code==0 才能进入 success 分支
                 if exit_code != Some(0) {
                     eprintln!("[VerifyPrinterPort] attempt={} exit_code={:?} stderr=\"{}\"", attempt, exit_code, verify_stderr);
-                    if attempt < 3 {
+                    if attempt < VERIFY_PORT_ATTEMPTS {
                         std::thread::sleep(std::time::Duration::from_millis(500));
                     } else {
                         return Err(InstallError::PowerShellFailed {
@@ -2846,7 +3248,7 @@ fn verify_printer_port(port_name: &str) -> Result<bool, InstallError> {
                     break;
                 } else {
                     eprintln!("[VerifyPrinterPort] attempt={} result=not_found port_name=\"{}\"", attempt, port_name);
-                    if attempt < 3 {
+                    if attempt < VERIFY_PORT_ATTEMPTS {
                         std::thread::sleep(std::time::Duration::from_millis(500));
                     }
                 }
@@ -2854,13 +3256,13 @@ fn verify_printer_port(port_name: &str) -> Result<bool, InstallError> {
             Err(e) => {
                 eprintln!("[VerifyPrinterPort] attempt={} result=error error=\"{}\"", attempt, e);
                 // 如果是最后一次尝试，返回错误
-                if attempt == 3 {
+                if attempt == VERIFY_PORT_ATTEMPTS {
                     return Err(InstallError::PowerShellFailed {
                         step: "verify_printer_port",
                         stderr: e,
                     });
                 }
-                if attempt < 3 {
+                if attempt < VERIFY_PORT_ATTEMPTS {
                     std::thread::sleep(std::time::Duration::from_millis(500));
                 }
             }
@@ -2875,14 +3277,21 @@ fn verify_printer_port(port_name: &str) -> Result<bool, InstallError> {
 /// - 如果端口已存在：输出 "PortExists"，不 throw，exit_code=0
 /// - 如果端口创建成功：输出 "PortSuccess"，exit_code=0
 /// - 其他错误：throw，exit_code!=0
-fn add_printer_port_modern(port_name: &str, ip_address: &str) -> Result<PortAddOutcome, InstallError> {
-    eprintln!("[DEBUG] 添加打印机端口 {}", port_name);
+fn add_printer_port_modern(port_name: &str, ip_address: &str, protocol: PortProtocol) -> Result<PortAddOutcome, InstallError> {
+    eprintln!("[DEBUG] 添加打印机端口 {} protocol={}", port_name, protocol.as_str());
+    let host_args = match protocol {
+        PortProtocol::Raw => "-PrinterHostAddress $HostAddress",
+        PortProtocol::Lpr => "-LprHostAddress $HostAddress -LprQueueName $LprQueue -LprByteCounting",
+    };
     // 改造脚本：所有幂等逻辑必须 swallow 异常并保持 exit 0
-    let port_add_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Add-PrinterPort -Name '{}' -PrinterHostAddress '{}' -ErrorAction Stop; Write-Output 'PortSuccess' }} catch {{ if ($_.Exception.Message -like '*already exists*' -or $_.Exception.Message -like '*已存在*') {{ Write-Output 'PortExists' }} else {{ Write-Error $_.Exception.Message; exit 1 }} }}",
-        port_name.replace("'", "''"),
-        ip_address.replace("'", "''")
-    );
+    let port_add_script = PsScript::new(format!(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Add-PrinterPort -Name $Name {} -ErrorAction Stop; Write-Output 'PortSuccess' }} catch {{ if ($_.Exception.Message -like '*already exists*' -or $_.Exception.Message -like '*已存在*') {{ Write-Output 'PortExists' }} else {{ Write-Error $_.Exception.Message; exit 1 }} }}",
+        host_args
+    ))
+    .param("Name", port_name)
+    .param("HostAddress", ip_address)
+    .param("LprQueue", LPR_QUEUE_NAME)
+    .build();
     let port_add_result = super::ps::run_powershell(&port_add_script);
     
     match port_add_result {
@@ -2895,6 +3304,9 @@ fn add_printer_port_modern(port_name: &str, ip_address: &str) -> Result<PortAddO
             if exit_code != Some(0) {
                 let evidence = format!("add_port_failed exit_code={:?} stdout=\"{}\" stderr=\"{}\"", exit_code, port_stdout, port_stderr);
                 eprintln!("[AddPrinterPortModern] step=add_port result=error evidence=\"{}\"", evidence);
+                if let Some(cmdlet) = missing_print_cmdlet(&port_stderr) {
+                    return Err(InstallError::PrintCmdletsUnavailable { cmdlet, stderr: port_stderr });
+                }
                 return Err(InstallError::PortAddFailedModern {
                     stdout: port_stdout,
                     stderr: port_stderr,
@@ -2940,14 +3352,18 @@ fn add_printer_port_modern(port_name: &str, ip_address: &str) -> Result<PortAddO
 /// 使用现代方式添加打印机（使用指定的驱动）
 fn add_printer_with_driver_modern(name: &str, port_name: &str, ip_address: &str, driver_name: &str, job_id: &str) -> InstallResult {
     eprintln!("[DEBUG] 使用驱动 '{}' 安装打印机 '{}' 到端口 '{}'", driver_name, name, port_name);
+    if let Err(e) = super::driver_pin::check_current_job(driver_name) {
+        return driver_mismatch_result(&e, job_id);
+    }
     
     // 使用指定的驱动添加打印机
-    let printer_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Add-Printer -Name '{}' -DriverName '{}' -PortName '{}' -ErrorAction Stop; Write-Output 'Success' }} catch {{ Write-Error $_.Exception.Message }}",
-        name.replace("'", "''"),
-        driver_name.replace("'", "''"),
-        port_name.replace("'", "''")
-    );
+    let printer_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Add-Printer -Name $Name -DriverName $DriverName -PortName $PortName -ErrorAction Stop; Write-Output 'Success' } catch { Write-Error $_.Exception.Message }",
+    )
+    .param("Name", name)
+    .param("DriverName", driver_name)
+    .param("PortName", port_name)
+    .build();
     let printer_output = super::ps::run_powershell(&printer_script);
     
     match printer_output {
@@ -2968,8 +3384,12 @@ fn add_printer_with_driver_modern(name: &str, port_name: &str, ip_address: &str,
             } else {
                 // 失败时包含诊断信息：驱动名、端口名、PowerShell stderr
                 let mut stderr_parts = Vec::new();
-                let error = InstallError::PrinterInstallFailedModern {
-                    stderr: printer_stderr.clone(),
+                let error = match missing_print_cmdlet(&printer_stderr) {
+                    Some(cmdlet) => InstallError::PrintCmdletsUnavailable { cmdlet, stderr: printer_stderr.clone() },
+                    None => InstallError::PrinterInstallFailedModern {
+                        stderr: printer_stderr.clone(),
+                        classified: classify_spooler_error(&format!("{}\n{}", printer_stderr, printer_stdout)),
+                    },
                 };
                 let base_stderr = error.format_stderr_with_code(Some(printer_stderr.clone())).unwrap_or_default();
                 stderr_parts.push(base_stderr);
@@ -3016,8 +3436,10 @@ fn add_printer_with_driver_modern(name: &str, port_name: &str, ip_address: &str,
 fn write_vbs_script_to_temp() -> Result<std::path::PathBuf, InstallError> {
     // 将嵌入的 VBS 脚本写入临时文件
     // 重要：直接写入原始字节，不要进行编码转换，因为 VBScript 需要 ANSI/GBK 编码
-    let temp_dir = std::env::temp_dir();
-    let script_path = temp_dir.join("prnport.vbs");
+    // 写入当前任务的工作目录（不在任务中时退回 %TEMP%）
+    let script_path = super::job_workdir::current_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("prnport.vbs");
     
     // 直接写入原始字节（保持原始编码，ANSI/GBK）
     let mut file = fs::File::create(&script_path)
@@ -3040,16 +3462,25 @@ fn write_vbs_script_to_temp() -> Result<std::path::PathBuf, InstallError> {
         })?;
     drop(file); // 确保文件已关闭
     
+    // 写入后立即校验哈希（防止临时目录中的脚本被替换后仍被执行）
+    super::integrity::verify_written_resource("prnport.vbs", &script_path)
+        .map_err(|e| InstallError::FileOperationFailed {
+            step: "write_vbs_script_to_temp",
+            operation: "校验脚本完整性",
+            error: e,
+        })?;
+    
     Ok(script_path)
 }
 
 /// 使用 VBS 脚本方式添加打印机端口
-fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_address: &str, job_id: &str) -> Result<InstallResult, InstallResult> {
+fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_address: &str, protocol: PortProtocol, job_id: &str) -> Result<InstallResult, Box<InstallResult>> {
     // 使用 cscript 运行 prnport.vbs 脚本添加端口（隐藏窗口）
     // 参数参考：cscript prnport.vbs -a -r IP_192.168.x.x -h 192.168.x.x -o raw
+    //          cscript prnport.vbs -a -r IP_192.168.x.x -h 192.168.x.x -o lpr -q lp
     // 注意：移除 //B 参数以便捕获错误信息
     let script_path_str = script_path.to_str().unwrap();
-    let output = super::cmd::run_command("cscript", &[
+    let mut args = vec![
         "//NoLogo",  // 不显示脚本横幅
         script_path_str,
         "-a",        // 添加端口
@@ -3058,13 +3489,19 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
         "-h",        // IP地址
         ip_address,  // IP地址值
         "-o",        // 输出类型
-        "raw"        // raw 类型
-    ]);
+        protocol.as_str(), // raw / lpr 类型
+    ];
+    if protocol == PortProtocol::Lpr {
+        args.extend(["-q", LPR_QUEUE_NAME]); // LPR 队列名
+    }
+    let output = super::cmd::run_command("cscript", &args);
     
     match output {
         Ok(result) => {
-            // 执行完毕后删除临时文件
-            let _ = std::fs::remove_file(script_path);
+            // 执行完毕后删除临时文件（任务工作目录中的脚本由任务结束时统一清理）
+            if !super::job_workdir::in_job_dir(script_path) {
+                let _ = std::fs::remove_file(script_path);
+            }
             
             let stdout = decode_windows_string(&result.stdout);
             let stderr = decode_windows_string(&result.stderr);
@@ -3098,7 +3535,7 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
                     stderr: stderr.clone(),
                 };
                 
-                Err(InstallResult {
+                Err(Box::new(InstallResult {
                     success: false,
                     message: error.to_user_message(),
                     method: Some("VBS".to_string()),
@@ -3106,7 +3543,7 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
                     stderr: error.format_stderr_with_code(Some(stderr)),
                     effective_dry_run: false, // 这是真实安装路径
                     job_id: job_id.to_string(),
-                })
+                }))
             }
         }
         Err(e) => {
@@ -3130,7 +3567,7 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
                 script_info: script_info.clone(),
             };
             
-            Err(InstallResult {
+            Err(Box::new(InstallResult {
                 success: false,
                 message: error.to_user_message(),
                 method: Some("VBS".to_string()),
@@ -3138,7 +3575,7 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
                 stderr: error.format_stderr_with_code(None),
                 effective_dry_run: false, // 这是真实安装路径
                 job_id: job_id.to_string(),
-            })
+            }))
         }
     }
 }
@@ -3146,14 +3583,18 @@ fn add_printer_port_vbs(script_path: &std::path::Path, port_name: &str, ip_addre
 /// 使用 VBS 方式添加打印机（使用指定的驱动）
 fn add_printer_with_driver_vbs(name: &str, port_name: &str, ip_address: &str, driver_name: &str, job_id: &str) -> InstallResult {
     eprintln!("[DEBUG] 使用驱动 '{}' 安装打印机 '{}' 到端口 '{}' (VBS方式)", driver_name, name, port_name);
+    if let Err(e) = super::driver_pin::check_current_job(driver_name) {
+        return driver_mismatch_result(&e, job_id);
+    }
     
     // 端口添加成功，现在使用 PowerShell Add-Printer 安装打印机
-    let ps_script = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Add-Printer -Name '{}' -DriverName '{}' -PortName '{}' -ErrorAction Stop; Write-Output 'Success' }} catch {{ Write-Error $_.Exception.Message }}",
-        name.replace("'", "''"),
-        driver_name.replace("'", "''"),
-        port_name.replace("'", "''")
-    );
+    let ps_script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Add-Printer -Name $Name -DriverName $DriverName -PortName $PortName -ErrorAction Stop; Write-Output 'Success' } catch { Write-Error $_.Exception.Message }",
+    )
+    .param("Name", name)
+    .param("DriverName", driver_name)
+    .param("PortName", port_name)
+    .build();
     let ps_output = super::ps::run_powershell(&ps_script);
     
     match ps_output {
@@ -3176,6 +3617,7 @@ fn add_printer_with_driver_vbs(name: &str, port_name: &str, ip_address: &str, dr
                 let mut stderr_parts = Vec::new();
                 let error = InstallError::PrinterInstallFailedVbs {
                     stderr: ps_stderr.clone(),
+                    classified: classify_spooler_error(&format!("{}\n{}", ps_stderr, ps_stdout)),
                 };
                 let base_stderr = error.format_stderr_with_code(Some(ps_stderr.clone())).unwrap_or_default();
                 stderr_parts.push(base_stderr);
@@ -3238,7 +3680,7 @@ fn generate_stable_id(printer_name: &str, printer_path: &str) -> String {
                         if printer.name == printer_name || printer.path == printer_path {
                             // 检查是否有 id 字段（虽然当前结构体没有，但为将来扩展预留）
                             // 如果没有 id，生成 hash
-                            let ip = printer_path.trim_start_matches("\\\\").trim_start_matches("\\").to_string();
+                            let ip = crate::platform::printer_address::strip_unc_prefix(printer_path);
                             let hash_input = format!("{}|{}|{}", area.area_name, printer.name, ip);
                             
                             // 使用 DefaultHasher 生成 hash（简单且跨机器一致）
@@ -3260,7 +3702,7 @@ fn generate_stable_id(printer_name: &str, printer_path: &str) -> String {
     }
     
     // 如果找不到配置，使用 name + path 生成
-    let ip = printer_path.trim_start_matches("\\\\").trim_start_matches("\\").to_string();
+    let ip = crate::platform::printer_address::strip_unc_prefix(printer_path);
     let hash_input = format!("{}|{}", printer_name, ip);
     let mut hasher = DefaultHasher::new();
     hash_input.hash(&mut hasher);
@@ -3294,6 +3736,274 @@ impl DriverInstallPolicy {
             _ => DriverInstallPolicy::Always,  // 默认值
         }
     }
+
+    fn as_str(self) -> &'static str {
+        match self {
+            DriverInstallPolicy::Always => "always",
+            DriverInstallPolicy::ReuseIfInstalled => "reuse_if_installed",
+        }
+    }
+}
+
+// ============================================================================
+// 失败后换策略自动重试（区域 / 打印机 autoRetry=true 时启用）
+// ============================================================================
+
+/// 可换策略重试的错误码（驱动缺失 / INF 安装失败 / PrintUIEntry 失败 / Add-Printer 报告驱动未知）
+const STRATEGY_RETRY_CODES: [&str; 4] = [
+    "WIN_DRIVER_NOT_FOUND",
+    "WIN_INF_INSTALL_FAILED",
+    "WIN_PRINTUI_INF_INSTALL_FAILED",
+    "WIN_UNKNOWN_PRINTER_DRIVER",
+];
+
+/// 单次安装任务最多自动重试次数
+const MAX_STRATEGY_RETRIES: usize = 1;
+
+/// 重试时覆盖的安装策略（None 表示沿用配置 / 前端传入的值）
+#[derive(Debug, Clone, Default)]
+struct StrategyOverride {
+    install_mode: Option<String>,
+    driver_policy: Option<DriverInstallPolicy>,
+    /// 端口预检自动改用的协议（portProtocolAutoSelect）
+    port_protocol: Option<PortProtocol>,
+}
+
+/// 单次尝试实际使用的安装策略（由 install_printer_windows_inner 填写）
+#[derive(Debug, Clone)]
+struct EffectiveStrategy {
+    install_mode: String,
+    driver_policy: DriverInstallPolicy,
+    /// 配置允许的驱动名称（安装后校验使用，为空表示不校验驱动）
+    driver_names: Vec<String>,
+}
+
+impl Default for EffectiveStrategy {
+    fn default() -> Self {
+        EffectiveStrategy {
+            install_mode: "auto".to_string(),
+            driver_policy: DriverInstallPolicy::Always,
+            driver_names: Vec::new(),
+        }
+    }
+}
+
+/// 从 stderr 的 [EASYPRINTER_CODE=...] 标记中提取错误码
+fn error_code_from_stderr(stderr: Option<&str>) -> Option<String> {
+    stderr.and_then(crate::platform::error_code::error_code).map(str::to_string)
+}
+
+/// 根据失败错误码与本次使用的策略选择下一次尝试的策略
+///
+/// - installMode=package 失败：改用 legacy_inf（不再强制 modern_only，允许 PrintUIEntry）
+/// - driverInstallPolicy=reuse_if_installed 失败：改用 always（重新安装 INF 驱动）
+///
+/// # 返回
+/// - `Some((覆盖策略, 调整说明))`；错误码不可重试或没有可调整的策略时返回 None
+fn next_retry_strategy(
+    code: &str,
+    used: &EffectiveStrategy,
+    current: &StrategyOverride,
+) -> Option<(StrategyOverride, String)> {
+    if !STRATEGY_RETRY_CODES.contains(&code) {
+        return None;
+    }
+    let mut next = current.clone();
+    let reason = if used.install_mode == "package" {
+        next.install_mode = Some("legacy_inf".to_string());
+        format!("{}: installMode package -> legacy_inf", code)
+    } else if matches!(used.driver_policy, DriverInstallPolicy::ReuseIfInstalled) {
+        next.driver_policy = Some(DriverInstallPolicy::Always);
+        format!("{}: driverInstallPolicy reuse_if_installed -> always", code)
+    } else {
+        return None;
+    };
+    Some((next, reason))
+}
+
+/// 端口预检发现：配置的端口不可达，另一协议的端口可达
+struct PortProbeFinding {
+    alternate: PortProtocol,
+    /// 实际会使用的端口号（已有端口的 PortNumber，没有已有端口时为协议默认端口）
+    configured_port: u16,
+    /// 端口已存在（安装会直接复用，改用另一协议不生效）
+    port_exists: bool,
+}
+
+/// 端口协议预检：同时探测配置的端口与另一协议的端口（阻塞调用）
+///
+/// 端口已存在时探测其 PortNumber（可能已通过 set_port_config 修改），否则探测配置协议的默认端口
+///
+/// # 返回
+/// 配置的端口不可达而另一协议可达时返回发现；共享连接、两者都可达或都不可达时返回 None
+fn probe_alternate_port_protocol(path: &str, configured: PortProtocol) -> Option<PortProbeFinding> {
+    let Ok(TargetType::TcpIpHost { host }) = detect_target_type(path) else {
+        return None;
+    };
+    let existing_port = super::port_config::get_port_config_windows(&generate_port_name(&host)).ok();
+    let port_exists = existing_port.is_some();
+    let configured_port = existing_port
+        .and_then(|port| port.port_number)
+        .and_then(|number| u16::try_from(number).ok())
+        .unwrap_or(configured.default_port());
+    let alternate = configured.alternate();
+    let reachable = super::port_probe::probe_ports(&host, &[configured_port, alternate.default_port()]);
+    super::log::write_log(&format!(
+        "[Preflight] port_probe host=\"{}\" {}:{}={} {}:{}={} port_exists={}",
+        host,
+        configured.as_str(),
+        configured_port,
+        reachable[0],
+        alternate.as_str(),
+        alternate.default_port(),
+        reachable[1],
+        port_exists
+    ));
+    match reachable.as_slice() {
+        [false, true] => Some(PortProbeFinding { alternate, configured_port, port_exists }),
+        _ => None,
+    }
+}
+
+/// 读取打印机的安装选项（区域默认值已合并；未匹配到配置或读取失败时返回默认值）
+fn configured_install_options(name: &str, path: &str) -> crate::InstallOptions {
+    crate::load_local_config()
+        .ok()
+        .and_then(|(config, _)| crate::find_printer_with_defaults(&config, name, path))
+        .map(|printer| printer.options)
+        .unwrap_or_default()
+}
+
+/// 发送 job.retry 事件（同一 jobId 下开始下一次尝试）
+fn emit_strategy_retry(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    attempt: u32,
+    reason: &str,
+) {
+    let ts_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let event = crate::InstallProgressEvent {
+        job_id: job_id.to_string(),
+        printer_name: printer_name.to_string(),
+        step_id: "job.retry".to_string(),
+        state: "running".to_string(),
+        message: format!("安装失败，正在更换安装策略重试（第 {} 次尝试）", attempt),
+        ts_ms,
+        progress: None,
+        error: None,
+        meta: Some(serde_json::json!({ "attempt": attempt, "reason": reason })),
+        install_mode: None,
+        legacy_phase: None,
+    };
+    if let Err(err) = emit_install_progress(app, event) {
+        eprintln!("[InstallPrinterWindows] job.retry emit failed for jobId={} error={}", job_id, err);
+    }
+}
+
+// ============================================================================
+// 免驱回退：未找到匹配的厂商驱动时改用 Microsoft IPP Class Driver（ippFallback，默认开启）
+// ============================================================================
+
+/// 发送 job.ippFallback 事件（meta.driverless=true 标明队列为免驱队列）
+fn emit_ipp_fallback(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    device: &super::ipp_class_driver::IppDevice,
+) {
+    let ts_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+    let event = crate::InstallProgressEvent {
+        job_id: job_id.to_string(),
+        printer_name: printer_name.to_string(),
+        step_id: "job.ippFallback".to_string(),
+        state: "running".to_string(),
+        message: "未找到匹配的厂商驱动，设备支持 IPP，正在使用 Microsoft IPP Class Driver 创建免驱队列".to_string(),
+        ts_ms,
+        progress: None,
+        error: None,
+        meta: Some(serde_json::json!({
+            "driverless": true,
+            "ippUrl": device.ipp_url,
+            "makeAndModel": device.make_and_model,
+        })),
+        install_mode: None,
+        legacy_phase: None,
+    };
+    if let Err(err) = emit_install_progress(app, event) {
+        eprintln!("[InstallPrinterWindows] job.ippFallback emit failed for jobId={} error={}", job_id, err);
+    }
+}
+
+/// 驱动缺失时尝试以 IPP Class Driver 创建免驱队列
+///
+/// # 返回
+/// - `Some((安装结果, 队列驱动名称))`: 已尝试回退（成功时 method=IppClassDriver）
+/// - `None`: 系统不自带 IPP Class Driver 或设备不支持 IPP，保留原结果
+async fn try_ipp_class_driver_fallback(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    name: &str,
+    path: &str,
+    original: &InstallResult,
+) -> Option<(InstallResult, String)> {
+    let device = match super::ipp_class_driver::ensure_supported() {
+        Ok(()) => super::ipp_class_driver::probe_ipp(path).await,
+        Err(e) => Err(e),
+    };
+    let device = match device {
+        Ok(device) => device,
+        Err(e) => {
+            super::job_log::append_to_job(job_id, &format!("[Job] IPP_FALLBACK skipped reason=\"{}\"", e));
+            return None;
+        }
+    };
+    emit_ipp_fallback(app, job_id, name, &device);
+    super::job_log::append_to_job(job_id, &format!(
+        "[Job] IPP_FALLBACK url=\"{}\" make_and_model={:?}",
+        device.ipp_url, device.make_and_model
+    ));
+    let result = match super::ipp_class_driver::add_ipp_queue(name, &device.ipp_url) {
+        Ok(driver_name) => {
+            let driver_name = if driver_name.is_empty() {
+                super::ipp_class_driver::IPP_CLASS_DRIVER_NAME.to_string()
+            } else {
+                driver_name
+            };
+            let result = InstallResult {
+                success: true,
+                message: format!(
+                    "未找到匹配的厂商驱动，已使用 {}（免驱）创建打印机 \"{}\"（{}）。\n\n免驱队列只提供基本打印功能，如需装订 / 多纸盒等高级功能请联系管理员提供厂商驱动。",
+                    driver_name, name, device.ipp_url
+                ),
+                method: Some(crate::platform::IPP_CLASS_DRIVER_METHOD.to_string()),
+                stdout: None,
+                stderr: None,
+                effective_dry_run: false,
+                job_id: job_id.to_string(),
+            };
+            (result, driver_name)
+        }
+        Err(e) => {
+            let result = InstallResult {
+                success: false,
+                message: format!("{}\n\n改用 IPP Class Driver 创建免驱队列也失败: {}", original.message, e),
+                method: Some(crate::platform::IPP_CLASS_DRIVER_METHOD.to_string()),
+                stdout: original.stdout.clone(),
+                stderr: Some(format!("{}\n{}", original.stderr.as_deref().unwrap_or_default(), e)),
+                effective_dry_run: false,
+                job_id: job_id.to_string(),
+            };
+            (result, String::new())
+        }
+    };
+    Some(result)
 }
 
 /// 发送安装进度事件的辅助函数（兼容旧版本，保留用于过渡期）
@@ -3329,6 +4039,12 @@ fn emit_progress_event(
         legacy_phase,
     };
     
+    // 步骤事件同时写入 job 日志，便于失败后回溯
+    super::job_log::append_line(&format!(
+        "[Step] stepId={} state={} message={}",
+        step_id, state, event.message
+    ));
+    
     // 统一日志：在emit调用之后打印，包含emit的Result
     match emit_install_progress(app, event) {
         Ok(_) => {
@@ -3346,6 +4062,83 @@ fn emit_progress_event(
     }
 }
 
+/// 端口创建阶段的子阶段（device.ensurePort 步骤内的 running 事件）
+#[derive(Debug, Clone, Copy)]
+enum PortPhase {
+    CheckExisting,
+    Create,
+    Verify,
+}
+
+impl PortPhase {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PortPhase::CheckExisting => "check_existing",
+            PortPhase::Create => "create",
+            PortPhase::Verify => "verify",
+        }
+    }
+}
+
+/// 发送端口创建子阶段事件（stepId 仍为 device.ensurePort，state=running，meta.phase 标识子阶段）
+///
+/// 验证阶段附带尝试次数：progress.current/total 与 meta.attempt/maxAttempts
+fn emit_port_phase_event(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    phase: PortPhase,
+    host: &str,
+    attempt: Option<(u32, u32)>,
+) {
+    let message = match (phase, attempt) {
+        (PortPhase::CheckExisting, _) => format!("正在检查已有端口: {}", host),
+        (PortPhase::Create, _) => format!("正在创建端口: {}", host),
+        (PortPhase::Verify, Some((n, max))) => format!("正在验证端口（第 {}/{} 次）: {}", n, max, host),
+        (PortPhase::Verify, None) => format!("正在验证端口: {}", host),
+    };
+    let mut meta = serde_json::json!({ "phase": phase.as_str(), "host": host });
+    if let Some((n, max)) = attempt {
+        meta["attempt"] = serde_json::json!(n);
+        meta["maxAttempts"] = serde_json::json!(max);
+    }
+    let ts_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64;
+
+    let event = crate::InstallProgressEvent {
+        job_id: job_id.to_string(),
+        printer_name: printer_name.to_string(),
+        step_id: "device.ensurePort".to_string(),
+        state: "running".to_string(),
+        message,
+        ts_ms,
+        progress: attempt.map(|(n, max)| crate::ProgressPayload {
+            current: Some(n as u64),
+            total: Some(max as u64),
+            unit: Some("attempts".to_string()),
+            percent: None,
+        }),
+        error: None,
+        meta: Some(meta),
+        install_mode: None,
+        legacy_phase: Some("ensurePort".to_string()),
+    };
+
+    super::job_log::append_line(&format!(
+        "[Step] stepId=device.ensurePort state=running phase={} message={}",
+        phase.as_str(),
+        event.message
+    ));
+    if let Err(e) = emit_install_progress(app, event) {
+        eprintln!(
+            "[ProgressEmit] jobId={} printer={} stepId=device.ensurePort phase={} result=Err error=\"{}\"",
+            job_id, printer_name, phase.as_str(), e
+        );
+    }
+}
+
 /// 辅助函数：发送 job.done 事件
 fn emit_job_done(
     app: &tauri::AppHandle,
@@ -3392,13 +4185,45 @@ fn emit_job_done(
     eprintln!("[InstallPrinterWindows] job.done event emitted for jobId={} state={}", job_id, state);
 }
 
+/// 暂缓发送的 finalVerify 失败事件（key = jobId，value = 失败信息）
+///
+/// 单次尝试失败时任务可能还会换策略重试，失败事件等所有尝试结束、确认最终失败后才发送
+static DEFERRED_FINAL_VERIFY_FAILURES: Mutex<Option<HashMap<String, Option<String>>>> = Mutex::new(None);
+
+fn with_deferred_final_verify<R>(f: impl FnOnce(&mut HashMap<String, Option<String>>) -> R) -> R {
+    let mut guard = DEFERRED_FINAL_VERIFY_FAILURES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+/// 取出任务暂缓的 finalVerify 失败事件（外层 Some 表示有暂缓的事件）
+fn take_deferred_final_verify(job_id: &str) -> Option<Option<String>> {
+    with_deferred_final_verify(|pending| pending.remove(job_id))
+}
+
 /// 辅助函数：发送 finalVerify 事件（如果尚未发送）
+///
+/// 失败事件先暂缓，由 install_printer_windows 在所有尝试结束后按最终结果发送
 fn emit_final_verify_if_needed(
     app: &tauri::AppHandle,
     job_id: &str,
     printer_name: &str,
     success: bool,
     message: Option<String>,
+) {
+    if !success {
+        with_deferred_final_verify(|pending| pending.insert(job_id.to_string(), message));
+        return;
+    }
+    emit_final_verify_event(app, job_id, printer_name, success, message);
+}
+
+/// 发送 finalVerify 事件
+fn emit_final_verify_event(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    success: bool,
+    message: Option<String>,
 ) {
     let ts_ms = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
@@ -3446,11 +4271,136 @@ fn emit_final_verify_if_needed(
     eprintln!("[InstallPrinterWindows] finalVerify event emitted for jobId={} state={}", job_id, state);
 }
 
+/// 按当前用户会话连接共享打印机（installScope=session，或 auto 且为 RDS 终端服务器）
+///
+/// Add-Printer -ConnectionName 建立的是当前用户的连接（驱动由打印服务器提供），
+/// 不需要管理员权限，也不会在本机创建对其他用户可见的队列
+async fn install_shared_connection_for_session(
+    app: &tauri::AppHandle,
+    job_id: &str,
+    printer_name: &str,
+    conn_path: &str,
+) -> InstallResult {
+    eprintln!("[SessionScope] EnsurePrinterQueue step=start connection_name=\"{}\"", conn_path);
+    emit_progress_event(
+        app,
+        job_id,
+        printer_name,
+        "device.ensureQueue",
+        "running",
+        format!("正在为当前用户连接共享打印机: {}", conn_path),
+        None,
+        None,
+        Some("ensureQueue".to_string()),
+    );
+    
+    let fail = |message: String, evidence: String| {
+        eprintln!("[SessionScope] EnsurePrinterQueue step=failed evidence=\"{}\"", evidence);
+        emit_progress_event(
+            app,
+            job_id,
+            printer_name,
+            "device.ensureQueue",
+            "failed",
+            message.clone(),
+            None,
+            Some(crate::ErrorPayload {
+                code: "SESSION_CONNECTION_FAILED".to_string(),
+                detail: message.clone(),
+                stdout: None,
+                stderr: Some(evidence.clone()),
+            }),
+            Some("ensureQueue".to_string()),
+        );
+        emit_final_verify_if_needed(app, job_id, printer_name, false, Some(message.clone()));
+        InstallResult {
+            success: false,
+            message,
+            method: Some("SessionConnection".to_string()),
+            stdout: None,
+            stderr: Some(evidence),
+            effective_dry_run: false,
+            job_id: job_id.to_string(),
+        }
+    };
+    
+    // 已保存服务器凭据时先建立 SMB 会话（持有到 Add-Printer 完成后自动断开）
+    let _smb_session = match super::smb_credentials::connect_for_path(conn_path) {
+        Ok(session) => session,
+        Err(e) => {
+            return fail(
+                format!("连接共享打印服务器失败: {}\n\n连接名称: {}", e, conn_path),
+                format!("smb_connect_failed error=\"{}\" connection_name=\"{}\"", e, conn_path),
+            );
+        }
+    };
+    
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; if (Get-Printer -Name $Connection -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $Connection }) { 'EXISTS' } else { Add-Printer -ConnectionName $Connection -ErrorAction Stop; 'CREATED' }",
+    )
+    .param("Connection", conn_path)
+    .build();
+    match super::ps::run_powershell_async(script).await {
+        Ok(output) if output.status.success() => {
+            let stdout = decode_windows_string(&output.stdout);
+            let reused = stdout.trim().ends_with("EXISTS");
+            eprintln!(
+                "[SessionScope] EnsurePrinterQueue step=success action={} connection=\"{}\"",
+                if reused { "reuse" } else { "create" }, conn_path
+            );
+            let message = if reused {
+                format!("当前用户已连接该共享打印机: {}", conn_path)
+            } else {
+                format!("已为当前用户连接共享打印机: {}", conn_path)
+            };
+            emit_progress_event(
+                app,
+                job_id,
+                printer_name,
+                "device.ensureQueue",
+                "success",
+                message.clone(),
+                None,
+                None,
+                Some("ensureQueue".to_string()),
+            );
+            emit_final_verify_if_needed(app, job_id, printer_name, true, Some("安装完成".to_string()));
+            InstallResult {
+                success: true,
+                message: format!("{}\n\n安装范围: 当前用户会话（远程桌面终端服务器）", message),
+                method: Some("SessionConnection".to_string()),
+                stdout: Some(stdout),
+                stderr: None,
+                effective_dry_run: false,
+                job_id: job_id.to_string(),
+            }
+        }
+        Ok(output) => {
+            let stderr = decode_windows_string(&output.stderr);
+            fail(
+                format!("共享连接创建失败: {}\n\n连接名称: {}", stderr.trim(), conn_path),
+                format!(
+                    "add_shared_failed stderr=\"{}\" exit_code={:?} connection_name=\"{}\"",
+                    stderr, output.status.code(), conn_path
+                ),
+            )
+        }
+        Err(e) => fail(
+            format!("共享连接创建命令失败: {}\n\n连接名称: {}", e, conn_path),
+            format!("add_shared_command_failed error=\"{}\" connection_name=\"{}\"", e, conn_path),
+        ),
+    }
+}
+
 /// Windows 平台打印机安装入口
 /// 
 /// 根据 Windows 版本自动选择安装方式：
 /// - Windows 10+ (构建号 >= 10240): 使用 Add-PrinterPort + Add-Printer
 /// - Windows 7/8 (构建号 < 10240): 使用 VBS 脚本 + Add-Printer
+///
+/// 打印机配置 autoRetry=true 时，驱动类错误（STRATEGY_RETRY_CODES）会在同一 jobId 下换策略重试一次；
+/// 最终仍为 WIN_DRIVER_NOT_FOUND 且设备支持 IPP 时，改用 Microsoft IPP Class Driver 创建免驱队列（ippFallback）；
+/// 安装成功后校验队列 / 端口 / 驱动（install_verify），返回值附带每次尝试的记录与校验结果
 #[allow(non_snake_case)]
 pub async fn install_printer_windows(
     app: tauri::AppHandle,  // 用于发送进度事件
@@ -3462,15 +4412,19 @@ pub async fn install_printer_windows(
     driverKey: Option<String>,  // v2.0.0+：驱动键（用于 meta 记录）
     installMode: Option<String>,  // 安装方式："auto" | "package" | "installer" | "ipp" | "legacy_inf"（使用 camelCase 匹配前端）
     dry_run: bool,  // 测试模式：true 表示仅模拟，不执行真实安装
-) -> Result<InstallResult, String> {
+) -> Result<InstallOutcome, String> {
     
-    // 生成 jobId（一次安装=一个 jobId）
+    // 生成 jobId（一次安装=一个 jobId；提权后继续的任务沿用原任务的 jobId）
+    let resume = if dry_run { None } else { super::install_jobs::take_resume_hint(&path) };
     let timestamp = std::time::SystemTime::now()
         .duration_since(std::time::UNIX_EPOCH)
         .unwrap_or_default()
         .as_millis();
     let random_suffix = (timestamp % 10000) as u32; // 简单的随机后缀
-    let job_id = format!("job_{}_{}", timestamp, random_suffix);
+    let job_id = match &resume {
+        Some(hint) => hint.job_id.clone(),
+        None => format!("job_{}_{}", timestamp, random_suffix),
+    };
     
     eprintln!("[InstallPrinterWindows] jobId={} printer=\"{}\" installMode={:?} driverKey={:?} dry_run={}", 
         job_id, name, installMode, driverKey, dry_run);
@@ -3489,13 +4443,34 @@ pub async fn install_printer_windows(
         driverKey.clone().unwrap_or_else(|| "(unknown)".to_string())
     ));
     meta.insert("dryRun".to_string(), serde_json::Value::Bool(dry_run));
+    // 按操作细分的权限探测结果（共享连接等无需管理员的操作不因未提升而被拦截）
+    let permissions = super::permission_probe::probe();
+    meta.insert(
+        "permissions".to_string(),
+        serde_json::to_value(&permissions).unwrap_or(serde_json::Value::Null),
+    );
+    let terminal_server = super::terminal_server::status();
+    meta.insert(
+        "terminalServer".to_string(),
+        serde_json::to_value(&terminal_server).unwrap_or(serde_json::Value::Null),
+    );
+    meta.insert(
+        "concurrency".to_string(),
+        serde_json::to_value(super::install_jobs::concurrency_limits()).unwrap_or(serde_json::Value::Null),
+    );
+    if let Some(hint) = &resume {
+        meta.insert(
+            "resumedFrom".to_string(),
+            serde_json::to_value(hint).unwrap_or(serde_json::Value::Null),
+        );
+    }
     
     let init_event = crate::InstallProgressEvent {
         job_id: job_id.to_string(),
         printer_name: name.clone(),
         step_id: "job.init".to_string(),
         state: "running".to_string(),
-        message: "开始安装".to_string(),
+        message: if resume.is_some() { "已获得管理员权限，继续安装" } else { "开始安装" }.to_string(),
         ts_ms,
         progress: None,
         error: None,
@@ -3509,19 +4484,227 @@ pub async fn install_printer_windows(
     }
     eprintln!("[InstallPrinterWindows] job.init event emitted for jobId={}", job_id);
     
+    // 登记安装任务（支持 cancel_install_job 取消），函数返回时自动注销；driverPin 在此解析一次供整个任务使用
+    let job_guard = super::install_jobs::register(
+        crate::platform::ActiveInstallJob {
+            job_id: job_id.clone(),
+            printer_name: name.clone(),
+            path: path.clone(),
+            driver_key: driverKey.clone(),
+            dry_run,
+            started_at: ts_ms,
+        },
+        super::driver_pin::resolve_driver_pin(&name, &path),
+    );
+    
     // 执行安装逻辑，并在所有返回点 emit job.done
-    let result = install_printer_windows_inner(
-        app.clone(),
-        name.clone(),
-        path,
-        driverPath,
-        model,
-        driverInstallPolicy,
-        installMode,
-        dry_run,
-        &job_id,
-    ).await;
+    // 在 job 日志作用域内执行，捕获本次安装的子进程输出与内部日志
+    super::job_log::append_to_job(&job_id, &format!(
+        "[Job] START printer=\"{}\" path=\"{}\" installMode={:?} driverKey={:?} dry_run={}",
+        name, path, installMode, driverKey, dry_run
+    ));
+    if let Some(hint) = &resume {
+        super::job_log::append_to_job(&job_id, &format!(
+            "[Job] RESUME after elevation failed_step={} completed_steps={:?}",
+            hint.failed_step, hint.completed_steps
+        ));
+    }
+    let printer_options = configured_install_options(&name, &path);
+    let auto_retry = !dry_run && printer_options.auto_retry.unwrap_or(false);
+    let (result, attempts, verification) = super::job_log::scope(job_id.clone(), async {
+        let mut overrides = StrategyOverride::default();
+        let mut retry_reason: Option<String> = None;
+
+        // 端口协议预检：配置的端口被过滤而另一协议可达时，按 portProtocolAutoSelect 自动改用或在结果中给出建议
+        // （探测含 DNS 解析与 TCP 连接，放到阻塞线程；测试模式不创建端口，未开启自动改用时不探测）
+        let configured_protocol = PortProtocol::from_config(printer_options.port_protocol.as_deref());
+        let auto_select = printer_options.port_protocol_auto_select.unwrap_or(false);
+        let finding = if dry_run && !auto_select {
+            None
+        } else {
+            let probe_path = path.clone();
+            let job_scope = super::job_log::current_job_id();
+            tokio::task::spawn_blocking(move || {
+                super::job_log::sync_scope(job_scope, || probe_alternate_port_protocol(&probe_path, configured_protocol))
+            })
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("[Preflight] port_probe task failed: {}", e);
+                None
+            })
+        };
+        let protocol_note = finding.map(|finding| {
+            let alternate = finding.alternate;
+            let note = if auto_select && !finding.port_exists {
+                overrides.port_protocol = Some(alternate);
+                format!(
+                    "端口预检：{} 端口 {} 无响应，{} 端口 {} 可达，已自动改用 {} 协议",
+                    configured_protocol.as_str().to_uppercase(),
+                    finding.configured_port,
+                    alternate.as_str().to_uppercase(),
+                    alternate.default_port(),
+                    alternate.as_str().to_uppercase()
+                )
+            } else {
+                format!(
+                    "端口预检：{} 端口 {} 无响应，但 {} 端口 {} 可达，安装后可能无法打印；建议将 portProtocol 改为 \"{}\"（或开启 portProtocolAutoSelect）{}",
+                    configured_protocol.as_str().to_uppercase(),
+                    finding.configured_port,
+                    alternate.as_str().to_uppercase(),
+                    alternate.default_port(),
+                    alternate.as_str(),
+                    if finding.port_exists { "；本机已有该打印机的端口，需删除旧端口后才会按新协议创建" } else { "" }
+                )
+            };
+            super::job_log::append_to_job(&job_id, &format!("[Job] PREFLIGHT {}", note));
+            note
+        });
+        let mut attempts: Vec<crate::platform::InstallAttempt> = Vec::new();
+        let (mut result, mut attempts, mut used) = loop {
+            let mut used = EffectiveStrategy::default();
+            let result = install_printer_windows_inner(
+                app.clone(),
+                name.clone(),
+                path.clone(),
+                driverPath.clone(),
+                model.clone(),
+                driverInstallPolicy.clone(),
+                installMode.clone(),
+                dry_run,
+                &job_id,
+                &overrides,
+                &mut used,
+            ).await;
+            
+            let error_code = match &result {
+                Ok(r) if !r.success => error_code_from_stderr(r.stderr.as_deref()),
+                _ => None,
+            };
+            let next = match &error_code {
+                Some(code) if auto_retry && attempts.len() < MAX_STRATEGY_RETRIES => {
+                    next_retry_strategy(code, &used, &overrides)
+                }
+                _ => None,
+            };
+            attempts.push(crate::platform::InstallAttempt {
+                attempt: attempts.len() as u32 + 1,
+                install_mode: used.install_mode.clone(),
+                driver_install_policy: used.driver_policy.as_str().to_string(),
+                success: matches!(&result, Ok(r) if r.success),
+                error_code,
+                message: match &result {
+                    Ok(r) => r.message.clone(),
+                    Err(e) => e.clone(),
+                },
+                retry_reason: retry_reason.take(),
+            });
+            
+            match next {
+                Some((next_overrides, reason)) => {
+                    let attempt = attempts.len() as u32 + 1;
+                    super::log::write_log(&format!(
+                        "[AutoRetry] jobId={} printer=\"{}\" attempt={} reason=\"{}\"",
+                        job_id, name, attempt, reason
+                    ));
+                    // 本次尝试的失败不是最终结果，丢弃暂缓的 finalVerify 失败事件
+                    take_deferred_final_verify(&job_id);
+                    emit_strategy_retry(&app, &job_id, &name, attempt, &reason);
+                    overrides = next_overrides;
+                    retry_reason = Some(reason);
+                }
+                None => break (result, attempts, used),
+            }
+        };
+
+        // 驱动缺失且设备支持 IPP：改用 Microsoft IPP Class Driver 创建免驱队列（ippFallback=false 时不回退）
+        let ipp_fallback = !dry_run && printer_options.ipp_fallback.unwrap_or(true);
+        let driver_missing = attempts.last().and_then(|a| a.error_code.as_deref()) == Some("WIN_DRIVER_NOT_FOUND");
+        if ipp_fallback && driver_missing {
+            let fallback = match &result {
+                Ok(original) => try_ipp_class_driver_fallback(&app, &job_id, &name, &path, original).await,
+                Err(_) => None,
+            };
+            if let Some((fallback_result, driver_name)) = fallback {
+                attempts.push(crate::platform::InstallAttempt {
+                    attempt: attempts.len() as u32 + 1,
+                    install_mode: "ipp_class_driver".to_string(),
+                    driver_install_policy: used.driver_policy.as_str().to_string(),
+                    success: fallback_result.success,
+                    error_code: error_code_from_stderr(fallback_result.stderr.as_deref()),
+                    message: fallback_result.message.clone(),
+                    retry_reason: Some("未找到匹配的厂商驱动，设备支持 IPP，改用 Microsoft IPP Class Driver".to_string()),
+                });
+                used.install_mode = "ipp_class_driver".to_string();
+                used.driver_names = vec![driver_name].into_iter().filter(|n| !n.is_empty()).collect();
+                result = Ok(fallback_result);
+            }
+        }
+
+        if let (Some(note), Ok(install_result)) = (&protocol_note, &mut result) {
+            install_result.message = format!("{}\n{}", install_result.message, note);
+        }
+
+        // 安装成功后设置打印处理器 / 数据类型（共享连接的设置由打印服务器决定，跳过）
+        let is_connection = matches!(detect_target_type(&path), Ok(TargetType::SharedConnection { .. }));
+        if let Ok(install_result) = &mut result {
+            if install_result.success && !dry_run && !is_connection {
+                if let Err(e) = super::print_processor::apply_print_processor(
+                    &name,
+                    printer_options.print_processor.as_deref(),
+                    printer_options.datatype.as_deref(),
+                ) {
+                    let error = InstallError::PrintProcessorFailed { reason: e };
+                    install_result.success = false;
+                    install_result.message = error.to_user_message();
+                    install_result.stderr = error.format_stderr_with_code(install_result.stderr.take());
+                }
+            }
+        }
+        
+        // 安装后校验：读回队列、端口地址与驱动，并按 verifyProbe 探测（不改变安装结果）
+        let verification = match &result {
+            Ok(install_result) if install_result.success && !dry_run => {
+                let address = if is_connection {
+                    None
+                } else {
+                    crate::platform::printer_address::PrinterAddress::parse(&path).ok()
+                };
+                let probe = super::install_verify::VerifyProbe::from_config(printer_options.verify_probe.as_deref());
+                // 读回队列（PowerShell）与探测（TCP / Spooler 作业）都是阻塞调用，放到阻塞线程
+                let (verify_name, driver_names) = (name.clone(), used.driver_names.clone());
+                let job_scope = super::job_log::current_job_id();
+                let verification = tokio::task::spawn_blocking(move || {
+                    super::job_log::sync_scope(job_scope, || {
+                        super::install_verify::verify_installed_queue(&verify_name, address.as_ref(), &driver_names, probe)
+                    })
+                })
+                .await;
+                match verification {
+                    Ok(verification) => {
+                        super::job_log::append_to_job(&job_id, &format!(
+                            "[Job] VERIFY verified={} detail={}",
+                            verification.verified, verification.detail
+                        ));
+                        Some(verification)
+                    }
+                    Err(e) => {
+                        super::job_log::append_to_job(&job_id, &format!("[Job] VERIFY task failed: {}", e));
+                        None
+                    }
+                }
+            }
+            _ => None,
+        };
+        (result, attempts, verification)
+    }).await;
     
+    // 所有尝试结束后再发送暂缓的 finalVerify 失败事件（最终成功时丢弃）
+    if let Some(message) = take_deferred_final_verify(&job_id) {
+        if !matches!(&result, Ok(r) if r.success) {
+            emit_final_verify_event(&app, &job_id, &name, false, message);
+        }
+    }
+
     // 在所有返回点之前 emit job.done
     match &result {
         Ok(install_result) => {
@@ -3561,7 +4744,18 @@ pub async fn install_printer_windows(
         }
     }
     
-    result
+    let done_line = match &result {
+        Ok(r) => format!("[Job] DONE success={} method={:?} message={}", r.success, r.method, r.message),
+        Err(e) => format!("[Job] DONE success=false error={}", e),
+    };
+    super::job_log::append_to_job(&job_id, &done_line);
+    
+    // 成功删除任务工作目录，失败保留用于排查
+    let succeeded = matches!(&result, Ok(r) if r.success);
+    super::job_workdir::finish(&job_id, succeeded);
+    job_guard.finish(succeeded);
+    
+    result.map(|result| InstallOutcome { result, attempts, verification })
 }
 
 /// 内部安装逻辑（不 emit job.done，由外层函数负责）
@@ -3576,38 +4770,29 @@ async fn install_printer_windows_inner(
     installMode: Option<String>,
     dry_run: bool,
     job_id: &str,
+    overrides: &StrategyOverride,
+    used: &mut EffectiveStrategy,
 ) -> Result<InstallResult, String> {
     
     // 打印接收到的参数
     eprintln!("[InstallPrinterWindows] received installMode={:?} dry_run={}", installMode, dry_run);
     
-    // ============================================================================
-    // Preflight: 检查管理员权限（在所有安装分支前）
-    // ============================================================================
-    let is_admin = is_running_as_admin();
-    eprintln!("[Preflight] is_admin={} printer=\"{}\" path=\"{}\"", is_admin, name, path);
-    
     // 先推导 effective_* 字段（用于 dry_run 和实际安装）
-    let (effective_spec, matched_printer) = match crate::load_local_config() {
+    let (mut effective_spec, matched_printer) = match crate::load_local_config() {
         Ok((config, _)) => {
-            let mut matched_printer: Option<&crate::Printer> = None;
-            'outer: for city in &config.cities {
-                for area in &city.areas {
-                    for printer in &area.printers {
-                        if printer.name == name || printer.path == path {
-                            matched_printer = Some(printer);
-                            break 'outer;
-                        }
-                    }
-                }
-            }
+            // 匹配到的打印机已合并区域级默认安装选项
+            let matched_printer = crate::find_printer_with_defaults(&config, &name, &path);
             
             if let Some(printer) = matched_printer {
-                let effective_spec = crate::resolve_effective_driver_spec(
-                    printer,
+                let mut effective_spec = crate::resolve_effective_driver_spec(
+                    &printer,
                     config.driver_catalog.as_ref(),
                 );
-                (effective_spec, Some(printer.clone()))
+                // 调用方传入的 installMode 已按 "前端 → 打印机/区域配置 → driverCatalog → auto" 决策，优先生效
+                if installMode.is_some() {
+                    effective_spec.effective_install_mode = installMode.clone();
+                }
+                (effective_spec, Some(printer))
             } else {
                 let effective_spec = crate::EffectiveDriverSpec {
                     source: "legacy".to_string(),
@@ -3616,6 +4801,7 @@ async fn install_printer_windows_inner(
                     effective_driver_names: vec![],
                     driver_key_used: None,
                     remote_driver: None,
+                    compatibility: None,
                 };
                 (effective_spec, None)
             }
@@ -3628,13 +4814,70 @@ async fn install_printer_windows_inner(
                 effective_driver_names: vec![],
                 driver_key_used: None,
                 remote_driver: None,
+                compatibility: None,
             };
             (effective_spec, None)
         }
     };
     
+    // 自动重试：覆盖本次尝试的安装策略，并记录实际使用的策略
+    if let Some(mode) = &overrides.install_mode {
+        eprintln!("[AutoRetry] override installMode {:?} -> {}", effective_spec.effective_install_mode, mode);
+        effective_spec.effective_install_mode = Some(mode.clone());
+    }
+    used.install_mode = effective_spec.effective_install_mode.clone().unwrap_or_else(|| "auto".to_string());
+    used.driver_policy = overrides
+        .driver_policy
+        .unwrap_or_else(|| DriverInstallPolicy::from_str(driverInstallPolicy.as_deref()));
+    used.driver_names = effective_spec.effective_driver_names.clone();
+    
     let resolved_install_mode = effective_spec.effective_install_mode.clone();
     let resolved_driver_path = effective_spec.effective_driver_path.clone();
+    let port_protocol = overrides.port_protocol.unwrap_or_else(|| {
+        PortProtocol::from_config(matched_printer.as_ref().and_then(|p| p.options.port_protocol.as_deref()))
+    });
+    let install_scope = InstallScope::from_config(
+        matched_printer.as_ref().and_then(|p| p.options.install_scope.as_deref()),
+    );
+    
+    // Preflight: 远程桌面会话主机（RDS）提示
+    let terminal_server = super::terminal_server::status();
+    for warning in &terminal_server.warnings {
+        super::log::write_log(&format!("[Preflight] terminal_server warning=\"{}\"", warning));
+    }
+    let session_scope = install_scope.is_session(terminal_server.is_terminal_server);
+    eprintln!(
+        "[Preflight] install_scope={:?} is_terminal_server={} session_scope={}",
+        install_scope, terminal_server.is_terminal_server, session_scope
+    );
+    
+    // Preflight: 驱动支持的 Windows 版本范围（driverCatalog[].compatibility，dryRun 同样检查）
+    if let Some(compatibility) = &effective_spec.compatibility {
+        match get_windows_build_number() {
+            Ok(build) => {
+                let is_server = super::session_cache::is_server_edition();
+                if let Err(reason) = compatibility.check(build, is_server) {
+                    super::log::write_log(&format!(
+                        "[Preflight] unsupported_os printer=\"{}\" build={} server={} supported=\"{}\"",
+                        name, build, is_server, compatibility.describe()
+                    ));
+                    let error = InstallError::UnsupportedOs { reason };
+                    return Ok(InstallResult {
+                        success: false,
+                        message: error.to_user_message(),
+                        method: None,
+                        stdout: None,
+                        stderr: error.format_stderr_with_code(None),
+                        effective_dry_run: dry_run,
+                        job_id: job_id.to_string(),
+                    });
+                }
+            }
+            Err(e) => {
+                super::log::write_log(&format!("[Preflight] 无法检测 Windows 构建号，跳过驱动版本范围检查: {}", e));
+            }
+        }
+    }
     
     // 如果是 dryRun 模式，执行模拟安装流程
     if dry_run {
@@ -3648,8 +4891,29 @@ async fn install_printer_windows_inner(
         ).await;
     }
     
+    // 会话范围：共享打印机只为当前用户建立连接；TCP/IP 打印机没有按用户安装的方式，仍按本机安装
+    if session_scope {
+        match detect_target_type(&path) {
+            Ok(TargetType::SharedConnection { path: conn_path }) => {
+                return Ok(install_shared_connection_for_session(&app, job_id, &name, &conn_path).await);
+            }
+            _ => {
+                super::log::write_log(&format!(
+                    "[Preflight] install_scope=session not applicable to TCP/IP printer, installing machine-wide printer=\"{}\"",
+                    name
+                ));
+            }
+        }
+    }
+    
     eprintln!("[InstallPrinterWindows] dryRun=false, entering real installation path");
     
+    // 远程驱动包：同一 driver_uuid 的下载 / 解压 / 暂存串行执行（持有到本次安装结束），不同驱动互不影响
+    let _driver_lock = match &effective_spec.remote_driver {
+        Some(remote) => Some(super::driver_locks::lock(&super::driver_fetch::driver_uuid_for_sha256(&remote.sha256)).await),
+        None => None,
+    };
+    
     // ============================================================================
     // 路由策略枚举
     // ============================================================================
@@ -3848,7 +5112,7 @@ async fn install_printer_windows_inner(
                                 effective_path,
                                 Some(&app),
                                 Some(&name),
-                                &job_id,
+                                job_id,
                             ).await {
                                 Ok(_bootstrap_result) => {
                                     eprintln!("[DriverBootstrap] step=bootstrap_complete");
@@ -3903,13 +5167,17 @@ async fn install_printer_windows_inner(
                                 Err(bootstrap_error) => {
                                     let error_msg = format!("Bootstrap 失败: {}", bootstrap_error);
                                     eprintln!("[DriverBootstrap] step=bootstrap_failed error=\"{}\"", error_msg);
+                                    let stderr = match bootstrap_error.code() {
+                                        Some(code) => format!("[EASYPRINTER_CODE={}] {}", code, error_msg),
+                                        None => error_msg.clone(),
+                                    };
                                     
                                     return Ok(InstallResult {
                                         success: false,
-                                        message: error_msg.clone(),
+                                        message: error_msg,
                                         method: None,
                                         stdout: None,
-                                        stderr: Some(error_msg),
+                                        stderr: Some(stderr),
                                         effective_dry_run: dry_run,
                                         job_id: job_id.to_string(),
                                     });
@@ -3978,6 +5246,21 @@ async fn install_printer_windows_inner(
     let has_driver_names = !resolved_driver_names.is_empty() && resolved_driver_names.iter().any(|n| !n.trim().is_empty());
     let has_driver_package = resolved_install_mode == "package";
     
+    // 驱动冲突检测：同名驱动版本不同会原地更新，影响其他已安装队列（仅告警，不阻断安装）
+    if let (Some(inf_path), true) = (&inf_abs_path, has_driver_names) {
+        match crate::platform::windows::driver_conflict::detect_driver_conflicts(inf_path, &resolved_driver_names, &name) {
+            Ok(check) if check.has_conflict => {
+                eprintln!("[DriverConflict] WARN printer=\"{}\" status={} message=\"{}\"", name, check.status, check.message);
+            }
+            Ok(check) => {
+                eprintln!("[DriverConflict] printer=\"{}\" status={}", name, check.status);
+            }
+            Err(e) => {
+                eprintln!("[DriverConflict] printer=\"{}\" check_failed error=\"{}\"", name, e);
+            }
+        }
+    }
+    
     // 确定路由策略（使用 effective_install_mode）
     let routing_policy = if resolved_install_mode == "package" {
         // 当 installMode="package"：强制 modern_only（禁止 PrintUIEntry）
@@ -3994,6 +5277,9 @@ async fn install_printer_windows_inner(
     eprintln!("[RoutingDecision] policy={:?} inputs=installMode={:?} driverPackage={} driverPath={} driver_name={} model={} target_path=\"{}\"", 
         routing_policy, resolved_install_mode, has_driver_package, inf_abs_path.is_some(), has_driver_names, has_model, path);
     
+    // 驱动安装与队列创建占用磁盘 / CPU，受全局并发限制（函数返回时释放）
+    let _install_slot = super::install_jobs::acquire_install_slot(job_id).await;
+    
     // ============================================================================
     // 路由决策：选择安装路径
     // ============================================================================
@@ -4001,7 +5287,7 @@ async fn install_printer_windows_inner(
     // 优先级 1：如果有 driver package（或已选择 package 模式）
     if has_driver_package {
         eprintln!("[RoutingDecision] selected_path=package reason=installMode_is_package");
-        return install_printer_package_branch(&app, &job_id, &name.clone(), name.clone(), path, inf_abs_path.clone(), model, dry_run, Some(resolved_driver_names.clone())).await;
+        return install_printer_package_branch(&app, job_id, &name.clone(), name.clone(), path, inf_abs_path.clone(), model, dry_run, Some(resolved_driver_names.clone()), port_protocol).await;
     }
     
     // 优先级 2：如果没有 package，但有 INF（resolved_driver_path）
@@ -4065,7 +5351,7 @@ async fn install_printer_windows_inner(
             // 发送 StageDriver 开始事件
             emit_progress_event(
                 &app,
-                &job_id,
+                job_id,
                 &name,
                 "driver.stageDriver",
                 "running",
@@ -4076,32 +5362,24 @@ async fn install_printer_windows_inner(
             );
             
             // 使用 SetupAPI（fallback 到 pnputil）stage 驱动包
-            let (published_name, published_inf_path, used_fallback, stage_evidence) = match stage_driver_with_setupapi_fallback(&inf_path) {
-                Ok((name, path, fallback, evidence)) => {
-                    if fallback {
-                        eprintln!("[ModernInf] step=stage_driver result=success method=pnputil_fallback published_name=\"{}\"", name);
-                    } else {
-                        eprintln!("[ModernInf] step=stage_driver result=success method=SetupCopyOEMInfW published_name=\"{}\"", name);
-                    }
+            let (published_name, published_inf_path, stage_mechanism, stage_evidence) = match stage_driver_with_setupapi_fallback(&inf_path, std::slice::from_ref(&driver_name)) {
+                Ok((name, path, mechanism, evidence)) => {
+                    eprintln!("[ModernInf] step=stage_driver result=success method={} published_name=\"{}\"", mechanism.as_str(), name);
                     
                     // 发送 StageDriver 成功事件
                     emit_progress_event(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         "driver.stageDriver",
                         "success",
-                        if fallback {
-                            "驱动包注册成功（已回退到 pnputil）".to_string()
-                        } else {
-                            "驱动包注册成功（SetupAPI）".to_string()
-                        },
+                        mechanism.success_message(),
                         None,
                         None,
                         Some("stageDriver".to_string()),
                     );
                     
-                    (name, path, fallback, evidence)
+                    (name, path, mechanism, evidence)
                 }
                 Err(e) => {
                     let evidence = format!("stage_driver_failed error=\"{}\"", e);
@@ -4110,7 +5388,7 @@ async fn install_printer_windows_inner(
                     // 发送 StageDriver 失败事件
                     emit_progress_event(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         "driver.stageDriver",
                         "failed",
@@ -4150,13 +5428,13 @@ async fn install_printer_windows_inner(
                     }
                 }
             };
-            eprintln!("[ModernInf] step=register_driver inputs=driver_name=\"{}\" published_inf_path=\"{}\"", 
-                driver_name, published_inf_path);
+            eprintln!("[ModernInf] step=register_driver inputs=driver_name=\"{}\" published_inf_path=\"{}\" stage_mechanism={}", 
+                driver_name, published_inf_path, stage_mechanism.as_str());
             
             // 发送 RegisterDriver 开始事件
             emit_progress_event(
                 &app,
-                &job_id,
+                job_id,
                 &name,
                 "driver.registerDriver",
                 "running",
@@ -4173,7 +5451,7 @@ async fn install_printer_windows_inner(
                     // 发送 RegisterDriver 成功事件
                     emit_progress_event(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         "driver.registerDriver",
                         "success",
@@ -4240,7 +5518,7 @@ async fn install_printer_windows_inner(
                     // 发送 EnsurePort 开始事件
                     emit_progress_event(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         "device.ensurePort",
                         "running",
@@ -4250,14 +5528,17 @@ async fn install_printer_windows_inner(
                         Some("ensurePort".to_string()),
                     );
                     
-                    let port_name = match ensure_printer_port(&host, 9100, is_legacy, &job_id) {
+                    let report_port_phase = |phase: PortPhase, attempt: Option<(u32, u32)>| {
+                        emit_port_phase_event(&app, job_id, &name, phase, &host, attempt);
+                    };
+                    let port_name = match ensure_printer_port(&host, port_protocol, is_legacy, job_id, &report_port_phase) {
                         Ok(port) => {
                             eprintln!("[ModernInf] step=ensure_port result=success port_name=\"{}\"", port);
                             
                             // 发送 EnsurePort 成功事件
                             emit_progress_event(
                                 &app,
-                                &job_id,
+                                job_id,
                                 &name,
                                 "device.ensurePort",
                                 "success",
@@ -4304,7 +5585,7 @@ async fn install_printer_windows_inner(
                     // 发送 EnsureQueue 开始事件
                     emit_progress_event(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         "device.ensureQueue",
                         "running",
@@ -4321,7 +5602,7 @@ async fn install_printer_windows_inner(
                             // 发送 EnsureQueue 成功事件
                             emit_progress_event(
                                 &app,
-                                &job_id,
+                                job_id,
                                 &name,
                                 "device.ensureQueue",
                                 "success",
@@ -4334,7 +5615,7 @@ async fn install_printer_windows_inner(
                             // 发送 FinalVerify 成功事件
                             emit_progress_event(
                                 &app,
-                                &job_id,
+                                job_id,
                                 &name,
                                 "device.finalVerify",
                                 "success",
@@ -4396,9 +5677,12 @@ async fn install_printer_windows_inner(
                     eprintln!("[ModernInf] step=ensure_queue_shared inputs=connection_name=\"{}\" driver_name=\"{}\"", 
                         conn_path, driver_name);
                     
-                    let parts: Vec<&str> = conn_path.split('\\').filter(|s| !s.is_empty()).collect();
-                    if parts.len() < 2 {
-                        let evidence = format!("InvalidSharedConnectionName connection_name=\"{}\" parts_count={}", conn_path, parts.len());
+                    let is_share = matches!(
+                        crate::platform::printer_address::PrinterAddress::parse(&conn_path),
+                        Ok(crate::platform::printer_address::PrinterAddress::SmbShare { .. })
+                    );
+                    if !is_share {
+                        let evidence = format!("InvalidSharedConnectionName connection_name=\"{}\"", conn_path);
                         eprintln!("[ModernInf] step=ensure_queue_shared result=error evidence=\"{}\"", evidence);
                         return Ok(InstallResult {
                             success: false,
@@ -4412,13 +5696,13 @@ async fn install_printer_windows_inner(
                     }
                     
                     // 修复：使用 Where-Object 精确过滤，避免 Get-Printer -Name 的通配符匹配导致误判
-                    let escaped_conn_path = conn_path.replace("'", "''");
-                    let check_shared_script = format!(
-                        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $q = '{}'; $printer = Get-Printer -Name $q -ErrorAction SilentlyContinue | Where-Object {{ $_.Name -eq $q }} | Select-Object -ExpandProperty Name",
-                        escaped_conn_path
-                    );
+                    let check_shared_script = PsScript::new(
+                        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $printer = Get-Printer -Name $Connection -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $Connection } | Select-Object -ExpandProperty Name",
+                    )
+                    .param("Connection", &conn_path)
+                    .build();
                     
-                    let queue_exists = match super::ps::run_powershell(&check_shared_script) {
+                    let queue_exists = match super::ps::run_powershell_async(check_shared_script).await {
                         Ok(output) => {
                             let stdout = decode_windows_string(&output.stdout);
                             // 二次确认：验证返回的名称是否完全等于 conn_path
@@ -4442,12 +5726,30 @@ async fn install_printer_windows_inner(
                             job_id: job_id.to_string(),
                         });
                     } else {
-                        let add_shared_script = format!(
-                            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-Printer -ConnectionName '{}' -ErrorAction Stop",
-                            conn_path.replace("'", "''")
-                        );
+                        // 已保存服务器凭据时先建立 SMB 会话（持有到 Add-Printer 完成后自动断开）
+                        let _smb_session = match super::smb_credentials::connect_for_path(&conn_path) {
+                            Ok(session) => session,
+                            Err(e) => {
+                                let evidence = format!("smb_connect_failed error=\"{}\"", e);
+                                eprintln!("[ModernInf] step=ensure_queue_shared result=error evidence=\"{}\"", evidence);
+                                return Ok(InstallResult {
+                                    success: false,
+                                    message: format!("连接共享打印服务器失败: {}\n\nEvidence: {}", e, evidence),
+                                    method: Some("ModernInf".to_string()),
+                                    stdout: Some(stage_evidence.clone()),
+                                    stderr: Some(evidence),
+                                    effective_dry_run: dry_run,
+                                    job_id: job_id.to_string(),
+                                });
+                            }
+                        };
+                        let add_shared_script = PsScript::new(
+                            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; Add-Printer -ConnectionName $Connection -ErrorAction Stop",
+                        )
+                        .param("Connection", &conn_path)
+                        .build();
                         
-                        match super::ps::run_powershell(&add_shared_script) {
+                        match super::ps::run_powershell_async(add_shared_script).await {
                             Ok(output) => {
                                 let stdout = decode_windows_string(&output.stdout);
                                 let stderr = decode_windows_string(&output.stderr);
@@ -4575,7 +5877,7 @@ async fn install_printer_windows_inner(
     eprintln!("[RoutingDecision] selected_path=legacy_printui reason=fallback_to_legacy");
     
     // 解析驱动安装策略
-    let policy = DriverInstallPolicy::from_str(driverInstallPolicy.as_deref());
+    let policy = used.driver_policy;
     eprintln!("[INFO] 驱动安装策略: {:?}", policy);
     
     // 使用 effective_* 字段（resolved_driver_names 和 resolved_driver_path）
@@ -4593,11 +5895,12 @@ async fn install_printer_windows_inner(
                 // 使用 PrintUIEntry /if 路径
                 eprintln!("[INFO] 检测到 driver_path 和 model，使用 PrintUIEntry /if 安装路径");
                 
-                // 从路径中提取 IP 地址（格式：\\192.168.x.x）
-                let ip_address = path.trim_start_matches("\\\\").to_string();
+                // 从路径中提取主机地址（格式：\\192.168.x.x / \\fd00::12 / \\printer.corp.local）
+                let address = crate::platform::printer_address::PrinterAddress::parse(&path)?;
+                let ip_address = address.host_address();
                 
-                // 端口名格式：IP_IP地址（用下划线替换点）
-                let port_name = format!("IP_{}", ip_address.replace(".", "_"));
+                // 端口名格式：IP_地址（用下划线替换 . 和 :）
+                let port_name = address.port_name();
                 
                 // 检测 Windows 构建号来判断是否支持 Add-PrinterPort
                 let windows_build = get_windows_build_number().unwrap_or(0);
@@ -4619,7 +5922,7 @@ async fn install_printer_windows_inner(
                 // 创建端口
                 if use_modern_method {
                     // Windows 10+ 使用 Add-PrinterPort
-                    match add_printer_port_modern(&port_name, &ip_address) {
+                    match add_printer_port_modern(&port_name, &ip_address, port_protocol) {
                         Err(e) => {
                             let (stdout, stderr) = e.get_output();
                             return Ok(InstallResult {
@@ -4641,8 +5944,8 @@ async fn install_printer_windows_inner(
                     let script_path = write_vbs_script_to_temp()
                         .map_err(|e| e.to_user_message())?;
                     
-                    match add_printer_port_vbs(&script_path, &port_name, &ip_address, &job_id) {
-                        Err(result) => return Ok(result),
+                    match add_printer_port_vbs(&script_path, &port_name, &ip_address, port_protocol, job_id) {
+                        Err(result) => return Ok(*result),
                         Ok(_) => {
                             eprintln!("[DEBUG] 端口创建成功（VBS），继续使用 PrintUIEntry 安装打印机");
                         }
@@ -4650,14 +5953,14 @@ async fn install_printer_windows_inner(
                 }
                 
                 // 使用 PrintUIEntry /if 安装打印机（同时导入驱动）
-                match install_printer_with_printui(&name, &inf_path, &port_name, model_str, &job_id) {
+                match install_printer_with_printui(&name, inf_path, &port_name, model_str, job_id) {
                     Ok(result) => {
                         // 安装成功后写入 ePrinty tag
                         if result.success {
                             // 发送 FinalVerify 成功事件
                             emit_final_verify_if_needed(
                                 &app,
-                                &job_id,
+                                job_id,
                                 &name,
                                 true,
                                 Some("安装完成".to_string()),
@@ -4739,7 +6042,7 @@ async fn install_printer_windows_inner(
                     .map(|names| names.as_slice())
                     .unwrap_or(&[]);
                 
-                match install_inf_driver(&inf_path, driver_names_for_install) {
+                match install_inf_driver(&app, job_id, &name, inf_path, driver_names_for_install).await {
                     Ok(()) => {
                         eprintln!("[DEBUG] INF 驱动安装成功");
                         inf_installed = true;
@@ -4820,7 +6123,7 @@ async fn install_printer_windows_inner(
                             
                             // 安装 INF 驱动
                             // install_inf_driver 内部已经验证了 driver_names，如果成功则说明驱动已注册
-                            match install_inf_driver(&inf_path, &names) {
+                            match install_inf_driver(&app, job_id, &name, inf_path, &names).await {
                                 Ok(()) => {
                                     eprintln!("[DEBUG] INF 驱动安装成功");
                                     inf_installed = true;
@@ -4936,11 +6239,12 @@ async fn install_printer_windows_inner(
         }
     };
     
-    // 从路径中提取 IP 地址（格式：\\192.168.x.x）
-    let ip_address = path.trim_start_matches("\\\\").to_string();
+    // 从路径中提取主机地址（格式：\\192.168.x.x / \\fd00::12 / \\printer.corp.local）
+    let address = crate::platform::printer_address::PrinterAddress::parse(&path)?;
+    let ip_address = address.host_address();
     
-    // 端口名格式：IP_IP地址（用下划线替换点）
-    let port_name = format!("IP_{}", ip_address.replace(".", "_"));
+    // 端口名格式：IP_地址（用下划线替换 . 和 :）
+    let port_name = address.port_name();
     
     // 检测 Windows 构建号来判断是否支持 Add-PrinterPort
     // Windows 10 (10240+) 和 Windows 11 (22000+) 都支持 Add-PrinterPort
@@ -4971,7 +6275,7 @@ async fn install_printer_windows_inner(
         eprintln!("[DEBUG] 使用 Add-PrinterPort 方式安装");
         // Windows 10+ 使用 Add-PrinterPort + Add-Printer（现代方式）
         // 步骤1：添加打印机端口（如果不存在则创建，如果已存在则忽略错误）
-        match add_printer_port_modern(&port_name, &ip_address) {
+        match add_printer_port_modern(&port_name, &ip_address, port_protocol) {
             Err(e) => {
                 // 端口添加失败，构造 InstallResult 并返回
                 let (stdout, stderr) = e.get_output();
@@ -4998,14 +6302,14 @@ async fn install_printer_windows_inner(
         }
         
         // 步骤2：使用选中的驱动添加打印机
-        let result = add_printer_with_driver_modern(&name, &port_name, &ip_address, &selected_driver, &job_id);
+        let result = add_printer_with_driver_modern(&name, &port_name, &ip_address, &selected_driver, job_id);
         
         // 如果安装成功，写入 ePrinty tag 并发送 finalVerify
         if result.success {
             // 发送 FinalVerify 成功事件
             emit_final_verify_if_needed(
                 &app,
-                &job_id,
+                job_id,
                 &name,
                 true,
                 Some("安装完成".to_string()),
@@ -5032,18 +6336,18 @@ async fn install_printer_windows_inner(
             .map_err(|e| e.to_user_message())?;
         
         // 步骤2：使用 cscript 运行 prnport.vbs 脚本添加端口
-        match add_printer_port_vbs(&script_path, &port_name, &ip_address, &job_id) {
-            Err(result) => Ok(result),
+        match add_printer_port_vbs(&script_path, &port_name, &ip_address, port_protocol, job_id) {
+            Err(result) => Ok(*result),
             Ok(_) => {
                 // 步骤3：端口添加成功，现在使用 PowerShell Add-Printer 安装打印机
-                let result = add_printer_with_driver_vbs(&name, &port_name, &ip_address, &selected_driver, &job_id);
+                let result = add_printer_with_driver_vbs(&name, &port_name, &ip_address, &selected_driver, job_id);
                 
                 // 如果安装成功，写入 ePrinty tag 并发送 finalVerify
                 if result.success {
                     // 发送 FinalVerify 成功事件
                     emit_final_verify_if_needed(
                         &app,
-                        &job_id,
+                        job_id,
                         &name,
                         true,
                         Some("安装完成".to_string()),
@@ -5066,3 +6370,34 @@ async fn install_printer_windows_inner(
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn used(install_mode: &str, driver_policy: DriverInstallPolicy) -> EffectiveStrategy {
+        EffectiveStrategy { install_mode: install_mode.to_string(), driver_policy, driver_names: Vec::new() }
+    }
+
+    #[test]
+    fn retry_strategy_switches_mode_then_policy() {
+        let none = StrategyOverride::default();
+
+        let (next, reason) = next_retry_strategy("WIN_DRIVER_NOT_FOUND", &used("package", DriverInstallPolicy::ReuseIfInstalled), &none).unwrap();
+        assert_eq!(next.install_mode.as_deref(), Some("legacy_inf"));
+        assert!(next.driver_policy.is_none());
+        assert!(reason.contains("package -> legacy_inf"));
+
+        let (next, reason) = next_retry_strategy("WIN_INF_INSTALL_FAILED", &used("auto", DriverInstallPolicy::ReuseIfInstalled), &none).unwrap();
+        assert!(matches!(next.driver_policy, Some(DriverInstallPolicy::Always)));
+        assert!(reason.contains("reuse_if_installed -> always"));
+
+        // Add-Printer 0x80070705：驱动未知
+        let (next, _) = next_retry_strategy("WIN_UNKNOWN_PRINTER_DRIVER", &used("package", DriverInstallPolicy::Always), &none).unwrap();
+        assert_eq!(next.install_mode.as_deref(), Some("legacy_inf"));
+
+        // 没有可调整的策略、错误码不可重试时不重试
+        assert!(next_retry_strategy("WIN_DRIVER_NOT_FOUND", &used("auto", DriverInstallPolicy::Always), &none).is_none());
+        assert!(next_retry_strategy("WIN_ACCESS_DENIED", &used("package", DriverInstallPolicy::ReuseIfInstalled), &none).is_none());
+    }
+}
@@ -0,0 +1,282 @@
+// Windows 平台安装任务管理模块
+// 记录进行中的安装任务（jobId）及其取消标志，供前端"取消安装"使用
+//
+// - register(): 安装开始时登记（含打印机名称 / 路径，供退出时保存未完成任务），返回的 JobGuard 在 Drop 时注销
+// - JobGuard::finish() / outcome(): 记录 / 查询已结束任务的结果（退出时区分已完成与被取消的任务，只保留最近 MAX_OUTCOMES 个）
+// - cancel() / cancel_all(): 置位取消标志（任务不存在或已结束时返回 false）
+// - active_jobs(): 进行中的任务列表（关闭窗口时等待 / 取消）
+// - current_cancel_flag(): 在 job_log 作用域内获取当前任务的取消标志
+// - current_job(): 在 job_log 作用域内获取当前任务的登记信息（打印机名称 / 路径）
+// - set_resume_hint() / take_resume_hint(): 提权后继续安装时，按打印机路径交接原任务的 jobId 与步骤
+//
+// 取消是协作式的：只有检查取消标志的步骤（目前为 pnputil 驱动安装）会终止子进程并提前结束
+//
+// 并发限制（老旧办公电脑上并行安装会占满磁盘 / CPU）：
+// - acquire_extraction_slot(): 驱动包解压名额（默认同时 1 个）
+// - acquire_install_slot(): 安装名额（驱动安装 + 端口 / 队列创建，默认同时 1 个）
+// - 低配机器（物理内存 < 4 GB 或逻辑 CPU ≤ 2）解压与安装共用 1 个名额，不再并行
+// - 同一任务先解压、释放后再申请安装名额，不会同时持有两个名额
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Instant;
+
+use serde::Serialize;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::platform::{ActiveInstallJob, InstallResumeHint};
+use crate::DriverPin;
+
+/// 默认同时进行的驱动包解压数
+const DEFAULT_MAX_EXTRACTIONS: usize = 1;
+
+/// 默认同时进行的安装数
+const DEFAULT_MAX_INSTALLS: usize = 1;
+
+/// 物理内存低于该值（MB）视为低配机器
+const LOW_MEMORY_THRESHOLD_MB: u64 = 4096;
+
+/// 逻辑 CPU 数不超过该值视为低配机器
+const LOW_CPU_THRESHOLD: usize = 2;
+
+/// 保留的已结束任务结果数
+const MAX_OUTCOMES: usize = 32;
+
+/// 已登记的任务
+struct JobEntry {
+    cancel: Arc<AtomicBool>,
+    info: ActiveInstallJob,
+    /// 登记时解析的 driverPin（Err 为无法确认配置，校验时按不一致处理）
+    driver_pin: Result<Option<DriverPin>, String>,
+}
+
+/// 进行中的安装任务（key = jobId）
+static JOBS: Mutex<Option<HashMap<String, JobEntry>>> = Mutex::new(None);
+
+fn with_jobs<R>(f: impl FnOnce(&mut HashMap<String, JobEntry>) -> R) -> R {
+    let mut guard = JOBS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+/// 最近结束的任务结果（jobId, 是否成功），按结束顺序
+static OUTCOMES: Mutex<VecDeque<(String, bool)>> = Mutex::new(VecDeque::new());
+
+/// 已登记任务的守卫（Drop 时注销）
+pub struct JobGuard {
+    job_id: String,
+}
+
+impl JobGuard {
+    /// 记录任务的最终结果（注销前调用；失败包括已响应取消）
+    pub fn finish(&self, success: bool) {
+        let mut outcomes = OUTCOMES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if outcomes.len() >= MAX_OUTCOMES {
+            outcomes.pop_front();
+        }
+        outcomes.push_back((self.job_id.clone(), success));
+    }
+}
+
+/// 已结束任务的结果
+///
+/// # 返回
+/// - `Some(true)` / `Some(false)`: 任务成功 / 失败（含取消）
+/// - `None`: 任务仍在进行、结束时未记录结果或记录已被淘汰
+pub fn outcome(job_id: &str) -> Option<bool> {
+    let outcomes = OUTCOMES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    outcomes.iter().rev().find(|(id, _)| id == job_id).map(|(_, success)| *success)
+}
+
+impl Drop for JobGuard {
+    fn drop(&mut self) {
+        with_jobs(|jobs| {
+            jobs.remove(&self.job_id);
+        });
+    }
+}
+
+/// 登记安装任务
+///
+/// # 参数
+/// - `info`: 任务信息（job_id 为登记键）
+/// - `driver_pin`: 创建任务时解析的 driverPin，任务内的驱动校验据此进行，不再重复读取配置
+pub fn register(info: ActiveInstallJob, driver_pin: Result<Option<DriverPin>, String>) -> JobGuard {
+    let job_id = info.job_id.clone();
+    with_jobs(|jobs| {
+        jobs.insert(job_id.clone(), JobEntry { cancel: Arc::new(AtomicBool::new(false)), info, driver_pin });
+    });
+    JobGuard { job_id }
+}
+
+/// 请求取消安装任务
+///
+/// # 返回
+/// - `true`: 已置位取消标志
+/// - `false`: 任务不存在或已结束
+pub fn cancel(job_id: &str) -> bool {
+    match with_jobs(|jobs| jobs.get(job_id).map(|entry| entry.cancel.clone())) {
+        Some(flag) => {
+            flag.store(true, Ordering::Relaxed);
+            super::job_log::append_to_job(job_id, "[Job] CANCEL requested");
+            true
+        }
+        None => false,
+    }
+}
+
+/// 请求取消全部进行中的安装任务
+///
+/// # 返回
+/// - 已置位取消标志的任务数
+pub fn cancel_all() -> usize {
+    let job_ids: Vec<String> = with_jobs(|jobs| jobs.keys().cloned().collect());
+    job_ids.iter().filter(|job_id| cancel(job_id)).count()
+}
+
+/// 进行中的安装任务数
+pub fn active_count() -> usize {
+    with_jobs(|jobs| jobs.len())
+}
+
+/// 进行中的安装任务（按开始时间排序）
+pub fn active_jobs() -> Vec<ActiveInstallJob> {
+    let mut list: Vec<ActiveInstallJob> = with_jobs(|jobs| jobs.values().map(|entry| entry.info.clone()).collect());
+    list.sort_by_key(|job| job.started_at);
+    list
+}
+
+/// 获取当前 job_log 作用域内任务的取消标志（不在安装任务中时返回 None）
+pub fn current_cancel_flag() -> Option<Arc<AtomicBool>> {
+    let job_id = super::job_log::current_job_id()?;
+    with_jobs(|jobs| jobs.get(&job_id).map(|entry| entry.cancel.clone()))
+}
+
+/// 获取当前 job_log 作用域内任务登记的 driverPin（不在安装任务中时返回 Ok(None)）
+pub fn current_driver_pin() -> Result<Option<DriverPin>, String> {
+    let Some(job_id) = super::job_log::current_job_id() else {
+        return Ok(None);
+    };
+    with_jobs(|jobs| jobs.get(&job_id).map(|entry| entry.driver_pin.clone())).unwrap_or(Ok(None))
+}
+
+/// 待继续的任务（key = 小写的打印机路径）
+static RESUME_HINTS: Mutex<Option<HashMap<String, InstallResumeHint>>> = Mutex::new(None);
+
+fn with_resume_hints<R>(f: impl FnOnce(&mut HashMap<String, InstallResumeHint>) -> R) -> R {
+    let mut guard = RESUME_HINTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+/// 设置 / 清除下一次安装该路径打印机时继续的任务（None 为清除）
+pub fn set_resume_hint(path: &str, hint: Option<InstallResumeHint>) {
+    let key = path.trim().to_lowercase();
+    with_resume_hints(|hints| match hint {
+        Some(hint) => {
+            hints.insert(key, hint);
+        }
+        None => {
+            hints.remove(&key);
+        }
+    });
+}
+
+/// 取出该路径打印机待继续的任务（取出后清除，只生效一次）
+pub fn take_resume_hint(path: &str) -> Option<InstallResumeHint> {
+    with_resume_hints(|hints| hints.remove(&path.trim().to_lowercase()))
+}
+
+/// 并发限制状态（写入 job.init 事件 meta.concurrency）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConcurrencyLimits {
+    /// 低配机器（解压与安装共用名额）
+    pub low_resource: bool,
+    pub total_memory_mb: Option<u64>,
+    pub logical_cpus: usize,
+    pub max_extractions: usize,
+    pub max_installs: usize,
+}
+
+struct Limiters {
+    info: ConcurrencyLimits,
+    extraction: Arc<Semaphore>,
+    install: Arc<Semaphore>,
+}
+
+static LIMITERS: OnceLock<Limiters> = OnceLock::new();
+
+/// 物理内存总量（MB），读取失败时返回 None
+fn total_memory_mb() -> Option<u64> {
+    use winapi::um::sysinfoapi::{GlobalMemoryStatusEx, MEMORYSTATUSEX};
+    let mut status: MEMORYSTATUSEX = unsafe { std::mem::zeroed() };
+    status.dwLength = std::mem::size_of::<MEMORYSTATUSEX>() as u32;
+    if unsafe { GlobalMemoryStatusEx(&mut status) } == 0 {
+        return None;
+    }
+    Some(status.ullTotalPhys / (1024 * 1024))
+}
+
+fn limiters() -> &'static Limiters {
+    LIMITERS.get_or_init(|| {
+        let total_memory_mb = total_memory_mb();
+        let logical_cpus = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let low_resource = total_memory_mb.is_some_and(|mb| mb < LOW_MEMORY_THRESHOLD_MB) || logical_cpus <= LOW_CPU_THRESHOLD;
+        let (extraction, install) = if low_resource {
+            let shared = Arc::new(Semaphore::new(1));
+            (shared.clone(), shared)
+        } else {
+            (
+                Arc::new(Semaphore::new(DEFAULT_MAX_EXTRACTIONS)),
+                Arc::new(Semaphore::new(DEFAULT_MAX_INSTALLS)),
+            )
+        };
+        let info = ConcurrencyLimits {
+            low_resource,
+            total_memory_mb,
+            logical_cpus,
+            max_extractions: DEFAULT_MAX_EXTRACTIONS,
+            max_installs: DEFAULT_MAX_INSTALLS,
+        };
+        eprintln!("[Job] concurrency limits {:?}", info);
+        Limiters { info, extraction, install }
+    })
+}
+
+/// 当前机器的并发限制
+pub fn concurrency_limits() -> ConcurrencyLimits {
+    limiters().info.clone()
+}
+
+/// 阶段名额（Drop 时释放）
+pub struct StageSlot {
+    _permit: OwnedSemaphorePermit,
+}
+
+async fn acquire(semaphore: &Arc<Semaphore>, stage: &str, job_id: &str) -> StageSlot {
+    if let Ok(permit) = semaphore.clone().try_acquire_owned() {
+        return StageSlot { _permit: permit };
+    }
+    super::job_log::append_to_job(job_id, &format!("[Job] WAIT stage={} reason=concurrency_limit", stage));
+    let started = Instant::now();
+    let permit = semaphore
+        .clone()
+        .acquire_owned()
+        .await
+        .expect("install concurrency semaphore is never closed");
+    super::job_log::append_to_job(
+        job_id,
+        &format!("[Job] ACQUIRED stage={} waited_ms={}", stage, started.elapsed().as_millis()),
+    );
+    StageSlot { _permit: permit }
+}
+
+/// 申请驱动包解压名额（名额已满时排队等待）
+pub async fn acquire_extraction_slot(job_id: &str) -> StageSlot {
+    acquire(&limiters().extraction, "extract", job_id).await
+}
+
+/// 申请安装名额（名额已满时排队等待）
+pub async fn acquire_install_slot(job_id: &str) -> StageSlot {
+    acquire(&limiters().install, "install", job_id).await
+}
@@ -0,0 +1,395 @@
+// Windows 平台安装后校验模块
+// Add-Printer 返回 0 只说明命令执行完成，不代表队列可用；安装成功后再读回实际状态：
+//
+// - 队列存在（Get-Printer），端口的 PrinterHostAddress / 端口名指向配置的地址（共享连接跳过）
+// - 驱动名称属于配置的 driverNames（未配置时只记录实际驱动，不判定）
+// - 可选探测（打印机配置 verifyProbe）：
+//   - zero_page：通过 Spooler 提交一个不含页面的 RAW 作业（StartDocPrinter / EndDocPrinter），确认队列接受作业
+//   - pjl_status：直连队列 RAW 端口的端口号（默认 9100，以 Get-PrinterPort 读回的 PortNumber 为准）发送 @PJL INFO STATUS，
+//     确认设备有响应；LPR 等非 RAW 端口不探测
+// - 校验失败不改变安装结果，只在 InstallResult.verified / verification 中如实反映
+// - check_queue_name：安装前读取同名队列的端口，判断是否指向其他地址（队列名冲突策略使用）
+
+use std::io::{Read, Write};
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::ptr;
+use std::time::Duration;
+
+use serde::Deserialize;
+use winapi::ctypes::c_void;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::winnt::LPWSTR;
+use winapi::um::winspool::{ClosePrinter, EndDocPrinter, OpenPrinterW, StartDocPrinterW, DOC_INFO_1W, PRINTER_DEFAULTSW};
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::printer_address::PrinterAddress;
+use crate::platform::ps_script::PsScript;
+use crate::platform::{InstallVerification, QueueNameStatus};
+
+/// PJL 探测的连接 / 读取超时
+const PJL_TIMEOUT_MS: u64 = 3_000;
+
+/// PJL 通用退出语言（UEL）
+const PJL_UEL: &[u8] = b"\x1b%-12345X";
+
+/// 提交作业仅需要使用权限
+const PRINTER_ACCESS_USE: u32 = 0x00000008;
+
+/// Get-Printer / Get-PrinterPort 读回的信息
+#[derive(Debug, Deserialize)]
+struct QueueInfo {
+    #[serde(rename = "Exists", default)]
+    exists: bool,
+    #[serde(rename = "DriverName", default)]
+    driver_name: Option<String>,
+    #[serde(rename = "PortName", default)]
+    port_name: Option<String>,
+    #[serde(rename = "PrinterHostAddress", default)]
+    printer_host_address: Option<String>,
+    /// Standard TCP/IP 端口的端口号
+    #[serde(rename = "PortNumber", default)]
+    port_number: Option<u32>,
+    /// Standard TCP/IP 端口的协议（1 = RAW，2 = LPR）
+    #[serde(rename = "Protocol", default)]
+    protocol: Option<u32>,
+}
+
+/// Standard TCP/IP 端口的 RAW 协议值
+const PORT_PROTOCOL_RAW: u32 = 1;
+
+/// 安装后探测方式（打印机配置 verifyProbe）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyProbe {
+    None,
+    ZeroPage,
+    PjlStatus,
+}
+
+impl VerifyProbe {
+    pub fn from_config(value: Option<&str>) -> Self {
+        match value {
+            Some("zero_page") => VerifyProbe::ZeroPage,
+            Some("pjl_status") => VerifyProbe::PjlStatus,
+            _ => VerifyProbe::None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&'static str> {
+        match self {
+            VerifyProbe::None => None,
+            VerifyProbe::ZeroPage => Some("zero_page"),
+            VerifyProbe::PjlStatus => Some("pjl_status"),
+        }
+    }
+}
+
+fn string_to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 读回队列的驱动与端口
+fn read_queue_info(printer_name: &str) -> Result<QueueInfo, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+         $p = Get-Printer -Name $Name -ErrorAction SilentlyContinue; \
+         if (-not $p) { Write-Output '{\"Exists\":false}'; exit 0 }; \
+         $port = Get-PrinterPort -Name $p.PortName -ErrorAction SilentlyContinue; \
+         [pscustomobject]@{ Exists = $true; DriverName = $p.DriverName; PortName = $p.PortName; PrinterHostAddress = $port.PrinterHostAddress; PortNumber = $port.PortNumber; Protocol = $port.Protocol } | ConvertTo-Json -Compress",
+    )
+    .param("Name", printer_name)
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("读取队列信息失败: {}", stderr.trim()));
+    }
+    serde_json::from_str(stdout.trim()).map_err(|e| format!("解析 Get-Printer 输出失败: {} | 输出: {}", e, stdout.trim()))
+}
+
+/// 端口是否指向期望的地址（PrinterHostAddress 相同、主机名解析到该地址，或端口名包含该地址）
+fn port_points_to(address: &PrinterAddress, port_name: &str, port_host: Option<&str>) -> bool {
+    if address.matches_port_name(port_name) {
+        return true;
+    }
+    let Some(port_host) = port_host.map(str::trim).filter(|h| !h.is_empty()) else {
+        return false;
+    };
+    let expected = address.host_address();
+    if port_host.eq_ignore_ascii_case(&expected) {
+        return true;
+    }
+    // 配置写主机名、端口记录的是 IP（或反之）时按解析结果比较
+    let resolve = |host: &str| -> Vec<IpAddr> {
+        match host.parse::<IpAddr>() {
+            Ok(ip) => vec![ip],
+            Err(_) => (host, 0u16).to_socket_addrs().map(|a| a.map(|s| s.ip()).collect()).unwrap_or_default(),
+        }
+    };
+    let expected_ips = resolve(&expected);
+    resolve(port_host).iter().any(|ip| expected_ips.contains(ip))
+}
+
+/// 安装前检查同名队列（共享连接与无法解析的路径不检查，视为不存在）
+///
+/// # 参数
+/// - `printer_name`: 将要创建的队列名称
+/// - `path`: 配置的打印机地址
+pub fn check_queue_name(printer_name: &str, path: &str) -> Result<QueueNameStatus, String> {
+    let Some(address) = PrinterAddress::parse(path).ok().filter(|a| !matches!(a, PrinterAddress::SmbShare { .. })) else {
+        return Ok(QueueNameStatus::Free);
+    };
+    let info = read_queue_info(printer_name)?;
+    if !info.exists {
+        return Ok(QueueNameStatus::Free);
+    }
+    let port_name = info.port_name.unwrap_or_default();
+    let port_host = info.printer_host_address.filter(|h| !h.trim().is_empty());
+    if port_points_to(&address, &port_name, port_host.as_deref()) {
+        return Ok(QueueNameStatus::SameTarget);
+    }
+    Ok(QueueNameStatus::Conflict {
+        current_target: port_host.unwrap_or(port_name),
+    })
+}
+
+/// 通过 Spooler 提交一个不含页面的 RAW 作业
+fn probe_zero_page(printer_name: &str) -> Result<String, String> {
+    let printer_name_wide = string_to_wide(printer_name);
+    let mut doc_name = string_to_wide("ePrinty install verification");
+    let mut datatype = string_to_wide("RAW");
+
+    unsafe {
+        let mut handle: *mut c_void = ptr::null_mut();
+        let mut defaults = PRINTER_DEFAULTSW {
+            pDataType: ptr::null_mut(),
+            pDevMode: ptr::null_mut(),
+            DesiredAccess: PRINTER_ACCESS_USE,
+        };
+        if OpenPrinterW(printer_name_wide.as_ptr() as LPWSTR, &mut handle, &mut defaults) == 0 {
+            return Err(format!("OpenPrinterW 失败 error_code={}", GetLastError()));
+        }
+
+        let mut doc_info = DOC_INFO_1W {
+            pDocName: doc_name.as_mut_ptr(),
+            pOutputFile: ptr::null_mut(),
+            pDatatype: datatype.as_mut_ptr(),
+        };
+        let job_id = StartDocPrinterW(handle, 1, &mut doc_info as *mut DOC_INFO_1W as *mut u8);
+        // 先取错误码再关闭句柄，避免 ClosePrinter 覆盖 LastError
+        let start_error = if job_id == 0 { GetLastError() } else { 0 };
+        let end_ok = job_id != 0 && EndDocPrinter(handle) != 0;
+        let end_error = if job_id != 0 && !end_ok { GetLastError() } else { 0 };
+        let _ = ClosePrinter(handle);
+
+        if job_id == 0 {
+            return Err(format!("StartDocPrinterW 失败 error_code={}", start_error));
+        }
+        if !end_ok {
+            return Err(format!("EndDocPrinter 失败 job_id={} error_code={}", job_id, end_error));
+        }
+        Ok(format!("队列已接受空作业 job_id={}", job_id))
+    }
+}
+
+/// 队列 PJL 探测使用的端口号（只探测 RAW 端口）
+fn pjl_port(info: &QueueInfo) -> Result<u16, String> {
+    match (info.protocol, info.port_number) {
+        (Some(PORT_PROTOCOL_RAW), Some(port)) => u16::try_from(port).map_err(|_| format!("端口号 {} 无效", port)),
+        (Some(protocol), _) if protocol != PORT_PROTOCOL_RAW => {
+            Err(format!("端口 {} 不是 RAW 协议（Protocol={}），无法直连查询 PJL 状态", info.port_name.as_deref().unwrap_or("(空)"), protocol))
+        }
+        _ => Err(format!("未读取到端口 {} 的端口号（非 Standard TCP/IP 端口）", info.port_name.as_deref().unwrap_or("(空)"))),
+    }
+}
+
+/// 直连设备的 RAW 端口查询 PJL 状态
+fn probe_pjl_status(address: &PrinterAddress, port: u16) -> Result<String, String> {
+    let host = address.host_address();
+    let socket = match host.parse::<IpAddr>() {
+        Ok(ip) => SocketAddr::new(ip, port),
+        Err(_) => (host.as_str(), port)
+            .to_socket_addrs()
+            .map_err(|e| format!("无法解析主机地址 {}: {}", host, e))?
+            .next()
+            .ok_or_else(|| format!("无法解析主机地址 {}", host))?,
+    };
+
+    let timeout = Duration::from_millis(PJL_TIMEOUT_MS);
+    let mut stream =
+        TcpStream::connect_timeout(&socket, timeout).map_err(|e| format!("无法连接 {}:{} ({})", host, port, e))?;
+    let _ = stream.set_read_timeout(Some(timeout));
+    let _ = stream.set_write_timeout(Some(timeout));
+
+    let mut request = Vec::new();
+    request.extend_from_slice(PJL_UEL);
+    request.extend_from_slice(b"@PJL INFO STATUS\r\n");
+    request.extend_from_slice(PJL_UEL);
+    stream.write_all(&request).map_err(|e| format!("发送 PJL 请求失败: {}", e))?;
+
+    let mut buffer = [0u8; 1024];
+    let mut response = Vec::new();
+    while response.len() < 4096 {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(n) => {
+                response.extend_from_slice(&buffer[..n]);
+                // PJL 回复以换页符（FF）结束
+                if response.contains(&0x0c) {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    let text = String::from_utf8_lossy(&response);
+    let status: Vec<&str> = text
+        .lines()
+        .map(str::trim)
+        .filter(|l| l.starts_with("CODE=") || l.starts_with("DISPLAY=") || l.starts_with("ONLINE="))
+        .collect();
+    if status.is_empty() {
+        return Err("设备未返回 PJL 状态（可能不支持 PJL）".to_string());
+    }
+    Ok(status.join(" "))
+}
+
+/// 校验已安装的打印队列
+///
+/// # 参数
+/// - `printer_name`: 打印机名称
+/// - `address`: 配置的打印机地址（None 或共享路径时不校验端口）
+/// - `expected_driver_names`: 配置允许的驱动名称（为空时不校验驱动）
+/// - `probe`: 额外探测方式
+///
+/// # 返回
+/// - 校验结果（读取失败时 verified=false，原因写入 detail）
+pub fn verify_installed_queue(
+    printer_name: &str,
+    address: Option<&PrinterAddress>,
+    expected_driver_names: &[String],
+    probe: VerifyProbe,
+) -> InstallVerification {
+    let address = address.filter(|a| !matches!(a, PrinterAddress::SmbShare { .. }));
+    let mut verification = InstallVerification {
+        verified: false,
+        queue_exists: false,
+        port_name: None,
+        port_host: None,
+        expected_host: address.map(|a| a.host_address()),
+        port_matches: None,
+        driver_name: None,
+        expected_driver_names: expected_driver_names.to_vec(),
+        driver_matches: None,
+        probe: probe.as_str().map(str::to_string),
+        probe_ok: None,
+        detail: String::new(),
+    };
+
+    let info = match read_queue_info(printer_name) {
+        Ok(info) => info,
+        Err(e) => {
+            verification.detail = e;
+            log::write_log(&format!("[InstallVerify] printer=\"{}\" read_failed detail=\"{}\"", printer_name, verification.detail));
+            return verification;
+        }
+    };
+
+    let mut problems = Vec::new();
+    let mut probe_detail = None;
+    verification.queue_exists = info.exists;
+    verification.port_name = info.port_name.clone();
+    verification.port_host = info.printer_host_address.clone().filter(|h| !h.trim().is_empty());
+    verification.driver_name = info.driver_name.clone();
+    if !info.exists {
+        problems.push("队列不存在".to_string());
+    } else {
+        if let Some(address) = address {
+            let matches = port_points_to(address, info.port_name.as_deref().unwrap_or_default(), verification.port_host.as_deref());
+            verification.port_matches = Some(matches);
+            if !matches {
+                problems.push(format!(
+                    "端口 {} 指向 {}，期望 {}",
+                    info.port_name.as_deref().unwrap_or("(空)"),
+                    verification.port_host.as_deref().unwrap_or("(未知)"),
+                    address.host_address()
+                ));
+            }
+        }
+
+        if !expected_driver_names.is_empty() {
+            let actual = info.driver_name.as_deref().unwrap_or_default().trim();
+            let matches = expected_driver_names.iter().any(|name| name.trim().eq_ignore_ascii_case(actual));
+            verification.driver_matches = Some(matches);
+            if !matches {
+                problems.push(format!(
+                    "驱动为 {}，期望 {}",
+                    if actual.is_empty() { "(空)" } else { actual },
+                    expected_driver_names.join(" / ")
+                ));
+            }
+        }
+
+        let probe_result = match probe {
+            VerifyProbe::None => None,
+            VerifyProbe::ZeroPage => Some(probe_zero_page(printer_name)),
+            VerifyProbe::PjlStatus => Some(match address {
+                Some(address) => pjl_port(&info).and_then(|port| probe_pjl_status(address, port)),
+                None => Err("共享连接 / 无设备地址，无法直连查询 PJL 状态".to_string()),
+            }),
+        };
+        match probe_result {
+            Some(Ok(detail)) => {
+                verification.probe_ok = Some(true);
+                probe_detail = Some(detail);
+            }
+            Some(Err(e)) => {
+                verification.probe_ok = Some(false);
+                problems.push(format!("{} 探测失败：{}", probe.as_str().unwrap_or_default(), e));
+            }
+            None => {}
+        }
+    }
+
+    verification.verified = problems.is_empty();
+    verification.detail = if problems.is_empty() {
+        match probe_detail {
+            Some(detail) => format!("安装后校验通过（{}）", detail),
+            None => "安装后校验通过".to_string(),
+        }
+    } else {
+        problems.join("；")
+    };
+    log::write_log(&format!(
+        "[InstallVerify] printer=\"{}\" verified={} port={:?} port_host={:?} driver={:?} probe={:?} detail=\"{}\"",
+        printer_name,
+        verification.verified,
+        verification.port_name,
+        verification.port_host,
+        verification.driver_name,
+        verification.probe,
+        verification.detail
+    ));
+    verification
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn port_points_to_matches_port_name_or_host() {
+        let address = PrinterAddress::parse("10.0.0.5").unwrap();
+        assert!(port_points_to(&address, "IP_10.0.0.5", None));
+        assert!(port_points_to(&address, "财务部_10.0.0.5", Some("10.0.0.9")));
+        assert!(port_points_to(&address, "HP_Port", Some(" 10.0.0.5 ")));
+        assert!(!port_points_to(&address, "IP_10.0.0.6", Some("10.0.0.6")));
+        assert!(!port_points_to(&address, "WSD-1234", None));
+        assert!(!port_points_to(&address, "WSD-1234", Some("  ")));
+
+        let host = PrinterAddress::parse("PRN-01.corp.local").unwrap();
+        assert!(port_points_to(&host, "HP_Port", Some("prn-01.CORP.local")));
+    }
+}
@@ -0,0 +1,166 @@
+// Windows 平台完整性自检模块
+// - 嵌入资源：重新计算 exe 内嵌资源（prnport.vbs）的 SHA256，与 build.rs 写入的编译期常量比对
+// - 落盘副本：%TEMP% 下已写出的资源副本若被篡改，同样报告（下次使用前会重新写入并校验）
+// - exe 签名：Get-AuthenticodeSignature 查询当前可执行文件的 Authenticode 签名状态
+
+use crate::utils::encoding::decode_windows_string;
+use crate::platform::{ExecutableSignature, ResourceIntegrity};
+use crate::platform::ps_script::PsScript;
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// 编译期计算的 prnport.vbs SHA256（见 build.rs）
+const PRNPORT_VBS_SHA256: &str = env!("EPRINTY_PRNPORT_VBS_SHA256");
+
+/// 嵌入资源描述
+struct EmbeddedResource {
+    name: &'static str,
+    bytes: &'static [u8],
+    expected_sha256: &'static str,
+    /// 运行时写出的副本路径（未落盘的资源为 None）
+    disk_path: Option<PathBuf>,
+}
+
+fn embedded_resources() -> Vec<EmbeddedResource> {
+    vec![EmbeddedResource {
+        name: "prnport.vbs",
+        bytes: super::install::PRNPORT_VBS_BYTES,
+        expected_sha256: PRNPORT_VBS_SHA256,
+        disk_path: Some(std::env::temp_dir().join("prnport.vbs")),
+    }]
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// 校验落盘资源副本是否与编译期哈希一致
+///
+/// # 参数
+/// - `name`: 资源名称（如 "prnport.vbs"）
+/// - `path`: 已写出的副本路径
+///
+/// # 返回
+/// - `Ok(())`: 副本完整
+/// - `Err(String)`: 副本无法读取或哈希不一致（可能被篡改）
+pub fn verify_written_resource(name: &str, path: &Path) -> Result<(), String> {
+    let resource = embedded_resources()
+        .into_iter()
+        .find(|r| r.name == name)
+        .ok_or_else(|| format!("未知的嵌入资源: {}", name))?;
+    let bytes = std::fs::read(path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    let actual = sha256_hex(&bytes);
+    if actual != resource.expected_sha256 {
+        return Err(format!(
+            "{} 完整性校验失败（expected={} actual={}），文件可能已被篡改",
+            path.display(),
+            resource.expected_sha256,
+            actual
+        ));
+    }
+    Ok(())
+}
+
+/// 校验所有嵌入资源及其落盘副本
+pub fn verify_embedded_resources_windows() -> Vec<ResourceIntegrity> {
+    let mut results = Vec::new();
+    for resource in embedded_resources() {
+        let actual = sha256_hex(resource.bytes);
+        results.push(ResourceIntegrity {
+            name: resource.name.to_string(),
+            location: "embedded".to_string(),
+            path: None,
+            ok: actual == resource.expected_sha256,
+            expected_sha256: resource.expected_sha256.to_string(),
+            actual_sha256: Some(actual),
+            message: None,
+        });
+
+        // 副本不存在属于正常情况（尚未使用过），不报告
+        if let Some(disk_path) = resource.disk_path.filter(|p| p.exists()) {
+            let (actual, message) = match std::fs::read(&disk_path) {
+                Ok(bytes) => (Some(sha256_hex(&bytes)), None),
+                Err(e) => (None, Some(format!("读取失败: {}", e))),
+            };
+            results.push(ResourceIntegrity {
+                name: resource.name.to_string(),
+                location: "disk".to_string(),
+                path: Some(disk_path.display().to_string()),
+                ok: actual.as_deref() == Some(resource.expected_sha256),
+                expected_sha256: resource.expected_sha256.to_string(),
+                actual_sha256: actual,
+                message,
+            });
+        }
+    }
+    results
+}
+
+/// 查询当前 exe 的 Authenticode 签名状态
+pub fn verify_executable_signature_windows() -> ExecutableSignature {
+    let exe_path = match std::env::current_exe() {
+        Ok(p) => p,
+        Err(e) => {
+            return ExecutableSignature {
+                status: "unknown".to_string(),
+                path: None,
+                signer: None,
+                message: format!("获取可执行文件路径失败: {}", e),
+            };
+        }
+    };
+
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+         $sig = Get-AuthenticodeSignature -LiteralPath $ExePath; \
+         [PSCustomObject]@{ Status = [string]$sig.Status; Signer = $(if ($sig.SignerCertificate) { $sig.SignerCertificate.Subject } else { $null }); Message = $sig.StatusMessage } | ConvertTo-Json -Compress",
+    )
+    .param("ExePath", exe_path.display().to_string())
+    .build();
+
+    let path = Some(exe_path.display().to_string());
+    let output = match super::ps::run_powershell(&script) {
+        Ok(o) => o,
+        Err(e) => {
+            return ExecutableSignature {
+                status: "unknown".to_string(),
+                path,
+                signer: None,
+                message: format!("查询签名失败: {}", e),
+            };
+        }
+    };
+
+    let stdout = decode_windows_string(&output.stdout);
+    let json: serde_json::Value = match serde_json::from_str(stdout.trim()) {
+        Ok(v) => v,
+        Err(e) => {
+            return ExecutableSignature {
+                status: "unknown".to_string(),
+                path,
+                signer: None,
+                message: format!("解析签名结果失败: {}", e),
+            };
+        }
+    };
+
+    let raw_status = json.get("Status").and_then(|v| v.as_str()).unwrap_or_default();
+    // Valid / NotSigned 之外的状态（HashMismatch、NotTrusted 等）均视为签名无效
+    let status = match raw_status {
+        "Valid" => "valid",
+        "NotSigned" => "unsigned",
+        "" | "UnknownError" => "unknown",
+        _ => "invalid",
+    };
+
+    ExecutableSignature {
+        status: status.to_string(),
+        path,
+        signer: json.get("Signer").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        message: json
+            .get("Message")
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+            .unwrap_or_else(|| raw_status.to_string()),
+    }
+}
@@ -0,0 +1,122 @@
+// Windows 平台 IPP Class Driver（免驱）安装模块
+// Windows 10 1903（构建号 18362）起系统自带 Microsoft IPP Class Driver，支持 IPP 的设备无需厂商驱动即可打印
+//
+// - probe_ipp(): 向 http://host:631/ipp/print 发送 Get-Printer-Attributes，应答成功即认为设备支持 IPP
+// - add_ipp_queue(): Add-Printer -IppURL 创建队列（系统自动选择 IPP Class Driver 并创建 IPP 端口）
+//
+// 仅在未找到匹配的厂商驱动时由 install 模块作为回退调用（配置 ippFallback=false 可关闭）
+
+use std::time::Duration;
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::ipp_probe;
+use crate::platform::printer_address::PrinterAddress;
+use crate::platform::ps_script::PsScript;
+
+/// 系统 IPP Class Driver 的驱动名称（安装后校验使用）
+pub const IPP_CLASS_DRIVER_NAME: &str = "Microsoft IPP Class Driver";
+
+/// 自带 IPP Class Driver 的最低构建号（Windows 10 1903）
+const MIN_BUILD: u32 = 18362;
+
+/// IPP 探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 探测到的 IPP 设备
+#[derive(Debug, Clone)]
+pub struct IppDevice {
+    /// Add-Printer -IppURL 使用的地址
+    pub ipp_url: String,
+    /// 设备返回的 printer-make-and-model
+    pub make_and_model: Option<String>,
+}
+
+/// 当前系统是否自带 IPP Class Driver
+///
+/// # 返回
+/// - `Ok(())`: 构建号 >= 18362
+/// - `Err(String)`: 系统版本过低或无法检测
+pub fn ensure_supported() -> Result<(), String> {
+    let build = super::session_cache::windows_build_number()?;
+    if build < MIN_BUILD {
+        return Err(format!(
+            "当前系统构建号 {} 不自带 Microsoft IPP Class Driver（需要 Windows 10 1903 / 构建号 {} 及以上）",
+            build, MIN_BUILD
+        ));
+    }
+    Ok(())
+}
+
+/// 探测设备是否支持 IPP
+///
+/// # 参数
+/// - `path`: 配置中的打印机路径（仅 TCP/IP 主机）
+///
+/// # 返回
+/// - `Ok(IppDevice)`: 设备应答了 Get-Printer-Attributes
+/// - `Err(String)`: 路径不是 TCP/IP 主机、连接失败或应答不是成功的 IPP 应答
+pub async fn probe_ipp(path: &str) -> Result<IppDevice, String> {
+    let address = PrinterAddress::parse(path)?;
+    if matches!(address, PrinterAddress::SmbShare { .. } | PrinterAddress::IppUrl(_)) {
+        return Err(format!("\"{}\" 不是 TCP/IP 打印机地址", path));
+    }
+    let ipp_url = ipp_probe::printer_url(&address.uri_host());
+    let client = reqwest::Client::builder()
+        .timeout(PROBE_TIMEOUT)
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+    let response = client
+        .post(&ipp_url)
+        .header(reqwest::header::CONTENT_TYPE, "application/ipp")
+        .body(ipp_probe::build_get_printer_attributes(&ipp_url))
+        .send()
+        .await
+        .map_err(|e| format!("IPP 探测失败 {}: {}", ipp_url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("IPP 探测失败 {}: 设备返回 HTTP {}", ipp_url, response.status()));
+    }
+    let bytes = response.bytes().await.map_err(|e| format!("读取 IPP 应答失败 {}: {}", ipp_url, e))?;
+    let reply = ipp_probe::parse_probe_reply(&bytes)?;
+    if !reply.is_success() {
+        return Err(format!("IPP 探测失败 {}: status-code=0x{:04X}", ipp_url, reply.status_code));
+    }
+    log::write_log(&format!(
+        "[IppClassDriver] probe ok url=\"{}\" make_and_model={:?}",
+        ipp_url, reply.make_and_model
+    ));
+    Ok(IppDevice { ipp_url, make_and_model: reply.make_and_model })
+}
+
+/// 使用 IPP Class Driver 创建打印队列
+///
+/// # 参数
+/// - `printer_name`: 队列名称（已存在时返回错误，不覆盖）
+/// - `ipp_url`: 设备的 IPP 地址
+///
+/// # 返回
+/// - `Ok(String)`: 队列实际使用的驱动名称
+/// - `Err(String)`: Add-Printer 失败
+pub fn add_ipp_queue(printer_name: &str, ipp_url: &str) -> Result<String, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         if (Get-Printer -Name $Name -ErrorAction SilentlyContinue) { throw \"打印机 '$Name' 已存在\" } \
+         Add-Printer -Name $Name -IppURL $Url -ErrorAction Stop; \
+         (Get-Printer -Name $Name -ErrorAction Stop).DriverName \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Name", printer_name)
+    .param("Url", ipp_url)
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("使用 IPP Class Driver 创建队列失败: {}", stderr.trim()));
+    }
+    let driver_name = decode_windows_string(&output.stdout).trim().to_string();
+    log::write_log(&format!(
+        "[IppClassDriver] queue created printer=\"{}\" url=\"{}\" driver=\"{}\"",
+        printer_name, ipp_url, driver_name
+    ));
+    Ok(driver_name)
+}
@@ -0,0 +1,222 @@
+// Windows 平台安装任务日志模块
+// 将单次安装（jobId）期间的子进程输出与内部日志行写入独立的日志文件，
+// 便于前端在安装失败后"查看详细日志"
+//
+// 日志封装入口：
+// - scope(): 在 jobId 作用域内执行安装 future（基于 tokio task_local，支持并发安装）
+// - sync_scope(): 在 spawn_blocking 线程中恢复 jobId 作用域
+// - append_line(): 追加内部日志行（由 log::write_log 自动调用）
+// - append_to_job(): 在作用域外向指定 job 追加日志行（任务开始/结束）
+// - append_process_output(): 追加子进程 stdout/stderr（由 ps/cmd/exec 等执行封装调用）
+// - append_command_trace(): 按执行顺序记录外部命令行（已脱敏）、退出码与耗时
+// - read_job_log(): 读取指定 jobId 的日志内容
+//
+// 日志路径：%LOCALAPPDATA%\ePrinty\logs\jobs\{jobId}.log
+// 新建日志文件时按保留天数 / 数量上限清理较早的日志（进行中的任务不删除）
+
+use std::fs::{self, OpenOptions};
+use std::future::Future;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::utils::command_trace::{scrub_command_line, CommandTraceEntry};
+use crate::utils::encoding::decode_windows_string;
+
+// 单个进程输出段的最大写入长度（字节），避免超大输出撑爆日志文件
+const MAX_OUTPUT_SECTION_LEN: usize = 64 * 1024;
+
+/// 保留的 job 日志数量上限
+const MAX_RETAINED_LOGS: usize = 200;
+
+/// job 日志保留时长（30 天）
+const MAX_LOG_AGE: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+tokio::task_local! {
+    static CURRENT_JOB_ID: String;
+}
+
+/// 在指定 jobId 作用域内执行 future
+///
+/// 作用域内所有 write_log / 子进程输出都会额外写入该 job 的日志文件
+pub async fn scope<F: Future>(job_id: String, fut: F) -> F::Output {
+    CURRENT_JOB_ID.scope(job_id, fut).await
+}
+
+/// 在阻塞线程中恢复 jobId 作用域（spawn_blocking 不继承 task_local）
+///
+/// `job_id` 为 None 时直接执行 `f`
+pub fn sync_scope<R>(job_id: Option<String>, f: impl FnOnce() -> R) -> R {
+    match job_id {
+        Some(id) => CURRENT_JOB_ID.sync_scope(id, f),
+        None => f(),
+    }
+}
+
+/// 获取当前作用域的 jobId（不在作用域内时返回 None）
+pub fn current_job_id() -> Option<String> {
+    CURRENT_JOB_ID.try_with(|id| id.clone()).ok()
+}
+
+/// 校验 jobId 是否合法（仅允许字母、数字、下划线、短横线，防止路径穿越）
+pub(super) fn is_valid_job_id(job_id: &str) -> bool {
+    !job_id.is_empty()
+        && job_id.len() <= 128
+        && job_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+}
+
+/// 获取 job 日志文件路径
+///
+/// 返回：%LOCALAPPDATA%\ePrinty\logs\jobs\{jobId}.log
+pub fn job_log_path(job_id: &str) -> Result<PathBuf, String> {
+    if !is_valid_job_id(job_id) {
+        return Err(format!("无效的 jobId: \"{}\"", job_id));
+    }
+    let jobs_dir = super::log::get_log_dir()?.join("jobs");
+    fs::create_dir_all(&jobs_dir)
+        .map_err(|e| format!("创建 job 日志目录失败: {}", e))?;
+    Ok(jobs_dir.join(format!("{}.log", job_id)))
+}
+
+/// 获取已存在的 job 日志文件路径（用于填充 InstallResult.logPath）
+pub fn existing_job_log_path(job_id: &str) -> Option<String> {
+    job_log_path(job_id)
+        .ok()
+        .filter(|p| p.exists())
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+/// 向指定 job 的日志文件追加一段文本（失败静默忽略，不阻塞业务逻辑）
+pub fn append_to_job(job_id: &str, text: &str) {
+    let path = match job_log_path(job_id) {
+        Ok(p) => p,
+        Err(_) => return,
+    };
+    let created = !path.exists();
+    let mut file = match OpenOptions::new().create(true).append(true).open(&path) {
+        Ok(f) => f,
+        Err(_) => return,
+    };
+    if created {
+        prune_logs(&path);
+    }
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+    let _ = file.write_all(format!("[{}] {}\n", timestamp, text).as_bytes());
+    let _ = file.flush();
+}
+
+/// 清理较早的 job 日志：按修改时间从新到旧保留，超出数量上限或保留时长的日志被删除
+///
+/// 刚创建的日志与进行中任务的日志不删除；这里不调用 write_log（会回写 job 日志）
+fn prune_logs(current: &Path) {
+    let Some(jobs_dir) = current.parent() else {
+        return;
+    };
+    let Ok(entries) = fs::read_dir(jobs_dir) else {
+        return;
+    };
+    let active: Vec<String> = super::install_jobs::active_jobs()
+        .into_iter()
+        .map(|job| format!("{}.log", job.job_id))
+        .collect();
+    let mut logs: Vec<(PathBuf, SystemTime)> = entries
+        .flatten()
+        .filter(|entry| entry.path() != current && entry.path().is_file())
+        .filter(|entry| entry.path().extension().and_then(|e| e.to_str()) == Some("log"))
+        .filter(|entry| !active.iter().any(|name| entry.file_name().to_str() == Some(name.as_str())))
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            (entry.path(), modified)
+        })
+        .collect();
+    logs.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+
+    let now = SystemTime::now();
+    for (index, (path, modified)) in logs.into_iter().enumerate() {
+        let expired = now.duration_since(modified).map(|age| age > MAX_LOG_AGE).unwrap_or(false);
+        // 刚创建的日志占用一个名额
+        if index + 1 < MAX_RETAINED_LOGS && !expired {
+            continue;
+        }
+        if let Err(e) = fs::remove_file(&path) {
+            eprintln!("[JobLog] 清理 job 日志失败 file=\"{}\": {}", path.display(), e);
+        }
+    }
+}
+
+/// 追加内部日志行到当前 job 日志（不在 job 作用域内时忽略）
+pub fn append_line(message: &str) {
+    if let Some(job_id) = current_job_id() {
+        append_to_job(&job_id, message);
+    }
+}
+
+/// 追加已解码的子进程输出到当前 job 日志（不在 job 作用域内时忽略）
+///
+/// # 参数
+/// - `label`: 命令标识（如 "powershell"、"pnputil.exe /add-driver ..."；写入前脱敏，见 command_trace::scrub_command_line）
+/// - `exit_code`: 进程退出码
+/// - `stdout` / `stderr`: 已解码的输出文本
+pub fn append_output_text(label: &str, exit_code: Option<i32>, stdout: &str, stderr: &str) {
+    let job_id = match current_job_id() {
+        Some(id) => id,
+        None => return,
+    };
+    let section = format!(
+        "[Process] command={} exit_code={:?}\n--- stdout ---\n{}\n--- stderr ---\n{}\n--- end ---",
+        scrub_command_line(label),
+        exit_code,
+        super::log::truncate(stdout.trim_end(), MAX_OUTPUT_SECTION_LEN),
+        super::log::truncate(stderr.trim_end(), MAX_OUTPUT_SECTION_LEN)
+    );
+    append_to_job(&job_id, &section);
+}
+
+/// 追加原始子进程输出到当前 job 日志（自动按 Windows 编码解码）
+pub fn append_process_output(label: &str, output: &std::process::Output) {
+    if current_job_id().is_none() {
+        return;
+    }
+    append_output_text(
+        label,
+        output.status.code(),
+        &decode_windows_string(&output.stdout),
+        &decode_windows_string(&output.stderr),
+    );
+}
+
+/// 记录一条外部命令到当前 job 日志（不在 job 作用域内时忽略）
+///
+/// # 参数
+/// - `command`: 完整命令行（写入前脱敏，见 command_trace::scrub_command_line）
+/// - `exit_code`: 进程退出码（超时、启动失败、取消时为 None）
+/// - `started`: 命令开始时间
+/// - `status`: None 时按退出码推断；超时等情况传 "timeout" / "spawn_failed" / "cancelled"
+pub fn append_command_trace(command: &str, exit_code: Option<i32>, started: Instant, status: Option<&str>) {
+    let job_id = match current_job_id() {
+        Some(id) => id,
+        None => return,
+    };
+    let entry = CommandTraceEntry::new(command, exit_code, started.elapsed().as_millis() as u64, status);
+    append_to_job(&job_id, &entry.to_log_line());
+}
+
+/// 读取指定 jobId 的日志内容
+///
+/// # 参数
+/// - `job_id`: 安装任务 ID
+///
+/// # 返回
+/// - `Ok(String)`: 日志内容
+/// - `Err(String)`: jobId 非法或日志不存在
+pub fn read_job_log(job_id: &str) -> Result<String, String> {
+    let path = job_log_path(job_id)?;
+    if !path.exists() {
+        return Err(format!("未找到该任务的日志: {}", job_id));
+    }
+    let bytes = fs::read(&path).map_err(|e| format!("读取任务日志失败: {}", e))?;
+    Ok(String::from_utf8_lossy(&bytes).to_string())
+}
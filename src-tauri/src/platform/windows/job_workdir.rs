@@ -0,0 +1,162 @@
+// Windows 平台安装任务工作目录模块
+// 每个安装任务（jobId）使用独立的工作目录存放临时文件（PowerShell 脚本、prnport.vbs、下载中的驱动包），
+// 避免并发任务在 %TEMP% 中按进程 id 命名的文件互相覆盖
+//
+// - temp_path(): 当前任务的临时文件路径（不在任务作用域内时退回 %TEMP%）
+// - in_job_dir(): 路径是否位于任务工作目录（由 finish() 统一清理，调用方无需逐个删除）
+// - finish(): 任务结束时调用：成功删除工作目录；失败保留用于排查，并按数量 / 总大小上限清理较早的保留目录
+//
+// 目录路径：%LOCALAPPDATA%\ePrinty\jobs\{jobId}\
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use super::log;
+
+/// 保留的失败任务目录数量上限
+const MAX_RETAINED_JOBS: usize = 20;
+
+/// 保留的失败任务目录总大小上限（字节）
+const MAX_RETAINED_BYTES: u64 = 200 * 1024 * 1024;
+
+/// 工作目录根：%LOCALAPPDATA%\ePrinty\jobs
+pub fn jobs_root() -> Result<PathBuf, String> {
+    let local_app_data = std::env::var("LOCALAPPDATA")
+        .map_err(|e| format!("无法获取 LOCALAPPDATA 环境变量: {}", e))?;
+    Ok(PathBuf::from(local_app_data).join("ePrinty").join("jobs"))
+}
+
+/// 获取（并创建）指定任务的工作目录
+pub fn job_dir(job_id: &str) -> Result<PathBuf, String> {
+    if !super::job_log::is_valid_job_id(job_id) {
+        return Err(format!("无效的 jobId: \"{}\"", job_id));
+    }
+    let dir = jobs_root()?.join(job_id);
+    fs::create_dir_all(&dir).map_err(|e| format!("创建任务工作目录失败: {}", e))?;
+    Ok(dir)
+}
+
+/// 当前 job_log 作用域内任务的工作目录（不在任务中或创建失败时返回 None）
+pub fn current_dir() -> Option<PathBuf> {
+    let job_id = super::job_log::current_job_id()?;
+    match job_dir(&job_id) {
+        Ok(dir) => Some(dir),
+        Err(e) => {
+            log::write_log(&format!("[JobWorkdir] {}，临时文件改用 %TEMP%", e));
+            None
+        }
+    }
+}
+
+/// 当前任务的临时文件路径
+///
+/// # 参数
+/// - `file_name`: 文件名（不在任务作用域内时会附加进程 id，保持原有的 %TEMP% 命名）
+pub fn temp_path(file_name: &str) -> PathBuf {
+    match current_dir() {
+        Some(dir) => dir.join(file_name),
+        None => {
+            let path = Path::new(file_name);
+            let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or(file_name);
+            let name = match path.extension().and_then(|e| e.to_str()) {
+                Some(ext) => format!("{}_{}.{}", stem, std::process::id(), ext),
+                None => format!("{}_{}", stem, std::process::id()),
+            };
+            std::env::temp_dir().join(name)
+        }
+    }
+}
+
+/// 路径是否位于某个任务的工作目录下
+pub fn in_job_dir(path: &Path) -> bool {
+    jobs_root().map(|root| path.starts_with(root)).unwrap_or(false)
+}
+
+/// 将临时文件移动到最终位置
+///
+/// 工作目录与目标目录可能不在同一卷（驱动目录位于程序目录下），跨卷时先复制到目标旁的临时名再重命名，
+/// 保证目标文件的出现仍是原子的
+pub fn move_into_place(from: &Path, to: &Path) -> std::io::Result<()> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    let staging = to.with_extension("staging");
+    fs::copy(from, &staging)?;
+    if let Err(e) = fs::rename(&staging, to) {
+        let _ = fs::remove_file(&staging);
+        return Err(e);
+    }
+    let _ = fs::remove_file(from);
+    Ok(())
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .map(|entry| match entry.metadata() {
+            Ok(meta) if meta.is_dir() => dir_size(&entry.path()),
+            Ok(meta) => meta.len(),
+            Err(_) => 0,
+        })
+        .sum()
+}
+
+/// 任务结束：成功删除工作目录，失败保留并清理较早的保留目录
+///
+/// # 参数
+/// - `job_id`: 安装任务 ID
+/// - `success`: 任务是否成功
+pub fn finish(job_id: &str, success: bool) {
+    let Ok(root) = jobs_root() else {
+        return;
+    };
+    let dir = root.join(job_id);
+    if !dir.exists() {
+        return;
+    }
+    if success {
+        if let Err(e) = fs::remove_dir_all(&dir) {
+            log::write_log(&format!("[JobWorkdir] 删除任务工作目录失败 jobId={}: {}", job_id, e));
+        }
+        return;
+    }
+    log::write_log(&format!("[JobWorkdir] 任务失败，保留工作目录 jobId={} size={}", job_id, dir_size(&dir)));
+    prune_retained(&root);
+}
+
+/// 按修改时间从新到旧保留目录，超出数量或总大小上限的较早目录被删除（进行中的任务不删除）
+fn prune_retained(root: &Path) {
+    let active: Vec<String> = super::install_jobs::active_jobs().into_iter().map(|job| job.job_id).collect();
+    let Ok(entries) = fs::read_dir(root) else {
+        return;
+    };
+    let mut retained: Vec<(PathBuf, SystemTime, u64)> = entries
+        .flatten()
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| !active.iter().any(|id| entry.file_name().to_str() == Some(id.as_str())))
+        .map(|entry| {
+            let modified = entry.metadata().and_then(|m| m.modified()).unwrap_or(SystemTime::UNIX_EPOCH);
+            let path = entry.path();
+            let size = dir_size(&path);
+            (path, modified, size)
+        })
+        .collect();
+    retained.sort_by_key(|(_, modified, _)| std::cmp::Reverse(*modified));
+
+    let mut total: u64 = 0;
+    for (index, (path, _, size)) in retained.into_iter().enumerate() {
+        total += size;
+        // 最新的失败目录总是保留（即使单个目录已超过大小上限）
+        if index == 0 || (index < MAX_RETAINED_JOBS && total <= MAX_RETAINED_BYTES) {
+            continue;
+        }
+        match fs::remove_dir_all(&path) {
+            Ok(()) => log::write_log(&format!("[JobWorkdir] pruned retained dir=\"{}\" size={}", path.display(), size)),
+            Err(e) => log::write_log(&format!("[JobWorkdir] 清理保留目录失败 dir=\"{}\": {}", path.display(), e)),
+        }
+    }
+}
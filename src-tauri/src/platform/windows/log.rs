@@ -4,15 +4,16 @@
 // 日志封装入口：
 // - write_log(): 文件日志（%LOCALAPPDATA%\ePrinty\logs\printer-detect.log）
 // - truncate(): 字符串截断辅助函数（用于控制台日志）
+// - get_log_dir(): 日志目录（job 日志等子目录共用）
 
 use std::fs::{self, OpenOptions};
 use std::io::Write;
 use std::path::PathBuf;
 
-/// 获取日志文件路径
+/// 获取日志目录（不存在时自动创建）
 /// 
-/// 返回：%LOCALAPPDATA%\ePrinty\logs\printer-detect.log
-fn get_log_file_path() -> Result<PathBuf, String> {
+/// 返回：%LOCALAPPDATA%\ePrinty\logs
+pub fn get_log_dir() -> Result<PathBuf, String> {
     // 获取 LOCALAPPDATA 环境变量
     let local_app_data = std::env::var("LOCALAPPDATA")
         .map_err(|e| format!("无法获取 LOCALAPPDATA 环境变量: {}", e))?;
@@ -24,6 +25,15 @@ fn get_log_file_path() -> Result<PathBuf, String> {
     fs::create_dir_all(&log_dir)
         .map_err(|e| format!("创建日志目录失败: {}", e))?;
     
+    Ok(log_dir)
+}
+
+/// 获取日志文件路径
+/// 
+/// 返回：%LOCALAPPDATA%\ePrinty\logs\printer-detect.log
+fn get_log_file_path() -> Result<PathBuf, String> {
+    let log_dir = get_log_dir()?;
+    
     // 构建日志文件路径
     let log_file = log_dir.join("printer-detect.log");
     
@@ -40,6 +50,9 @@ fn get_log_file_path() -> Result<PathBuf, String> {
 /// - 如果写入失败，静默忽略（不阻塞业务逻辑）
 /// - 每次写入都重新打开文件，避免文件句柄管理问题
 pub fn write_log(message: &str) {
+    // 处于安装任务作用域内时，同时写入该任务的 job 日志
+    super::job_log::append_line(message);
+    
     // 获取日志文件路径
     let log_path = match get_log_file_path() {
         Ok(path) => path,
@@ -93,7 +106,12 @@ pub fn truncate(s: &str, max_len: usize) -> String {
     if s.len() <= max_len {
         s.to_string()
     } else {
-        format!("{}...<truncated>", &s[..max_len])
+        // 回退到字符边界，避免截断中文等多字节字符时 panic
+        let mut end = max_len;
+        while end > 0 && !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        format!("{}...<truncated>", &s[..end])
     }
 }
 
@@ -104,11 +122,14 @@ pub fn truncate(s: &str, max_len: usize) -> String {
 /// 
 /// # 返回
 /// - 如果是临时目录下的文件，返回 "%TEMP%\\filename"
+/// - 如果是任务工作目录下的文件，返回 "%JOBS%\\{jobId}\\filename"
 /// - 否则返回文件名
 pub fn sanitize_path(path: &std::path::Path) -> String {
     let temp_dir = std::env::temp_dir();
     if let Ok(relative) = path.strip_prefix(&temp_dir) {
         format!("%TEMP%\\{}", relative.display())
+    } else if let Some(relative) = super::job_workdir::jobs_root().ok().and_then(|root| path.strip_prefix(root).ok().map(|r| r.display().to_string())) {
+        format!("%JOBS%\\{}", relative)
     } else {
         path.file_name()
             .and_then(|n| n.to_str())
@@ -0,0 +1,114 @@
+// Windows 平台本机残留清理模块
+// 卸载前删除应用在安装目录以外创建的系统对象（文件目录由服务层清理）：
+//
+// - 委托安装计划任务（schtasks /Delete，任务名称来自配置 delegatedInstall.taskName）
+// - 事件日志事件源（HKLM\...\EventLog\Application\ePrinty）
+// - 凭据管理器中以 ePrinty/ 开头的凭据（SMB 共享凭据、加密驱动包密码）
+//
+// 每一项单独返回处理结果，某一项失败不影响其他项
+
+use std::ptr;
+
+use winapi::shared::minwindef::DWORD;
+use winapi::shared::winerror::ERROR_NOT_FOUND;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::wincred::{CredDeleteW, CredEnumerateW, CredFree, PCREDENTIALW};
+
+use crate::platform::ArtifactRemoval;
+
+/// 应用创建的凭据目标名过滤条件
+const CREDENTIAL_FILTER: &str = "ePrinty/*";
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 删除计划任务（不存在时返回 notFound）
+pub fn remove_scheduled_task(task_name: &str) -> ArtifactRemoval {
+    let exists = super::cmd::run_command("schtasks", &["/Query", "/TN", task_name])
+        .map(|output| output.status.success())
+        .unwrap_or(false);
+    if !exists {
+        return ArtifactRemoval::not_found("scheduledTask", task_name);
+    }
+    match super::cmd::run_command("schtasks", &["/Delete", "/TN", task_name, "/F"]) {
+        Ok(output) if output.status.success() => ArtifactRemoval::removed("scheduledTask", task_name),
+        Ok(output) => {
+            let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
+            ArtifactRemoval::failed("scheduledTask", task_name, format!("schtasks /Delete 失败: {}", stderr.trim()))
+        }
+        Err(e) => ArtifactRemoval::failed("scheduledTask", task_name, e),
+    }
+}
+
+/// 删除事件日志事件源
+pub fn remove_event_source() -> ArtifactRemoval {
+    let target = format!("EventLog/Application/{}", super::event_log::EVENT_SOURCE);
+    match super::event_log::unregister_source() {
+        Ok(true) => ArtifactRemoval::removed("eventSource", &target),
+        Ok(false) => ArtifactRemoval::not_found("eventSource", &target),
+        Err(e) => ArtifactRemoval::failed("eventSource", &target, e),
+    }
+}
+
+/// 枚举应用保存的凭据（目标名与类型）
+fn enumerate_credentials() -> Result<Vec<(String, DWORD)>, String> {
+    let filter = to_wide(CREDENTIAL_FILTER);
+    let mut count: DWORD = 0;
+    let mut credentials: *mut PCREDENTIALW = ptr::null_mut();
+    let ok = unsafe { CredEnumerateW(filter.as_ptr(), 0, &mut count, &mut credentials) };
+    if ok == 0 {
+        let error = unsafe { GetLastError() };
+        if error == ERROR_NOT_FOUND {
+            return Ok(Vec::new());
+        }
+        return Err(format!("CredEnumerateW 失败 error_code={}", error));
+    }
+    let mut found = Vec::with_capacity(count as usize);
+    unsafe {
+        for index in 0..count as usize {
+            let credential = *credentials.add(index);
+            if credential.is_null() || (*credential).TargetName.is_null() {
+                continue;
+            }
+            let name_ptr = (*credential).TargetName;
+            let len = (0..).take_while(|i| *name_ptr.add(*i) != 0).count();
+            let name = String::from_utf16_lossy(std::slice::from_raw_parts(name_ptr, len));
+            found.push((name, (*credential).Type));
+        }
+        CredFree(credentials as *mut _);
+    }
+    Ok(found)
+}
+
+/// 删除凭据管理器中应用保存的全部凭据
+pub fn remove_stored_credentials() -> Vec<ArtifactRemoval> {
+    let credentials = match enumerate_credentials() {
+        Ok(credentials) => credentials,
+        Err(e) => return vec![ArtifactRemoval::failed("credential", CREDENTIAL_FILTER, e)],
+    };
+    credentials
+        .into_iter()
+        .map(|(target, credential_type)| {
+            let wide = to_wide(&target);
+            let ok = unsafe { CredDeleteW(wide.as_ptr(), credential_type, 0) };
+            if ok != 0 {
+                ArtifactRemoval::removed("credential", &target)
+            } else {
+                let error = unsafe { GetLastError() };
+                ArtifactRemoval::failed("credential", &target, format!("CredDeleteW 失败 error_code={}", error))
+            }
+        })
+        .collect()
+}
+
+/// 清理全部系统对象
+///
+/// # 参数
+/// - `task_names`: 要删除的计划任务名称
+pub fn remove_system_artifacts(task_names: &[String]) -> Vec<ArtifactRemoval> {
+    let mut removals: Vec<ArtifactRemoval> = task_names.iter().map(|name| remove_scheduled_task(name)).collect();
+    removals.push(remove_event_source());
+    removals.extend(remove_stored_credentials());
+    removals
+}
@@ -0,0 +1,35 @@
+// Windows 平台机器标识模块（供 fleet 上报计算机器指纹）
+// - 主机名：COMPUTERNAME 环境变量
+// - MAC：Win32_NetworkAdapter 中 PhysicalAdapter=True 的适配器（排除虚拟网卡 / VPN）
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::MachineIdentity;
+
+/// 获取本机主机名与物理网卡 MAC 地址
+pub fn get_machine_identity_windows() -> Result<MachineIdentity, String> {
+    let hostname = std::env::var("COMPUTERNAME")
+        .map_err(|e| format!("无法获取 COMPUTERNAME 环境变量: {}", e))?;
+
+    let script = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+        @(Get-CimInstance Win32_NetworkAdapter -Filter 'PhysicalAdapter=True' -ErrorAction SilentlyContinue | \
+        Where-Object { $_.MACAddress } | ForEach-Object { $_.MACAddress }) | ConvertTo-Json -Compress";
+    let output = super::ps::run_powershell(script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("查询网卡 MAC 地址失败: {}", log::truncate(stderr.trim(), 300)));
+    }
+
+    // ConvertTo-Json 对单元素数组输出字符串、空数组输出空
+    let mac_addresses = match serde_json::from_str::<serde_json::Value>(stdout.trim()) {
+        Ok(serde_json::Value::Array(items)) => items
+            .iter()
+            .filter_map(|v| v.as_str().map(|s| s.to_string()))
+            .collect(),
+        Ok(serde_json::Value::String(mac)) => vec![mac],
+        _ => Vec::new(),
+    };
+
+    Ok(MachineIdentity { hostname, mac_addresses })
+}
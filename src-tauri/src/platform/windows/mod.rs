@@ -1,22 +1,55 @@
 pub mod archive;
+pub mod archive_password;
 pub mod cmd;
+pub mod crash_dump;
 pub mod delete;
 pub mod driver_bootstrap;
+pub mod driver_conflict;
 pub mod driver_fetch;
+pub mod driver_locks;
+pub mod driver_pin;
+pub mod driver_store;
 #[cfg(windows)]
 pub mod driver_store_setupapi;
-pub mod encoding;
+pub mod elevation;
 pub mod enum_printers;
+pub mod event_log;
 pub mod install;
+pub mod install_jobs;
+pub mod install_verify;
+pub mod integrity;
+pub mod ipp_class_driver;
+pub mod job_log;
+pub mod job_workdir;
 pub mod list;
 pub mod log;
+pub mod machine_cleanup;
+pub mod machine_id;
+pub mod network_cost;
 pub mod open;
+pub mod permission_probe;
+pub mod port_config;
+pub mod port_probe;
+pub mod post_install;
 pub mod powershell_install;
+pub mod print_environment;
+pub mod print_file;
+pub mod print_processor;
+pub mod process_watchdog;
+pub mod printer_acl;
 pub mod printer_exists;
 pub mod ps;
+pub mod queue_control;
 pub mod remove;
+pub mod route_probe;
+pub mod scheduled_task;
+pub mod session_cache;
+pub mod smb_credentials;
 pub mod step_reporter;
+pub mod terminal_server;
 pub mod test_page;
+pub mod webview2_bootstrap;
+pub mod webview_watchdog;
 
 // 重新导出 DetailedPrinterInfo 以便子模块使用
 pub use crate::platform::DetailedPrinterInfo;
@@ -0,0 +1,43 @@
+// Windows 平台网络计费状态检测模块
+// 通过 Windows.Networking.Connectivity.NetworkInformation 读取当前 Internet 连接的计费信息，
+// 用于在 4G 热点等按流量计费的网络上限制或推迟驱动包下载
+//
+// - NetworkCostType: Unrestricted（不计费）/ Fixed（有流量上限）/ Variable（按流量计费）/ Unknown
+// - Fixed / Variable、漫游、已超出流量上限均视为按流量计费
+
+use windows::Networking::Connectivity::{NetworkCostType, NetworkInformation};
+
+/// 当前连接的计费信息
+#[derive(Debug, Clone)]
+pub struct ConnectionCost {
+    /// "unrestricted" | "fixed" | "variable" | "unknown"
+    pub cost_type: &'static str,
+    /// 是否按流量计费（需要节省流量）
+    pub metered: bool,
+}
+
+/// 读取当前 Internet 连接的计费信息；无网络或 API 不可用时返回 None
+pub fn current_connection_cost() -> Option<ConnectionCost> {
+    let profile = match NetworkInformation::GetInternetConnectionProfile() {
+        Ok(profile) => profile,
+        Err(e) => {
+            eprintln!("[NetworkCost] GetInternetConnectionProfile failed: {}", e);
+            return None;
+        }
+    };
+    let cost = profile.GetConnectionCost().ok()?;
+    let cost_type = match cost.NetworkCostType().unwrap_or(NetworkCostType::Unknown) {
+        NetworkCostType::Unrestricted => "unrestricted",
+        NetworkCostType::Fixed => "fixed",
+        NetworkCostType::Variable => "variable",
+        _ => "unknown",
+    };
+    let roaming = cost.Roaming().unwrap_or(false);
+    let over_data_limit = cost.OverDataLimit().unwrap_or(false);
+    let metered = matches!(cost_type, "fixed" | "variable") || roaming || over_data_limit;
+    eprintln!(
+        "[NetworkCost] cost_type={} roaming={} over_data_limit={} metered={}",
+        cost_type, roaming, over_data_limit, metered
+    );
+    Some(ConnectionCost { cost_type, metered })
+}
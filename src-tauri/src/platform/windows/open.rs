@@ -24,8 +24,27 @@ pub fn open_url_windows(url: &str) -> Result<(), String> {
     if result.status.success() {
         Ok(())
     } else {
-        let error = String::from_utf8_lossy(&result.stderr);
+        let error = crate::utils::encoding::decode_windows_string(&result.stderr);
         Err(format!("无法打开 URL: {}", error))
     }
 }
 
+
+/// Windows 平台在资源管理器中打开路径
+/// 
+/// - 目录：`explorer.exe <dir>`
+/// - 文件：`explorer.exe /select,<file>`（打开所在文件夹并选中该文件）
+/// 
+/// 注意：explorer.exe 即使成功也常返回非 0 退出码，因此只检查进程能否启动
+pub fn reveal_path_windows(path: &std::path::Path, select: bool) -> Result<(), String> {
+    let path_str = path.display().to_string();
+    let arg = if select {
+        format!("/select,{}", path_str)
+    } else {
+        path_str
+    };
+    
+    cmd::run_command("explorer.exe", &[arg.as_str()])
+        .map(|_| ())
+        .map_err(|e| format!("无法打开资源管理器: {}", e))
+}
@@ -0,0 +1,235 @@
+// Windows 平台权限探测模块
+// 不再只用"是否管理员"判断能否安装，而是逐项探测各操作实际需要的权限：
+//
+// - SeLoadDriverPrivilege：进程令牌中是否存在（INF 驱动导入 / pnputil 需要；存在但未启用时可按需启用）
+// - \\.\pipe\spoolss：能否连接 Spooler 命名管道（所有打印操作的前提，Spooler 未运行时失败）
+// - 打印服务器管理权限：OpenPrinterW(NULL, SERVER_ACCESS_ADMINISTER)（添加端口 / 本地队列 / 驱动需要）
+// - drivers_root 写权限：在 exe 同目录 drivers（不存在时为 exe 目录）创建并删除探测文件
+//
+// 连接共享打印机只需要 Spooler，未提升的进程即可完成，不应要求管理员
+
+use std::ffi::OsStr;
+use std::mem;
+use std::os::windows::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+use winapi::ctypes::c_void;
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
+use winapi::um::securitybaseapi::GetTokenInformation;
+use winapi::um::winbase::LookupPrivilegeValueW;
+use winapi::um::winnt::{
+    TokenElevation, TokenPrivileges, HANDLE, LUID, SE_PRIVILEGE_ENABLED, TOKEN_ELEVATION, TOKEN_PRIVILEGES,
+    TOKEN_QUERY,
+};
+use winapi::um::winspool::{ClosePrinter, OpenPrinterW, PRINTER_DEFAULTSW};
+
+use crate::platform::{OperationPermission, PermissionReport};
+
+const SPOOLER_PIPE: &str = r"\\.\pipe\spoolss";
+
+/// 本机打印服务器管理权限
+const SERVER_ACCESS_ADMINISTER: u32 = 0x00000001;
+
+/// 所有实例都忙（管道存在，Spooler 正常）
+const ERROR_PIPE_BUSY: i32 = 231;
+
+/// 操作标识
+pub const OP_STAGE_DRIVER: &str = "stage_driver";
+pub const OP_ADD_TCPIP_PRINTER: &str = "add_tcpip_printer";
+pub const OP_CONNECT_SHARED_PRINTER: &str = "connect_shared_printer";
+pub const OP_DOWNLOAD_DRIVER: &str = "download_driver";
+
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+}
+
+/// 以 TOKEN_QUERY 打开当前进程令牌并执行查询，结束后关闭句柄
+fn with_process_token<T>(query: impl FnOnce(HANDLE) -> T) -> Option<T> {
+    unsafe {
+        let mut token: HANDLE = ptr::null_mut();
+        if OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) == 0 {
+            return None;
+        }
+        let result = query(token);
+        CloseHandle(token);
+        Some(result)
+    }
+}
+
+/// 当前进程令牌是否已提升
+pub fn is_elevated() -> bool {
+    with_process_token(|token| unsafe {
+        let mut elevation: TOKEN_ELEVATION = mem::zeroed();
+        let mut ret_size: u32 = 0;
+        let ok = GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut _ as *mut _,
+            mem::size_of::<TOKEN_ELEVATION>() as u32,
+            &mut ret_size,
+        );
+        ok != 0 && elevation.TokenIsElevated != 0
+    })
+    .unwrap_or(false)
+}
+
+/// 查询 SeLoadDriverPrivilege 在令牌中的状态
+fn load_driver_privilege_state() -> Result<&'static str, String> {
+    let name = to_wide("SeLoadDriverPrivilege");
+    let mut luid: LUID = unsafe { mem::zeroed() };
+    if unsafe { LookupPrivilegeValueW(ptr::null(), name.as_ptr(), &mut luid) } == 0 {
+        return Err("LookupPrivilegeValueW(SeLoadDriverPrivilege) 失败".to_string());
+    }
+
+    with_process_token(|token| unsafe {
+        let mut size: u32 = 0;
+        GetTokenInformation(token, TokenPrivileges, ptr::null_mut(), 0, &mut size);
+        if size == 0 {
+            return Err("GetTokenInformation(TokenPrivileges) 未返回大小".to_string());
+        }
+        // u64 对齐缓冲区，满足 TOKEN_PRIVILEGES 的对齐要求
+        let mut buffer = vec![0u64; (size as usize).div_ceil(mem::size_of::<u64>())];
+        if GetTokenInformation(token, TokenPrivileges, buffer.as_mut_ptr() as *mut c_void, size, &mut size) == 0 {
+            return Err("GetTokenInformation(TokenPrivileges) 失败".to_string());
+        }
+
+        let privileges = &*(buffer.as_ptr() as *const TOKEN_PRIVILEGES);
+        let entries = std::slice::from_raw_parts(
+            privileges.Privileges.as_ptr(),
+            privileges.PrivilegeCount as usize,
+        );
+        let state = entries
+            .iter()
+            .find(|entry| entry.Luid.LowPart == luid.LowPart && entry.Luid.HighPart == luid.HighPart)
+            .map(|entry| if entry.Attributes & SE_PRIVILEGE_ENABLED != 0 { "enabled" } else { "present" })
+            .unwrap_or("absent");
+        Ok(state)
+    })
+    .unwrap_or_else(|| Err("OpenProcessToken 失败".to_string()))
+}
+
+/// 能否连接 Spooler 命名管道
+fn probe_spooler_pipe() -> Result<(), String> {
+    match std::fs::OpenOptions::new().read(true).write(true).open(SPOOLER_PIPE) {
+        Ok(_) => Ok(()),
+        Err(e) if e.raw_os_error() == Some(ERROR_PIPE_BUSY) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Err("Print Spooler 服务未运行（spoolss 管道不存在）".to_string()),
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => Err("无权连接 Print Spooler（spoolss 管道拒绝访问）".to_string()),
+        Err(e) => Err(format!("连接 spoolss 管道失败: {}", e)),
+    }
+}
+
+/// 能否以管理权限打开本机打印服务器
+fn probe_print_server_admin() -> Result<(), String> {
+    unsafe {
+        let mut handle: *mut c_void = ptr::null_mut();
+        let mut defaults = PRINTER_DEFAULTSW {
+            pDataType: ptr::null_mut(),
+            pDevMode: ptr::null_mut(),
+            DesiredAccess: SERVER_ACCESS_ADMINISTER,
+        };
+        if OpenPrinterW(ptr::null_mut(), &mut handle, &mut defaults) == 0 {
+            return Err(format!(
+                "无本机打印服务器管理权限（OpenPrinterW error_code={}）",
+                winapi::um::errhandlingapi::GetLastError()
+            ));
+        }
+        ClosePrinter(handle);
+        Ok(())
+    }
+}
+
+/// 驱动目录（与安装流程一致：exe 同目录 drivers）
+fn drivers_root() -> Option<PathBuf> {
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(|dir| dir.join("drivers")))
+}
+
+/// 在目录（不存在时取其父目录）中创建并删除探测文件
+fn probe_writable(dir: &Path) -> Result<(), String> {
+    let target = if dir.is_dir() { dir } else { dir.parent().unwrap_or(dir) };
+    let probe_file = target.join(format!(".eprinty_write_probe_{}", std::process::id()));
+    std::fs::write(&probe_file, b"probe").map_err(|e| format!("{} 不可写: {}", target.display(), e))?;
+    let _ = std::fs::remove_file(&probe_file);
+    Ok(())
+}
+
+/// 汇总各操作所需权限
+fn evaluate_operations(report: &PermissionReport) -> Vec<OperationPermission> {
+    let spooler = (report.spooler_pipe_accessible, "无法连接 Print Spooler（\\\\.\\pipe\\spoolss）");
+    let server_admin = (report.print_server_admin, "缺少本机打印服务器管理权限（需以管理员身份运行）");
+    let load_driver = (report.load_driver_privilege != "absent", "进程令牌缺少 SeLoadDriverPrivilege");
+    let drivers_root = (report.drivers_root_writable, "驱动目录不可写");
+
+    let requirements: [(&str, Vec<(bool, &str)>); 4] = [
+        (OP_STAGE_DRIVER, vec![spooler, server_admin, load_driver, drivers_root]),
+        (OP_ADD_TCPIP_PRINTER, vec![spooler, server_admin]),
+        (OP_CONNECT_SHARED_PRINTER, vec![spooler]),
+        (OP_DOWNLOAD_DRIVER, vec![drivers_root]),
+    ];
+    requirements
+        .into_iter()
+        .map(|(operation, checks)| {
+            let missing: Vec<String> = checks
+                .into_iter()
+                .filter(|(ok, _)| !ok)
+                .map(|(_, reason)| reason.to_string())
+                .collect();
+            OperationPermission {
+                operation: operation.to_string(),
+                allowed: missing.is_empty(),
+                missing,
+            }
+        })
+        .collect()
+}
+
+/// 逐项探测当前进程的打印相关权限
+///
+/// # 返回
+/// - 权限探测结果（单项探测失败记录到 notes，并按不具备该权限处理）
+pub fn probe() -> PermissionReport {
+    let mut notes = Vec::new();
+
+    let load_driver_privilege = load_driver_privilege_state().unwrap_or_else(|e| {
+        notes.push(e);
+        "unknown"
+    });
+    let spooler_pipe_accessible = probe_spooler_pipe().map_err(|e| notes.push(e)).is_ok();
+    let print_server_admin = probe_print_server_admin().map_err(|e| notes.push(e)).is_ok();
+    let drivers_root = drivers_root();
+    let drivers_root_writable = match &drivers_root {
+        Some(dir) => probe_writable(dir).map_err(|e| notes.push(e)).is_ok(),
+        None => {
+            notes.push("无法获取驱动目录".to_string());
+            false
+        }
+    };
+
+    let mut report = PermissionReport {
+        is_elevated: is_elevated(),
+        load_driver_privilege: load_driver_privilege.to_string(),
+        spooler_pipe_accessible,
+        print_server_admin,
+        drivers_root: drivers_root.map(|d| d.display().to_string()),
+        drivers_root_writable,
+        operations: Vec::new(),
+        notes,
+    };
+    report.operations = evaluate_operations(&report);
+
+    super::log::write_log(&format!(
+        "[PermissionProbe] elevated={} load_driver={} spooler_pipe={} server_admin={} drivers_root_writable={} denied={:?} notes={:?}",
+        report.is_elevated,
+        report.load_driver_privilege,
+        report.spooler_pipe_accessible,
+        report.print_server_admin,
+        report.drivers_root_writable,
+        report.operations.iter().filter(|o| !o.allowed).map(|o| o.operation.as_str()).collect::<Vec<_>>(),
+        report.notes
+    ));
+    report
+}
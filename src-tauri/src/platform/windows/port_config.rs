@@ -0,0 +1,366 @@
+// Windows 平台打印机端口配置模块
+// 查询 / 修改 Standard TCP/IP 端口的端口号与 SNMP 状态查询（关闭 SNMP 可避免打印机不响应 SNMP 时被误报"脱机"）
+//
+// - Windows 10+（构建号 >= 10240）：Get-PrinterPort 读取；修改通过 Win32_TCPIPPrinterPort 的 CIM 实例一次写入，立即生效
+// - Windows 7/8：直接读写注册表 Monitors\Standard TCP/IP Port\Ports\<端口>，需重启 Print Spooler 后生效
+//
+// retarget_printer_port_windows：打印机 IP 变化时把队列改挂到新地址的端口（不动驱动），仅 Windows 10+
+
+use serde::Deserialize;
+use std::ptr;
+use winapi::shared::minwindef::{DWORD, HKEY};
+use winapi::um::winnt::{REG_DWORD, REG_SZ};
+use winapi::um::winreg::{RegGetValueW, RegSetKeyValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_DWORD, RRF_RT_REG_SZ};
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::ps_script::PsScript;
+use crate::platform::{PortConfig, PortConfigChange, PortConfigUpdate, PortRetargetResult};
+
+/// Standard TCP/IP 端口的注册表位置
+const PORTS_KEY: &str = r"SYSTEM\CurrentControlSet\Control\Print\Monitors\Standard TCP/IP Port\Ports";
+
+/// Get-PrinterPort 输出
+#[derive(Debug, Deserialize)]
+struct PrinterPortInfo {
+    #[serde(rename = "Name")]
+    name: String,
+    #[serde(rename = "PrinterHostAddress", default)]
+    printer_host_address: Option<String>,
+    #[serde(rename = "PortNumber", default)]
+    port_number: Option<u32>,
+    #[serde(rename = "Protocol", default)]
+    protocol: Option<serde_json::Value>,
+    #[serde(rename = "SNMPEnabled", default)]
+    snmp_enabled: Option<bool>,
+    #[serde(rename = "SNMPCommunity", default)]
+    snmp_community: Option<String>,
+}
+
+/// 改挂端口脚本输出
+#[derive(Debug, Deserialize)]
+struct RetargetOutput {
+    #[serde(rename = "OldPort")]
+    old_port: String,
+    #[serde(rename = "Removed", default)]
+    removed: bool,
+}
+
+/// Protocol 取值（1 = RAW，2 = LPR；PowerShell 可能输出数字或枚举名）
+fn protocol_name(value: u32) -> Option<String> {
+    match value {
+        1 => Some("raw".to_string()),
+        2 => Some("lpr".to_string()),
+        _ => None,
+    }
+}
+
+/// 是否使用 PrintManagement 模块（构建号检测失败时按 Windows 10+ 处理）
+fn use_powershell() -> bool {
+    let build = super::session_cache::windows_build_number().unwrap_or(0);
+    build == 0 || build >= 10240
+}
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+fn port_key(port_name: &str) -> Vec<u16> {
+    to_wide(&format!(r"{}\{}", PORTS_KEY, port_name))
+}
+
+/// 读取注册表 DWORD 值（不存在时为 None）
+fn read_registry_dword(port_name: &str, value_name: &str) -> Result<Option<u32>, String> {
+    let key = port_key(port_name);
+    let value = to_wide(value_name);
+    let mut data: DWORD = 0;
+    let mut size = std::mem::size_of::<DWORD>() as DWORD;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_DWORD,
+            ptr::null_mut(),
+            &mut data as *mut DWORD as *mut _,
+            &mut size,
+        )
+    };
+    match status {
+        0 => Ok(Some(data)),
+        2 => Ok(None),
+        code => Err(format!("读取注册表 {} 失败 error_code={}", value_name, code)),
+    }
+}
+
+/// 读取注册表字符串值（不存在时为 None）
+fn read_registry_string(port_name: &str, value_name: &str) -> Result<Option<String>, String> {
+    let key = port_key(port_name);
+    let value = to_wide(value_name);
+    let mut buffer = vec![0u16; 512];
+    let mut size = (buffer.len() * 2) as DWORD;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            key.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    match status {
+        0 => {
+            let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+            Ok(Some(String::from_utf16_lossy(&buffer[..len])))
+        }
+        2 => Ok(None),
+        code => Err(format!("读取注册表 {} 失败 error_code={}", value_name, code)),
+    }
+}
+
+/// 写入注册表值（需要管理员权限）
+fn write_registry_value(port_name: &str, value_name: &str, value_type: DWORD, data: &[u8]) -> Result<(), String> {
+    let key = port_key(port_name);
+    let value = to_wide(value_name);
+    let status = unsafe {
+        RegSetKeyValueW(
+            HKEY_LOCAL_MACHINE as HKEY,
+            key.as_ptr(),
+            value.as_ptr(),
+            value_type,
+            data.as_ptr() as *const _,
+            data.len() as DWORD,
+        )
+    };
+    match status {
+        0 => Ok(()),
+        5 => Err(format!("写入注册表 {} 被拒绝（5），请以管理员身份运行", value_name)),
+        code => Err(format!("写入注册表 {} 失败 error_code={}", value_name, code)),
+    }
+}
+
+fn get_port_config_registry(port_name: &str) -> Result<PortConfig, String> {
+    // PortNumber 是 Standard TCP/IP 端口必有的值，不存在说明端口不存在或不是 TCP/IP 端口
+    let port_number = read_registry_dword(port_name, "PortNumber")?
+        .ok_or_else(|| format!("端口 \"{}\" 不存在或不是 Standard TCP/IP 端口", port_name))?;
+    let host_address = match read_registry_string(port_name, "HostName")?.filter(|s| !s.is_empty()) {
+        Some(host) => Some(host),
+        None => read_registry_string(port_name, "IPAddress")?,
+    };
+    Ok(PortConfig {
+        port_name: port_name.to_string(),
+        host_address,
+        port_number: Some(port_number),
+        protocol: read_registry_dword(port_name, "Protocol")?.and_then(protocol_name),
+        snmp_enabled: read_registry_dword(port_name, "SNMP Enabled")?.map(|v| v != 0),
+        snmp_community: read_registry_string(port_name, "SNMP Community")?,
+        source: "registry".to_string(),
+    })
+}
+
+fn get_port_config_powershell(port_name: &str) -> Result<PortConfig, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { Get-PrinterPort -Name $Port -ErrorAction Stop | Select-Object Name, PrinterHostAddress, PortNumber, Protocol, SNMPEnabled, SNMPCommunity | ConvertTo-Json -Compress } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Port", port_name)
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("读取端口 \"{}\" 失败: {}", port_name, stderr.trim()));
+    }
+    let info: PrinterPortInfo = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析 Get-PrinterPort 输出失败: {} | 输出: {}", e, stdout.trim()))?;
+    let protocol = match info.protocol {
+        Some(serde_json::Value::Number(n)) => n.as_u64().and_then(|v| protocol_name(v as u32)),
+        Some(serde_json::Value::String(s)) => Some(s.to_lowercase()),
+        _ => None,
+    };
+    Ok(PortConfig {
+        port_name: info.name,
+        host_address: info.printer_host_address.filter(|s| !s.is_empty()),
+        port_number: info.port_number,
+        protocol,
+        snmp_enabled: info.snmp_enabled,
+        snmp_community: info.snmp_community.filter(|s| !s.is_empty()),
+        source: "powershell".to_string(),
+    })
+}
+
+/// 读取端口配置
+///
+/// # 参数
+/// - `port_name`: 端口名称
+///
+/// # 返回
+/// - `Ok(PortConfig)`: 端口配置（source 标明读取方式）
+/// - `Err(String)`: 端口不存在或读取失败
+pub fn get_port_config_windows(port_name: &str) -> Result<PortConfig, String> {
+    if use_powershell() {
+        get_port_config_powershell(port_name)
+    } else {
+        get_port_config_registry(port_name)
+    }
+}
+
+fn set_port_config_powershell(port_name: &str, update: &PortConfigUpdate) -> Result<(), String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         $null = Get-PrinterPort -Name $Port -ErrorAction Stop; \
+         $cim = Get-CimInstance -ClassName Win32_TCPIPPrinterPort -ErrorAction Stop | Where-Object { $_.Name -eq $Port } | Select-Object -First 1; \
+         if (-not $cim) { throw \"端口 $Port 不是 Standard TCP/IP 端口\" }; \
+         $props = @{}; \
+         if ($PortNumber -ne '') { $props.PortNumber = [uint32]$PortNumber }; \
+         if ($SnmpEnabled -ne '') { $props.SNMPEnabled = ($SnmpEnabled -eq 'true') }; \
+         if ($Community -ne '') { $props.SNMPCommunity = $Community }; \
+         Set-CimInstance -InputObject $cim -Property $props -ErrorAction Stop \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Port", port_name)
+    .param("PortNumber", update.port_number.map(|n| n.to_string()).unwrap_or_default())
+    .param("SnmpEnabled", update.snmp_enabled.map(|b| b.to_string()).unwrap_or_default())
+    .param("Community", update.snmp_community.as_deref().unwrap_or_default())
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("修改端口 \"{}\" 失败: {}", port_name, stderr.trim()));
+    }
+    Ok(())
+}
+
+fn set_port_config_registry(port_name: &str, update: &PortConfigUpdate) -> Result<(), String> {
+    // 先确认端口存在，避免在 Ports 下新建无效的键
+    get_port_config_registry(port_name)?;
+    if let Some(port_number) = update.port_number {
+        write_registry_value(port_name, "PortNumber", REG_DWORD, &port_number.to_le_bytes())?;
+    }
+    if let Some(enabled) = update.snmp_enabled {
+        write_registry_value(port_name, "SNMP Enabled", REG_DWORD, &u32::from(enabled).to_le_bytes())?;
+    }
+    if let Some(community) = update.snmp_community.as_deref() {
+        let bytes: Vec<u8> = to_wide(community).iter().flat_map(|c| c.to_le_bytes()).collect();
+        write_registry_value(port_name, "SNMP Community", REG_SZ, &bytes)?;
+    }
+    Ok(())
+}
+
+/// 修改端口配置并读回
+///
+/// # 参数
+/// - `port_name`: 端口名称
+/// - `update`: 修改项（None 的字段保持不变）
+///
+/// # 返回
+/// - `Ok(PortConfigChange)`: 修改后读回的配置；通过注册表修改时 requires_spooler_restart=true
+/// - `Err(String)`: 端口不存在、权限不足或写入失败
+pub fn set_port_config_windows(port_name: &str, update: &PortConfigUpdate) -> Result<PortConfigChange, String> {
+    log::write_log(&format!(
+        "[PortConfig] set port=\"{}\" port_number={:?} snmp_enabled={:?} snmp_community_set={}",
+        port_name,
+        update.port_number,
+        update.snmp_enabled,
+        update.snmp_community.is_some()
+    ));
+    let powershell = use_powershell();
+    let result = if powershell {
+        set_port_config_powershell(port_name, update)
+    } else {
+        set_port_config_registry(port_name, update)
+    };
+    if let Err(e) = &result {
+        log::write_log(&format!("[PortConfig] FAIL port=\"{}\" error={}", port_name, e));
+    }
+    result?;
+
+    let port = get_port_config_windows(port_name)?;
+    let message = if powershell {
+        format!("端口 \"{}\" 配置已更新", port_name)
+    } else {
+        format!("端口 \"{}\" 配置已写入注册表，重启 Print Spooler 服务后生效", port_name)
+    };
+    Ok(PortConfigChange {
+        port,
+        method: if powershell { "powershell" } else { "registry" }.to_string(),
+        requires_spooler_restart: !powershell,
+        message,
+    })
+}
+
+/// 把队列改挂到新地址的端口（驱动与队列设置保持不变）
+///
+/// 新端口不存在时按旧端口的协议创建（RAW 沿用旧端口号，LPR 沿用队列名）；
+/// 改挂后旧端口若是本工具创建的 IP_ 端口且已无队列使用，则一并删除
+///
+/// # 参数
+/// - `queue_name`: 系统队列名称
+/// - `host_address`: 新的打印机地址
+/// - `port_name`: 新端口名称（IP_ 规则）
+/// - `port_number`: 配置中指定的端口号（None 时沿用旧端口）
+///
+/// # 返回
+/// - `Ok(PortRetargetResult)`: 改挂结果
+/// - `Err(String)`: 队列不存在、系统不支持或执行失败
+pub fn retarget_printer_port_windows(
+    queue_name: &str,
+    host_address: &str,
+    port_name: &str,
+    port_number: Option<u16>,
+) -> Result<PortRetargetResult, String> {
+    if !use_powershell() {
+        return Err("当前系统不支持原地更新打印机端口（需要 Windows 10 及以上），请重新安装打印机".to_string());
+    }
+    log::write_log(&format!(
+        "[PortConfig] retarget queue=\"{}\" host=\"{}\" port=\"{}\" port_number={:?}",
+        queue_name, host_address, port_name, port_number
+    ));
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         $old = (Get-Printer -Name $Queue -ErrorAction Stop).PortName; $removed = $false; \
+         if ($old -ne $Port) { \
+           if (-not (Get-PrinterPort -Name $Port -ErrorAction SilentlyContinue)) { \
+             $src = Get-PrinterPort -Name $old -ErrorAction SilentlyContinue; \
+             if ($src -and (\"$($src.Protocol)\" -eq '2' -or \"$($src.Protocol)\" -eq 'LPR')) { \
+               Add-PrinterPort -Name $Port -LprHostAddress $HostAddress -LprQueueName $src.LprQueueName -ErrorAction Stop \
+             } else { \
+               $num = if ($PortNumber -ne '') { [uint32]$PortNumber } elseif ($src -and $src.PortNumber) { $src.PortNumber } else { 9100 }; \
+               Add-PrinterPort -Name $Port -PrinterHostAddress $HostAddress -PortNumber $num -ErrorAction Stop \
+             } \
+           }; \
+           Set-Printer -Name $Queue -PortName $Port -ErrorAction Stop; \
+           if ($old -like 'IP_*' -and -not (Get-Printer | Where-Object { $_.PortName -eq $old })) { \
+             Remove-PrinterPort -Name $old -ErrorAction SilentlyContinue; $removed = $? \
+           } \
+         }; \
+         [pscustomobject]@{ OldPort = $old; Removed = $removed } | ConvertTo-Json -Compress \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Queue", queue_name)
+    .param("HostAddress", host_address)
+    .param("Port", port_name)
+    .param("PortNumber", port_number.map(|n| n.to_string()).unwrap_or_default())
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        log::write_log(&format!("[PortConfig] retarget FAIL queue=\"{}\" error={}", queue_name, stderr.trim()));
+        return Err(format!("更新 \"{}\" 的端口失败: {}", queue_name, stderr.trim()));
+    }
+    let parsed: RetargetOutput = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析端口更新输出失败: {} | 输出: {}", e, stdout.trim()))?;
+    log::write_log(&format!(
+        "[PortConfig] retarget OK queue=\"{}\" old_port=\"{}\" new_port=\"{}\" old_port_removed={}",
+        queue_name, parsed.old_port, port_name, parsed.removed
+    ));
+    Ok(PortRetargetResult {
+        queue_name: queue_name.to_string(),
+        old_port: parsed.old_port,
+        new_port: port_name.to_string(),
+        host_address: host_address.to_string(),
+        old_port_removed: parsed.removed,
+    })
+}
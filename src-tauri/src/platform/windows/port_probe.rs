@@ -0,0 +1,38 @@
+// Windows 平台端口协议预检模块
+// 安装前对 TCP/IP 打印机同时探测 RAW（9100）与 LPR（515）端口：
+// 网络中只放行其中一种协议时，按配置协议创建的端口能安装成功但无法打印
+//
+// 只返回各端口的 TCP 可达性，协议的选择与提示由 install 模块完成
+
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+/// 单个端口的连接超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 端口是否可以建立 TCP 连接（主机名解析失败视为不可达）
+fn tcp_reachable(host: &str, port: u16) -> bool {
+    let target = match host.parse::<IpAddr>() {
+        Ok(ip) => Some(SocketAddr::new(ip, port)),
+        Err(_) => (host, port).to_socket_addrs().ok().and_then(|mut addrs| addrs.next()),
+    };
+    match target {
+        Some(addr) => TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT).is_ok(),
+        None => false,
+    }
+}
+
+/// 并行探测多个端口
+///
+/// # 参数
+/// - `host`: 打印机主机地址（IP 或主机名）
+/// - `ports`: 要探测的端口
+///
+/// # 返回
+/// 与 `ports` 一一对应的可达性（总耗时不超过单个端口的超时）
+pub fn probe_ports(host: &str, ports: &[u16]) -> Vec<bool> {
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = ports.iter().map(|port| scope.spawn(move || tcp_reachable(host, *port))).collect();
+        handles.into_iter().map(|handle| handle.join().unwrap_or(false)).collect()
+    })
+}
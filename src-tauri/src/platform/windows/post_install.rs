@@ -0,0 +1,103 @@
+// Windows 平台安装后动作模块
+// 只提供两种白名单操作（是否允许执行、签名校验由服务层负责）：
+// - set_printer_property: Set-PrinterProperty 设置打印机配置项（参数经 PsScript 编码传入，不拼接脚本）
+// - run_vendor_utility: 直接启动驱动包内的厂商工具（参数数组原样传给进程，不经过 cmd / PowerShell）
+// - read_public_key: 读取管理员部署的签名公钥（HKLM，普通用户无法写入）
+
+use crate::utils::encoding::decode_windows_string;
+use crate::platform::ps_script::PsScript;
+use std::path::Path;
+use winapi::um::winreg::{RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ};
+
+/// 签名公钥所在的注册表项与值名称
+const PUBLIC_KEY_SUBKEY: &str = r"SOFTWARE\ePrinty";
+const PUBLIC_KEY_VALUE: &str = "PostInstallPublicKey";
+
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 读取管理员部署的安装后动作签名公钥（HKLM\SOFTWARE\ePrinty 的 PostInstallPublicKey，REG_SZ 十六进制）
+///
+/// # 返回
+/// - `Ok(Some(String))`: 公钥文本（未校验格式）
+/// - `Ok(None)`: 未部署
+/// - `Err(String)`: 读取失败
+pub fn read_public_key() -> Result<Option<String>, String> {
+    let subkey = to_wide(PUBLIC_KEY_SUBKEY);
+    let value = to_wide(PUBLIC_KEY_VALUE);
+    let mut buffer = vec![0u16; 256];
+    let mut size = (buffer.len() * 2) as u32;
+    let status = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            subkey.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    match status {
+        0 => {
+            let len = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+            Ok(Some(String::from_utf16_lossy(&buffer[..len])))
+        }
+        2 => Ok(None),
+        code => Err(format!("读取注册表 {}\\{} 失败 error_code={}", PUBLIC_KEY_SUBKEY, PUBLIC_KEY_VALUE, code)),
+    }
+}
+
+/// 设置打印机配置项
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+/// - `key`: 属性名（如 "Config:AccountingCode"）
+/// - `value`: 属性值
+///
+/// # 返回
+/// - `Ok(String)`: 已设置（说明信息）
+/// - `Err(String)`: 打印机不存在、属性不受驱动支持或执行失败
+pub fn set_printer_property(printer_name: &str, key: &str, value: &str) -> Result<String, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         Set-PrinterProperty -PrinterName $Name -PropertyName $Key -Value $Value -ErrorAction Stop \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Name", printer_name)
+    .param("Key", key)
+    .param("Value", value)
+    .build();
+    let output = super::ps::run_powershell(&script)?;
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("设置 \"{}\" 的 {} 失败: {}", printer_name, key, stderr.trim()));
+    }
+    eprintln!("[PostInstall] set property printer=\"{}\" key=\"{}\"", printer_name, key);
+    Ok(format!("已设置 {}", key))
+}
+
+/// 运行厂商工具（隐藏窗口，120 秒超时）
+///
+/// # 参数
+/// - `program`: 工具可执行文件的完整路径
+/// - `args`: 固定参数
+///
+/// # 返回
+/// - `Ok(String)`: 退出码为 0（说明信息）
+/// - `Err(String)`: 启动失败、超时或退出码非 0
+pub fn run_vendor_utility(program: &Path, args: &[String]) -> Result<String, String> {
+    let program_str = program.to_string_lossy().to_string();
+    let arg_refs: Vec<&str> = args.iter().map(String::as_str).collect();
+    let output = super::cmd::run_command(&program_str, &arg_refs)?;
+    let code = output.status.code();
+    eprintln!("[PostInstall] vendor utility program=\"{}\" exit_code={:?}", program_str, code);
+    if output.status.success() {
+        return Ok("退出码 0".to_string());
+    }
+    let stderr = decode_windows_string(&output.stderr);
+    let stdout = decode_windows_string(&output.stdout);
+    let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+    Err(format!("退出码 {:?}: {}", code, detail.trim()))
+}
@@ -6,7 +6,7 @@ use std::io::Write;
 use std::path::Path;
 
 use crate::exec;
-use crate::platform::windows::encoding::decode_windows_string;
+use crate::utils::encoding::decode_windows_string;
 
 /// 统一的错误拼装函数
 /// 用于格式化命令执行错误信息，包含前缀、标准输出、错误输出和退出码
@@ -45,7 +45,7 @@ pub(crate) async fn run_powershell_file_hidden(
     })?;
 
     // 启动 PowerShell 进程
-    let child = exec::spawn_hidden_piped("powershell", &[
+    let args = [
         "-NoProfile",
         "-WindowStyle",
         "Hidden",
@@ -53,8 +53,12 @@ pub(crate) async fn run_powershell_file_hidden(
         "Bypass",
         "-File",
         script_path_str,
-    ])
+    ];
+    let command_line = crate::utils::command_trace::format_command_line(&super::ps::powershell_program().program, &args);
+    let started = std::time::Instant::now();
+    let child = exec::spawn_hidden_piped(&super::ps::powershell_program().program, &args)
     .map_err(|e| {
+        super::job_log::append_command_trace(&command_line, None, started, Some("spawn_failed"));
         format_command_error(
             &format!("{}: 启动 PowerShell 脚本失败", prefix),
             "",
@@ -66,6 +70,7 @@ pub(crate) async fn run_powershell_file_hidden(
     // 保存子进程 ID 以便在超时时杀死进程
     #[cfg(windows)]
     let child_pid = child.id();
+    let _tracked = crate::platform::windows::process_watchdog::track(&child, "powershell");
 
     // 使用 tokio::time::timeout 添加超时控制
     let join_handle = tokio::task::spawn_blocking(move || {
@@ -88,6 +93,13 @@ pub(crate) async fn run_powershell_file_hidden(
                             let stdout_str = decode_windows_string(&output.stdout);
                             let stderr_str = decode_windows_string(&output.stderr);
                             let exit_code = output.status.code();
+                            super::job_log::append_command_trace(&command_line, exit_code, started, None);
+                            crate::platform::windows::job_log::append_output_text(
+                                &format!("powershell -File {}", script_path_str),
+                                exit_code,
+                                &stdout_str,
+                                &stderr_str,
+                            );
                             Ok((stdout_str, stderr_str, exit_code))
                         }
                         Err(e) => Err(format_command_error(
@@ -112,6 +124,7 @@ pub(crate) async fn run_powershell_file_hidden(
             {
                 let _ = exec::taskkill_pid_hidden(child_pid);
             }
+            super::job_log::append_command_trace(&command_line, None, started, Some("timeout"));
             Err(format_command_error(
                 &format!("{}: PowerShell 脚本执行超时({}秒)", prefix, timeout_secs),
                 "",
@@ -137,11 +150,10 @@ pub(crate) async fn run_install_script_via_temp_file(
     timeout_secs: u64,
     prefix: &str,
 ) -> Result<(String, String, Option<i32>), String> {
-    // 使用 std::env::temp_dir() + 进程 id 生成唯一脚本名
-    let temp_dir = std::env::temp_dir();
-    let script_path = temp_dir.join(format!("install_printer_{}.ps1", std::process::id()));
+    // 写入当前任务的工作目录（不在任务中时退回 %TEMP% + 进程 id）
+    let script_path = super::job_workdir::temp_path("install_printer.ps1");
     
-    // 创建文件 -> 写 BOM -> 写 ps_script -> flush/sync -> drop(file)
+    // 创建文件 -> 写 BOM + ps_script -> flush/sync -> drop(file)
     {
         let mut file = fs::File::create(&script_path)
             .map_err(|e| {
@@ -153,19 +165,8 @@ pub(crate) async fn run_install_script_via_temp_file(
                 )
             })?;
         
-        // 写入 UTF-8 BOM (0xEF, 0xBB, 0xBF)
-        file.write_all(&[0xEF, 0xBB, 0xBF])
-            .map_err(|e| {
-                format_command_error(
-                    &format!("{}: 写入 UTF-8 BOM 失败", prefix),
-                    "",
-                    &e.to_string(),
-                    None,
-                )
-            })?;
-        
-        // 写入脚本内容（UTF-8 编码）
-        file.write_all(ps_script.as_bytes())
+        // 写入 UTF-8 BOM + 脚本内容（PowerShell 5.1 依赖 BOM 识别 UTF-8 脚本）
+        file.write_all(&crate::utils::encoding::encode_utf8_with_bom(ps_script))
             .map_err(|e| {
                 format_command_error(
                     &format!("{}: 写入脚本内容失败", prefix),
@@ -191,7 +192,10 @@ pub(crate) async fn run_install_script_via_temp_file(
     // 调用 run_powershell_file_hidden 执行脚本
     let result = run_powershell_file_hidden(&script_path, timeout_secs, prefix).await;
     
-    // 无论成功失败，都尽力删除临时脚本文件
+    // 无论成功失败，都尽力删除临时脚本文件（任务工作目录中的脚本由任务结束时统一清理，失败时保留用于排查）
+    if super::job_workdir::in_job_dir(&script_path) {
+        return result;
+    }
     if let Err(e) = fs::remove_file(&script_path) {
         eprintln!("[DEBUG] 删除临时脚本文件失败: {:?}, 错误: {}", script_path, e);
     }
@@ -0,0 +1,125 @@
+// Windows 平台打印环境报告模块
+// 技术支持习惯使用 prnmngr.vbs / prndrvr.vbs / prnport.vbs 的输出排查问题，
+// 这里用一次 PowerShell 采集同等信息并输出结构化结果：
+// - 队列：Get-Printer（驱动、端口、共享、状态）
+// - 驱动：Get-PrinterDriver（版本、INF 路径、架构）
+// - 端口：Get-PrinterPort（TCP/IP 主机地址、端口号、协议）
+// - Spooler：服务状态、启动类型、默认假脱机目录
+// - 策略：Point and Print / Package Point and Print / RPC 等打印相关注册表值
+//
+// 单项采集失败只记录到 errors，不影响其他部分
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+use crate::platform::{EnvDriverInfo, EnvPolicyValue, EnvPortInfo, EnvQueueInfo, EnvSpoolerInfo, PrintEnvironmentReport};
+use serde::de::DeserializeOwned;
+
+/// 采集脚本（输出单个 JSON 对象）
+const COLLECT_SCRIPT: &str = r#"[Console]::OutputEncoding = [System.Text.Encoding]::UTF8
+$errors = @()
+$os = $null
+try { $o = Get-CimInstance Win32_OperatingSystem -ErrorAction Stop; $os = "$($o.Caption) $($o.Version) (build $($o.BuildNumber))" } catch { $errors += "Win32_OperatingSystem: $($_.Exception.Message)" }
+$queues = @()
+try {
+  $queues = @(Get-Printer -ErrorAction Stop | ForEach-Object { [PSCustomObject]@{
+    name = $_.Name; driverName = $_.DriverName; portName = $_.PortName; shared = [bool]$_.Shared; shareName = $_.ShareName;
+    queueType = [string]$_.Type; status = [string]$_.PrinterStatus; location = $_.Location; comment = $_.Comment } })
+} catch { $errors += "Get-Printer: $($_.Exception.Message)" }
+$drivers = @()
+try {
+  $drivers = @(Get-PrinterDriver -ErrorAction Stop | ForEach-Object {
+    $v = [UInt64]$_.DriverVersion
+    $ver = if ($v -gt 0) { '{0}.{1}.{2}.{3}' -f (($v -shr 48) -band 0xFFFF), (($v -shr 32) -band 0xFFFF), (($v -shr 16) -band 0xFFFF), ($v -band 0xFFFF) } else { $null }
+    [PSCustomObject]@{ name = $_.Name; version = $ver; infPath = $_.InfPath; manufacturer = $_.Manufacturer;
+      environment = $_.PrinterEnvironment; majorVersion = [int]$_.MajorVersion } })
+} catch { $errors += "Get-PrinterDriver: $($_.Exception.Message)" }
+$ports = @()
+try {
+  $ports = @(Get-PrinterPort -ErrorAction Stop | ForEach-Object {
+    $proto = switch ([string]$_.Protocol) { '1' { 'RAW' } '2' { 'LPR' } default { $null } }
+    $num = if ($_.PortNumber) { [int]$_.PortNumber } else { $null }
+    [PSCustomObject]@{ name = $_.Name; description = $_.Description; hostAddress = $_.PrinterHostAddress;
+      portNumber = $num; protocol = $proto; snmpEnabled = $_.SNMPEnabled } })
+} catch { $errors += "Get-PrinterPort: $($_.Exception.Message)" }
+$spooler = $null
+try {
+  $svc = Get-Service -Name Spooler -ErrorAction Stop
+  $dir = (Get-ItemProperty 'HKLM:\SYSTEM\CurrentControlSet\Control\Print\Printers' -Name DefaultSpoolDirectory -ErrorAction SilentlyContinue).DefaultSpoolDirectory
+  $spooler = [PSCustomObject]@{ status = [string]$svc.Status; startType = [string]$svc.StartType; spoolDirectory = $dir }
+} catch { $errors += "Spooler: $($_.Exception.Message)" }
+$policies = @()
+$policyKeys = @(
+  'HKLM:\SOFTWARE\Policies\Microsoft\Windows NT\Printers',
+  'HKLM:\SOFTWARE\Policies\Microsoft\Windows NT\Printers\PointAndPrint',
+  'HKLM:\SOFTWARE\Policies\Microsoft\Windows NT\Printers\PackagePointAndPrint',
+  'HKLM:\SOFTWARE\Policies\Microsoft\Windows NT\Printers\PackagePointAndPrint\ListofServers',
+  'HKLM:\SOFTWARE\Policies\Microsoft\Windows NT\Printers\RPC',
+  'HKCU:\Software\Policies\Microsoft\Windows NT\Printers'
+)
+foreach ($p in $policyKeys) {
+  $item = Get-Item -Path $p -ErrorAction SilentlyContinue
+  if ($item) { foreach ($n in $item.GetValueNames()) { $policies += [PSCustomObject]@{ path = $p; name = $n; value = [string](@($item.GetValue($n)) -join ',') } } }
+}
+$print = Get-Item -Path 'HKLM:\SYSTEM\CurrentControlSet\Control\Print' -ErrorAction SilentlyContinue
+if ($print -and ($print.GetValueNames() -contains 'RpcAuthnLevelPrivacyEnabled')) {
+  $policies += [PSCustomObject]@{ path = 'HKLM:\SYSTEM\CurrentControlSet\Control\Print'; name = 'RpcAuthnLevelPrivacyEnabled'; value = [string]$print.GetValue('RpcAuthnLevelPrivacyEnabled') }
+}
+[PSCustomObject]@{ osVersion = $os; queues = $queues; drivers = $drivers; ports = $ports; spooler = $spooler; policies = $policies; errors = $errors } | ConvertTo-Json -Compress -Depth 4"#;
+
+/// ConvertTo-Json 对单元素数组可能输出对象，这里统一为数组后逐项解析（无法解析的项跳过）
+fn parse_list<T: DeserializeOwned>(value: Option<&serde_json::Value>) -> Vec<T> {
+    let items = match value {
+        Some(serde_json::Value::Array(items)) => items.clone(),
+        Some(serde_json::Value::Null) | None => Vec::new(),
+        Some(other) => vec![other.clone()],
+    };
+    items
+        .into_iter()
+        .filter_map(|item| serde_json::from_value(item).ok())
+        .collect()
+}
+
+/// 采集 Windows 打印环境报告
+pub fn dump_print_environment_windows() -> Result<PrintEnvironmentReport, String> {
+    let output = super::ps::run_powershell(COLLECT_SCRIPT)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() || stdout.trim().is_empty() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("采集打印环境失败: {}", log::truncate(stderr.trim(), 300)));
+    }
+
+    let json: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析打印环境报告失败: {}", e))?;
+
+    let mut queues: Vec<EnvQueueInfo> = parse_list(json.get("queues"));
+    let mut drivers: Vec<EnvDriverInfo> = parse_list(json.get("drivers"));
+    let mut ports: Vec<EnvPortInfo> = parse_list(json.get("ports"));
+    queues.sort_by(|a, b| a.name.cmp(&b.name));
+    drivers.sort_by(|a, b| a.name.cmp(&b.name));
+    ports.sort_by(|a, b| a.name.cmp(&b.name));
+
+    let report = PrintEnvironmentReport {
+        platform: "windows".to_string(),
+        generated_at: chrono::Local::now().to_rfc3339(),
+        os_version: json.get("osVersion").and_then(|v| v.as_str()).map(|s| s.to_string()),
+        queues,
+        drivers,
+        ports,
+        spooler: json
+            .get("spooler")
+            .filter(|v| !v.is_null())
+            .and_then(|v| serde_json::from_value::<EnvSpoolerInfo>(v.clone()).ok()),
+        policies: parse_list::<EnvPolicyValue>(json.get("policies")),
+        errors: parse_list(json.get("errors")),
+        exported_path: None,
+    };
+    eprintln!(
+        "[PrintEnvironment] collected queues={} drivers={} ports={} policies={} errors={}",
+        report.queues.len(),
+        report.drivers.len(),
+        report.ports.len(),
+        report.policies.len(),
+        report.errors.len()
+    );
+    Ok(report)
+}
@@ -0,0 +1,138 @@
+// Windows 平台打印文件模块
+// 将本地 PDF / 文本文件提交到指定打印机，并返回假脱机作业 ID 便于跟踪
+//
+// - 文本：Get-Content | Out-Printer（与测试页相同路径）
+// - PDF：Start-Process -Verb PrintTo（即 ShellExecute "printto"，依赖系统关联的 PDF 阅读器）
+// - 作业 ID：提交前后对比 Get-PrintJob 的 Id 列表，取新增的作业
+
+use crate::platform::ps_script::PsScript;
+use crate::platform::{PrintFileKind, PrintFileResult};
+use std::path::Path;
+
+// 整个打印脚本的超时时间（含等待作业出现在队列中）
+const PRINT_SCRIPT_TIMEOUT_MS: u64 = 90_000;
+
+// 提交后等待作业出现在队列中的最长时间（秒）
+const JOB_DETECT_TIMEOUT_SECS: u32 = 15;
+
+/// 构造提交单份打印的 PowerShell 片段
+fn build_submit_snippet(kind: PrintFileKind) -> &'static str {
+    match kind {
+        PrintFileKind::Text => {
+            "Get-Content -LiteralPath $file -Raw | Out-Printer -Name $printer"
+        }
+        PrintFileKind::Pdf => {
+            // ShellExecute printto：参数为带引号的打印机名称；阅读器进程可能常驻，只等待有限时间
+            "$proc = Start-Process -FilePath $file -Verb PrintTo -ArgumentList ('\"' + $printer + '\"') -WindowStyle Hidden -PassThru; \
+             if ($proc) { $null = $proc.WaitForExit(30000) }"
+        }
+    }
+}
+
+/// Windows 平台打印文件实现
+///
+/// # 参数
+/// - `printer_name`: 目标打印机名称
+/// - `file_path`: 已通过类型校验的文件路径
+/// - `kind`: 文件类型
+/// - `copies`: 份数（逐份提交）
+///
+/// # 返回
+/// - `Ok(PrintFileResult)`: 提交完成（success 表示是否成功提交）
+/// - `Err(String)`: PowerShell 无法执行
+pub fn print_file_windows(printer_name: &str, file_path: &Path, kind: PrintFileKind, copies: u32) -> Result<PrintFileResult, String> {
+    let file_display = file_path.display().to_string();
+    eprintln!(
+        "[PrintFile] START printer=\"{}\" file=\"{}\" kind={} copies={}",
+        printer_name, super::log::sanitize_path(file_path), kind.as_str(), copies
+    );
+
+    let make_result = |success: bool, spool_job_id: Option<String>, error_code: Option<&str>, message: String, evidence: Option<String>| PrintFileResult {
+        success,
+        printer_name: printer_name.to_string(),
+        file_path: file_display.clone(),
+        file_kind: kind.as_str().to_string(),
+        copies,
+        spool_job_id,
+        error_code: error_code.map(|c| c.to_string()),
+        message,
+        evidence,
+    };
+
+    let (exists, last_error, _) = super::printer_exists::printer_exists(printer_name);
+    if !exists {
+        return Ok(make_result(
+            false,
+            None,
+            Some("WIN_PRINTER_NOT_FOUND"),
+            format!("打印机不存在或未连接: {}", printer_name),
+            last_error.map(|e| format!("OpenPrinterW last_error={}", e)),
+        ));
+    }
+
+    // $printer / $file 由 PsScript 参数传入
+    let script = PsScript::new(format!(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+         $ErrorActionPreference = 'Stop'; \
+         $before = @(Get-PrintJob -PrinterName $printer -ErrorAction SilentlyContinue | ForEach-Object {{ $_.Id }}); \
+         for ($i = 0; $i -lt {copies}; $i++) {{ {submit} }}; \
+         $jobId = $null; $deadline = (Get-Date).AddSeconds({detect}); \
+         while ((Get-Date) -lt $deadline) {{ \
+           $new = @(Get-PrintJob -PrinterName $printer -ErrorAction SilentlyContinue | Where-Object {{ $before -notcontains $_.Id }}); \
+           if ($new.Count -gt 0) {{ $jobId = ($new | ForEach-Object {{ $_.Id }}) -join ','; break }}; \
+           Start-Sleep -Milliseconds 500 \
+         }}; \
+         Write-Output ('JOB_ID=' + $jobId)",
+        copies = copies,
+        submit = build_submit_snippet(kind),
+        detect = JOB_DETECT_TIMEOUT_SECS,
+    ))
+    .param("printer", printer_name)
+    .param("file", &file_display)
+    .build();
+
+    let output = super::ps::run_powershell_with_timeout(&script, PRINT_SCRIPT_TIMEOUT_MS)?;
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
+    let evidence = format!(
+        "exit_code={:?} stdout=\"{}\" stderr=\"{}\"",
+        output.status.code(),
+        super::log::truncate(stdout.trim(), 500),
+        super::log::truncate(stderr.trim(), 1000)
+    );
+
+    if !output.status.success() {
+        let lower = stderr.to_lowercase();
+        let (code, message) = if lower.contains("no application is associated")
+            || stderr.contains("没有与之关联的应用")
+        {
+            (
+                "WIN_NO_PRINT_HANDLER",
+                "系统未关联可打印 PDF 的应用（printto），请安装 PDF 阅读器后重试".to_string(),
+            )
+        } else if lower.contains("access is denied") || stderr.contains("拒绝访问") {
+            ("WIN_PERMISSION_DENIED", "没有权限向该打印机提交作业".to_string())
+        } else {
+            (
+                "WIN_PRINT_FILE_FAILED",
+                format!("提交打印作业失败: {}", super::log::truncate(stderr.trim(), 300)),
+            )
+        };
+        eprintln!("[PrintFile] FAILURE code={} evidence={}", code, evidence);
+        return Ok(make_result(false, None, Some(code), message, Some(evidence)));
+    }
+
+    let spool_job_id = stdout
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("JOB_ID="))
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    eprintln!("[PrintFile] SUCCESS printer=\"{}\" spool_job_id={:?}", printer_name, spool_job_id);
+
+    let message = match &spool_job_id {
+        Some(id) => format!("文件已提交到打印机: {}（作业 {}）", printer_name, id),
+        None => format!("文件已提交到打印机: {}（未能获取作业 ID，作业可能已完成）", printer_name),
+    };
+    Ok(make_result(true, spool_job_id, None, message, Some(evidence)))
+}
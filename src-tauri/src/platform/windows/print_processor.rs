@@ -0,0 +1,97 @@
+// Windows 平台打印处理器 / 数据类型设置模块
+// 部分标签打印机需要显式使用 WinPrint 处理器与 RAW 数据类型，否则驱动默认的 EMF 假脱机会输出乱码
+//
+// - apply_print_processor(): 安装成功后通过 Set-Printer -PrintProcessor / -Datatype 设置，
+//   再用 Get-Printer 读回校验（不区分大小写），读回值不一致视为失败
+
+use serde::Deserialize;
+
+use crate::utils::encoding::decode_windows_string;
+use crate::platform::ps_script::PsScript;
+use super::log;
+
+/// Get-Printer 读回的设置
+#[derive(Debug, Deserialize)]
+struct PrinterProcessorInfo {
+    #[serde(rename = "PrintProcessor", default)]
+    print_processor: Option<String>,
+    #[serde(rename = "Datatype", default)]
+    datatype: Option<String>,
+}
+
+/// 设置打印机的打印处理器与数据类型并校验
+///
+/// # 参数
+/// - `printer_name`: 打印机名称
+/// - `print_processor`: 打印处理器（如 "winprint"），None 表示不修改
+/// - `datatype`: 数据类型（如 "RAW"），None 表示不修改
+///
+/// # 返回
+/// - `Ok(())`: 设置成功且读回一致（两者均为 None 时直接返回）
+/// - `Err(String)`: Set-Printer 失败或读回值不一致
+pub fn apply_print_processor(
+    printer_name: &str,
+    print_processor: Option<&str>,
+    datatype: Option<&str>,
+) -> Result<(), String> {
+    let print_processor = print_processor.map(str::trim).filter(|s| !s.is_empty());
+    let datatype = datatype.map(str::trim).filter(|s| !s.is_empty());
+    if print_processor.is_none() && datatype.is_none() {
+        return Ok(());
+    }
+
+    let mut set_args = String::new();
+    if print_processor.is_some() {
+        set_args.push_str(" -PrintProcessor $Processor");
+    }
+    if datatype.is_some() {
+        set_args.push_str(" -Datatype $Datatype");
+    }
+    let script = PsScript::new(format!(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try {{ Set-Printer -Name $Name{args} -ErrorAction Stop; Get-Printer -Name $Name -ErrorAction Stop | Select-Object PrintProcessor, Datatype | ConvertTo-Json -Compress }} catch {{ Write-Error $_.Exception.Message; exit 1 }}",
+        args = set_args
+    ))
+    .param("Name", printer_name)
+    .param("Processor", print_processor.unwrap_or_default())
+    .param("Datatype", datatype.unwrap_or_default())
+    .build();
+
+    log::write_log(&format!(
+        "[PrintProcessor] apply printer=\"{}\" print_processor={:?} datatype={:?}",
+        printer_name, print_processor, datatype
+    ));
+    let output = super::ps::run_powershell(&script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        log::write_log(&format!("[PrintProcessor] set_failed printer=\"{}\" stderr=\"{}\"", printer_name, stderr.trim()));
+        return Err(format!("设置打印处理器 / 数据类型失败: {}", stderr.trim()));
+    }
+
+    let info: PrinterProcessorInfo = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析 Get-Printer 输出失败: {} | 输出: {}", e, stdout.trim()))?;
+    log::write_log(&format!(
+        "[PrintProcessor] verify printer=\"{}\" print_processor={:?} datatype={:?}",
+        printer_name, info.print_processor, info.datatype
+    ));
+
+    let matches = |expected: Option<&str>, actual: &Option<String>| match expected {
+        Some(e) => actual.as_deref().map(str::trim).unwrap_or_default().eq_ignore_ascii_case(e),
+        None => true,
+    };
+    if !matches(print_processor, &info.print_processor) {
+        return Err(format!(
+            "打印处理器设置未生效：期望 {}，实际 {}（请确认驱动支持该处理器）",
+            print_processor.unwrap_or_default(),
+            info.print_processor.as_deref().unwrap_or("(空)")
+        ));
+    }
+    if !matches(datatype, &info.datatype) {
+        return Err(format!(
+            "数据类型设置未生效：期望 {}，实际 {}（请确认打印处理器支持该数据类型）",
+            datatype.unwrap_or_default(),
+            info.datatype.as_deref().unwrap_or("(空)")
+        ));
+    }
+    Ok(())
+}
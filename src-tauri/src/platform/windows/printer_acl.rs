@@ -0,0 +1,75 @@
+// Windows 平台打印队列权限模块
+// - resolve_account_sids: 账户名（域\账户 / 本地组名）经 NTAccount.Translate 翻译为 SID
+// - get_printer_sddl: Get-Printer -Full 读取 PermissionSDDL
+// - set_printer_sddl: Set-Printer -PermissionSDDL 写入安全描述符（需要管理员权限）
+//
+// SDDL 的构造与解析见 eprinty_core::printer_acl
+
+use crate::utils::encoding::decode_windows_string;
+use crate::platform::ps_script::PsScript;
+
+/// 运行脚本并返回 stdout（失败时带上 stderr）
+fn run_script(script: &str, action: &str) -> Result<String, String> {
+    let output = super::ps::run_powershell(script)?;
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("{}失败: {}", action, stderr.trim()));
+    }
+    Ok(decode_windows_string(&output.stdout).trim().to_string())
+}
+
+/// 把账户名翻译为 SID（顺序与输入一致）
+///
+/// # 参数
+/// - `names`: 账户名（如 "CONTOSO\Finance" / "打印用户"）
+///
+/// # 返回
+/// - `Ok(Vec<String>)`: 对应的 SID
+/// - `Err(String)`: 任一账户不存在或无法联系域控制器
+pub fn resolve_account_sids(names: &[String]) -> Result<Vec<String>, String> {
+    if names.is_empty() {
+        return Ok(Vec::new());
+    }
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         foreach ($n in ($Names -split \"`n\")) { \
+           try { ([System.Security.Principal.NTAccount]::new($n)).Translate([System.Security.Principal.SecurityIdentifier]).Value } \
+           catch { throw \"账户 '$n' 无法解析为 SID: $($_.Exception.Message)\" } \
+         } } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Names", names.join("\n"))
+    .build();
+    let stdout = run_script(&script, "解析账户 SID ")?;
+    let sids: Vec<String> = stdout.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect();
+    if sids.len() != names.len() {
+        return Err(format!("解析账户 SID 结果数量不符（{} 个账户，{} 个 SID）", names.len(), sids.len()));
+    }
+    Ok(sids)
+}
+
+/// 读取打印机的 PermissionSDDL
+pub fn get_printer_sddl(printer_name: &str) -> Result<String, String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         (Get-Printer -Name $Name -Full -ErrorAction Stop).PermissionSDDL \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Name", printer_name)
+    .build();
+    run_script(&script, &format!("读取 \"{}\" 的权限", printer_name))
+}
+
+/// 写入打印机的 PermissionSDDL
+pub fn set_printer_sddl(printer_name: &str, sddl: &str) -> Result<(), String> {
+    let script = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; try { \
+         Set-Printer -Name $Name -PermissionSDDL $Sddl -ErrorAction Stop \
+         } catch { Write-Error $_.Exception.Message; exit 1 }",
+    )
+    .param("Name", printer_name)
+    .param("Sddl", sddl)
+    .build();
+    run_script(&script, &format!("设置 \"{}\" 的权限", printer_name))?;
+    eprintln!("[PrinterAcl] applied printer=\"{}\" sddl=\"{}\"", printer_name, sddl);
+    Ok(())
+}
@@ -0,0 +1,286 @@
+// Windows 平台子进程看门狗模块
+// 应用崩溃或被强制结束时，超时逻辑来不及执行，挂起的 powershell.exe 等子进程会一直残留；
+// 这里记录本应用启动的每个子进程，并在启动时与定期巡检中清理超龄的残留进程
+//
+// 识别方式：
+// - 环境标记：所有子进程都带 EPRINTY_OWNER_PID=<应用 PID> 环境变量（孙进程继承），便于人工排查
+// - 登记文件：%LOCALAPPDATA%\ePrinty\children\{应用PID}.json，记录 PID、创建时间、所属 jobId
+//   （读取其他进程的环境块需要读取 PEB，这里用 PID + 进程创建时间双重匹配，避免误杀复用了 PID 的进程）
+//
+// 封装入口：
+// - mark(): 为 Command 写入环境标记（spawn 前调用）
+// - track(): 登记已启动的子进程，返回的 TrackedChild 在 Drop 时注销
+// - sweep(): 清理已退出实例遗留的子进程，以及本实例中超过阈值仍未结束的子进程
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{Child, Command};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use winapi::shared::minwindef::{DWORD, FALSE, FILETIME};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::{GetExitCodeProcess, GetProcessTimes, OpenProcess, TerminateProcess};
+use winapi::um::winnt::{HANDLE, PROCESS_QUERY_LIMITED_INFORMATION, PROCESS_TERMINATE};
+
+/// 子进程环境标记变量名
+pub const OWNER_ENV: &str = "EPRINTY_OWNER_PID";
+
+/// GetExitCodeProcess 返回的"仍在运行"状态码
+const STILL_ACTIVE: DWORD = 259;
+
+/// 本实例登记的子进程（key = PID）
+static CHILDREN: Mutex<Option<HashMap<u32, ChildRecord>>> = Mutex::new(None);
+
+/// 子进程登记记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChildRecord {
+    pub pid: u32,
+    /// 进程创建时间（FILETIME，100ns 单位），用于识别 PID 复用
+    pub created_filetime: u64,
+    pub started_at_ms: i64,
+    pub label: String,
+    pub job_id: Option<String>,
+}
+
+/// 登记文件格式
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ChildRegistryFile {
+    owner_pid: u32,
+    owner_created_filetime: u64,
+    children: Vec<ChildRecord>,
+}
+
+/// 巡检结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchdogSweepReport {
+    pub killed: Vec<ChildRecord>,
+    /// 清理掉的已退出实例登记文件数
+    pub stale_registries: usize,
+}
+
+/// 已登记子进程的守卫（Drop 时注销）
+pub struct TrackedChild {
+    pid: u32,
+}
+
+impl Drop for TrackedChild {
+    fn drop(&mut self) {
+        with_children(|children| {
+            children.remove(&self.pid);
+        });
+    }
+}
+
+/// 为 Command 写入环境标记（spawn 前调用）
+pub fn mark(cmd: &mut Command) -> &mut Command {
+    cmd.env(OWNER_ENV, std::process::id().to_string())
+}
+
+/// 登记已启动的子进程（记录当前 jobId）
+///
+/// # 参数
+/// - `child`: 已启动的子进程
+/// - `label`: 日志用名称（如 "powershell"）
+pub fn track(child: &Child, label: &str) -> TrackedChild {
+    let pid = child.id();
+    let record = ChildRecord {
+        pid,
+        created_filetime: process_created_filetime(pid).unwrap_or(0),
+        started_at_ms: chrono::Utc::now().timestamp_millis(),
+        label: label.to_string(),
+        job_id: super::job_log::current_job_id(),
+    };
+    with_children(|children| {
+        children.insert(pid, record);
+    });
+    TrackedChild { pid }
+}
+
+/// 修改登记表并同步写入登记文件（写入失败只记录日志）
+fn with_children(f: impl FnOnce(&mut HashMap<u32, ChildRecord>)) {
+    let mut guard = CHILDREN.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let children = guard.get_or_insert_with(HashMap::new);
+    f(children);
+    if let Err(e) = persist(children) {
+        eprintln!("[ProcessWatchdog] persist registry failed: {}", e);
+    }
+}
+
+/// 登记文件目录：%LOCALAPPDATA%\ePrinty\children
+fn registry_dir() -> Result<PathBuf, String> {
+    let local_app_data = std::env::var("LOCALAPPDATA")
+        .map_err(|e| format!("无法获取 LOCALAPPDATA 环境变量: {}", e))?;
+    let dir = PathBuf::from(local_app_data).join("ePrinty").join("children");
+    fs::create_dir_all(&dir).map_err(|e| format!("创建子进程登记目录失败: {}", e))?;
+    Ok(dir)
+}
+
+fn persist(children: &HashMap<u32, ChildRecord>) -> Result<(), String> {
+    let owner_pid = std::process::id();
+    let path = registry_dir()?.join(format!("{}.json", owner_pid));
+    let mut records: Vec<ChildRecord> = children.values().cloned().collect();
+    records.sort_by_key(|r| r.pid);
+    let file = ChildRegistryFile {
+        owner_pid,
+        owner_created_filetime: process_created_filetime(owner_pid).unwrap_or(0),
+        children: records,
+    };
+    let content = serde_json::to_string_pretty(&file).map_err(|e| format!("序列化子进程登记失败: {}", e))?;
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content).map_err(|e| format!("写入子进程登记失败: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("替换子进程登记文件失败: {}", e))
+}
+
+/// 打开进程句柄（失败返回 None，如进程已退出或无权限）
+fn open_process(pid: u32, access: DWORD) -> Option<HANDLE> {
+    let handle = unsafe { OpenProcess(access, FALSE, pid) };
+    if handle.is_null() {
+        None
+    } else {
+        Some(handle)
+    }
+}
+
+/// 查询进程创建时间（FILETIME）；进程已退出时返回 None
+fn process_created_filetime(pid: u32) -> Option<u64> {
+    let handle = open_process(pid, PROCESS_QUERY_LIMITED_INFORMATION)?;
+    let zero = || FILETIME { dwLowDateTime: 0, dwHighDateTime: 0 };
+    let (mut created, mut exited, mut kernel, mut user) = (zero(), zero(), zero(), zero());
+    let mut exit_code: DWORD = 0;
+    let result = unsafe {
+        let ok = GetProcessTimes(handle, &mut created, &mut exited, &mut kernel, &mut user) != 0
+            && GetExitCodeProcess(handle, &mut exit_code) != 0
+            && exit_code == STILL_ACTIVE;
+        CloseHandle(handle);
+        ok
+    };
+    if result {
+        Some(((created.dwHighDateTime as u64) << 32) | created.dwLowDateTime as u64)
+    } else {
+        None
+    }
+}
+
+/// 终止进程（仅当 PID 对应的仍是登记时的同一进程）
+fn kill_if_same_process(record: &ChildRecord) -> bool {
+    if record.created_filetime == 0 || process_created_filetime(record.pid) != Some(record.created_filetime) {
+        return false;
+    }
+    match open_process(record.pid, PROCESS_TERMINATE) {
+        Some(handle) => unsafe {
+            let ok = TerminateProcess(handle, 1) != 0;
+            CloseHandle(handle);
+            ok
+        },
+        None => false,
+    }
+}
+
+fn record_age(record: &ChildRecord, now_ms: i64) -> Duration {
+    Duration::from_millis((now_ms - record.started_at_ms).max(0) as u64)
+}
+
+/// 清理残留子进程
+///
+/// - 已退出实例的登记文件：其中超过 `max_age` 的子进程全部终止，随后删除登记文件
+/// - 本实例：超过 `max_age` 仍在运行的子进程视为挂起，终止（所属调用会随之返回错误）
+///
+/// # 参数
+/// - `max_age`: 子进程最长存活时间（应明显大于各执行封装的超时时间）
+pub fn sweep(max_age: Duration) -> Result<WatchdogSweepReport, String> {
+    let mut report = WatchdogSweepReport::default();
+    let now_ms = chrono::Utc::now().timestamp_millis();
+    let own_pid = std::process::id();
+    let dir = registry_dir()?;
+
+    let entries = fs::read_dir(&dir).map_err(|e| format!("读取子进程登记目录失败: {}", e))?;
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().map(|ext| ext != "json").unwrap_or(true) {
+            continue;
+        }
+        let registry: ChildRegistryFile = match fs::read_to_string(&path)
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+        {
+            Some(r) => r,
+            None => {
+                let _ = fs::remove_file(&path);
+                continue;
+            }
+        };
+        if registry.owner_pid == own_pid {
+            continue;
+        }
+        // 登记实例仍在运行（同一 PID 且创建时间一致）时由它自己负责
+        let owner_alive = registry.owner_created_filetime != 0
+            && process_created_filetime(registry.owner_pid) == Some(registry.owner_created_filetime);
+        if owner_alive {
+            continue;
+        }
+
+        // 未到阈值且仍在运行的子进程保留在登记文件中，留给下一次巡检
+        let mut remaining = Vec::new();
+        for record in registry.children {
+            let still_running = record.created_filetime != 0
+                && process_created_filetime(record.pid) == Some(record.created_filetime);
+            if !still_running {
+                continue;
+            }
+            if record_age(&record, now_ms) >= max_age && kill_if_same_process(&record) {
+                eprintln!(
+                    "[ProcessWatchdog] killed orphan pid={} label={} job_id={:?} owner_pid={}",
+                    record.pid, record.label, record.job_id, registry.owner_pid
+                );
+                report.killed.push(record);
+            } else {
+                remaining.push(record);
+            }
+        }
+        if remaining.is_empty() {
+            let _ = fs::remove_file(&path);
+            report.stale_registries += 1;
+        } else if let Ok(content) = serde_json::to_string_pretty(&ChildRegistryFile {
+            children: remaining,
+            ..registry
+        }) {
+            let _ = fs::write(&path, content);
+        }
+    }
+
+    let mut hung = Vec::new();
+    with_children(|children| {
+        hung = children
+            .values()
+            .filter(|r| record_age(r, now_ms) >= max_age)
+            .cloned()
+            .collect();
+    });
+    for record in hung {
+        if kill_if_same_process(&record) {
+            eprintln!(
+                "[ProcessWatchdog] killed hung child pid={} label={} job_id={:?} age_secs={}",
+                record.pid,
+                record.label,
+                record.job_id,
+                record_age(&record, now_ms).as_secs()
+            );
+            if let Some(job_id) = &record.job_id {
+                super::job_log::append_to_job(
+                    job_id,
+                    &format!("[ProcessWatchdog] 子进程 {}（pid={}）超时未结束，已被终止", record.label, record.pid),
+                );
+            }
+            report.killed.push(record);
+        }
+    }
+
+    Ok(report)
+}
+
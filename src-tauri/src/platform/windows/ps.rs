@@ -2,11 +2,16 @@
 //
 // 日志封装入口：
 // - run_powershell(): 统一执行 PowerShell 命令，记录关键执行信息
+// - run_powershell_async(): 异步上下文使用，将 run_powershell 放入 spawn_blocking 执行
+// - powershell_program(): 实际启动的 powershell.exe；32 位进程（WOW64）下 System32 被重定向到 SysWOW64，
+//   32 位 PowerShell 加载不到 64 位的 PrintManagement 模块（Add-Printer 等报 "cmdlet 不存在"），
+//   此时改用 %SystemRoot%\Sysnative 下的 64 位 PowerShell
 
 #[cfg(windows)]
 use std::os::windows::process::CommandExt;
 use std::process::Command;
 use std::process::Stdio;
+use std::sync::OnceLock;
 use std::time::{Duration, Instant};
 
 #[cfg(windows)]
@@ -18,6 +23,52 @@ const POWERSHELL_TIMEOUT_SECS: u64 = 120;
 // 超时检查轮询间隔（毫秒）
 const POLL_INTERVAL_MS: u64 = 100;
 
+/// 实际使用的 PowerShell 程序（进程内只检测一次）
+static POWERSHELL_PROGRAM: OnceLock<PowerShellProgram> = OnceLock::new();
+
+/// PowerShell 程序检测结果
+#[derive(Debug, Clone)]
+pub struct PowerShellProgram {
+    /// 传给 Command::new 的程序（"powershell" 或 Sysnative 下的完整路径）
+    pub program: String,
+    /// 当前进程运行在 WOW64 下（32 位进程、64 位系统）
+    pub wow64: bool,
+    /// WOW64 下找到了 Sysnative 64 位 PowerShell
+    pub sysnative: bool,
+}
+
+/// 当前进程是否为 64 位 Windows 上的 32 位进程
+fn is_wow64_process() -> bool {
+    use winapi::um::processthreadsapi::GetCurrentProcess;
+    use winapi::um::wow64apiset::IsWow64Process;
+
+    let mut wow64 = 0;
+    unsafe { IsWow64Process(GetCurrentProcess(), &mut wow64) != 0 && wow64 != 0 }
+}
+
+fn detect_powershell_program() -> PowerShellProgram {
+    let wow64 = is_wow64_process();
+    if wow64 {
+        let system_root = std::env::var("SystemRoot").unwrap_or_else(|_| r"C:\Windows".to_string());
+        let sysnative = std::path::Path::new(&system_root)
+            .join("Sysnative")
+            .join("WindowsPowerShell")
+            .join("v1.0")
+            .join("powershell.exe");
+        if sysnative.exists() {
+            eprintln!("[PowerShell] WOW64 detected, using 64-bit PowerShell path={}", sysnative.display());
+            return PowerShellProgram { program: sysnative.display().to_string(), wow64, sysnative: true };
+        }
+        eprintln!("[PowerShell] WOW64 detected but Sysnative PowerShell not found, printing cmdlets may be unavailable");
+    }
+    PowerShellProgram { program: "powershell".to_string(), wow64, sysnative: false }
+}
+
+/// 获取实际使用的 PowerShell 程序（所有启动 PowerShell 的地方都应使用它，而不是直接写 "powershell"）
+pub fn powershell_program() -> &'static PowerShellProgram {
+    POWERSHELL_PROGRAM.get_or_init(detect_powershell_program)
+}
+
 /// 统一执行 PowerShell 命令的封装函数
 /// 
 /// # 参数
@@ -38,15 +89,18 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
     
     // 禁止在脚本中使用 2>&1，直接执行原始脚本
     // stdout 和 stderr 分别读取，不在脚本层合流
-    let mut child = Command::new("powershell")
-        .args([
-            "-NoProfile",
-            "-NonInteractive",
-            "-ExecutionPolicy", "Bypass",
-            "-WindowStyle", "Hidden",
-            "-Command",
-            script
-        ])
+    let args = [
+        "-NoProfile",
+        "-NonInteractive",
+        "-ExecutionPolicy", "Bypass",
+        "-WindowStyle", "Hidden",
+        "-Command",
+        script
+    ];
+    let command_line = crate::utils::command_trace::format_command_line(&powershell_program().program, &args);
+    let start_time = Instant::now();
+    let mut child = super::process_watchdog::mark(&mut Command::new(&powershell_program().program))
+        .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
@@ -55,14 +109,17 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
         .map_err(|e| {
             let err_msg = format!("执行 PowerShell 命令失败: {}", e);
             eprintln!("[PowerShell] ERROR step=SPAWN message={}", err_msg);
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("spawn_failed"));
             err_msg
         })?;
+    let _tracked = super::process_watchdog::track(&child, "powershell");
+    // 轮询期间持续读取输出（打印机 / 驱动 / 端口等 JSON 远超管道缓冲区，否则子进程会阻塞在写入上）
+    let drain = super::cmd::PipeDrain::start(&mut child);
     
-    let start_time = Instant::now();
     let timeout = Duration::from_secs(POWERSHELL_TIMEOUT_SECS);
     
     // 轮询检查进程是否完成
-    loop {
+    let status = loop {
         // 先检查超时
         if start_time.elapsed() >= timeout {
             // 超时，杀死进程
@@ -72,6 +129,7 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
             let _ = child.wait();
             let err_msg = format!("执行 PowerShell 超时: {}s (elapsed: {:.2}s)", POWERSHELL_TIMEOUT_SECS, elapsed.as_secs_f64());
             eprintln!("[PowerShell] ERROR step=TIMEOUT message={}", err_msg);
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("timeout"));
             return Err(err_msg);
         }
         
@@ -80,7 +138,7 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
                 // 进程已完成，退出循环
                 eprintln!("[PowerShell] PROCESS_COMPLETE exit_code={:?} elapsed_secs={:.2}", 
                     status.code(), start_time.elapsed().as_secs_f64());
-                break;
+                break status;
             }
             Ok(None) => {
                 // 进程仍在运行，等待后继续
@@ -95,15 +153,19 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
                 return Err(err_msg);
             }
         }
-    }
+    };
     
     // 循环结束后，获取输出
-    let output = child.wait_with_output()
-        .map_err(|e| {
-            let err_msg = format!("获取 PowerShell 输出失败: {}", e);
-            eprintln!("[PowerShell] ERROR step=GET_OUTPUT message={}", err_msg);
-            err_msg
-        })?;
+    let output = drain.finish(status);
+    
+    super::job_log::append_command_trace(&command_line, output.status.code(), start_time, None);
+    super::job_log::append_process_output(
+        &format!(
+            "powershell script_preview=\"{}\"",
+            super::log::truncate(&crate::utils::command_trace::scrub_command_line(script), 200)
+        ),
+        &output,
+    );
     
     let exit_code = output.status.code();
     let stdout_len = output.stdout.len();
@@ -128,6 +190,23 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
     Ok(output)
 }
 
+/// 异步执行 PowerShell 命令（供 async 安装流程使用）
+///
+/// PowerShell 冷启动可能需要数秒，直接在异步运行时线程上调用 run_powershell
+/// 会占住工作线程、拖慢其他命令；这里放入 spawn_blocking 执行，并保留当前 jobId 作用域
+///
+/// # 参数
+/// - `script`: PowerShell 脚本内容
+///
+/// # 返回
+/// - 与 run_powershell 相同
+pub async fn run_powershell_async(script: String) -> Result<std::process::Output, String> {
+    let job_id = super::job_log::current_job_id();
+    tokio::task::spawn_blocking(move || super::job_log::sync_scope(job_id, || run_powershell(&script)))
+        .await
+        .map_err(|e| format!("PowerShell 执行任务异常: {}", e))?
+}
+
 /// 带超时的 PowerShell 命令执行函数
 /// 
 /// # 参数
@@ -145,32 +224,40 @@ pub fn run_powershell(script: &str) -> Result<std::process::Output, String> {
 /// - 可配置的超时控制
 /// - 超时后自动杀死进程并返回明确错误码
 pub fn run_powershell_with_timeout(script: &str, timeout_ms: u64) -> Result<std::process::Output, String> {
-    let mut child = Command::new("powershell")
-        .args([
-            "-NoProfile",
-            "-NonInteractive",
-            "-WindowStyle", "Hidden",
-            "-Command",
-            script
-        ])
+    let args = [
+        "-NoProfile",
+        "-NonInteractive",
+        "-WindowStyle", "Hidden",
+        "-Command",
+        script
+    ];
+    let command_line = crate::utils::command_trace::format_command_line(&powershell_program().program, &args);
+    let start_time = Instant::now();
+    let mut child = super::process_watchdog::mark(&mut Command::new(&powershell_program().program))
+        .args(args)
         .stdin(Stdio::null())
         .stdout(Stdio::piped())
         .stderr(Stdio::piped())
         .creation_flags(CREATE_NO_WINDOW)
         .spawn()
-        .map_err(|e| format!("执行 PowerShell 命令失败: {}", e))?;
+        .map_err(|e| {
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("spawn_failed"));
+            format!("执行 PowerShell 命令失败: {}", e)
+        })?;
+    let _tracked = super::process_watchdog::track(&child, "powershell");
+    let drain = super::cmd::PipeDrain::start(&mut child);
     
-    let start_time = Instant::now();
     let timeout = Duration::from_millis(timeout_ms);
     
     // 轮询检查进程是否完成
-    loop {
+    let status = loop {
         // 先检查超时
         if start_time.elapsed() >= timeout {
             // 超时，杀死进程
             let _ = child.kill();
             // 等待进程结束，避免僵尸进程
             let _ = child.wait();
+            super::job_log::append_command_trace(&command_line, None, start_time, Some("timeout"));
             let elapsed_ms = start_time.elapsed().as_millis();
             // 提取脚本的前50个字符作为提示
             let script_hint = if script.len() > 50 {
@@ -185,9 +272,9 @@ pub fn run_powershell_with_timeout(script: &str, timeout_ms: u64) -> Result<std:
         }
         
         match child.try_wait() {
-            Ok(Some(_status)) => {
+            Ok(Some(status)) => {
                 // 进程已完成，退出循环
-                break;
+                break status;
             }
             Ok(None) => {
                 // 进程仍在运行，等待后继续
@@ -200,11 +287,18 @@ pub fn run_powershell_with_timeout(script: &str, timeout_ms: u64) -> Result<std:
                 return Err(format!("检查 PowerShell 进程状态失败: {}", e));
             }
         }
-    }
+    };
     
     // 循环结束后，获取输出
-    let output = child.wait_with_output()
-        .map_err(|e| format!("获取 PowerShell 输出失败: {}", e))?;
+    let output = drain.finish(status);
+    super::job_log::append_command_trace(&command_line, output.status.code(), start_time, None);
+    super::job_log::append_process_output(
+        &format!(
+            "powershell script_preview=\"{}\"",
+            super::log::truncate(&crate::utils::command_trace::scrub_command_line(script), 200)
+        ),
+        &output,
+    );
     Ok(output)
 }
 
@@ -0,0 +1,183 @@
+// Windows 平台打印队列暂停/恢复模块
+// 使用 Win32 Print Spooler API SetPrinterW(PRINTER_CONTROL_PAUSE / PRINTER_CONTROL_RESUME)
+// 另提供打开系统队列窗口（rundll32 printui.dll,PrintUIEntry /o），供用户查看排队中的作业
+
+use super::log;
+use winapi::um::winspool::{OpenPrinterW, SetPrinterW, ClosePrinter, PRINTER_DEFAULTSW};
+use winapi::um::winnt::LPWSTR;
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::ctypes::c_void;
+use std::ptr;
+
+// SetPrinterW 控制命令
+const PRINTER_CONTROL_PAUSE: u32 = 1;
+const PRINTER_CONTROL_RESUME: u32 = 2;
+
+// 暂停/恢复仅需要管理权限
+const PRINTER_ACCESS_ADMINISTER: u32 = 0x00000004;
+
+/// 队列控制动作
+#[derive(Debug, Clone, Copy)]
+pub enum QueueControlAction {
+    Pause,
+    Resume,
+}
+
+impl QueueControlAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            QueueControlAction::Pause => "pause",
+            QueueControlAction::Resume => "resume",
+        }
+    }
+
+    fn control_code(&self) -> u32 {
+        match self {
+            QueueControlAction::Pause => PRINTER_CONTROL_PAUSE,
+            QueueControlAction::Resume => PRINTER_CONTROL_RESUME,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            QueueControlAction::Pause => "暂停",
+            QueueControlAction::Resume => "恢复",
+        }
+    }
+}
+
+/// 将 Rust String 转换为 UTF-16 宽字符串（以 null 结尾）
+fn string_to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 将 Win32 错误码映射为统一错误码与用户提示
+fn map_win32_error(action: QueueControlAction, printer_name: &str, step: &str, error_code: u32) -> crate::platform::QueueControlResult {
+    let (code, message) = match error_code {
+        5 => (
+            "WIN_PERMISSION_DENIED",
+            format!(
+                "拒绝访问（5）。{}打印机需要管理员权限，请以管理员身份运行后重试。\n\n打印机: {}",
+                action.label(), printer_name
+            ),
+        ),
+        1801 | 2 => (
+            "WIN_PRINTER_NOT_FOUND",
+            format!("打印机 \"{}\" 不存在", printer_name),
+        ),
+        _ => (
+            "WIN_QUEUE_CONTROL_FAILED",
+            format!("{}打印机失败: error_code={}", action.label(), error_code),
+        ),
+    };
+
+    crate::platform::QueueControlResult {
+        success: false,
+        printer_name: printer_name.to_string(),
+        action: action.as_str().to_string(),
+        error_code: Some(code.to_string()),
+        message,
+        evidence: Some(format!("{} failed: error_code={}", step, error_code)),
+    }
+}
+
+/// Windows 平台暂停/恢复打印队列
+/// 
+/// # 参数
+/// - `printer_name`: 打印机队列名称
+/// - `action`: 暂停或恢复
+/// 
+/// # 返回
+/// - `Ok(QueueControlResult)`: 执行结果（失败时 success=false 且带 error_code）
+pub fn set_queue_state_windows(printer_name: &str, action: QueueControlAction) -> Result<crate::platform::QueueControlResult, String> {
+    log::write_log(&format!("[QueueControl] START printer_name=\"{}\" action={}", printer_name, action.as_str()));
+
+    unsafe {
+        let printer_name_wide = string_to_wide(printer_name);
+
+        let mut printer_handle: *mut c_void = ptr::null_mut();
+        let mut defaults: PRINTER_DEFAULTSW = PRINTER_DEFAULTSW {
+            pDataType: ptr::null_mut(),
+            pDevMode: ptr::null_mut(),
+            DesiredAccess: PRINTER_ACCESS_ADMINISTER,
+        };
+
+        let open_result = OpenPrinterW(
+            printer_name_wide.as_ptr() as LPWSTR,
+            &mut printer_handle,
+            &mut defaults,
+        );
+
+        if open_result == 0 {
+            let error_code = GetLastError();
+            log::write_log(&format!("[QueueControl] FAIL printer_name=\"{}\" step=OpenPrinterW error_code={}", printer_name, error_code));
+            return Ok(map_win32_error(action, printer_name, "OpenPrinterW", error_code));
+        }
+
+        let set_result = SetPrinterW(printer_handle, 0, ptr::null_mut(), action.control_code());
+        // 先取错误码再关闭句柄，避免 ClosePrinter 覆盖 LastError
+        let error_code = if set_result == 0 { GetLastError() } else { 0 };
+        let _ = ClosePrinter(printer_handle);
+
+        if set_result == 0 {
+            log::write_log(&format!("[QueueControl] FAIL printer_name=\"{}\" step=SetPrinterW error_code={}", printer_name, error_code));
+            return Ok(map_win32_error(action, printer_name, "SetPrinterW", error_code));
+        }
+    }
+
+    log::write_log(&format!("[QueueControl] OK printer_name=\"{}\" action={}", printer_name, action.as_str()));
+    Ok(crate::platform::QueueControlResult {
+        success: true,
+        printer_name: printer_name.to_string(),
+        action: action.as_str().to_string(),
+        error_code: None,
+        message: format!("已{}打印机: {}", action.label(), printer_name),
+        evidence: Some(format!("SetPrinterW ok command={}", action.control_code())),
+    })
+}
+
+/// Windows 平台打开打印队列窗口（查看排队中的作业）
+/// 
+/// 使用 `rundll32 printui.dll,PrintUIEntry /o /n "<name>"`；窗口由用户关闭，不等待进程结束，
+/// 也不纳入子进程清理（应用退出后窗口保留）
+/// 
+/// # 参数
+/// - `printer_name`: 打印机队列名称
+/// 
+/// # 返回
+/// - `Ok(OpenQueueResult)`: 执行结果（打印机不存在时 success=false）
+/// - `Err(String)`: rundll32 无法启动
+pub fn open_queue_window_windows(printer_name: &str) -> Result<crate::platform::OpenQueueResult, String> {
+    log::write_log(&format!("[QueueControl] START printer_name=\"{}\" action=open_queue", printer_name));
+
+    let (exists, last_error, _) = super::printer_exists::printer_exists(printer_name);
+    if !exists {
+        log::write_log(&format!(
+            "[QueueControl] FAIL printer_name=\"{}\" action=open_queue step=printer_exists last_error={:?}",
+            printer_name, last_error
+        ));
+        return Ok(crate::platform::OpenQueueResult {
+            success: false,
+            printer_name: printer_name.to_string(),
+            method: "printui".to_string(),
+            error_code: Some("WIN_PRINTER_NOT_FOUND".to_string()),
+            message: format!("打印机 \"{}\" 不存在", printer_name),
+            jobs_output: None,
+        });
+    }
+
+    std::process::Command::new("rundll32.exe")
+        .args(["printui.dll,PrintUIEntry", "/o", "/n", printer_name])
+        .spawn()
+        .map_err(|e| format!("启动 rundll32 printui.dll 失败: {}", e))?;
+
+    log::write_log(&format!("[QueueControl] OK printer_name=\"{}\" action=open_queue", printer_name));
+    Ok(crate::platform::OpenQueueResult {
+        success: true,
+        printer_name: printer_name.to_string(),
+        method: "printui".to_string(),
+        error_code: None,
+        message: format!("已打开打印队列: {}", printer_name),
+        jobs_output: None,
+    })
+}
@@ -37,7 +37,7 @@ fn generate_stable_id_for_config(printer_name: &str, printer_path: &str) -> Stri
                     for printer in &area.printers {
                         if printer.name == printer_name || printer.path == printer_path {
                             // 生成 hash
-                            let ip = printer_path.trim_start_matches("\\\\").trim_start_matches("\\").to_string();
+                            let ip = crate::platform::printer_address::strip_unc_prefix(printer_path);
                             let hash_input = format!("{}|{}|{}", area.area_name, printer.name, ip);
                             
                             let mut hasher = DefaultHasher::new();
@@ -56,7 +56,7 @@ fn generate_stable_id_for_config(printer_name: &str, printer_path: &str) -> Stri
     }
     
     // 如果找不到配置，使用 name + path 生成
-    let ip = printer_path.trim_start_matches("\\\\").trim_start_matches("\\").to_string();
+    let ip = crate::platform::printer_address::strip_unc_prefix(printer_path);
     let hash_input = format!("{}|{}", printer_name, ip);
     let mut hasher = DefaultHasher::new();
     hash_input.hash(&mut hasher);
@@ -205,7 +205,7 @@ fn enum_printers_level_2() -> Result<Vec<InstalledPrinter>, String> {
         } | ConvertTo-Json -Compress
     "#;
 
-    match cmd::run_command("powershell.exe", &[
+    match cmd::run_command(&super::ps::powershell_program().program, &[
         "-NoProfile",
         "-NonInteractive",
         "-Command",
@@ -213,11 +213,11 @@ fn enum_printers_level_2() -> Result<Vec<InstalledPrinter>, String> {
     ]) {
         Ok(output) => {
             if !output.status.success() {
-                let stderr = String::from_utf8_lossy(&output.stderr);
+                let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
                 return Err(format!("PowerShell Get-Printer 执行失败: {}", stderr));
             }
 
-            let stdout = String::from_utf8_lossy(&output.stdout);
+            let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
             
             // 解析 JSON 输出
             match serde_json::from_str::<Vec<serde_json::Value>>(&stdout) {
@@ -271,27 +271,11 @@ fn enum_printers_level_2() -> Result<Vec<InstalledPrinter>, String> {
     }
 }
 
-/// 从路径中提取 IP 地址
+/// 从路径中提取主机地址（IPv4 / IPv6 / hostname）
 fn extract_ip_from_path(path: &str) -> Option<String> {
-    // 移除 "\\\\" 前缀（如果有）
-    let path = path.trim_start_matches("\\\\");
-    // 移除 "\\" 前缀（如果有）
-    let path = path.trim_start_matches("\\");
-    
-    // 简单的 IP 地址匹配（IPv4）
-    if let Some(ip) = path.split('/').next() {
-        if ip.contains('.') && ip.split('.').count() == 4 {
-            return Some(ip.to_string());
-        }
-    }
-    
-    None
-}
-
-/// 将 IP 地址转换为下划线格式（用于端口名匹配）
-/// 例如：192.168.1.100 -> 192_168_1_100
-fn ip_to_underscore(ip: &str) -> String {
-    ip.replace('.', "_")
+    crate::platform::printer_address::PrinterAddress::parse(path)
+        .ok()
+        .map(|address| address.host_address())
 }
 
 /// 解析目标打印机（简化版：按名称精确匹配，可选 IP/端口名过滤）
@@ -307,7 +291,7 @@ fn ip_to_underscore(ip: &str) -> String {
 /// # 匹配规则
 /// 1. candidates = installed where name == config_name
 /// 2. if ip provided:
-///    - prefer candidates where port_name == "IP_<ip转下划线>" OR port_name contains ip
+///    - prefer candidates where port_name == "IP_<地址转下划线>" OR port_name contains ip
 /// 3. if still multiple:
 ///    - choose first deterministically（保持排序稳定：按 name/port_name 字典序）
 /// 4. if none: return Err("未找到同名打印机，无法删除/重装")
@@ -345,13 +329,16 @@ pub fn resolve_target_printer(config_name: String, ip: Option<String>) -> Result
     
     // 第二步：如果提供了 IP，优先匹配端口名
     if let Some(ip) = &ip {
-        let ip_underscore = format!("IP_{}", ip_to_underscore(ip));
+        let address = crate::platform::printer_address::PrinterAddress::parse(ip).ok();
         
-        // 优先匹配：port_name == "IP_<ip转下划线>" 或 port_name contains ip
+        // 优先匹配：port_name == "IP_<地址转下划线>" 或 port_name contains ip
         let ip_matches: Vec<&DetailedPrinterInfo> = candidates.iter()
             .filter(|p| {
                 if let Some(port) = &p.port_name {
-                    port == &ip_underscore || port.contains(ip)
+                    match &address {
+                        Some(address) => address.matches_port_name(port),
+                        None => port.contains(ip.as_str()),
+                    }
                 } else {
                     false
                 }
@@ -398,6 +385,18 @@ pub fn resolve_target_printer(config_name: String, ip: Option<String>) -> Result
     Ok(selected.name.clone())
 }
 
+/// 列出带 ePrinty tag 的打印机名称（由本工具安装并写入 tag 的队列）
+pub fn list_tagged_printer_names() -> Result<Vec<String>, String> {
+    let printers = enum_printers_level_2()?;
+    let names: Vec<String> = printers
+        .into_iter()
+        .filter(|p| extract_eprinty_tag(&p.comment, &p.location).is_some())
+        .map(|p| p.name)
+        .collect();
+    log::write_log(&format!("[ListTagged] 带 ePrinty tag 的打印机数: {}", names.len()));
+    Ok(names)
+}
+
 /// 从 comment 或 location 中提取 ePrinty tag
 fn extract_eprinty_tag(comment: &Option<String>, location: &Option<String>) -> Option<String> {
     // 检查 comment
@@ -487,6 +486,11 @@ pub async fn reinstall_printer_windows(
                 stderr: Some(format!("同名打印机已存在: {}", target_name)),
                 effective_dry_run: false, // 重装是真实操作
                 job_id,
+                log_path: None,
+                validation_errors: None,
+                attempts: None,
+                verified: false,
+                verification: None,
             });
         }
         Err(e) => {
@@ -510,7 +514,9 @@ pub async fn reinstall_printer_windows(
     ).await;
 
     match install_result {
-        Ok(result) => {
+        Ok(outcome) => {
+            let (result, attempts) = (outcome.result, outcome.attempts);
+            let verified = outcome.verification.as_ref().is_some_and(|v| v.verified);
             if result.success {
                 log::write_log(&format!("[ReinstallPrinter][#{}] INSTALL_PHASE_OK", call_id));
                 
@@ -536,6 +542,11 @@ pub async fn reinstall_printer_windows(
                     stdout: result.stdout,
                     stderr: result.stderr,
                     effective_dry_run: result.effective_dry_run, // 从安装结果中获取
+                    log_path: super::job_log::existing_job_log_path(&result.job_id),
+                    validation_errors: None,
+                    attempts: Some(attempts).filter(|a| !a.is_empty()),
+                    verified,
+                    verification: outcome.verification,
                     job_id: result.job_id, // 从安装结果中获取
                 })
             } else {
@@ -548,6 +559,11 @@ pub async fn reinstall_printer_windows(
                     stdout: result.stdout,
                     stderr: result.stderr,
                     effective_dry_run: result.effective_dry_run, // 从安装结果中获取
+                    log_path: super::job_log::existing_job_log_path(&result.job_id),
+                    validation_errors: None,
+                    attempts: Some(attempts).filter(|a| !a.is_empty()),
+                    verified,
+                    verification: outcome.verification,
                     job_id: result.job_id, // 从安装结果中获取
                 })
             }
@@ -565,6 +581,11 @@ pub async fn reinstall_printer_windows(
                 stderr: Some(e),
                 effective_dry_run: false, // 重装是真实操作
                 job_id,
+                log_path: None,
+                validation_errors: None,
+                attempts: None,
+                verified: false,
+                verification: None,
             })
         }
     }
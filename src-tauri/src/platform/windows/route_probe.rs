@@ -0,0 +1,26 @@
+// Windows 平台 TTL 受限 ping 模块
+// 连通性检查的逐跳诊断：ping -n 1 -i <ttl> -w <ms> 发送单个 ICMP 回显请求，
+// TTL 耗尽时由中途路由器回复 "TTL expired in transit" / "TTL 传输中过期"
+//
+// 只返回原始输出，响应地址的解析由服务层完成（中英文输出格式不同）
+
+use std::net::Ipv4Addr;
+
+use crate::platform::PingProbeOutput;
+
+/// 发送一个 TTL 受限的 ICMP 回显请求
+///
+/// # 参数
+/// - `target`: 目标地址
+/// - `ttl`: 生存时间（跳数）
+/// - `timeout_ms`: 等待回复的超时时间
+pub fn ping_with_ttl(target: Ipv4Addr, ttl: u8, timeout_ms: u32) -> Result<PingProbeOutput, String> {
+    let ttl_arg = ttl.to_string();
+    let timeout_arg = timeout_ms.to_string();
+    let target_arg = target.to_string();
+    let output = super::cmd::run_command("ping", &["-n", "1", "-i", &ttl_arg, "-w", &timeout_arg, &target_arg])?;
+    Ok(PingProbeOutput {
+        success: output.status.success(),
+        output: crate::utils::encoding::decode_windows_string(&output.stdout),
+    })
+}
@@ -0,0 +1,30 @@
+// Windows 平台计划任务触发模块
+// 委托安装：管理员预先注册以最高权限运行的计划任务，普通用户通过 schtasks /Run 触发
+// （计划任务需在"安全选项"中允许该用户运行；触发本身不需要管理员权限）
+//
+// 只负责触发，不等待任务完成；任务结果由服务层通过结果文件获取
+
+/// 立即运行已注册的计划任务
+///
+/// # 参数
+/// - `task_name`: 计划任务名称（含文件夹路径，如 "\ePrinty\DelegatedInstall"）
+///
+/// # 返回
+/// - `Ok(())`: 任务已触发
+/// - `Err(String)`: 任务不存在、当前用户无权运行或 schtasks 执行失败
+pub fn run_scheduled_task(task_name: &str) -> Result<(), String> {
+    let output = super::cmd::run_command("schtasks", &["/Run", "/TN", task_name])?;
+    if output.status.success() {
+        eprintln!("[ScheduledTask] triggered task=\"{}\"", task_name);
+        return Ok(());
+    }
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let detail = if stderr.trim().is_empty() { stdout } else { stderr };
+    Err(format!(
+        "触发计划任务 \"{}\" 失败（退出码 {:?}）: {}\n请确认管理员已注册该任务，并允许当前用户运行",
+        task_name,
+        output.status.code(),
+        detail.trim()
+    ))
+}
@@ -0,0 +1,140 @@
+// Windows 平台会话级缓存模块
+// 安装流程中 Windows 构建号检测与 Get-PrinterDriver 检查会多次启动 PowerShell（每次安装约 10 次），
+// 这里在应用会话内缓存结果：
+// - Windows 构建号：检测成功后整个会话不变
+// - 系统类型（桌面版 / 服务器版）：VerifyVersionInfo 检查 wProductType，整个会话不变
+// - 已注册打印机驱动清单：一次 Get-PrinterDriver 扫描，按名称查询；
+//   本应用安装/删除驱动后（Add-PrinterDriver / pnputil / printui / Remove-PrinterDriver）必须调用 invalidate_driver_inventory()
+//
+// 注意：应用外部的驱动变更不会自动反映到缓存；缓存命中"未安装"时只是多走一次注册流程
+
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::utils::encoding::decode_windows_string;
+use super::log;
+
+/// 已检测到的 Windows 构建号
+static WINDOWS_BUILD: OnceLock<u32> = OnceLock::new();
+
+/// 是否为服务器版系统
+static SERVER_EDITION: OnceLock<bool> = OnceLock::new();
+
+/// 已注册打印机驱动清单（None 表示未扫描或已失效）
+static DRIVER_INVENTORY: Mutex<Option<Arc<Vec<String>>>> = Mutex::new(None);
+
+/// 获取 Windows 构建号（会话内只检测一次；检测失败不缓存）
+///
+/// 注意：GetVersionExW API 在 Windows 10+ 可能返回兼容版本信息（如 9200），不准确
+/// 因此优先使用 PowerShell 获取真实版本信息
+pub fn windows_build_number() -> Result<u32, String> {
+    if let Some(build) = WINDOWS_BUILD.get() {
+        return Ok(*build);
+    }
+    let build = detect_windows_build_number()?;
+    eprintln!("[SessionCache] windows_build={} cached", build);
+    Ok(*WINDOWS_BUILD.get_or_init(|| build))
+}
+
+fn detect_windows_build_number() -> Result<u32, String> {
+    // 优先使用 PowerShell 检测真实构建号（更可靠）
+    // 使用 Get-CimInstance 获取真实的操作系统版本信息
+    for script in [
+        "(Get-CimInstance Win32_OperatingSystem).BuildNumber",
+        // 备用方案：使用 Environment.OSVersion
+        "[System.Environment]::OSVersion.Version.Build",
+    ] {
+        if let Ok(output) = super::ps::run_powershell(script) {
+            if let Ok(build_number) = decode_windows_string(&output.stdout).trim().parse::<u32>() {
+                return Ok(build_number);
+            }
+        }
+    }
+
+    // 最后的备用方案：使用 GetVersionExW（但可能不准确）
+    use winapi::um::sysinfoapi::GetVersionExW;
+    use winapi::um::winnt::OSVERSIONINFOW;
+
+    unsafe {
+        let mut os_info: OSVERSIONINFOW = std::mem::zeroed();
+        os_info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOW>() as u32;
+
+        if GetVersionExW(&mut os_info) != 0 {
+            Ok(os_info.dwBuildNumber)
+        } else {
+            Err("无法检测 Windows 构建号".to_string())
+        }
+    }
+}
+
+/// 当前系统是否为服务器版（Windows Server，含域控制器）
+pub fn is_server_edition() -> bool {
+    *SERVER_EDITION.get_or_init(|| {
+        use winapi::um::winbase::VerifyVersionInfoW;
+        use winapi::um::winnt::{VerSetConditionMask, OSVERSIONINFOEXW, VER_EQUAL, VER_NT_WORKSTATION, VER_PRODUCT_TYPE};
+
+        let is_workstation = unsafe {
+            let mut info: OSVERSIONINFOEXW = std::mem::zeroed();
+            info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOEXW>() as u32;
+            info.wProductType = VER_NT_WORKSTATION;
+            let mask = VerSetConditionMask(0, VER_PRODUCT_TYPE, VER_EQUAL);
+            VerifyVersionInfoW(&mut info, VER_PRODUCT_TYPE, mask) != 0
+        };
+        eprintln!("[SessionCache] server_edition={} cached", !is_workstation);
+        !is_workstation
+    })
+}
+
+/// 获取已注册打印机驱动名称清单（缓存失效时重新扫描）
+pub fn driver_inventory() -> Result<Arc<Vec<String>>, String> {
+    let mut guard = DRIVER_INVENTORY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(inventory) = guard.as_ref() {
+        return Ok(Arc::clone(inventory));
+    }
+    let inventory = Arc::new(scan_driver_inventory()?);
+    eprintln!("[SessionCache] driver_inventory scanned count={}", inventory.len());
+    *guard = Some(Arc::clone(&inventory));
+    Ok(inventory)
+}
+
+/// 按名称查找已注册的打印机驱动（忽略大小写，与 Get-PrinterDriver -Name 一致），返回系统中的驱动名
+pub fn find_installed_driver(driver_name: &str) -> Result<Option<String>, String> {
+    Ok(driver_inventory()?
+        .iter()
+        .find(|name| name.eq_ignore_ascii_case(driver_name.trim()))
+        .cloned())
+}
+
+/// 使驱动清单缓存失效（安装或删除驱动后调用）
+pub fn invalidate_driver_inventory() {
+    let mut guard = DRIVER_INVENTORY.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if guard.take().is_some() {
+        eprintln!("[SessionCache] driver_inventory invalidated");
+    }
+}
+
+fn scan_driver_inventory() -> Result<Vec<String>, String> {
+    let script = "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; \
+        @(Get-PrinterDriver -ErrorAction SilentlyContinue | Select-Object -ExpandProperty Name) \
+        | ConvertTo-Json -Compress -Depth 3";
+
+    let output = super::ps::run_powershell(script)?;
+    let stdout = decode_windows_string(&output.stdout);
+    if !output.status.success() {
+        let stderr = decode_windows_string(&output.stderr);
+        return Err(format!("查询已注册打印机驱动失败: {}", log::truncate(stderr.trim(), 300)));
+    }
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let json: serde_json::Value = serde_json::from_str(stdout.trim())
+        .map_err(|e| format!("解析已注册打印机驱动失败: {}", e))?;
+    // ConvertTo-Json 对单元素数组可能输出对象，这里统一为数组
+    let items = match json {
+        serde_json::Value::Array(items) => items,
+        serde_json::Value::Null => Vec::new(),
+        other => vec![other],
+    };
+
+    Ok(items.iter().filter_map(|v| v.as_str().map(|s| s.to_string())).collect())
+}
@@ -0,0 +1,185 @@
+// Windows 平台 SMB 共享打印机凭据模块
+// 连接 \\server\queue 需要当前用户无法提供的凭据时，使用保存在 Windows 凭据管理器中的账号密码：
+// - 凭据按打印服务器保存（同一服务器上的共享打印机共用），目标名 ePrinty/SMB/<server>，类型 GENERIC
+// - Add-Printer -ConnectionName 之前以 WNetAddConnection2 建立到 \\server\IPC$ 的会话
+// - SmbSession 在 Drop 时断开会话（不影响已建立的打印机连接，也不落盘为持久映射）
+//
+// 封装入口：
+// - store_credentials() / delete_credentials()
+// - connect_for_path(): 有已保存凭据时建立会话，返回 Some(SmbSession)
+
+use std::ptr;
+
+use crate::platform::printer_address::PrinterAddress;
+
+use winapi::shared::minwindef::{DWORD, FALSE, TRUE};
+use winapi::shared::winerror::{ERROR_NOT_FOUND, NO_ERROR};
+use winapi::um::errhandlingapi::GetLastError;
+use winapi::um::wincred::{
+    CredDeleteW, CredFree, CredReadW, CredWriteW, CREDENTIALW, CRED_PERSIST_LOCAL_MACHINE, CRED_TYPE_GENERIC,
+    PCREDENTIALW,
+};
+use winapi::um::winnetwk::{WNetAddConnection2W, WNetCancelConnection2W, NETRESOURCEW, RESOURCETYPE_ANY};
+
+/// 已存在使用其他凭据的会话（WNetAddConnection2 返回）
+const ERROR_SESSION_CREDENTIAL_CONFLICT: DWORD = 1219;
+
+/// 凭据目标名前缀
+const TARGET_PREFIX: &str = "ePrinty/SMB/";
+
+/// 将 Rust String 转换为 UTF-16 宽字符串（以 null 结尾）
+fn to_wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(std::iter::once(0)).collect()
+}
+
+/// 从 \\server\queue 中提取服务器名
+pub fn server_from_path(path: &str) -> Result<String, String> {
+    match PrinterAddress::parse(path) {
+        Ok(PrinterAddress::SmbShare { server, .. }) => Ok(server),
+        _ => Err(format!("不是有效的共享打印机路径（应为 \\\\server\\queue）: {}", path)),
+    }
+}
+
+fn target_name(server: &str) -> String {
+    format!("{}{}", TARGET_PREFIX, server.to_lowercase())
+}
+
+/// 保存服务器凭据到 Windows 凭据管理器（已存在时覆盖）
+pub fn store_credentials(server: &str, username: &str, password: &str) -> Result<(), String> {
+    let mut target = to_wide(&target_name(server));
+    let mut user = to_wide(username);
+    // 密码以 UTF-16（不含结尾 null）保存，与系统凭据的约定一致
+    let mut blob: Vec<u8> = password.encode_utf16().flat_map(|u| u.to_le_bytes()).collect();
+
+    let mut credential: CREDENTIALW = unsafe { std::mem::zeroed() };
+    credential.Type = CRED_TYPE_GENERIC;
+    credential.TargetName = target.as_mut_ptr();
+    credential.UserName = user.as_mut_ptr();
+    credential.CredentialBlobSize = blob.len() as DWORD;
+    credential.CredentialBlob = if blob.is_empty() { ptr::null_mut() } else { blob.as_mut_ptr() };
+    credential.Persist = CRED_PERSIST_LOCAL_MACHINE;
+
+    let ok = unsafe { CredWriteW(&mut credential, 0) };
+    // 尽快清掉内存中的密码副本
+    blob.iter_mut().for_each(|b| *b = 0);
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        return Err(format!("保存凭据失败（server={}，错误码 {}）", server, code));
+    }
+    eprintln!("[SmbCredentials] stored server=\"{}\" user=\"{}\"", server, username);
+    Ok(())
+}
+
+/// 删除服务器凭据（不存在时视为成功）
+pub fn delete_credentials(server: &str) -> Result<bool, String> {
+    let target = to_wide(&target_name(server));
+    let ok = unsafe { CredDeleteW(target.as_ptr(), CRED_TYPE_GENERIC, 0) };
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        if code == ERROR_NOT_FOUND {
+            return Ok(false);
+        }
+        return Err(format!("删除凭据失败（server={}，错误码 {}）", server, code));
+    }
+    eprintln!("[SmbCredentials] deleted server=\"{}\"", server);
+    Ok(true)
+}
+
+/// 读取服务器凭据：Ok(None) 表示未保存
+fn read_credentials(server: &str) -> Result<Option<(String, String)>, String> {
+    let target = to_wide(&target_name(server));
+    let mut credential: PCREDENTIALW = ptr::null_mut();
+    let ok = unsafe { CredReadW(target.as_ptr(), CRED_TYPE_GENERIC, 0, &mut credential) };
+    if ok == FALSE {
+        let code = unsafe { GetLastError() };
+        if code == ERROR_NOT_FOUND {
+            return Ok(None);
+        }
+        return Err(format!("读取凭据失败（server={}，错误码 {}）", server, code));
+    }
+
+    let result = unsafe {
+        let cred = &*credential;
+        let username = if cred.UserName.is_null() {
+            String::new()
+        } else {
+            let len = (0..).take_while(|&i| *cred.UserName.offset(i) != 0).count();
+            String::from_utf16_lossy(std::slice::from_raw_parts(cred.UserName, len))
+        };
+        let password = if cred.CredentialBlob.is_null() || cred.CredentialBlobSize == 0 {
+            String::new()
+        } else {
+            let bytes = std::slice::from_raw_parts(cred.CredentialBlob, cred.CredentialBlobSize as usize);
+            let wide: Vec<u16> = bytes.chunks_exact(2).map(|c| u16::from_le_bytes([c[0], c[1]])).collect();
+            String::from_utf16_lossy(&wide)
+        };
+        CredFree(credential as *mut _);
+        (username, password)
+    };
+    Ok(Some(result))
+}
+
+/// 已建立的 SMB 会话（Drop 时断开）
+pub struct SmbSession {
+    remote: String,
+}
+
+impl Drop for SmbSession {
+    fn drop(&mut self) {
+        let remote = to_wide(&self.remote);
+        let code = unsafe { WNetCancelConnection2W(remote.as_ptr(), 0, TRUE) };
+        if code == NO_ERROR {
+            eprintln!("[SmbCredentials] session closed remote=\"{}\"", self.remote);
+        } else {
+            eprintln!("[SmbCredentials] close session failed remote=\"{}\" code={}", self.remote, code);
+        }
+    }
+}
+
+/// 有已保存凭据时，为共享打印机路径建立 SMB 会话
+///
+/// # 参数
+/// - `path`: 共享打印机路径（\\server\queue）
+///
+/// # 返回
+/// - `Ok(Some(SmbSession))`: 已使用保存的凭据建立会话（Add-Printer 完成后释放即可）
+/// - `Ok(None)`: 未保存凭据，沿用当前用户身份
+/// - `Err(String)`: 凭据读取失败或会话建立失败（如账号密码错误）
+pub fn connect_for_path(path: &str) -> Result<Option<SmbSession>, String> {
+    let server = server_from_path(path)?;
+    let (username, password) = match read_credentials(&server)? {
+        Some(c) => c,
+        None => return Ok(None),
+    };
+
+    let remote = format!("\\\\{}\\IPC$", server);
+    let mut remote_wide = to_wide(&remote);
+    let user_wide = to_wide(&username);
+    let password_wide = to_wide(&password);
+
+    let mut resource: NETRESOURCEW = unsafe { std::mem::zeroed() };
+    resource.dwType = RESOURCETYPE_ANY;
+    resource.lpRemoteName = remote_wide.as_mut_ptr();
+
+    // flags=0：不记住连接，不写入持久映射
+    let code = unsafe {
+        WNetAddConnection2W(
+            &mut resource,
+            password_wide.as_ptr(),
+            if username.is_empty() { ptr::null() } else { user_wide.as_ptr() },
+            0,
+        )
+    };
+    match code {
+        NO_ERROR => {
+            eprintln!("[SmbCredentials] session opened remote=\"{}\" user=\"{}\"", remote, username);
+            super::job_log::append_line(&format!("[SmbCredentials] 已使用保存的凭据连接 {}（用户 {}）", remote, username));
+            Ok(Some(SmbSession { remote }))
+        }
+        ERROR_SESSION_CREDENTIAL_CONFLICT => Err(format!(
+            "已存在使用其他账号到 {} 的连接（错误码 1219），请先断开（net use {} /delete）后重试",
+            server, remote
+        )),
+        _ => Err(format!("使用保存的凭据连接 {} 失败（错误码 {}），请检查用户名和密码", remote, code)),
+    }
+}
@@ -0,0 +1,98 @@
+// Windows 平台远程桌面（RDS / 终端服务器）环境检测模块
+// RDS 会话主机上多个用户共用一台机器：本机创建的打印队列、端口和驱动对所有登录用户生效，
+// 客户端打印机还会以 Easy Print 重定向队列（"xxx (重定向 2)"、端口 TS001）出现在会话中
+//
+// - is_terminal_server：VerifyVersionInfo 含 VER_SUITE_TERMINAL 且不含 VER_SUITE_SINGLEUSERTS
+//   （普通 Windows 10/11 开启远程桌面时为单用户模式，不算终端服务器）
+// - is_remote_session：GetSystemMetrics(SM_REMOTESESSION)
+// - printer_redirection_enabled：Terminal Services 策略 fDisableCpm 未置 1（仅终端服务器上检测）
+//
+// 检测结果在应用会话内缓存
+
+use std::sync::OnceLock;
+
+use crate::platform::TerminalServerStatus;
+
+use winapi::um::processthreadsapi::{GetCurrentProcessId, ProcessIdToSessionId};
+use winapi::um::winbase::VerifyVersionInfoW;
+use winapi::um::winnt::{
+    VerSetConditionMask, OSVERSIONINFOEXW, VER_AND, VER_SUITENAME, VER_SUITE_SINGLEUSERTS, VER_SUITE_TERMINAL,
+};
+use winapi::um::winuser::{GetSystemMetrics, SM_REMOTESESSION};
+
+/// 远程桌面打印机重定向策略所在注册表键
+const TS_POLICY_KEY: &str = r"HKLM\SOFTWARE\Policies\Microsoft\Windows NT\Terminal Services";
+
+static STATUS: OnceLock<TerminalServerStatus> = OnceLock::new();
+
+/// 获取远程桌面环境状态（会话内只检测一次）
+pub fn status() -> TerminalServerStatus {
+    STATUS
+        .get_or_init(|| {
+            let status = detect();
+            eprintln!(
+                "[TerminalServer] is_terminal_server={} is_remote_session={} session_id={:?} printer_redirection_enabled={}",
+                status.is_terminal_server, status.is_remote_session, status.session_id, status.printer_redirection_enabled
+            );
+            status
+        })
+        .clone()
+}
+
+fn detect() -> TerminalServerStatus {
+    let is_terminal_server = suite_present(VER_SUITE_TERMINAL) && !suite_present(VER_SUITE_SINGLEUSERTS);
+    let is_remote_session = unsafe { GetSystemMetrics(SM_REMOTESESSION) } != 0;
+
+    let mut session_id: u32 = 0;
+    let session_id = if unsafe { ProcessIdToSessionId(GetCurrentProcessId(), &mut session_id) } != 0 {
+        Some(session_id)
+    } else {
+        None
+    };
+
+    let printer_redirection_enabled = is_terminal_server && read_policy_dword("fDisableCpm") != Some(1);
+
+    let mut warnings = Vec::new();
+    if is_terminal_server {
+        warnings.push(
+            "当前电脑为远程桌面会话主机（RDS）：本机安装的打印机、端口和驱动对所有登录用户生效".to_string(),
+        );
+    }
+    if printer_redirection_enabled {
+        warnings.push(
+            "已启用远程桌面打印机重定向：客户端打印机会以 Easy Print 重定向队列出现，可能与本应用安装的打印机重复".to_string(),
+        );
+    }
+
+    TerminalServerStatus {
+        is_terminal_server,
+        is_remote_session,
+        session_id,
+        printer_redirection_enabled,
+        warnings,
+    }
+}
+
+/// 检查系统是否包含指定的产品套件标志
+fn suite_present(suite: u32) -> bool {
+    unsafe {
+        let mut info: OSVERSIONINFOEXW = std::mem::zeroed();
+        info.dwOSVersionInfoSize = std::mem::size_of::<OSVERSIONINFOEXW>() as u32;
+        info.wSuiteMask = suite as u16;
+        let mask = VerSetConditionMask(0, VER_SUITENAME, VER_AND);
+        VerifyVersionInfoW(&mut info, VER_SUITENAME, mask) != 0
+    }
+}
+
+/// 读取远程桌面策略 DWORD 值（未配置或读取失败时返回 None）
+fn read_policy_dword(value_name: &str) -> Option<u32> {
+    let output = super::cmd::run_command("reg.exe", &["query", TS_POLICY_KEY, "/v", value_name]).ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    // 输出格式：    fDisableCpm    REG_DWORD    0x1
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let line = stdout.lines().find(|line| line.contains("REG_DWORD"))?;
+    let hex = line.split_whitespace().last()?.trim_start_matches("0x");
+    u32::from_str_radix(hex, 16).ok()
+}
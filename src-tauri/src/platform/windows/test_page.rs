@@ -1,5 +1,15 @@
 use std::fs;
 use std::io::Write;
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::platform::ps_script::PsScript;
+
+/// 是否保留测试页临时文件（用户设置 keepTestPageFile，调试开关）
+static KEEP_TEST_PAGE_FILE: AtomicBool = AtomicBool::new(false);
+
+/// 设置是否保留测试页临时文件（由用户设置同步）
+pub fn set_keep_test_page_file(keep: bool) {
+    KEEP_TEST_PAGE_FILE.store(keep, Ordering::SeqCst);
+}
 
 /// Windows 平台打印测试页实现
 /// 
@@ -34,15 +44,15 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
     // 非 Windows 平台：保持原有 PowerShell 检查逻辑
     #[cfg(not(target_os = "windows"))]
     let printer_exists = {
-        let escaped_printer_name = printer_name.replace("'", "''");
-        let check_script = format!(
-            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $q = '{}'; $printer = Get-Printer -Name $q -ErrorAction SilentlyContinue | Where-Object {{ $_.Name -eq $q }} | Select-Object -ExpandProperty Name",
-            escaped_printer_name
-        );
+        let check_script = PsScript::new(
+            "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $printer = Get-Printer -Name $PrinterName -ErrorAction SilentlyContinue | Where-Object { $_.Name -eq $PrinterName } | Select-Object -ExpandProperty Name",
+        )
+        .param("PrinterName", printer_name)
+        .build();
         
         match crate::platform::windows::ps::run_powershell(&check_script) {
             Ok(output) => {
-                let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
+                let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
                 let exit_code = output.status.code();
                 // 二次确认：验证返回的名称是否完全等于 printer_name
                 let exists = exit_code == Some(0) 
@@ -69,12 +79,11 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
     let now = chrono::Local::now();
     let test_content = crate::platform::test_page_content::build_test_page_content(
         &printer_name,
-        &now.format("%Y年%m月%d日 %H:%M:%S").to_string(),
+        &now.format(crate::platform::test_page_content::TEST_PAGE_TIME_FORMAT).to_string(),
     );
     
     // 创建临时文件
-    let temp_dir = std::env::temp_dir();
-    let temp_file = temp_dir.join(format!("printer_test_{}.txt", std::process::id()));
+    let temp_file = super::job_workdir::temp_path("printer_test.txt");
     let temp_file_display = super::log::sanitize_path(&temp_file);
     eprintln!("[PrintTestPage] TEMP_FILE_CREATE path=\"{}\"", temp_file_display);
     
@@ -83,12 +92,8 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
         let mut file = fs::File::create(&temp_file)
             .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=创建临时文件失败: {}", e))?;
         
-        // 写入 UTF-8 BOM
-        file.write_all(&[0xEF, 0xBB, 0xBF])
-            .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=写入 UTF-8 BOM 失败: {}", e))?;
-        
-        // 写入测试内容
-        file.write_all(test_content.as_bytes())
+        // 写入测试内容（UTF-8 BOM + 文本，与预览字节一致）
+        file.write_all(&crate::platform::test_page_content::build_test_page_bytes(&test_content))
             .map_err(|e| format!("[PrintTestPage] ERROR step=TEMP_FILE_CREATE message=写入测试内容失败: {}", e))?;
         
         file.sync_all()
@@ -101,16 +106,15 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
     eprintln!("[PrintTestPage] TEMP_FILE_CREATE result size={} bytes", file_size);
     
     // 使用 PowerShell 打印文件到指定打印机（主路径，无 fallback）
-    let ps_command = format!(
-        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $content = Get-Content '{}' -Encoding UTF8 -Raw; $content | Out-Printer -Name '{}'; if ($LASTEXITCODE -eq 0 -or $?) {{ Write-Output 'Success' }} else {{ Write-Error '打印失败' }}",
-        temp_file.to_str().unwrap().replace("'", "''").replace("\\", "\\\\"),
-        printer_name.replace("'", "''").replace("\\", "\\\\")
-    );
+    let ps_command = PsScript::new(
+        "[Console]::OutputEncoding = [System.Text.Encoding]::UTF8; $content = Get-Content -LiteralPath $File -Encoding UTF8 -Raw; $content | Out-Printer -Name $Name; if ($LASTEXITCODE -eq 0 -or $?) { Write-Output 'Success' } else { Write-Error '打印失败' }",
+    )
+    .param("File", temp_file.to_string_lossy())
+    .param("Name", &printer_name)
+    .build();
     
     // 检查是否保留临时文件（调试开关）
-    let keep_file = std::env::var("EPRINTY_DEBUG_KEEP_TESTPAGE_FILE")
-        .map(|v| v == "1")
-        .unwrap_or(false);
+    let keep_file = KEEP_TEST_PAGE_FILE.load(Ordering::SeqCst);
     
     let output = match crate::platform::windows::ps::run_powershell(&ps_command) {
         Ok(output) => output,
@@ -126,8 +130,8 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
         }
     };
     
-    let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
-    let stderr = crate::platform::windows::encoding::decode_windows_string(&output.stderr);
+    let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
+    let stderr = crate::utils::encoding::decode_windows_string(&output.stderr);
     
     // 使用统一的截断函数（默认 2000 字符）
     let stdout_display = super::log::truncate(&stdout, 2000);
@@ -173,13 +177,14 @@ pub fn print_test_page_windows(printer_name: String) -> Result<String, String> {
 /// - `step`: 失败步骤
 fn print_printer_evidence(printer_name: &str, step: &str) {
     // 获取打印机详细信息（DriverName、PortName）
-    let info_script = format!(
-        "Get-Printer -Name '{}' -ErrorAction SilentlyContinue | Select-Object Name, DriverName, PortName | ConvertTo-Json -Compress",
-        printer_name.replace("'", "''")
-    );
+    let info_script = PsScript::new(
+        "Get-Printer -Name $Name -ErrorAction SilentlyContinue | Select-Object Name, DriverName, PortName | ConvertTo-Json -Compress",
+    )
+    .param("Name", printer_name)
+    .build();
     
     if let Ok(output) = crate::platform::windows::ps::run_powershell(&info_script) {
-        let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
+        let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
         if !stdout.trim().is_empty() {
             let info_display = super::log::truncate(&stdout, 500);
             eprintln!("[PrintTestPage] EVIDENCE step={} printer_info=\"{}\"", step, info_display);
@@ -187,13 +192,14 @@ fn print_printer_evidence(printer_name: &str, step: &str) {
     }
     
     // 检查权限问题（Access Denied）
-    let perm_script = format!(
-        "Get-Printer -Name '{}' -ErrorAction SilentlyContinue | Select-Object -ExpandProperty PermissionSDDL",
-        printer_name.replace("'", "''")
-    );
+    let perm_script = PsScript::new(
+        "Get-Printer -Name $Name -ErrorAction SilentlyContinue | Select-Object -ExpandProperty PermissionSDDL",
+    )
+    .param("Name", printer_name)
+    .build();
     
     if let Ok(output) = crate::platform::windows::ps::run_powershell(&perm_script) {
-        let stdout = crate::platform::windows::encoding::decode_windows_string(&output.stdout);
+        let stdout = crate::utils::encoding::decode_windows_string(&output.stdout);
         if !stdout.trim().is_empty() {
             let perm_display = super::log::truncate(&stdout, 4000);
             eprintln!("[PrintTestPage] EVIDENCE step={} permission_sddl=\"{}\"", step, perm_display);
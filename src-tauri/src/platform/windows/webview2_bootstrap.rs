@@ -0,0 +1,159 @@
+// Windows 平台 WebView2 运行时引导安装模块
+// 应用因缺少 WebView2 无法创建窗口时，不依赖 WebView 的纯 Win32 对话框流程：
+//
+// - offer_install_and_relaunch(): MessageBox 询问用户 → 下载 Evergreen Bootstrapper 到临时目录
+//   → 以 /silent /install 静默安装并等待结束 → 成功后以原参数重新启动应用并退出当前进程
+// - 用户拒绝、下载或安装失败时返回，由调用方继续显示原有的错误提示（含手动下载地址）
+
+use std::ffi::OsStr;
+use std::io::Read;
+use std::os::windows::ffi::OsStrExt;
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+use winapi::um::winuser::{MessageBoxW, IDYES, MB_ICONERROR, MB_ICONINFORMATION, MB_ICONWARNING, MB_OK, MB_YESNO};
+
+const CREATE_NO_WINDOW: u32 = 0x08000000;
+
+/// Bootstrapper 下载超时
+const DOWNLOAD_TIMEOUT_SECS: u64 = 120;
+
+/// Bootstrapper 体积上限（正常约 2 MB）
+const MAX_BOOTSTRAPPER_BYTES: usize = 20 * 1024 * 1024;
+
+/// 静默安装最长等待时间（Bootstrapper 会在线下载完整运行时）
+const INSTALL_TIMEOUT_SECS: u64 = 600;
+
+const DIALOG_TITLE: &str = "ePrinty - WebView2 运行时";
+
+/// 重新启动时设置的环境变量：安装后仍无法启动时不再重复引导，直接显示错误提示
+const RELAUNCHED_ENV: &str = "EPRINTY_WEBVIEW2_BOOTSTRAPPED";
+
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+}
+
+fn message_box(message: &str, flags: u32) -> i32 {
+    let title = to_wide(DIALOG_TITLE);
+    let message = to_wide(message);
+    unsafe { MessageBoxW(std::ptr::null_mut(), message.as_ptr(), title.as_ptr(), flags) }
+}
+
+/// 下载 Bootstrapper 到临时目录
+fn download_bootstrapper(download_url: &str) -> Result<PathBuf, String> {
+    let client = reqwest::blocking::Client::builder()
+        .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+    let response = client
+        .get(download_url)
+        .send()
+        .map_err(|e| format!("下载 WebView2 安装程序失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("下载 WebView2 安装程序失败: 服务器返回 {}", response.status()));
+    }
+
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_BOOTSTRAPPER_BYTES as u64 + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("下载 WebView2 安装程序失败: {}", e))?;
+    if bytes.len() > MAX_BOOTSTRAPPER_BYTES {
+        return Err("下载的 WebView2 安装程序过大，已中止".to_string());
+    }
+    if !bytes.starts_with(b"MZ") {
+        return Err("下载的内容不是可执行文件（可能被代理或网关拦截）".to_string());
+    }
+
+    let path = std::env::temp_dir().join("ePrinty_MicrosoftEdgeWebview2Setup.exe");
+    std::fs::write(&path, &bytes).map_err(|e| format!("保存 WebView2 安装程序失败 {}: {}", path.display(), e))?;
+    eprintln!("[WebView2Bootstrap] downloaded size={} path=\"{}\"", bytes.len(), path.display());
+    Ok(path)
+}
+
+/// 静默安装并等待结束
+fn run_bootstrapper(path: &Path) -> Result<(), String> {
+    let mut child = std::process::Command::new(path)
+        .args(["/silent", "/install"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .spawn()
+        .map_err(|e| format!("启动 WebView2 安装程序失败: {}", e))?;
+
+    let started = Instant::now();
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(format!("WebView2 安装程序退出码 {:?}", status.code()));
+            }
+            Ok(None) if started.elapsed() > Duration::from_secs(INSTALL_TIMEOUT_SECS) => {
+                let _ = child.kill();
+                return Err(format!("WebView2 安装超过 {} 秒未完成", INSTALL_TIMEOUT_SECS));
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(500)),
+            Err(e) => return Err(format!("等待 WebView2 安装程序失败: {}", e)),
+        }
+    }
+}
+
+/// 以原参数重新启动应用
+fn relaunch() -> Result<(), String> {
+    let exe = std::env::current_exe().map_err(|e| format!("获取可执行文件路径失败: {}", e))?;
+    std::process::Command::new(&exe)
+        .args(std::env::args_os().skip(1))
+        .env(RELAUNCHED_ENV, "1")
+        .spawn()
+        .map(|_| ())
+        .map_err(|e| format!("重新启动应用失败: {}", e))
+}
+
+/// 询问用户并引导安装 WebView2，安装成功后重新启动应用（不返回）
+///
+/// # 参数
+/// - `download_url`: Evergreen Bootstrapper 下载地址
+///
+/// 仅在用户拒绝、下载 / 安装失败（已弹框说明原因），或本进程已是安装后重新启动的进程时返回
+pub fn offer_install_and_relaunch(download_url: &str) {
+    if std::env::var_os(RELAUNCHED_ENV).is_some() {
+        eprintln!("[WebView2Bootstrap] already bootstrapped once, skip");
+        return;
+    }
+
+    let answer = message_box(
+        "此应用需要 Microsoft Edge WebView2 运行时才能显示界面，当前电脑未安装或已损坏。\n\n\
+         是否现在自动下载并安装？\n\
+         • 下载约 2 MB 的微软官方安装程序，安装过程约需 1-3 分钟，期间无需操作\n\
+         • 安装完成后应用会自动重新启动\n\n\
+         选择“否”将显示手动安装说明。",
+        MB_YESNO | MB_ICONWARNING,
+    );
+    if answer != IDYES {
+        eprintln!("[WebView2Bootstrap] user declined");
+        return;
+    }
+
+    let result = download_bootstrapper(download_url).and_then(|path| {
+        let installed = run_bootstrapper(&path);
+        let _ = std::fs::remove_file(&path);
+        installed
+    });
+    if let Err(e) = result {
+        eprintln!("[WebView2Bootstrap] failed: {}", e);
+        message_box(
+            &format!("自动安装 WebView2 运行时失败：{}\n\n请按下一步提示手动安装。", e),
+            MB_OK | MB_ICONERROR,
+        );
+        return;
+    }
+
+    eprintln!("[WebView2Bootstrap] installed, relaunching");
+    if let Err(e) = relaunch() {
+        eprintln!("[WebView2Bootstrap] relaunch failed: {}", e);
+        message_box(
+            &format!("WebView2 运行时已安装，但{}。\n\n请手动重新打开 ePrinty。", e),
+            MB_OK | MB_ICONINFORMATION,
+        );
+    }
+    std::process::exit(0);
+}
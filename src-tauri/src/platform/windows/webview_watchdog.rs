@@ -0,0 +1,116 @@
+// Windows 平台 WebView2 进程监控模块
+// 应用运行期间 WebView2 运行时在后台自动更新、或渲染进程崩溃时，界面会白屏 / 无响应，
+// 而 Rust 侧的安装任务仍在运行；这里监听 ProcessFailed 事件，把失败类型交给调用方处理：
+//
+// - watch(): 为窗口注册 ProcessFailed 回调（回调在主线程执行，调用方应尽快返回）
+// - reload(): 重新加载页面（渲染进程退出 / 无响应时可恢复）
+// - confirm_dialog(): 原生 MessageBox（WebView 不可用时无法使用前端对话框）
+//
+// 浏览器主进程退出后同一窗口内的 WebView 无法恢复，只能重新启动应用
+
+use std::ffi::OsStr;
+use std::os::windows::ffi::OsStrExt;
+
+use webview2_com::Microsoft::Web::WebView2::Win32::{
+    COREWEBVIEW2_PROCESS_FAILED_KIND, COREWEBVIEW2_PROCESS_FAILED_KIND_BROWSER_PROCESS_EXITED,
+    COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_EXITED, COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE,
+};
+use webview2_com::ProcessFailedEventHandler;
+use winapi::um::winuser::{MessageBoxW, IDYES, MB_ICONWARNING, MB_SYSTEMMODAL, MB_YESNO};
+
+use super::log;
+use crate::platform::WebviewFailure;
+
+const DIALOG_TITLE: &str = "ePrinty - 界面异常";
+
+fn to_wide(s: &str) -> Vec<u16> {
+    OsStr::new(s).encode_wide().chain(Some(0)).collect()
+}
+
+fn failure_from_kind(kind: COREWEBVIEW2_PROCESS_FAILED_KIND) -> WebviewFailure {
+    if kind == COREWEBVIEW2_PROCESS_FAILED_KIND_BROWSER_PROCESS_EXITED {
+        WebviewFailure::BrowserExited
+    } else if kind == COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_EXITED {
+        WebviewFailure::RendererExited
+    } else if kind == COREWEBVIEW2_PROCESS_FAILED_KIND_RENDER_PROCESS_UNRESPONSIVE {
+        WebviewFailure::RendererUnresponsive
+    } else {
+        // GPU / 工具进程等子进程退出，WebView2 会自行重建，不影响页面
+        WebviewFailure::Other(kind.0)
+    }
+}
+
+/// 为窗口注册 WebView2 进程失败回调
+///
+/// # 参数
+/// - `window`: 主窗口
+/// - `on_failed`: 进程失败时调用（主线程）
+///
+/// # 返回
+/// - `Ok(())`: 已提交注册（实际注册在主线程执行，失败只记录日志）
+/// - `Err(String)`: 无法访问窗口的 WebView
+pub fn watch<F>(window: &tauri::Window, on_failed: F) -> Result<(), String>
+where
+    F: Fn(WebviewFailure) + Send + 'static,
+{
+    window
+        .with_webview(move |webview| {
+            let handler = ProcessFailedEventHandler::create(Box::new(move |_, args| {
+                let mut kind = COREWEBVIEW2_PROCESS_FAILED_KIND::default();
+                if let Some(args) = args {
+                    unsafe { args.ProcessFailedKind(&mut kind) }?;
+                }
+                let failure = failure_from_kind(kind);
+                log::write_log(&format!("[WebviewWatchdog] process failed kind={} failure={:?}", kind.0, failure));
+                on_failed(failure);
+                Ok(())
+            }));
+            let mut token = Default::default();
+            let registered = unsafe {
+                webview
+                    .controller()
+                    .CoreWebView2()
+                    .and_then(|core| core.add_ProcessFailed(&handler, &mut token))
+            };
+            match registered {
+                Ok(()) => log::write_log("[WebviewWatchdog] ProcessFailed handler registered"),
+                Err(e) => log::write_log(&format!("[WebviewWatchdog] 注册 ProcessFailed 回调失败: {}", e)),
+            }
+        })
+        .map_err(|e| format!("访问窗口 WebView 失败: {}", e))
+}
+
+/// 重新加载窗口页面
+///
+/// # 返回
+/// - `Ok(())`: 已提交重新加载（在主线程执行）
+/// - `Err(String)`: 无法访问窗口的 WebView
+pub fn reload(window: &tauri::Window) -> Result<(), String> {
+    window
+        .with_webview(|webview| {
+            let reloaded = unsafe { webview.controller().CoreWebView2().and_then(|core| core.Reload()) };
+            match reloaded {
+                Ok(()) => log::write_log("[WebviewWatchdog] reload requested"),
+                Err(e) => log::write_log(&format!("[WebviewWatchdog] 重新加载页面失败: {}", e)),
+            }
+        })
+        .map_err(|e| format!("访问窗口 WebView 失败: {}", e))
+}
+
+/// 原生是 / 否对话框（阻塞直到用户选择，应在后台线程调用）
+///
+/// # 返回
+/// - `true`: 用户选择"是"
+pub fn confirm_dialog(message: &str) -> bool {
+    let title = to_wide(DIALOG_TITLE);
+    let message = to_wide(message);
+    let choice = unsafe {
+        MessageBoxW(
+            std::ptr::null_mut(),
+            message.as_ptr(),
+            title.as_ptr(),
+            MB_YESNO | MB_ICONWARNING | MB_SYSTEMMODAL,
+        )
+    };
+    choice == IDYES
+}
@@ -0,0 +1,395 @@
+/**
+ * 应用状态导出 / 导入服务（机房镜像克隆）
+ * 职责：
+ * - export_app_state：把本地配置、用户设置、meta 状态（安装历史 / 主机解析记录 / 图片索引）与驱动缓存清单打包为一个 ZIP
+ * - import_app_state：校验后还原配置与 meta 状态，可选按导出时已安装的打印机重新执行安装（replayInstalls）
+ * - 机器相关的状态（fleet 登记记录、配置 HTTP 缓存）不导出；驱动缓存只导出清单（路径 + 大小），
+ *   导入时报告本机缺少的缓存文件，安装时按配置重新下载
+ *
+ * 归档格式：manifest.json + printer_config.json + meta/{name}.json，manifest.schemaVersion=1
+ */
+
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+/// 归档格式版本
+const ARCHIVE_SCHEMA_VERSION: u32 = 1;
+
+/// 归档中的条目名称
+const MANIFEST_ENTRY: &str = "manifest.json";
+const CONFIG_ENTRY: &str = "printer_config.json";
+
+/// 随应用状态导出的 meta 存储（不含机器相关的 fleet_registration / config_http_cache）
+const EXPORTED_META_STORES: [&str; 5] = ["settings", "install_history", "printer_hosts", "printer_images", "driver_refs"];
+
+/// 单个归档条目的体积上限（防止导入异常归档占满内存）
+const MAX_ENTRY_BYTES: u64 = 20 * 1024 * 1024;
+
+/// 驱动缓存清单条目
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverCacheEntry {
+    /// 相对驱动目录的路径（'/' 分隔）
+    pub path: String,
+    pub size: u64,
+}
+
+/// 归档清单（manifest.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppStateManifest {
+    pub schema_version: u32,
+    pub app_version: String,
+    pub platform: String,
+    pub exported_at: i64,
+    /// 归档中包含的 meta 存储名称
+    pub meta_stores: Vec<String>,
+    /// 导出时成功安装过的打印机（配置中的名称，replayInstalls 按此重新安装）
+    pub installed_printers: Vec<String>,
+    pub driver_cache: Vec<DriverCacheEntry>,
+}
+
+/// export_app_state 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportAppStateResult {
+    pub path: String,
+    pub meta_stores: Vec<String>,
+    pub installed_printers: Vec<String>,
+    pub driver_cache_files: usize,
+}
+
+/// 重新安装的单台打印机结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayInstallResult {
+    pub printer_name: String,
+    pub success: bool,
+    pub job_id: Option<String>,
+    pub message: String,
+}
+
+/// import_app_state 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportAppStateResult {
+    /// 已还原的条目（"printer_config.json" / meta 存储名称）
+    pub restored: Vec<String>,
+    /// 归档清单中有、本机驱动目录中缺少（或大小不同）的缓存文件
+    pub missing_driver_files: Vec<String>,
+    pub replayed: Vec<ReplayInstallResult>,
+}
+
+/// 驱动缓存清单（macOS 驱动目录为系统 PPD 目录，不属于本工具缓存，返回空）
+fn driver_cache_manifest() -> Vec<DriverCacheEntry> {
+    if cfg!(target_os = "macos") {
+        return Vec::new();
+    }
+    let Ok(root) = super::fs_paths::get_drivers_root() else {
+        return Vec::new();
+    };
+    walkdir::WalkDir::new(&root)
+        .sort_by_file_name()
+        .into_iter()
+        .flatten()
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let relative = entry.path().strip_prefix(&root).ok()?;
+            let path = relative
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy().to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+            Some(DriverCacheEntry { path, size: entry.metadata().ok()?.len() })
+        })
+        .collect()
+}
+
+/// 清单中本机缺少或大小不同的缓存文件
+fn missing_driver_files(manifest: &[DriverCacheEntry], local: &[DriverCacheEntry]) -> Vec<String> {
+    manifest
+        .iter()
+        .filter(|entry| !local.contains(entry))
+        .map(|entry| entry.path.clone())
+        .collect()
+}
+
+/// 安装历史中成功安装过的打印机（配置中的名称，去重，按首次安装顺序）
+async fn installed_config_names() -> Vec<String> {
+    let mut records = super::install_history::list_install_history(None).await.unwrap_or_default();
+    records.reverse();
+    let mut names: Vec<String> = Vec::new();
+    for record in records.into_iter().filter(|r| r.success && !r.dry_run) {
+        let name = record.display_name.unwrap_or(record.printer_name);
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    names
+}
+
+fn write_entry<W: Write + std::io::Seek>(zip: &mut zip::ZipWriter<W>, name: &str, content: &[u8]) -> Result<(), String> {
+    let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+    zip.start_file(name, options)
+        .map_err(|e| format!("写入归档条目失败 {}: {}", name, e))?;
+    zip.write_all(content)
+        .map_err(|e| format!("写入归档条目失败 {}: {}", name, e))
+}
+
+/// 打包应用状态（阻塞调用）
+fn export_blocking(config_path: PathBuf, output: PathBuf, installed_printers: Vec<String>) -> Result<ExportAppStateResult, String> {
+    let config_content = std::fs::read(&config_path)
+        .map_err(|e| format!("读取本地配置失败 {}: {}", config_path.display(), e))?;
+    let meta_dir = super::fs_paths::get_meta_dir()?;
+    let meta_files: Vec<(String, Vec<u8>)> = EXPORTED_META_STORES
+        .iter()
+        .filter_map(|name| {
+            let content = std::fs::read(meta_dir.join(format!("{}.json", name))).ok()?;
+            Some((name.to_string(), content))
+        })
+        .collect();
+
+    let manifest = AppStateManifest {
+        schema_version: ARCHIVE_SCHEMA_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: std::env::consts::OS.to_string(),
+        exported_at: chrono::Utc::now().timestamp_millis(),
+        meta_stores: meta_files.iter().map(|(name, _)| name.clone()).collect(),
+        installed_printers,
+        driver_cache: driver_cache_manifest(),
+    };
+    let manifest_json = serde_json::to_vec_pretty(&manifest).map_err(|e| format!("序列化归档清单失败: {}", e))?;
+
+    if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败 {}: {}", parent.display(), e))?;
+    }
+    let file = std::fs::File::create(&output).map_err(|e| format!("创建归档失败 {}: {}", output.display(), e))?;
+    let mut zip = zip::ZipWriter::new(file);
+    write_entry(&mut zip, MANIFEST_ENTRY, &manifest_json)?;
+    write_entry(&mut zip, CONFIG_ENTRY, &config_content)?;
+    for (name, content) in &meta_files {
+        write_entry(&mut zip, &format!("meta/{}.json", name), content)?;
+    }
+    zip.finish().map_err(|e| format!("完成归档写入失败 {}: {}", output.display(), e))?;
+
+    eprintln!(
+        "[AppState] exported path=\"{}\" meta={:?} printers={} driver_files={}",
+        output.display(),
+        manifest.meta_stores,
+        manifest.installed_printers.len(),
+        manifest.driver_cache.len()
+    );
+    Ok(ExportAppStateResult {
+        path: output.display().to_string(),
+        meta_stores: manifest.meta_stores,
+        installed_printers: manifest.installed_printers,
+        driver_cache_files: manifest.driver_cache.len(),
+    })
+}
+
+/// 导出应用状态到单个 ZIP 归档
+///
+/// # 参数
+/// - `output_path`: 输出归档路径（已存在时覆盖）
+///
+/// # 返回
+/// - `Ok(ExportAppStateResult)`: 导出内容摘要
+/// - `Err(String)`: 本地配置不存在或写入失败
+pub async fn export_app_state(app: &tauri::AppHandle, output_path: &str) -> Result<ExportAppStateResult, String> {
+    if output_path.trim().is_empty() {
+        return Err("导出路径不能为空".to_string());
+    }
+    let config_path = super::fs_paths::get_local_config_path(app)?;
+    let output = PathBuf::from(output_path.trim());
+    let installed_printers = installed_config_names().await;
+    tokio::task::spawn_blocking(move || export_blocking(config_path, output, installed_printers))
+        .await
+        .map_err(|e| format!("导出应用状态任务异常: {}", e))?
+}
+
+/// 读取归档条目（限制体积）
+fn read_entry<R: Read + std::io::Seek>(archive: &mut zip::ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let entry = archive
+        .by_name(name)
+        .map_err(|e| format!("归档缺少条目 {}: {}", name, e))?;
+    if entry.size() > MAX_ENTRY_BYTES {
+        return Err(format!("归档条目 {} 过大（{} 字节）", name, entry.size()));
+    }
+    let mut content = Vec::with_capacity(entry.size() as usize);
+    entry
+        .take(MAX_ENTRY_BYTES)
+        .read_to_end(&mut content)
+        .map_err(|e| format!("读取归档条目失败 {}: {}", name, e))?;
+    Ok(content)
+}
+
+/// 还原配置与 meta 状态（阻塞调用；先全部校验，再写入）
+fn import_blocking(archive_path: &Path, config_path: &Path) -> Result<(AppStateManifest, crate::PrinterConfig, Vec<String>), String> {
+    let file = std::fs::File::open(archive_path)
+        .map_err(|e| format!("打开归档失败 {}: {}", archive_path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("归档格式无效: {}", e))?;
+
+    let manifest: AppStateManifest = serde_json::from_slice(&read_entry(&mut archive, MANIFEST_ENTRY)?)
+        .map_err(|e| format!("解析归档清单失败: {}", e))?;
+    if manifest.schema_version > ARCHIVE_SCHEMA_VERSION {
+        return Err(format!(
+            "归档由更高版本的应用导出（schemaVersion={}，当前支持 {}），请升级应用",
+            manifest.schema_version, ARCHIVE_SCHEMA_VERSION
+        ));
+    }
+    if manifest.platform != std::env::consts::OS {
+        eprintln!("[AppState] WARN 归档来自 {} 平台，当前为 {}", manifest.platform, std::env::consts::OS);
+    }
+
+    let config_content = read_entry(&mut archive, CONFIG_ENTRY)?;
+    let config: crate::PrinterConfig =
+        serde_json::from_slice(&config_content).map_err(|e| format!("解析归档中的配置失败: {}", e))?;
+    crate::validate_printer_config_v2(&config)?;
+
+    let mut meta_files: Vec<(String, Vec<u8>)> = Vec::new();
+    for name in &manifest.meta_stores {
+        if !EXPORTED_META_STORES.contains(&name.as_str()) {
+            eprintln!("[AppState] skip unknown meta store \"{}\"", name);
+            continue;
+        }
+        let content = read_entry(&mut archive, &format!("meta/{}.json", name))?;
+        let value: serde_json::Value =
+            serde_json::from_slice(&content).map_err(|e| format!("解析归档中的 {} 失败: {}", name, e))?;
+        if value.get("schemaVersion").and_then(|v| v.as_u64()).is_none() {
+            return Err(format!("归档中的 {} 缺少 schemaVersion", name));
+        }
+        meta_files.push((name.clone(), content));
+    }
+
+    crate::save_config_to_local(&config, config_path)?;
+    super::config_sources::remember_saved_origin(None);
+    let mut restored = vec![CONFIG_ENTRY.to_string()];
+    let meta_dir = super::fs_paths::get_meta_dir()?;
+    for (name, content) in meta_files {
+        let path = meta_dir.join(format!("{}.json", name));
+        let _lock = super::meta_store::FileLock::acquire(&path)?;
+        super::meta_store::write_atomic(&path, &content)?;
+        restored.push(name);
+    }
+    Ok((manifest, config, restored))
+}
+
+/// 按配置重新安装一台打印机（与用户手动安装走同一入口）
+async fn replay_install(app: &tauri::AppHandle, config: &crate::PrinterConfig, name: &str) -> ReplayInstallResult {
+    let printer = config
+        .cities
+        .iter()
+        .flat_map(|c| c.areas.iter())
+        .find_map(|area| {
+            area.printers.iter().find(|p| p.name == name).map(|p| {
+                let mut resolved = p.clone();
+                resolved.options = p.options.or(&area.defaults);
+                resolved
+            })
+        });
+    let Some(printer) = printer else {
+        return ReplayInstallResult {
+            printer_name: name.to_string(),
+            success: false,
+            job_id: None,
+            message: "打印机不在导入的配置中，已跳过".to_string(),
+        };
+    };
+
+    let policy = printer.options.driver_install_policy.clone().unwrap_or_else(|| "always".to_string());
+    let result = crate::install_printer(
+        app.clone(),
+        printer.name.clone(),
+        printer.path.clone(),
+        printer.driver_key.clone(),
+        None,
+        printer.model.clone(),
+        Some(policy),
+        None,
+        Some(false),
+        None,
+    )
+    .await;
+    match result {
+        Ok(r) => ReplayInstallResult {
+            printer_name: name.to_string(),
+            success: r.success,
+            job_id: Some(r.job_id),
+            message: r.message,
+        },
+        Err(e) => ReplayInstallResult {
+            printer_name: name.to_string(),
+            success: false,
+            job_id: None,
+            message: e,
+        },
+    }
+}
+
+/// 从归档还原应用状态
+///
+/// # 参数
+/// - `archive_path`: export_app_state 生成的归档
+/// - `replay_installs`: 是否按导出时已安装的打印机依次重新安装
+///
+/// # 返回
+/// - `Ok(ImportAppStateResult)`: 已还原的条目、缺少的驱动缓存文件与重新安装结果
+/// - `Err(String)`: 归档无效、配置校验失败或写入失败（校验失败时本机状态保持不变）
+pub async fn import_app_state(
+    app: &tauri::AppHandle,
+    archive_path: &str,
+    replay_installs: bool,
+) -> Result<ImportAppStateResult, String> {
+    if archive_path.trim().is_empty() {
+        return Err("归档路径不能为空".to_string());
+    }
+    let config_path = super::fs_paths::get_local_config_path(app)?;
+    let archive = PathBuf::from(archive_path.trim());
+    let (manifest, config, restored) = tokio::task::spawn_blocking(move || import_blocking(&archive, &config_path))
+        .await
+        .map_err(|e| format!("导入应用状态任务异常: {}", e))??;
+
+    super::user_settings::reload();
+    let local_cache = tokio::task::spawn_blocking(driver_cache_manifest)
+        .await
+        .unwrap_or_default();
+    let missing = missing_driver_files(&manifest.driver_cache, &local_cache);
+
+    let mut replayed = Vec::new();
+    if replay_installs {
+        for name in &manifest.installed_printers {
+            let result = replay_install(app, &config, name).await;
+            eprintln!(
+                "[AppState] replay printer=\"{}\" success={} message=\"{}\"",
+                result.printer_name, result.success, result.message
+            );
+            replayed.push(result);
+        }
+    }
+
+    eprintln!(
+        "[AppState] imported restored={:?} missing_driver_files={} replayed={}",
+        restored,
+        missing.len(),
+        replayed.len()
+    );
+    Ok(ImportAppStateResult {
+        restored,
+        missing_driver_files: missing,
+        replayed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reports_missing_or_resized_driver_cache_files() {
+        let entry = |path: &str, size: u64| DriverCacheEntry { path: path.to_string(), size };
+        let manifest = vec![entry("hp/hp.inf", 10), entry("hp/hp.cab", 2000), entry("canon/canon.inf", 5)];
+        let local = vec![entry("hp/hp.inf", 10), entry("hp/hp.cab", 1999)];
+        assert_eq!(missing_driver_files(&manifest, &local), vec!["hp/hp.cab".to_string(), "canon/canon.inf".to_string()]);
+    }
+}
@@ -0,0 +1,70 @@
+/**
+ * 审计事件服务
+ * 职责：
+ * - 安装结束（成功 / 失败）、删除打印机后，把结构化审计事件写入系统事件日志（Windows 应用程序日志，事件源 ePrinty）
+ * - 驱动包添加事件由 Windows 安装流程在 stage 驱动成功时直接写入
+ * - dryRun 安装不写事件；写入失败只记录日志，不影响操作结果；配置 eventLog.enabled=false 时关闭
+ */
+
+use super::install_history::InstallRecord;
+use crate::platform::{AuditEventKind, DeletePrinterResult};
+
+/// 在阻塞线程中写入审计事件
+async fn report(kind: AuditEventKind, summary: String, fields: Vec<(&'static str, String)>) {
+    let result = tokio::task::spawn_blocking(move || crate::platform::report_audit_event(kind, &summary, &fields)).await;
+    match result {
+        Ok(Ok(_)) => {}
+        Ok(Err(e)) => eprintln!("[AuditLog] 写入审计事件失败 kind={:?}: {}", kind, e),
+        Err(e) => eprintln!("[AuditLog] 写入任务异常: {}", e),
+    }
+}
+
+fn current_user() -> String {
+    std::env::var("USERNAME").or_else(|_| std::env::var("USER")).unwrap_or_default()
+}
+
+/// 记录安装结果（dryRun 不记录）
+pub async fn record_install(record: &InstallRecord) {
+    if record.dry_run {
+        return;
+    }
+    let (kind, summary) = if record.success {
+        (AuditEventKind::InstallSucceeded, format!("ePrinty 已安装打印机 {}", record.printer_name))
+    } else {
+        (AuditEventKind::InstallFailed, format!("ePrinty 安装打印机 {} 失败", record.printer_name))
+    };
+    let fields = vec![
+        ("action", "install".to_string()),
+        ("result", if record.success { "success" } else { "failure" }.to_string()),
+        ("printer", record.printer_name.clone()),
+        ("driverKey", record.driver_key.clone().unwrap_or_default()),
+        ("installMode", record.install_mode.clone().unwrap_or_default()),
+        ("jobId", record.job_id.clone()),
+        ("durationMs", (record.finished_at - record.started_at).to_string()),
+        ("user", current_user()),
+        ("message", record.message.clone()),
+    ];
+    report(kind, summary, fields).await;
+}
+
+/// 记录删除打印机结果
+pub async fn record_delete(result: &DeletePrinterResult) {
+    let (kind, summary) = if result.success {
+        (AuditEventKind::PrinterDeleted, format!("ePrinty 已删除打印机 {}", result.printer_name))
+    } else {
+        (AuditEventKind::PrinterDeleteFailed, format!("ePrinty 删除打印机 {} 未完成", result.printer_name))
+    };
+    let fields = vec![
+        ("action", "delete".to_string()),
+        ("result", if result.success { "success" } else { "failure" }.to_string()),
+        ("printer", result.printer_name.clone()),
+        ("removedQueue", result.removed_queue.to_string()),
+        ("removedPort", result.removed_port.to_string()),
+        ("removedDriver", result.removed_driver.to_string()),
+        ("port", result.port_name.clone().unwrap_or_default()),
+        ("driver", result.driver_name.clone().unwrap_or_default()),
+        ("user", current_user()),
+        ("message", result.message.clone()),
+    ];
+    report(kind, summary, fields).await;
+}
@@ -0,0 +1,229 @@
+/**
+ * 打印机目录视图服务
+ * 职责：
+ * - get_printer_catalog：将配置中的打印机按区域分组、排序、分页（前端直接渲染，无需再过滤原始配置）
+ * - 合并系统安装状态（installed / online），匹配规则与前端 isInstalled 保持一致
+ */
+
+use crate::platform::PrinterDetectEntry;
+use serde::{Deserialize, Serialize};
+
+/// 默认每页打印机数量
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// 每页打印机数量上限
+const MAX_PAGE_SIZE: usize = 200;
+
+/// CUPS 队列状态：stopped
+const CUPS_STATE_STOPPED: i32 = 5;
+
+/// get_printer_catalog 查询参数
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterCatalogQuery {
+    /// 区域过滤（匹配 areaName 及其上级区域 / cityName，忽略大小写的包含匹配；为空时不过滤）
+    pub area: Option<String>,
+    /// 页码（从 1 开始，默认 1）
+    pub page: Option<usize>,
+    /// 每页打印机数量（默认 50，最大 200）
+    pub page_size: Option<usize>,
+    /// 排序方式："config"（配置顺序，默认）| "name"（区域、打印机按名称）| "installed"（区域内已安装优先）
+    pub sort: Option<String>,
+}
+
+/// 目录中的单台打印机（配置 + 安装状态）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterCatalogItem {
+    pub name: String,
+    pub path: String,
+    pub model: Option<String>,
+    pub driver_key: Option<String>,
+    pub installed: bool,
+    /// 已安装队列是否可用（平台未提供队列状态或未安装时为 None）
+    pub online: Option<bool>,
+    /// 匹配到的系统队列名
+    pub system_queue_name: Option<String>,
+}
+
+/// 按区域分组的打印机
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterCatalogGroup {
+    pub city_id: String,
+    pub city_name: String,
+    pub area_name: String,
+    /// 从顶层区域到该区域的名称路径（如 ["A栋", "3楼"]；未设置 parent 的区域只有自身）
+    pub area_path: Vec<String>,
+    /// 该区域（过滤后）的打印机总数，不受分页影响
+    pub area_total: usize,
+    pub area_installed: usize,
+    /// 本页中属于该区域的打印机
+    pub printers: Vec<PrinterCatalogItem>,
+}
+
+/// 分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterCatalogPage {
+    pub page: usize,
+    pub page_size: usize,
+    pub total_printers: usize,
+    pub total_pages: usize,
+    pub total_installed: usize,
+    pub sort: String,
+    pub groups: Vec<PrinterCatalogGroup>,
+    /// 枚举系统打印机失败时的说明（此时 installed 全部为 false）
+    pub install_state_error: Option<String>,
+}
+
+/// 查找配置打印机对应的系统队列（优先精确匹配，其次与前端一致的包含匹配）
+pub fn find_installed<'a>(printer_name: &str, installed: &'a [PrinterDetectEntry]) -> Option<&'a PrinterDetectEntry> {
+    let names = |entry: &'a PrinterDetectEntry| {
+        std::iter::once(entry.system_queue_name.as_str()).chain(entry.display_name.as_deref())
+    };
+    installed
+        .iter()
+        .find(|entry| names(entry).any(|n| n == printer_name))
+        .or_else(|| {
+            installed.iter().find(|entry| {
+                names(entry).any(|n| !n.is_empty() && (n.contains(printer_name) || printer_name.contains(n)))
+            })
+        })
+}
+
+fn queue_online(entry: &PrinterDetectEntry) -> Option<bool> {
+    match (entry.is_accepting_jobs, entry.state) {
+        (None, None) => None,
+        (accepting, state) => Some(accepting.unwrap_or(true) && state != Some(CUPS_STATE_STOPPED)),
+    }
+}
+
+/// 按查询参数生成分组分页视图
+fn build_catalog_page(
+    config: &crate::PrinterConfig,
+    installed: &[PrinterDetectEntry],
+    query: &PrinterCatalogQuery,
+) -> Result<PrinterCatalogPage, String> {
+    let sort = query.sort.as_deref().map(str::trim).filter(|s| !s.is_empty()).unwrap_or("config");
+    if !matches!(sort, "config" | "name" | "installed") {
+        return Err(format!("不支持的排序方式: {}（可选 config / name / installed）", sort));
+    }
+    let page_size = query.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let page = query.page.unwrap_or(1).max(1);
+    let area_filter = query
+        .area
+        .as_deref()
+        .map(|a| a.trim().to_lowercase())
+        .filter(|a| !a.is_empty());
+
+    // 先生成完整分组（过滤 + 排序），再按打印机分页
+    let mut groups: Vec<PrinterCatalogGroup> = Vec::new();
+    for city in &config.cities {
+        for area in &city.areas {
+            let area_path = city.area_path(&area.area_name);
+            if let Some(filter) = &area_filter {
+                // 按楼宇过滤时包含其下所有楼层
+                let matched = area_path.iter().any(|name| name.to_lowercase().contains(filter.as_str()))
+                    || city.city_name.to_lowercase().contains(filter.as_str())
+                    || city.city_id.eq_ignore_ascii_case(filter);
+                if !matched {
+                    continue;
+                }
+            }
+            let mut printers: Vec<PrinterCatalogItem> = area
+                .printers
+                .iter()
+                .map(|p| {
+                    let entry = find_installed(&p.name, installed);
+                    PrinterCatalogItem {
+                        name: p.name.clone(),
+                        path: p.path.clone(),
+                        model: p.model.clone(),
+                        driver_key: p.driver_key.clone(),
+                        installed: entry.is_some(),
+                        online: entry.and_then(queue_online),
+                        system_queue_name: entry.map(|e| e.system_queue_name.clone()),
+                    }
+                })
+                .collect();
+            match sort {
+                "name" => printers.sort_by(|a, b| a.name.cmp(&b.name)),
+                // 稳定排序：已安装优先，其余保持配置顺序
+                "installed" => printers.sort_by_key(|p| !p.installed),
+                _ => {}
+            }
+            groups.push(PrinterCatalogGroup {
+                city_id: city.city_id.clone(),
+                city_name: city.city_name.clone(),
+                area_name: area.area_name.clone(),
+                area_path,
+                area_total: printers.len(),
+                area_installed: printers.iter().filter(|p| p.installed).count(),
+                printers,
+            });
+        }
+    }
+    if sort == "name" {
+        groups.sort_by(|a, b| a.city_name.cmp(&b.city_name).then_with(|| a.area_path.cmp(&b.area_path)));
+    }
+
+    let total_printers: usize = groups.iter().map(|g| g.area_total).sum();
+    let total_installed: usize = groups.iter().map(|g| g.area_installed).sum();
+    let total_pages = total_printers.div_ceil(page_size).max(1);
+    let start = (page - 1) * page_size;
+    let end = start + page_size;
+
+    // 只保留落在本页范围内的打印机，分组信息（areaTotal 等）保持完整
+    let mut offset = 0;
+    let mut page_groups = Vec::new();
+    for mut group in groups {
+        let group_start = offset;
+        offset += group.area_total;
+        if offset <= start || group_start >= end {
+            continue;
+        }
+        let skip = start.saturating_sub(group_start);
+        let take = end.min(offset) - group_start - skip;
+        group.printers = group.printers.into_iter().skip(skip).take(take).collect();
+        page_groups.push(group);
+    }
+
+    Ok(PrinterCatalogPage {
+        page,
+        page_size,
+        total_printers,
+        total_pages,
+        total_installed,
+        sort: sort.to_string(),
+        groups: page_groups,
+        install_state_error: None,
+    })
+}
+
+/// 获取分组、排序、分页后的打印机目录（合并安装状态）
+///
+/// # 参数
+/// - `query`: 区域过滤 / 页码 / 每页数量 / 排序方式
+///
+/// # 返回
+/// - `Ok(PrinterCatalogPage)`: 本页分组数据（页码超出范围时 groups 为空）
+/// - `Err(String)`: 读取配置失败或排序方式无效
+pub async fn get_printer_catalog(query: PrinterCatalogQuery) -> Result<PrinterCatalogPage, String> {
+    tokio::task::spawn_blocking(move || {
+        let (config, _) = crate::load_local_config()?;
+        // 枚举失败不影响目录展示，只是无法标记安装状态
+        let (installed, install_state_error) = match crate::platform::list_printers() {
+            Ok(list) => (list, None),
+            Err(e) => {
+                eprintln!("[PrinterCatalog] list_printers failed, install state unavailable: {}", e);
+                (Vec::new(), Some(e))
+            }
+        };
+        let mut page = build_catalog_page(&config, &installed, &query)?;
+        page.install_state_error = install_state_error;
+        Ok(page)
+    })
+    .await
+    .map_err(|e| format!("生成打印机目录任务异常: {}", e))?
+}
@@ -0,0 +1,351 @@
+/**
+ * 整机清理服务（退役 / 交接电脑时使用）
+ * 职责：
+ * - 汇总本工具管理的打印机：安装历史（install_history.json）+ 平台标记（ePrinty tag / eprinty- 前缀）
+ * - 逐个删除队列（可选删除端口 / 驱动），生成最终报告
+ * - 按区域批量删除（楼层撤场）：只删除 configKey 属于该区域（含下级区域）的受管理队列
+ * - 不在上述来源中的打印机（用户自行添加的）一律不动
+ */
+
+use crate::platform::{DeletePrinterResult, PrinterDetectEntry};
+use serde::{Deserialize, Serialize};
+
+/// 单台打印机的清理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallEntry {
+    /// 系统队列名称
+    pub printer_name: String,
+    /// 识别来源："history" | "tag" | "history+tag"
+    pub source: String,
+    /// "removed" | "failed" | "not_installed"
+    pub status: String,
+    pub message: String,
+    pub result: Option<DeletePrinterResult>,
+}
+
+/// 整机清理报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UninstallAllReport {
+    pub success: bool,
+    pub remove_ports: bool,
+    pub remove_drivers: bool,
+    pub total: usize,
+    pub removed: usize,
+    pub failed: usize,
+    pub not_installed: usize,
+    pub entries: Vec<UninstallEntry>,
+    pub message: String,
+}
+
+/// 按区域批量删除报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AreaDeleteReport {
+    pub success: bool,
+    pub area: String,
+    pub remove_ports: bool,
+    pub remove_drivers: bool,
+    pub total: usize,
+    pub removed: usize,
+    pub failed: usize,
+    pub not_installed: usize,
+    pub entries: Vec<UninstallEntry>,
+    pub message: String,
+}
+
+/// 在已安装列表中查找历史记录对应的队列（按系统队列名或显示名匹配）
+fn find_installed<'a>(installed: &'a [PrinterDetectEntry], name: &str) -> Option<&'a PrinterDetectEntry> {
+    installed
+        .iter()
+        .find(|p| p.system_queue_name == name)
+        .or_else(|| installed.iter().find(|p| p.display_name.as_deref() == Some(name)))
+}
+
+/// 删除单个队列并生成清理结果
+fn delete_one(log_tag: &str, printer_name: String, source: String, remove_ports: bool, remove_drivers: bool) -> UninstallEntry {
+    let deleted = super::printer_locks::acquire(&[&printer_name], "delete")
+        .and_then(|_lock| crate::platform::delete_printer(&printer_name, remove_ports, remove_drivers));
+    match deleted {
+        Ok(result) if result.success => {
+            eprintln!("[{}] removed printer=\"{}\"", log_tag, printer_name);
+            UninstallEntry {
+                printer_name,
+                source,
+                status: "removed".to_string(),
+                message: result.message.clone(),
+                result: Some(result),
+            }
+        }
+        Ok(result) => {
+            eprintln!("[{}] failed printer=\"{}\" message=\"{}\"", log_tag, printer_name, result.message);
+            UninstallEntry {
+                printer_name,
+                source,
+                status: "failed".to_string(),
+                message: result.message.clone(),
+                result: Some(result),
+            }
+        }
+        Err(e) => {
+            eprintln!("[{}] failed printer=\"{}\" error=\"{}\"", log_tag, printer_name, e);
+            UninstallEntry {
+                printer_name,
+                source,
+                status: "failed".to_string(),
+                message: e,
+                result: None,
+            }
+        }
+    }
+}
+
+/// 清理已删除队列的主机记录与驱动包引用
+fn forget_removed(log_tag: &str, entries: &[UninstallEntry]) {
+    let removed_names: Vec<String> = entries
+        .iter()
+        .filter(|e| e.status == "removed")
+        .map(|e| e.printer_name.clone())
+        .collect();
+    if !removed_names.is_empty() {
+        if let Err(e) = super::host_resolution::forget_printers(&removed_names) {
+            eprintln!("[{}] 清理主机记录失败: {}", log_tag, e);
+        }
+        if let Err(e) = super::driver_cache::release_queues(&removed_names) {
+            eprintln!("[{}] 释放驱动包引用失败: {}", log_tag, e);
+        }
+    }
+}
+
+/// 统计 (removed, failed, not_installed)
+fn count_statuses(entries: &[UninstallEntry]) -> (usize, usize, usize) {
+    let count = |status: &str| entries.iter().filter(|e| e.status == status).count();
+    (count("removed"), count("failed"), count("not_installed"))
+}
+
+/// 卸载本工具管理的全部打印机（阻塞调用）
+fn uninstall_all_blocking(remove_ports: bool, remove_drivers: bool) -> Result<UninstallAllReport, String> {
+    eprintln!(
+        "[UninstallAll] START remove_ports={} remove_drivers={}",
+        remove_ports, remove_drivers
+    );
+
+    let installed = crate::platform::list_printers()?;
+    let history_names = super::install_history::installed_printer_names().unwrap_or_else(|e| {
+        eprintln!("[UninstallAll] 读取安装历史失败，仅按平台标记识别: {}", e);
+        Vec::new()
+    });
+    let tagged_names = crate::platform::list_managed_printer_names()?;
+
+    // 合并候选：(系统队列名, 来源)，未安装的历史记录保留原名称用于报告
+    let mut candidates: Vec<(String, String, bool)> = Vec::new();
+    for name in &history_names {
+        let (queue_name, present) = match find_installed(&installed, name) {
+            Some(entry) => (entry.system_queue_name.clone(), true),
+            None => (name.clone(), false),
+        };
+        if !candidates.iter().any(|(n, _, _)| n == &queue_name) {
+            candidates.push((queue_name, "history".to_string(), present));
+        }
+    }
+    for name in &tagged_names {
+        match candidates.iter_mut().find(|(n, _, _)| n == name) {
+            Some(candidate) => candidate.1 = "history+tag".to_string(),
+            None => candidates.push((name.clone(), "tag".to_string(), true)),
+        }
+    }
+
+    eprintln!(
+        "[UninstallAll] candidates={} (history={}, tagged={}, installed_total={})",
+        candidates.len(),
+        history_names.len(),
+        tagged_names.len(),
+        installed.len()
+    );
+
+    let mut entries = Vec::with_capacity(candidates.len());
+    for (printer_name, source, present) in candidates {
+        if !present {
+            entries.push(UninstallEntry {
+                printer_name,
+                source,
+                status: "not_installed".to_string(),
+                message: "该打印机已不在系统中，跳过".to_string(),
+                result: None,
+            });
+            continue;
+        }
+
+        entries.push(delete_one("UninstallAll", printer_name, source, remove_ports, remove_drivers));
+    }
+    forget_removed("UninstallAll", &entries);
+
+    let (removed, failed, not_installed) = count_statuses(&entries);
+    let message = if entries.is_empty() {
+        "未发现由本工具安装的打印机".to_string()
+    } else {
+        format!(
+            "清理完成：已删除 {} 台，失败 {} 台，已不存在 {} 台（未受管理的打印机未做改动）",
+            removed, failed, not_installed
+        )
+    };
+
+    eprintln!("[UninstallAll] DONE removed={} failed={} not_installed={}", removed, failed, not_installed);
+
+    Ok(UninstallAllReport {
+        success: failed == 0,
+        remove_ports,
+        remove_drivers,
+        total: entries.len(),
+        removed,
+        failed,
+        not_installed,
+        entries,
+        message,
+    })
+}
+
+/// 卸载本工具管理的全部打印机
+///
+/// # 参数
+/// - `remove_ports`: 是否同时删除端口
+/// - `remove_drivers`: 是否同时删除驱动（仅 Windows 生效，驱动仍被其他队列使用时由平台层跳过）
+pub async fn uninstall_all_managed_printers(remove_ports: bool, remove_drivers: bool) -> Result<UninstallAllReport, String> {
+    tokio::task::spawn_blocking(move || uninstall_all_blocking(remove_ports, remove_drivers))
+        .await
+        .map_err(|e| format!("整机清理任务异常: {}", e))?
+}
+
+/// 配置中属于该区域（含以该区域为上级的下级区域）的打印机名称
+///
+/// # 返回
+/// - `Ok(Vec<String>)`: 打印机名称（区域存在但没有打印机时为空）
+/// - `Err(String)`: 配置中没有该区域
+fn area_printer_names(config: &crate::PrinterConfig, area: &str) -> Result<Vec<String>, String> {
+    let mut found = false;
+    let mut names = Vec::new();
+    for city in &config.cities {
+        for candidate in &city.areas {
+            if !city.area_path(&candidate.area_name).iter().any(|a| a == area) {
+                continue;
+            }
+            found = true;
+            names.extend(candidate.printers.iter().map(|p| p.name.clone()));
+        }
+    }
+    if !found {
+        return Err(format!("配置中不存在区域 \"{}\"", area));
+    }
+    Ok(names)
+}
+
+/// 按区域批量删除打印机（阻塞调用）
+fn delete_printers_by_area_blocking(area: &str, remove_ports: bool, remove_drivers: bool) -> Result<AreaDeleteReport, String> {
+    eprintln!(
+        "[DeleteByArea] START area=\"{}\" remove_ports={} remove_drivers={}",
+        area, remove_ports, remove_drivers
+    );
+
+    let (config, _) = crate::load_local_config()?;
+    let printer_names = area_printer_names(&config, area)?;
+    let installed = super::printer_service::list_managed_printers_blocking()?;
+
+    let mut entries = Vec::new();
+    for name in &printer_names {
+        let queues: Vec<String> = installed
+            .iter()
+            .filter(|e| e.managed && e.config_key.as_deref() == Some(name.as_str()))
+            .map(|e| e.system_queue_name.clone())
+            .collect();
+        if queues.is_empty() {
+            entries.push(UninstallEntry {
+                printer_name: name.clone(),
+                source: "config".to_string(),
+                status: "not_installed".to_string(),
+                message: "该打印机未安装在本机，跳过".to_string(),
+                result: None,
+            });
+            continue;
+        }
+        for queue in queues {
+            entries.push(delete_one("DeleteByArea", queue, "config".to_string(), remove_ports, remove_drivers));
+        }
+    }
+    forget_removed("DeleteByArea", &entries);
+
+    let (removed, failed, not_installed) = count_statuses(&entries);
+    let message = if printer_names.is_empty() {
+        format!("区域 {} 下没有配置打印机", area)
+    } else {
+        format!(
+            "区域 {} 清理完成：已删除 {} 台，失败 {} 台，未安装 {} 台",
+            area, removed, failed, not_installed
+        )
+    };
+
+    eprintln!(
+        "[DeleteByArea] DONE area=\"{}\" removed={} failed={} not_installed={}",
+        area, removed, failed, not_installed
+    );
+
+    Ok(AreaDeleteReport {
+        success: failed == 0,
+        area: area.to_string(),
+        remove_ports,
+        remove_drivers,
+        total: entries.len(),
+        removed,
+        failed,
+        not_installed,
+        entries,
+        message,
+    })
+}
+
+/// 删除配置中某个区域（含下级区域）的全部已安装打印机
+///
+/// 只处理本工具管理且 configKey 属于该区域的队列；每个删除结果同时写入审计日志
+///
+/// # 参数
+/// - `area`: 区域名称（楼宇名称会同时覆盖其下的楼层）
+/// - `remove_ports`: 是否同时删除端口
+/// - `remove_drivers`: 是否同时删除驱动（仅 Windows 生效，驱动仍被其他队列使用时由平台层跳过）
+pub async fn delete_printers_by_area(area: &str, remove_ports: bool, remove_drivers: bool) -> Result<AreaDeleteReport, String> {
+    let area_name = area.trim().to_string();
+    if area_name.is_empty() {
+        return Err("区域名称不能为空".to_string());
+    }
+    let report = tokio::task::spawn_blocking(move || delete_printers_by_area_blocking(&area_name, remove_ports, remove_drivers))
+        .await
+        .map_err(|e| format!("按区域删除任务异常: {}", e))??;
+    for result in report.entries.iter().filter_map(|e| e.result.as_ref()) {
+        super::audit_log::record_delete(result).await;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn area_includes_child_areas() {
+        let config: crate::PrinterConfig = serde_json::from_value(serde_json::json!({
+            "cities": [{
+                "cityId": "sh",
+                "cityName": "上海",
+                "areas": [
+                    { "areaName": "A栋", "printers": [{ "name": "大厅", "path": "\\\\10.0.0.1" }] },
+                    { "areaName": "3F", "parent": "A栋", "printers": [{ "name": "前台", "path": "\\\\10.0.0.2" }] },
+                    { "areaName": "B栋", "printers": [{ "name": "财务", "path": "\\\\10.0.0.3" }] }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(area_printer_names(&config, "A栋").unwrap(), vec!["大厅", "前台"]);
+        assert_eq!(area_printer_names(&config, "3F").unwrap(), vec!["前台"]);
+        assert!(area_printer_names(&config, "C栋").is_err());
+    }
+}
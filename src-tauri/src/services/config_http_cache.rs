@@ -0,0 +1,165 @@
+/**
+ * printer_config.json HTTP 条件请求缓存
+ * 职责：
+ * - 记录写入本地的远程配置对应的 ETag / Last-Modified（以及写入时本地文件的大小与修改时间）
+ * - 拉取远程配置时附带 If-None-Match / If-Modified-Since，304 视为"无更新"，直接复用本地配置
+ * - 本地配置被其他途径改写（seed 复制、手动编辑）后验证器自动失效，回退为完整下载
+ * - 服务器不返回验证器时不做任何缓存，行为与之前一致
+ * - 使用 extends 的配置不做缓存（overlay 返回 304 时无法得知基础配置是否变化）
+ */
+
+use super::meta_store::MetaStore;
+use crate::PrinterConfig;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+/// 条件请求缓存存储（schemaVersion=1）
+static CONFIG_HTTP_CACHE: MetaStore<ConfigHttpCache> = MetaStore::new("config_http_cache", 1);
+
+/// 最近一次远程响应的验证器（尚未写入本地配置时保存在内存中）
+static PENDING: Mutex<Option<PendingValidators>> = Mutex::new(None);
+
+/// 已写入本地的远程配置对应的验证器
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigHttpCache {
+    pub url: String,
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub config_path: Option<String>,
+    pub config_version: Option<String>,
+    /// 写入时的本地文件大小与修改时间（用于判断本地配置是否被其他途径改写）
+    pub config_size: u64,
+    pub config_modified_ms: u64,
+}
+
+#[derive(Debug, Clone)]
+struct PendingValidators {
+    url: String,
+    config_version: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+}
+
+/// 可用于条件请求的验证器
+#[derive(Debug, Clone)]
+pub struct ConditionalRequest {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+    pub config_path: PathBuf,
+}
+
+/// 读取文件大小与修改时间（毫秒）
+fn file_stamp(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified_ms = metadata
+        .modified()
+        .ok()?
+        .duration_since(std::time::UNIX_EPOCH)
+        .ok()?
+        .as_millis() as u64;
+    Some((metadata.len(), modified_ms))
+}
+
+/// 获取条件请求验证器
+///
+/// # 参数
+/// - `url`: 远程配置地址
+///
+/// # 返回
+/// - `Some(ConditionalRequest)`: 本地配置仍是上次下载写入的版本，可发送条件请求
+/// - `None`: 无记录、地址变化或本地配置已被改写（应完整下载）
+pub fn conditional_request(url: &str) -> Option<ConditionalRequest> {
+    let cache = match CONFIG_HTTP_CACHE.load() {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("[ConfigHttpCache] 读取缓存记录失败，完整下载: {}", e);
+            return None;
+        }
+    };
+    if cache.url != url || (cache.etag.is_none() && cache.last_modified.is_none()) {
+        return None;
+    }
+
+    let config_path = PathBuf::from(cache.config_path.as_ref()?);
+    if file_stamp(&config_path) != Some((cache.config_size, cache.config_modified_ms)) {
+        eprintln!(
+            "[ConfigHttpCache] 本地配置已变化，验证器失效 path={}",
+            config_path.display()
+        );
+        return None;
+    }
+
+    Some(ConditionalRequest {
+        etag: cache.etag,
+        last_modified: cache.last_modified,
+        config_path,
+    })
+}
+
+/// 记录远程响应的验证器（配置写入本地时由 commit 持久化）
+///
+/// # 参数
+/// - `url`: 远程配置地址
+/// - `config_version`: 响应对应的配置版本（commit 时用于确认写入的是同一份配置）
+/// - `etag` / `last_modified`: 响应头中的验证器
+pub fn remember_response(
+    url: &str,
+    config_version: Option<String>,
+    etag: Option<String>,
+    last_modified: Option<String>,
+) {
+    let pending = if etag.is_none() && last_modified.is_none() {
+        None
+    } else {
+        Some(PendingValidators {
+            url: url.to_string(),
+            config_version,
+            etag,
+            last_modified,
+        })
+    };
+    *PENDING.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = pending;
+}
+
+/// 本地配置写入成功后调用：持久化对应的验证器
+///
+/// 写入的配置与最近一次远程响应版本不一致时清除记录，避免用过期验证器得到错误的 304
+///
+/// # 参数
+/// - `config`: 已写入的配置
+/// - `config_path`: 本地配置文件路径
+pub fn commit(config: &PrinterConfig, config_path: &Path) {
+    let pending = PENDING
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+        .clone()
+        .filter(|p| p.config_version == config.version && config.extends.is_none());
+
+    let result = CONFIG_HTTP_CACHE.update(|cache| match (pending, file_stamp(config_path)) {
+        (Some(pending), Some((size, modified_ms))) => {
+            *cache = ConfigHttpCache {
+                url: pending.url,
+                etag: pending.etag,
+                last_modified: pending.last_modified,
+                config_path: Some(config_path.display().to_string()),
+                config_version: pending.config_version,
+                config_size: size,
+                config_modified_ms: modified_ms,
+            };
+        }
+        _ => *cache = ConfigHttpCache::default(),
+    });
+
+    if let Err(e) = result {
+        eprintln!("[ConfigHttpCache] 保存缓存记录失败（下次启动将完整下载）: {}", e);
+    }
+}
+
+/// 304 时读取本地配置作为远程结果
+pub fn load_cached_config(request: &ConditionalRequest) -> Result<PrinterConfig, String> {
+    let content = std::fs::read_to_string(&request.config_path)
+        .map_err(|e| format!("读取本地缓存配置失败 {}: {}", request.config_path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析本地缓存配置失败: {}", e))
+}
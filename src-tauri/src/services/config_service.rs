@@ -5,15 +5,20 @@
  * - refresh_remote_config
  * - load_config
  * - confirm_update_config
+ * - resolve_extends / get_effective_config（基础配置 + 站点 overlay 继承）
  */
 
 use crate::*;
+use std::collections::HashMap;
 use std::fs;
 
+/// overlay 合并基础配置时的请求超时
+const BASE_CONFIG_TIMEOUT_SECS: u64 = 5;
+
 pub fn get_cached_config(app: &tauri::AppHandle) -> Result<CachedConfigResult, String> {
     eprintln!("[CACHE_LOADED] 开始读取缓存配置");
 
-    let config_path = get_config_path(&app)?;
+    let config_path = get_config_path(app)?;
 
     // 步骤 1: 如果本地配置存在，直接读取并返回
     if config_path.exists() {
@@ -50,7 +55,7 @@ pub fn get_cached_config(app: &tauri::AppHandle) -> Result<CachedConfigResult, S
 
     // 步骤 2: 本地不存在，尝试从 seed 复制
     eprintln!("[CACHE_LOADED] 本地配置不存在，尝试从 seed 复制");
-    match seed_config_if_needed(&app) {
+    match seed_config_if_needed(app) {
         Ok(_) => {
             let content = fs::read_to_string(&config_path)
                 .map_err(|e| format!("读取 seed 复制的配置文件失败: {}", e))?;
@@ -95,23 +100,25 @@ pub fn get_cached_config(app: &tauri::AppHandle) -> Result<CachedConfigResult, S
 
     let remote_config = rt.block_on(async {
         tokio::time::timeout(
-            std::time::Duration::from_secs(10),
+            super::config_sources::overall_timeout(std::time::Duration::from_secs(10)),
             load_remote_config(),
         )
         .await
     });
 
     match remote_config {
-        Ok(Ok(config)) => {
+        Ok(Ok(super::config_sources::RemoteConfig { config, source })) => {
             save_config_to_local(&config, &config_path)
                 .map_err(|e| format!("保存远程配置到本地失败: {}", e))?;
+            super::config_sources::remember_saved_origin(Some(&source.url));
 
             let config_version = config.version.clone();
 
             eprintln!(
-                "[CONFIG_LOADED] source=remote_bootstrap path={} version={:?}",
+                "[CONFIG_LOADED] source=remote_bootstrap path={} version={:?} url=\"{}\"",
                 config_path.display(),
-                config_version
+                config_version,
+                source.url
             );
 
             Ok(CachedConfigResult {
@@ -134,7 +141,7 @@ pub fn get_cached_config(app: &tauri::AppHandle) -> Result<CachedConfigResult, S
 pub async fn refresh_remote_config(app: &tauri::AppHandle) -> Result<RefreshConfigResult, String> {
     eprintln!("[REMOTE_REFRESH_START] 开始刷新远程配置");
 
-    let config_path = get_config_path(&app)?;
+    let config_path = get_config_path(app)?;
 
     let local_version = if config_path.exists() {
         match fs::read_to_string(&config_path) {
@@ -152,13 +159,13 @@ pub async fn refresh_remote_config(app: &tauri::AppHandle) -> Result<RefreshConf
     };
 
     let remote_result = tokio::time::timeout(
-        std::time::Duration::from_millis(3000),
+        super::config_sources::overall_timeout(std::time::Duration::from_millis(3000)),
         load_remote_config(),
     )
     .await;
 
     match remote_result {
-        Ok(Ok(remote_config)) => {
+        Ok(Ok(super::config_sources::RemoteConfig { config: remote_config, source })) => {
             let remote_version = remote_config.version.clone();
 
             let should_update = match (&local_version, &remote_version) {
@@ -183,10 +190,12 @@ pub async fn refresh_remote_config(app: &tauri::AppHandle) -> Result<RefreshConf
 
                 match save_config_to_local(&remote_config, &config_path) {
                     Ok(_) => {
+                        super::config_sources::remember_saved_origin(Some(&source.url));
                         eprintln!(
-                            "[REMOTE_REFRESH_OK] 远程配置已更新到本地 version={:?} path={}",
+                            "[REMOTE_REFRESH_OK] 远程配置已更新到本地 version={:?} path={} url=\"{}\"",
                             remote_version,
-                            config_path.display()
+                            config_path.display(),
+                            source.url
                         );
 
                         let payload = serde_json::json!({
@@ -266,3 +275,381 @@ pub async fn refresh_remote_config(app: &tauri::AppHandle) -> Result<RefreshConf
         }
     }
 }
+
+/// 配置条目来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigProvenanceEntry {
+    /// "driver" | "city" | "area" | "printer"
+    pub kind: String,
+    /// 条目路径：driverKey / cityId / cityId/areaName / cityId/areaName/printerName
+    pub key: String,
+    /// "base"（来自基础配置）| "overlay"（overlay 新增）| "overlay_override"（overlay 覆盖了基础配置）
+    pub source: String,
+}
+
+/// 生效配置及来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveConfigResult {
+    pub config: PrinterConfig,
+    pub extends: Option<String>,
+    pub base_version: Option<String>,
+    pub provenance: Vec<ConfigProvenanceEntry>,
+    /// 拉取基础配置失败时的说明（此时 provenance 为空）
+    pub base_error: Option<String>,
+}
+
+fn provenance(kind: &str, key: String, source: &str) -> ConfigProvenanceEntry {
+    ConfigProvenanceEntry {
+        kind: kind.to_string(),
+        key,
+        source: source.to_string(),
+    }
+}
+
+/// 比较两个条目序列化后是否一致（用于判断 overlay 是否真正改写了基础条目）
+fn same_entry<T: Serialize>(a: &T, b: &T) -> bool {
+    match (serde_json::to_value(a), serde_json::to_value(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+fn override_source<T: Serialize>(base: &T, overlay: &T) -> &'static str {
+    if same_entry(base, overlay) { "base" } else { "overlay_override" }
+}
+
+/// 合并基础配置与 overlay
+///
+/// 规则：
+/// - driverCatalog：按 driverKey 合并，同名条目以 overlay 为准
+/// - cities：按 cityId 匹配；overlay 新城市追加到末尾
+/// - areas：同城市内按 areaName 匹配，区域默认安装选项逐字段以 overlay 为准；overlay 设置 parent 时改挂到新的上级区域
+///   （未设置时保留基础配置的层级）；overlay 新区域追加到末尾
+/// - printers：同区域内按 name 覆盖，overlay 新打印机追加到末尾
+/// - fleetReporting / crashReporting：overlay 设置时以 overlay 为准
+/// - version：按 compare_versions 取两者中较新者（任一方升版都会触发客户端更新）
+///
+/// # 返回
+/// 合并后的配置与每个条目的来源
+pub fn merge_config_overlay(base: PrinterConfig, overlay: PrinterConfig) -> (PrinterConfig, Vec<ConfigProvenanceEntry>) {
+    let mut entries = Vec::new();
+
+    let mut catalog = base.driver_catalog.clone().unwrap_or_default();
+    let mut driver_sources: HashMap<String, &str> = catalog.keys().map(|k| (k.clone(), "base")).collect();
+    for (key, entry) in overlay.driver_catalog.clone().unwrap_or_default() {
+        let source = match catalog.get(&key) {
+            Some(existing) => override_source(existing, &entry),
+            None => "overlay",
+        };
+        driver_sources.insert(key.clone(), source);
+        catalog.insert(key, entry);
+    }
+    let mut driver_keys: Vec<&String> = driver_sources.keys().collect();
+    driver_keys.sort();
+    for key in driver_keys {
+        entries.push(provenance("driver", key.clone(), driver_sources[key]));
+    }
+
+    let mut cities = base.cities.clone();
+    let mut city_sources: Vec<&str> = vec!["base"; cities.len()];
+    for overlay_city in overlay.cities {
+        let city_index = match cities.iter().position(|c| c.city_id == overlay_city.city_id) {
+            Some(index) => {
+                if !same_entry(&cities[index].city_name, &overlay_city.city_name) {
+                    cities[index].city_name = overlay_city.city_name.clone();
+                    city_sources[index] = "overlay_override";
+                }
+                index
+            }
+            None => {
+                cities.push(City {
+                    city_id: overlay_city.city_id.clone(),
+                    city_name: overlay_city.city_name.clone(),
+                    areas: Vec::new(),
+                });
+                city_sources.push("overlay");
+                cities.len() - 1
+            }
+        };
+
+        let city = &mut cities[city_index];
+        for overlay_area in overlay_city.areas {
+            let area_key = format!("{}/{}", city.city_id, overlay_area.area_name);
+            let area = match city.areas.iter_mut().find(|a| a.area_name == overlay_area.area_name) {
+                Some(area) => area,
+                None => {
+                    entries.push(provenance("area", area_key.clone(), "overlay"));
+                    for printer in &overlay_area.printers {
+                        entries.push(provenance("printer", format!("{}/{}", area_key, printer.name), "overlay"));
+                    }
+                    city.areas.push(overlay_area);
+                    continue;
+                }
+            };
+
+            let merged_defaults = overlay_area.defaults.or(&area.defaults);
+            let merged_parent = overlay_area.parent.or_else(|| area.parent.clone());
+            let source = if merged_parent != area.parent {
+                "overlay_override"
+            } else {
+                override_source(&area.defaults, &merged_defaults)
+            };
+            entries.push(provenance("area", area_key.clone(), source));
+            area.defaults = merged_defaults;
+            area.parent = merged_parent;
+
+            for printer in overlay_area.printers {
+                let printer_key = format!("{}/{}", area_key, printer.name);
+                match area.printers.iter_mut().find(|p| p.name == printer.name) {
+                    Some(existing) => {
+                        entries.push(provenance("printer", printer_key, override_source(&*existing, &printer)));
+                        *existing = printer;
+                    }
+                    None => {
+                        entries.push(provenance("printer", printer_key, "overlay"));
+                        area.printers.push(printer);
+                    }
+                }
+            }
+        }
+    }
+
+    // 基础配置中未被 overlay 提及的区域 / 打印机
+    for city in &base.cities {
+        for area in &city.areas {
+            let area_key = format!("{}/{}", city.city_id, area.area_name);
+            if !entries.iter().any(|e| e.kind == "area" && e.key == area_key) {
+                entries.push(provenance("area", area_key.clone(), "base"));
+            }
+            for printer in &area.printers {
+                let printer_key = format!("{}/{}", area_key, printer.name);
+                if !entries.iter().any(|e| e.kind == "printer" && e.key == printer_key) {
+                    entries.push(provenance("printer", printer_key, "base"));
+                }
+            }
+        }
+    }
+    for (city, source) in cities.iter().zip(city_sources) {
+        entries.push(provenance("city", city.city_id.clone(), source));
+    }
+
+    let version = match (base.version, overlay.version) {
+        (Some(b), Some(o)) => Some(if crate::compare_versions(&o, &b) { b } else { o }),
+        (b, o) => o.or(b),
+    };
+
+    let merged = PrinterConfig {
+        version,
+        extends: overlay.extends,
+        driver_catalog: Some(catalog),
+        cities,
+        fleet_reporting: overlay.fleet_reporting.or(base.fleet_reporting),
+        download_policy: overlay.download_policy.or(base.download_policy),
+        rollout_groups: if overlay.rollout_groups.is_empty() { base.rollout_groups } else { overlay.rollout_groups },
+        crash_reporting: overlay.crash_reporting.or(base.crash_reporting),
+        event_log: overlay.event_log.or(base.event_log),
+        printer_watch: overlay.printer_watch.or(base.printer_watch),
+        name_template: overlay.name_template.or(base.name_template),
+        update_policy: overlay.update_policy.or(base.update_policy),
+        delegated_install: overlay.delegated_install.or(base.delegated_install),
+        install_feedback: overlay.install_feedback.or(base.install_feedback),
+        kiosk: overlay.kiosk.or(base.kiosk),
+    };
+    (merged, entries)
+}
+
+/// 解析 extends 地址（相对地址基于 overlay 自身地址）
+fn resolve_extends_url(extends: &str, overlay_url: &str) -> Result<String, String> {
+    let resolved = url::Url::parse(extends)
+        .or_else(|_| url::Url::parse(overlay_url).and_then(|base| base.join(extends)))
+        .map_err(|e| format!("extends 地址无效 '{}': {}", extends, e))?;
+    if resolved.scheme() != "http" && resolved.scheme() != "https" {
+        return Err(format!("extends 仅支持 http/https 地址: {}", resolved));
+    }
+    Ok(resolved.to_string())
+}
+
+/// 拉取基础配置
+async fn fetch_base_config(url: &str) -> Result<PrinterConfig, String> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(BASE_CONFIG_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("拉取基础配置失败 {}: {}", url, e))?;
+    if !response.status().is_success() {
+        return Err(format!("拉取基础配置失败 {}: 服务器返回 {}", url, response.status()));
+    }
+    let base: PrinterConfig = response
+        .json()
+        .await
+        .map_err(|e| format!("解析基础配置失败 {}: {}", url, e))?;
+    if base.extends.is_some() {
+        return Err(format!("基础配置 {} 不能再使用 extends（仅支持一级继承）", url));
+    }
+    Ok(base)
+}
+
+/// overlay 配置声明了 extends 时拉取基础配置并合并；未声明时原样返回
+///
+/// # 参数
+/// - `overlay`: 远程拉取到的配置
+/// - `overlay_url`: overlay 的地址（用于解析相对 extends）
+pub async fn resolve_extends(overlay: PrinterConfig, overlay_url: &str) -> Result<PrinterConfig, String> {
+    let extends = match overlay.extends.as_deref().map(str::trim).filter(|e| !e.is_empty()) {
+        Some(e) => e.to_string(),
+        None => return Ok(overlay),
+    };
+    let base_url = resolve_extends_url(&extends, overlay_url)?;
+    let base = fetch_base_config(&base_url).await?;
+    let (merged, entries) = merge_config_overlay(base, overlay);
+    eprintln!(
+        "[CONFIG_EXTENDS] merged base={} version={:?} entries={} overridden={} added={}",
+        base_url,
+        merged.version,
+        entries.len(),
+        entries.iter().filter(|e| e.source == "overlay_override").count(),
+        entries.iter().filter(|e| e.source == "overlay").count()
+    );
+    Ok(merged)
+}
+
+/// 获取生效配置及每个条目的来源
+///
+/// 本地保存的是合并后的配置；这里重新拉取基础配置与之比对，得出每个条目来自基础配置还是 overlay
+pub async fn get_effective_config() -> Result<EffectiveConfigResult, String> {
+    let (config, _) = tokio::task::spawn_blocking(load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))??;
+
+    let extends = match config.extends.clone().filter(|e| !e.trim().is_empty()) {
+        Some(e) => e,
+        None => {
+            return Ok(EffectiveConfigResult {
+                config,
+                extends: None,
+                base_version: None,
+                provenance: Vec::new(),
+                base_error: None,
+            });
+        }
+    };
+
+    // 相对 extends 以本地配置实际的来源为基准（备用源 / 版本地址提供的配置不按首选来源解析）
+    let overlay_url =
+        super::config_sources::saved_origin().unwrap_or_else(super::user_settings::config_url);
+    let base = match resolve_extends_url(&extends, &overlay_url) {
+        Ok(url) => fetch_base_config(&url).await,
+        Err(e) => Err(e),
+    };
+    match base {
+        Ok(base) => {
+            let base_version = base.version.clone();
+            let (_, provenance) = merge_config_overlay(base, config.clone());
+            Ok(EffectiveConfigResult {
+                config,
+                extends: Some(extends),
+                base_version,
+                provenance,
+                base_error: None,
+            })
+        }
+        Err(e) => Ok(EffectiveConfigResult {
+            config,
+            extends: Some(extends),
+            base_version: None,
+            provenance: Vec::new(),
+            base_error: Some(e),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> PrinterConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn overlay_overrides_printers_by_name_and_appends_areas() {
+        let base = parse(serde_json::json!({
+            "version": "2.0.10",
+            "driverCatalog": { "HP": { "installMode": "package" } },
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [{
+                    "areaName": "A座",
+                    "printers": [
+                        { "name": "前台", "path": "\\\\10.0.0.1", "driverKey": "HP" },
+                        { "name": "财务", "path": "\\\\10.0.0.2", "driverKey": "HP" }
+                    ]
+                }]
+            }]
+        }));
+        let overlay = parse(serde_json::json!({
+            "version": "2.0.9",
+            "extends": "base.json",
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [
+                    { "areaName": "A座", "printers": [{ "name": "前台", "path": "\\\\10.0.9.9", "driverKey": "HP" }] },
+                    { "areaName": "B座", "printers": [{ "name": "前台", "path": "\\\\10.0.1.1", "driverKey": "HP" }] }
+                ]
+            }]
+        }));
+
+        let (merged, provenance) = merge_config_overlay(base, overlay);
+        assert_eq!(merged.version.as_deref(), Some("2.0.10"));
+        assert_eq!(merged.extends.as_deref(), Some("base.json"));
+        let areas = &merged.cities[0].areas;
+        assert_eq!(areas.len(), 2);
+        assert_eq!(areas[0].printers[0].path, "\\\\10.0.9.9");
+        assert_eq!(areas[0].printers[1].name, "财务");
+
+        let source_of = |key: &str| provenance.iter().find(|e| e.key == key).map(|e| e.source.as_str());
+        assert_eq!(source_of("bj/A座/前台"), Some("overlay_override"));
+        assert_eq!(source_of("bj/A座/财务"), Some("base"));
+        assert_eq!(source_of("bj/B座"), Some("overlay"));
+        assert_eq!(source_of("HP"), Some("base"));
+    }
+
+    #[test]
+    fn overlay_can_regroup_flat_areas_under_a_building() {
+        let base = parse(serde_json::json!({
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [
+                    { "areaName": "3楼", "printers": [{ "name": "前台", "path": "\\\\10.0.0.1" }] },
+                    { "areaName": "4楼", "printers": [] }
+                ]
+            }]
+        }));
+        let overlay = parse(serde_json::json!({
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [
+                    { "areaName": "A栋", "printers": [] },
+                    { "areaName": "3楼", "parent": "A栋", "printers": [] }
+                ]
+            }]
+        }));
+
+        let (merged, provenance) = merge_config_overlay(base, overlay);
+        let city = &merged.cities[0];
+        assert_eq!(city.area_path("3楼"), vec!["A栋", "3楼"]);
+        assert_eq!(city.area_path("4楼"), vec!["4楼"]);
+        assert_eq!(city.areas[0].printers.len(), 1);
+        assert!(city.area_hierarchy_problems().is_empty());
+
+        let source_of = |key: &str| provenance.iter().find(|e| e.key == key).map(|e| e.source.as_str());
+        assert_eq!(source_of("bj/3楼"), Some("overlay_override"));
+        assert_eq!(source_of("bj/A栋"), Some("overlay"));
+    }
+}
@@ -0,0 +1,203 @@
+/**
+ * 远程配置源故障转移
+ * 职责：
+ * - 远程打印机配置可配置多个来源（主 CDN、备用源站、内网镜像……），按用户设置 configSources 的顺序依次尝试，
+ *   每个来源单独计时（timeoutSecs，默认 5 秒），前一个失败或超时后才尝试下一个
+ * - 未配置 configSources 时只有一个来源：configUrl 覆盖地址或内置地址（与原行为一致）
+ * - 有多个来源时调用方的整体超时为各来源超时之和，每个来源都能用满自己的超时
+ * - 返回实际提供配置的来源及之前失败的来源，随 LoadConfigResult.remote_source 返回前端；
+ *   提供配置的来源地址记在进程内，作为 extends 相对地址与版本索引的基准（user_settings::config_url）
+ * - 写入本地的配置来自哪个地址另存于 meta/config_origin.json，重启或故障转移后查看生效配置时
+ *   extends 仍按该地址解析（get_effective_config）
+ */
+
+use super::meta_store::MetaStore;
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 单个来源的默认超时
+pub const DEFAULT_SOURCE_TIMEOUT_SECS: u64 = 5;
+
+/// 最近一次提供配置的来源地址
+static SERVED_URL: Mutex<Option<String>> = Mutex::new(None);
+
+/// 本地配置的来源地址存储（schemaVersion=1）
+static SAVED_ORIGIN: MetaStore<SavedConfigOrigin> = MetaStore::new("config_origin", 1);
+
+/// 写入本地的配置来自哪个地址
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedConfigOrigin {
+    /// 提供配置的来源地址或版本地址（None：来源未知，如从归档导入）
+    pub url: Option<String>,
+}
+
+/// 配置源
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSource {
+    pub url: String,
+    /// 本来源的请求超时（秒，None = DEFAULT_SOURCE_TIMEOUT_SECS）
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+}
+
+impl ConfigSource {
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs.unwrap_or(DEFAULT_SOURCE_TIMEOUT_SECS))
+    }
+}
+
+/// 尝试失败的来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceFailure {
+    pub url: String,
+    pub error: String,
+}
+
+/// 实际提供配置的来源
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigSourceReport {
+    pub url: String,
+    /// 在来源列表中的序号（0 = 首选来源）
+    pub index: usize,
+    /// 之前尝试失败的来源（按尝试顺序）
+    pub failed: Vec<SourceFailure>,
+}
+
+/// 远程配置及其来源
+#[derive(Debug, Clone)]
+pub struct RemoteConfig {
+    pub config: crate::PrinterConfig,
+    pub source: ConfigSourceReport,
+}
+
+/// 整体超时：只有一个来源时沿用调用方的预算，有多个来源时为各来源超时之和
+fn overall_deadline(sources: &[ConfigSource], primary_budget: Duration) -> Duration {
+    if sources.len() <= 1 {
+        return primary_budget;
+    }
+    sources.iter().map(ConfigSource::timeout).sum()
+}
+
+/// 调用方的整体超时预算（见 overall_deadline）
+///
+/// # 参数
+/// - `primary_budget`: 只有一个来源时调用方使用的超时
+pub fn overall_timeout(primary_budget: Duration) -> Duration {
+    overall_deadline(&super::user_settings::config_sources(), primary_budget)
+}
+
+/// 最近一次提供配置的来源地址（本进程还没有成功拉取过时为 None）
+pub fn served_url() -> Option<String> {
+    SERVED_URL.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// 记录写入本地的配置来自哪个地址（写入失败只记录日志）
+///
+/// # 参数
+/// - `url`: 提供配置的地址；None 表示来源未知
+pub fn remember_saved_origin(url: Option<&str>) {
+    if let Err(e) = SAVED_ORIGIN.update(|origin| origin.url = url.map(str::to_string)) {
+        eprintln!("[ConfigSources] 记录本地配置来源失败: {}", e);
+    }
+}
+
+/// 本地配置的来源地址（未记录或读取失败时为 None）
+pub fn saved_origin() -> Option<String> {
+    SAVED_ORIGIN.load().ok().and_then(|origin| origin.url)
+}
+
+/// 按顺序尝试各来源，返回第一个成功的结果
+///
+/// # 参数
+/// - `sources`: 来源列表（按优先级排列）
+/// - `fetch`: 从单个来源获取配置
+///
+/// # 返回
+/// - `Ok((T, ConfigSourceReport))`: 成功的结果与来源
+/// - `Err(String)`: 全部来源失败（只有一个来源时原样返回它的错误）
+pub async fn first_available<T, F, Fut>(sources: &[ConfigSource], mut fetch: F) -> Result<(T, ConfigSourceReport), String>
+where
+    F: FnMut(ConfigSource) -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    let mut failed: Vec<SourceFailure> = Vec::new();
+    for (index, source) in sources.iter().enumerate() {
+        let timeout = source.timeout();
+        let result = match tokio::time::timeout(timeout, fetch(source.clone())).await {
+            Ok(result) => result,
+            Err(_) => Err(format!("请求超时（{} 秒）", timeout.as_secs())),
+        };
+        match result {
+            Ok(value) => {
+                if index > 0 {
+                    eprintln!("[ConfigSources] served by fallback index={} url=\"{}\" failed={}", index, source.url, failed.len());
+                }
+                *SERVED_URL.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(source.url.clone());
+                return Ok((value, ConfigSourceReport { url: source.url.clone(), index, failed }));
+            }
+            Err(error) => {
+                eprintln!("[ConfigSources] source failed index={} url=\"{}\": {}", index, source.url, error);
+                failed.push(SourceFailure { url: source.url.clone(), error });
+            }
+        }
+    }
+    match failed.len() {
+        0 => Err("未配置远程配置地址".to_string()),
+        1 => Err(failed.remove(0).error),
+        _ => Err(format!(
+            "所有配置源均不可用：{}",
+            failed
+                .iter()
+                .enumerate()
+                .map(|(i, f)| format!("[{}] {}: {}", i + 1, f.url, f.error))
+                .collect::<Vec<_>>()
+                .join("；")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn falls_over_to_next_source_and_reports_failures() {
+        let source = |url: &str, timeout_secs: Option<u64>| ConfigSource { url: url.to_string(), timeout_secs };
+        let sources = vec![
+            source("https://cdn.example.com/printer_config.json", None),
+            source("https://origin.example.com/printer_config.json", Some(1)),
+            source("http://10.0.0.5/printer_config.json", None),
+        ];
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+
+        let (value, report) = runtime
+            .block_on(first_available(&sources, |s| async move {
+                if s.url.contains("cdn") {
+                    Err("服务器返回错误: 503 Service Unavailable".to_string())
+                } else if s.url.contains("origin") {
+                    // 超过本来源 1 秒的超时
+                    tokio::time::sleep(Duration::from_secs(3)).await;
+                    Ok("origin")
+                } else {
+                    Ok("mirror")
+                }
+            }))
+            .unwrap();
+        assert_eq!(value, "mirror");
+        assert_eq!(report.index, 2);
+        assert_eq!(report.failed.len(), 2);
+        assert_eq!(report.failed[1].error, "请求超时（1 秒）");
+        assert_eq!(served_url().as_deref(), Some("http://10.0.0.5/printer_config.json"));
+        assert_eq!(overall_deadline(&sources, Duration::from_millis(1500)), Duration::from_secs(11));
+        assert_eq!(overall_deadline(&sources[..1], Duration::from_millis(1500)), Duration::from_millis(1500));
+
+        let single = runtime.block_on(first_available(&sources[..1], |_| async { Err::<(), _>("网络请求失败".to_string()) }));
+        assert_eq!(single.unwrap_err(), "网络请求失败");
+    }
+}
@@ -0,0 +1,258 @@
+/**
+ * 配置更新行动计划
+ * 职责：
+ * - confirm_update_config 保存远程配置时，逐台比较新旧配置中的打印机（按名称匹配）：
+ *   路径、驱动（driverKey / driverCatalog 远程包 url / sha256 / version、INF、驱动名称）、安装选项、型号
+ * - 结合本机已安装的队列给出行动计划：哪些已安装的打印机需要修复（重新安装），哪些已从配置移除，
+ *   而不是整体失效重装或让已安装的打印机默默停留在旧配置
+ * - TCP/IP 打印机只改了地址时给出 update_port（update_printer_port 原地改挂端口，不重装驱动）
+ */
+
+use super::printer_service::tcp_address;
+use crate::platform::PrinterDetectEntry;
+use crate::{resolve_effective_driver_spec, EffectiveDriverSpec, Printer, PrinterConfig};
+use serde::{Deserialize, Serialize};
+
+/// 需要重新安装才能生效的变更
+const REPAIR_CHANGES: [&str; 8] = [
+    "path",
+    "driver_key",
+    "driver_url",
+    "driver_sha256",
+    "driver_version",
+    "driver_path",
+    "driver_names",
+    "install_options",
+];
+
+/// 单台打印机的配置变更与建议操作
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterUpdateAction {
+    pub printer_name: String,
+    pub city_id: String,
+    pub area_name: String,
+    /// "added" | "removed" | "changed"
+    pub kind: String,
+    /// 变更的字段："path" | "driver_key" | "driver_url" | "driver_sha256" | "driver_version"
+    /// | "driver_path" | "driver_names" | "install_options" | "model"
+    pub changes: Vec<String>,
+    pub installed: bool,
+    pub system_queue_name: Option<String>,
+    /// "update_port"（已安装且只改了 TCP/IP 地址，可原地改挂端口）| "repair"（已安装且变更需重新安装）
+    /// | "remove"（已安装但已从配置移除，可删除）| "none"
+    pub action: String,
+}
+
+/// 配置更新行动计划
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigUpdatePlan {
+    /// 有变更的打印机（未变更的不列出）
+    pub printers: Vec<PrinterUpdateAction>,
+    pub unchanged_count: usize,
+    pub repair_count: usize,
+    pub update_port_count: usize,
+    /// 枚举系统打印机失败时的说明（此时 installed 全部为 false，不给出修复建议）
+    pub install_state_error: Option<String>,
+}
+
+/// 打印机在配置中的位置与生效规格
+struct PrinterEntry<'a> {
+    city_id: &'a str,
+    area_name: &'a str,
+    printer: Printer,
+    spec: EffectiveDriverSpec,
+}
+
+/// 展开配置中的全部打印机（已合并区域默认安装选项；重名时取第一个，与安装时的匹配规则一致）
+fn collect_printers(config: &PrinterConfig) -> Vec<PrinterEntry<'_>> {
+    let mut entries: Vec<PrinterEntry<'_>> = Vec::new();
+    for city in &config.cities {
+        for area in &city.areas {
+            for printer in &area.printers {
+                if entries.iter().any(|e| e.printer.name == printer.name) {
+                    continue;
+                }
+                let mut resolved = printer.clone();
+                resolved.options = printer.options.or(&area.defaults);
+                let spec = resolve_effective_driver_spec(&resolved, config.driver_catalog.as_ref());
+                entries.push(PrinterEntry {
+                    city_id: &city.city_id,
+                    area_name: &area.area_name,
+                    printer: resolved,
+                    spec,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// 比较同名打印机的新旧配置，返回变更字段
+fn printer_changes(old: &PrinterEntry<'_>, new: &PrinterEntry<'_>) -> Vec<String> {
+    let remote = |entry: &PrinterEntry<'_>| {
+        entry
+            .spec
+            .remote_driver
+            .as_ref()
+            .map(|r| (r.url.clone(), r.sha256.to_ascii_lowercase(), r.version.clone()))
+    };
+    let (old_remote, new_remote) = (remote(old), remote(new));
+    let options_json = |p: &Printer| serde_json::to_value(&p.options).unwrap_or_default();
+
+    let checks = [
+        ("path", old.printer.path.trim() != new.printer.path.trim()),
+        ("driver_key", old.spec.driver_key_used != new.spec.driver_key_used),
+        ("driver_url", old_remote.as_ref().map(|r| &r.0) != new_remote.as_ref().map(|r| &r.0)),
+        ("driver_sha256", old_remote.as_ref().map(|r| &r.1) != new_remote.as_ref().map(|r| &r.1)),
+        ("driver_version", old_remote.as_ref().map(|r| &r.2) != new_remote.as_ref().map(|r| &r.2)),
+        ("driver_path", old.spec.effective_driver_path != new.spec.effective_driver_path),
+        ("driver_names", old.spec.effective_driver_names != new.spec.effective_driver_names),
+        (
+            "install_options",
+            old.spec.effective_install_mode != new.spec.effective_install_mode
+                || options_json(&old.printer) != options_json(&new.printer),
+        ),
+        ("model", old.printer.model != new.printer.model),
+    ];
+    checks
+        .into_iter()
+        .filter(|(_, changed)| *changed)
+        .map(|(field, _)| field.to_string())
+        .collect()
+}
+
+/// 计算配置更新的行动计划
+///
+/// # 参数
+/// - `old`: 更新前的本地配置
+/// - `new`: 即将保存的配置
+/// - `installed`: 本机已安装的打印机（枚举失败时传空）
+pub fn build_update_plan(old: &PrinterConfig, new: &PrinterConfig, installed: &[PrinterDetectEntry]) -> ConfigUpdatePlan {
+    let old_printers = collect_printers(old);
+    let new_printers = collect_printers(new);
+    let mut plan = ConfigUpdatePlan::default();
+
+    let mut push = |entry: &PrinterEntry<'_>, kind: &str, changes: Vec<String>, port_only: bool| {
+        let queue = super::catalog_service::find_installed(&entry.printer.name, installed);
+        let action = match (kind, queue.is_some()) {
+            ("removed", true) => "remove",
+            ("changed", true) if port_only => "update_port",
+            ("changed", true) if changes.iter().any(|c| REPAIR_CHANGES.contains(&c.as_str())) => "repair",
+            _ => "none",
+        };
+        plan.printers.push(PrinterUpdateAction {
+            printer_name: entry.printer.name.clone(),
+            city_id: entry.city_id.to_string(),
+            area_name: entry.area_name.to_string(),
+            kind: kind.to_string(),
+            changes,
+            installed: queue.is_some(),
+            system_queue_name: queue.map(|q| q.system_queue_name.clone()),
+            action: action.to_string(),
+        });
+    };
+
+    for new_entry in &new_printers {
+        match old_printers.iter().find(|o| o.printer.name == new_entry.printer.name) {
+            Some(old_entry) => {
+                let changes = printer_changes(old_entry, new_entry);
+                let port_only = changes == ["path"]
+                    && tcp_address(&old_entry.printer.path).is_some()
+                    && tcp_address(&new_entry.printer.path).is_some();
+                if !changes.is_empty() {
+                    push(new_entry, "changed", changes, port_only);
+                }
+            }
+            None => push(new_entry, "added", Vec::new(), false),
+        }
+    }
+    for old_entry in &old_printers {
+        if !new_printers.iter().any(|n| n.printer.name == old_entry.printer.name) {
+            push(old_entry, "removed", Vec::new(), false);
+        }
+    }
+
+    plan.unchanged_count = new_printers.len() - plan.printers.iter().filter(|p| p.kind != "removed").count();
+    plan.repair_count = plan.printers.iter().filter(|p| p.action == "repair").count();
+    plan.update_port_count = plan.printers.iter().filter(|p| p.action == "update_port").count();
+    plan
+}
+
+/// 枚举本机打印机并计算行动计划（阻塞调用）
+pub fn plan_with_installed_state(old: &PrinterConfig, new: &PrinterConfig) -> ConfigUpdatePlan {
+    let (installed, install_state_error) = match crate::platform::list_printers() {
+        Ok(list) => (list, None),
+        Err(e) => {
+            eprintln!("[ConfigUpdatePlan] list_printers failed, install state unavailable: {}", e);
+            (Vec::new(), Some(e))
+        }
+    };
+    let mut plan = build_update_plan(old, new, &installed);
+    plan.install_state_error = install_state_error;
+    eprintln!(
+        "[ConfigUpdatePlan] changed={} unchanged={} repair={} update_port={} remove={}",
+        plan.printers.len(),
+        plan.unchanged_count,
+        plan.repair_count,
+        plan.update_port_count,
+        plan.printers.iter().filter(|p| p.action == "remove").count()
+    );
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: serde_json::Value) -> PrinterConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn installed(name: &str) -> PrinterDetectEntry {
+        serde_json::from_value(serde_json::json!({ "installedKey": name, "systemQueueName": name, "platform": "windows" })).unwrap()
+    }
+
+    fn config(sha: &str, finance_path: &str) -> PrinterConfig {
+        parse(serde_json::json!({
+            "driverCatalog": {
+                "HP": { "remote": { "url": "https://example.com/hp.zip", "sha256": sha } },
+                "Canon": { "installMode": "package" }
+            },
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [{
+                    "areaName": "3楼",
+                    "printers": [
+                        { "name": "前台", "path": "\\\\10.0.0.1", "driverKey": "HP" },
+                        { "name": "财务", "path": finance_path, "driverKey": "Canon" },
+                        { "name": "会议室", "path": "\\\\10.0.0.3", "driverKey": "Canon" }
+                    ]
+                }]
+            }]
+        }))
+    }
+
+    #[test]
+    fn repairs_only_installed_printers_whose_driver_or_path_changed() {
+        let old = config("aa", "\\\\10.0.0.2");
+        let new = config("bb", "\\\\10.0.0.9");
+        let plan = build_update_plan(&old, &new, &[installed("前台"), installed("会议室")]);
+
+        let action = |name: &str| plan.printers.iter().find(|p| p.printer_name == name);
+        assert_eq!(action("前台").map(|p| p.action.as_str()), Some("repair"));
+        assert_eq!(action("前台").map(|p| p.changes.clone()), Some(vec!["driver_sha256".to_string()]));
+        // 未安装的打印机只列出变更，不建议修复
+        assert_eq!(action("财务").map(|p| p.action.as_str()), Some("none"));
+        assert!(action("会议室").is_none());
+        assert_eq!(plan.unchanged_count, 1);
+        assert_eq!(plan.repair_count, 1);
+
+        // 已安装的 TCP/IP 打印机只改了地址：原地改挂端口，不重新安装
+        let plan = build_update_plan(&old, &new, &[installed("财务")]);
+        let finance = plan.printers.iter().find(|p| p.printer_name == "财务").unwrap();
+        assert_eq!(finance.action, "update_port");
+        assert_eq!((plan.repair_count, plan.update_port_count), (0, 1));
+    }
+}
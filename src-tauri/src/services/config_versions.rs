@@ -0,0 +1,285 @@
+/**
+ * 远程配置历史版本服务
+ * 职责：
+ * - list_remote_config_versions：读取配置地址同目录下的版本索引 printer_config_versions.json，列出近期发布的配置版本
+ * - apply_remote_config_version：下载指定版本（可选 sha256 校验、version 必须与索引一致），经 extends 合并与校验后
+ *   保存为本地配置，并给出已安装打印机的行动计划；用于排障时把单台机器回退 / 前进到已知可用的版本
+ *
+ * 索引格式：{ "versions": [{ "version", "url"（可相对索引地址）, "publishedAt"?, "note"?, "sha256"? }] }
+ * 回退到旧版本后，下次刷新远程配置时仍会照常提示更新到最新版本
+ */
+
+use crate::PrinterConfig;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+
+/// 版本索引文件名（与配置文件同目录）
+const VERSION_INDEX_FILE: &str = "printer_config_versions.json";
+
+/// 最多列出的版本数
+const MAX_VERSIONS: usize = 20;
+
+/// 索引中的版本条目
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct IndexEntry {
+    version: String,
+    url: String,
+    #[serde(default)]
+    published_at: Option<String>,
+    #[serde(default)]
+    note: Option<String>,
+    #[serde(default)]
+    sha256: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VersionIndex {
+    #[serde(default)]
+    versions: Vec<IndexEntry>,
+}
+
+/// 可应用的远程配置版本
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfigVersion {
+    pub version: String,
+    /// 已解析为绝对地址
+    pub url: String,
+    pub published_at: Option<String>,
+    pub note: Option<String>,
+    pub sha256: Option<String>,
+    /// 是否为本地配置当前的版本
+    pub current: bool,
+}
+
+/// list_remote_config_versions 结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteConfigVersionList {
+    pub index_url: String,
+    pub local_version: Option<String>,
+    /// 按版本号从新到旧
+    pub versions: Vec<RemoteConfigVersion>,
+}
+
+/// apply_remote_config_version 结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedConfigVersion {
+    pub version: String,
+    pub previous_version: Option<String>,
+    pub config: PrinterConfig,
+    /// 已安装打印机的行动计划（计算失败时为 None）
+    pub update_plan: Option<super::config_update_plan::ConfigUpdatePlan>,
+}
+
+/// 版本索引地址（配置地址同目录）
+fn index_url(config_url: &str) -> Result<url::Url, String> {
+    url::Url::parse(config_url)
+        .and_then(|base| base.join(VERSION_INDEX_FILE))
+        .map_err(|e| format!("配置地址无效 '{}': {}", config_url, e))
+}
+
+/// 解析索引：条目地址相对索引地址解析，只接受 http/https，按版本号从新到旧排序
+fn parse_index(index: VersionIndex, index_url: &url::Url, local_version: Option<&str>) -> Result<Vec<RemoteConfigVersion>, String> {
+    let mut versions = Vec::new();
+    for entry in index.versions {
+        let version = entry.version.trim().to_string();
+        if version.is_empty() {
+            return Err("版本索引中存在空的 version".to_string());
+        }
+        let url = index_url
+            .join(entry.url.trim())
+            .map_err(|e| format!("版本 {} 的地址无效 '{}': {}", version, entry.url, e))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(format!("版本 {} 的地址仅支持 http/https: {}", version, url));
+        }
+        if versions.iter().any(|v: &RemoteConfigVersion| v.version == version) {
+            return Err(format!("版本索引中 {} 重复出现", version));
+        }
+        versions.push(RemoteConfigVersion {
+            current: local_version == Some(version.as_str()),
+            version,
+            url: url.to_string(),
+            published_at: entry.published_at,
+            note: entry.note,
+            sha256: entry.sha256.map(|s| s.trim().to_ascii_lowercase()).filter(|s| !s.is_empty()),
+        });
+    }
+    // 按数字段从新到旧排序（与检查更新的 compare_versions 一致，"1.10" 新于 "1.9"）
+    versions.sort_by(|a, b| {
+        if crate::compare_versions(&b.version, &a.version) {
+            Ordering::Less
+        } else if crate::compare_versions(&a.version, &b.version) {
+            Ordering::Greater
+        } else {
+            Ordering::Equal
+        }
+    });
+    versions.truncate(MAX_VERSIONS);
+    Ok(versions)
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(crate::HTTP_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))
+}
+
+/// 下载地址内容
+async fn fetch_bytes(client: &reqwest::Client, url: &str, what: &str) -> Result<Vec<u8>, String> {
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("下载{}失败 {}: {}", what, url, e))?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(format!("配置服务器未提供{}（{} 返回 404）", what, url));
+    }
+    if !response.status().is_success() {
+        return Err(format!("下载{}失败 {}: 服务器返回 {}", what, url, response.status()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| format!("读取{}失败 {}: {}", what, url, e))?;
+    Ok(bytes.to_vec())
+}
+
+/// 本地配置当前的版本（读取失败时为 None）
+async fn local_version() -> Option<String> {
+    tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .ok()
+        .and_then(|r| r.ok())
+        .and_then(|(config, _)| config.version)
+}
+
+async fn fetch_versions(client: &reqwest::Client, local_version: Option<&str>) -> Result<RemoteConfigVersionList, String> {
+    let index_url = index_url(&super::user_settings::config_url())?;
+    let bytes = fetch_bytes(client, index_url.as_str(), "版本索引").await?;
+    let index: VersionIndex = serde_json::from_slice(&bytes).map_err(|e| format!("解析版本索引失败: {}", e))?;
+    Ok(RemoteConfigVersionList {
+        versions: parse_index(index, &index_url, local_version)?,
+        index_url: index_url.to_string(),
+        local_version: local_version.map(str::to_string),
+    })
+}
+
+/// 列出远程配置的近期版本
+///
+/// # 返回
+/// - `Ok(RemoteConfigVersionList)`: 版本列表（current 标明本地当前版本）
+/// - `Err(String)`: 服务器未提供版本索引、下载或解析失败
+pub async fn list_remote_config_versions() -> Result<RemoteConfigVersionList, String> {
+    let client = http_client()?;
+    let local_version = local_version().await;
+    let list = fetch_versions(&client, local_version.as_deref()).await?;
+    eprintln!(
+        "[ConfigVersions] index={} versions={} local={:?}",
+        list.index_url,
+        list.versions.len(),
+        list.local_version
+    );
+    Ok(list)
+}
+
+/// 下载并应用指定版本的远程配置
+///
+/// # 参数
+/// - `version`: 版本索引中的版本号
+///
+/// # 返回
+/// - `Ok(AppliedConfigVersion)`: 已保存为本地配置（含已安装打印机的行动计划）
+/// - `Err(String)`: 版本不存在、校验失败或保存失败（本地配置保持不变）
+pub async fn apply_remote_config_version(app: &tauri::AppHandle, version: &str) -> Result<AppliedConfigVersion, String> {
+    let version = version.trim();
+    if version.is_empty() {
+        return Err("版本号不能为空".to_string());
+    }
+    let client = http_client()?;
+    let (old_config, config_path) = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))??;
+    let list = fetch_versions(&client, old_config.version.as_deref()).await?;
+    let entry = list
+        .versions
+        .iter()
+        .find(|v| v.version == version)
+        .ok_or_else(|| format!("版本索引中没有 {}", version))?;
+
+    let bytes = fetch_bytes(&client, &entry.url, &format!("配置版本 {}", version)).await?;
+    if let Some(expected) = entry.sha256.as_deref() {
+        let actual = format!("{:x}", Sha256::digest(&bytes));
+        if actual != expected {
+            return Err(format!("配置版本 {} 的 sha256 不匹配（期望 {}，实际 {}）", version, expected, actual));
+        }
+    }
+    let config: PrinterConfig =
+        serde_json::from_slice(&bytes).map_err(|e| format!("解析配置版本 {} 失败: {}", version, e))?;
+    if config.version.as_deref() != Some(version) {
+        return Err(format!("配置文件中的 version={:?} 与索引中的 {} 不一致", config.version, version));
+    }
+    let config = super::config_service::resolve_extends(config, &entry.url).await?;
+    crate::validate_printer_config_v2(&config)?;
+
+    let save_path = crate::get_local_config_path(app).unwrap_or(config_path);
+    crate::save_config_to_local(&config, &save_path)?;
+    super::config_sources::remember_saved_origin(Some(&entry.url));
+    let previous_version = old_config.version.clone();
+    eprintln!(
+        "[ConfigVersions] applied version={} previous={:?} path={}",
+        version,
+        previous_version,
+        save_path.display()
+    );
+    super::events::emit_config_updated(app, &config, config.version.clone());
+
+    let new_config = config.clone();
+    let update_plan = tokio::task::spawn_blocking(move || {
+        super::config_update_plan::plan_with_installed_state(&old_config, &new_config)
+    })
+    .await
+    .map_err(|e| eprintln!("[ConfigVersions] 计算配置更新计划任务异常: {}", e))
+    .ok();
+
+    Ok(AppliedConfigVersion {
+        version: version.to_string(),
+        previous_version,
+        config,
+        update_plan,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolves_and_sorts_index_entries() {
+        let index_url = index_url("https://example.com/site/printer_config.json").unwrap();
+        assert_eq!(index_url.as_str(), "https://example.com/site/printer_config_versions.json");
+
+        let index: VersionIndex = serde_json::from_value(serde_json::json!({
+            "versions": [
+                { "version": "2.1.0", "url": "versions/2.1.0.json", "sha256": " AB " },
+                { "version": "2.10.0", "url": "https://cdn.example.com/2.10.0.json", "note": "新增 5 楼" },
+                { "version": "2.9.0", "url": "versions/2.9.0.json" }
+            ]
+        }))
+        .unwrap();
+        let versions = parse_index(index, &index_url, Some("2.1.0")).unwrap();
+        let order: Vec<&str> = versions.iter().map(|v| v.version.as_str()).collect();
+        assert_eq!(order, vec!["2.10.0", "2.9.0", "2.1.0"]);
+        assert_eq!(versions[2].url, "https://example.com/site/versions/2.1.0.json");
+        assert_eq!(versions[2].sha256.as_deref(), Some("ab"));
+        assert!(versions[2].current && !versions[0].current);
+
+        let bad: VersionIndex =
+            serde_json::from_value(serde_json::json!({ "versions": [{ "version": "1", "url": "file:///c:/x.json" }] })).unwrap();
+        assert!(parse_index(bad, &index_url, None).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+/**
+ * 崩溃报告服务
+ * 职责：
+ * - 启动时注册 panic hook（Windows 另注册未处理异常过滤器写 minidump），崩溃摘要与 dump 写入 logs/crashes
+ * - 下次启动时由前端读取未处理的崩溃报告，提示"上次运行时发生了崩溃，是否发送报告？"
+ * - 仅在用户确认（或设置中已同意发送诊断数据）且配置了上传地址时上传（先 POST JSON 摘要，再 POST minidump）；
+ *   上传地址取用户设置 crashReportEndpoint，未设置时取配置 crashReporting.endpoint；
+ *   无论上传与否，处理过的报告都标记为已处理，不再重复提示
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use std::time::Duration;
+
+/// 崩溃报告子目录（位于日志目录下）
+const CRASH_DIR_NAME: &str = "crashes";
+
+/// 保留的崩溃报告数量上限（超出时删除最旧的）
+const MAX_KEPT_REPORTS: usize = 10;
+
+/// 上传请求超时
+const UPLOAD_TIMEOUT_SECS: u64 = 30;
+
+/// 上传的 minidump 体积上限
+const MAX_UPLOAD_DUMP_BYTES: u64 = 50 * 1024 * 1024;
+
+/// 本次进程启动时间（只提示早于本次启动的崩溃）
+static STARTED_AT_MS: OnceLock<i64> = OnceLock::new();
+
+/// 崩溃摘要（<id>.json）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReport {
+    pub id: String,
+    /// "panic" | "exception"
+    pub kind: String,
+    pub message: String,
+    /// panic 位置（file:line:column）
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub backtrace: Option<String>,
+    /// 结构化异常码（如 0xC0000005，仅 exception）
+    pub exception_code: Option<String>,
+    pub dump_path: Option<String>,
+    pub app_version: String,
+    pub platform: String,
+    pub arch: String,
+    pub created_at: i64,
+    /// "pending" | "uploaded" | "dismissed"
+    pub status: String,
+}
+
+/// resolve_crash_report 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashReportResolution {
+    pub id: String,
+    pub status: String,
+    /// 是否同时上传了 minidump
+    pub dump_uploaded: bool,
+}
+
+/// 崩溃报告目录（%LOCALAPPDATA%\ePrinty\logs\crashes 等）
+pub fn crash_dir() -> Result<PathBuf, String> {
+    let dir = super::fs_paths::get_logs_dir()?.join(CRASH_DIR_NAME);
+    std::fs::create_dir_all(&dir).map_err(|e| format!("创建崩溃报告目录失败 {}: {}", dir.display(), e))?;
+    Ok(dir)
+}
+
+/// 生成崩溃报告 id（时间戳 + 进程号，同一进程内多次 panic 以毫秒区分）
+pub fn new_report_id() -> String {
+    format!("crash_{}_{}", chrono::Utc::now().timestamp_millis(), std::process::id())
+}
+
+fn summary_path(dir: &Path, id: &str) -> PathBuf {
+    dir.join(format!("{}.json", id))
+}
+
+fn new_report(id: &str, kind: &str, message: String) -> CrashReport {
+    CrashReport {
+        id: id.to_string(),
+        kind: kind.to_string(),
+        message,
+        location: None,
+        thread: None,
+        backtrace: None,
+        exception_code: None,
+        dump_path: None,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: std::env::consts::OS.to_string(),
+        arch: std::env::consts::ARCH.to_string(),
+        created_at: chrono::Utc::now().timestamp_millis(),
+        status: "pending".to_string(),
+    }
+}
+
+fn save_report(dir: &Path, report: &CrashReport) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(report).map_err(|e| format!("序列化崩溃报告失败: {}", e))?;
+    let path = summary_path(dir, &report.id);
+    std::fs::write(&path, content).map_err(|e| format!("写入崩溃报告失败 {}: {}", path.display(), e))
+}
+
+/// 读取目录中的全部崩溃摘要（按时间从新到旧）
+fn load_reports(dir: &Path) -> Vec<CrashReport> {
+    let mut reports: Vec<CrashReport> = std::fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().and_then(|e| e.to_str()) == Some("json"))
+                .filter_map(|path| std::fs::read_to_string(path).ok())
+                .filter_map(|content| serde_json::from_str(&content).ok())
+                .collect()
+        })
+        .unwrap_or_default();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.created_at));
+    reports
+}
+
+/// 只保留最近 MAX_KEPT_REPORTS 份报告（连同 dump 一起删除）
+fn prune_reports(dir: &Path) {
+    for report in load_reports(dir).into_iter().skip(MAX_KEPT_REPORTS) {
+        if let Some(dump) = &report.dump_path {
+            let _ = std::fs::remove_file(dump);
+        }
+        let _ = std::fs::remove_file(summary_path(dir, &report.id));
+    }
+}
+
+/// panic 时记录摘要（Windows 同时写入 minidump）
+fn record_panic(dir: &Path, info: &std::panic::PanicHookInfo<'_>) {
+    let payload = info.payload();
+    let message = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "(非字符串 panic 信息)".to_string());
+
+    let id = new_report_id();
+    let mut report = new_report(&id, "panic", message);
+    report.location = info.location().map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+    report.thread = std::thread::current().name().map(str::to_string);
+    report.backtrace = Some(std::backtrace::Backtrace::force_capture().to_string());
+
+    #[cfg(windows)]
+    {
+        let dump_path = dir.join(format!("{}.dmp", id));
+        match crate::platform::windows::crash_dump::write_current_process_dump(&dump_path) {
+            Ok(()) => report.dump_path = Some(dump_path.display().to_string()),
+            Err(e) => eprintln!("[CrashReport] 写入 minidump 失败: {}", e),
+        }
+    }
+
+    match save_report(dir, &report) {
+        Ok(()) => eprintln!("[CrashReport] panic 已记录 id={} location={:?}", id, report.location),
+        Err(e) => eprintln!("[CrashReport] {}", e),
+    }
+}
+
+/// 未处理异常时记录摘要（由 Windows 异常过滤器在写完 minidump 后调用）
+#[cfg(windows)]
+fn record_exception(id: &str, dump_path: Option<&Path>, code: u32) {
+    let Ok(dir) = crash_dir() else {
+        return;
+    };
+    let mut report = new_report(id, "exception", format!("未处理的异常 0x{:08X}", code));
+    report.exception_code = Some(format!("0x{:08X}", code));
+    report.thread = std::thread::current().name().map(str::to_string);
+    report.dump_path = dump_path.map(|p| p.display().to_string());
+    let _ = save_report(&dir, &report);
+}
+
+/// 注册崩溃捕获（main 开头调用一次；日志目录不可用时只保留默认 panic 输出）
+pub fn install() {
+    let _ = STARTED_AT_MS.set(chrono::Utc::now().timestamp_millis());
+    let dir = match crash_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[CrashReport] 崩溃报告目录不可用，不捕获崩溃: {}", e);
+            return;
+        }
+    };
+    prune_reports(&dir);
+
+    #[cfg(windows)]
+    crate::platform::windows::crash_dump::install_exception_filter(dir.clone(), new_report_id, record_exception);
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        record_panic(&dir, info);
+        default_hook(info);
+    }));
+}
+
+/// 获取最近一次未处理的崩溃报告（只返回本次启动之前发生的）
+///
+/// # 返回
+/// - `Ok(Some(CrashReport))`: 有未处理的崩溃报告（前端据此提示是否发送）
+/// - `Ok(None)`: 没有
+pub fn pending_report() -> Result<Option<CrashReport>, String> {
+    let dir = crash_dir()?;
+    let started_at = STARTED_AT_MS.get().copied().unwrap_or(i64::MAX);
+    Ok(load_reports(&dir)
+        .into_iter()
+        .find(|r| r.status == "pending" && r.created_at < started_at))
+}
+
+/// 上传崩溃摘要与 minidump
+async fn upload_report(endpoint: &str, report: &CrashReport) -> Result<bool, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(UPLOAD_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+
+    let response = client
+        .post(endpoint)
+        .json(report)
+        .send()
+        .await
+        .map_err(|e| format!("上传崩溃报告失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("上传崩溃报告失败: 服务器返回 {}", response.status()));
+    }
+
+    let Some(dump_path) = report.dump_path.as_deref().map(PathBuf::from) else {
+        return Ok(false);
+    };
+    let size = std::fs::metadata(&dump_path).map(|m| m.len()).unwrap_or(0);
+    if size == 0 || size > MAX_UPLOAD_DUMP_BYTES {
+        eprintln!("[CrashReport] 跳过 minidump 上传 size={} path=\"{}\"", size, dump_path.display());
+        return Ok(false);
+    }
+    let bytes = tokio::fs::read(&dump_path)
+        .await
+        .map_err(|e| format!("读取 minidump 失败 {}: {}", dump_path.display(), e))?;
+    let response = client
+        .post(endpoint)
+        .header("Content-Type", "application/octet-stream")
+        .header("X-Crash-Report-Id", report.id.as_str())
+        .body(bytes)
+        .send()
+        .await
+        .map_err(|e| format!("上传 minidump 失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("上传 minidump 失败: 服务器返回 {}", response.status()));
+    }
+    Ok(true)
+}
+
+/// 处理崩溃报告：用户确认时上传，否则标记为已忽略
+///
+/// # 参数
+/// - `id`: 崩溃报告 id
+/// - `upload`: 用户是否同意发送
+///
+/// # 返回
+/// - `Ok(CrashReportResolution)`: 处理结果（上传成功为 "uploaded"，不上传为 "dismissed"）
+/// - `Err(String)`: 报告不存在、未配置上传地址或上传失败（此时报告保持 pending，下次启动仍会提示）
+pub async fn resolve_report(id: &str, upload: bool) -> Result<CrashReportResolution, String> {
+    let dir = crash_dir()?;
+    let mut report = load_reports(&dir)
+        .into_iter()
+        .find(|r| r.id == id)
+        .ok_or_else(|| format!("崩溃报告不存在: {}", id))?;
+
+    let mut dump_uploaded = false;
+    if upload {
+        let (config, _) = tokio::task::spawn_blocking(crate::load_local_config)
+            .await
+            .map_err(|e| format!("读取本地配置任务异常: {}", e))??;
+        // 用户设置中的上传地址优先
+        let endpoint = super::user_settings::current()
+            .crash_report_endpoint
+            .or_else(|| config.crash_reporting.map(|c| c.endpoint.trim().to_string()))
+            .filter(|e| !e.is_empty())
+            .ok_or_else(|| format!("未配置 crashReporting.endpoint，无法上传。崩溃报告已保存在 {}", dir.display()))?;
+        dump_uploaded = upload_report(&endpoint, &report).await?;
+        report.status = "uploaded".to_string();
+    } else {
+        report.status = "dismissed".to_string();
+    }
+    save_report(&dir, &report)?;
+    eprintln!("[CrashReport] resolved id={} status={} dump_uploaded={}", report.id, report.status, dump_uploaded);
+
+    Ok(CrashReportResolution {
+        id: report.id,
+        status: report.status,
+        dump_uploaded,
+    })
+}
@@ -0,0 +1,297 @@
+/**
+ * 委托安装服务（普通用户无法获得 UAC 授权的环境）
+ * 职责：
+ * - 管理员预先注册以最高权限运行 `ePrinty.exe --run-delegated-install --silent` 的计划任务（配置 delegatedInstall.taskName）
+ * - 用户进程未提权时：把安装参数写入交换目录（%ProgramData%\ePrinty\delegated）的 <id>.request.json，
+ *   触发计划任务，轮询等待 <id>.result.json，超时后撤回请求
+ * - 计划任务进程（worker）：逐个处理未完成的请求文件，只接受名称与路径都匹配同一台配置打印机的请求，
+ *   路径、driverKey、型号与安装选项一律取自该配置条目（交换目录任何本机用户都可写入，请求内容不可信），
+ *   以 dryRun=false 走完整安装流程，把 InstallResult 写入结果文件后退出
+ *
+ * 用户无需管理员权限；能安装的只有配置中列出的打印机
+ */
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 计划任务启动 worker 时使用的命令行参数
+const WORKER_ARG: &str = "--run-delegated-install";
+
+/// 请求 / 结果文件后缀
+const REQUEST_SUFFIX: &str = ".request.json";
+const RESULT_SUFFIX: &str = ".result.json";
+
+/// 默认等待结果的超时时间（秒）
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// timeoutSeconds 允许的范围（秒）
+const MIN_TIMEOUT_SECS: u64 = 30;
+const MAX_TIMEOUT_SECS: u64 = 3600;
+
+/// 轮询结果文件的间隔（毫秒）
+const POLL_INTERVAL_MS: u64 = 1000;
+
+/// worker 不再处理早于该时长的请求（毫秒；请求方早已超时放弃）
+const REQUEST_MAX_AGE_MS: i64 = (MAX_TIMEOUT_SECS as i64) * 1000;
+
+/// 安装请求文件内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegatedInstallRequest {
+    pub request_id: String,
+    /// 发起请求的 Windows 用户名（仅用于日志）
+    pub requested_by: String,
+    pub created_at: i64,
+    /// 配置中的打印机名称与路径（worker 据此查找配置条目，其余安装参数均取自配置）
+    pub name: String,
+    pub path: String,
+    /// 队列名冲突策略（请求方已确定的策略，worker 按同一策略处理）
+    #[serde(default)]
+    pub queue_conflict: Option<String>,
+}
+
+/// 结果文件内容（result 与 error 二选一）
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DelegatedInstallOutcome {
+    request_id: String,
+    finished_at: i64,
+    result: Option<crate::InstallResult>,
+    error: Option<String>,
+}
+
+/// 校验 delegatedInstall 配置
+pub fn validate_config(config: &crate::DelegatedInstallConfig) -> Result<(), String> {
+    let task_name = config.task_name.trim();
+    if task_name.is_empty() {
+        return Err("taskName 不能为空".to_string());
+    }
+    if task_name.chars().any(|c| c == '"' || c.is_control()) {
+        return Err(format!("taskName=\"{}\" 不能包含引号或控制字符", task_name));
+    }
+    if let Some(timeout) = config.timeout_seconds {
+        if !(MIN_TIMEOUT_SECS..=MAX_TIMEOUT_SECS).contains(&timeout) {
+            return Err(format!(
+                "timeoutSeconds={} 超出范围（{}-{}）",
+                timeout, MIN_TIMEOUT_SECS, MAX_TIMEOUT_SECS
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// 当前进程是否由计划任务以 worker 身份启动
+pub fn worker_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg.eq_ignore_ascii_case(WORKER_ARG))
+}
+
+fn request_path(dir: &Path, request_id: &str) -> PathBuf {
+    dir.join(format!("{}{}", request_id, REQUEST_SUFFIX))
+}
+
+fn result_path(dir: &Path, request_id: &str) -> PathBuf {
+    dir.join(format!("{}{}", request_id, RESULT_SUFFIX))
+}
+
+/// 从交换目录的文件名中挑出尚无结果文件的请求 ID（按文件名排序，即按创建时间）
+fn pending_request_ids(file_names: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = file_names
+        .iter()
+        .filter_map(|name| name.strip_suffix(REQUEST_SUFFIX))
+        .filter(|id| !id.is_empty() && !file_names.iter().any(|n| *n == format!("{}{}", id, RESULT_SUFFIX)))
+        .map(str::to_string)
+        .collect();
+    ids.sort();
+    ids
+}
+
+/// 通过计划任务代为安装（用户进程调用，阻塞等待结果直到超时）
+///
+/// # 参数
+/// - `config`: delegatedInstall 配置
+/// - `request`: 安装参数（request_id / requested_by / created_at 由本函数填写）
+///
+/// # 返回
+/// - `Ok(InstallResult)`: worker 执行的安装结果
+/// - `Err(String)`: 写入请求 / 触发任务失败、worker 拒绝请求或等待超时
+pub async fn install_via_task(
+    config: &crate::DelegatedInstallConfig,
+    mut request: DelegatedInstallRequest,
+) -> Result<crate::InstallResult, String> {
+    let dir = super::fs_paths::get_delegated_install_dir()?;
+    let now = chrono::Utc::now().timestamp_millis();
+    request.request_id = format!("{}_{}", now, std::process::id());
+    request.requested_by = std::env::var("USERNAME").unwrap_or_default();
+    request.created_at = now;
+
+    let request_file = request_path(&dir, &request.request_id);
+    let result_file = result_path(&dir, &request.request_id);
+    let content = serde_json::to_vec_pretty(&request).map_err(|e| format!("序列化委托安装请求失败: {}", e))?;
+    super::meta_store::write_atomic(&request_file, &content)?;
+    eprintln!(
+        "[DelegatedInstall] request written id={} printer=\"{}\" task=\"{}\"",
+        request.request_id, request.name, config.task_name
+    );
+
+    let task_name = config.task_name.trim().to_string();
+    let triggered = tokio::task::spawn_blocking(move || crate::platform::run_scheduled_task(&task_name))
+        .await
+        .map_err(|e| format!("触发计划任务异常: {}", e))?;
+    if let Err(e) = triggered {
+        let _ = tokio::fs::remove_file(&request_file).await;
+        return Err(e);
+    }
+
+    let timeout_secs = config.timeout_seconds.unwrap_or(DEFAULT_TIMEOUT_SECS);
+    let deadline = tokio::time::Instant::now() + tokio::time::Duration::from_secs(timeout_secs);
+    while tokio::time::Instant::now() < deadline {
+        tokio::time::sleep(tokio::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+        let Ok(content) = tokio::fs::read_to_string(&result_file).await else {
+            continue;
+        };
+        let outcome: DelegatedInstallOutcome =
+            serde_json::from_str(&content).map_err(|e| format!("解析委托安装结果失败: {}", e))?;
+        let _ = tokio::fs::remove_file(&result_file).await;
+        let _ = tokio::fs::remove_file(&request_file).await;
+        eprintln!(
+            "[DelegatedInstall] result received id={} success={:?}",
+            outcome.request_id,
+            outcome.result.as_ref().map(|r| r.success)
+        );
+        return match (outcome.result, outcome.error) {
+            (Some(result), _) => Ok(result),
+            (None, error) => Err(format!("计划任务执行安装失败: {}", error.unwrap_or_default())),
+        };
+    }
+
+    // 超时：撤回请求，避免 worker 之后再执行一个已无人等待的安装
+    let _ = tokio::fs::remove_file(&request_file).await;
+    Err(format!(
+        "等待计划任务 \"{}\" 的安装结果超时（{} 秒）。请确认任务已注册为以最高权限运行 \"ePrinty.exe {}\"",
+        config.task_name, timeout_secs, WORKER_ARG
+    ))
+}
+
+/// 处理单个请求（worker 调用）
+///
+/// 请求文件只用于指明要安装哪一台配置打印机：名称与路径须匹配同一个配置条目，
+/// 路径、driverKey、型号取自该条目，安装方式与驱动策略按配置解析（不使用请求中的值）
+async fn handle_request(app: &tauri::AppHandle, request: DelegatedInstallRequest) -> Result<crate::InstallResult, String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    if now - request.created_at > REQUEST_MAX_AGE_MS {
+        return Err("请求已过期".to_string());
+    }
+    let (config, _) = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))??;
+    let Some(printer) = crate::find_configured_printer(&config, &request.name, &request.path) else {
+        return Err(format!(
+            "打印机 \"{}\"（{}）不在当前配置中，拒绝委托安装",
+            request.name, request.path
+        ));
+    };
+    crate::install_printer(
+        app.clone(),
+        printer.name,
+        printer.path,
+        printer.driver_key,
+        None,
+        printer.model,
+        None,
+        None,
+        Some(false),
+        // worker 无人值守：请求未指定策略时按 fail 处理，不改动他人的同名队列
+        Some(request.queue_conflict.unwrap_or_else(|| super::queue_conflict::QueueConflictStrategy::Fail.as_str().to_string())),
+    )
+    .await
+}
+
+/// 计划任务 worker：处理交换目录中所有未完成的请求（处理期间新到的请求也会处理）
+///
+/// # 返回
+/// - 本次处理的请求数
+pub async fn run_worker(app: tauri::AppHandle) -> usize {
+    let dir = match super::fs_paths::get_delegated_install_dir() {
+        Ok(dir) => dir,
+        Err(e) => {
+            eprintln!("[DelegatedInstall] worker: {}", e);
+            return 0;
+        }
+    };
+    let mut handled = 0;
+    loop {
+        let file_names: Vec<String> = std::fs::read_dir(&dir)
+            .map(|entries| entries.flatten().map(|e| e.file_name().to_string_lossy().to_string()).collect())
+            .unwrap_or_default();
+        let pending = pending_request_ids(&file_names);
+        if pending.is_empty() {
+            break;
+        }
+        for request_id in pending {
+            let request = std::fs::read_to_string(request_path(&dir, &request_id))
+                .map_err(|e| format!("读取请求失败: {}", e))
+                .and_then(|c| serde_json::from_str::<DelegatedInstallRequest>(&c).map_err(|e| format!("解析请求失败: {}", e)));
+            let outcome = match request {
+                Ok(request) => {
+                    eprintln!(
+                        "[DelegatedInstall] worker handling id={} printer=\"{}\" requested_by=\"{}\"",
+                        request_id, request.name, request.requested_by
+                    );
+                    handle_request(&app, request).await
+                }
+                Err(e) => Err(e),
+            };
+            if let Err(e) = &outcome {
+                eprintln!("[DelegatedInstall] worker rejected id={} error={}", request_id, e);
+            }
+            let (result, error) = match outcome {
+                Ok(result) => (Some(result), None),
+                Err(e) => (None, Some(e)),
+            };
+            let outcome = DelegatedInstallOutcome {
+                request_id: request_id.clone(),
+                finished_at: chrono::Utc::now().timestamp_millis(),
+                result,
+                error,
+            };
+            let written = serde_json::to_vec_pretty(&outcome)
+                .map_err(|e| format!("序列化结果失败: {}", e))
+                .and_then(|content| super::meta_store::write_atomic(&result_path(&dir, &request_id), &content));
+            if let Err(e) = written {
+                // 结果写不出去时删除请求，避免下一轮重复安装
+                eprintln!("[DelegatedInstall] worker write result failed id={} error={}", request_id, e);
+                let _ = std::fs::remove_file(request_path(&dir, &request_id));
+            }
+            handled += 1;
+        }
+    }
+    eprintln!("[DelegatedInstall] worker finished handled={}", handled);
+    handled
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn picks_requests_without_results() {
+        let names: Vec<String> = [
+            "200_2.request.json",
+            "100_1.request.json",
+            "100_1.result.json",
+            "300_3.request.json.tmp",
+            "150_9.request.json",
+            ".request.json",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        assert_eq!(pending_request_ids(&names), vec!["150_9", "200_2"]);
+
+        let config = |name: &str, timeout| crate::DelegatedInstallConfig { task_name: name.to_string(), timeout_seconds: timeout };
+        assert!(validate_config(&config("\\ePrinty\\DelegatedInstall", Some(600))).is_ok());
+        assert!(validate_config(&config("  ", None)).is_err());
+        assert!(validate_config(&config("a\" /F", None)).is_err());
+        assert!(validate_config(&config("ePrinty", Some(5))).is_err());
+    }
+}
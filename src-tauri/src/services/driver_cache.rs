@@ -0,0 +1,314 @@
+/**
+ * 驱动包缓存引用计数服务
+ * 职责：
+ * - 记录已安装队列与驱动包缓存目录（drivers_root/<driver_uuid>，含 payload.zip 与解压目录）的引用关系（driver_refs.json）
+ * - 安装成功时登记引用、删除队列后释放引用
+ * - 缓存清理（gc_driver_cache）：先剔除系统中已不存在的队列，再按需删除无引用的 uuid 目录以回收磁盘空间
+ * - 未登记过的 uuid 目录（引用计数上线前安装的驱动）只报告为 untracked，不自动删除
+ */
+
+use super::meta_store::MetaStore;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 驱动包引用存储（schemaVersion=1）
+static DRIVER_REFS: MetaStore<DriverRefs> = MetaStore::new("driver_refs", 1);
+
+/// uuid 目录名前缀（与 driver_fetch::driver_uuid_for_sha256 一致）
+const DRIVER_UUID_PREFIX: &str = "drv_";
+
+/// 单个驱动包的引用
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPackageRefs {
+    pub driver_key: String,
+    /// 引用该驱动包的系统队列名称
+    pub queues: Vec<String>,
+    pub updated_at: i64,
+}
+
+/// 所有驱动包的引用（key = driver_uuid）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverRefs {
+    pub packages: BTreeMap<String, DriverPackageRefs>,
+}
+
+/// 单个缓存目录的清理结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverCacheGcEntry {
+    pub driver_uuid: String,
+    pub driver_key: Option<String>,
+    /// "referenced" | "unreferenced" | "removed" | "untracked" | "failed"
+    pub status: String,
+    pub queues: Vec<String>,
+    pub bytes: u64,
+    pub message: Option<String>,
+}
+
+/// 缓存清理报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverCacheGcReport {
+    pub remove_unreferenced: bool,
+    /// 已从引用中剔除的队列（系统中已不存在）
+    pub pruned_queues: Vec<String>,
+    pub entries: Vec<DriverCacheGcEntry>,
+    pub reclaimed_bytes: u64,
+    pub message: String,
+}
+
+/// 由驱动包 SHA256 计算 driver_uuid（"drv_" + sha256 前 12 位小写）
+pub fn driver_uuid_for_sha256(sha256: &str) -> String {
+    format!("{}{}", DRIVER_UUID_PREFIX, sha256.chars().take(12).collect::<String>().to_lowercase())
+}
+
+/// driverKey 对应的驱动包 uuid（driverCatalog 中没有 remote.sha256 时为 None）
+pub fn package_uuid(config: &crate::PrinterConfig, driver_key: &str) -> Option<String> {
+    let sha256 = config
+        .driver_catalog
+        .as_ref()?
+        .get(driver_key)?
+        .remote
+        .as_ref()?
+        .sha256
+        .as_deref()
+        .filter(|s| !s.trim().is_empty())?;
+    Some(driver_uuid_for_sha256(sha256.trim()))
+}
+
+/// 从所有驱动包引用中移除队列（不区分大小写），返回是否有改动
+fn remove_queue(refs: &mut DriverRefs, queue: &str, now: i64) -> bool {
+    let mut changed = false;
+    for package in refs.packages.values_mut() {
+        let before = package.queues.len();
+        package.queues.retain(|q| !q.eq_ignore_ascii_case(queue));
+        if package.queues.len() != before {
+            package.updated_at = now;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// 安装成功后登记驱动包引用（驱动不来自远程驱动包时不登记；写入失败只记录日志）
+///
+/// # 参数
+/// - `queue_name`: 系统队列名称
+/// - `driver_key`: 安装使用的 driverKey
+pub async fn record_queue(queue_name: String, driver_key: String) {
+    let result = tokio::task::spawn_blocking(move || -> Result<(), String> {
+        let (config, _) = crate::load_local_config()?;
+        let Some(uuid) = package_uuid(&config, &driver_key) else {
+            return Ok(());
+        };
+        let now = chrono::Utc::now().timestamp_millis();
+        DRIVER_REFS.update(|refs| {
+            // 队列重新安装时可能换了驱动包，先从旧引用中移除
+            remove_queue(refs, &queue_name, now);
+            let package = refs.packages.entry(uuid).or_default();
+            package.driver_key = driver_key;
+            package.queues.push(queue_name);
+            package.updated_at = now;
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[DriverCache] 登记驱动包引用失败: {}", e),
+        Err(e) => eprintln!("[DriverCache] 登记任务异常: {}", e),
+    }
+}
+
+/// 删除队列后释放驱动包引用（阻塞调用）
+///
+/// # 参数
+/// - `queue_names`: 已删除的系统队列名称
+pub fn release_queues(queue_names: &[String]) -> Result<(), String> {
+    let now = chrono::Utc::now().timestamp_millis();
+    DRIVER_REFS.update(|refs| {
+        for queue in queue_names {
+            remove_queue(refs, queue, now);
+        }
+    })
+}
+
+/// drivers_root 下的 uuid 目录及其占用字节数
+fn list_uuid_dirs(drivers_root: &std::path::Path) -> Vec<(String, u64)> {
+    let Ok(entries) = std::fs::read_dir(drivers_root) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<(String, u64)> = entries
+        .flatten()
+        .filter(|e| e.file_type().map(|t| t.is_dir()).unwrap_or(false))
+        .filter_map(|e| {
+            let name = e.file_name().to_string_lossy().to_string();
+            if !name.starts_with(DRIVER_UUID_PREFIX) {
+                return None;
+            }
+            let bytes = walkdir::WalkDir::new(e.path())
+                .into_iter()
+                .flatten()
+                .filter_map(|f| f.metadata().ok())
+                .filter(|m| m.is_file())
+                .map(|m| m.len())
+                .sum();
+            Some((name, bytes))
+        })
+        .collect();
+    dirs.sort();
+    dirs
+}
+
+/// 按引用状态分类缓存目录（不做删除）
+fn classify(refs: &DriverRefs, dirs: &[(String, u64)]) -> Vec<DriverCacheGcEntry> {
+    dirs.iter()
+        .map(|(uuid, bytes)| {
+            let package = refs.packages.get(uuid);
+            let status = match package {
+                Some(p) if !p.queues.is_empty() => "referenced",
+                Some(_) => "unreferenced",
+                None => "untracked",
+            };
+            DriverCacheGcEntry {
+                driver_uuid: uuid.clone(),
+                driver_key: package.map(|p| p.driver_key.clone()),
+                status: status.to_string(),
+                queues: package.map(|p| p.queues.clone()).unwrap_or_default(),
+                bytes: *bytes,
+                message: None,
+            }
+        })
+        .collect()
+}
+
+/// 缓存清理（阻塞调用）
+fn gc_blocking(remove_unreferenced: bool) -> Result<DriverCacheGcReport, String> {
+    let installed: Vec<String> = crate::platform::list_printers()?
+        .into_iter()
+        .map(|e| e.system_queue_name)
+        .collect();
+    let now = chrono::Utc::now().timestamp_millis();
+    let (refs, pruned_queues) = DRIVER_REFS.update(|refs| {
+        let stale: Vec<String> = refs
+            .packages
+            .values()
+            .flat_map(|p| p.queues.iter())
+            .filter(|q| !installed.iter().any(|i| i.eq_ignore_ascii_case(q)))
+            .cloned()
+            .collect();
+        for queue in &stale {
+            remove_queue(refs, queue, now);
+        }
+        (refs.clone(), stale)
+    })?;
+
+    let drivers_root = super::fs_paths::get_drivers_root()?;
+    let mut entries = classify(&refs, &list_uuid_dirs(&drivers_root));
+
+    let active_jobs = crate::platform::active_install_job_count();
+    if remove_unreferenced && active_jobs > 0 {
+        return Err(format!("当前有 {} 个安装任务正在进行，请在安装完成后再清理驱动缓存", active_jobs));
+    }
+
+    let mut reclaimed_bytes = 0;
+    let mut removed_uuids = Vec::new();
+    if remove_unreferenced {
+        for entry in entries.iter_mut().filter(|e| e.status == "unreferenced") {
+            match std::fs::remove_dir_all(drivers_root.join(&entry.driver_uuid)) {
+                Ok(()) => {
+                    eprintln!("[DriverCache] removed driver_uuid={} bytes={}", entry.driver_uuid, entry.bytes);
+                    entry.status = "removed".to_string();
+                    reclaimed_bytes += entry.bytes;
+                    removed_uuids.push(entry.driver_uuid.clone());
+                }
+                Err(e) => {
+                    eprintln!("[DriverCache] remove failed driver_uuid={} error={}", entry.driver_uuid, e);
+                    entry.status = "failed".to_string();
+                    entry.message = Some(format!("删除失败: {}", e));
+                }
+            }
+        }
+    }
+    if !removed_uuids.is_empty() {
+        DRIVER_REFS.update(|refs| {
+            for uuid in &removed_uuids {
+                refs.packages.remove(uuid);
+            }
+        })?;
+    }
+
+    let count = |status: &str| entries.iter().filter(|e| e.status == status).count();
+    let message = if remove_unreferenced {
+        format!(
+            "已删除 {} 个无引用的驱动包缓存，回收 {:.1} MB；仍在使用 {} 个，未登记 {} 个{}",
+            count("removed"),
+            reclaimed_bytes as f64 / (1024.0 * 1024.0),
+            count("referenced"),
+            count("untracked"),
+            if count("failed") > 0 { format!("，删除失败 {} 个", count("failed")) } else { String::new() }
+        )
+    } else {
+        format!(
+            "无引用的驱动包缓存 {} 个（可删除），仍在使用 {} 个，未登记 {} 个",
+            count("unreferenced"),
+            count("referenced"),
+            count("untracked")
+        )
+    };
+
+    Ok(DriverCacheGcReport {
+        remove_unreferenced,
+        pruned_queues,
+        entries,
+        reclaimed_bytes,
+        message,
+    })
+}
+
+/// 驱动包缓存清理
+///
+/// # 参数
+/// - `remove_unreferenced`: 是否删除无引用的 uuid 目录（false 时只报告）
+///
+/// # 返回
+/// - `Ok(DriverCacheGcReport)`: 各缓存目录的引用状态与删除结果
+/// - `Err(String)`: 枚举打印机 / 读写引用失败，或有安装任务进行中（删除时）
+pub async fn gc_driver_cache(remove_unreferenced: bool) -> Result<DriverCacheGcReport, String> {
+    tokio::task::spawn_blocking(move || gc_blocking(remove_unreferenced))
+        .await
+        .map_err(|e| format!("驱动缓存清理任务异常: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn releases_queues_and_classifies_cache_dirs() {
+        let mut refs = DriverRefs::default();
+        refs.packages.insert(
+            "drv_aaaaaaaaaaaa".to_string(),
+            DriverPackageRefs { driver_key: "hp".to_string(), queues: vec!["前台".to_string(), "财务".to_string()], updated_at: 0 },
+        );
+        refs.packages.insert(
+            "drv_bbbbbbbbbbbb".to_string(),
+            DriverPackageRefs { driver_key: "canon".to_string(), queues: vec!["大厅".to_string()], updated_at: 0 },
+        );
+
+        assert!(remove_queue(&mut refs, "大厅", 1));
+        assert!(!remove_queue(&mut refs, "不存在", 1));
+
+        let dirs = vec![
+            ("drv_aaaaaaaaaaaa".to_string(), 10),
+            ("drv_bbbbbbbbbbbb".to_string(), 20),
+            ("drv_cccccccccccc".to_string(), 30),
+        ];
+        let statuses: Vec<String> = classify(&refs, &dirs).into_iter().map(|e| e.status).collect();
+        assert_eq!(statuses, vec!["referenced", "unreferenced", "untracked"]);
+
+        assert_eq!(driver_uuid_for_sha256("ABCDEF0123456789"), "drv_abcdef012345");
+    }
+}
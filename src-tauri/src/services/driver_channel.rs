@@ -0,0 +1,234 @@
+/**
+ * 驱动通道服务（稳定版 / 测试版驱动包）
+ * 职责：
+ * - 配置中的打印机可同时声明 driverKey（稳定版）与 betaDriverKey（测试版），两者都引用 driverCatalog 条目
+ * - 本机通道：用户设置 driverChannel 为整机默认值，switch_driver_channel 可为单台打印机覆盖（保存在本地 meta 存储 driver_channels.json）
+ * - 通道为 beta 且打印机配置了 betaDriverKey 时，安装 / 重新安装使用测试版驱动；未配置 betaDriverKey 的打印机始终使用稳定版
+ * - switch_driver_channel：保存覆盖后按新通道的驱动重新暂存并安装（已存在的队列改绑驱动），便于在部分机器上试用厂商新驱动
+ *
+ * 修改整机默认通道不会自动重新安装已有队列，下一次安装 / 重新安装时生效
+ */
+
+use super::meta_store::MetaStore;
+use crate::{Printer, PrinterConfig};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+/// 按打印机覆盖的通道（schemaVersion=1）
+static CHANNEL_OVERRIDES: MetaStore<ChannelOverrides> = MetaStore::new("driver_channels", 1);
+
+/// 进程内缓存的通道覆盖（首次读取时加载，save_override 写入后同步更新）
+static CURRENT_OVERRIDES: Mutex<Option<ChannelOverrides>> = Mutex::new(None);
+
+/// 驱动通道
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DriverChannel {
+    /// 稳定版（driverKey）
+    #[default]
+    Stable,
+    /// 测试版（betaDriverKey，未配置时回退稳定版）
+    Beta,
+}
+
+impl DriverChannel {
+    /// 解析前端传入的通道名称
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "stable" => Ok(Self::Stable),
+            "beta" => Ok(Self::Beta),
+            other => Err(format!("驱动通道 \"{}\" 无效，可选值：stable / beta", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
+}
+
+/// 按打印机名称（配置中的 name）覆盖的通道
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct ChannelOverrides {
+    printers: BTreeMap<String, DriverChannel>,
+}
+
+/// switch_driver_channel 结果
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverChannelSwitchResult {
+    pub printer_name: String,
+    /// 切换后的通道（安装失败时已恢复为切换前的通道）
+    pub channel: DriverChannel,
+    /// 本次安装使用的 driverKey
+    pub driver_key: String,
+    pub install: crate::InstallResult,
+}
+
+/// 单台打印机的通道覆盖（首次调用时从磁盘加载）
+fn override_for(printer_name: &str) -> Option<DriverChannel> {
+    let mut cached = CURRENT_OVERRIDES.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let overrides = cached.get_or_insert_with(|| {
+        CHANNEL_OVERRIDES.load().unwrap_or_else(|e| {
+            eprintln!("[DriverChannel] 读取通道覆盖失败，使用整机默认值: {}", e);
+            ChannelOverrides::default()
+        })
+    });
+    overrides.printers.get(printer_name).copied()
+}
+
+/// 打印机当前生效的通道（单台覆盖优先，否则为整机默认值）
+pub fn channel_for(printer_name: &str) -> DriverChannel {
+    override_for(printer_name).unwrap_or_else(|| super::user_settings::current().driver_channel)
+}
+
+/// 按通道选择打印机的驱动键
+fn select_driver_key(printer: &Printer, channel: DriverChannel) -> Option<String> {
+    match (channel, &printer.beta_driver_key) {
+        (DriverChannel::Beta, Some(beta_key)) => Some(beta_key.clone()),
+        _ => printer.driver_key.clone(),
+    }
+}
+
+/// 按本机通道改写配置打印机的 driver_key（未配置 betaDriverKey 时不读取通道设置）
+pub fn apply_channel(printer: &mut Printer) {
+    if printer.beta_driver_key.is_none() {
+        return;
+    }
+    printer.driver_key = select_driver_key(printer, channel_for(&printer.name));
+}
+
+/// 安装请求实际使用的驱动键
+///
+/// # 参数
+/// - `config`: 本地配置
+/// - `name` / `path`: 安装请求的打印机名称与路径
+/// - `requested`: 请求携带的 driverKey
+///
+/// # 返回
+/// 请求的是配置中的稳定版 driverKey 且本机通道为 beta 时返回 betaDriverKey，否则原样返回 requested
+pub fn driver_key_for_request(config: &PrinterConfig, name: &str, path: &str, requested: String) -> String {
+    let printer = crate::find_config_entry(config, name, path).map(|(_, printer)| printer);
+    let Some(printer) = printer.filter(|p| p.beta_driver_key.is_some() && p.driver_key.as_deref() == Some(requested.as_str())) else {
+        return requested;
+    };
+    let channel = channel_for(&printer.name);
+    match select_driver_key(printer, channel) {
+        Some(key) if key != requested => {
+            eprintln!("[DriverChannel] printer=\"{}\" channel={} driverKey \"{}\" -> \"{}\"", printer.name, channel.as_str(), requested, key);
+            key
+        }
+        _ => requested,
+    }
+}
+
+/// 保存单台打印机的通道覆盖（None = 清除覆盖，使用整机默认值）
+fn save_override(printer_name: &str, channel: Option<DriverChannel>) -> Result<(), String> {
+    let updated = CHANNEL_OVERRIDES.update(|overrides| {
+        match channel {
+            Some(channel) => {
+                overrides.printers.insert(printer_name.to_string(), channel);
+            }
+            None => {
+                overrides.printers.remove(printer_name);
+            }
+        }
+        overrides.clone()
+    })?;
+    *CURRENT_OVERRIDES.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(updated);
+    Ok(())
+}
+
+/// 切换打印机的驱动通道并重新安装
+///
+/// # 参数
+/// - `app`: 用于安装进度事件
+/// - `printer_name`: 配置中的打印机名称
+/// - `channel`: "stable" | "beta"
+///
+/// # 返回
+/// - `Ok(DriverChannelSwitchResult)`: 安装结果（安装失败时通道恢复为切换前的设置）
+/// - `Err(String)`: 通道无效、打印机不在配置中或未配置测试版驱动
+pub async fn switch_driver_channel(app: tauri::AppHandle, printer_name: &str, channel: &str) -> Result<DriverChannelSwitchResult, String> {
+    let channel = DriverChannel::parse(channel)?;
+    let (config, _) = crate::load_local_config()?;
+    let (area, printer) = config
+        .cities
+        .iter()
+        .flat_map(|city| city.areas.iter())
+        .flat_map(|area| area.printers.iter().map(move |printer| (area, printer)))
+        .find(|(_, p)| p.name == printer_name)
+        .ok_or_else(|| format!("配置中不存在打印机 \"{}\"", printer_name))?;
+    let printer = printer.clone();
+    // 按打印机 / 区域配置的 installMode 重新安装；ipp 安装不使用驱动包，切换通道没有意义
+    let install_mode = printer.options.or(&area.defaults).install_mode;
+    if install_mode.as_deref() == Some("ipp") {
+        return Err(format!("打印机 \"{}\" 配置为 IPP 安装（installMode=ipp），不使用驱动包，无法切换驱动通道", printer_name));
+    }
+    if channel == DriverChannel::Beta && printer.beta_driver_key.is_none() {
+        return Err(format!("打印机 \"{}\" 未配置测试版驱动（betaDriverKey），无法切换到 beta 通道", printer_name));
+    }
+    let driver_key = select_driver_key(&printer, channel)
+        .ok_or_else(|| format!("打印机 \"{}\" 缺少 driverKey", printer_name))?;
+
+    let previous = override_for(printer_name);
+    save_override(printer_name, Some(channel))?;
+    eprintln!("[DriverChannel] switch printer=\"{}\" channel={} driverKey=\"{}\"", printer_name, channel.as_str(), driver_key);
+
+    // 强制重新暂存驱动：同名队列已存在时由安装流程改绑到新驱动
+    let result = crate::install_printer(
+        app,
+        printer.name.clone(),
+        printer.path.clone(),
+        Some(driver_key.clone()),
+        None,
+        printer.model.clone(),
+        Some("always".to_string()),
+        install_mode,
+        Some(false),
+        Some(super::queue_conflict::QueueConflictStrategy::Overwrite.as_str().to_string()),
+    )
+    .await;
+
+    let succeeded = matches!(&result, Ok(r) if r.success);
+    if !succeeded {
+        if let Err(e) = save_override(printer_name, previous) {
+            eprintln!("[DriverChannel] 恢复通道设置失败 printer=\"{}\": {}", printer_name, e);
+        }
+    }
+    let install = result?;
+    Ok(DriverChannelSwitchResult {
+        printer_name: printer.name,
+        channel: if succeeded { channel } else { channel_for(printer_name) },
+        driver_key,
+        install,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beta_channel_uses_beta_key_only_when_configured() {
+        let mut printer: Printer = serde_json::from_value(serde_json::json!({
+            "name": "前台",
+            "path": "192.168.1.10",
+            "driverKey": "hp_stable",
+            "betaDriverKey": "hp_beta"
+        }))
+        .unwrap();
+        assert_eq!(select_driver_key(&printer, DriverChannel::Stable).as_deref(), Some("hp_stable"));
+        assert_eq!(select_driver_key(&printer, DriverChannel::Beta).as_deref(), Some("hp_beta"));
+
+        printer.beta_driver_key = None;
+        assert_eq!(select_driver_key(&printer, DriverChannel::Beta).as_deref(), Some("hp_stable"));
+
+        assert_eq!(DriverChannel::parse(" beta ").unwrap(), DriverChannel::Beta);
+        assert!(DriverChannel::parse("nightly").is_err());
+    }
+}
@@ -0,0 +1,613 @@
+/**
+ * 驱动维护相关服务
+ * 职责：
+ * - list_staged_printer_drivers
+ * - remove_staged_driver
+ * - check_driver_conflicts
+ * - build_driver_pack（管理员制作驱动 ZIP 包并生成 driverCatalog 配置片段）
+ * - build_driver_patch（比较新旧驱动包制作增量补丁，生成 remote.patch 配置片段）
+ * - inspect_driver_pack（不安装、不解压，列出驱动 ZIP 内的 INF 与其声明的型号）
+ * - set_metered_download_allowed（用户确认在按流量计费网络上下载驱动包）
+ * - set_driver_archive_password / clear_driver_archive_password（加密驱动包的解压密码，按 archivePasswordRef 保存在系统凭据管理器）
+ */
+
+use crate::utils::inf_parse::{self, InfSummary};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+pub fn list_staged_printer_drivers() -> Result<Vec<crate::platform::StagedDriverInfo>, String> {
+    crate::platform::list_staged_printer_drivers()
+}
+
+pub fn remove_staged_driver(published_name: &str) -> Result<crate::platform::RemoveStagedDriverResult, String> {
+    if published_name.trim().is_empty() {
+        return Err("驱动包发布名称不能为空".to_string());
+    }
+    crate::platform::remove_staged_driver(published_name)
+}
+
+/// 检测安装指定打印机时是否会与已安装驱动冲突
+///
+/// # 参数
+/// - `printer_name`: 配置中的打印机名称
+pub fn check_driver_conflicts(printer_name: &str) -> Result<crate::platform::DriverConflictCheckResult, String> {
+    let (config, _) = crate::load_local_config()?;
+    let printer = config
+        .cities
+        .iter()
+        .flat_map(|city| city.areas.iter())
+        .flat_map(|area| area.printers.iter())
+        .find(|p| p.name == printer_name)
+        .ok_or_else(|| format!("配置中未找到打印机: {}", printer_name))?;
+
+    let spec = crate::resolve_effective_driver_spec(printer, config.driver_catalog.as_ref());
+    let driver_path = match spec.effective_driver_path.as_deref().filter(|p| !p.trim().is_empty()) {
+        Some(path) => path,
+        None => {
+            return Ok(crate::platform::DriverConflictCheckResult {
+                status: "OK".to_string(),
+                has_conflict: false,
+                printer_name: printer_name.to_string(),
+                inf_path: None,
+                target_version: None,
+                conflicts: Vec::new(),
+                message: "该打印机未配置驱动包，安装时不会更新系统驱动".to_string(),
+            });
+        }
+    };
+
+    crate::platform::check_driver_conflicts(printer_name, driver_path, &spec.effective_driver_names)
+}
+
+/// 驱动包制作结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPackResult {
+    pub output_zip: String,
+    /// ZIP 文件 SHA256（大写十六进制，与配置示例一致）
+    pub sha256: String,
+    pub size_bytes: u64,
+    pub file_count: usize,
+    /// 主 INF 相对 driversRoot 的路径（含顶层目录，即 local.infRel）
+    pub inf_rel: String,
+    pub driver_names: Vec<String>,
+    pub driver_version: Option<String>,
+    pub driver_key: String,
+    /// 可直接粘贴进 driverCatalog 的配置片段
+    pub snippet: serde_json::Value,
+    pub snippet_text: String,
+    pub warnings: Vec<String>,
+}
+
+/// 由目录名生成建议的 driverKey（大写，非字母数字替换为下划线）
+fn suggest_driver_key(dir_name: &str) -> String {
+    let mut key: String = dir_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_uppercase() } else { '_' })
+        .collect();
+    while key.contains("__") {
+        key = key.replace("__", "_");
+    }
+    let key = key.trim_matches('_').to_string();
+    if key.is_empty() { "DRIVER_PACK".to_string() } else { key }
+}
+
+/// 计算文件 SHA256（大写十六进制）
+pub fn file_sha256(path: &Path) -> Result<String, String> {
+    let mut file = std::fs::File::open(path)
+        .map_err(|e| format!("打开文件失败 {}: {}", path.display(), e))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = [0u8; 64 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("读取文件失败 {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:X}", hasher.finalize()))
+}
+
+/// 查找目录下所有打印机类 INF（按相对路径排序）
+fn find_printer_infs(source_dir: &Path, warnings: &mut Vec<String>) -> Result<Vec<(PathBuf, InfSummary)>, String> {
+    let mut found = Vec::new();
+    for entry in walkdir::WalkDir::new(source_dir).sort_by_file_name() {
+        let entry = entry.map_err(|e| format!("遍历源目录失败 {}: {}", source_dir.display(), e))?;
+        let is_inf = entry.file_type().is_file()
+            && entry
+                .path()
+                .extension()
+                .map(|ext| ext.eq_ignore_ascii_case("inf"))
+                .unwrap_or(false);
+        if !is_inf {
+            continue;
+        }
+        let summary = inf_parse::parse_inf(&inf_parse::read_inf_text(entry.path())?);
+        let relative = entry.path().strip_prefix(source_dir).unwrap_or(entry.path()).to_path_buf();
+        if !summary.is_printer_class() {
+            warnings.push(format!(
+                "跳过非打印机类 INF: {}（Class={}）",
+                relative.display(),
+                summary.class.as_deref().unwrap_or("未声明")
+            ));
+            continue;
+        }
+        found.push((relative, summary));
+    }
+    Ok(found)
+}
+
+/// 制作驱动包：确定性打包目录、计算 SHA256、校验 INF 并生成 driverCatalog 配置片段
+///
+/// # 参数
+/// - `source_dir`: 驱动目录（目录名作为 ZIP 内顶层目录，对应 layout=drivers_root）
+/// - `output_zip`: 输出 ZIP 路径（不能位于 source_dir 内）
+///
+/// # 返回
+/// - `Ok(DriverPackResult)`: 打包结果与配置片段
+/// - `Err(String)`: 目录无效、没有可解析的打印机 INF 或写入失败
+pub fn build_driver_pack(source_dir: &str, output_zip: &str) -> Result<DriverPackResult, String> {
+    if source_dir.trim().is_empty() {
+        return Err("驱动目录不能为空".to_string());
+    }
+    if output_zip.trim().is_empty() {
+        return Err("输出 ZIP 路径不能为空".to_string());
+    }
+    let source = std::fs::canonicalize(source_dir.trim())
+        .map_err(|e| format!("驱动目录不存在或无法访问 {}: {}", source_dir, e))?;
+    if !source.is_dir() {
+        return Err(format!("驱动路径不是目录: {}", source.display()));
+    }
+    let output = PathBuf::from(output_zip.trim());
+    let output_abs = if output.is_absolute() {
+        output.clone()
+    } else {
+        std::env::current_dir()
+            .map_err(|e| format!("获取当前目录失败: {}", e))?
+            .join(&output)
+    };
+    let output_parent = output_abs
+        .parent()
+        .and_then(|p| std::fs::canonicalize(p).ok())
+        .unwrap_or_else(|| output_abs.clone());
+    if output_parent.starts_with(&source) {
+        return Err(format!("输出 ZIP 不能位于驱动目录内: {}", output_abs.display()));
+    }
+
+    let dir_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("无法确定驱动目录名称: {}", source.display()))?;
+
+    let mut warnings = Vec::new();
+    let infs = find_printer_infs(&source, &mut warnings)?;
+    let (inf_relative, summary) = infs
+        .iter()
+        .find(|(_, s)| !s.model_names.is_empty())
+        .cloned()
+        .ok_or_else(|| format!("驱动目录中未找到可解析出驱动名称的打印机 INF（Class=Printer）: {}", source.display()))?;
+    if infs.len() > 1 {
+        warnings.push(format!(
+            "目录中有 {} 个打印机 INF，已选择 {}，请确认是否正确",
+            infs.len(),
+            inf_relative.display()
+        ));
+    }
+    if summary.driver_version.is_none() {
+        warnings.push("INF 未声明 DriverVer 版本号，remote.version 需手动填写".to_string());
+    }
+
+    let report = crate::utils::zip_pack::pack_dir_deterministic(&source, &output_abs)?;
+    let sha256 = file_sha256(&output_abs)?;
+    let size_bytes = std::fs::metadata(&output_abs)
+        .map(|m| m.len())
+        .map_err(|e| format!("读取 ZIP 文件信息失败 {}: {}", output_abs.display(), e))?;
+
+    let inf_rel = std::iter::once(dir_name.clone())
+        .chain(inf_relative.components().map(|c| c.as_os_str().to_string_lossy().to_string()))
+        .collect::<Vec<_>>()
+        .join("/");
+    let zip_name = output_abs
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| format!("{}.zip", dir_name));
+    let driver_key = suggest_driver_key(&dir_name);
+
+    let entry = crate::DriverCatalogEntry {
+        install_mode: Some("package".to_string()),
+        local: Some(crate::DriverLocalSpec {
+            inf_rel: Some(inf_rel.clone()),
+            driver_names: Some(summary.model_names.clone()),
+        }),
+        remote: Some(crate::DriverRemoteSpec {
+            url: Some(format!("<下载地址>/{}", zip_name)),
+            sha256: Some(sha256.clone()),
+            version: summary.driver_version.clone(),
+            layout: Some("drivers_root".to_string()),
+            size: Some(size_bytes),
+            archive_password_ref: None,
+            patch: None,
+        }),
+        image_url: None,
+        compatibility: None,
+    };
+    let mut snippet = serde_json::Map::new();
+    snippet.insert(
+        driver_key.clone(),
+        serde_json::to_value(&entry).map_err(|e| format!("生成配置片段失败: {}", e))?,
+    );
+    let snippet = serde_json::Value::Object(snippet);
+    let snippet_text = serde_json::to_string_pretty(&snippet).map_err(|e| format!("生成配置片段失败: {}", e))?;
+
+    eprintln!(
+        "[DriverPack] built output=\"{}\" files={} bytes_read={} size={} sha256={} inf_rel=\"{}\" warnings={}",
+        output_abs.display(),
+        report.files_packed,
+        report.bytes_read,
+        size_bytes,
+        sha256,
+        inf_rel,
+        warnings.len()
+    );
+
+    Ok(DriverPackResult {
+        output_zip: output_abs.display().to_string(),
+        sha256,
+        size_bytes,
+        file_count: report.files_packed,
+        inf_rel,
+        driver_names: summary.model_names,
+        driver_version: summary.driver_version,
+        driver_key,
+        snippet,
+        snippet_text,
+        warnings,
+    })
+}
+
+/// 增量补丁制作结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPatchResult {
+    pub output_patch: String,
+    /// 补丁文件 SHA256（大写十六进制）
+    pub sha256: String,
+    pub size_bytes: u64,
+    /// 旧驱动包 SHA256（即 patch.baseSha256）
+    pub base_sha256: String,
+    /// 新驱动包 SHA256（即 remote.sha256）
+    pub new_sha256: String,
+    pub new_size_bytes: u64,
+    pub files_changed: usize,
+    pub entries_removed: usize,
+    pub files_unchanged: usize,
+    /// 可直接粘贴进 driverCatalog remote 的配置片段（"patch" 字段）
+    pub snippet: serde_json::Value,
+    pub snippet_text: String,
+    pub warnings: Vec<String>,
+}
+
+/// 制作增量补丁：比较新旧驱动包，只打包新增 / 变化的文件与已删除条目列表
+///
+/// # 参数
+/// - `base_zip`: 旧驱动包（客户端缓存中已有的版本，必须是 build_driver_pack 制作的未加密 ZIP）
+/// - `new_zip`: 新驱动包（同样由 build_driver_pack 制作）
+/// - `output_patch`: 输出补丁路径
+///
+/// # 返回
+/// - `Ok(DriverPatchResult)`: 补丁信息与配置片段（已验证补丁能在旧驱动包上重建出新驱动包）
+/// - `Err(String)`: 驱动包无法读取，或重建结果与新驱动包不一致（新旧驱动包不是确定性打包的）
+pub fn build_driver_patch(base_zip: &str, new_zip: &str, output_patch: &str) -> Result<DriverPatchResult, String> {
+    let (base, new, output) = (Path::new(base_zip.trim()), Path::new(new_zip.trim()), PathBuf::from(output_patch.trim()));
+    if base_zip.trim().is_empty() || new_zip.trim().is_empty() || output_patch.trim().is_empty() {
+        return Err("旧驱动包、新驱动包与输出补丁路径都不能为空".to_string());
+    }
+    let base_sha256 = file_sha256(base)?;
+    let new_sha256 = file_sha256(new)?;
+    if base_sha256 == new_sha256 {
+        return Err("新旧驱动包内容相同，不需要制作补丁".to_string());
+    }
+
+    let report = crate::utils::zip_patch::build_patch(base, new, &output)?;
+
+    // 与客户端相同的方式重建一次，确认补丁可用
+    let work = tempfile::tempdir().map_err(|e| format!("创建临时目录失败: {}", e))?;
+    let rebuilt = work.path().join("rebuilt.zip");
+    crate::utils::zip_patch::apply_patch(base, &output, &work.path().join("work"), &rebuilt)?;
+    if file_sha256(&rebuilt)? != new_sha256 {
+        let _ = std::fs::remove_file(&output);
+        return Err(
+            "补丁重建结果与新驱动包不一致：新旧驱动包都需要用 build_driver_pack 确定性打包（不支持其他工具制作的 ZIP）".to_string(),
+        );
+    }
+
+    let sha256 = file_sha256(&output)?;
+    let size_bytes = std::fs::metadata(&output)
+        .map(|m| m.len())
+        .map_err(|e| format!("读取补丁文件信息失败 {}: {}", output.display(), e))?;
+    let new_size_bytes = std::fs::metadata(new)
+        .map(|m| m.len())
+        .map_err(|e| format!("读取驱动包文件信息失败 {}: {}", new.display(), e))?;
+
+    let mut warnings = Vec::new();
+    if size_bytes * 2 > new_size_bytes {
+        warnings.push(format!(
+            "补丁大小超过新驱动包的一半（{} / {} 字节），增量更新节省的流量有限",
+            size_bytes, new_size_bytes
+        ));
+    }
+
+    let patch_name = output
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| "patch.zip".to_string());
+    let spec = crate::DriverPatchSpec {
+        base_sha256: Some(base_sha256.clone()),
+        url: Some(format!("<下载地址>/{}", patch_name)),
+        sha256: Some(sha256.clone()),
+        size: Some(size_bytes),
+    };
+    let snippet = serde_json::json!({
+        "patch": serde_json::to_value(&spec).map_err(|e| format!("生成配置片段失败: {}", e))?,
+    });
+    let snippet_text = serde_json::to_string_pretty(&snippet).map_err(|e| format!("生成配置片段失败: {}", e))?;
+
+    eprintln!(
+        "[DriverPack] patch built output=\"{}\" size={} new_size={} changed={} removed={} unchanged={}",
+        output.display(),
+        size_bytes,
+        new_size_bytes,
+        report.files_changed,
+        report.entries_removed,
+        report.files_unchanged
+    );
+
+    Ok(DriverPatchResult {
+        output_patch: output.display().to_string(),
+        sha256,
+        size_bytes,
+        base_sha256,
+        new_sha256,
+        new_size_bytes,
+        files_changed: report.files_changed,
+        entries_removed: report.entries_removed,
+        files_unchanged: report.files_unchanged,
+        snippet,
+        snippet_text,
+        warnings,
+    })
+}
+
+/// 单个 INF 条目的最大读取体积（超过时跳过解析）
+const MAX_INSPECT_INF_BYTES: u64 = 16 * 1024 * 1024;
+
+/// 下载待检查驱动包时的连接超时
+const INSPECT_CONNECT_TIMEOUT_SECS: u64 = 30;
+
+/// 驱动包中的 INF 信息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPackInf {
+    /// ZIP 内路径
+    pub path: String,
+    pub class: Option<String>,
+    pub is_printer_class: bool,
+    pub driver_version: Option<String>,
+    pub driver_date: Option<String>,
+    /// INF 声明的驱动名称（型号）
+    pub models: Vec<String>,
+}
+
+/// 驱动包检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DriverPackInspection {
+    /// 输入的本地路径或下载地址
+    pub source: String,
+    /// ZIP 文件 SHA256（大写十六进制，与 driverCatalog remote.sha256 一致）
+    pub sha256: String,
+    pub zip_size_bytes: u64,
+    pub file_count: usize,
+    /// 解压后的总大小
+    pub total_uncompressed_bytes: u64,
+    pub infs: Vec<DriverPackInf>,
+    /// 所有打印机类 INF 声明的驱动名称（去重）
+    pub driver_names: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+/// 下载驱动包到临时文件（返回的临时文件 Drop 时自动删除）
+async fn download_pack_to_temp(url: &str) -> Result<tempfile::NamedTempFile, String> {
+    use std::io::Write;
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(INSPECT_CONNECT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("下载驱动包失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("下载驱动包失败: 服务器返回 {}", response.status()));
+    }
+
+    let mut file = tempfile::Builder::new()
+        .prefix("eprinty-inspect-")
+        .suffix(".zip")
+        .tempfile()
+        .map_err(|e| format!("创建临时文件失败: {}", e))?;
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("下载驱动包失败: {}", e))? {
+        file.write_all(&chunk).map_err(|e| format!("写入临时文件失败: {}", e))?;
+    }
+    file.flush().map_err(|e| format!("写入临时文件失败: {}", e))?;
+    Ok(file)
+}
+
+/// 读取 ZIP 目录并解析其中的 INF（阻塞调用）
+fn inspect_zip(source: &str, zip_path: &Path) -> Result<DriverPackInspection, String> {
+    let sha256 = file_sha256(zip_path)?;
+    let zip_size_bytes = std::fs::metadata(zip_path)
+        .map_err(|e| format!("读取 ZIP 文件信息失败 {}: {}", zip_path.display(), e))?
+        .len();
+    let file = std::fs::File::open(zip_path).map_err(|e| format!("打开 ZIP 文件失败 {}: {}", zip_path.display(), e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("不是有效的 ZIP 文件: {}", e))?;
+
+    let mut file_count = 0;
+    let mut total_uncompressed_bytes = 0u64;
+    let mut infs = Vec::new();
+    let mut warnings = Vec::new();
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("无法读取 ZIP 条目 #{}: {}", i, e))?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        file_count += 1;
+        total_uncompressed_bytes += entry.size();
+        if entry.enclosed_name().is_none() {
+            warnings.push(format!("包含不安全的路径（安装时会被拒绝解压）: {}", name));
+            continue;
+        }
+
+        let is_inf = Path::new(&name)
+            .extension()
+            .map(|ext| ext.eq_ignore_ascii_case("inf"))
+            .unwrap_or(false);
+        if !is_inf {
+            continue;
+        }
+        if entry.size() > MAX_INSPECT_INF_BYTES {
+            warnings.push(format!("INF 过大，跳过解析: {}（{} 字节）", name, entry.size()));
+            continue;
+        }
+        let mut bytes = Vec::with_capacity(entry.size() as usize);
+        if let Err(e) = entry.read_to_end(&mut bytes) {
+            warnings.push(format!("读取 INF 失败: {}（{}）", name, e));
+            continue;
+        }
+        let summary = inf_parse::parse_inf(&inf_parse::decode_inf_bytes(&bytes));
+        infs.push(DriverPackInf {
+            path: name,
+            is_printer_class: summary.is_printer_class(),
+            class: summary.class,
+            driver_version: summary.driver_version,
+            driver_date: summary.driver_date,
+            models: summary.model_names,
+        });
+    }
+    infs.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let mut driver_names: Vec<String> = Vec::new();
+    for inf in infs.iter().filter(|inf| inf.is_printer_class) {
+        for model in &inf.models {
+            if !driver_names.contains(model) {
+                driver_names.push(model.clone());
+            }
+        }
+    }
+    if infs.is_empty() {
+        warnings.push("驱动包中没有 INF 文件".to_string());
+    } else if !infs.iter().any(|inf| inf.is_printer_class) {
+        warnings.push("驱动包中没有打印机类（Class=Printer）INF".to_string());
+    }
+
+    eprintln!(
+        "[DriverPack] inspected source=\"{}\" files={} uncompressed={} infs={} drivers={} sha256={}",
+        source,
+        file_count,
+        total_uncompressed_bytes,
+        infs.len(),
+        driver_names.len(),
+        sha256
+    );
+
+    Ok(DriverPackInspection {
+        source: source.to_string(),
+        sha256,
+        zip_size_bytes,
+        file_count,
+        total_uncompressed_bytes,
+        infs,
+        driver_names,
+        warnings,
+    })
+}
+
+/// 检查驱动包内容：列出 INF、各 INF 声明的型号、文件数与总大小（不解压、不安装）
+///
+/// # 参数
+/// - `zip_path_or_url`: 本地 ZIP 路径或 http/https 下载地址（下载到临时文件，检查后删除）
+///
+/// # 返回
+/// - `Ok(DriverPackInspection)`: 检查结果
+/// - `Err(String)`: 文件不存在、下载失败或不是有效的 ZIP
+pub async fn inspect_driver_pack(zip_path_or_url: &str) -> Result<DriverPackInspection, String> {
+    let source = zip_path_or_url.trim().to_string();
+    if source.is_empty() {
+        return Err("驱动包路径不能为空".to_string());
+    }
+
+    let lower = source.to_ascii_lowercase();
+    if lower.starts_with("http://") || lower.starts_with("https://") {
+        let temp = download_pack_to_temp(&source).await?;
+        tokio::task::spawn_blocking(move || inspect_zip(&source, temp.path()))
+            .await
+            .map_err(|e| format!("驱动包检查任务异常: {}", e))?
+    } else {
+        let path = PathBuf::from(&source);
+        if !path.is_file() {
+            return Err(format!("驱动包不存在或不是文件: {}", source));
+        }
+        tokio::task::spawn_blocking(move || inspect_zip(&source, &path))
+            .await
+            .map_err(|e| format!("驱动包检查任务异常: {}", e))?
+    }
+}
+
+/// 确认（或撤销）在按流量计费网络上下载大驱动包
+///
+/// 安装返回 DOWNLOAD_DEFERRED_METERED 后，前端征得用户同意再调用此命令并重试安装；会话内有效
+pub fn set_metered_download_allowed(allowed: bool) -> Result<(), String> {
+    crate::platform::set_metered_download_allowed(allowed)
+}
+
+/// 校验加密驱动包的密码引用名（与配置 archivePasswordRef 对应）
+fn validate_password_ref(reference: &str) -> Result<&str, String> {
+    let reference = reference.trim();
+    if reference.is_empty() {
+        return Err("密码引用名不能为空".to_string());
+    }
+    if reference.len() > 64 || !reference.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.')) {
+        return Err(format!("密码引用名无效（仅允许字母、数字、- _ .，最长 64 个字符）: {}", reference));
+    }
+    Ok(reference)
+}
+
+/// 保存加密驱动包的解压密码
+///
+/// # 参数
+/// - `reference`: 密码引用名（driverCatalog remote.archivePasswordRef）
+/// - `password`: 解压密码
+pub fn set_driver_archive_password(reference: &str, password: &str) -> Result<(), String> {
+    let reference = validate_password_ref(reference)?;
+    if password.is_empty() {
+        return Err("密码不能为空".to_string());
+    }
+    crate::platform::set_driver_archive_password(reference, password)
+}
+
+/// 删除加密驱动包的解压密码
+///
+/// # 返回
+/// - `Ok(bool)`: 是否删除了已保存的密码
+pub fn clear_driver_archive_password(reference: &str) -> Result<bool, String> {
+    crate::platform::clear_driver_archive_password(validate_password_ref(reference)?)
+}
@@ -0,0 +1,260 @@
+/**
+ * 提权后继续安装服务
+ * 职责：
+ * - 安装仅因未提权失败（WIN_PERMISSION_DENIED，当前进程未提权、非 dryRun）时，把任务状态保存到本地 meta 存储
+ *   （elevation_resume.json）：原安装请求、jobId、失败的步骤（平台层在权限错误处登记）与已完成的步骤；
+ *   同一打印机之后安装成功时清除
+ * - relaunch_elevated：以管理员身份重新启动应用（UAC 提示），用户授权后当前实例走退出流程（等待 / 保存进行中的任务）；
+ *   用户拒绝时返回 ELEVATION_CANCELLED，保存的任务保留
+ * - 以管理员身份启动时自动继续保存的任务：沿用原 jobId（job 日志续写同一文件）与原请求的驱动策略，各步骤按原请求重新执行
+ *   （已下载的驱动包从缓存复用、已存在的端口直接复用）；结果通过 elevation_resume_finished 事件发送
+ * - 保存超过 RESUME_TTL_MS 的任务视为过期，启动时丢弃
+ */
+
+use super::install_history::InstallRecord;
+use super::meta_store::MetaStore;
+use super::shutdown_service::ResumedJob;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 待继续任务存储（schemaVersion=1）
+static ELEVATION_RESUME: MetaStore<ElevationResumeState> = MetaStore::new("elevation_resume", 1);
+
+/// 未提权导致安装失败的错误码
+const PERMISSION_DENIED_CODE: &str = "WIN_PERMISSION_DENIED";
+
+/// 保存的任务有效期（失败后 30 分钟内提权才自动继续）
+const RESUME_TTL_MS: i64 = 30 * 60 * 1000;
+
+/// 启动后等待前端订阅安装进度事件的时间
+const STARTUP_DELAY: Duration = Duration::from_secs(3);
+
+/// 因未提权失败、等待提权后继续的安装任务
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevationResumeJob {
+    pub job_id: String,
+    /// 安装请求中的打印机名称（配置名称）
+    pub printer_name: String,
+    pub path: String,
+    pub driver_key: Option<String>,
+    pub model: Option<String>,
+    pub install_mode: Option<String>,
+    /// 原请求的驱动策略（None 时按配置）
+    #[serde(default)]
+    pub driver_install_policy: Option<String>,
+    pub queue_conflict: Option<String>,
+    /// 失败的步骤（平台层没有登记失败步骤时为 "preflight"）
+    pub failed_step: String,
+    /// 失败前已完成的步骤（按完成顺序）
+    pub completed_steps: Vec<String>,
+    pub failed_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ElevationResumeState {
+    pub jobs: Vec<ElevationResumeJob>,
+}
+
+/// 由安装记录得出需要保存的任务（只保存真实安装中因未提权失败的任务）
+fn resume_job_from_record(
+    printer_name: &str,
+    path: &str,
+    model: Option<&str>,
+    driver_install_policy: Option<&str>,
+    queue_conflict: Option<&str>,
+    record: &InstallRecord,
+) -> Option<ElevationResumeJob> {
+    if record.success || record.dry_run || record.error_code.as_deref() != Some(PERMISSION_DENIED_CODE) {
+        return None;
+    }
+    let failed_step = record.failed_step.clone().unwrap_or_else(|| "preflight".to_string());
+    let steps: Vec<String> = record
+        .step_durations
+        .iter()
+        .map(|s| s.step.clone())
+        .filter(|step| *step != failed_step)
+        .collect();
+    Some(ElevationResumeJob {
+        job_id: record.job_id.clone(),
+        printer_name: printer_name.to_string(),
+        path: path.to_string(),
+        driver_key: record.driver_key.clone(),
+        model: model.map(str::to_string),
+        install_mode: record.install_mode.clone(),
+        driver_install_policy: driver_install_policy.map(str::to_string),
+        queue_conflict: queue_conflict.map(str::to_string),
+        failed_step,
+        completed_steps: steps,
+        failed_at: record.finished_at,
+    })
+}
+
+/// 按安装结果保存 / 清除待继续的任务
+///
+/// # 参数
+/// - `printer_name` / `path` / `model` / `driver_install_policy` / `queue_conflict`: 原安装请求（打印机名称为配置名称）
+/// - `record`: 本次安装的记录
+pub async fn record_install(
+    printer_name: &str,
+    path: &str,
+    model: Option<&str>,
+    driver_install_policy: Option<&str>,
+    queue_conflict: Option<&str>,
+    record: &InstallRecord,
+) {
+    let pending = resume_job_from_record(printer_name, path, model, driver_install_policy, queue_conflict, record)
+        .filter(|_| !crate::platform::is_elevated());
+    let installed = record.success && !record.dry_run;
+    if pending.is_none() && !installed {
+        return;
+    }
+    let (name, path) = (printer_name.to_string(), path.to_string());
+    let saved = tokio::task::spawn_blocking(move || {
+        ELEVATION_RESUME.update(|state| {
+            state.jobs.retain(|job| job.printer_name != name && !job.path.eq_ignore_ascii_case(&path));
+            if let Some(job) = pending {
+                eprintln!(
+                    "[ElevationResume] saved jobId={} printer=\"{}\" failed_step={}",
+                    job.job_id, job.printer_name, job.failed_step
+                );
+                state.jobs.push(job);
+            }
+        })
+    })
+    .await;
+    match saved {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[ElevationResume] 保存待继续任务失败: {}", e),
+        Err(e) => eprintln!("[ElevationResume] 保存待继续任务任务异常: {}", e),
+    }
+}
+
+/// 等待提权后继续的任务
+pub async fn get_pending_jobs() -> Result<Vec<ElevationResumeJob>, String> {
+    tokio::task::spawn_blocking(|| ELEVATION_RESUME.load().map(|state| state.jobs))
+        .await
+        .map_err(|e| format!("读取待继续任务异常: {}", e))?
+}
+
+/// 以管理员身份重新启动应用（用户授权后当前实例退出）
+///
+/// # 返回
+/// - `Ok(())`: 新实例已启动，当前实例正在退出
+/// - `Err(String)`: 已是管理员身份、用户拒绝授权（ELEVATION_CANCELLED）或启动失败
+pub async fn relaunch_elevated(app: tauri::AppHandle) -> Result<(), String> {
+    if crate::platform::is_elevated() {
+        return Err("应用已以管理员身份运行".to_string());
+    }
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    tokio::task::spawn_blocking(move || {
+        let args: Vec<&str> = args.iter().map(String::as_str).collect();
+        crate::platform::relaunch_elevated(&args)
+    })
+    .await
+    .map_err(|e| format!("以管理员身份重新启动任务异常: {}", e))??;
+    eprintln!("[ElevationResume] elevated instance started, shutting down current instance");
+    tauri::async_runtime::spawn(super::shutdown_service::shutdown(app, false));
+    Ok(())
+}
+
+/// 以管理员身份启动时继续保存的任务（未提权或没有保存的任务时直接返回）
+pub async fn resume_on_startup(app: tauri::AppHandle) {
+    if !crate::platform::is_elevated() {
+        return;
+    }
+    let taken = tokio::task::spawn_blocking(|| ELEVATION_RESUME.update(|state| std::mem::take(&mut state.jobs))).await;
+    let jobs = match taken {
+        Ok(Ok(jobs)) => jobs,
+        Ok(Err(e)) => return eprintln!("[ElevationResume] 读取待继续任务失败: {}", e),
+        Err(e) => return eprintln!("[ElevationResume] 读取待继续任务任务异常: {}", e),
+    };
+    let now = chrono::Utc::now().timestamp_millis();
+    let (jobs, expired): (Vec<_>, Vec<_>) = jobs.into_iter().partition(|job| now - job.failed_at <= RESUME_TTL_MS);
+    if !expired.is_empty() {
+        eprintln!("[ElevationResume] dropped expired jobs={}", expired.len());
+    }
+    if jobs.is_empty() {
+        return;
+    }
+
+    tokio::time::sleep(STARTUP_DELAY).await;
+    let mut results = Vec::new();
+    for job in jobs {
+        eprintln!(
+            "[ElevationResume] resuming jobId={} printer=\"{}\" failed_step={}",
+            job.job_id, job.printer_name, job.failed_step
+        );
+        crate::platform::set_install_resume_hint(
+            &job.path,
+            Some(crate::platform::InstallResumeHint {
+                job_id: job.job_id.clone(),
+                failed_step: job.failed_step.clone(),
+                completed_steps: job.completed_steps.clone(),
+            }),
+        );
+        let result = crate::install_printer(
+            app.clone(),
+            job.printer_name.clone(),
+            job.path.clone(),
+            job.driver_key.clone(),
+            None,
+            job.model.clone(),
+            job.driver_install_policy.clone(),
+            job.install_mode.clone(),
+            Some(false),
+            job.queue_conflict.clone(),
+        )
+        .await;
+        // 安装在进入平台层之前就返回（操作锁、配置校验等）时 hint 未被使用，清除以免影响之后的安装
+        crate::platform::set_install_resume_hint(&job.path, None);
+        results.push(match result {
+            Ok(r) => ResumedJob { printer_name: job.printer_name, success: r.success, job_id: Some(r.job_id), message: r.message },
+            Err(e) => ResumedJob { printer_name: job.printer_name, success: false, job_id: Some(job.job_id), message: e },
+        });
+    }
+    eprintln!(
+        "[ElevationResume] resumed jobs={} succeeded={}",
+        results.len(),
+        results.iter().filter(|r| r.success).count()
+    );
+    super::events::emit_elevation_resume_finished(&app, &results);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use super::super::install_history::StepDuration;
+
+    #[test]
+    fn saves_only_jobs_that_failed_for_missing_elevation() {
+        let step = |id: &str| StepDuration { step: id.to_string(), duration_ms: 10 };
+        let mut record = InstallRecord {
+            job_id: "job_1".to_string(),
+            printer_name: "财务部 HP".to_string(),
+            driver_key: Some("HP_M227_WIN_X64".to_string()),
+            install_mode: Some("package".to_string()),
+            message: "需要管理员权限".to_string(),
+            started_at: 1,
+            finished_at: 2,
+            error_code: Some(PERMISSION_DENIED_CODE.to_string()),
+            step_durations: vec![step("driver.download"), step("driver.stageDriver"), step("driver.extract")],
+            failed_step: Some("driver.stageDriver".to_string()),
+            ..Default::default()
+        };
+        let job = resume_job_from_record("财务部 HP", "10.0.0.9", None, Some("always"), Some("fail"), &record).unwrap();
+        assert_eq!(job.failed_step, "driver.stageDriver");
+        assert_eq!(job.completed_steps, vec!["driver.download", "driver.extract"]);
+        assert_eq!(job.driver_install_policy.as_deref(), Some("always"));
+        assert_eq!((job.job_id.as_str(), job.failed_at), ("job_1", 2));
+
+        record.failed_step = None;
+        assert_eq!(resume_job_from_record("财务部 HP", "10.0.0.9", None, None, None, &record).unwrap().failed_step, "preflight");
+        record.error_code = Some("WIN_DRIVER_NOT_FOUND".to_string());
+        assert!(resume_job_from_record("财务部 HP", "10.0.0.9", None, None, None, &record).is_none());
+        record.error_code = Some(PERMISSION_DENIED_CODE.to_string());
+        record.dry_run = true;
+        assert!(resume_job_from_record("财务部 HP", "10.0.0.9", None, None, None, &record).is_none());
+    }
+}
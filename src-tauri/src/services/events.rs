@@ -4,6 +4,11 @@
  * - 封装事件发送逻辑
  * - config_updated
  * - config_refresh_failed
+ * - settings_changed
+ * - printer_watch_report
+ * - shutdown_requested
+ * - update_download_progress
+ * - elevation_resume_finished
  */
 
 use crate::*;
@@ -33,3 +38,33 @@ pub fn emit_config_refresh_failed(app: &tauri::AppHandle, error: &str) {
         eprintln!("[WARN] 发送 config_refresh_failed 事件失败: {}", e);
     }
 }
+
+pub fn emit_settings_changed(app: &tauri::AppHandle, update: &super::user_settings::SettingsUpdate) {
+    if let Err(e) = app.emit_all("settings_changed", update) {
+        eprintln!("[WARN] 发送 settings_changed 事件失败: {}", e);
+    }
+}
+
+pub fn emit_printer_watch_report(app: &tauri::AppHandle, report: &super::printer_watch::WatchCycleReport) {
+    if let Err(e) = app.emit_all("printer_watch_report", report) {
+        eprintln!("[WARN] 发送 printer_watch_report 事件失败: {}", e);
+    }
+}
+
+pub fn emit_shutdown_requested(app: &tauri::AppHandle, request: &super::shutdown_service::ShutdownRequest) {
+    if let Err(e) = app.emit_all("shutdown_requested", request) {
+        eprintln!("[WARN] 发送 shutdown_requested 事件失败: {}", e);
+    }
+}
+
+pub fn emit_update_download_progress(app: &tauri::AppHandle, progress: &super::update_download::UpdateDownloadProgress) {
+    if let Err(e) = app.emit_all("update_download_progress", progress) {
+        eprintln!("[WARN] 发送 update_download_progress 事件失败: {}", e);
+    }
+}
+
+pub fn emit_elevation_resume_finished(app: &tauri::AppHandle, results: &[super::shutdown_service::ResumedJob]) {
+    if let Err(e) = app.emit_all("elevation_resume_finished", results) {
+        eprintln!("[WARN] 发送 elevation_resume_finished 事件失败: {}", e);
+    }
+}
@@ -0,0 +1,381 @@
+/**
+ * 安装结果回传服务（installFeedback）
+ * 职责：
+ * - 仅在 printer_config.json 的 installFeedback.enabled=true 时运行（默认不回传任何信息）
+ * - 每次真实安装结束后，按 城市 / 区域 / 配置中的打印机名称 累计安装次数、成功 / 失败次数与失败错误码，
+ *   保存在本地 meta 存储（install_feedback.json）；不在当前配置中的打印机不统计
+ * - 累计安装次数达到 batchSize 时自动回传一批；flush_feedback 立即回传；启动时回传上次遗留的统计
+ * - 回传内容只有汇总计数与应用 / 配置版本，不含机器指纹、主机名、用户名与安装消息
+ * - 回传失败时统计放回本地存储，下次回传时一并发送
+ */
+
+use super::install_history::InstallRecord;
+use super::meta_store::MetaStore;
+use crate::PrinterConfig;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// 回传统计存储（schemaVersion=1）
+static FEEDBACK_STORE: MetaStore<FeedbackState> = MetaStore::new("install_feedback", 1);
+
+/// 默认批量大小（累计安装次数）
+const DEFAULT_BATCH_SIZE: u32 = 20;
+
+/// 批量大小上限
+const MAX_BATCH_SIZE: u32 = 1000;
+
+/// 本地最多累计的条目数（超出后新打印机的统计丢弃，已有条目照常累计）
+const MAX_ENTRIES: usize = 500;
+
+/// 单次回传请求超时
+const REPORT_TIMEOUT_SECS: u64 = 10;
+
+/// 单台打印机的安装统计
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackEntry {
+    pub city_id: String,
+    pub area_name: String,
+    /// 配置中的打印机名称
+    pub printer_key: String,
+    pub driver_key: Option<String>,
+    pub installs: u32,
+    pub succeeded: u32,
+    pub failed: u32,
+    /// 失败错误码 → 次数（没有错误码的失败记为 UNKNOWN）
+    #[serde(default)]
+    pub error_codes: BTreeMap<String, u32>,
+}
+
+/// 本地累计状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackState {
+    pub entries: Vec<FeedbackEntry>,
+    /// 尚未回传的安装次数
+    pub pending_installs: u32,
+    /// 尚未回传部分的最早安装时间
+    pub period_start: Option<i64>,
+    pub last_flush_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// 回传内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct FeedbackBatch<'a> {
+    app_version: &'static str,
+    config_version: Option<&'a str>,
+    platform: &'static str,
+    period_start: Option<i64>,
+    period_end: i64,
+    entries: &'a [FeedbackEntry],
+}
+
+/// flush_feedback 结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedbackFlushResult {
+    /// 是否发送了请求（没有待回传的统计时为 false）
+    pub sent: bool,
+    pub entries: usize,
+    pub installs: u32,
+    pub message: String,
+}
+
+/// 校验 installFeedback 配置（仅 enabled=true 时调用）
+pub fn validate_config(config: &crate::InstallFeedbackConfig) -> Result<(), String> {
+    let valid = url::Url::parse(config.endpoint.trim())
+        .map(|u| u.scheme() == "http" || u.scheme() == "https")
+        .unwrap_or(false);
+    if !valid {
+        return Err(format!("endpoint=\"{}\" 无效。开启回传时必须填写 http/https 地址", config.endpoint));
+    }
+    if let Some(size) = config.batch_size.filter(|s| !(1..=MAX_BATCH_SIZE).contains(s)) {
+        return Err(format!("batchSize={} 无效（1-{}）", size, MAX_BATCH_SIZE));
+    }
+    Ok(())
+}
+
+/// 按名称查找配置中的打印机所在的城市 / 区域
+fn locate_printer(config: &PrinterConfig, name: &str) -> Option<(String, String)> {
+    config.cities.iter().find_map(|city| {
+        city.areas
+            .iter()
+            .find(|area| area.printers.iter().any(|p| p.name == name))
+            .map(|area| (city.city_id.clone(), area.area_name.clone()))
+    })
+}
+
+/// 把一次安装累计到状态中
+///
+/// # 返回
+/// - `false`: 条目数已达上限，本次未统计
+fn add_record(state: &mut FeedbackState, city_id: &str, area_name: &str, printer_key: &str, record: &InstallRecord) -> bool {
+    let index = state
+        .entries
+        .iter()
+        .position(|e| e.city_id == city_id && e.area_name == area_name && e.printer_key == printer_key);
+    let index = match index {
+        Some(i) => i,
+        None if state.entries.len() >= MAX_ENTRIES => return false,
+        None => {
+            state.entries.push(FeedbackEntry {
+                city_id: city_id.to_string(),
+                area_name: area_name.to_string(),
+                printer_key: printer_key.to_string(),
+                ..Default::default()
+            });
+            state.entries.len() - 1
+        }
+    };
+    let entry = &mut state.entries[index];
+    entry.installs += 1;
+    if record.driver_key.is_some() {
+        entry.driver_key = record.driver_key.clone();
+    }
+    if record.success {
+        entry.succeeded += 1;
+    } else {
+        entry.failed += 1;
+        let code = record.error_code.clone().unwrap_or_else(|| "UNKNOWN".to_string());
+        *entry.error_codes.entry(code).or_default() += 1;
+    }
+    state.pending_installs += 1;
+    state.period_start = Some(state.period_start.map_or(record.finished_at, |start| start.min(record.finished_at)));
+    true
+}
+
+/// 把回传失败的统计放回状态（与期间新增的统计合并）
+fn merge_back(state: &mut FeedbackState, entries: Vec<FeedbackEntry>, installs: u32, period_start: Option<i64>) {
+    for returned in entries {
+        match state
+            .entries
+            .iter_mut()
+            .find(|e| e.city_id == returned.city_id && e.area_name == returned.area_name && e.printer_key == returned.printer_key)
+        {
+            Some(entry) => {
+                entry.installs += returned.installs;
+                entry.succeeded += returned.succeeded;
+                entry.failed += returned.failed;
+                for (code, count) in returned.error_codes {
+                    *entry.error_codes.entry(code).or_default() += count;
+                }
+                if entry.driver_key.is_none() {
+                    entry.driver_key = returned.driver_key;
+                }
+            }
+            None => state.entries.push(returned),
+        }
+    }
+    state.pending_installs += installs;
+    state.period_start = match (state.period_start, period_start) {
+        (Some(a), Some(b)) => Some(a.min(b)),
+        (a, b) => a.or(b),
+    };
+}
+
+/// 当前开启的回传配置（未开启时为 None）
+fn enabled_config(config: &PrinterConfig) -> Option<crate::InstallFeedbackConfig> {
+    config.install_feedback.clone().filter(|f| f.enabled && !f.endpoint.trim().is_empty())
+}
+
+async fn load_config() -> Result<PrinterConfig, String> {
+    tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))?
+        .map(|(config, _)| config)
+}
+
+/// 发送一批统计
+async fn send_batch(endpoint: &str, batch: &FeedbackBatch<'_>) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REPORT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+    let response = client
+        .post(endpoint)
+        .json(batch)
+        .send()
+        .await
+        .map_err(|e| format!("网络请求失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("服务器返回错误: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// 取出全部待回传统计并发送（失败时放回本地存储）
+async fn flush_with(config: &PrinterConfig, feedback: &crate::InstallFeedbackConfig) -> Result<FeedbackFlushResult, String> {
+    let taken = tokio::task::spawn_blocking(|| {
+        FEEDBACK_STORE.update(|state| {
+            let installs = std::mem::take(&mut state.pending_installs);
+            (std::mem::take(&mut state.entries), installs, state.period_start.take())
+        })
+    })
+    .await
+    .map_err(|e| format!("读取回传统计任务异常: {}", e))??;
+    let (entries, installs, period_start) = taken;
+    if entries.is_empty() {
+        return Ok(FeedbackFlushResult { sent: false, entries: 0, installs: 0, message: "没有待回传的安装统计".to_string() });
+    }
+
+    let batch = FeedbackBatch {
+        app_version: env!("CARGO_PKG_VERSION"),
+        config_version: config.version.as_deref(),
+        platform: std::env::consts::OS,
+        period_start,
+        period_end: chrono::Utc::now().timestamp_millis(),
+        entries: &entries,
+    };
+    let sent = send_batch(feedback.endpoint.trim(), &batch).await;
+    let now = chrono::Utc::now().timestamp_millis();
+    let count = entries.len();
+    let failure = sent.as_ref().err().cloned();
+    let saved = tokio::task::spawn_blocking(move || {
+        FEEDBACK_STORE.update(move |state| {
+            match failure {
+                Some(e) => {
+                    merge_back(state, entries, installs, period_start);
+                    state.last_error = Some(e);
+                }
+                None => {
+                    state.last_flush_at = Some(now);
+                    state.last_error = None;
+                }
+            }
+        })
+    })
+    .await;
+    match saved {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[Feedback] 保存回传状态失败: {}", e),
+        Err(e) => eprintln!("[Feedback] 保存回传状态任务异常: {}", e),
+    }
+
+    sent.map_err(|e| {
+        eprintln!("[Feedback] 回传失败，统计保留到下次: {}", e);
+        format!("回传安装统计失败（统计已保留，下次一并回传）: {}", e)
+    })?;
+    eprintln!("[Feedback] 回传成功 entries={} installs={}", count, installs);
+    Ok(FeedbackFlushResult {
+        sent: true,
+        entries: count,
+        installs,
+        message: format!("已回传 {} 台打印机的 {} 次安装统计", count, installs),
+    })
+}
+
+/// 累计一次安装结果（dryRun、未开启回传或打印机不在配置中时忽略；错误只记录日志）
+pub async fn record_install(record: &InstallRecord) {
+    if record.dry_run {
+        return;
+    }
+    let config = match load_config().await {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[Feedback] 读取本地配置失败，跳过统计: {}", e);
+            return;
+        }
+    };
+    let Some(feedback) = enabled_config(&config) else {
+        return;
+    };
+    let printer_key = record.display_name.clone().unwrap_or_else(|| record.printer_name.clone());
+    let Some((city_id, area_name)) = locate_printer(&config, &printer_key) else {
+        return;
+    };
+
+    let record = record.clone();
+    let added = tokio::task::spawn_blocking(move || {
+        FEEDBACK_STORE.update(|state| {
+            add_record(state, &city_id, &area_name, &printer_key, &record).then_some(state.pending_installs)
+        })
+    })
+    .await;
+    let pending = match added {
+        Ok(Ok(Some(pending))) => pending,
+        Ok(Ok(None)) => {
+            eprintln!("[Feedback] 本地统计条目已达上限 {}，新打印机不再统计", MAX_ENTRIES);
+            return;
+        }
+        Ok(Err(e)) => {
+            eprintln!("[Feedback] 保存安装统计失败: {}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("[Feedback] 保存安装统计任务异常: {}", e);
+            return;
+        }
+    };
+    if pending >= feedback.batch_size.unwrap_or(DEFAULT_BATCH_SIZE) {
+        tauri::async_runtime::spawn(async move {
+            let _ = flush_with(&config, &feedback).await;
+        });
+    }
+}
+
+/// 立即回传本地累计的安装统计
+///
+/// # 返回
+/// - `Ok(FeedbackFlushResult)`: 回传结果（没有待回传统计时 sent=false）
+/// - `Err(String)`: 未开启 installFeedback、读取配置失败或回传失败（统计保留）
+pub async fn flush_feedback() -> Result<FeedbackFlushResult, String> {
+    let config = load_config().await?;
+    let feedback = enabled_config(&config).ok_or_else(|| "配置未开启安装结果回传（installFeedback.enabled）".to_string())?;
+    flush_with(&config, &feedback).await
+}
+
+/// 启动时回传上次遗留的统计（在后台任务中调用，所有错误只记录日志）
+pub async fn flush_on_startup() {
+    let Ok(config) = load_config().await else {
+        return;
+    };
+    let Some(feedback) = enabled_config(&config) else {
+        return;
+    };
+    if let Err(e) = flush_with(&config, &feedback).await {
+        eprintln!("[Feedback] 启动时回传失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(success: bool, code: Option<&str>, finished_at: i64) -> InstallRecord {
+        InstallRecord {
+            job_id: format!("job_{}", finished_at),
+            printer_name: "HR 彩色".to_string(),
+            driver_key: Some("HP_M227_WIN_X64".to_string()),
+            success,
+            started_at: finished_at - 1000,
+            finished_at,
+            error_code: code.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn aggregates_and_merges_back_failed_batches() {
+        let mut state = FeedbackState::default();
+        assert!(add_record(&mut state, "sh", "3F", "HR 彩色", &record(true, None, 300)));
+        assert!(add_record(&mut state, "sh", "3F", "HR 彩色", &record(false, Some("WIN_DRIVER_NOT_FOUND"), 200)));
+        assert!(add_record(&mut state, "sh", "3F", "HR 彩色", &record(false, None, 400)));
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!(state.pending_installs, 3);
+        assert_eq!(state.period_start, Some(200));
+        let entry = &state.entries[0];
+        assert_eq!((entry.installs, entry.succeeded, entry.failed), (3, 1, 2));
+        assert_eq!(entry.error_codes.get("UNKNOWN"), Some(&1));
+
+        let sent = std::mem::take(&mut state.entries);
+        state.pending_installs = 0;
+        state.period_start = None;
+        assert!(add_record(&mut state, "sh", "3F", "HR 彩色", &record(true, None, 500)));
+        merge_back(&mut state, sent, 3, Some(200));
+        assert_eq!(state.entries.len(), 1);
+        assert_eq!((state.entries[0].installs, state.pending_installs, state.period_start), (4, 4, Some(200)));
+    }
+}
@@ -0,0 +1,221 @@
+/**
+ * 机器登记上报服务（企业 fleet 看板）
+ * 职责：
+ * - 仅在 printer_config.json 的 fleetReporting.enabled=true 时运行（默认不上报任何信息）
+ * - 启动时上报：机器指纹（主机名 + MAC 的 SHA256，不上传原始值）、应用版本、配置版本、系统版本
+ * - 本地缓存最近一次成功上报的内容摘要，内容不变且未超过 intervalHours 时跳过
+ * - 网络失败按退避间隔重试，最终失败记录到本地状态文件，下次启动再报
+ */
+
+use super::meta_store::MetaStore;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::Duration;
+
+/// 上报状态存储（schemaVersion=1）
+static FLEET_STATE: MetaStore<FleetRegistrationState> = MetaStore::new("fleet_registration", 1);
+
+/// 内容不变时的默认重报间隔（小时）
+const DEFAULT_INTERVAL_HOURS: u64 = 24;
+
+/// 单次上报请求超时
+const REPORT_TIMEOUT_SECS: u64 = 10;
+
+/// 失败后的重试等待时间（依次使用，用完即放弃）
+const RETRY_DELAYS_SECS: [u64; 3] = [5, 30, 120];
+
+/// 本地上报状态
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetRegistrationState {
+    pub fingerprint: Option<String>,
+    /// 最近一次成功上报内容的摘要（不含时间戳）
+    pub last_payload_hash: Option<String>,
+    pub last_success_at: Option<i64>,
+    pub last_attempt_at: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+/// 上报内容
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FleetRegistration {
+    pub fingerprint: String,
+    pub app_version: String,
+    pub config_version: Option<String>,
+    pub platform: String,
+    pub arch: String,
+    pub os_version: String,
+    pub os_build: Option<String>,
+}
+
+/// 计算机器指纹：SHA256(小写主机名 | 排序后的首个 MAC)
+///
+/// 只取排序后的首个物理 MAC，插拔 USB 网卡等不改变已有网卡时指纹保持稳定
+fn machine_fingerprint(identity: &crate::platform::MachineIdentity) -> String {
+    let mut macs: Vec<String> = identity
+        .mac_addresses
+        .iter()
+        .map(|m| m.trim().to_ascii_uppercase().replace('-', ":"))
+        .filter(|m| !m.is_empty())
+        .collect();
+    macs.sort();
+    let source = format!(
+        "{}|{}",
+        identity.hostname.trim().to_lowercase(),
+        macs.first().map(|m| m.as_str()).unwrap_or_default()
+    );
+    format!("{:x}", Sha256::digest(source.as_bytes()))
+}
+
+/// 计算本机机器指纹（阻塞调用：Windows 下会启动 PowerShell；update_rollout 灰度分桶共用）
+pub fn current_machine_fingerprint() -> Result<String, String> {
+    crate::platform::get_machine_identity().map(|identity| machine_fingerprint(&identity))
+}
+
+/// 收集上报内容（阻塞调用：Windows 下会启动 PowerShell）
+fn build_registration(config: &crate::PrinterConfig) -> Result<FleetRegistration, String> {
+    let identity = crate::platform::get_machine_identity()?;
+    let system = crate::get_system_info()?;
+    Ok(FleetRegistration {
+        fingerprint: machine_fingerprint(&identity),
+        app_version: system.app_version,
+        config_version: config.version.clone(),
+        platform: system.platform,
+        arch: system.arch,
+        os_version: system.os_display.unwrap_or(system.os_version),
+        os_build: system.build_number.or(system.kernel_version),
+    })
+}
+
+/// 发送一次上报
+async fn send_registration(endpoint: &str, registration: &FleetRegistration) -> Result<(), String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(REPORT_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+
+    let mut body = serde_json::to_value(registration)
+        .map_err(|e| format!("序列化上报内容失败: {}", e))?;
+    body["reportedAt"] = serde_json::json!(chrono::Utc::now().to_rfc3339());
+
+    let response = client
+        .post(endpoint)
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| format!("网络请求失败: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("服务器返回错误: {}", response.status()));
+    }
+    Ok(())
+}
+
+/// 保存上报状态（阻塞调用，失败只记录日志）
+fn update_state(f: impl FnOnce(&mut FleetRegistrationState)) {
+    if let Err(e) = FLEET_STATE.update(f) {
+        eprintln!("[Fleet] 保存上报状态失败: {}", e);
+    }
+}
+
+/// 启动时登记上报（在后台任务中调用，所有错误只记录日志）
+pub async fn register_on_startup() {
+    let (config, _) = match tokio::task::spawn_blocking(crate::load_local_config).await {
+        Ok(Ok(loaded)) => loaded,
+        Ok(Err(e)) => {
+            eprintln!("[Fleet] 读取本地配置失败，跳过上报: {}", e);
+            return;
+        }
+        Err(e) => {
+            eprintln!("[Fleet] 读取本地配置任务异常: {}", e);
+            return;
+        }
+    };
+
+    let fleet = match config.fleet_reporting.clone().filter(|f| f.enabled) {
+        Some(f) => f,
+        None => return,
+    };
+    let endpoint = fleet.endpoint.trim().to_string();
+    let interval_ms = fleet.interval_hours.unwrap_or(DEFAULT_INTERVAL_HOURS) as i64 * 3600 * 1000;
+
+    let prepared = tokio::task::spawn_blocking(move || -> Result<_, String> {
+        let registration = build_registration(&config)?;
+        let state = FLEET_STATE.load()?;
+        Ok((registration, state))
+    })
+    .await
+    .map_err(|e| format!("收集上报信息任务异常: {}", e))
+    .and_then(|r| r);
+    let (registration, state) = match prepared {
+        Ok(p) => p,
+        Err(e) => {
+            eprintln!("[Fleet] 收集上报信息失败: {}", e);
+            return;
+        }
+    };
+
+    let payload_hash = serde_json::to_vec(&registration)
+        .map(|bytes| format!("{:x}", Sha256::digest(&bytes)))
+        .unwrap_or_default();
+    let now = chrono::Utc::now().timestamp_millis();
+    let recently_reported = state.last_payload_hash.as_deref() == Some(payload_hash.as_str())
+        && state.last_success_at.map(|t| now - t < interval_ms).unwrap_or(false);
+    if recently_reported {
+        eprintln!("[Fleet] 上报内容未变化且未超过 {}ms，跳过", interval_ms);
+        return;
+    }
+
+    let mut attempt = 0;
+    let result = loop {
+        match send_registration(&endpoint, &registration).await {
+            Ok(()) => break Ok(()),
+            Err(e) if attempt < RETRY_DELAYS_SECS.len() => {
+                eprintln!(
+                    "[Fleet] 上报失败（第 {} 次），{}s 后重试: {}",
+                    attempt + 1,
+                    RETRY_DELAYS_SECS[attempt],
+                    e
+                );
+                tokio::time::sleep(Duration::from_secs(RETRY_DELAYS_SECS[attempt])).await;
+                attempt += 1;
+            }
+            Err(e) => break Err(e),
+        }
+    };
+
+    let fingerprint = registration.fingerprint.clone();
+    let finished_at = chrono::Utc::now().timestamp_millis();
+    match result {
+        Ok(()) => {
+            eprintln!(
+                "[Fleet] 上报成功 fingerprint={} app_version={} config_version={:?}",
+                fingerprint, registration.app_version, registration.config_version
+            );
+            tokio::task::spawn_blocking(move || {
+                update_state(move |state| {
+                    state.fingerprint = Some(fingerprint);
+                    state.last_payload_hash = Some(payload_hash);
+                    state.last_success_at = Some(finished_at);
+                    state.last_attempt_at = Some(finished_at);
+                    state.last_error = None;
+                })
+            })
+            .await
+            .ok();
+        }
+        Err(e) => {
+            eprintln!("[Fleet] 上报最终失败，下次启动重试: {}", e);
+            tokio::task::spawn_blocking(move || {
+                update_state(move |state| {
+                    state.fingerprint = Some(fingerprint);
+                    state.last_attempt_at = Some(finished_at);
+                    state.last_error = Some(e);
+                })
+            })
+            .await
+            .ok();
+        }
+    }
+}
@@ -4,9 +4,11 @@
  * - 集中管理本地路径获取
  * - Windows: exe_dir
  * - macOS: app_config_dir
+ * - 支持入口 reveal_path：在资源管理器 / 访达中打开配置、驱动、日志、历史所在位置
  */
 
 use crate::*;
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::PathBuf;
 
@@ -109,3 +111,232 @@ pub fn get_seed_config_path(app: &tauri::AppHandle) -> Option<PathBuf> {
     eprintln!("[CONFIG_SEED] 未找到 seed 配置文件");
     None
 }
+
+// 获取本地状态（meta）目录（不依赖 AppHandle，后台任务也可调用）
+// Windows: %LOCALAPPDATA%\ePrinty\meta
+// macOS: ~/Library/Application Support/ePrinty/meta
+// 其他平台: ~/.local/share/ePrinty/meta
+pub fn get_meta_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    let base = std::env::var("LOCALAPPDATA")
+        .map(PathBuf::from)
+        .map_err(|e| format!("无法获取 LOCALAPPDATA 环境变量: {}", e))?;
+
+    #[cfg(target_os = "macos")]
+    let base = std::env::var("HOME")
+        .map(|home| PathBuf::from(home).join("Library").join("Application Support"))
+        .map_err(|e| format!("无法获取 HOME 环境变量: {}", e))?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let base = std::env::var("HOME")
+        .map(|home| PathBuf::from(home).join(".local").join("share"))
+        .map_err(|e| format!("无法获取 HOME 环境变量: {}", e))?;
+
+    let meta_dir = base.join("ePrinty").join("meta");
+    fs::create_dir_all(&meta_dir)
+        .map_err(|e| format!("创建 meta 目录失败: {}", e))?;
+
+    Ok(meta_dir)
+}
+
+// 获取打印机图片缓存目录（与 meta 目录同级）
+// Windows: %LOCALAPPDATA%\ePrinty\images
+// macOS: ~/Library/Application Support/ePrinty/images
+// 其他平台: ~/.local/share/ePrinty/images
+pub fn get_image_cache_dir() -> Result<PathBuf, String> {
+    let meta_dir = get_meta_dir()?;
+    let image_dir = meta_dir
+        .parent()
+        .map(|base| base.join("images"))
+        .ok_or_else(|| "无法获取图片缓存目录".to_string())?;
+    fs::create_dir_all(&image_dir)
+        .map_err(|e| format!("创建图片缓存目录失败: {}", e))?;
+
+    Ok(image_dir)
+}
+
+// 获取日志目录（与平台日志模块保持一致）
+// Windows: %LOCALAPPDATA%\ePrinty\logs
+// macOS: ~/Library/Logs/ePrinty
+// 其他平台: ~/.local/share/ePrinty/logs
+pub fn get_logs_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    let logs_dir = std::env::var("LOCALAPPDATA")
+        .map(|base| PathBuf::from(base).join("ePrinty").join("logs"))
+        .map_err(|e| format!("无法获取 LOCALAPPDATA 环境变量: {}", e))?;
+
+    #[cfg(target_os = "macos")]
+    let logs_dir = std::env::var("HOME")
+        .map(|home| PathBuf::from(home).join("Library").join("Logs").join("ePrinty"))
+        .map_err(|e| format!("无法获取 HOME 环境变量: {}", e))?;
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    let logs_dir = std::env::var("HOME")
+        .map(|home| PathBuf::from(home).join(".local").join("share").join("ePrinty").join("logs"))
+        .map_err(|e| format!("无法获取 HOME 环境变量: {}", e))?;
+
+    Ok(logs_dir)
+}
+
+// 获取应用在安装目录以外创建的数据根目录（不创建目录，卸载清理使用）
+// Windows: %LOCALAPPDATA%\ePrinty（meta / logs / images / jobs 等）、%LOCALAPPDATA%\<bundle identifier>（WebView2 用户数据）、
+//          %ProgramData%\ePrinty（委托安装）
+// macOS: ~/Library/Application Support/ePrinty、~/Library/Logs/ePrinty、应用配置目录
+// 其他平台: ~/.local/share/ePrinty
+pub fn get_app_data_roots(app: &tauri::AppHandle) -> Vec<PathBuf> {
+    #[cfg(target_os = "windows")]
+    {
+        let mut roots: Vec<PathBuf> = ["LOCALAPPDATA", "ProgramData"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .map(|base| PathBuf::from(base).join("ePrinty"))
+            .collect();
+        if let Ok(local) = std::env::var("LOCALAPPDATA") {
+            roots.push(PathBuf::from(local).join(&app.config().tauri.bundle.identifier));
+        }
+        roots
+    }
+
+    #[cfg(target_os = "macos")]
+    {
+        let mut roots: Vec<PathBuf> = std::env::var("HOME")
+            .map(|home| {
+                let library = PathBuf::from(home).join("Library");
+                vec![library.join("Application Support").join("ePrinty"), library.join("Logs").join("ePrinty")]
+            })
+            .unwrap_or_default();
+        roots.extend(tauri::api::path::app_config_dir(&app.config()));
+        roots
+    }
+
+    #[cfg(not(any(target_os = "windows", target_os = "macos")))]
+    {
+        let _ = app;
+        std::env::var("HOME")
+            .map(|home| vec![PathBuf::from(home).join(".local").join("share").join("ePrinty")])
+            .unwrap_or_default()
+    }
+}
+
+// 获取委托安装的请求 / 结果交换目录（机器级，普通用户进程与管理员计划任务共用）
+// Windows: %ProgramData%\ePrinty\delegated
+// 其他平台: 不支持委托安装
+pub fn get_delegated_install_dir() -> Result<PathBuf, String> {
+    #[cfg(target_os = "windows")]
+    {
+        let dir = std::env::var("ProgramData")
+            .map(|base| PathBuf::from(base).join("ePrinty").join("delegated"))
+            .map_err(|e| format!("无法获取 ProgramData 环境变量: {}", e))?;
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("创建委托安装目录失败 ({}): {}", dir.display(), e))?;
+        Ok(dir)
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        Err("委托安装仅支持 Windows 平台".to_string())
+    }
+}
+
+// 获取驱动目录
+// Windows: exe 同目录下的 drivers（驱动包缓存 / 解压目录）
+// macOS: 系统 PPD 目录 /Library/Printers/PPDs/Contents/Resources（由系统管理，不自动创建）
+pub fn get_drivers_root() -> Result<PathBuf, String> {
+    #[cfg(target_os = "macos")]
+    {
+        Ok(PathBuf::from("/Library/Printers/PPDs/Contents/Resources"))
+    }
+
+    #[cfg(not(target_os = "macos"))]
+    {
+        let exe_path = std::env::current_exe()
+            .map_err(|e| format!("获取可执行文件路径失败: {}", e))?;
+        let exe_dir = exe_path
+            .parent()
+            .ok_or_else(|| "无法获取可执行文件目录".to_string())?;
+        Ok(exe_dir.join("drivers"))
+    }
+}
+
+/// reveal_path 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RevealPathResult {
+    pub kind: String,
+    /// 实际打开的路径（文件时在文件夹中选中该文件）
+    pub path: String,
+    /// 目录原本不存在、本次已创建
+    pub created: bool,
+}
+
+/// 在资源管理器 / 访达中打开指定类型的本地路径
+///
+/// # 参数
+/// - `kind`: "config" | "drivers_root" | "logs" | "history"
+///
+/// # 返回
+/// - `Ok(RevealPathResult)`: 已打开
+/// - `Err(String)`: 类型无效、路径无法创建或打开失败
+pub fn reveal_path(app: &tauri::AppHandle, kind: &str) -> Result<RevealPathResult, String> {
+    // (目录, 目录内需选中的文件)
+    let (dir, file) = match kind {
+        "config" => {
+            let config_path = get_config_path(app)?;
+            let dir = config_path
+                .parent()
+                .map(|p| p.to_path_buf())
+                .ok_or_else(|| format!("无法获取配置文件目录: {}", config_path.display()))?;
+            (dir, Some(config_path))
+        }
+        "drivers_root" => (get_drivers_root()?, None),
+        "logs" => (get_logs_dir()?, None),
+        "history" => {
+            let history_path = super::install_history::history_file_path()?;
+            let dir = history_path
+                .parent()
+                .map(|p| p.to_path_buf())
+                .ok_or_else(|| format!("无法获取历史记录目录: {}", history_path.display()))?;
+            (dir, Some(history_path))
+        }
+        _ => {
+            return Err(format!(
+                "不支持的路径类型: \"{}\"（可选：config | drivers_root | logs | history）",
+                kind
+            ));
+        }
+    };
+
+    let mut created = false;
+    if !dir.exists() {
+        #[cfg(target_os = "macos")]
+        {
+            if kind == "drivers_root" {
+                return Err(format!("系统驱动目录不存在: {}（尚未安装任何 PPD 驱动）", dir.display()));
+            }
+        }
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("创建目录失败 {}: {}", dir.display(), e))?;
+        created = true;
+    }
+
+    let target = match file {
+        Some(file) if file.exists() => file,
+        _ => dir,
+    };
+    let select = target.is_file();
+
+    eprintln!(
+        "[RevealPath] kind={} path=\"{}\" select={} created={}",
+        kind,
+        target.display(),
+        select,
+        created
+    );
+    crate::platform::reveal_path(&target, select)?;
+
+    Ok(RevealPathResult {
+        kind: kind.to_string(),
+        path: target.display().to_string(),
+        created,
+    })
+}
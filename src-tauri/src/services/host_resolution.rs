@@ -0,0 +1,324 @@
+/**
+ * 主机名解析服务
+ * 职责：
+ * - 安装前解析 hostname 型打印机路径（\\printer-3f.corp.local），解析失败时提前报错
+ * - 安装成功后记录 hostname 与解析到的 IP（printer_hosts.json）
+ * - 连通性探测失败时重新解析，检测 IP 是否变化并提示更新端口
+ * - 可选：仍不可达时对 IPv4 地址做逐跳路由诊断（route_trace），结果附在连通性检查结果中
+ */
+
+use super::meta_store::MetaStore;
+use crate::platform::printer_address::{HostKind, PrinterAddress};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+/// DNS 解析超时
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 单个端口的 TCP 探测超时
+const PROBE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// 连通性探测端口（RAW 9100 / IPP 631 / LPD 515）
+const PROBE_PORTS: [u16; 3] = [9100, 631, 515];
+
+/// 打印机主机记录存储（schemaVersion=1）
+static PRINTER_HOSTS: MetaStore<PrinterHosts> = MetaStore::new("printer_hosts", 1);
+
+/// 单台打印机的主机解析记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterHostRecord {
+    pub hostname: String,
+    /// 安装时解析到的 IP（端口创建时使用的地址）
+    pub installed_ips: Vec<String>,
+    pub installed_at: i64,
+    /// 最近一次解析到的 IP
+    pub last_resolved_ips: Vec<String>,
+    pub last_resolved_at: i64,
+}
+
+/// 所有打印机的主机记录（key = 打印机名称）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterHosts {
+    pub printers: HashMap<String, PrinterHostRecord>,
+}
+
+/// 安装前解析结果
+#[derive(Debug, Clone)]
+pub struct ResolvedHost {
+    pub hostname: String,
+    pub ips: Vec<String>,
+}
+
+/// 连通性检查结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectivityCheckResult {
+    pub printer_name: String,
+    pub host: String,
+    /// "ipv4" | "ipv6" | "hostname"
+    pub host_kind: String,
+    pub reachable: bool,
+    /// 探测成功的端口
+    pub reachable_port: Option<u16>,
+    /// 本次解析到的 IP（仅 hostname）
+    pub resolved_ips: Vec<String>,
+    /// 安装时记录的 IP（仅 hostname 且有记录时）
+    pub installed_ips: Vec<String>,
+    /// hostname 当前解析结果与安装时记录不一致
+    pub ip_changed: bool,
+    /// 建议更新打印机端口（IP 已变化且原地址不可达）
+    pub suggest_port_update: bool,
+    /// 逐跳路由诊断（仅请求诊断且不可达的 IPv4 地址）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub route_trace: Option<super::route_trace::RouteTrace>,
+    pub message: String,
+}
+
+fn host_kind_str(kind: HostKind) -> &'static str {
+    match kind {
+        HostKind::Ipv4 => "ipv4",
+        HostKind::Ipv6 => "ipv6",
+        HostKind::Hostname => "hostname",
+    }
+}
+
+/// 解析 hostname（带超时，结果去重排序）
+async fn resolve_hostname(hostname: &str) -> Result<Vec<String>, String> {
+    let lookup = tokio::net::lookup_host((hostname, 0));
+    let addrs = tokio::time::timeout(RESOLVE_TIMEOUT, lookup)
+        .await
+        .map_err(|_| format!("解析主机名 {} 超时（{} 秒）", hostname, RESOLVE_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("无法解析主机名 {}: {}", hostname, e))?;
+
+    let mut ips: Vec<String> = addrs.map(|addr| addr.ip().to_string()).collect();
+    ips.sort();
+    ips.dedup();
+
+    if ips.is_empty() {
+        return Err(format!("主机名 {} 没有可用的 DNS 记录", hostname));
+    }
+    Ok(ips)
+}
+
+/// 安装前解析打印机路径中的 hostname
+///
+/// # 返回
+/// - `Ok(Some(ResolvedHost))`: 路径为 hostname 且解析成功
+/// - `Ok(None)`: 路径为 IP 地址或无法识别（交由安装流程处理）
+/// - `Err(String)`: hostname 无法解析（安装不会继续）
+pub async fn resolve_install_host(path: &str) -> Result<Option<ResolvedHost>, String> {
+    let hostname = match PrinterAddress::parse(path) {
+        Ok(address) if address.host_kind() == HostKind::Hostname => address.host_address(),
+        _ => return Ok(None),
+    };
+
+    let ips = resolve_hostname(&hostname).await.map_err(|e| {
+        format!(
+            "[DNS_RESOLVE_FAILED] {}\n\n请检查：\n1. 本机 DNS 设置是否正确\n2. 打印机主机名是否已在 DNS 中注册\n3. 是否已连接到公司网络 / VPN",
+            e
+        )
+    })?;
+
+    eprintln!("[HostResolution] install host=\"{}\" resolved_ips={:?}", hostname, ips);
+    Ok(Some(ResolvedHost {
+        hostname,
+        ips,
+    }))
+}
+
+/// 安装成功后记录 hostname 与解析到的 IP（写入失败只记录日志）
+pub async fn record_install_host(printer_name: String, resolved: ResolvedHost) {
+    let result = tokio::task::spawn_blocking(move || {
+        let now = chrono::Utc::now().timestamp_millis();
+        PRINTER_HOSTS.update(|hosts| {
+            hosts.printers.insert(
+                printer_name,
+                PrinterHostRecord {
+                    hostname: resolved.hostname,
+                    installed_ips: resolved.ips.clone(),
+                    installed_at: now,
+                    last_resolved_ips: resolved.ips,
+                    last_resolved_at: now,
+                },
+            );
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[HostResolution] 写入主机记录失败: {}", e),
+        Err(e) => eprintln!("[HostResolution] 写入任务异常: {}", e),
+    }
+}
+
+/// 依次探测常用打印端口，返回第一个可连接的端口
+async fn probe_ports(ips: &[String]) -> Option<u16> {
+    for ip in ips {
+        let ip = match ip.parse() {
+            Ok(ip) => ip,
+            Err(_) => continue,
+        };
+        for port in PROBE_PORTS {
+            let connect = tokio::net::TcpStream::connect(SocketAddr::new(ip, port));
+            if let Ok(Ok(_)) = tokio::time::timeout(PROBE_TIMEOUT, connect).await {
+                return Some(port);
+            }
+        }
+    }
+    None
+}
+
+/// 不可达时对第一个 IPv4 地址做逐跳诊断，并把结论追加到 message（诊断失败只记录日志）
+async fn attach_route_trace(result: &mut ConnectivityCheckResult, ips: &[String]) {
+    let Some(target) = ips.iter().find_map(|ip| ip.parse::<std::net::Ipv4Addr>().ok()) else {
+        return;
+    };
+    match super::route_trace::trace_route(target).await {
+        Ok(trace) => {
+            result.message = format!("{}；{}", result.message, trace.message);
+            result.route_trace = Some(trace);
+        }
+        Err(e) => eprintln!("[HostResolution] 逐跳诊断失败 target={} error={}", target, e),
+    }
+}
+
+/// 检查打印机连通性；hostname 型路径在探测失败时重新解析并检测 IP 变化
+///
+/// # 参数
+/// - `printer_name`: 打印机名称（用于查找安装时的记录）
+/// - `path`: 打印机路径（\\192.168.1.5 / \\fd00::12 / \\printer.corp.local）
+/// - `trace_route`: 不可达时是否做逐跳路由诊断（仅 IPv4，最长约 15 秒）
+pub async fn check_printer_connectivity(
+    printer_name: &str,
+    path: &str,
+    trace_route: bool,
+) -> Result<ConnectivityCheckResult, String> {
+    let address = PrinterAddress::parse(path)?;
+    let host = address.host_address();
+    let mut result = ConnectivityCheckResult {
+        printer_name: printer_name.to_string(),
+        host: host.clone(),
+        host_kind: host_kind_str(address.host_kind()).to_string(),
+        reachable: false,
+        reachable_port: None,
+        resolved_ips: Vec::new(),
+        installed_ips: Vec::new(),
+        ip_changed: false,
+        suggest_port_update: false,
+        route_trace: None,
+        message: String::new(),
+    };
+
+    if address.host_kind() != HostKind::Hostname {
+        // zone id（fe80::1%12）无法直接解析为 IpAddr，去掉后探测
+        let ip = host.split('%').next().unwrap_or_default().to_string();
+        result.reachable_port = probe_ports(std::slice::from_ref(&ip)).await;
+        result.reachable = result.reachable_port.is_some();
+        result.message = match result.reachable_port {
+            Some(port) => format!("打印机 {} 可连接（端口 {}）", host, port),
+            None => format!("无法连接到打印机 {}（已探测端口 {:?}）", host, PROBE_PORTS),
+        };
+        if trace_route && !result.reachable {
+            attach_route_trace(&mut result, &[ip]).await;
+        }
+        return Ok(result);
+    }
+
+    let record = tokio::task::spawn_blocking(|| PRINTER_HOSTS.load())
+        .await
+        .map_err(|e| format!("读取主机记录任务异常: {}", e))??
+        .printers
+        .get(printer_name)
+        .cloned();
+    result.installed_ips = record.as_ref().map(|r| r.installed_ips.clone()).unwrap_or_default();
+
+    // 先探测安装时的地址（端口实际指向的地址）
+    if !result.installed_ips.is_empty() {
+        if let Some(port) = probe_ports(&result.installed_ips).await {
+            result.reachable = true;
+            result.reachable_port = Some(port);
+            result.resolved_ips = result.installed_ips.clone();
+            result.message = format!("打印机 {} 可连接（端口 {}）", host, port);
+            return Ok(result);
+        }
+    }
+
+    // 探测失败或无记录：重新解析
+    let resolved_ips = match resolve_hostname(&host).await {
+        Ok(ips) => ips,
+        Err(e) => {
+            result.message = format!("[DNS_RESOLVE_FAILED] {}", e);
+            return Ok(result);
+        }
+    };
+    result.resolved_ips = resolved_ips.clone();
+    result.ip_changed = !result.installed_ips.is_empty() && result.installed_ips != resolved_ips;
+    result.reachable_port = probe_ports(&resolved_ips).await;
+    result.reachable = result.reachable_port.is_some();
+    result.suggest_port_update = result.ip_changed;
+
+    eprintln!(
+        "[HostResolution] recheck host=\"{}\" installed_ips={:?} resolved_ips={:?} ip_changed={} reachable={}",
+        host, result.installed_ips, resolved_ips, result.ip_changed, result.reachable
+    );
+
+    if record.is_some() {
+        let printer_name = printer_name.to_string();
+        let ips = resolved_ips.clone();
+        let _ = tokio::task::spawn_blocking(move || {
+            PRINTER_HOSTS.update(|hosts| {
+                if let Some(record) = hosts.printers.get_mut(&printer_name) {
+                    record.last_resolved_ips = ips;
+                    record.last_resolved_at = chrono::Utc::now().timestamp_millis();
+                }
+            })
+        })
+        .await;
+    }
+
+    result.message = if result.ip_changed {
+        format!(
+            "主机名 {} 已解析到新地址 {}（安装时为 {}），建议更新打印机端口或重新安装该打印机",
+            host,
+            resolved_ips.join(", "),
+            result.installed_ips.join(", ")
+        )
+    } else if result.reachable {
+        format!("打印机 {} 可连接（端口 {}）", host, result.reachable_port.unwrap_or_default())
+    } else {
+        format!(
+            "主机名 {} 解析到 {}，但无法连接（已探测端口 {:?}）",
+            host,
+            resolved_ips.join(", "),
+            PROBE_PORTS
+        )
+    };
+    if trace_route && !result.reachable {
+        attach_route_trace(&mut result, &resolved_ips).await;
+    }
+    Ok(result)
+}
+
+/// 删除指定打印机的主机记录（卸载后调用，阻塞调用）
+pub fn forget_printers(printer_names: &[String]) -> Result<(), String> {
+    PRINTER_HOSTS.update(|hosts| {
+        for name in printer_names {
+            hosts.printers.remove(name);
+        }
+    })
+}
+
+/// 安装时为打印机端口解析到的 IP（无记录或读取失败时为空，阻塞调用）
+pub fn installed_ips(printer_name: &str) -> Vec<String> {
+    PRINTER_HOSTS
+        .load()
+        .ok()
+        .and_then(|mut hosts| hosts.printers.remove(printer_name))
+        .map(|record| record.installed_ips)
+        .unwrap_or_default()
+}
@@ -0,0 +1,317 @@
+/**
+ * 安装耗时预估服务
+ * 职责：
+ * - 按本机安装历史与驱动包大小预估一次安装的耗时，供界面显示"约 2 分钟"而不是无限转圈
+ * - 预估依据（按优先级）：同一打印机最近的成功安装 → 同一 driverKey 最近的成功安装 →
+ *   本机最近成功安装的耗时中位数（不含下载）+ 驱动包下载时间 → 内置默认值
+ * - 驱动包已在本机缓存时扣除历史记录中的下载耗时；下载时间按 driverCatalog 的 remote.size 与本机历史下载速度估算
+ * - 安装开始前登记到 install_event_emitter，job.init 事件（含 dryRun 预检）的 meta.estimate 带有预估结果
+ *
+ * 数据来源为 install_history 存储；dryRun 与失败的记录不参与预估
+ */
+
+use super::install_history::InstallRecord;
+use crate::PrinterConfig;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 每种依据最多使用的历史记录数（最新的在前）
+const MAX_SAMPLES: usize = 5;
+
+/// 没有任何历史时的默认耗时（不含下载）
+const DEFAULT_BASE_MS: i64 = 30_000;
+
+/// 没有下载历史时假定的下载速度（字节 / 秒）
+const DEFAULT_THROUGHPUT_BPS: f64 = 2.0 * 1024.0 * 1024.0;
+
+/// 下载步骤 ID
+const DOWNLOAD_STEP: &str = "driver.download";
+
+/// 安装耗时预估
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallEstimate {
+    pub estimated_duration_ms: i64,
+    /// 区间下限 / 上限（历史记录的最短 / 最长耗时；没有历史时为预估值的 ±50%）
+    pub low_ms: i64,
+    pub high_ms: i64,
+    /// "printerHistory" | "driverHistory" | "machineHistory" | "default"
+    pub source: String,
+    /// 参与预估的历史记录数
+    pub sample_count: usize,
+    /// 预估中包含的驱动包下载时间（已缓存或大小未知时为 0）
+    pub download_ms: i64,
+    /// 驱动包是否已在本机缓存（driverCatalog 中没有 remote.sha256 时为 None）
+    pub package_cached: Option<bool>,
+    /// 界面显示文本，如 "约 2 分钟"
+    pub label: String,
+}
+
+/// 预估所需的驱动包信息
+#[derive(Debug, Clone, Default)]
+struct PackageInfo {
+    size: Option<u64>,
+    cached: Option<bool>,
+}
+
+fn duration_ms(record: &InstallRecord) -> i64 {
+    (record.finished_at - record.started_at).max(0)
+}
+
+fn download_ms(record: &InstallRecord) -> i64 {
+    record.step_durations.iter().filter(|s| s.step == DOWNLOAD_STEP).map(|s| s.duration_ms).sum()
+}
+
+fn median(values: &mut [i64]) -> i64 {
+    values.sort_unstable();
+    match values.len() {
+        0 => 0,
+        n if n % 2 == 1 => values[n / 2],
+        n => (values[n / 2 - 1] + values[n / 2]) / 2,
+    }
+}
+
+/// 界面显示文本（按分钟取整，不足 1 分钟单独表示）
+fn label_for(ms: i64) -> String {
+    if ms < 60_000 {
+        return "不到 1 分钟".to_string();
+    }
+    format!("约 {} 分钟", (ms + 30_000) / 60_000)
+}
+
+/// 本机历史下载速度（字节 / 秒；只统计 driverCatalog 声明了 remote.size 的驱动包）
+fn observed_throughput(records: &[&InstallRecord], sizes: &HashMap<String, u64>) -> Option<f64> {
+    let (bytes, ms) = records
+        .iter()
+        .filter_map(|r| Some((*sizes.get(r.driver_key.as_deref()?)?, download_ms(r))))
+        .filter(|(_, ms)| *ms > 0)
+        .fold((0u64, 0i64), |(bytes, total), (size, ms)| (bytes + size, total + ms));
+    if ms > 0 {
+        Some(bytes as f64 / (ms as f64 / 1000.0))
+    } else {
+        None
+    }
+}
+
+fn from_samples(samples: &[&InstallRecord], source: &str, package: &PackageInfo) -> InstallEstimate {
+    // 驱动包已缓存时这次不会再下载
+    let skip_download = package.cached == Some(true);
+    let mut durations: Vec<i64> = samples
+        .iter()
+        .map(|r| if skip_download { duration_ms(r) - download_ms(r) } else { duration_ms(r) }.max(0))
+        .collect();
+    let estimated = median(&mut durations);
+    InstallEstimate {
+        estimated_duration_ms: estimated,
+        low_ms: durations.first().copied().unwrap_or(estimated),
+        high_ms: durations.last().copied().unwrap_or(estimated),
+        source: source.to_string(),
+        sample_count: samples.len(),
+        download_ms: 0,
+        package_cached: package.cached,
+        label: label_for(estimated),
+    }
+}
+
+/// 按安装历史预估耗时
+///
+/// # 参数
+/// - `records`: 安装历史（最新的在前）
+/// - `printer_name`: 配置中的打印机名称
+/// - `driver_key`: 本次安装使用的 driverKey
+/// - `package`: 驱动包大小与缓存状态
+/// - `sizes`: driverCatalog 中各 driverKey 的驱动包大小（估算历史下载速度）
+fn estimate(
+    records: &[InstallRecord],
+    printer_name: &str,
+    driver_key: Option<&str>,
+    package: &PackageInfo,
+    sizes: &HashMap<String, u64>,
+) -> InstallEstimate {
+    let successful: Vec<&InstallRecord> = records.iter().filter(|r| r.success && !r.dry_run).collect();
+
+    let same_printer: Vec<&InstallRecord> = successful
+        .iter()
+        .copied()
+        .filter(|r| r.printer_name == printer_name || r.display_name.as_deref() == Some(printer_name))
+        .take(MAX_SAMPLES)
+        .collect();
+    if !same_printer.is_empty() {
+        return from_samples(&same_printer, "printerHistory", package);
+    }
+    let same_driver: Vec<&InstallRecord> = successful
+        .iter()
+        .copied()
+        .filter(|r| driver_key.is_some() && r.driver_key.as_deref() == driver_key)
+        .take(MAX_SAMPLES)
+        .collect();
+    if !same_driver.is_empty() {
+        return from_samples(&same_driver, "driverHistory", package);
+    }
+
+    // 没有同一打印机 / 驱动的历史：其他安装的耗时（不含下载）+ 按驱动包大小估算的下载时间
+    let mut base: Vec<i64> = successful
+        .iter()
+        .take(MAX_SAMPLES)
+        .map(|r| (duration_ms(r) - download_ms(r)).max(0))
+        .collect();
+    let (base_ms, source) = match base.is_empty() {
+        true => (DEFAULT_BASE_MS, "default"),
+        false => (median(&mut base), "machineHistory"),
+    };
+    let download = match (package.size, package.cached) {
+        (Some(size), cached) if cached != Some(true) => {
+            let throughput = observed_throughput(&successful, sizes).unwrap_or(DEFAULT_THROUGHPUT_BPS);
+            (size as f64 / throughput * 1000.0) as i64
+        }
+        _ => 0,
+    };
+    let estimated = base_ms + download;
+    InstallEstimate {
+        estimated_duration_ms: estimated,
+        low_ms: estimated / 2,
+        high_ms: estimated + estimated / 2,
+        source: source.to_string(),
+        sample_count: base.len(),
+        download_ms: download,
+        package_cached: package.cached,
+        label: label_for(estimated),
+    }
+}
+
+/// driverKey 对应驱动包的大小与缓存状态
+fn package_info(config: &PrinterConfig, driver_key: Option<&str>) -> PackageInfo {
+    let Some(driver_key) = driver_key else {
+        return PackageInfo::default();
+    };
+    let size = config
+        .driver_catalog
+        .as_ref()
+        .and_then(|catalog| catalog.get(driver_key))
+        .and_then(|entry| entry.remote.as_ref())
+        .and_then(|remote| remote.size);
+    let cached = super::driver_cache::package_uuid(config, driver_key).map(|uuid| {
+        super::fs_paths::get_drivers_root()
+            .map(|root| root.join(uuid).join("payload").join("payload.zip").is_file())
+            .unwrap_or(false)
+    });
+    PackageInfo { size, cached }
+}
+
+/// 预估一台打印机的安装耗时
+///
+/// # 参数
+/// - `config`: 当前配置（None 时不考虑驱动包大小）
+/// - `printer_name`: 配置中的打印机名称
+/// - `driver_key`: 本次安装使用的 driverKey
+///
+/// # 返回
+/// - `Ok(InstallEstimate)`: 预估结果（没有任何历史时使用内置默认值）
+/// - `Err(String)`: 读取安装历史失败
+pub async fn estimate_install(config: Option<&PrinterConfig>, printer_name: &str, driver_key: Option<&str>) -> Result<InstallEstimate, String> {
+    let records = super::install_history::list_install_history(None).await?;
+    let (package, sizes) = match config {
+        Some(config) => {
+            let sizes = config
+                .driver_catalog
+                .iter()
+                .flatten()
+                .filter_map(|(key, entry)| Some((key.clone(), entry.remote.as_ref()?.size?)))
+                .collect();
+            (package_info(config, driver_key), sizes)
+        }
+        None => (PackageInfo::default(), HashMap::new()),
+    };
+    Ok(estimate(&records, printer_name, driver_key, &package, &sizes))
+}
+
+/// 预估配置中一台打印机的安装耗时（get_install_estimate 命令，安装前显示）
+///
+/// # 参数
+/// - `printer_name`: 配置中的打印机名称
+/// - `driver_key`: 为 None 时使用配置中该打印机的 driverKey
+pub async fn estimate_for_printer(printer_name: &str, driver_key: Option<String>) -> Result<InstallEstimate, String> {
+    let config = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))?
+        .ok()
+        .map(|(config, _)| config);
+    let driver_key = driver_key.or_else(|| {
+        config
+            .as_ref()
+            .and_then(|config| super::printer_watch::find_config_printer(config, printer_name))
+            .and_then(|printer| printer.driver_key)
+    });
+    estimate_install(config.as_ref(), printer_name, driver_key.as_deref()).await
+}
+
+/// 预估安装耗时并登记到该打印机的下一个 job.init 事件（读取历史失败只记录日志）
+///
+/// # 参数
+/// - `queue_name`: 传给平台安装入口的打印机名称（job.init 事件中的 printerName）
+/// - `config` / `printer_name` / `driver_key`: 同 estimate_install
+pub async fn attach_to_next_job(queue_name: &str, config: Option<&PrinterConfig>, printer_name: &str, driver_key: Option<&str>) {
+    match estimate_install(config, printer_name, driver_key).await {
+        Ok(estimate) => {
+            eprintln!(
+                "[InstallEstimate] printer=\"{}\" estimated_ms={} source={} samples={}",
+                printer_name, estimate.estimated_duration_ms, estimate.source, estimate.sample_count
+            );
+            let value = serde_json::to_value(&estimate).unwrap_or(serde_json::Value::Null);
+            crate::install_event_emitter::register_install_estimate(queue_name, value);
+        }
+        Err(e) => eprintln!("[InstallEstimate] 预估安装耗时失败: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::install_history::StepDuration;
+
+    fn record(printer: &str, driver_key: &str, success: bool, total_ms: i64, download: i64) -> InstallRecord {
+        InstallRecord {
+            job_id: format!("job_{}", total_ms),
+            printer_name: printer.to_string(),
+            driver_key: Some(driver_key.to_string()),
+            install_mode: Some("package".to_string()),
+            success,
+            started_at: 1_000,
+            finished_at: 1_000 + total_ms,
+            step_durations: vec![StepDuration { step: DOWNLOAD_STEP.to_string(), duration_ms: download }],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn estimates_from_history_then_pack_size() {
+        let records = vec![
+            record("前台 HP", "HP", true, 100_000, 40_000),
+            record("前台 HP", "HP", false, 5_000, 0),
+            record("前台 HP", "HP", true, 140_000, 40_000),
+            record("财务部 Canon", "CANON", true, 50_000, 20_000),
+        ];
+        let not_cached = PackageInfo { size: Some(100 * 1024 * 1024), cached: Some(false) };
+        let cached = PackageInfo { size: Some(100 * 1024 * 1024), cached: Some(true) };
+        let sizes = HashMap::from([("CANON".to_string(), 40 * 1024 * 1024)]);
+
+        let by_printer = estimate(&records, "前台 HP", Some("HP"), &not_cached, &sizes);
+        assert_eq!((by_printer.source.as_str(), by_printer.sample_count), ("printerHistory", 2));
+        assert_eq!((by_printer.estimated_duration_ms, by_printer.low_ms, by_printer.high_ms), (120_000, 100_000, 140_000));
+        assert_eq!(by_printer.label, "约 2 分钟");
+        // 驱动包已缓存时扣除下载耗时
+        assert_eq!(estimate(&records, "前台 HP", Some("HP"), &cached, &sizes).estimated_duration_ms, 80_000);
+
+        assert_eq!(estimate(&records, "二楼 HP", Some("HP"), &cached, &sizes).source, "driverHistory");
+
+        // 新驱动：其他安装的耗时（不含下载，中位数 60s）+ 100 MB ÷ 本机下载速度（40 MB / 20 s）
+        let fresh = estimate(&records, "三楼 Ricoh", Some("RICOH"), &not_cached, &sizes);
+        assert_eq!((fresh.source.as_str(), fresh.download_ms, fresh.estimated_duration_ms), ("machineHistory", 50_000, 110_000));
+        // 本机历史同样只取最近 MAX_SAMPLES 条
+        let many: Vec<InstallRecord> = (1..=7).map(|i| record("其他", "X", true, i * 10_000, 0)).collect();
+        let capped = estimate(&many, "三楼 Ricoh", None, &PackageInfo::default(), &HashMap::new());
+        assert_eq!((capped.sample_count, capped.estimated_duration_ms), (MAX_SAMPLES, 30_000));
+        let none = estimate(&[], "三楼 Ricoh", None, &PackageInfo::default(), &HashMap::new());
+        assert_eq!((none.source.as_str(), none.estimated_duration_ms, none.label.as_str()), ("default", DEFAULT_BASE_MS, "不到 1 分钟"));
+    }
+}
@@ -0,0 +1,302 @@
+/**
+ * 安装历史记录服务
+ * 职责：
+ * - 每次安装结束后追加一条记录到本地 meta 存储（install_history.json）
+ * - 后台巡检（printer_watch）发现的问题与修复动作记录在同一存储（watchActions）
+ * - 每次打印测试页的结果也记录在同一存储（testPages），供支持人员按打印机查询
+ * - 提供历史记录查询
+ *
+ * 存储由 meta_store 负责并发安全与 schemaVersion
+ */
+
+use super::meta_store::MetaStore;
+use serde::{Deserialize, Serialize};
+
+/// 最多保留的历史记录条数（超出后丢弃最旧的记录；安装指标也只能基于保留的记录统计）
+pub const MAX_RECORDS: usize = 200;
+
+/// 安装历史存储（schemaVersion=1）
+static INSTALL_HISTORY: MetaStore<InstallHistory> = MetaStore::new("install_history", 1);
+
+/// 单次安装记录
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallRecord {
+    pub job_id: String,
+    /// 系统队列名称（按 nameTemplate 命名时与配置中的名称不同）
+    pub printer_name: String,
+    /// 配置中的原始打印机名称（仅按 nameTemplate 改名安装时有值）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
+    pub driver_key: Option<String>,
+    pub install_mode: Option<String>,
+    pub success: bool,
+    pub dry_run: bool,
+    pub message: String,
+    pub started_at: i64,
+    pub finished_at: i64,
+    /// 失败时 stderr 中的错误码（[EASYPRINTER_CODE=...]）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_code: Option<String>,
+    /// 各步骤耗时（按结束顺序；旧记录没有该字段）
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub step_durations: Vec<StepDuration>,
+    /// 失败的步骤（平台层在出错处登记；未登记或旧记录为 None）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failed_step: Option<String>,
+    /// 安装后动作（postInstall）的执行结果
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub post_install: Vec<super::post_install::PostInstallActionResult>,
+    /// 打印队列权限（permissions）的设置结果
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<super::printer_permissions::PrinterPermissionsReport>,
+    /// 是否为免驱队列（未找到厂商驱动，使用 Microsoft IPP Class Driver 创建）
+    #[serde(default)]
+    pub driverless: bool,
+}
+
+/// 单个安装步骤的耗时
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepDuration {
+    /// 步骤 ID（如 "driver.download" / "device.ensureQueue"）
+    pub step: String,
+    pub duration_ms: i64,
+}
+
+impl InstallRecord {
+    /// 由安装结果构造记录
+    pub fn from_result(
+        printer_name: String,
+        driver_key: Option<String>,
+        install_mode: Option<String>,
+        started_at: i64,
+        result: &crate::InstallResult,
+    ) -> Self {
+        let (durations, failed_step) = crate::install_event_emitter::take_step_timings(&result.job_id);
+        Self {
+            job_id: result.job_id.clone(),
+            printer_name,
+            display_name: None,
+            driver_key,
+            install_mode,
+            success: result.success,
+            dry_run: result.effective_dry_run,
+            message: result.message.clone(),
+            started_at,
+            finished_at: chrono::Utc::now().timestamp_millis(),
+            error_code: result
+                .stderr
+                .as_deref()
+                .and_then(crate::platform::error_code::error_code)
+                .map(str::to_string)
+                .filter(|_| !result.success),
+            step_durations: durations
+                .into_iter()
+                .map(|(step, duration_ms)| StepDuration { step, duration_ms })
+                .collect(),
+            failed_step: failed_step.filter(|_| !result.success),
+            post_install: Vec::new(),
+            permissions: None,
+            driverless: result.success && result.method.as_deref() == Some(crate::platform::IPP_CLASS_DRIVER_METHOD),
+        }
+    }
+}
+
+/// 后台巡检动作记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchActionRecord {
+    pub printer_name: String,
+    pub system_queue_name: String,
+    /// 发现的问题："port_changed" | "driver_missing"
+    pub issues: Vec<String>,
+    /// "port_updated"（只改了端口地址）| "repaired" | "repair_failed" | "notified"
+    pub action: String,
+    /// 自动修复时的安装任务 ID
+    pub job_id: Option<String>,
+    pub message: String,
+    pub at: i64,
+}
+
+/// 测试页打印记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TestPageRecord {
+    pub printer_name: String,
+    /// 提交方式："out-printer"（Windows）| "lp"（macOS）
+    pub method: String,
+    /// 系统打印任务 ID（仅 macOS lp 返回）
+    pub job_id: Option<String>,
+    /// 任务状态（仅 macOS）："completed" | "pending" | "unknown"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub job_status: Option<String>,
+    pub success: bool,
+    pub message: String,
+    /// 失败步骤（错误信息中的 step=，如 "CHECK_EXISTS" / "PS_RESULT"）
+    pub error_step: Option<String>,
+    pub at: i64,
+}
+
+/// 安装历史（按时间顺序，最新的在末尾）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallHistory {
+    pub records: Vec<InstallRecord>,
+    #[serde(default)]
+    pub watch_actions: Vec<WatchActionRecord>,
+    #[serde(default)]
+    pub test_pages: Vec<TestPageRecord>,
+}
+
+/// 追加一条安装记录（写入失败只记录日志，不影响安装结果）
+pub async fn record_install(record: InstallRecord) {
+    let result = tokio::task::spawn_blocking(move || {
+        INSTALL_HISTORY.update(|history| {
+            history.records.push(record);
+            if history.records.len() > MAX_RECORDS {
+                let overflow = history.records.len() - MAX_RECORDS;
+                history.records.drain(..overflow);
+            }
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[InstallHistory] 写入安装记录失败: {}", e),
+        Err(e) => eprintln!("[InstallHistory] 写入任务异常: {}", e),
+    }
+}
+
+/// 查询安装历史（最新的在前）
+///
+/// # 参数
+/// - `limit`: 最多返回条数（None 表示全部）
+pub async fn list_install_history(limit: Option<usize>) -> Result<Vec<InstallRecord>, String> {
+    let history = tokio::task::spawn_blocking(|| INSTALL_HISTORY.load())
+        .await
+        .map_err(|e| format!("读取安装历史任务异常: {}", e))??;
+
+    let mut records = history.records;
+    records.reverse();
+    if let Some(limit) = limit {
+        records.truncate(limit);
+    }
+    Ok(records)
+}
+
+/// 追加后台巡检动作记录（写入失败只记录日志）
+pub async fn record_watch_actions(actions: Vec<WatchActionRecord>) {
+    if actions.is_empty() {
+        return;
+    }
+    let result = tokio::task::spawn_blocking(move || {
+        INSTALL_HISTORY.update(|history| {
+            history.watch_actions.extend(actions);
+            if history.watch_actions.len() > MAX_RECORDS {
+                let overflow = history.watch_actions.len() - MAX_RECORDS;
+                history.watch_actions.drain(..overflow);
+            }
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[InstallHistory] 写入巡检记录失败: {}", e),
+        Err(e) => eprintln!("[InstallHistory] 写入任务异常: {}", e),
+    }
+}
+
+/// 查询后台巡检动作记录（最新的在前）
+///
+/// # 参数
+/// - `limit`: 最多返回条数（None 表示全部）
+pub async fn list_watch_actions(limit: Option<usize>) -> Result<Vec<WatchActionRecord>, String> {
+    let history = tokio::task::spawn_blocking(|| INSTALL_HISTORY.load())
+        .await
+        .map_err(|e| format!("读取安装历史任务异常: {}", e))??;
+
+    let mut actions = history.watch_actions;
+    actions.reverse();
+    if let Some(limit) = limit {
+        actions.truncate(limit);
+    }
+    Ok(actions)
+}
+
+/// 追加一条测试页记录（写入失败只记录日志，不影响打印结果）
+pub async fn record_test_page(record: TestPageRecord) {
+    let result = tokio::task::spawn_blocking(move || {
+        INSTALL_HISTORY.update(|history| {
+            history.test_pages.push(record);
+            if history.test_pages.len() > MAX_RECORDS {
+                let overflow = history.test_pages.len() - MAX_RECORDS;
+                history.test_pages.drain(..overflow);
+            }
+        })
+    })
+    .await;
+
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[InstallHistory] 写入测试页记录失败: {}", e),
+        Err(e) => eprintln!("[InstallHistory] 写入任务异常: {}", e),
+    }
+}
+
+/// 查询某台打印机的测试页记录（最新的在前）
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称（不区分大小写）
+/// - `limit`: 最多返回条数（None 表示全部）
+pub async fn list_test_pages(printer_name: &str, limit: Option<usize>) -> Result<Vec<TestPageRecord>, String> {
+    let history = tokio::task::spawn_blocking(|| INSTALL_HISTORY.load())
+        .await
+        .map_err(|e| format!("读取安装历史任务异常: {}", e))??;
+
+    let mut records: Vec<TestPageRecord> = history
+        .test_pages
+        .into_iter()
+        .filter(|r| r.printer_name.eq_ignore_ascii_case(printer_name))
+        .collect();
+    records.reverse();
+    if let Some(limit) = limit {
+        records.truncate(limit);
+    }
+    Ok(records)
+}
+
+/// 历史中成功安装过（非 dryRun）的打印机名称（去重，阻塞调用）
+pub fn installed_printer_names() -> Result<Vec<String>, String> {
+    let history = INSTALL_HISTORY.load()?;
+    let mut names: Vec<String> = Vec::new();
+    for record in history.records {
+        if record.success && !record.dry_run && !names.contains(&record.printer_name) {
+            names.push(record.printer_name);
+        }
+    }
+    Ok(names)
+}
+
+/// 历史中按 nameTemplate 改名安装的队列：(系统队列名称, 配置中的原始名称)（去重，阻塞调用）
+pub fn installed_display_names() -> Result<Vec<(String, String)>, String> {
+    let history = INSTALL_HISTORY.load()?;
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    for record in history.records {
+        let Some(display_name) = record.display_name.filter(|_| record.success && !record.dry_run) else {
+            continue;
+        };
+        let pair = (record.printer_name, display_name);
+        if !pairs.contains(&pair) {
+            pairs.push(pair);
+        }
+    }
+    Ok(pairs)
+}
+
+/// 安装历史文件路径（供“打开所在文件夹”使用）
+pub fn history_file_path() -> Result<std::path::PathBuf, String> {
+    INSTALL_HISTORY.path()
+}
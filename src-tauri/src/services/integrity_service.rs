@@ -0,0 +1,105 @@
+/**
+ * 安装完整性自检服务
+ * 职责：
+ * - 启动时重新计算内嵌资源哈希并与编译期常量比对（结果缓存，随 get_system_info 返回）
+ * - verify_installation：资源校验 + 可执行文件签名状态
+ */
+
+use crate::platform::{ExecutableSignature, ResourceIntegrity};
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// 最近一次自检结果（启动自检或 verify_installation 写入）
+static LAST_REPORT: Mutex<Option<IntegrityReport>> = Mutex::new(None);
+
+/// 完整性自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IntegrityReport {
+    /// 所有资源完整，且签名不是 invalid
+    pub ok: bool,
+    pub checked_at: i64,
+    pub resources: Vec<ResourceIntegrity>,
+    /// 签名状态（启动自检不查询签名，为 None）
+    pub signature: Option<ExecutableSignature>,
+    pub message: String,
+}
+
+fn build_report(resources: Vec<ResourceIntegrity>, signature: Option<ExecutableSignature>) -> IntegrityReport {
+    let tampered: Vec<String> = resources
+        .iter()
+        .filter(|r| !r.ok)
+        .map(|r| match &r.path {
+            Some(path) => format!("{}（{}）", r.name, path),
+            None => r.name.clone(),
+        })
+        .collect();
+    let signature_invalid = signature.as_ref().map(|s| s.status == "invalid").unwrap_or(false);
+
+    let mut problems = Vec::new();
+    if !tampered.is_empty() {
+        problems.push(format!("资源校验失败：{}", tampered.join("、")));
+    }
+    if signature_invalid {
+        problems.push("可执行文件签名无效，安装包可能已被修改".to_string());
+    }
+
+    let message = if problems.is_empty() {
+        match signature.as_ref().map(|s| s.status.as_str()) {
+            Some("unsigned") => "内嵌资源完整（可执行文件未签名）".to_string(),
+            _ => "内嵌资源完整".to_string(),
+        }
+    } else {
+        problems.join("；")
+    };
+
+    IntegrityReport {
+        ok: problems.is_empty(),
+        checked_at: chrono::Utc::now().timestamp_millis(),
+        resources,
+        signature,
+        message,
+    }
+}
+
+fn store_report(report: &IntegrityReport) {
+    *LAST_REPORT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(report.clone());
+}
+
+/// 启动自检：只校验内嵌资源（不启动外部进程，结果写入缓存）
+pub fn run_startup_check() -> IntegrityReport {
+    let report = build_report(crate::platform::verify_embedded_resources(), None);
+    if report.ok {
+        eprintln!("[Integrity] startup check ok resources={}", report.resources.len());
+    } else {
+        eprintln!("[Integrity] startup check FAILED message=\"{}\"", report.message);
+    }
+    store_report(&report);
+    report
+}
+
+/// 获取最近一次自检结果（未执行过时返回 None）
+pub fn last_report() -> Option<IntegrityReport> {
+    LAST_REPORT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).clone()
+}
+
+/// 完整校验：内嵌资源 + 可执行文件签名
+pub async fn verify_installation() -> Result<IntegrityReport, String> {
+    let report = tokio::task::spawn_blocking(|| {
+        build_report(
+            crate::platform::verify_embedded_resources(),
+            Some(crate::platform::verify_executable_signature()),
+        )
+    })
+    .await
+    .map_err(|e| format!("完整性校验任务异常: {}", e))?;
+
+    eprintln!(
+        "[Integrity] verify_installation ok={} signature={:?} message=\"{}\"",
+        report.ok,
+        report.signature.as_ref().map(|s| s.status.as_str()),
+        report.message
+    );
+    store_report(&report);
+    Ok(report)
+}
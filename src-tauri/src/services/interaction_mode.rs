@@ -0,0 +1,111 @@
+/**
+ * 交互模式服务（非交互 / 静默模式）
+ * 职责：
+ * - 由命令行参数 --silent / --non-interactive，或环境变量 EPRINTY_NON_INTERACTIVE=1 开启（main 启动时读取一次）
+ * - 开启后不弹出任何原生对话框（启动错误提示、WebView2 引导安装询问）：错误写入 error.log 与 stderr，
+ *   并以退出码区分失败原因；前端通过 get_interaction_mode 读取，跳过确认步骤
+ */
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU8, Ordering};
+
+/// 开启非交互模式的命令行参数
+const NON_INTERACTIVE_ARGS: [&str; 2] = ["--silent", "--non-interactive"];
+
+/// 开启非交互模式的环境变量
+const NON_INTERACTIVE_ENV: &str = "EPRINTY_NON_INTERACTIVE";
+
+/// 启动失败（通用）
+pub const EXIT_STARTUP_FAILED: i32 = 1;
+
+/// 启动失败：WebView2 运行时缺失或损坏
+pub const EXIT_WEBVIEW2_MISSING: i32 = 2;
+
+/// 当前模式来源：0 = 交互模式，其余见 ModeSource
+static MODE: AtomicU8 = AtomicU8::new(0);
+
+/// 非交互模式的开启方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ModeSource {
+    Args = 1,
+    Env = 2,
+}
+
+impl ModeSource {
+    fn as_str(value: u8) -> Option<&'static str> {
+        match value {
+            1 => Some("args"),
+            2 => Some("env"),
+            _ => None,
+        }
+    }
+}
+
+/// get_interaction_mode 结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InteractionMode {
+    pub non_interactive: bool,
+    /// 开启方式："args" | "env"（交互模式为 None）
+    pub source: Option<String>,
+}
+
+/// 判断启动参数 / 环境变量是否要求非交互模式
+fn requested_source<I, S>(args: I, env_value: Option<&str>) -> Option<ModeSource>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let by_args = args
+        .into_iter()
+        .any(|arg| NON_INTERACTIVE_ARGS.iter().any(|flag| arg.as_ref().eq_ignore_ascii_case(flag)));
+    if by_args {
+        return Some(ModeSource::Args);
+    }
+    let by_env = env_value
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false);
+    by_env.then_some(ModeSource::Env)
+}
+
+/// 按进程启动参数与环境变量初始化（main 开头调用一次）
+///
+/// # 返回
+/// - 是否处于非交互模式
+pub fn init_from_process() -> bool {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let env_value = std::env::var(NON_INTERACTIVE_ENV).ok();
+    if let Some(source) = requested_source(&args, env_value.as_deref()) {
+        MODE.store(source as u8, Ordering::SeqCst);
+        eprintln!("[InteractionMode] 非交互模式已开启 source={}", ModeSource::as_str(source as u8).unwrap_or_default());
+    }
+    is_non_interactive()
+}
+
+/// 是否处于非交互模式（不得弹出任何对话框或等待用户确认）
+pub fn is_non_interactive() -> bool {
+    MODE.load(Ordering::SeqCst) != 0
+}
+
+/// 当前交互模式
+pub fn current() -> InteractionMode {
+    let mode = MODE.load(Ordering::SeqCst);
+    InteractionMode {
+        non_interactive: mode != 0,
+        source: ModeSource::as_str(mode).map(str::to_string),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_flag_or_env() {
+        assert_eq!(requested_source(["--silent"], None), Some(ModeSource::Args));
+        assert_eq!(requested_source(["--NON-INTERACTIVE"], Some("0")), Some(ModeSource::Args));
+        assert_eq!(requested_source(Vec::<String>::new(), Some(" true ")), Some(ModeSource::Env));
+        assert_eq!(requested_source(["--verbose"], Some("0")), None);
+        assert_eq!(requested_source(Vec::<String>::new(), None), None);
+    }
+}
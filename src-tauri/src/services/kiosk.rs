@@ -0,0 +1,175 @@
+/**
+ * 自助终端模式（kiosk）服务
+ * 职责：
+ * - 配置中存在 kiosk 块时，命令层在执行前检查操作是否在 allowedActions 中，策略以外的操作返回
+ *   [EASYPRINTER_CODE=ACTION_NOT_ALLOWED] 错误（api_* 命令的 error.code 即为 ACTION_NOT_ALLOWED）
+ * - allowedAreas 非空时，安装 / 重新安装只允许这些区域（含下级区域）中的打印机：名称与路径须匹配同一个配置条目，
+ *   不在配置中的打印机（或已配置打印机的名称搭配其他地址）一律拒绝
+ * - 策略随配置文件缓存在进程内，配置文件修改时间变化后重新读取
+ * - dryRun 安装不改动本机，不受限制；后台巡检的自动修复同样经过 install_printer，受同一策略限制；--cleanup-machine-artifacts 命令行清理不受限制
+ *
+ * 操作名称见 VALID_KIOSK_ACTIONS；前端可从 load_config 返回的配置中读取 kiosk 块隐藏对应按钮
+ */
+
+use crate::{KioskConfig, PrinterConfig, VALID_KIOSK_ACTIONS};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// 策略拒绝时的错误码
+pub const ACTION_NOT_ALLOWED: &str = "ACTION_NOT_ALLOWED";
+
+/// 读取到的策略与所在配置（未配置 kiosk 时为 None）
+type Policy = Option<Arc<(KioskConfig, PrinterConfig)>>;
+
+/// 进程内缓存的策略（配置文件路径与修改时间不变时直接复用）
+struct CachedPolicy {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    policy: Policy,
+}
+
+static POLICY_CACHE: Mutex<Option<CachedPolicy>> = Mutex::new(None);
+
+/// 校验 kiosk 配置（操作名称合法、区域存在于配置中）
+pub fn validate_config(kiosk: &KioskConfig, config: &PrinterConfig) -> Result<(), String> {
+    if let Some(action) = kiosk.allowed_actions.iter().find(|a| !VALID_KIOSK_ACTIONS.contains(&a.as_str())) {
+        return Err(format!(
+            "allowedActions 中的 \"{}\" 无效，可选值：{}",
+            action,
+            VALID_KIOSK_ACTIONS.join(" | ")
+        ));
+    }
+    let area_exists = |name: &str| config.cities.iter().any(|city| city.areas.iter().any(|area| area.area_name == name));
+    if let Some(area) = kiosk.allowed_areas.iter().find(|a| !area_exists(a)) {
+        return Err(format!("allowedAreas 中的区域 \"{}\" 不存在", area));
+    }
+    Ok(())
+}
+
+fn not_allowed(message: String) -> String {
+    eprintln!("[Kiosk] denied: {}", message);
+    format!("[EASYPRINTER_CODE={}] {}", ACTION_NOT_ALLOWED, message)
+}
+
+fn modified_at(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path).and_then(|m| m.modified()).ok()
+}
+
+/// 当前配置的 kiosk 策略（配置读取失败时视为未开启）
+fn current_policy() -> Policy {
+    let mut cache = POLICY_CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(cached) = cache.as_ref().filter(|c| c.modified.is_some() && modified_at(&c.path) == c.modified) {
+        return cached.policy.clone();
+    }
+    match crate::load_local_config() {
+        Ok((mut config, path)) => {
+            let policy = config.kiosk.take().map(|kiosk| Arc::new((kiosk, config)));
+            *cache = Some(CachedPolicy { modified: modified_at(&path), path, policy: policy.clone() });
+            policy
+        }
+        Err(_) => {
+            *cache = None;
+            None
+        }
+    }
+}
+
+fn action_allowed(kiosk: &KioskConfig, action: &str) -> bool {
+    kiosk.allowed_actions.iter().any(|a| a == action)
+}
+
+/// 打印机是否属于允许的区域（名称与路径须匹配同一台配置打印机，区域路径上任一级在 allowedAreas 中即允许）
+fn printer_in_allowed_area(kiosk: &KioskConfig, config: &PrinterConfig, name: &str, path: &str) -> bool {
+    if kiosk.allowed_areas.is_empty() {
+        return true;
+    }
+    config.cities.iter().any(|city| {
+        city.areas
+            .iter()
+            .filter(|area| area.printers.iter().any(|p| p.name == name && p.path == path))
+            .any(|area| city.area_path(&area.area_name).iter().any(|a| kiosk.allowed_areas.contains(a)))
+    })
+}
+
+/// 检查操作是否允许
+///
+/// # 参数
+/// - `action`: VALID_KIOSK_ACTIONS 中的操作名称
+pub fn check_action(action: &str) -> Result<(), String> {
+    match current_policy().as_deref() {
+        Some((kiosk, _)) if !action_allowed(kiosk, action) => {
+            Err(not_allowed(format!("自助终端模式下不允许执行此操作（{}）", action)))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 检查安装 / 重新安装是否允许（操作 install 与区域限制）
+///
+/// # 参数
+/// - `name`: 打印机名称
+/// - `path`: 打印机路径
+pub fn check_install(name: &str, path: &str) -> Result<(), String> {
+    let Some(policy) = current_policy() else {
+        return Ok(());
+    };
+    let (kiosk, config) = &*policy;
+    if !action_allowed(kiosk, "install") {
+        return Err(not_allowed("自助终端模式下不允许安装打印机（install）".to_string()));
+    }
+    if !printer_in_allowed_area(kiosk, config, name, path) {
+        return Err(not_allowed(format!(
+            "自助终端模式下只能安装以下区域的打印机：{}（\"{}\" 不在其中）",
+            kiosk.allowed_areas.join("、"),
+            name
+        )));
+    }
+    Ok(())
+}
+
+/// 按配置中的打印机名称检查安装是否允许（只知道配置名称的命令使用，路径取自配置条目）
+pub fn check_config_printer(name: &str) -> Result<(), String> {
+    let path = current_policy()
+        .as_deref()
+        .and_then(|(_, config)| super::printer_watch::find_config_printer(config, name))
+        .map(|printer| printer.path)
+        .unwrap_or_default();
+    check_install(name, &path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restricts_installs_to_allowed_areas_and_children() {
+        let config: PrinterConfig = serde_json::from_value(serde_json::json!({
+            "driverCatalog": {},
+            "cities": [{
+                "cityId": "sh",
+                "cityName": "上海",
+                "areas": [
+                    { "areaName": "图书馆", "printers": [] },
+                    { "areaName": "2楼阅览室", "parent": "图书馆", "printers": [{ "name": "阅览室 HP", "path": "10.0.2.10" }] },
+                    { "areaName": "办公区", "printers": [{ "name": "财务 Canon", "path": "10.0.9.20" }] }
+                ]
+            }]
+        }))
+        .unwrap();
+        let kiosk = KioskConfig {
+            allowed_areas: vec!["图书馆".to_string()],
+            allowed_actions: vec!["install".to_string(), "testPage".to_string()],
+        };
+        assert!(validate_config(&kiosk, &config).is_ok());
+        assert!(printer_in_allowed_area(&kiosk, &config, "阅览室 HP", "10.0.2.10"));
+        assert!(!printer_in_allowed_area(&kiosk, &config, "阅览室 HP", "10.0.0.99"));
+        assert!(!printer_in_allowed_area(&kiosk, &config, "", "10.0.2.10"));
+        assert!(!printer_in_allowed_area(&kiosk, &config, "财务 Canon", "10.0.9.20"));
+        assert!(!printer_in_allowed_area(&kiosk, &config, "不在配置中", "10.0.0.1"));
+        assert!(action_allowed(&kiosk, "testPage") && !action_allowed(&kiosk, "delete"));
+
+        let invalid = KioskConfig { allowed_areas: vec!["后台".to_string()], allowed_actions: vec!["format".to_string()] };
+        assert!(validate_config(&invalid, &config).unwrap_err().contains("format"));
+    }
+}
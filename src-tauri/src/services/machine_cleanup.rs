@@ -0,0 +1,154 @@
+/**
+ * 本机残留清理服务（卸载前清理）
+ * 职责：
+ * - cleanup_machine_artifacts：删除应用在安装目录以外创建的全部内容，逐项报告处理结果
+ *   - 系统对象（平台层）：委托安装计划任务、事件日志事件源、凭据管理器中的 ePrinty/ 凭据
+ *   - 临时目录中的遗留文件（更新安装包、安装脚本、测试页文件）
+ *   - 数据目录：meta / 日志 / 图片缓存 / 任务工作目录 / 委托安装交换目录 / WebView2 用户数据等（见 fs_paths::get_app_data_roots）
+ * - 以 --cleanup-machine-artifacts 启动应用（安装包未配置卸载钩子，由部署脚本在卸载前执行）：不显示窗口，
+ *   执行清理后把 JSON 报告写到标准输出并退出
+ *   （退出码 0 = 没有失败项，1 = 有失败项）
+ *
+ * 已安装的打印机队列、驱动库中的驱动不属于残留，不删除；安装目录内的驱动包缓存由卸载程序删除
+ */
+
+use crate::platform::ArtifactRemoval;
+use serde::Serialize;
+use std::path::Path;
+
+/// 命令行清理模式的参数
+pub const CLEANUP_ARG: &str = "--cleanup-machine-artifacts";
+
+/// 临时目录中由应用创建的文件名前缀
+const TEMP_FILE_PREFIXES: [&str; 4] = ["easyPrinter_update_", "install_printer_", "printer_test_", "prnport"];
+
+/// 清理报告
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MachineCleanupReport {
+    /// 没有失败项
+    pub success: bool,
+    pub removals: Vec<ArtifactRemoval>,
+}
+
+/// 删除临时目录中应用遗留的文件
+fn remove_temp_files(temp_dir: &Path) -> Vec<ArtifactRemoval> {
+    let Ok(entries) = std::fs::read_dir(temp_dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.path().is_file())
+        .filter(|entry| {
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            TEMP_FILE_PREFIXES.iter().any(|prefix| name.starts_with(prefix))
+        })
+        .map(|entry| {
+            let path = entry.path();
+            let target = path.display().to_string();
+            match std::fs::remove_file(&path) {
+                Ok(()) => ArtifactRemoval::removed("file", &target),
+                Err(e) => ArtifactRemoval::failed("file", &target, format!("删除失败: {}", e)),
+            }
+        })
+        .collect()
+}
+
+/// 删除数据目录（不存在时记为 notFound）
+fn remove_directory(dir: &Path) -> ArtifactRemoval {
+    let target = dir.display().to_string();
+    if !dir.exists() {
+        return ArtifactRemoval::not_found("directory", &target);
+    }
+    match std::fs::remove_dir_all(dir) {
+        Ok(()) => ArtifactRemoval::removed("directory", &target),
+        Err(e) => ArtifactRemoval::failed("directory", &target, format!("删除失败（文件可能被占用或需要管理员权限）: {}", e)),
+    }
+}
+
+/// 清理本机残留
+///
+/// # 参数
+/// - `app`: 用于定位应用配置目录（macOS）
+///
+/// # 返回
+/// 每一项的处理结果；存在进行中的安装任务时不清理，直接返回失败项
+pub fn cleanup_machine_artifacts(app: &tauri::AppHandle) -> MachineCleanupReport {
+    let active = crate::platform::active_install_jobs();
+    if !active.is_empty() {
+        let names = active.iter().map(|job| job.printer_name.as_str()).collect::<Vec<_>>().join("、");
+        return MachineCleanupReport {
+            success: false,
+            removals: vec![ArtifactRemoval::failed("installJob", &names, "仍有安装任务在进行，请等待完成后再清理".to_string())],
+        };
+    }
+
+    // 计划任务名称来自本地配置，需在删除数据目录之前读取
+    let task_names: Vec<String> = crate::load_local_config()
+        .ok()
+        .and_then(|(config, _)| config.delegated_install)
+        .map(|delegated| vec![delegated.task_name])
+        .unwrap_or_default();
+
+    let mut removals = crate::platform::remove_system_artifacts(&task_names);
+    removals.extend(remove_temp_files(&std::env::temp_dir()));
+    // 数据目录最后删除（平台层的文件日志写在其中，删除后不再写日志）
+    removals.extend(super::fs_paths::get_app_data_roots(app).iter().map(|dir| remove_directory(dir)));
+
+    for removal in &removals {
+        eprintln!(
+            "[MachineCleanup] kind={} status={} target=\"{}\"{}",
+            removal.kind,
+            removal.status,
+            removal.target,
+            removal.message.as_deref().map(|m| format!(" message=\"{}\"", m)).unwrap_or_default()
+        );
+    }
+    let success = removals.iter().all(|r| r.status != "failed");
+    MachineCleanupReport { success, removals }
+}
+
+/// 当前进程是否以命令行清理模式启动
+pub fn cleanup_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg.eq_ignore_ascii_case(CLEANUP_ARG))
+}
+
+/// 命令行清理：执行清理并把报告写到标准输出
+///
+/// # 返回
+/// - 进程退出码（0 = 没有失败项，1 = 有失败项）
+pub fn run_from_command_line(app: &tauri::AppHandle) -> i32 {
+    let report = cleanup_machine_artifacts(app);
+    match serde_json::to_string_pretty(&report) {
+        Ok(json) => println!("{}", json),
+        Err(e) => eprintln!("[MachineCleanup] 序列化清理报告失败: {}", e),
+    }
+    if report.success {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removes_only_app_temp_files() {
+        let dir = std::env::temp_dir().join(format!("eprinty_cleanup_test_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("easyPrinter_update_0123456789abcdef.exe.part"), b"x").unwrap();
+        std::fs::write(dir.join("install_printer_42.ps1"), b"x").unwrap();
+        std::fs::write(dir.join("other_app.tmp"), b"x").unwrap();
+
+        let removals = remove_temp_files(&dir);
+        assert_eq!(removals.len(), 2);
+        assert!(removals.iter().all(|r| r.kind == "file" && r.status == "removed"));
+        assert!(dir.join("other_app.tmp").exists());
+
+        assert_eq!(remove_directory(&dir.join("missing")).status, "notFound");
+        assert_eq!(remove_directory(&dir).status, "removed");
+    }
+}
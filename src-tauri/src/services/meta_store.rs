@@ -0,0 +1,329 @@
+/**
+ * 本地状态（meta / history）存储层
+ * 职责：
+ * - 为本地 JSON 状态文件提供并发安全的读写（多开实例、后台 reconcile 与安装任务可能同时写入）
+ * - 进程内：全局互斥锁串行化写操作
+ * - 跨进程：基于 `{文件名}.lock` 的建议性文件锁（create_new 原子创建，超时视为陈旧锁）
+ * - 写入统一走 temp -> rename 原子替换，读取方永远看到完整文件
+ * - schemaVersion 信封，为后续数据格式迁移预留入口
+ *
+ * 注意：加锁可能阻塞等待（最长 LOCK_TIMEOUT），异步上下文中请放入 spawn_blocking 调用
+ */
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime};
+
+/// 获取文件锁的最长等待时间
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 获取文件锁的重试间隔
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 超过该时长未释放的锁文件视为陈旧锁（持有进程崩溃等），允许抢占
+const STALE_LOCK_AGE: Duration = Duration::from_secs(30);
+
+/// 进程内写锁（串行化本进程内所有 meta 写操作）
+static PROCESS_LOCK: Mutex<()> = Mutex::new(());
+
+/// 数据迁移函数：将 `from_version` 版本的 data 升级到当前 schemaVersion
+pub type MigrateFn = fn(from_version: u32, data: serde_json::Value) -> Result<serde_json::Value, String>;
+
+/// 磁盘上的信封格式
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Envelope<T> {
+    schema_version: u32,
+    updated_at: i64,
+    data: T,
+}
+
+/// 跨进程建议性文件锁（Drop 时释放）
+pub struct FileLock {
+    lock_path: PathBuf,
+}
+
+impl FileLock {
+    /// 获取目标文件的独占锁
+    ///
+    /// # 参数
+    /// - `target`: 被保护的文件路径（锁文件为同目录下的 `{文件名}.lock`）
+    ///
+    /// # 返回
+    /// - `Ok(FileLock)`: 加锁成功
+    /// - `Err(String)`: 等待超时或无法创建锁文件
+    pub fn acquire(target: &Path) -> Result<Self, String> {
+        let file_name = target
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("无效的文件路径: {}", target.display()))?;
+        let lock_path = target.with_file_name(format!("{}.lock", file_name));
+
+        if let Some(parent) = lock_path.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+        }
+
+        let started = std::time::Instant::now();
+        loop {
+            match OpenOptions::new().write(true).create_new(true).open(&lock_path) {
+                Ok(mut file) => {
+                    let _ = write!(file, "pid={} ts={}", std::process::id(), now_ms());
+                    return Ok(Self { lock_path });
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                    if is_stale_lock(&lock_path) {
+                        eprintln!("[MetaStore] 发现陈旧锁文件，强制释放: {}", lock_path.display());
+                        let _ = fs::remove_file(&lock_path);
+                        continue;
+                    }
+                    if started.elapsed() >= LOCK_TIMEOUT {
+                        return Err(format!(
+                            "等待文件锁超时（{} 秒）: {}，可能有其他实例正在写入",
+                            LOCK_TIMEOUT.as_secs(),
+                            lock_path.display()
+                        ));
+                    }
+                    std::thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(e) => {
+                    return Err(format!("创建锁文件失败 {}: {}", lock_path.display(), e));
+                }
+            }
+        }
+    }
+}
+
+impl Drop for FileLock {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.lock_path);
+    }
+}
+
+/// 锁文件是否已陈旧（修改时间超过 STALE_LOCK_AGE）
+fn is_stale_lock(lock_path: &Path) -> bool {
+    fs::metadata(lock_path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map(|age| age > STALE_LOCK_AGE)
+        .unwrap_or(false)
+}
+
+fn now_ms() -> i64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as i64
+}
+
+/// 原子写入文件（temp -> rename）
+///
+/// 调用方负责持有 FileLock；本函数只保证读取方不会看到写了一半的文件
+pub fn write_atomic(path: &Path, content: &[u8]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("创建目录失败: {}", e))?;
+    }
+
+    let file_name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("无效的文件路径: {}", path.display()))?;
+    let temp_path = path.with_file_name(format!("{}.tmp", file_name));
+
+    {
+        let mut file = fs::File::create(&temp_path)
+            .map_err(|e| format!("创建临时文件失败 {}: {}", temp_path.display(), e))?;
+        file.write_all(content)
+            .map_err(|e| format!("写入临时文件失败 {}: {}", temp_path.display(), e))?;
+        file.sync_all()
+            .map_err(|e| format!("同步临时文件失败 {}: {}", temp_path.display(), e))?;
+    }
+
+    fs::rename(&temp_path, path).map_err(|e| {
+        let _ = fs::remove_file(&temp_path);
+        format!("重命名临时文件失败 {}: {}", path.display(), e)
+    })
+}
+
+/// 带 schemaVersion 的 JSON 状态文件
+///
+/// 文件位于 `fs_paths::get_meta_dir()/{name}.json`，格式：
+/// `{ "schemaVersion": 1, "updatedAt": 1700000000000, "data": {...} }`
+pub struct MetaStore<T> {
+    name: &'static str,
+    schema_version: u32,
+    migrate: Option<MigrateFn>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T: Serialize + DeserializeOwned + Default> MetaStore<T> {
+    /// 创建存储描述（不触碰磁盘）
+    ///
+    /// # 参数
+    /// - `name`: 文件名（不含扩展名），如 "install_history"
+    /// - `schema_version`: 当前数据格式版本（从 1 开始）
+    pub const fn new(name: &'static str, schema_version: u32) -> Self {
+        Self {
+            name,
+            schema_version,
+            migrate: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// 指定旧版本数据的迁移函数
+    #[allow(dead_code)] // 预留：首次升级 schemaVersion 时使用
+    pub const fn with_migration(mut self, migrate: MigrateFn) -> Self {
+        self.migrate = Some(migrate);
+        self
+    }
+
+    /// 获取状态文件路径
+    pub fn path(&self) -> Result<PathBuf, String> {
+        Ok(super::fs_paths::get_meta_dir()?.join(format!("{}.json", self.name)))
+    }
+
+    /// 读取当前数据（文件不存在时返回默认值）
+    ///
+    /// 写入为原子替换，读取无需持有文件锁
+    pub fn load(&self) -> Result<T, String> {
+        self.load_from(&self.path()?)
+    }
+
+    /// 读-改-写（持有进程内锁 + 文件锁，期间其他实例的写入会排队等待）
+    ///
+    /// # 参数
+    /// - `f`: 修改闭包，返回值原样透传给调用方
+    ///
+    /// # 返回
+    /// - `Ok(R)`: 修改已持久化
+    /// - `Err(String)`: 加锁、读取、迁移或写入失败（磁盘数据保持不变）
+    pub fn update<R>(&self, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        self.update_at(&self.path()?, f)
+    }
+
+    fn load_from(&self, path: &Path) -> Result<T, String> {
+        if !path.exists() {
+            return Ok(T::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("读取状态文件失败 {}: {}", path.display(), e))?;
+        let raw: serde_json::Value = serde_json::from_str(&content)
+            .map_err(|e| format!("解析状态文件失败 {}: {}", path.display(), e))?;
+
+        let file_version = raw
+            .get("schemaVersion")
+            .and_then(|v| v.as_u64())
+            .ok_or_else(|| format!("状态文件缺少 schemaVersion: {}", path.display()))? as u32;
+        let data = raw.get("data").cloned().unwrap_or(serde_json::Value::Null);
+
+        let data = if file_version == self.schema_version {
+            data
+        } else if file_version > self.schema_version {
+            return Err(format!(
+                "状态文件 {} 由更高版本的应用写入（schemaVersion={}，当前支持 {}），请升级应用",
+                path.display(),
+                file_version,
+                self.schema_version
+            ));
+        } else {
+            let migrate = self.migrate.ok_or_else(|| {
+                format!(
+                    "状态文件 {} 的 schemaVersion={} 低于当前版本 {}，但未提供迁移函数",
+                    path.display(),
+                    file_version,
+                    self.schema_version
+                )
+            })?;
+            eprintln!(
+                "[MetaStore] name={} migrate from={} to={}",
+                self.name, file_version, self.schema_version
+            );
+            migrate(file_version, data)?
+        };
+
+        serde_json::from_value(data)
+            .map_err(|e| format!("状态文件数据格式错误 {}: {}", path.display(), e))
+    }
+
+    fn update_at<R>(&self, path: &Path, f: impl FnOnce(&mut T) -> R) -> Result<R, String> {
+        let _process_guard = PROCESS_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _file_lock = FileLock::acquire(path)?;
+
+        let mut data = self.load_from(path)?;
+        let result = f(&mut data);
+
+        let envelope = Envelope {
+            schema_version: self.schema_version,
+            updated_at: now_ms(),
+            data,
+        };
+        let json = serde_json::to_vec_pretty(&envelope)
+            .map_err(|e| format!("序列化状态文件失败: {}", e))?;
+        write_atomic(path, &json)?;
+
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, Serialize, Deserialize, PartialEq)]
+    struct Counter {
+        count: u32,
+    }
+
+    fn migrate_counter(from_version: u32, data: serde_json::Value) -> Result<serde_json::Value, String> {
+        match from_version {
+            1 => Ok(serde_json::json!({ "count": data.get("legacyCount").cloned().unwrap_or_default() })),
+            _ => Err(format!("不支持的版本: {}", from_version)),
+        }
+    }
+
+    #[test]
+    fn concurrent_updates_are_not_lost() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.json");
+
+        let handles: Vec<_> = (0..8)
+            .map(|_| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    let store: MetaStore<Counter> = MetaStore::new("counter", 1);
+                    for _ in 0..10 {
+                        store.update_at(&path, |c| c.count += 1).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        let store: MetaStore<Counter> = MetaStore::new("counter", 1);
+        assert_eq!(store.load_from(&path).unwrap(), Counter { count: 80 });
+        assert!(!path.with_file_name("counter.json.lock").exists());
+    }
+
+    #[test]
+    fn schema_versions_are_migrated_or_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("counter.json");
+        fs::write(&path, r#"{"schemaVersion":1,"updatedAt":0,"data":{"legacyCount":7}}"#).unwrap();
+
+        let store: MetaStore<Counter> = MetaStore::new("counter", 2).with_migration(migrate_counter);
+        assert_eq!(store.load_from(&path).unwrap(), Counter { count: 7 });
+
+        let old_store: MetaStore<Counter> = MetaStore::new("counter", 1);
+        fs::write(&path, r#"{"schemaVersion":3,"updatedAt":0,"data":{"count":1}}"#).unwrap();
+        assert!(old_store.load_from(&path).is_err());
+    }
+}
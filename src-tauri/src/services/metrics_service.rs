@@ -0,0 +1,206 @@
+/**
+ * 安装指标汇总服务（内置管理视图的图表数据）
+ * 职责：
+ * - 按统计周期（day / week / month / all）汇总安装历史：成功率、平均耗时、各步骤平均耗时
+ * - 统计最常见的错误码与安装次数最多的打印机
+ *
+ * 数据来源为 install_history 存储；dryRun 记录不计入统计。安装历史最多保留 MAX_RECORDS（200）条，
+ * 达到上限后更早的记录已被丢弃，周期起点早于最旧记录时汇总不完整（historyCapped=true）
+ */
+
+use super::install_history::{InstallRecord, MAX_RECORDS};
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// 错误码 / 打印机排行保留的条数
+const TOP_N: usize = 10;
+
+/// 没有错误码的失败记录归入该分类
+const UNKNOWN_ERROR_CODE: &str = "UNKNOWN";
+
+/// 单个步骤的平均耗时
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StepMetric {
+    pub step: String,
+    pub count: usize,
+    pub avg_duration_ms: i64,
+}
+
+/// 排行项（错误码 / 打印机名称 + 次数）
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CountEntry {
+    pub key: String,
+    pub count: usize,
+}
+
+/// 指标汇总
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSummary {
+    pub period: String,
+    /// 统计起点（毫秒时间戳；period=all 时为 None）
+    pub since: Option<i64>,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    /// 成功率（0.0-1.0；没有记录时为 0）
+    pub success_rate: f64,
+    pub avg_duration_ms: i64,
+    /// 按步骤 ID 排序
+    pub step_durations: Vec<StepMetric>,
+    /// 按次数降序
+    pub top_error_codes: Vec<CountEntry>,
+    /// 按安装次数降序（含失败的安装）
+    pub top_printers: Vec<CountEntry>,
+    /// 安装历史已达保留上限且最旧的记录晚于统计起点：周期内更早的安装已被丢弃，汇总不完整
+    pub history_capped: bool,
+}
+
+/// 统计周期对应的时长（毫秒；all 为 None）
+fn period_span_ms(period: &str) -> Result<Option<i64>, String> {
+    const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+    match period {
+        "day" => Ok(Some(DAY_MS)),
+        "week" => Ok(Some(7 * DAY_MS)),
+        "month" => Ok(Some(30 * DAY_MS)),
+        "all" => Ok(None),
+        other => Err(format!("不支持的统计周期 \"{}\"（可选 day / week / month / all）", other)),
+    }
+}
+
+/// 计数降序、同数按名称排序后取前 TOP_N 项
+fn top_entries(counts: HashMap<String, usize>) -> Vec<CountEntry> {
+    let mut entries: Vec<CountEntry> = counts.into_iter().map(|(key, count)| CountEntry { key, count }).collect();
+    entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.key.cmp(&b.key)));
+    entries.truncate(TOP_N);
+    entries
+}
+
+/// 汇总安装记录
+///
+/// # 参数
+/// - `records`: 安装历史记录
+/// - `period`: 统计周期
+/// - `now`: 当前时间（毫秒时间戳）
+fn summarize(records: &[InstallRecord], period: &str, now: i64) -> Result<MetricsSummary, String> {
+    let since = period_span_ms(period)?.map(|span| now - span);
+    let history_capped = records.len() >= MAX_RECORDS
+        && records
+            .iter()
+            .map(|r| r.finished_at)
+            .min()
+            .is_some_and(|oldest| since.is_none_or(|since| oldest > since));
+    let records: Vec<&InstallRecord> = records
+        .iter()
+        .filter(|r| !r.dry_run && since.is_none_or(|since| r.finished_at >= since))
+        .collect();
+
+    let total = records.len();
+    let succeeded = records.iter().filter(|r| r.success).count();
+    let total_duration: i64 = records.iter().map(|r| (r.finished_at - r.started_at).max(0)).sum();
+
+    let mut steps: HashMap<String, (usize, i64)> = HashMap::new();
+    let mut error_codes: HashMap<String, usize> = HashMap::new();
+    let mut printers: HashMap<String, usize> = HashMap::new();
+    for record in &records {
+        for step in &record.step_durations {
+            let entry = steps.entry(step.step.clone()).or_default();
+            entry.0 += 1;
+            entry.1 += step.duration_ms;
+        }
+        if !record.success {
+            let code = record.error_code.clone().unwrap_or_else(|| UNKNOWN_ERROR_CODE.to_string());
+            *error_codes.entry(code).or_default() += 1;
+        }
+        let printer = record.display_name.clone().unwrap_or_else(|| record.printer_name.clone());
+        *printers.entry(printer).or_default() += 1;
+    }
+
+    let mut step_durations: Vec<StepMetric> = steps
+        .into_iter()
+        .map(|(step, (count, sum))| StepMetric { step, count, avg_duration_ms: sum / count as i64 })
+        .collect();
+    step_durations.sort_by(|a, b| a.step.cmp(&b.step));
+
+    Ok(MetricsSummary {
+        period: period.to_string(),
+        since,
+        total,
+        succeeded,
+        failed: total - succeeded,
+        success_rate: if total == 0 { 0.0 } else { succeeded as f64 / total as f64 },
+        avg_duration_ms: if total == 0 { 0 } else { total_duration / total as i64 },
+        step_durations,
+        top_error_codes: top_entries(error_codes),
+        top_printers: top_entries(printers),
+        history_capped,
+    })
+}
+
+/// 获取安装指标汇总
+///
+/// # 参数
+/// - `period`: 统计周期 "day" | "week" | "month" | "all"（默认 "week"）
+///
+/// # 返回
+/// - `Ok(MetricsSummary)`: 汇总结果
+/// - `Err(String)`: 周期无效或读取安装历史失败
+pub async fn get_metrics_summary(period: Option<String>) -> Result<MetricsSummary, String> {
+    let period = period.map(|p| p.trim().to_lowercase()).filter(|p| !p.is_empty()).unwrap_or_else(|| "week".to_string());
+    let records = super::install_history::list_install_history(None).await?;
+    summarize(&records, &period, chrono::Utc::now().timestamp_millis())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::install_history::StepDuration;
+
+    fn record(printer: &str, success: bool, dry_run: bool, finished_at: i64, code: Option<&str>, steps: &[(&str, i64)]) -> InstallRecord {
+        InstallRecord {
+            job_id: format!("job_{}", finished_at),
+            printer_name: printer.to_string(),
+            success,
+            dry_run,
+            started_at: finished_at - 1000,
+            finished_at,
+            error_code: code.map(str::to_string),
+            step_durations: steps.iter().map(|(step, ms)| StepDuration { step: step.to_string(), duration_ms: *ms }).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn summarizes_records_within_period() {
+        let day = 24 * 60 * 60 * 1000;
+        let now = 100 * day;
+        let records = vec![
+            record("A", true, false, now - 10, None, &[("driver.download", 400), ("device.ensureQueue", 100)]),
+            record("A", false, false, now - 20, Some("WIN_UNKNOWN_PRINTER_DRIVER"), &[("driver.download", 200)]),
+            record("B", false, false, now - 30, None, &[]),
+            record("B", true, true, now - 40, None, &[]),
+            record("C", true, false, now - 2 * day, None, &[]),
+        ];
+
+        let summary = summarize(&records, "day", now).unwrap();
+        assert_eq!((summary.total, summary.succeeded, summary.failed), (3, 1, 2));
+        assert_eq!(summary.avg_duration_ms, 1000);
+        assert_eq!(summary.step_durations[0].step, "device.ensureQueue");
+        assert_eq!((summary.step_durations[1].count, summary.step_durations[1].avg_duration_ms), (2, 300));
+        let codes: Vec<&str> = summary.top_error_codes.iter().map(|e| e.key.as_str()).collect();
+        assert_eq!(codes, vec![UNKNOWN_ERROR_CODE, "WIN_UNKNOWN_PRINTER_DRIVER"]);
+        assert_eq!((summary.top_printers[0].key.as_str(), summary.top_printers[0].count), ("A", 2));
+
+        assert_eq!(summarize(&records, "all", now).unwrap().total, 4);
+        assert!(summarize(&records, "year", now).is_err());
+        assert!(!summary.history_capped);
+
+        // 历史已达上限：最旧记录晚于周期起点时标记为不完整
+        let full: Vec<InstallRecord> = (0..MAX_RECORDS as i64).map(|i| record("A", true, false, now - i * 1000, None, &[])).collect();
+        assert!(summarize(&full, "day", now).unwrap().history_capped);
+        assert!(summarize(&full, "all", now).unwrap().history_capped);
+        assert!(!summarize(&full, "day", now + day).unwrap().history_capped);
+    }
+}
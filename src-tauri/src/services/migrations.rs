@@ -0,0 +1,270 @@
+/**
+ * 本地数据格式迁移服务
+ * 职责：
+ * - 记录 meta 目录整体数据格式版本（data_format.json），启动时按顺序执行未完成的迁移步骤
+ * - 迁移前备份涉及的文件到 meta/backups/format-v{from}-v{to}-{时间戳}/，失败时还原并停止后续步骤
+ * - 提供 rewrite_meta_file：将 MetaStore 信封文件就地升级到新的 schemaVersion（迁移步骤使用）
+ *
+ * 新增格式变更时：在 MIGRATIONS 末尾追加一步（version 递增），列出需要备份的文件
+ * 单个 MetaStore 的读时迁移（with_migration）仍然保留，用于兜底未经过启动迁移的文件
+ */
+
+use super::meta_store::{write_atomic, FileLock};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// 格式状态文件名（位于 meta 目录）
+const STATE_FILE: &str = "data_format.json";
+
+/// 备份目录名（位于 meta 目录）
+const BACKUP_DIR: &str = "backups";
+
+/// 单个迁移步骤：将 meta 目录从 version - 1 升级到 version
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// 迁移前需要备份的文件（相对 meta 目录，不存在的文件跳过）
+    pub files: &'static [&'static str],
+    pub run: fn(meta_dir: &Path) -> Result<(), String>,
+}
+
+/// 按版本顺序排列的迁移步骤
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "记录初始数据格式（install_history / printer_hosts / fleet_registration / config_http_cache 均为 schemaVersion=1）",
+    files: &[],
+    run: |_| Ok(()),
+}];
+
+/// 已执行的迁移记录
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedMigration {
+    pub version: u32,
+    pub description: String,
+    pub applied_at: i64,
+    /// 迁移前的备份目录（无需备份时为空）
+    pub backup_dir: Option<String>,
+}
+
+/// data_format.json 内容
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct FormatState {
+    format_version: u32,
+    #[serde(default)]
+    applied: Vec<AppliedMigration>,
+}
+
+/// 启动迁移结果
+#[derive(Debug, Clone, Default)]
+pub struct MigrationReport {
+    pub from_version: u32,
+    pub to_version: u32,
+    pub applied: Vec<AppliedMigration>,
+}
+
+fn now_ms() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+fn load_state(path: &Path) -> Result<FormatState, String> {
+    if !path.exists() {
+        return Ok(FormatState::default());
+    }
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("读取数据格式状态失败 {}: {}", path.display(), e))?;
+    serde_json::from_str(&content).map_err(|e| format!("解析数据格式状态失败 {}: {}", path.display(), e))
+}
+
+fn save_state(path: &Path, state: &FormatState) -> Result<(), String> {
+    let json = serde_json::to_vec_pretty(state).map_err(|e| format!("序列化数据格式状态失败: {}", e))?;
+    write_atomic(path, &json)
+}
+
+/// 备份迁移涉及的文件，返回备份目录（没有需要备份的文件时返回 None）
+fn backup_files(meta_dir: &Path, migration: &Migration, from_version: u32) -> Result<Option<PathBuf>, String> {
+    let existing: Vec<&str> = migration
+        .files
+        .iter()
+        .copied()
+        .filter(|f| meta_dir.join(f).is_file())
+        .collect();
+    if existing.is_empty() {
+        return Ok(None);
+    }
+
+    let backup_dir = meta_dir.join(BACKUP_DIR).join(format!(
+        "format-v{}-v{}-{}",
+        from_version,
+        migration.version,
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    fs::create_dir_all(&backup_dir).map_err(|e| format!("创建备份目录失败 {}: {}", backup_dir.display(), e))?;
+    for file in existing {
+        let target = backup_dir.join(file);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(|e| format!("创建备份目录失败 {}: {}", parent.display(), e))?;
+        }
+        fs::copy(meta_dir.join(file), &target).map_err(|e| format!("备份文件失败 {}: {}", file, e))?;
+    }
+    Ok(Some(backup_dir))
+}
+
+/// 从备份目录还原文件（迁移失败时调用）
+fn restore_files(meta_dir: &Path, migration: &Migration, backup_dir: &Path) {
+    for file in migration.files {
+        let source = backup_dir.join(file);
+        if source.is_file() {
+            if let Err(e) = fs::copy(&source, meta_dir.join(file)) {
+                eprintln!("[Migrations] restore failed file={} error={}", file, e);
+            }
+        }
+    }
+}
+
+/// 在指定 meta 目录执行迁移步骤
+fn run_migrations_in(meta_dir: &Path, migrations: &[Migration]) -> Result<MigrationReport, String> {
+    let state_path = meta_dir.join(STATE_FILE);
+    let _file_lock = FileLock::acquire(&state_path)?;
+
+    let mut state = load_state(&state_path)?;
+    let target_version = migrations.last().map(|m| m.version).unwrap_or(0);
+    let mut report = MigrationReport {
+        from_version: state.format_version,
+        to_version: state.format_version,
+        applied: Vec::new(),
+    };
+
+    if state.format_version > target_version {
+        eprintln!(
+            "[Migrations] data format v{} is newer than supported v{}, skip (written by a newer app)",
+            state.format_version, target_version
+        );
+        return Ok(report);
+    }
+
+    let start_version = state.format_version;
+    for migration in migrations.iter().filter(|m| m.version > start_version) {
+        let from_version = state.format_version;
+        let backup_dir = backup_files(meta_dir, migration, from_version)?;
+        eprintln!(
+            "[Migrations] apply v{} -> v{} \"{}\" backup={:?}",
+            from_version, migration.version, migration.description, backup_dir
+        );
+
+        if let Err(e) = (migration.run)(meta_dir) {
+            if let Some(dir) = &backup_dir {
+                restore_files(meta_dir, migration, dir);
+            }
+            return Err(format!(
+                "数据格式迁移 v{} -> v{} 失败（已还原备份）: {}",
+                from_version, migration.version, e
+            ));
+        }
+
+        let applied = AppliedMigration {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied_at: now_ms(),
+            backup_dir: backup_dir.map(|d| d.display().to_string()),
+        };
+        state.format_version = migration.version;
+        state.applied.push(applied.clone());
+        // 每步完成即落盘，中途失败时已完成的步骤不会重复执行
+        save_state(&state_path, &state)?;
+        report.applied.push(applied);
+        report.to_version = migration.version;
+    }
+
+    Ok(report)
+}
+
+/// 启动时执行未完成的数据格式迁移（应在读取任何 meta 状态之前调用）
+///
+/// # 返回
+/// - `Ok(MigrationReport)`: 迁移前后版本及本次执行的步骤
+/// - `Err(String)`: 某一步迁移失败（该步已还原备份，后续步骤未执行）
+pub fn run_startup_migrations() -> Result<MigrationReport, String> {
+    let meta_dir = super::fs_paths::get_meta_dir()?;
+    run_migrations_in(&meta_dir, MIGRATIONS)
+}
+
+/// 将 MetaStore 信封文件的 data 就地升级到新的 schemaVersion（文件不存在或已是目标版本时跳过）
+///
+/// # 参数
+/// - `path`: 状态文件路径
+/// - `to_version`: 升级后的 schemaVersion（需与对应 MetaStore::new 的版本一致）
+/// - `migrate`: 数据转换函数，参数为文件当前 schemaVersion 与 data
+#[allow(dead_code)] // 预留：首个需要改写 meta 文件的迁移步骤使用
+pub fn rewrite_meta_file(
+    path: &Path,
+    to_version: u32,
+    migrate: impl FnOnce(u32, serde_json::Value) -> Result<serde_json::Value, String>,
+) -> Result<bool, String> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let _file_lock = FileLock::acquire(path)?;
+    let content = fs::read_to_string(path).map_err(|e| format!("读取状态文件失败 {}: {}", path.display(), e))?;
+    let mut raw: serde_json::Value =
+        serde_json::from_str(&content).map_err(|e| format!("解析状态文件失败 {}: {}", path.display(), e))?;
+    let from_version = raw.get("schemaVersion").and_then(|v| v.as_u64()).unwrap_or(0) as u32;
+    if from_version >= to_version {
+        return Ok(false);
+    }
+
+    let data = raw.get("data").cloned().unwrap_or(serde_json::Value::Null);
+    raw["data"] = migrate(from_version, data)?;
+    raw["schemaVersion"] = serde_json::json!(to_version);
+    raw["updatedAt"] = serde_json::json!(now_ms());
+    let json = serde_json::to_vec_pretty(&raw).map_err(|e| format!("序列化状态文件失败: {}", e))?;
+    write_atomic(path, &json)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rename_count(meta_dir: &Path) -> Result<(), String> {
+        rewrite_meta_file(&meta_dir.join("counter.json"), 2, |_, data| {
+            Ok(serde_json::json!({ "count": data.get("legacyCount").cloned().unwrap_or_default() }))
+        })
+        .map(|_| ())
+    }
+
+    fn always_fail(meta_dir: &Path) -> Result<(), String> {
+        fs::write(meta_dir.join("counter.json"), "corrupted").unwrap();
+        Err("boom".to_string())
+    }
+
+    const STEPS: &[Migration] = &[
+        Migration { version: 1, description: "baseline", files: &[], run: |_| Ok(()) },
+        Migration { version: 2, description: "rename count", files: &["counter.json"], run: rename_count },
+        Migration { version: 3, description: "broken", files: &["counter.json"], run: always_fail },
+    ];
+
+    #[test]
+    fn applies_in_order_and_restores_on_failure() {
+        let dir = tempfile::tempdir().unwrap();
+        let original = r#"{"schemaVersion":1,"updatedAt":0,"data":{"legacyCount":7}}"#;
+        fs::write(dir.path().join("counter.json"), original).unwrap();
+
+        let report = run_migrations_in(dir.path(), &STEPS[..2]).unwrap();
+        assert_eq!((report.from_version, report.to_version), (0, 2));
+        let migrated: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(dir.path().join("counter.json")).unwrap()).unwrap();
+        assert_eq!(migrated["schemaVersion"], 2);
+        assert_eq!(migrated["data"]["count"], 7);
+        let backup = report.applied[1].backup_dir.as_ref().unwrap();
+        assert_eq!(fs::read_to_string(Path::new(backup).join("counter.json")).unwrap(), original);
+
+        // 已完成的步骤不重复执行；失败步骤还原文件且不推进版本
+        let before = fs::read_to_string(dir.path().join("counter.json")).unwrap();
+        assert!(run_migrations_in(dir.path(), STEPS).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("counter.json")).unwrap(), before);
+        assert_eq!(load_state(&dir.path().join(STATE_FILE)).unwrap().format_version, 2);
+    }
+}
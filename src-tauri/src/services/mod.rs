@@ -5,5 +5,48 @@
 
 pub mod config_service;
 pub mod printer_service;
+pub mod driver_service;
 pub mod fs_paths;
 pub mod events;
+pub mod request_coordinator;
+pub mod meta_store;
+pub mod migrations;
+pub mod install_history;
+pub mod host_resolution;
+pub mod route_trace;
+pub mod cleanup_service;
+pub mod integrity_service;
+pub mod config_http_cache;
+pub mod fleet_service;
+pub mod catalog_service;
+pub mod watchdog_service;
+pub mod printer_image;
+pub mod update_rollout;
+pub mod interaction_mode;
+pub mod crash_report;
+pub mod config_update_plan;
+pub mod audit_log;
+pub mod user_settings;
+pub mod printer_watch;
+pub mod printer_naming;
+pub mod update_download;
+pub mod app_state;
+pub mod driver_cache;
+pub mod delegated_install;
+pub mod metrics_service;
+pub mod post_install;
+pub mod printer_permissions;
+pub mod config_versions;
+pub mod shutdown_service;
+pub mod feedback_service;
+pub mod webview_recovery;
+pub mod config_sources;
+pub mod self_test;
+pub mod snmp_service;
+pub mod driver_channel;
+pub mod machine_cleanup;
+pub mod queue_conflict;
+pub mod kiosk;
+pub mod printer_locks;
+pub mod elevation_resume;
+pub mod install_estimate;
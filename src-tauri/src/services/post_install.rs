@@ -0,0 +1,363 @@
+/**
+ * 安装后动作服务（printers[].postInstall，仅 Windows）
+ * 职责：
+ * - 校验动作格式：只允许白名单操作（setConfig 设置打印机配置项 / runVendorUtility 运行驱动包内的厂商工具），
+ *   不接受任意命令字符串
+ * - 校验签名：Ed25519(私钥, {"actions": [...], "packageSha256": "<驱动包>", "printer": "<配置中的名称>"})，本机只保存公钥，无法生成签名：
+ *   公钥为构建时嵌入的 EPRINTY_POST_INSTALL_PUBLIC_KEY，或管理员部署在注册表
+ *   HKLM\SOFTWARE\ePrinty 的 PostInstallPublicKey（普通用户无法写入），任一公钥校验通过即可
+ * - 私钥由管理员离线保管并在本应用以外签名，应用不提供签名命令
+ * - 厂商工具只在本次安装的驱动包与签名中的 packageSha256 一致、payload.zip 与工具文件的哈希都与签名值一致时运行，
+ *   改动 driverCatalog 指向其他驱动包不能借用已签名的动作列表
+ * - 安装成功后按顺序执行，遇到失败即停止；每个动作的结果记入安装历史
+ *
+ * 签名不通过或本机没有公钥时不执行任何动作
+ */
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// 构建时嵌入的签名公钥（64 位十六进制，未设置时只使用注册表中的公钥）
+const EMBEDDED_PUBLIC_KEY: Option<&str> = option_env!("EPRINTY_POST_INSTALL_PUBLIC_KEY");
+
+/// 单台打印机最多的动作数
+const MAX_ACTIONS: usize = 10;
+
+/// 厂商工具最多的参数个数
+const MAX_ARGS: usize = 16;
+
+/// setConfig 属性名 / 属性值的最大长度
+const MAX_KEY_LEN: usize = 64;
+const MAX_VALUE_LEN: usize = 256;
+
+/// 单个动作的执行结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostInstallActionResult {
+    /// "setConfig" | "runVendorUtility" | "verifySignature"
+    pub action: String,
+    /// setConfig 的属性名 / runVendorUtility 的相对路径
+    pub target: String,
+    pub success: bool,
+    pub message: String,
+}
+
+impl PostInstallActionResult {
+    fn new(action: &str, target: &str, outcome: Result<String, String>) -> Self {
+        let success = outcome.is_ok();
+        Self {
+            action: action.to_string(),
+            target: target.to_string(),
+            success,
+            message: outcome.unwrap_or_else(|e| e),
+        }
+    }
+}
+
+/// 校验 postInstall 动作格式（签名在执行时校验）
+pub fn validate_config(config: &crate::PostInstallConfig) -> Result<(), String> {
+    if config.actions.is_empty() {
+        return Err("actions 不能为空".to_string());
+    }
+    if config.actions.len() > MAX_ACTIONS {
+        return Err(format!("actions 最多 {} 个", MAX_ACTIONS));
+    }
+    if config.signature.trim().len() != 128 || !config.signature.trim().chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("signature 必须为 128 位十六进制 Ed25519 签名".to_string());
+    }
+    let has_utility = config
+        .actions
+        .iter()
+        .any(|action| matches!(action, crate::PostInstallAction::RunVendorUtility { .. }));
+    match config.package_sha256.as_deref() {
+        Some(sha256) => validate_sha256(sha256, "packageSha256")?,
+        None if has_utility => return Err("包含 runVendorUtility 时必须提供 packageSha256".to_string()),
+        None => {}
+    }
+    for action in &config.actions {
+        match action {
+            crate::PostInstallAction::SetConfig { key, value } => {
+                let key_ok = !key.is_empty()
+                    && key.len() <= MAX_KEY_LEN
+                    && key.chars().all(|c| c.is_ascii_alphanumeric() || ":._-".contains(c));
+                if !key_ok {
+                    return Err(format!(
+                        "setConfig.key=\"{}\" 无效（1-{} 个字母、数字或 : . _ -）",
+                        key, MAX_KEY_LEN
+                    ));
+                }
+                if value.chars().count() > MAX_VALUE_LEN || value.chars().any(char::is_control) {
+                    return Err(format!("setConfig \"{}\" 的 value 过长或包含控制字符", key));
+                }
+            }
+            crate::PostInstallAction::RunVendorUtility { path, args, sha256 } => {
+                validate_utility_path(path)?;
+                validate_sha256(sha256, &format!("runVendorUtility \"{}\" 的 sha256", path))?;
+                if args.len() > MAX_ARGS {
+                    return Err(format!("runVendorUtility \"{}\" 的 args 最多 {} 个", path, MAX_ARGS));
+                }
+                if args.iter().any(|arg| arg.chars().any(char::is_control)) {
+                    return Err(format!("runVendorUtility \"{}\" 的 args 不能包含控制字符", path));
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// SHA256 必须为 64 位十六进制
+fn validate_sha256(sha256: &str, label: &str) -> Result<(), String> {
+    if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{} 必须为 64 位十六进制 SHA256", label));
+    }
+    Ok(())
+}
+
+/// 厂商工具路径必须是驱动包内的相对 .exe 路径（不能包含 ..、盘符或 UNC）
+fn validate_utility_path(path: &str) -> Result<(), String> {
+    let invalid = path.trim().is_empty()
+        || path.contains(':')
+        || path.split(['/', '\\']).any(|segment| segment.is_empty() || segment == "." || segment == "..")
+        || !path.to_ascii_lowercase().ends_with(".exe");
+    if invalid {
+        return Err(format!(
+            "runVendorUtility.path=\"{}\" 无效，必须是驱动包解压目录内的相对 .exe 路径",
+            path
+        ));
+    }
+    Ok(())
+}
+
+/// 签名原文：{"actions": [...], "packageSha256": "<驱动包>" | null, "printer": "<名称>"}（键按字母排序的紧凑 JSON）
+fn signing_payload(printer_name: &str, config: &crate::PostInstallConfig) -> Result<Vec<u8>, String> {
+    serde_json::to_vec(&serde_json::json!({
+        "actions": config.actions,
+        "packageSha256": config.package_sha256,
+        "printer": printer_name,
+    }))
+    .map_err(|e| format!("序列化签名原文失败: {}", e))
+}
+
+/// 十六进制解码（长度须为 expected_len 字节）
+fn decode_hex(hex: &str, expected_len: usize, label: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.trim();
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()))
+        .collect::<Option<Vec<u8>>>()
+        .filter(|bytes| bytes.len() == expected_len)
+        .ok_or_else(|| format!("{} 不是 {} 字节的十六进制", label, expected_len))?;
+    Ok(bytes)
+}
+
+/// 解析十六进制公钥
+fn parse_public_key(hex: &str) -> Result<VerifyingKey, String> {
+    let bytes: [u8; 32] = decode_hex(hex, 32, "签名公钥")?
+        .try_into()
+        .map_err(|_| "签名公钥长度无效".to_string())?;
+    VerifyingKey::from_bytes(&bytes).map_err(|e| format!("签名公钥无效: {}", e))
+}
+
+/// 用给定公钥校验签名
+fn verify_signature(key: &VerifyingKey, printer_name: &str, config: &crate::PostInstallConfig) -> Result<(), String> {
+    let bytes: [u8; 64] = decode_hex(&config.signature, 64, "signature")?
+        .try_into()
+        .map_err(|_| "signature 长度无效".to_string())?;
+    key.verify_strict(&signing_payload(printer_name, config)?, &Signature::from_bytes(&bytes))
+        .map_err(|_| "签名校验失败：动作列表、驱动包或打印机名称与签名不一致，或签名私钥与本机公钥不匹配".to_string())
+}
+
+/// 本机可用的签名公钥（构建时嵌入的与注册表中的；格式无效的公钥记录日志后忽略）
+fn load_public_keys() -> Result<Vec<VerifyingKey>, String> {
+    let registry = crate::platform::read_post_install_public_key().unwrap_or_else(|e| {
+        eprintln!("[PostInstall] {}", e);
+        None
+    });
+    let keys: Vec<VerifyingKey> = EMBEDDED_PUBLIC_KEY
+        .map(str::to_string)
+        .into_iter()
+        .chain(registry)
+        .filter_map(|hex| parse_public_key(&hex).map_err(|e| eprintln!("[PostInstall] {}", e)).ok())
+        .collect();
+    if keys.is_empty() {
+        return Err("本机未部署安装后动作签名公钥（HKLM\\SOFTWARE\\ePrinty\\PostInstallPublicKey）".to_string());
+    }
+    Ok(keys)
+}
+
+/// 用本机任一公钥校验签名
+fn verify_with_any(keys: &[VerifyingKey], printer_name: &str, config: &crate::PostInstallConfig) -> Result<(), String> {
+    let mut last_error = String::new();
+    for key in keys {
+        match verify_signature(key, printer_name, config) {
+            Ok(()) => return Ok(()),
+            Err(e) => last_error = e,
+        }
+    }
+    Err(last_error)
+}
+
+/// 本次安装所用驱动包的解压目录（drivers_root/<driver_uuid>/extracted）
+///
+/// 驱动包须为签名中的 packageSha256，且缓存的 payload.zip 哈希与之一致
+fn package_dir(package_uuid: Option<&str>, package_sha256: Option<&str>) -> Result<PathBuf, String> {
+    let uuid = package_uuid.ok_or_else(|| "本次安装未使用远程驱动包，无法运行厂商工具".to_string())?;
+    let signed = package_sha256.ok_or_else(|| "签名中没有 packageSha256，无法运行厂商工具".to_string())?;
+    if super::driver_cache::driver_uuid_for_sha256(signed) != uuid {
+        return Err(format!("本次安装的驱动包 {} 不是签名中的驱动包 {}", uuid, signed));
+    }
+    let package_root = super::fs_paths::get_drivers_root()?.join(uuid);
+    let actual = super::driver_service::file_sha256(&package_root.join("payload").join("payload.zip"))?;
+    if !actual.eq_ignore_ascii_case(signed) {
+        return Err(format!("驱动包哈希与签名不一致（实际 {}，签名 {}）", actual, signed));
+    }
+    Ok(package_root.join("extracted"))
+}
+
+/// 厂商工具文件的哈希须与签名中的 sha256 一致
+fn verify_utility_hash(program: &Path, expected: &str) -> Result<(), String> {
+    let actual = super::driver_service::file_sha256(program)?;
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(format!("厂商工具 \"{}\" 哈希与签名不一致（实际 {}）", program.display(), actual));
+    }
+    Ok(())
+}
+
+/// 解析厂商工具完整路径（规范化后仍须位于驱动包目录内）
+fn resolve_utility(package_dir: &Path, path: &str) -> Result<PathBuf, String> {
+    validate_utility_path(path)?;
+    let root = package_dir
+        .canonicalize()
+        .map_err(|e| format!("驱动包目录不存在 ({}): {}", package_dir.display(), e))?;
+    let program = root
+        .join(path)
+        .canonicalize()
+        .map_err(|e| format!("厂商工具不存在 ({}): {}", path, e))?;
+    if !program.starts_with(&root) || !program.is_file() {
+        return Err(format!("厂商工具 \"{}\" 不在驱动包目录内", path));
+    }
+    Ok(program)
+}
+
+/// 执行安装后动作（阻塞调用）
+fn run_actions(
+    printer_name: &str,
+    queue_name: &str,
+    package_uuid: Option<&str>,
+    config: &crate::PostInstallConfig,
+) -> Vec<PostInstallActionResult> {
+    let verified = validate_config(config)
+        .and_then(|_| load_public_keys())
+        .and_then(|keys| verify_with_any(&keys, printer_name, config));
+    if let Err(e) = verified {
+        eprintln!("[PostInstall] skipped printer=\"{}\": {}", printer_name, e);
+        return vec![PostInstallActionResult::new("verifySignature", printer_name, Err(e))];
+    }
+
+    let mut results = Vec::new();
+    for action in &config.actions {
+        let result = match action {
+            crate::PostInstallAction::SetConfig { key, value } => PostInstallActionResult::new(
+                "setConfig",
+                key,
+                crate::platform::set_printer_property(queue_name, key, value),
+            ),
+            crate::PostInstallAction::RunVendorUtility { path, args, sha256 } => PostInstallActionResult::new(
+                "runVendorUtility",
+                path,
+                package_dir(package_uuid, config.package_sha256.as_deref())
+                    .and_then(|dir| resolve_utility(&dir, path))
+                    .and_then(|program| verify_utility_hash(&program, sha256).map(|_| program))
+                    .and_then(|program| crate::platform::run_vendor_utility(&program, args)),
+            ),
+        };
+        let failed = !result.success;
+        eprintln!(
+            "[PostInstall] printer=\"{}\" action={} target=\"{}\" success={} message=\"{}\"",
+            printer_name, result.action, result.target, result.success, result.message
+        );
+        results.push(result);
+        if failed {
+            break;
+        }
+    }
+    results
+}
+
+/// 安装成功后执行打印机的安装后动作（结果只记录，不改变安装结果）
+///
+/// # 参数
+/// - `printer_name`: 配置中的打印机名称（签名原文使用）
+/// - `queue_name`: 系统队列名称
+/// - `package_uuid`: 本次安装所用驱动包的 uuid（安装开始时按当时的配置确定；不是远程驱动包时为 None）
+/// - `config`: postInstall 配置
+///
+/// # 返回
+/// - 各动作的执行结果（签名校验失败时只有一条 verifySignature 结果）
+pub async fn run_post_install(
+    printer_name: String,
+    queue_name: String,
+    package_uuid: Option<String>,
+    config: crate::PostInstallConfig,
+) -> Vec<PostInstallActionResult> {
+    let label = printer_name.clone();
+    tokio::task::spawn_blocking(move || run_actions(&printer_name, &queue_name, package_uuid.as_deref(), &config))
+        .await
+        .unwrap_or_else(|e| {
+            vec![PostInstallActionResult::new("verifySignature", &label, Err(format!("执行安装后动作任务异常: {}", e)))]
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PostInstallAction;
+
+    #[test]
+    fn verifies_signed_whitelisted_actions() {
+        use ed25519_dalek::{Signer, SigningKey};
+
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{:02x}", b)).collect::<String>();
+        let signing_key = SigningKey::from_bytes(&[7u8; 32]);
+        let public_key = parse_public_key(&hex(signing_key.verifying_key().as_bytes())).unwrap();
+        let other_key = SigningKey::from_bytes(&[9u8; 32]).verifying_key();
+        let actions = vec![
+            PostInstallAction::SetConfig { key: "Config:AccountingCode".to_string(), value: "1234".to_string() },
+            PostInstallAction::RunVendorUtility {
+                path: "tools\\setup.exe".to_string(),
+                args: vec!["/quiet".to_string()],
+                sha256: "b".repeat(64),
+            },
+        ];
+        let unsigned = crate::PostInstallConfig { actions, package_sha256: Some("a".repeat(64)), signature: String::new() };
+        let signature = hex(&signing_key.sign(&signing_payload("3F-HP", &unsigned).unwrap()).to_bytes());
+        let config = crate::PostInstallConfig { signature, ..unsigned };
+        assert!(validate_config(&config).is_ok());
+        assert!(verify_signature(&public_key, "3F-HP", &config).is_ok());
+        assert!(verify_signature(&public_key, "4F-HP", &config).is_err());
+        assert!(verify_signature(&other_key, "3F-HP", &config).is_err());
+        assert!(verify_with_any(&[other_key, public_key], "3F-HP", &config).is_ok());
+
+        let mut tampered = config.clone();
+        tampered.actions[0] = PostInstallAction::SetConfig { key: "Config:AccountingCode".to_string(), value: "9999".to_string() };
+        assert!(verify_signature(&public_key, "3F-HP", &tampered).is_err());
+        // 换成其他驱动包后签名失效
+        let mut other_package = config.clone();
+        other_package.package_sha256 = Some("c".repeat(64));
+        assert!(verify_signature(&public_key, "3F-HP", &other_package).is_err());
+        assert!(package_dir(Some("drv_cccccccccccc"), config.package_sha256.as_deref()).is_err());
+        // 厂商工具必须带 packageSha256 与自身的 sha256
+        let mut no_package = config.clone();
+        no_package.package_sha256 = None;
+        assert!(validate_config(&no_package).is_err());
+        let mut no_hash = config.clone();
+        no_hash.actions[1] = PostInstallAction::RunVendorUtility { path: "tools\\setup.exe".to_string(), args: Vec::new(), sha256: String::new() };
+        assert!(validate_config(&no_hash).is_err());
+        assert!(parse_public_key("abcd").is_err());
+
+        for path in ["..\\evil.exe", "C:\\Windows\\cmd.exe", "\\\\server\\share\\x.exe", "tools/run.bat", ""] {
+            assert!(validate_utility_path(path).is_err(), "{}", path);
+        }
+    }
+}
@@ -0,0 +1,233 @@
+/**
+ * 打印机图片缓存服务
+ * 职责：
+ * - 解析打印机图片地址：打印机节点 imageUrl 优先，其次其 driverKey 对应的 driverCatalog 条目
+ * - 下载图片到本地缓存目录（fs_paths::get_image_cache_dir），限制大小并按文件头校验图片格式
+ * - 缓存文件以内容 sha256 命名（相同图片只保存一份），索引记录 url -> 缓存文件
+ * - get_printer_image：返回本地文件路径，前端通过 convertFileSrc 加载（asset 协议仅放行图片缓存目录）
+ *
+ * 同一 url 下载成功后不再重新请求；更换图片请在配置中使用新的 url
+ */
+
+use super::meta_store::{write_atomic, MetaStore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// 图片缓存索引（schemaVersion=1）
+static IMAGE_INDEX: MetaStore<ImageIndex> = MetaStore::new("printer_images", 1);
+
+/// 单张图片的最大体积
+const MAX_IMAGE_BYTES: usize = 5 * 1024 * 1024;
+
+/// 图片下载超时
+const FETCH_TIMEOUT_SECS: u64 = 15;
+
+/// 已缓存的图片
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachedImage {
+    pub sha256: String,
+    /// 缓存目录内的文件名（{sha256}.{扩展名}）
+    pub file_name: String,
+    pub size: u64,
+    pub content_type: String,
+    pub fetched_at: i64,
+}
+
+/// 图片缓存索引（key = 图片 url）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageIndex {
+    pub images: HashMap<String, CachedImage>,
+}
+
+/// get_printer_image 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterImage {
+    pub key: String,
+    pub url: String,
+    /// 本地文件绝对路径
+    pub path: String,
+    pub sha256: String,
+    pub size: u64,
+    pub content_type: String,
+    /// 是否直接命中本地缓存（未发起网络请求）
+    pub from_cache: bool,
+}
+
+/// 按文件头识别图片格式，返回 (扩展名, Content-Type)
+fn sniff_image_format(bytes: &[u8]) -> Option<(&'static str, &'static str)> {
+    if bytes.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some(("png", "image/png"))
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(("jpg", "image/jpeg"))
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some(("gif", "image/gif"))
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some(("webp", "image/webp"))
+    } else {
+        None
+    }
+}
+
+/// 解析图片地址（key 为打印机名称或 driverKey）
+fn resolve_image_url(config: &crate::PrinterConfig, key: &str) -> Option<String> {
+    let catalog_image = |driver_key: &str| {
+        config
+            .driver_catalog
+            .as_ref()
+            .and_then(|catalog| catalog.get(driver_key))
+            .and_then(|entry| entry.image_url.clone())
+    };
+
+    let printer = config
+        .cities
+        .iter()
+        .flat_map(|c| c.areas.iter())
+        .flat_map(|a| a.printers.iter())
+        .find(|p| p.name == key);
+    match printer {
+        Some(printer) => printer
+            .image_url
+            .clone()
+            .or_else(|| printer.driver_key.as_deref().and_then(catalog_image)),
+        None => catalog_image(key),
+    }
+    .map(|url| url.trim().to_string())
+    .filter(|url| !url.is_empty())
+}
+
+/// 下载图片（超过大小上限立即中止）
+async fn download_image(url: &str) -> Result<Vec<u8>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(FETCH_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("下载图片失败: {}", e))?;
+    if !response.status().is_success() {
+        return Err(format!("下载图片失败: 服务器返回 {}", response.status()));
+    }
+    if let Some(length) = response.content_length() {
+        if length as usize > MAX_IMAGE_BYTES {
+            return Err(format!("图片过大（{} KB），最大支持 {} KB", length / 1024, MAX_IMAGE_BYTES / 1024));
+        }
+    }
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("下载图片失败: {}", e))? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_IMAGE_BYTES {
+            return Err(format!("图片过大，最大支持 {} KB", MAX_IMAGE_BYTES / 1024));
+        }
+    }
+    Ok(bytes)
+}
+
+/// 获取打印机图片的本地缓存文件（未缓存时下载）
+///
+/// # 参数
+/// - `key`: 打印机名称（按打印机 imageUrl → driverCatalog 查找）或 driverKey
+///
+/// # 返回
+/// - `Ok(PrinterImage)`: 本地文件路径及内容摘要
+/// - `Err(String)`: 未配置图片、地址无效、下载失败或内容不是图片
+pub async fn get_printer_image(key: &str) -> Result<PrinterImage, String> {
+    let key = key.trim().to_string();
+    if key.is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+
+    let (config, _) = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取配置任务异常: {}", e))??;
+    let url = resolve_image_url(&config, &key).ok_or_else(|| format!("打印机 {} 未配置图片（imageUrl）", key))?;
+    match url::Url::parse(&url) {
+        Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+        _ => return Err(format!("图片地址无效（仅支持 http/https）: {}", url)),
+    }
+
+    let cache_dir = super::fs_paths::get_image_cache_dir()?;
+    let cached = tokio::task::spawn_blocking({
+        let url = url.clone();
+        move || IMAGE_INDEX.load().map(|index| index.images.get(&url).cloned())
+    })
+    .await
+    .map_err(|e| format!("读取图片缓存任务异常: {}", e))??;
+    if let Some(image) = cached {
+        let path = cache_dir.join(&image.file_name);
+        if path.is_file() {
+            return Ok(PrinterImage {
+                key,
+                url,
+                path: path.display().to_string(),
+                sha256: image.sha256,
+                size: image.size,
+                content_type: image.content_type,
+                from_cache: true,
+            });
+        }
+    }
+
+    let bytes = download_image(&url).await?;
+    let (extension, content_type) =
+        sniff_image_format(&bytes).ok_or_else(|| format!("下载的内容不是 PNG / JPEG / GIF / WebP 图片: {}", url))?;
+    let sha256 = format!("{:x}", Sha256::digest(&bytes));
+    let image = CachedImage {
+        file_name: format!("{}.{}", sha256, extension),
+        sha256,
+        size: bytes.len() as u64,
+        content_type: content_type.to_string(),
+        fetched_at: chrono::Utc::now().timestamp_millis(),
+    };
+    let path = cache_dir.join(&image.file_name);
+    eprintln!(
+        "[PrinterImage] fetched key=\"{}\" url=\"{}\" size={} sha256={}",
+        key, url, image.size, image.sha256
+    );
+
+    tokio::task::spawn_blocking({
+        let (url, image, path) = (url.clone(), image.clone(), path.clone());
+        move || -> Result<(), String> {
+            if !path.is_file() {
+                write_atomic(&path, &bytes)?;
+            }
+            IMAGE_INDEX.update(|index| {
+                index.images.insert(url, image);
+            })
+        }
+    })
+    .await
+    .map_err(|e| format!("写入图片缓存任务异常: {}", e))??;
+
+    Ok(PrinterImage {
+        key,
+        url,
+        path: path.display().to_string(),
+        sha256: image.sha256,
+        size: image.size,
+        content_type: image.content_type,
+        from_cache: false,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sniffs_supported_image_formats() {
+        assert_eq!(sniff_image_format(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some(("png", "image/png")));
+        assert_eq!(sniff_image_format(&[0xFF, 0xD8, 0xFF, 0xE0]), Some(("jpg", "image/jpeg")));
+        assert_eq!(sniff_image_format(b"RIFF\x10\0\0\0WEBPVP8 "), Some(("webp", "image/webp")));
+        assert_eq!(sniff_image_format(b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>"), None);
+        assert_eq!(sniff_image_format(b"<html>404</html>"), None);
+    }
+}
@@ -0,0 +1,175 @@
+/**
+ * 打印机操作锁服务
+ * 职责：
+ * - 同一台打印机同一时间只允许一个会改动队列的操作（安装 / 重新安装 / 删除 / 修复 / 打印测试页）
+ * - 操作开始前按打印机键（配置名称与系统队列名，不区分大小写）加锁，返回的守卫 Drop 时释放
+ * - 已被占用时不排队等待，立即返回 [EASYPRINTER_CODE=PRINTER_BUSY] 错误，错误信息中带有占用方的操作与任务 ID
+ *   （安装任务的任务 ID 由平台层生成，按打印机名称从进行中的安装任务中查找；找不到时使用锁自身的操作 ID）
+ * - dryRun 安装不改动本机，不加锁
+ */
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// 打印机被占用时的错误码
+pub const PRINTER_BUSY: &str = "PRINTER_BUSY";
+
+static LOCKS: Mutex<Option<HashMap<String, PrinterLockHolder>>> = Mutex::new(None);
+static NEXT_OPERATION_ID: AtomicU64 = AtomicU64::new(1);
+
+/// 当前持有锁的操作
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterLockHolder {
+    /// 加锁时使用的打印机名称（原样）
+    pub printer_name: String,
+    /// install | reinstall | delete | repair | testPage
+    pub operation: String,
+    pub operation_id: String,
+    /// 开始时间（Unix 毫秒）
+    pub started_at: i64,
+}
+
+/// 操作锁守卫（Drop 时释放本次加的全部锁）
+#[derive(Debug)]
+pub struct PrinterOperationGuard {
+    keys: Vec<String>,
+    operation_id: String,
+}
+
+impl Drop for PrinterOperationGuard {
+    fn drop(&mut self) {
+        with_locks(|locks| {
+            for key in &self.keys {
+                if locks.get(key).map(|h| h.operation_id == self.operation_id).unwrap_or(false) {
+                    locks.remove(key);
+                }
+            }
+        });
+    }
+}
+
+fn with_locks<T>(f: impl FnOnce(&mut HashMap<String, PrinterLockHolder>) -> T) -> T {
+    let mut guard = LOCKS.lock().unwrap_or_else(|e| e.into_inner());
+    f(guard.get_or_insert_with(HashMap::new))
+}
+
+fn lock_key(name: &str) -> String {
+    name.trim().to_lowercase()
+}
+
+fn operation_label(operation: &str) -> &str {
+    match operation {
+        "install" => "安装",
+        "reinstall" => "重新安装",
+        "delete" => "删除",
+        "repair" => "修复",
+        "testPage" => "打印测试页",
+        other => other,
+    }
+}
+
+/// 占用方的任务 ID（安装类操作优先使用平台层登记的安装任务 ID）
+fn conflicting_job_id(holder: &PrinterLockHolder) -> String {
+    if matches!(holder.operation.as_str(), "install" | "reinstall" | "repair") {
+        let key = lock_key(&holder.printer_name);
+        if let Some(job) = crate::platform::active_install_jobs()
+            .into_iter()
+            .find(|job| !job.dry_run && lock_key(&job.printer_name) == key)
+        {
+            return job.job_id;
+        }
+    }
+    holder.operation_id.clone()
+}
+
+fn busy_error(holder: &PrinterLockHolder) -> String {
+    format!(
+        "[EASYPRINTER_CODE={}] 打印机 \"{}\" 正在执行{}（任务 {}），请等待完成后重试",
+        PRINTER_BUSY,
+        holder.printer_name,
+        operation_label(&holder.operation),
+        conflicting_job_id(holder)
+    )
+}
+
+/// 为一台打印机加操作锁
+///
+/// # 参数
+/// - `printer_names`: 同一台打印机的名称（配置名称、系统队列名等，空值与重复值忽略）
+/// - `operation`: install | reinstall | delete | repair | testPage
+///
+/// # 返回
+/// - `Ok(PrinterOperationGuard)`: 加锁成功，守卫存活期间其他操作返回 PRINTER_BUSY
+/// - `Err(String)`: 任一名称已被其他操作占用（不会部分加锁）
+pub fn acquire(printer_names: &[&str], operation: &str) -> Result<PrinterOperationGuard, String> {
+    let mut keys: Vec<(String, &str)> = Vec::new();
+    for name in printer_names.iter().filter(|n| !n.trim().is_empty()) {
+        let key = lock_key(name);
+        if !keys.iter().any(|(k, _)| *k == key) {
+            keys.push((key, name.trim()));
+        }
+    }
+    let operation_id = format!(
+        "{}_{}_{}",
+        operation,
+        chrono::Utc::now().timestamp_millis(),
+        NEXT_OPERATION_ID.fetch_add(1, Ordering::Relaxed)
+    );
+
+    let conflict = with_locks(|locks| {
+        if let Some(holder) = keys.iter().find_map(|(key, _)| locks.get(key)) {
+            return Some(holder.clone());
+        }
+        let started_at = chrono::Utc::now().timestamp_millis();
+        for (key, name) in &keys {
+            locks.insert(
+                key.clone(),
+                PrinterLockHolder {
+                    printer_name: name.to_string(),
+                    operation: operation.to_string(),
+                    operation_id: operation_id.clone(),
+                    started_at,
+                },
+            );
+        }
+        None
+    });
+
+    if let Some(holder) = conflict {
+        eprintln!(
+            "[PrinterLocks] busy operation={} printers={:?} held_by={} ({})",
+            operation, printer_names, holder.operation, holder.operation_id
+        );
+        return Err(busy_error(&holder));
+    }
+    Ok(PrinterOperationGuard { keys: keys.into_iter().map(|(k, _)| k).collect(), operation_id })
+}
+
+/// 当前持有锁的操作（同一操作的多个名称各占一项，按开始时间排序）
+pub fn held_locks() -> Vec<PrinterLockHolder> {
+    let mut list: Vec<PrinterLockHolder> = with_locks(|locks| locks.values().cloned().collect());
+    list.sort_by_key(|h| h.started_at);
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_conflicting_operations_until_released() {
+        let guard = acquire(&["财务部 HP", "财务部 HP (2)"], "delete").unwrap();
+        let err = acquire(&["财务部 hp (2)"], "testPage").unwrap_err();
+        assert!(err.starts_with("[EASYPRINTER_CODE=PRINTER_BUSY]") && err.contains("正在执行删除"));
+        assert!(err.contains(&guard.operation_id));
+        // 冲突时不会部分加锁
+        assert!(acquire(&["前台 Canon", "财务部 HP"], "install").is_err());
+        drop(acquire(&["前台 Canon"], "install").unwrap());
+
+        drop(guard);
+        assert!(acquire(&["财务部 HP (2)"], "testPage").is_ok());
+    }
+}
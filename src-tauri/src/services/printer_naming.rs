@@ -0,0 +1,231 @@
+/**
+ * 打印机队列命名服务
+ * 职责：
+ * - 按配置根节点 nameTemplate（如 "{area}-{floor}-{model}"）生成安装时使用的系统队列名称
+ * - 占位符：{city} 城市名称、{area} 顶层区域、{floor} 打印机所在的下级区域（区域没有上级时为空）、
+ *   {model} 型号、{name} 配置中的打印机名称；空占位符留下的多余分隔符会被去掉，结果为空时退回打印机名称
+ * - 重名处理：与配置中排在前面的打印机、或系统中不属于该打印机的队列重名时追加序号（"-2"、"-3"…）
+ * - 配置中的原始名称作为 displayName 记入安装历史，list_printers 据此关联 configKey
+ */
+
+use crate::PrinterConfig;
+
+/// 模板渲染后需要合并 / 去除的分隔符
+const SEPARATORS: [char; 4] = ['-', '_', ' ', '.'];
+
+/// 单台打印机的模板取值
+#[derive(Debug, Clone, Default)]
+struct NameFields {
+    city: String,
+    area: String,
+    floor: String,
+    model: String,
+    name: String,
+}
+
+/// 校验 nameTemplate（占位符必须成对且为已知名称）
+///
+/// # 参数
+/// - `template`: 配置中的 nameTemplate
+///
+/// # 返回
+/// - `Ok(())`: 模板有效
+/// - `Err(String)`: 模板为空、括号不成对或包含未知占位符
+pub fn validate_template(template: &str) -> Result<(), String> {
+    if template.trim().is_empty() {
+        return Err("nameTemplate 不能为空".to_string());
+    }
+    let mut rest = template;
+    while let Some(start) = rest.find(['{', '}']) {
+        if rest[start..].starts_with('}') {
+            return Err(format!("nameTemplate=\"{}\" 中的 '}}' 没有对应的 '{{'", template));
+        }
+        let end = rest[start..]
+            .find('}')
+            .ok_or_else(|| format!("nameTemplate=\"{}\" 中的 '{{' 没有对应的 '}}'", template))?;
+        let placeholder = &rest[start + 1..start + end];
+        if !crate::VALID_NAME_TEMPLATE_PLACEHOLDERS.contains(&placeholder) {
+            return Err(format!(
+                "nameTemplate=\"{}\" 包含未知占位符 {{{}}}。可用占位符：{}",
+                template,
+                placeholder,
+                crate::VALID_NAME_TEMPLATE_PLACEHOLDERS
+                    .iter()
+                    .map(|p| format!("{{{}}}", p))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            ));
+        }
+        rest = &rest[start + end + 1..];
+    }
+    Ok(())
+}
+
+/// 渲染模板并整理分隔符（连续分隔符只保留第一个，首尾分隔符去掉）
+fn render_template(template: &str, fields: &NameFields) -> String {
+    let rendered = template
+        .replace("{city}", fields.city.trim())
+        .replace("{area}", fields.area.trim())
+        .replace("{floor}", fields.floor.trim())
+        .replace("{model}", fields.model.trim())
+        .replace("{name}", fields.name.trim());
+
+    let mut result = String::with_capacity(rendered.len());
+    for c in rendered.chars() {
+        let after_separator = result.chars().last().is_none_or(|last| SEPARATORS.contains(&last));
+        if SEPARATORS.contains(&c) && after_separator {
+            continue;
+        }
+        result.push(c);
+    }
+    let result = result.trim_end_matches(SEPARATORS).to_string();
+    if result.is_empty() {
+        fields.name.clone()
+    } else {
+        result
+    }
+}
+
+/// 按配置顺序计算每台打印机的队列名称（配置内重名时后出现的追加序号）
+///
+/// model 覆盖值只用于 target 指定的打印机（安装请求中的型号）
+fn config_queue_names(config: &PrinterConfig, template: &str, target: (&str, Option<&str>)) -> Vec<(String, String)> {
+    let mut assigned: Vec<(String, String)> = Vec::new();
+    for city in &config.cities {
+        for area in &city.areas {
+            let path = city.area_path(&area.area_name);
+            for printer in &area.printers {
+                let model = if printer.name == target.0 { target.1.or(printer.model.as_deref()) } else { printer.model.as_deref() };
+                let fields = NameFields {
+                    city: city.city_name.clone(),
+                    area: path.first().cloned().unwrap_or_default(),
+                    floor: if path.len() > 1 { path.last().cloned().unwrap_or_default() } else { String::new() },
+                    model: model.unwrap_or_default().to_string(),
+                    name: printer.name.clone(),
+                };
+                let base = render_template(template, &fields);
+                let taken = |candidate: &str| assigned.iter().any(|(_, q)| q.eq_ignore_ascii_case(candidate));
+                let queue_name = with_counter(&base, taken);
+                assigned.push((printer.name.clone(), queue_name));
+            }
+        }
+    }
+    assigned
+}
+
+/// 在 base 后追加最小的可用序号（base 本身可用时原样返回）
+fn with_counter(base: &str, taken: impl Fn(&str) -> bool) -> String {
+    if !taken(base) {
+        return base.to_string();
+    }
+    (2..)
+        .map(|n| format!("{}-{}", base, n))
+        .find(|candidate| !taken(candidate))
+        .unwrap_or_else(|| base.to_string())
+}
+
+/// 计算打印机安装时使用的系统队列名称
+///
+/// # 参数
+/// - `config`: 当前配置
+/// - `name`: 配置中的打印机名称
+/// - `model`: 安装请求中的型号（None 时取配置中的 model）
+/// - `existing_queues`: 系统中已存在的队列名称
+/// - `owned_queues`: 安装历史中属于该打印机的队列名称（重新安装时复用，不视为重名）
+///
+/// # 返回
+/// - `Some(String)`: 按 nameTemplate 生成的队列名称
+/// - `None`: 未配置 nameTemplate 或打印机不在配置中（使用原始名称安装）
+pub fn resolve_queue_name(
+    config: &PrinterConfig,
+    name: &str,
+    model: Option<&str>,
+    existing_queues: &[String],
+    owned_queues: &[String],
+) -> Option<String> {
+    let template = config.name_template.as_deref().filter(|t| !t.trim().is_empty())?;
+    let assigned = config_queue_names(config, template, (name, model));
+    let index = assigned.iter().position(|(printer, _)| printer == name)?;
+    let (_, base) = &assigned[index];
+    let reserved_by_config = |candidate: &str| {
+        assigned
+            .iter()
+            .enumerate()
+            .any(|(i, (_, q))| i != index && q.eq_ignore_ascii_case(candidate))
+    };
+    let taken_by_system = |candidate: &str| {
+        existing_queues.iter().any(|q| q.eq_ignore_ascii_case(candidate))
+            && !owned_queues.iter().any(|q| q.eq_ignore_ascii_case(candidate))
+    };
+    Some(with_counter(base, |candidate| reserved_by_config(candidate) || taken_by_system(candidate)))
+}
+
+/// 安装前计算系统队列名称（枚举系统队列与读取安装历史放入 spawn_blocking；失败时按无已有队列处理）
+///
+/// # 返回
+/// - `Some(String)`: 按 nameTemplate 生成的队列名称
+/// - `None`: 未配置 nameTemplate 或打印机不在配置中
+pub async fn queue_name_for_install(config: &PrinterConfig, name: &str, model: Option<&str>) -> Option<String> {
+    config.name_template.as_ref()?;
+    let config = config.clone();
+    let name = name.to_string();
+    let model = model.map(str::to_string);
+    tokio::task::spawn_blocking(move || {
+        let existing: Vec<String> = crate::platform::list_printers()
+            .map(|entries| entries.into_iter().map(|e| e.system_queue_name).collect())
+            .unwrap_or_else(|e| {
+                eprintln!("[PrinterNaming] 枚举系统队列失败，按无重名处理: {}", e);
+                Vec::new()
+            });
+        let owned: Vec<String> = super::install_history::installed_display_names()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, display_name)| display_name == &name)
+            .map(|(queue, _)| queue)
+            .collect();
+        resolve_queue_name(&config, &name, model.as_deref(), &existing, &owned)
+    })
+    .await
+    .unwrap_or_else(|e| {
+        eprintln!("[PrinterNaming] 计算队列名称任务异常: {}", e);
+        None
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_template_and_appends_counter_on_collision() {
+        let config: PrinterConfig = serde_json::from_value(serde_json::json!({
+            "nameTemplate": "{area}-{floor}-{model}",
+            "cities": [{
+                "cityId": "sh",
+                "cityName": "上海",
+                "areas": [
+                    { "areaName": "A栋", "printers": [{ "name": "大厅", "path": "\\\\10.0.0.1", "model": "M428" }] },
+                    { "areaName": "3F", "parent": "A栋", "printers": [
+                        { "name": "前台", "path": "\\\\10.0.0.2", "model": "M428" },
+                        { "name": "财务", "path": "\\\\10.0.0.3", "model": "M428" }
+                    ] }
+                ]
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(resolve_queue_name(&config, "大厅", None, &[], &[]).as_deref(), Some("A栋-M428"));
+        assert_eq!(resolve_queue_name(&config, "前台", None, &[], &[]).as_deref(), Some("A栋-3F-M428"));
+        assert_eq!(resolve_queue_name(&config, "财务", None, &[], &[]).as_deref(), Some("A栋-3F-M428-2"));
+
+        let existing = vec!["A栋-3F-M428".to_string()];
+        assert_eq!(resolve_queue_name(&config, "前台", None, &existing, &[]).as_deref(), Some("A栋-3F-M428-3"));
+        assert_eq!(resolve_queue_name(&config, "前台", None, &existing, &existing).as_deref(), Some("A栋-3F-M428"));
+        assert_eq!(resolve_queue_name(&config, "不存在", None, &[], &[]), None);
+
+        assert!(validate_template("{area}-{model}").is_ok());
+        assert!(validate_template("{room}").is_err());
+        assert!(validate_template("{area").is_err());
+        assert!(validate_template("area}").is_err());
+    }
+}
@@ -0,0 +1,196 @@
+/**
+ * 打印队列权限服务（printers[].permissions，仅 Windows）
+ * 职责：
+ * - 校验 allow / deny 账户列表格式
+ * - 把账户（SID / 常用组名 / 域\账户）转换为 SDDL：常用组名直接映射别名，其他账户由平台层翻译为 SID
+ * - 安装成功后通过 Set-Printer -PermissionSDDL 应用权限，并读回实际生效的 ACL 记入安装历史
+ * - 查询当前队列权限（get_printer_permissions）、预览配置对应的 SDDL（preview_printer_permissions）
+ *
+ * 权限设置失败只记录结果，不改变安装结果
+ */
+
+use crate::platform::printer_acl::{self, AclEntry};
+use serde::{Deserialize, Serialize};
+
+/// allow / deny 各自最多的账户数
+const MAX_TRUSTEES: usize = 32;
+
+/// 权限设置 / 查询结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterPermissionsReport {
+    pub printer_name: String,
+    pub success: bool,
+    /// 实际生效的 PermissionSDDL（读取失败时为 None）
+    pub sddl: Option<String>,
+    pub entries: Vec<AclEntry>,
+    pub message: String,
+}
+
+/// 配置对应的 SDDL 预览
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionsPreview {
+    pub sddl: String,
+    pub entries: Vec<AclEntry>,
+}
+
+/// 校验 permissions 配置格式（账户是否存在在应用时检查）
+pub fn validate_config(config: &crate::PrinterPermissionsConfig) -> Result<(), String> {
+    if config.allow.is_empty() && config.deny.is_empty() {
+        return Err("allow 与 deny 不能同时为空".to_string());
+    }
+    for (field, list) in [("allow", &config.allow), ("deny", &config.deny)] {
+        if list.len() > MAX_TRUSTEES {
+            return Err(format!("{} 最多 {} 个账户", field, MAX_TRUSTEES));
+        }
+        for name in list {
+            let trimmed = name.trim();
+            if trimmed.is_empty() || trimmed.chars().any(|c| c.is_control() || "();\"".contains(c)) {
+                return Err(format!("{} 中的账户 \"{}\" 无效（不能为空或包含括号、分号、引号）", field, name));
+            }
+        }
+    }
+    if let Some(both) = config.allow.iter().find(|a| config.deny.iter().any(|d| d.trim().eq_ignore_ascii_case(a.trim()))) {
+        return Err(format!("账户 \"{}\" 同时出现在 allow 与 deny 中", both));
+    }
+    Ok(())
+}
+
+/// 把账户列表转换为 SDDL 账户（顺序不变；需要翻译的账户一次性交给平台层）
+fn resolve_trustees(names: &[String]) -> Result<Vec<String>, String> {
+    let mut trustees: Vec<Option<String>> = names.iter().map(|n| printer_acl::direct_trustee(n)).collect();
+    let pending: Vec<String> = names
+        .iter()
+        .zip(&trustees)
+        .filter(|(_, t)| t.is_none())
+        .map(|(n, _)| n.trim().to_string())
+        .collect();
+    if pending.is_empty() {
+        return Ok(trustees.into_iter().flatten().collect());
+    }
+    let mut resolved = crate::platform::resolve_account_sids(&pending)?.into_iter();
+    for trustee in trustees.iter_mut().filter(|t| t.is_none()) {
+        *trustee = resolved.next();
+    }
+    Ok(trustees.into_iter().flatten().collect())
+}
+
+/// 由配置构造 SDDL（阻塞调用）
+fn build_sddl(config: &crate::PrinterPermissionsConfig) -> Result<String, String> {
+    validate_config(config)?;
+    let allow = resolve_trustees(&config.allow)?;
+    let deny = resolve_trustees(&config.deny)?;
+    Ok(printer_acl::build_printer_sddl(&allow, &deny))
+}
+
+/// 读取队列当前权限（阻塞调用）
+fn read_report(printer_name: &str, success: bool, message: String) -> PrinterPermissionsReport {
+    match crate::platform::get_printer_sddl(printer_name) {
+        Ok(sddl) => PrinterPermissionsReport {
+            printer_name: printer_name.to_string(),
+            success,
+            entries: printer_acl::parse_sddl_entries(&sddl),
+            sddl: Some(sddl),
+            message,
+        },
+        Err(e) => PrinterPermissionsReport {
+            printer_name: printer_name.to_string(),
+            success: false,
+            sddl: None,
+            entries: Vec::new(),
+            message: if success { e } else { format!("{}；{}", message, e) },
+        },
+    }
+}
+
+/// 安装成功后应用打印队列权限
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+/// - `config`: permissions 配置
+///
+/// # 返回
+/// - 权限设置结果（含读回的实际 ACL；失败时 success=false）
+pub async fn apply_permissions(printer_name: String, config: crate::PrinterPermissionsConfig) -> PrinterPermissionsReport {
+    let label = printer_name.clone();
+    tokio::task::spawn_blocking(move || {
+        let applied = build_sddl(&config).and_then(|sddl| crate::platform::set_printer_sddl(&printer_name, &sddl));
+        let report = match applied {
+            Ok(()) => read_report(&printer_name, true, "已应用打印队列权限".to_string()),
+            Err(e) => read_report(&printer_name, false, e),
+        };
+        eprintln!(
+            "[PrinterPermissions] printer=\"{}\" success={} entries={} message=\"{}\"",
+            report.printer_name,
+            report.success,
+            report.entries.len(),
+            report.message
+        );
+        report
+    })
+    .await
+    .unwrap_or_else(|e| PrinterPermissionsReport {
+        printer_name: label,
+        success: false,
+        sddl: None,
+        entries: Vec::new(),
+        message: format!("设置打印队列权限任务异常: {}", e),
+    })
+}
+
+/// 查询打印队列当前权限
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+pub async fn get_printer_permissions(printer_name: String) -> Result<PrinterPermissionsReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let sddl = crate::platform::get_printer_sddl(&printer_name)?;
+        Ok(PrinterPermissionsReport {
+            printer_name,
+            success: true,
+            entries: printer_acl::parse_sddl_entries(&sddl),
+            sddl: Some(sddl),
+            message: String::new(),
+        })
+    })
+    .await
+    .map_err(|e| format!("读取打印队列权限任务异常: {}", e))?
+}
+
+/// 预览 permissions 配置对应的 SDDL（账户名会被翻译为 SID，不修改任何打印机）
+///
+/// # 参数
+/// - `config`: permissions 配置
+pub async fn preview_printer_permissions(config: crate::PrinterPermissionsConfig) -> Result<PermissionsPreview, String> {
+    tokio::task::spawn_blocking(move || {
+        let sddl = build_sddl(&config)?;
+        Ok(PermissionsPreview { entries: printer_acl::parse_sddl_entries(&sddl), sddl })
+    })
+    .await
+    .map_err(|e| format!("生成打印队列权限任务异常: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_and_builds_well_known_trustees() {
+        let config = |allow: &[&str], deny: &[&str]| crate::PrinterPermissionsConfig {
+            allow: allow.iter().map(|s| s.to_string()).collect(),
+            deny: deny.iter().map(|s| s.to_string()).collect(),
+        };
+        assert!(validate_config(&config(&[], &[])).is_err());
+        assert!(validate_config(&config(&["Users"], &["users"])).is_err());
+        assert!(validate_config(&config(&["A)(A;;GA;;;WD"], &[])).is_err());
+
+        let sddl = build_sddl(&config(&["Authenticated Users", "S-1-5-21-1-2-3-1105"], &["Guests"])).unwrap();
+        let allowed: Vec<String> = printer_acl::parse_sddl_entries(&sddl)
+            .into_iter()
+            .filter(|e| e.rights == "print")
+            .map(|e| format!("{}:{}", e.access, e.trustee))
+            .collect();
+        assert_eq!(allowed, vec!["deny:BG", "allow:AU", "allow:S-1-5-21-1-2-3-1105"]);
+    }
+}
@@ -1,14 +1,534 @@
 /**
  * 打印机相关命令
  * 职责：
- * - list_printers
+ * - list_printers（标注 managed / configKey）
  * - list_printers_detailed
+ * - get_job_log
+ * - get_job_command_trace / export_job_diagnostics（任务执行过的外部命令记录与诊断包）
+ * - cancel_install_job
+ * - get_install_history
+ * - 测试页历史（记录每次打印测试页的结果，get_test_page_history 按打印机查询）
+ * - pause_printer / resume_printer
+ * - open_print_queue
+ * - print_file
+ * - check_printer_connectivity
+ * - render_test_page_preview
+ * - set_printer_credentials / clear_printer_credentials
+ * - dump_print_environment
+ * - probe_permissions
+ * - get_port_config / set_port_config（端口号、SNMP 状态查询）
+ * - update_printer_port（打印机 IP 变化时把已安装队列改挂到新地址的端口，不重装驱动）
  */
 
-pub fn list_printers() -> Result<Vec<crate::platform::PrinterDetectEntry>, String> {
-    crate::platform::list_printers()
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use crate::platform::printer_address::PrinterAddress;
+use crate::utils::command_trace::{self, CommandTraceEntry};
+
+/// 单个打印文件的最大体积
+const MAX_PRINT_FILE_BYTES: u64 = 100 * 1024 * 1024;
+
+/// 最大打印份数
+const MAX_PRINT_COPIES: u32 = 99;
+
+/// print_file 选项
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrintFileOptions {
+    /// 份数（默认 1）
+    pub copies: Option<u32>,
+}
+
+/// 枚举打印机（PowerShell / lpstat 放入 spawn_blocking，避免阻塞其他命令）
+///
+/// 每个队列标注是否由本工具管理（managed / configKey），前端仅对 managed 队列提供删除 / 修复
+pub async fn list_printers() -> Result<Vec<crate::platform::PrinterDetectEntry>, String> {
+    tokio::task::spawn_blocking(list_managed_printers_blocking)
+        .await
+        .map_err(|e| format!("枚举打印机任务异常: {}", e))?
+}
+
+/// 枚举打印机并标注 managed / configKey（阻塞调用，供其他服务在 spawn_blocking 中复用）
+pub fn list_managed_printers_blocking() -> Result<Vec<crate::platform::PrinterDetectEntry>, String> {
+    let mut entries = crate::platform::list_printers()?;
+    classify_managed_printers(&mut entries);
+    Ok(entries)
+}
+
+/// 按安装历史与平台标记（Windows Comment tag / macOS "eprinty-" 前缀）识别本工具管理的队列，
+/// 并关联当前配置中的打印机名称（按 nameTemplate 改名安装的队列以历史中的原始名称作为 displayName）；
+/// 任一来源读取失败时只记录日志，不影响枚举结果
+fn classify_managed_printers(entries: &mut [crate::platform::PrinterDetectEntry]) {
+    let history_names = super::install_history::installed_printer_names().unwrap_or_else(|e| {
+        eprintln!("[ListPrinters] 读取安装历史失败，仅按平台标记识别: {}", e);
+        Vec::new()
+    });
+    let display_names = super::install_history::installed_display_names().unwrap_or_default();
+    let tagged_names = crate::platform::list_managed_printer_names().unwrap_or_else(|e| {
+        eprintln!("[ListPrinters] 读取平台标记失败，仅按安装历史识别: {}", e);
+        Vec::new()
+    });
+    let config_names: Vec<String> = match crate::load_local_config() {
+        Ok((config, _)) => config
+            .cities
+            .iter()
+            .flat_map(|c| c.areas.iter())
+            .flat_map(|a| a.printers.iter())
+            .map(|p| p.name.clone())
+            .collect(),
+        Err(e) => {
+            eprintln!("[ListPrinters] 读取配置失败，不关联 configKey: {}", e);
+            Vec::new()
+        }
+    };
+
+    for entry in entries.iter_mut() {
+        if let Some((_, display_name)) = display_names
+            .iter()
+            .rev()
+            .find(|(queue, _)| queue.eq_ignore_ascii_case(&entry.system_queue_name))
+        {
+            entry.display_name = Some(display_name.clone());
+        }
+        let names = [Some(entry.system_queue_name.as_str()), entry.display_name.as_deref()];
+        let in_history = names.iter().flatten().any(|n| history_names.iter().any(|h| h == n));
+        let tagged = tagged_names.iter().any(|t| t.eq_ignore_ascii_case(&entry.system_queue_name));
+        entry.managed = in_history || tagged;
+        entry.config_key = if entry.managed {
+            names
+                .iter()
+                .flatten()
+                .find_map(|n| config_names.iter().find(|c| c.as_str() == *n).cloned())
+        } else {
+            None
+        };
+    }
+}
+
+pub async fn list_printers_detailed() -> Result<Vec<crate::platform::DetailedPrinterInfo>, String> {
+    tokio::task::spawn_blocking(crate::platform::list_printers_detailed)
+        .await
+        .map_err(|e| format!("枚举打印机任务异常: {}", e))?
+}
+
+pub fn get_job_log(job_id: &str) -> Result<String, String> {
+    if job_id.trim().is_empty() {
+        return Err("jobId 不能为空".to_string());
+    }
+    crate::platform::get_job_log(job_id.trim())
+}
+
+/// 安装任务按顺序执行过的外部命令（已脱敏，记录写在 job 日志中）
+pub fn get_job_command_trace(job_id: &str) -> Result<Vec<CommandTraceEntry>, String> {
+    Ok(command_trace::condensed_trace(&get_job_log(job_id)?))
+}
+
+/// 诊断包导出结果
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobDiagnosticsExport {
+    pub path: String,
+    pub command_count: usize,
+    /// 非 ok 状态的命令数
+    pub failed_commands: usize,
+}
+
+/// 导出安装任务诊断包
+///
+/// ZIP 内容：`job.log`（完整任务日志）、`commands.txt`（按顺序的命令重放清单）、`commands.json`（结构化命令记录）
+///
+/// # 参数
+/// - `job_id`: 安装任务 ID
+/// - `output_path`: 输出 ZIP 路径（已存在时覆盖）
+pub async fn export_job_diagnostics(job_id: &str, output_path: &str) -> Result<JobDiagnosticsExport, String> {
+    if output_path.trim().is_empty() {
+        return Err("输出路径不能为空".to_string());
+    }
+    let job_id = job_id.trim().to_string();
+    let output = PathBuf::from(output_path.trim());
+    tokio::task::spawn_blocking(move || {
+        use std::io::Write;
+
+        let log = get_job_log(&job_id)?;
+        let entries = command_trace::condensed_trace(&log);
+        let commands_json = serde_json::to_vec_pretty(&entries).map_err(|e| format!("序列化命令记录失败: {}", e))?;
+        if let Some(parent) = output.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent).map_err(|e| format!("创建输出目录失败 {}: {}", parent.display(), e))?;
+        }
+        let file = std::fs::File::create(&output).map_err(|e| format!("创建诊断包失败 {}: {}", output.display(), e))?;
+        let mut zip = zip::ZipWriter::new(file);
+        let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, content) in [
+            ("job.log", log.as_bytes()),
+            ("commands.txt", command_trace::replay_text(&job_id, &entries).as_bytes()),
+            ("commands.json", commands_json.as_slice()),
+        ] {
+            zip.start_file(name, options)
+                .and_then(|_| zip.write_all(content).map_err(Into::into))
+                .map_err(|e| format!("写入诊断包条目失败 {}: {}", name, e))?;
+        }
+        zip.finish().map_err(|e| format!("完成诊断包写入失败 {}: {}", output.display(), e))?;
+
+        let failed_commands = entries.iter().filter(|e| e.status != "ok").count();
+        eprintln!(
+            "[JobDiagnostics] exported job_id={} path=\"{}\" commands={} failed={}",
+            job_id,
+            output.display(),
+            entries.len(),
+            failed_commands
+        );
+        Ok(JobDiagnosticsExport { path: output.display().to_string(), command_count: entries.len(), failed_commands })
+    })
+    .await
+    .map_err(|e| format!("导出诊断包任务异常: {}", e))?
+}
+
+/// 请求取消进行中的安装任务（返回 false 表示任务不存在或已结束）
+pub fn cancel_install_job(job_id: &str) -> Result<bool, String> {
+    if job_id.trim().is_empty() {
+        return Err("jobId 不能为空".to_string());
+    }
+    crate::platform::cancel_install_job(job_id.trim())
+}
+
+pub async fn get_install_history(limit: Option<usize>) -> Result<Vec<super::install_history::InstallRecord>, String> {
+    super::install_history::list_install_history(limit).await
+}
+
+/// 测试页错误信息中的失败步骤（"[PrintTestPage] ERROR step=CHECK_EXISTS message=..." → "CHECK_EXISTS"）
+fn test_page_error_step(error: &str) -> Option<String> {
+    let rest = &error[error.find("step=")? + "step=".len()..];
+    let step: String = rest.chars().take_while(|c| !c.is_whitespace()).collect();
+    (!step.is_empty()).then_some(step)
+}
+
+/// 记录一次测试页打印结果（写入失败只记录日志）
+pub async fn record_test_page(printer_name: &str, result: &Result<crate::platform::TestPageSubmission, String>) {
+    let (success, message, job_id, job_status, error_step) = match result {
+        Ok(submission) => (
+            true,
+            submission.message.clone(),
+            submission.job_id.clone(),
+            submission.job_status.clone(),
+            None,
+        ),
+        Err(e) => (false, e.clone(), None, None, test_page_error_step(e)),
+    };
+    super::install_history::record_test_page(super::install_history::TestPageRecord {
+        printer_name: printer_name.to_string(),
+        method: crate::platform::TEST_PAGE_METHOD.to_string(),
+        job_id,
+        job_status,
+        success,
+        message,
+        error_step,
+        at: chrono::Utc::now().timestamp_millis(),
+    })
+    .await;
+}
+
+/// 查询某台打印机的测试页历史（最新的在前）
+///
+/// # 参数
+/// - `printer_name`: 系统队列名称
+/// - `limit`: 最多返回条数（None 表示全部）
+pub async fn get_test_page_history(
+    printer_name: &str,
+    limit: Option<usize>,
+) -> Result<Vec<super::install_history::TestPageRecord>, String> {
+    if printer_name.trim().is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    super::install_history::list_test_pages(printer_name.trim(), limit).await
+}
+
+pub fn set_printer_paused(printer_name: &str, pause: bool) -> Result<crate::platform::QueueControlResult, String> {
+    if printer_name.trim().is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    crate::platform::set_printer_paused(printer_name, pause)
+}
+
+pub fn open_print_queue(printer_name: &str) -> Result<crate::platform::OpenQueueResult, String> {
+    if printer_name.trim().is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    crate::platform::open_print_queue(printer_name)
+}
+
+/// 校验待打印文件并识别类型
+///
+/// - 仅支持 .pdf 与 .txt / .log
+/// - PDF 必须以 "%PDF-" 开头；文本文件不得包含 NUL 字节（拒绝二进制 / UTF-16 文件）
+fn detect_print_file_kind(path: &Path) -> Result<crate::platform::PrintFileKind, String> {
+    use crate::platform::PrintFileKind;
+
+    let metadata = std::fs::metadata(path)
+        .map_err(|e| format!("无法访问文件 {}: {}", path.display(), e))?;
+    if !metadata.is_file() {
+        return Err(format!("路径不是文件: {}", path.display()));
+    }
+    if metadata.len() == 0 {
+        return Err(format!("文件为空: {}", path.display()));
+    }
+    if metadata.len() > MAX_PRINT_FILE_BYTES {
+        return Err(format!(
+            "文件过大（{} MB），最大支持 {} MB",
+            metadata.len() / 1024 / 1024,
+            MAX_PRINT_FILE_BYTES / 1024 / 1024
+        ));
+    }
+
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let kind = match extension.as_str() {
+        "pdf" => PrintFileKind::Pdf,
+        "txt" | "log" => PrintFileKind::Text,
+        _ => {
+            return Err(format!(
+                "不支持的文件类型: \"{}\"（仅支持 .pdf / .txt / .log）",
+                extension
+            ))
+        }
+    };
+
+    let mut head = vec![0u8; 8192];
+    let read = std::fs::File::open(path)
+        .and_then(|mut f| f.read(&mut head))
+        .map_err(|e| format!("读取文件失败 {}: {}", path.display(), e))?;
+    let head = &head[..read];
+
+    match kind {
+        PrintFileKind::Pdf if !head.starts_with(b"%PDF-") => {
+            Err(format!("文件扩展名为 .pdf，但内容不是有效的 PDF: {}", path.display()))
+        }
+        PrintFileKind::Text if head.contains(&0) => {
+            Err(format!("文本文件包含二进制内容（或为 UTF-16 编码），无法直接打印: {}", path.display()))
+        }
+        _ => Ok(kind),
+    }
+}
+
+pub async fn print_file(
+    printer_name: &str,
+    file_path: &str,
+    options: PrintFileOptions,
+) -> Result<crate::platform::PrintFileResult, String> {
+    if printer_name.trim().is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    if file_path.trim().is_empty() {
+        return Err("文件路径不能为空".to_string());
+    }
+
+    let copies = options.copies.unwrap_or(1);
+    if copies == 0 || copies > MAX_PRINT_COPIES {
+        return Err(format!("份数必须在 1-{} 之间（当前 {}）", MAX_PRINT_COPIES, copies));
+    }
+
+    let path = PathBuf::from(file_path.trim());
+    let kind = detect_print_file_kind(&path)?;
+
+    // 提交与作业轮询会阻塞数十秒，放入阻塞线程池执行
+    let printer_name = printer_name.to_string();
+    tokio::task::spawn_blocking(move || crate::platform::print_file(&printer_name, &path, kind, copies))
+        .await
+        .map_err(|e| format!("打印任务执行异常: {}", e))?
+}
+
+pub async fn check_printer_connectivity(
+    printer_name: &str,
+    path: &str,
+    trace_route: bool,
+) -> Result<super::host_resolution::ConnectivityCheckResult, String> {
+    if path.trim().is_empty() {
+        return Err("打印机路径不能为空".to_string());
+    }
+    super::host_resolution::check_printer_connectivity(printer_name.trim(), path.trim(), trace_route).await
+}
+
+/// 生成测试页预览（与 print_test_page 提交的字节一致）
+pub fn render_test_page_preview(printer_name: &str) -> Result<crate::platform::test_page_content::TestPagePreview, String> {
+    if printer_name.trim().is_empty() {
+        return Err("打印机名称不能为空".to_string());
+    }
+    crate::platform::test_page_content::render_test_page_preview(printer_name.trim())
+}
+
+/// 从配置中查找打印机的共享路径，并解析出打印服务器名（仅支持 \\server\queue）
+fn shared_printer_server(printer_name: &str) -> Result<String, String> {
+    let (config, _) = crate::load_local_config()?;
+    let printer = config
+        .cities
+        .iter()
+        .flat_map(|c| c.areas.iter())
+        .flat_map(|a| a.printers.iter())
+        .find(|p| p.name == printer_name)
+        .ok_or_else(|| format!("配置中未找到打印机: {}", printer_name))?;
+    match PrinterAddress::parse(&printer.path) {
+        Ok(PrinterAddress::SmbShare { server, .. }) => Ok(server),
+        _ => Err(format!(
+            "打印机 {} 不是共享打印机（路径应为 \\\\server\\queue）: {}",
+            printer_name, printer.path
+        )),
+    }
+}
+
+/// 保存共享打印机的连接凭据（按打印服务器保存，同一服务器上的打印机共用）
+///
+/// # 参数
+/// - `printer_name`: 配置中的打印机名称（路径须为 \\server\queue）
+/// - `username`: 用户名（如 DOMAIN\user）
+/// - `password`: 密码
+///
+/// # 返回
+/// - `Ok(String)`: 凭据对应的打印服务器名
+pub fn set_printer_credentials(printer_name: &str, username: &str, password: &str) -> Result<String, String> {
+    if username.trim().is_empty() {
+        return Err("用户名不能为空".to_string());
+    }
+    let server = shared_printer_server(printer_name.trim())?;
+    crate::platform::set_smb_credentials(&server, username.trim(), password)?;
+    Ok(server)
+}
+
+/// 删除共享打印机的连接凭据
+///
+/// # 返回
+/// - `Ok(bool)`: 是否删除了已保存的凭据
+pub fn clear_printer_credentials(printer_name: &str) -> Result<bool, String> {
+    let server = shared_printer_server(printer_name.trim())?;
+    crate::platform::clear_smb_credentials(&server)
+}
+
+/// 生成打印系统环境报告（队列 / 驱动 / 端口 / Spooler / 策略），可选导出为 JSON 文件
+///
+/// # 参数
+/// - `output_path`: 导出的 JSON 文件路径（为空时只返回报告）
+///
+/// # 返回
+/// - `Ok(PrintEnvironmentReport)`: 环境报告（导出时 exportedPath 为写入的文件路径）
+/// - `Err(String)`: 采集或写入失败
+pub async fn dump_print_environment(output_path: Option<String>) -> Result<crate::platform::PrintEnvironmentReport, String> {
+    let output_path = output_path.map(|p| p.trim().to_string()).filter(|p| !p.is_empty());
+    tokio::task::spawn_blocking(move || {
+        let mut report = crate::platform::dump_print_environment()?;
+        if let Some(path) = output_path {
+            let path = PathBuf::from(path);
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)
+                    .map_err(|e| format!("创建导出目录失败 {}: {}", parent.display(), e))?;
+            }
+            report.exported_path = Some(path.display().to_string());
+            let content = serde_json::to_string_pretty(&report)
+                .map_err(|e| format!("序列化打印环境报告失败: {}", e))?;
+            std::fs::write(&path, content)
+                .map_err(|e| format!("写入打印环境报告失败 {}: {}", path.display(), e))?;
+        }
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("打印环境采集任务异常: {}", e))?
+}
+
+/// 按操作细分探测当前进程的打印相关权限（驱动导入 / 添加 TCP/IP 打印机 / 连接共享打印机 / 下载驱动）
+///
+/// # 返回
+/// - `Ok(PermissionReport)`: 各项权限与各操作是否可在当前权限下执行
+/// - `Err(String)`: 当前平台不支持
+pub async fn probe_permissions() -> Result<crate::platform::PermissionReport, String> {
+    tokio::task::spawn_blocking(crate::platform::probe_permissions)
+        .await
+        .map_err(|e| format!("权限探测任务异常: {}", e))?
+}
+
+/// SNMP 团体名最大长度（Standard TCP/IP 端口监视器限制）
+const MAX_SNMP_COMMUNITY_LEN: usize = 32;
+
+/// 读取打印机端口配置
+///
+/// # 参数
+/// - `port_name`: 端口名称（如 "IP_192_168_20_65"）
+pub async fn get_port_config(port_name: &str) -> Result<crate::platform::PortConfig, String> {
+    let port_name = port_name.trim().to_string();
+    if port_name.is_empty() {
+        return Err("端口名称不能为空".to_string());
+    }
+    tokio::task::spawn_blocking(move || crate::platform::get_port_config(&port_name))
+        .await
+        .map_err(|e| format!("读取端口配置任务异常: {}", e))?
+}
+
+/// 修改打印机端口配置（需要管理员权限）
+///
+/// # 参数
+/// - `port_name`: 端口名称
+/// - `update`: 修改项（至少一项；portNumber 1-65535，snmpCommunity 不能为空）
+pub async fn set_port_config(
+    port_name: &str,
+    update: crate::platform::PortConfigUpdate,
+) -> Result<crate::platform::PortConfigChange, String> {
+    let port_name = port_name.trim().to_string();
+    if port_name.is_empty() {
+        return Err("端口名称不能为空".to_string());
+    }
+    if update.port_number.is_none() && update.snmp_enabled.is_none() && update.snmp_community.is_none() {
+        return Err("未指定要修改的端口设置".to_string());
+    }
+    if let Some(port_number) = update.port_number {
+        if !(1..=65535).contains(&port_number) {
+            return Err(format!("portNumber={} 无效，应为 1-65535", port_number));
+        }
+    }
+    if let Some(community) = update.snmp_community.as_deref() {
+        if community.trim().is_empty() || community.len() > MAX_SNMP_COMMUNITY_LEN {
+            return Err(format!("snmpCommunity 不能为空且不超过 {} 个字符", MAX_SNMP_COMMUNITY_LEN));
+        }
+    }
+    tokio::task::spawn_blocking(move || crate::platform::set_port_config(&port_name, &update))
+        .await
+        .map_err(|e| format!("修改端口配置任务异常: {}", e))?
+}
+
+/// 配置 path 对应的 TCP/IP 地址（共享连接 / 设备 URI 没有本地 TCP/IP 端口，返回 None）
+pub fn tcp_address(path: &str) -> Option<PrinterAddress> {
+    PrinterAddress::parse(path)
+        .ok()
+        .filter(|a| !matches!(a, PrinterAddress::SmbShare { .. } | PrinterAddress::IppUrl(_)))
 }
 
-pub fn list_printers_detailed() -> Result<Vec<crate::platform::DetailedPrinterInfo>, String> {
-    crate::platform::list_printers_detailed()
+/// 按当前配置原地更新已安装打印机的端口地址（需要管理员权限）
+///
+/// 用于打印机只改了 IP 的情况：队列改挂到新地址的 IP_ 端口，驱动与队列设置保持不变
+///
+/// # 参数
+/// - `printer_name`: 配置中的打印机名称
+///
+/// # 返回
+/// - `Ok(PortRetargetResult)`: 改挂结果
+/// - `Err(String)`: 打印机不在配置中 / 不是 TCP/IP 打印机 / 未由本工具安装 / 改挂失败
+pub async fn update_printer_port(printer_name: &str) -> Result<crate::platform::PortRetargetResult, String> {
+    let printer_name = printer_name.trim().to_string();
+    tokio::task::spawn_blocking(move || {
+        let (config, _) = crate::load_local_config()?;
+        let printer = super::printer_watch::find_config_printer(&config, &printer_name)
+            .ok_or_else(|| format!("配置中没有打印机 \"{}\"", printer_name))?;
+        let address = tcp_address(&printer.path)
+            .ok_or_else(|| format!("\"{}\" 不是 TCP/IP 打印机（{}），无法更新端口", printer_name, printer.path))?;
+        let installed = list_managed_printers_blocking()?;
+        let queue = super::catalog_service::find_installed(&printer_name, &installed)
+            .filter(|entry| entry.managed)
+            .ok_or_else(|| format!("\"{}\" 未由本工具安装，无法更新端口", printer_name))?;
+        let _printer_lock = super::printer_locks::acquire(&[&printer_name, &queue.system_queue_name], "repair")?;
+        crate::platform::retarget_printer_port(
+            &queue.system_queue_name,
+            &address.host_address(),
+            &address.port_name(),
+            address.port(),
+        )
+    })
+    .await
+    .map_err(|e| format!("更新打印机端口任务异常: {}", e))?
 }
@@ -0,0 +1,379 @@
+/**
+ * 打印机后台巡检服务（printerWatch）
+ * 职责：
+ * - reconcile_printers：逐台比对本工具安装（managed）且仍在配置中的打印机队列与当前配置，找出需要修复的队列：
+ *   TCP/IP 端口地址与配置 path 不一致（port_changed）、队列使用的驱动已不在系统驱动列表中（driver_missing）
+ * - 后台巡检：每 printerWatch.intervalHours 小时巡检一次；mode=auto 时自动修复有问题的队列
+ *   （只有端口地址变化时原地改挂端口，失败或驱动丢失时重新安装），
+ *   mode=notify-only 时只发送 printer_watch_report 事件提示用户，mode=off（默认）不巡检
+ * - 发现的问题与处理结果写入安装历史存储（watchActions）；自动修复本身也作为一次安装记入安装历史
+ */
+
+use super::install_history::WatchActionRecord;
+use crate::platform::{PrintEnvironmentReport, PrinterDetectEntry};
+use crate::{Printer, PrinterConfig};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+/// 默认巡检间隔（小时）
+const DEFAULT_INTERVAL_HOURS: u64 = 6;
+
+/// 启动后首次巡检前的等待（避开启动时的配置刷新与用户操作）
+const STARTUP_DELAY: Duration = Duration::from_secs(10 * 60);
+
+/// 巡检关闭时重新读取配置的间隔
+const DISABLED_RECHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// 需要修复的打印机
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokenPrinter {
+    /// 配置中的打印机名称
+    pub printer_name: String,
+    pub system_queue_name: String,
+    /// "port_changed" | "driver_missing"
+    pub issues: Vec<String>,
+    /// 配置 path 对应的主机地址
+    pub expected_host: Option<String>,
+    /// 队列端口当前的主机地址
+    pub actual_host: Option<String>,
+    /// 队列使用的驱动名称
+    pub driver_name: Option<String>,
+}
+
+/// reconcile_printers 结果
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReconcileReport {
+    /// 参与比对的打印机数量（managed 且仍在配置中）
+    pub checked_count: usize,
+    pub broken: Vec<BrokenPrinter>,
+    pub checked_at: i64,
+}
+
+/// 一轮后台巡检的结果（printer_watch_report 事件内容）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct WatchCycleReport {
+    /// "auto" | "notify-only"
+    pub mode: String,
+    pub report: ReconcileReport,
+    pub actions: Vec<WatchActionRecord>,
+}
+
+/// 在配置中按名称查找打印机（已合并区域默认安装选项）
+pub fn find_config_printer(config: &PrinterConfig, name: &str) -> Option<Printer> {
+    config
+        .cities
+        .iter()
+        .flat_map(|city| city.areas.iter())
+        .find_map(|area| {
+            area.printers.iter().find(|p| p.name == name).map(|p| {
+                let mut resolved = p.clone();
+                resolved.options = p.options.or(&area.defaults);
+                resolved
+            })
+        })
+}
+
+/// 比对 managed 队列与配置，返回参与比对的数量与需要修复的打印机
+///
+/// # 参数
+/// - `config`: 当前配置
+/// - `entries`: 已标注 managed / configKey 的系统打印机
+/// - `env`: 打印环境报告（队列的端口 / 驱动，端口主机地址，已安装驱动）
+/// - `installed_ips`: 打印机安装时为 hostname 解析到的 IP（端口可能以 IP 创建）
+fn find_broken_printers(
+    config: &PrinterConfig,
+    entries: &[PrinterDetectEntry],
+    env: &PrintEnvironmentReport,
+    installed_ips: impl Fn(&str) -> Vec<String>,
+) -> (usize, Vec<BrokenPrinter>) {
+    let mut checked_count = 0;
+    let mut broken = Vec::new();
+
+    for entry in entries.iter().filter(|e| e.managed) {
+        let Some(printer) = entry.config_key.as_deref().and_then(|key| find_config_printer(config, key)) else {
+            continue;
+        };
+        let Some(queue) = env.queues.iter().find(|q| q.name.eq_ignore_ascii_case(&entry.system_queue_name)) else {
+            continue;
+        };
+        checked_count += 1;
+
+        let mut issues = Vec::new();
+        let mut expected_host = None;
+        let mut actual_host = None;
+
+        // 端口地址：只比对 TCP/IP 打印机（共享连接 / 设备 URI 没有本地 TCP/IP 端口）
+        let tcp_address = super::printer_service::tcp_address(&printer.path);
+        let port_host = queue
+            .port_name
+            .as_deref()
+            .and_then(|port| env.ports.iter().find(|p| p.name.eq_ignore_ascii_case(port)))
+            .and_then(|p| p.host_address.clone());
+        if let (Some(address), Some(host), false) = (tcp_address, port_host, queue.queue_type.as_deref() == Some("Connection")) {
+            let expected = address.host_address();
+            let matches = host.eq_ignore_ascii_case(&expected) || installed_ips(&printer.name).iter().any(|ip| ip == &host);
+            if !matches {
+                issues.push("port_changed".to_string());
+            }
+            expected_host = Some(expected);
+            actual_host = Some(host);
+        }
+
+        // 驱动：队列使用的驱动已不在系统驱动列表中
+        if let Some(driver) = queue.driver_name.as_deref().filter(|d| !d.is_empty()) {
+            if !env.drivers.iter().any(|d| d.name.eq_ignore_ascii_case(driver)) {
+                issues.push("driver_missing".to_string());
+            }
+        }
+
+        if !issues.is_empty() {
+            broken.push(BrokenPrinter {
+                printer_name: printer.name.clone(),
+                system_queue_name: entry.system_queue_name.clone(),
+                issues,
+                expected_host,
+                actual_host,
+                driver_name: queue.driver_name.clone(),
+            });
+        }
+    }
+    (checked_count, broken)
+}
+
+/// 比对本工具安装的打印机与当前配置，列出需要修复的队列（只检查，不修复）
+///
+/// # 返回
+/// - `Ok(ReconcileReport)`: 比对结果
+/// - `Err(String)`: 枚举打印机 / 读取配置 / 采集打印环境失败
+pub async fn reconcile_printers() -> Result<ReconcileReport, String> {
+    let entries = super::printer_service::list_printers().await?;
+    tokio::task::spawn_blocking(move || {
+        let (config, _) = crate::load_local_config()?;
+        let env = crate::platform::dump_print_environment()?;
+        let (checked_count, broken) =
+            find_broken_printers(&config, &entries, &env, super::host_resolution::installed_ips);
+        eprintln!("[PrinterWatch] reconcile checked={} broken={}", checked_count, broken.len());
+        Ok(ReconcileReport {
+            checked_count,
+            broken,
+            checked_at: chrono::Utc::now().timestamp_millis(),
+        })
+    })
+    .await
+    .map_err(|e| format!("打印机巡检任务异常: {}", e))?
+}
+
+/// 修复有问题的打印机：只有端口地址变化时先原地改挂端口，否则（或改挂失败时）重新安装
+/// （与用户手动安装走同一入口，安装记录照常写入）
+async fn repair_printer(app: &tauri::AppHandle, printer: Printer, broken: &BrokenPrinter) -> WatchActionRecord {
+    if broken.issues.iter().all(|i| i == "port_changed") {
+        match super::printer_service::update_printer_port(&printer.name).await {
+            Ok(result) => {
+                return WatchActionRecord {
+                    printer_name: broken.printer_name.clone(),
+                    system_queue_name: broken.system_queue_name.clone(),
+                    issues: broken.issues.clone(),
+                    action: "port_updated".to_string(),
+                    job_id: None,
+                    message: format!("端口已由 {} 改为 {}", result.old_port, result.new_port),
+                    at: chrono::Utc::now().timestamp_millis(),
+                };
+            }
+            Err(e) => eprintln!("[PrinterWatch] 原地更新端口失败，改为重新安装 printer=\"{}\": {}", printer.name, e),
+        }
+    }
+    // 驱动丢失时必须重新安装驱动；其他情况沿用配置中的策略
+    let policy = if broken.issues.iter().any(|i| i == "driver_missing") {
+        "always".to_string()
+    } else {
+        printer.options.driver_install_policy.clone().unwrap_or_else(|| "always".to_string())
+    };
+    eprintln!(
+        "[PrinterWatch] repair printer=\"{}\" issues={:?} policy={}",
+        printer.name, broken.issues, policy
+    );
+    let result = crate::install_printer(
+        app.clone(),
+        printer.name.clone(),
+        printer.path.clone(),
+        printer.driver_key.clone(),
+        None,
+        printer.model.clone(),
+        Some(policy),
+        None,
+        Some(false),
+        // 修复的是本应用安装的队列：同名队列指向其他地址正是要修复的问题，改绑到配置地址
+        Some(super::queue_conflict::QueueConflictStrategy::Overwrite.as_str().to_string()),
+    )
+    .await;
+
+    let (action, job_id, message) = match result {
+        Ok(r) if r.success => ("repaired", Some(r.job_id), r.message),
+        Ok(r) => ("repair_failed", Some(r.job_id), r.message),
+        Err(e) => ("repair_failed", None, e),
+    };
+    WatchActionRecord {
+        printer_name: broken.printer_name.clone(),
+        system_queue_name: broken.system_queue_name.clone(),
+        issues: broken.issues.clone(),
+        action: action.to_string(),
+        job_id,
+        message,
+        at: chrono::Utc::now().timestamp_millis(),
+    }
+}
+
+/// 执行一轮巡检并按模式处理（自动修复 / 只提示）
+async fn run_cycle(app: &tauri::AppHandle, mode: &str) {
+    let report = match reconcile_printers().await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("[PrinterWatch] reconcile failed: {}", e);
+            return;
+        }
+    };
+    if report.broken.is_empty() {
+        return;
+    }
+
+    let mut actions = Vec::new();
+    if mode == "auto" {
+        let config = match tokio::task::spawn_blocking(crate::load_local_config).await {
+            Ok(Ok((config, _))) => config,
+            Ok(Err(e)) => {
+                eprintln!("[PrinterWatch] 读取配置失败，跳过修复: {}", e);
+                return;
+            }
+            Err(e) => {
+                eprintln!("[PrinterWatch] 读取配置任务异常: {}", e);
+                return;
+            }
+        };
+        for broken in &report.broken {
+            if let Some(printer) = find_config_printer(&config, &broken.printer_name) {
+                actions.push(repair_printer(app, printer, broken).await);
+            }
+        }
+    } else {
+        let now = chrono::Utc::now().timestamp_millis();
+        actions.extend(report.broken.iter().map(|broken| WatchActionRecord {
+            printer_name: broken.printer_name.clone(),
+            system_queue_name: broken.system_queue_name.clone(),
+            issues: broken.issues.clone(),
+            action: "notified".to_string(),
+            job_id: None,
+            message: "已提示用户重新安装".to_string(),
+            at: now,
+        }));
+    }
+
+    super::install_history::record_watch_actions(actions.clone()).await;
+    super::events::emit_printer_watch_report(
+        app,
+        &WatchCycleReport {
+            mode: mode.to_string(),
+            report,
+            actions,
+        },
+    );
+}
+
+/// 读取巡检模式与间隔（配置读取失败时视为关闭）
+fn watch_settings() -> (String, Duration) {
+    let watch = crate::load_local_config().ok().and_then(|(config, _)| config.printer_watch);
+    let mode = watch
+        .as_ref()
+        .and_then(|w| w.mode.clone())
+        .unwrap_or_else(|| "off".to_string());
+    let hours = watch
+        .and_then(|w| w.interval_hours)
+        .filter(|h| *h > 0)
+        .unwrap_or(DEFAULT_INTERVAL_HOURS);
+    (mode, Duration::from_secs(hours * 60 * 60))
+}
+
+/// 后台巡检主循环（在后台任务中运行，所有错误只记录日志）
+///
+/// 每轮开始时重新读取配置，printerWatch 的修改无需重启即可生效
+pub async fn run_printer_watch(app: tauri::AppHandle) {
+    tokio::time::sleep(STARTUP_DELAY).await;
+    loop {
+        let (mode, interval) = match tokio::task::spawn_blocking(watch_settings).await {
+            Ok(settings) => settings,
+            Err(e) => {
+                eprintln!("[PrinterWatch] 读取巡检配置任务异常: {}", e);
+                ("off".to_string(), DISABLED_RECHECK_INTERVAL)
+            }
+        };
+        if mode == "off" {
+            tokio::time::sleep(DISABLED_RECHECK_INTERVAL).await;
+            continue;
+        }
+        eprintln!("[PrinterWatch] cycle start mode={} interval_hours={}", mode, interval.as_secs() / 3600);
+        run_cycle(&app, &mode).await;
+        tokio::time::sleep(interval).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_changed_port_and_missing_driver_for_managed_queues_only() {
+        let config: PrinterConfig = serde_json::from_value(serde_json::json!({
+            "cities": [{
+                "cityId": "bj", "cityName": "北京",
+                "areas": [{
+                    "areaName": "3楼",
+                    "printers": [
+                        { "name": "前台", "path": "10.0.0.9", "driverKey": "HP" },
+                        { "name": "财务", "path": "printer-2f.corp.local", "driverKey": "HP" },
+                        { "name": "会议室", "path": "10.0.0.3", "driverKey": "HP" }
+                    ]
+                }]
+            }]
+        }))
+        .unwrap();
+        let entry = |name: &str, managed: bool| -> PrinterDetectEntry {
+            serde_json::from_value(serde_json::json!({
+                "installedKey": name, "systemQueueName": name, "platform": "windows",
+                "managed": managed, "configKey": if managed { Some(name) } else { None }
+            }))
+            .unwrap()
+        };
+        let env: PrintEnvironmentReport = serde_json::from_value(serde_json::json!({
+            "platform": "windows",
+            "generatedAt": "",
+            "queues": [
+                { "name": "前台", "driverName": "HP Universal", "portName": "IP_10_0_0_1" },
+                { "name": "财务", "driverName": "HP Universal", "portName": "IP_10_0_0_2" },
+                { "name": "会议室", "driverName": "Old Driver", "portName": "IP_10_0_0_3" }
+            ],
+            "drivers": [{ "name": "HP Universal" }],
+            "ports": [
+                { "name": "IP_10_0_0_1", "hostAddress": "10.0.0.1" },
+                { "name": "IP_10_0_0_2", "hostAddress": "10.0.0.2" },
+                { "name": "IP_10_0_0_3", "hostAddress": "10.0.0.3" }
+            ],
+            "policies": [],
+            "errors": []
+        }))
+        .unwrap();
+        let installed_ips = |name: &str| if name == "财务" { vec!["10.0.0.2".to_string()] } else { Vec::new() };
+
+        let (checked, broken) = find_broken_printers(&config, &[entry("前台", true), entry("财务", true), entry("会议室", false)], &env, installed_ips);
+        assert_eq!(checked, 2);
+        // hostname 打印机的端口以安装时解析到的 IP 创建，不视为变化；未被管理的队列不参与比对
+        assert_eq!(broken.len(), 1);
+        assert_eq!(broken[0].printer_name, "前台");
+        assert_eq!(broken[0].issues, vec!["port_changed".to_string()]);
+        assert_eq!(broken[0].expected_host.as_deref(), Some("10.0.0.9"));
+
+        let (_, broken) = find_broken_printers(&config, &[entry("会议室", true)], &env, installed_ips);
+        assert_eq!(broken[0].issues, vec!["driver_missing".to_string()]);
+    }
+}
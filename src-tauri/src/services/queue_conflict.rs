@@ -0,0 +1,198 @@
+/**
+ * 队列名冲突服务
+ * 职责：
+ * - 安装前检查目标队列名：同名队列已存在且指向其他地址时，按本次安装请求的 queueConflict 策略处理
+ *   - fail：不安装，返回带 QUEUE_CONFLICT 错误码的安装结果（非交互模式的默认值，避免无人值守时改掉别人的队列）
+ *   - overwrite：沿用同名队列，安装流程改绑驱动与端口（交互模式的默认值，与以往行为一致）
+ *   - rename-new：在名称后追加 " (2)"、" (3)" … 创建新队列；已有的追加名称指向目标地址时直接复用
+ * - 同名队列已指向目标地址（重新安装）或不存在时不做处理
+ */
+
+use crate::platform::QueueNameStatus;
+
+/// 按策略不安装时的错误码
+pub const QUEUE_CONFLICT: &str = "QUEUE_CONFLICT";
+
+/// rename-new 最多尝试的后缀序号
+const MAX_RENAME_SUFFIX: u32 = 20;
+
+/// 队列名冲突策略
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueueConflictStrategy {
+    Fail,
+    Overwrite,
+    RenameNew,
+}
+
+impl QueueConflictStrategy {
+    /// 解析安装请求中的策略名称
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "fail" => Ok(Self::Fail),
+            "overwrite" => Ok(Self::Overwrite),
+            "rename-new" => Ok(Self::RenameNew),
+            other => Err(format!("queueConflict \"{}\" 无效，可选值：fail / overwrite / rename-new", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fail => "fail",
+            Self::Overwrite => "overwrite",
+            Self::RenameNew => "rename-new",
+        }
+    }
+}
+
+/// 本次安装使用的策略（请求未指定时：非交互模式为 fail，交互模式为 overwrite）
+pub fn strategy_for_request(requested: Option<&str>) -> Result<QueueConflictStrategy, String> {
+    match requested.map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => QueueConflictStrategy::parse(value),
+        None if super::interaction_mode::is_non_interactive() => Ok(QueueConflictStrategy::Fail),
+        None => Ok(QueueConflictStrategy::Overwrite),
+    }
+}
+
+/// rename-new 的候选名称
+fn renamed_candidate(base: &str, suffix: u32) -> String {
+    format!("{} ({})", base, suffix)
+}
+
+/// 队列名冲突的处理结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueueNameResolution {
+    /// 使用该队列名安装（与 `queue_name` 参数含义相同，rename-new 改名时为新名称）
+    Use(Option<String>),
+    /// 不安装：错误信息已带 QUEUE_CONFLICT 错误码
+    Conflict(String),
+}
+
+/// 按策略确定实际创建的队列名（`check` 读取候选队列名的状态）
+fn resolve_with(
+    name: &str,
+    queue_name: Option<String>,
+    path: &str,
+    strategy: QueueConflictStrategy,
+    mut check: impl FnMut(&str) -> Result<QueueNameStatus, String>,
+) -> Result<QueueNameResolution, String> {
+    let target = queue_name.clone().unwrap_or_else(|| name.to_string());
+    let current_target = match check(&target) {
+        Ok(QueueNameStatus::Conflict { current_target }) => current_target,
+        Ok(_) => return Ok(QueueNameResolution::Use(queue_name)),
+        Err(e) => {
+            // 读取失败不阻断安装（与以往行为一致），由安装流程自行处理已存在的队列
+            eprintln!("[QueueConflict] queue=\"{}\" check failed, skip: {}", target, e);
+            return Ok(QueueNameResolution::Use(queue_name));
+        }
+    };
+    eprintln!(
+        "[QueueConflict] queue=\"{}\" points to \"{}\" expected=\"{}\" strategy={}",
+        target, current_target, path, strategy.as_str()
+    );
+
+    match strategy {
+        QueueConflictStrategy::Fail => Ok(QueueNameResolution::Conflict(format!(
+            "[EASYPRINTER_CODE={}] 队列 \"{}\" 已存在且指向 {}（本次安装地址为 {}），按 queueConflict=fail 未安装。\n\n可改用 queueConflict=\"overwrite\"（改绑已有队列）或 \"rename-new\"（以新名称创建队列）",
+            QUEUE_CONFLICT, target, current_target, path
+        ))),
+        QueueConflictStrategy::Overwrite => Ok(QueueNameResolution::Use(queue_name)),
+        QueueConflictStrategy::RenameNew => {
+            for suffix in 2..=MAX_RENAME_SUFFIX {
+                let candidate = renamed_candidate(&target, suffix);
+                match check(&candidate)? {
+                    QueueNameStatus::Conflict { .. } => continue,
+                    _ => {
+                        eprintln!("[QueueConflict] queue=\"{}\" rename-new -> \"{}\"", target, candidate);
+                        return Ok(QueueNameResolution::Use(Some(candidate)));
+                    }
+                }
+            }
+            Ok(QueueNameResolution::Conflict(format!(
+                "[EASYPRINTER_CODE={}] 队列 \"{}\" 已存在且指向 {}，\"{}\" 至 \"{}\" 均已被占用，无法以新名称安装",
+                QUEUE_CONFLICT,
+                target,
+                current_target,
+                renamed_candidate(&target, 2),
+                renamed_candidate(&target, MAX_RENAME_SUFFIX)
+            )))
+        }
+    }
+}
+
+/// 按策略确定实际创建的队列名
+///
+/// # 参数
+/// - `name`: 安装请求中的打印机名称
+/// - `queue_name`: nameTemplate 生成的队列名（None 表示使用 name）
+/// - `path`: 打印机地址
+/// - `strategy`: 冲突策略
+///
+/// # 返回
+/// - `Ok(QueueNameResolution::Use)`: 按该队列名继续安装
+/// - `Ok(QueueNameResolution::Conflict)`: fail 策略下存在冲突，或 rename-new 找不到可用名称
+/// - `Err(String)`: 检查任务异常，或 rename-new 读取候选队列失败
+pub async fn resolve_queue_name(
+    name: &str,
+    queue_name: Option<String>,
+    path: &str,
+    strategy: QueueConflictStrategy,
+) -> Result<QueueNameResolution, String> {
+    let name = name.to_string();
+    let path = path.to_string();
+    tokio::task::spawn_blocking(move || {
+        resolve_with(&name, queue_name, &path, strategy, |candidate| crate::platform::check_queue_name(candidate, &path))
+    })
+    .await
+    .map_err(|e| format!("检查队列名冲突失败: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_strategies_and_builds_rename_candidates() {
+        assert_eq!(QueueConflictStrategy::parse("rename-new").unwrap(), QueueConflictStrategy::RenameNew);
+        assert_eq!(QueueConflictStrategy::parse(" fail ").unwrap().as_str(), "fail");
+        assert!(QueueConflictStrategy::parse("delete").is_err());
+        assert_eq!(strategy_for_request(Some("overwrite")).unwrap(), QueueConflictStrategy::Overwrite);
+        assert_eq!(renamed_candidate("财务部 HP M404", 2), "财务部 HP M404 (2)");
+    }
+
+    /// 模拟已被其他地址占用的队列名
+    fn taken(names: Vec<String>) -> impl FnMut(&str) -> Result<QueueNameStatus, String> {
+        move |candidate| {
+            Ok(match names.iter().any(|n| n == candidate) {
+                true => QueueNameStatus::Conflict { current_target: "10.0.0.9".to_string() },
+                false => QueueNameStatus::Free,
+            })
+        }
+    }
+
+    #[test]
+    fn resolves_queue_name_per_strategy() {
+        let path = "10.0.0.5";
+        let hp = || vec!["HP".to_string()];
+
+        let free = resolve_with("HP", None, path, QueueConflictStrategy::Fail, taken(vec![])).unwrap();
+        assert_eq!(free, QueueNameResolution::Use(None));
+
+        match resolve_with("HP", None, path, QueueConflictStrategy::Fail, taken(hp())).unwrap() {
+            QueueNameResolution::Conflict(message) => assert!(message.starts_with("[EASYPRINTER_CODE=QUEUE_CONFLICT]")),
+            other => panic!("unexpected {:?}", other),
+        }
+        let overwrite = resolve_with("HP", None, path, QueueConflictStrategy::Overwrite, taken(hp())).unwrap();
+        assert_eq!(overwrite, QueueNameResolution::Use(None));
+
+        let renamed = resolve_with("HP", None, path, QueueConflictStrategy::RenameNew, taken(vec!["HP".into(), "HP (2)".into()]));
+        assert_eq!(renamed.unwrap(), QueueNameResolution::Use(Some("HP (3)".to_string())));
+
+        let all_taken = hp().into_iter().chain((2..=MAX_RENAME_SUFFIX).map(|n| renamed_candidate("HP", n))).collect();
+        let exhausted = resolve_with("HP", None, path, QueueConflictStrategy::RenameNew, taken(all_taken)).unwrap();
+        assert!(matches!(exhausted, QueueNameResolution::Conflict(message) if message.contains("QUEUE_CONFLICT")));
+
+        // 读取失败不阻断安装
+        let unreadable = resolve_with("HP", None, path, QueueConflictStrategy::Fail, |_: &str| Err("拒绝访问".to_string()));
+        assert_eq!(unreadable.unwrap(), QueueNameResolution::Use(None));
+    }
+}
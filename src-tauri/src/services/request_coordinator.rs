@@ -0,0 +1,94 @@
+/**
+ * 远程请求协调器
+ * 职责：
+ * - 按 endpoint 做 in-flight 去重：同一时刻只有一个请求真正发出，其余调用等待并复用结果
+ * - 最小刷新间隔：间隔内的重复调用直接复用上次成功结果，不再发起 HTTP 请求
+ *
+ * 由 load_config / confirm_update_config / refresh_remote_config / check_version_update 共用
+ */
+
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+
+/// printer_config.json 最小刷新间隔
+pub const REMOTE_CONFIG_MIN_INTERVAL: Duration = Duration::from_secs(5);
+
+/// version_config.json 最小刷新间隔
+pub const VERSION_CONFIG_MIN_INTERVAL: Duration = Duration::from_secs(10);
+
+/// printer_config.json 请求协调状态
+pub static REMOTE_CONFIG: Endpoint<super::config_sources::RemoteConfig> =
+    Endpoint::new("printer_config", REMOTE_CONFIG_MIN_INTERVAL);
+
+/// version_config.json 请求协调状态
+pub static VERSION_CONFIG: Endpoint<crate::VersionConfig> =
+    Endpoint::new("version_config", VERSION_CONFIG_MIN_INTERVAL);
+
+/// 单个 endpoint 的最近一次请求结果
+struct LastResult<T> {
+    finished_at: Instant,
+    result: Result<T, String>,
+}
+
+/// 单个 endpoint 的协调状态
+pub struct Endpoint<T> {
+    name: &'static str,
+    min_interval: Duration,
+    // 持有锁即代表请求 in-flight；后到的调用在锁上排队
+    last: Mutex<Option<LastResult<T>>>,
+}
+
+impl<T: Clone> Endpoint<T> {
+    pub const fn new(name: &'static str, min_interval: Duration) -> Self {
+        Self {
+            name,
+            min_interval,
+            last: Mutex::const_new(None),
+        }
+    }
+
+    /// 通过协调器执行请求
+    ///
+    /// # 参数
+    /// - `fetch`: 真正发起请求的闭包（仅在需要时调用）
+    ///
+    /// # 返回
+    /// - 复用或新获取的结果
+    ///
+    /// # 复用规则
+    /// 1. 请求在本次调用排队期间完成 → 复用该结果（无论成功失败，即 in-flight 去重）
+    /// 2. 上次成功结果仍在最小刷新间隔内 → 复用
+    /// 3. 否则发起新请求
+    pub async fn run<F, Fut>(&self, fetch: F) -> Result<T, String>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let arrived_at = Instant::now();
+        let mut guard = self.last.lock().await;
+
+        if let Some(last) = guard.as_ref() {
+            if last.finished_at >= arrived_at {
+                eprintln!("[RequestCoordinator] endpoint={} action=reuse_inflight ok={}", self.name, last.result.is_ok());
+                return last.result.clone();
+            }
+            if last.result.is_ok() && last.finished_at.elapsed() < self.min_interval {
+                eprintln!(
+                    "[RequestCoordinator] endpoint={} action=reuse_recent age_ms={}",
+                    self.name,
+                    last.finished_at.elapsed().as_millis()
+                );
+                return last.result.clone();
+            }
+        }
+
+        eprintln!("[RequestCoordinator] endpoint={} action=fetch", self.name);
+        let result = fetch().await;
+        *guard = Some(LastResult {
+            finished_at: Instant::now(),
+            result: result.clone(),
+        });
+        result
+    }
+}
@@ -0,0 +1,173 @@
+/**
+ * 逐跳路由诊断服务（跨 VLAN 打印故障排查）
+ * 职责：
+ * - 打印机不可达时，对 IPv4 地址发送 TTL 从 1 递增的单次 ping（类似 traceroute），记录每一跳的响应地址
+ * - 到达目标、或连续 MAX_SILENT_HOPS 跳无响应时停止，最多探测 MAX_HOPS 跳
+ * - 汇总第一个无响应的跳数与最后一个有响应的路由器，供网络组判断数据包在哪里被丢弃
+ *   （常见原因：缺少静态路由、VLAN 间 ACL 拦截）
+ */
+
+use serde::{Deserialize, Serialize};
+use std::net::Ipv4Addr;
+
+/// 最多探测的跳数
+const MAX_HOPS: u8 = 15;
+
+/// 连续无响应的跳数达到该值时停止
+const MAX_SILENT_HOPS: usize = 3;
+
+/// 单跳等待回复的超时时间（毫秒）
+const HOP_TIMEOUT_MS: u32 = 1000;
+
+/// 单跳探测结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteHop {
+    pub ttl: u8,
+    /// 回复的地址（中途路由器或目标；None 表示超时无响应）
+    pub responder: Option<String>,
+}
+
+/// 逐跳诊断结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RouteTrace {
+    pub target: String,
+    pub hops: Vec<RouteHop>,
+    /// ICMP 能到达目标（此时打印端口不通多为防火墙 / 打印机服务问题）
+    pub reached_target: bool,
+    /// 第一个无响应的跳数（其后再无任何响应）
+    pub first_unreachable_hop: Option<u8>,
+    /// 最后一个有响应的中途路由器
+    pub last_responding_hop: Option<String>,
+    pub message: String,
+}
+
+/// 从 ping 输出中取出回复地址（第一个不等于目标的 IPv4 地址；标题 / 统计行中的目标地址不算）
+///
+/// # 返回
+/// - `Some(addr)`: 中途路由器回复（TTL 耗尽 / 目标不可达）
+/// - `None`: 输出中只有目标地址或没有地址
+fn intermediate_responder(output: &str, target: Ipv4Addr) -> Option<Ipv4Addr> {
+    output
+        .split(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .filter_map(|token| token.trim_matches('.').parse::<Ipv4Addr>().ok())
+        .find(|addr| *addr != target)
+}
+
+/// 根据各跳结果生成诊断汇总
+fn summarize(target: Ipv4Addr, hops: Vec<RouteHop>, reached_target: bool) -> RouteTrace {
+    let last_response_index = hops.iter().rposition(|h| h.responder.is_some());
+    let first_unreachable_hop = if reached_target {
+        None
+    } else {
+        let start = last_response_index.map(|i| i + 1).unwrap_or(0);
+        hops.get(start).map(|h| h.ttl)
+    };
+    let target_text = target.to_string();
+    let last_responding_hop = hops
+        .iter()
+        .filter_map(|h| h.responder.clone())
+        .rfind(|r| *r != target_text);
+
+    let message = if reached_target {
+        format!(
+            "ICMP 可到达 {}（{} 跳），网络路径正常；打印端口不通请检查打印机本身或其防火墙",
+            target,
+            hops.len()
+        )
+    } else {
+        match (first_unreachable_hop, &last_responding_hop) {
+            (Some(ttl), Some(router)) => format!(
+                "数据包在第 {} 跳丢失（最后响应的路由器 {}），请联系网络组检查该路由器到打印机网段的路由 / VLAN 访问控制",
+                ttl, router
+            ),
+            (Some(_), None) => "第 1 跳即无响应，请检查本机网关或网络连接".to_string(),
+            _ => format!("{} 跳内未到达 {}", hops.len(), target),
+        }
+    };
+
+    RouteTrace {
+        target: target_text,
+        hops,
+        reached_target,
+        first_unreachable_hop,
+        last_responding_hop,
+        message,
+    }
+}
+
+/// 逐跳探测（阻塞调用）
+fn trace_route_blocking(target: Ipv4Addr) -> Result<RouteTrace, String> {
+    let mut hops = Vec::new();
+    let mut reached = false;
+    let mut silent = 0;
+    for ttl in 1..=MAX_HOPS {
+        let probe = crate::platform::ping_with_ttl(target, ttl, HOP_TIMEOUT_MS)?;
+        let responder = match intermediate_responder(&probe.output, target) {
+            Some(router) => Some(router.to_string()),
+            None if probe.success => Some(target.to_string()),
+            None => None,
+        };
+        reached = responder.as_deref() == Some(target.to_string().as_str());
+        silent = if responder.is_some() { 0 } else { silent + 1 };
+        hops.push(RouteHop { ttl, responder });
+        if reached || silent >= MAX_SILENT_HOPS {
+            break;
+        }
+    }
+    let trace = summarize(target, hops, reached);
+    eprintln!(
+        "[RouteTrace] target={} hops={} reached={} first_unreachable_hop={:?} last_responding_hop={:?}",
+        trace.target,
+        trace.hops.len(),
+        trace.reached_target,
+        trace.first_unreachable_hop,
+        trace.last_responding_hop
+    );
+    Ok(trace)
+}
+
+/// 对 IPv4 地址做逐跳路由诊断
+///
+/// # 参数
+/// - `target`: 打印机 IPv4 地址
+///
+/// # 返回
+/// - `Ok(RouteTrace)`: 诊断结果
+/// - `Err(String)`: 当前平台不支持或 ping 无法执行
+pub async fn trace_route(target: Ipv4Addr) -> Result<RouteTrace, String> {
+    tokio::task::spawn_blocking(move || trace_route_blocking(target))
+        .await
+        .map_err(|e| format!("逐跳诊断任务异常: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_hop_where_packets_die() {
+        let target: Ipv4Addr = "10.20.3.15".parse().unwrap();
+        let en = "Pinging 10.20.3.15 with 32 bytes of data:\r\nReply from 10.1.0.1: TTL expired in transit.\r\n\r\nPing statistics for 10.20.3.15:";
+        let zh = "正在 Ping 10.20.3.15 具有 32 字节的数据:\r\n来自 192.168.1.1 的回复: TTL 传输中过期。";
+        let direct = "Reply from 10.20.3.15: bytes=32 time<1ms TTL=64";
+        assert_eq!(intermediate_responder(en, target), Some("10.1.0.1".parse().unwrap()));
+        assert_eq!(intermediate_responder(zh, target), Some("192.168.1.1".parse().unwrap()));
+        assert_eq!(intermediate_responder(direct, target), None);
+        assert_eq!(intermediate_responder("Request timed out.", target), None);
+
+        let hop = |ttl, responder: Option<&str>| RouteHop { ttl, responder: responder.map(str::to_string) };
+        let trace = summarize(
+            target,
+            vec![hop(1, Some("192.168.1.1")), hop(2, None), hop(3, Some("10.1.0.1")), hop(4, None), hop(5, None), hop(6, None)],
+            false,
+        );
+        assert_eq!(trace.first_unreachable_hop, Some(4));
+        assert_eq!(trace.last_responding_hop.as_deref(), Some("10.1.0.1"));
+
+        let reached = summarize(target, vec![hop(1, Some("192.168.1.1")), hop(2, Some("10.20.3.15"))], true);
+        assert_eq!(reached.first_unreachable_hop, None);
+        assert_eq!(reached.last_responding_hop.as_deref(), Some("192.168.1.1"));
+    }
+}
@@ -0,0 +1,407 @@
+/**
+ * 端到端自检（CI / 现场工程师验证构建与目标镜像）
+ * 职责：
+ * - 隐藏命令 self_test 与命令行 `--self-test [--self-test-output=<路径>]`（不显示窗口，写出报告后退出，
+ *   退出码 0 = 全部通过 / 1 = 有失败阶段）
+ * - 依次执行各阶段，每个阶段给出 passed / failed / skipped 与说明（前置阶段失败时后续阶段 skipped）：
+ *   1. config_load: 加载并校验本地配置
+ *   2. fixture_package: 在临时目录生成夹具驱动包（UTF-16LE INF）并确定性打包为 ZIP
+ *   3. driver_fetch: 启动 127.0.0.1 上的夹具 HTTP 服务，走安装流程同一套下载 / 校验实现获取驱动包
+ *   4. zip_extract: 走安装流程同一套解压实现解压驱动包
+ *   5. inf_parse: 解析解压出的 INF，核对 Class 与型号名称
+ *   6. install_plan: 对配置中的每台打印机做安装前检查（参数、driverKey、驱动规格），并以 dryRun 安装第一台
+ * - 所有文件写入临时目录，结束后删除；不修改真实驱动目录，不安装任何打印机
+ */
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// 命令行参数：以自检模式启动
+const SELF_TEST_ARG: &str = "--self-test";
+
+/// 命令行参数前缀：报告输出路径
+const OUTPUT_ARG_PREFIX: &str = "--self-test-output=";
+
+/// 报告默认文件名（写入 meta 目录）
+const DEFAULT_REPORT_FILE: &str = "self_test_report.json";
+
+/// 夹具驱动包的型号名称
+const FIXTURE_MODEL: &str = "ePrinty Self-Test Printer";
+
+/// 夹具驱动包目录名（ZIP 顶层目录）
+const FIXTURE_DIR: &str = "ePrintySelfTest";
+
+/// 夹具 INF（写入时编码为 UTF-16LE BOM，与多数厂商驱动一致）
+const FIXTURE_INF: &str = "[Version]\r\n\
+Signature=\"$Windows NT$\"\r\n\
+Class=Printer\r\n\
+ClassGUID={4D36E979-E325-11CE-BFC1-08002BE10318}\r\n\
+Provider=%MFG%\r\n\
+DriverVer=01/01/2024,1.0.0.0\r\n\
+\r\n\
+[Manufacturer]\r\n\
+%MFG% = ePrinty, NTamd64\r\n\
+\r\n\
+[ePrinty.NTamd64]\r\n\
+%MODEL% = SELFTEST, ePrintySelfTest0001\r\n\
+\r\n\
+[Strings]\r\n\
+MFG = \"ePrinty\"\r\n\
+MODEL = \"ePrinty Self-Test Printer\"\r\n";
+
+/// 阶段状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StageStatus {
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// 单个阶段的结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestStage {
+    pub stage: String,
+    pub status: StageStatus,
+    pub elapsed_ms: u64,
+    /// 通过时为摘要，失败时为错误信息，跳过时为原因
+    pub detail: String,
+}
+
+/// 自检报告
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SelfTestReport {
+    /// 没有失败的阶段
+    pub passed: bool,
+    pub app_version: String,
+    pub platform: String,
+    /// 开始时间（Unix 毫秒）
+    pub started_at: i64,
+    pub elapsed_ms: u64,
+    pub stages: Vec<SelfTestStage>,
+}
+
+/// 夹具驱动包
+struct FixturePackage {
+    zip_bytes: Vec<u8>,
+    sha256: String,
+}
+
+/// 顺序执行阶段并收集结果
+#[derive(Default)]
+struct StageRunner {
+    stages: Vec<SelfTestStage>,
+}
+
+impl StageRunner {
+    /// 执行阶段：`Ok((值, 摘要))` 记为通过并返回值，`Err` 记为失败
+    async fn run<T, Fut>(&mut self, stage: &str, fut: Fut) -> Option<T>
+    where
+        Fut: Future<Output = Result<(T, String), String>>,
+    {
+        let started = Instant::now();
+        let outcome = fut.await;
+        let elapsed_ms = started.elapsed().as_millis() as u64;
+        let (status, detail, value) = match outcome {
+            Ok((value, detail)) => (StageStatus::Passed, detail, Some(value)),
+            Err(error) => (StageStatus::Failed, error, None),
+        };
+        eprintln!("[SelfTest] stage={} status={:?} elapsed_ms={} detail={}", stage, status, elapsed_ms, detail);
+        self.stages.push(SelfTestStage { stage: stage.to_string(), status, elapsed_ms, detail });
+        value
+    }
+
+    /// 记录跳过的阶段
+    fn skip<T>(&mut self, stage: &str, reason: impl Into<String>) -> Option<T> {
+        let detail = reason.into();
+        eprintln!("[SelfTest] stage={} status=Skipped detail={}", stage, detail);
+        self.stages.push(SelfTestStage { stage: stage.to_string(), status: StageStatus::Skipped, elapsed_ms: 0, detail });
+        None
+    }
+
+    fn passed(&self) -> bool {
+        self.stages.iter().all(|s| s.status != StageStatus::Failed)
+    }
+}
+
+async fn load_config() -> Result<(crate::PrinterConfig, String), String> {
+    let (config, path) = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("加载配置任务异常: {}", e))??;
+    let printers: usize = config.cities.iter().flat_map(|c| &c.areas).map(|a| a.printers.len()).sum();
+    let detail = format!("path={} version={:?} printers={}", path.display(), config.version, printers);
+    Ok((config, detail))
+}
+
+/// 生成夹具驱动包：<dir>/ePrintySelfTest/selftest.inf → <dir>/fixture.zip
+fn build_fixture(dir: &Path) -> Result<(FixturePackage, String), String> {
+    let source_dir = dir.join(FIXTURE_DIR);
+    std::fs::create_dir_all(&source_dir).map_err(|e| format!("创建夹具目录失败: {}", e))?;
+    let mut inf_bytes = vec![0xFF, 0xFE];
+    inf_bytes.extend(FIXTURE_INF.encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    std::fs::write(source_dir.join("selftest.inf"), &inf_bytes).map_err(|e| format!("写入夹具 INF 失败: {}", e))?;
+
+    let zip_path = dir.join("fixture.zip");
+    let report = crate::utils::zip_pack::pack_dir_deterministic(&source_dir, &zip_path)?;
+    let zip_bytes = std::fs::read(&zip_path).map_err(|e| format!("读取夹具驱动包失败: {}", e))?;
+    let sha256 = format!("{:x}", Sha256::digest(&zip_bytes));
+    let detail = format!("files={} bytes={} sha256={}", report.files_packed, zip_bytes.len(), sha256);
+    Ok((FixturePackage { zip_bytes, sha256 }, detail))
+}
+
+/// 夹具 HTTP 服务：对任意路径的 HEAD / GET 返回驱动包（每个连接处理一个请求）
+async fn serve_fixture(listener: tokio::net::TcpListener, body: std::sync::Arc<Vec<u8>>) {
+    loop {
+        let Ok((mut stream, _)) = listener.accept().await else {
+            return;
+        };
+        let body = body.clone();
+        tokio::spawn(async move {
+            let mut request = Vec::new();
+            let mut buf = [0u8; 1024];
+            while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 16 * 1024 {
+                match stream.read(&mut buf).await {
+                    Ok(0) | Err(_) => return,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let head = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/zip\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                body.len()
+            );
+            let _ = stream.write_all(head.as_bytes()).await;
+            if !request.starts_with(b"HEAD ") {
+                let _ = stream.write_all(&body).await;
+            }
+            let _ = stream.shutdown().await;
+        });
+    }
+}
+
+async fn fetch_fixture(
+    drivers_root: &Path,
+    fixture: &FixturePackage,
+) -> Result<(crate::platform::FetchedDriverPayload, String), String> {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0")
+        .await
+        .map_err(|e| format!("启动夹具 HTTP 服务失败: {}", e))?;
+    let addr = listener.local_addr().map_err(|e| format!("读取夹具 HTTP 服务地址失败: {}", e))?;
+    let server = tokio::spawn(serve_fixture(listener, std::sync::Arc::new(fixture.zip_bytes.clone())));
+    let url = format!("http://{}/drivers/fixture.zip", addr);
+    let fetched = crate::platform::fetch_driver_payload(
+        drivers_root,
+        &url,
+        &fixture.sha256,
+        Some(fixture.zip_bytes.len() as u64),
+        "self_test",
+    )
+    .await;
+    server.abort();
+    let fetched = fetched?;
+    let detail = format!("url={} source={} bytes={}", url, fetched.source_used, fetched.bytes);
+    Ok((fetched, detail))
+}
+
+fn extract_fixture(
+    drivers_root: &Path,
+    fetched: &crate::platform::FetchedDriverPayload,
+) -> Result<(PathBuf, String), String> {
+    let (extracted_root, file_count) =
+        crate::platform::extract_driver_payload(&fetched.payload_zip, drivers_root, &fetched.driver_uuid, "self_test")?;
+    let detail = format!("extracted_root={} files={}", extracted_root.display(), file_count);
+    Ok((extracted_root, detail))
+}
+
+/// 核对夹具 INF 的解析结果
+fn check_fixture_inf(summary: &crate::utils::inf_parse::InfSummary) -> Result<String, String> {
+    if !summary.is_printer_class() {
+        return Err(format!("INF Class={:?}，应为 Printer", summary.class));
+    }
+    if !summary.model_names.iter().any(|m| m == FIXTURE_MODEL) {
+        return Err(format!("INF 型号 {:?} 中缺少 \"{}\"", summary.model_names, FIXTURE_MODEL));
+    }
+    Ok(format!("class=Printer driver_version={:?} models={:?}", summary.driver_version, summary.model_names))
+}
+
+fn parse_fixture_inf(extracted_root: &Path) -> Result<((), String), String> {
+    let inf_path = walkdir::WalkDir::new(extracted_root)
+        .into_iter()
+        .flatten()
+        .map(|entry| entry.into_path())
+        .find(|path| path.extension().map(|ext| ext.eq_ignore_ascii_case("inf")).unwrap_or(false))
+        .ok_or_else(|| format!("解压目录中没有 INF 文件: {}", extracted_root.display()))?;
+    let text = crate::utils::inf_parse::read_inf_text(&inf_path)?;
+    let detail = check_fixture_inf(&crate::utils::inf_parse::parse_inf(&text))?;
+    Ok(((), detail))
+}
+
+/// 安装前检查每台打印机，并以 dryRun 安装第一台
+async fn plan_installs(app: &tauri::AppHandle, config: &crate::PrinterConfig) -> Result<((), String), String> {
+    let printers: Vec<crate::Printer> = config
+        .cities
+        .iter()
+        .flat_map(|c| &c.areas)
+        .flat_map(|a| &a.printers)
+        .filter_map(|p| crate::find_printer_with_defaults(config, &p.name, &p.path))
+        .collect();
+    if printers.is_empty() {
+        return Err("配置中没有打印机".to_string());
+    }
+
+    let mut problems = Vec::new();
+    for printer in &printers {
+        if let Err(errors) = crate::platform::install_input::validate_install_input(&printer.name, &printer.path, printer.model.as_deref()) {
+            problems.push(format!("\"{}\": 参数校验失败 {:?}", printer.name, errors));
+            continue;
+        }
+        let Some(driver_key) = printer.driver_key.as_deref() else {
+            problems.push(format!("\"{}\": 缺少 driverKey", printer.name));
+            continue;
+        };
+        if !config.driver_catalog.as_ref().map(|c| c.contains_key(driver_key)).unwrap_or(false) {
+            problems.push(format!("\"{}\": driverKey=\"{}\" 不在 driverCatalog 中", printer.name, driver_key));
+            continue;
+        }
+        let spec = crate::resolve_effective_driver_spec(printer, config.driver_catalog.as_ref());
+        eprintln!(
+            "[SelfTest] plan printer=\"{}\" source={} install_mode={:?} driver_names={:?}",
+            printer.name, spec.source, spec.effective_install_mode, spec.effective_driver_names
+        );
+    }
+    if !problems.is_empty() {
+        return Err(format!("{} 台打印机未通过安装前检查：{}", problems.len(), problems.join("；")));
+    }
+
+    let first = &printers[0];
+    let policy = first.options.driver_install_policy.clone().unwrap_or_else(|| "always".to_string());
+    let result = crate::install_printer(
+        app.clone(),
+        first.name.clone(),
+        first.path.clone(),
+        first.driver_key.clone(),
+        None,
+        first.model.clone(),
+        Some(policy),
+        None,
+        Some(true),
+        None,
+    )
+    .await?;
+    if !result.success || !result.effective_dry_run {
+        return Err(format!("dryRun 安装 \"{}\" 失败: {}", first.name, result.message));
+    }
+    Ok(((), format!("printers={} dry_run=\"{}\" method={:?}", printers.len(), first.name, result.method)))
+}
+
+/// 执行全部自检阶段
+pub async fn run_self_test(app: &tauri::AppHandle) -> SelfTestReport {
+    let started = Instant::now();
+    let started_at = chrono::Utc::now().timestamp_millis();
+    let mut runner = StageRunner::default();
+
+    let config = runner.run("config_load", load_config()).await;
+
+    let work_dir = tempfile::Builder::new().prefix("eprinty_self_test").tempdir();
+    let work_dir = match work_dir {
+        Ok(dir) => Some(dir),
+        Err(e) => runner.skip("fixture_package", format!("创建临时目录失败: {}", e)),
+    };
+    let fixture = match &work_dir {
+        Some(dir) => runner.run("fixture_package", async { build_fixture(dir.path()) }).await,
+        None => None,
+    };
+
+    let drivers_root = work_dir.as_ref().map(|dir| dir.path().join("drivers"));
+    let fetched = match (&fixture, &drivers_root) {
+        _ if !cfg!(windows) => runner.skip("driver_fetch", "当前平台不使用驱动包"),
+        (Some(fixture), Some(root)) => runner.run("driver_fetch", fetch_fixture(root, fixture)).await,
+        _ => runner.skip("driver_fetch", "fixture_package 未通过"),
+    };
+    let extracted = match (&fetched, &drivers_root) {
+        _ if !cfg!(windows) => runner.skip("zip_extract", "当前平台不使用驱动包"),
+        (Some(fetched), Some(root)) => runner.run("zip_extract", async { extract_fixture(root, fetched) }).await,
+        _ => runner.skip("zip_extract", "driver_fetch 未通过"),
+    };
+    match &extracted {
+        _ if !cfg!(windows) => runner.skip::<()>("inf_parse", "当前平台不使用驱动包"),
+        Some(root) => runner.run("inf_parse", async { parse_fixture_inf(root) }).await,
+        None => runner.skip("inf_parse", "zip_extract 未通过"),
+    };
+    match &config {
+        Some(config) => runner.run("install_plan", plan_installs(app, config)).await,
+        None => runner.skip("install_plan", "config_load 未通过"),
+    };
+
+    let report = SelfTestReport {
+        passed: runner.passed(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        platform: std::env::consts::OS.to_string(),
+        started_at,
+        elapsed_ms: started.elapsed().as_millis() as u64,
+        stages: runner.stages,
+    };
+    eprintln!("[SelfTest] finished passed={} elapsed_ms={}", report.passed, report.elapsed_ms);
+    report
+}
+
+/// 当前进程是否以自检模式启动
+pub fn self_test_requested() -> bool {
+    std::env::args().skip(1).any(|arg| arg.eq_ignore_ascii_case(SELF_TEST_ARG))
+}
+
+/// 报告输出路径（--self-test-output=<路径>，未指定时为 meta 目录下的 self_test_report.json）
+fn report_output_path() -> Result<PathBuf, String> {
+    if let Some(path) = std::env::args().skip(1).find_map(|arg| arg.strip_prefix(OUTPUT_ARG_PREFIX).map(PathBuf::from)) {
+        return Ok(path);
+    }
+    Ok(super::fs_paths::get_meta_dir()?.join(DEFAULT_REPORT_FILE))
+}
+
+/// 命令行自检：执行全部阶段、写出报告
+///
+/// # 返回
+/// - 进程退出码（0 = 全部通过，1 = 有失败阶段或报告写入失败）
+pub async fn run_from_command_line(app: &tauri::AppHandle) -> i32 {
+    let report = run_self_test(app).await;
+    let written = report_output_path().and_then(|path| {
+        let json = serde_json::to_vec_pretty(&report).map_err(|e| format!("序列化自检报告失败: {}", e))?;
+        std::fs::write(&path, json).map_err(|e| format!("写入自检报告失败 {}: {}", path.display(), e))?;
+        Ok(path)
+    });
+    match written {
+        Ok(path) => eprintln!("[SelfTest] report written to {}", path.display()),
+        Err(e) => {
+            eprintln!("[SelfTest] {}", e);
+            return 1;
+        }
+    }
+    if report.passed {
+        0
+    } else {
+        1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixture_inf_survives_utf16_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let (fixture, _) = build_fixture(dir.path()).unwrap();
+        assert_eq!(fixture.sha256.len(), 64);
+
+        let inf_path = dir.path().join(FIXTURE_DIR).join("selftest.inf");
+        let text = crate::utils::inf_parse::read_inf_text(&inf_path).unwrap();
+        let summary = crate::utils::inf_parse::parse_inf(&text);
+        assert!(check_fixture_inf(&summary).is_ok());
+        assert_eq!(summary.driver_version.as_deref(), Some("1.0.0.0"));
+    }
+}
@@ -0,0 +1,296 @@
+/**
+ * 退出时的安装任务处理
+ * 职责：
+ * - 关闭主窗口时检查进行中的安装任务：没有任务直接关闭；有任务时阻止关闭，
+ *   交互模式发送 shutdown_requested 事件由前端询问，非交互模式直接等待
+ * - shutdown_app：最多等待 shutdownGraceSecs 秒让任务自然结束，超时后请求取消（协作式，
+ *   正在执行的 pnputil 会被终止），再等待取消生效后退出
+ * - 取消后按任务结果保存：仍未结束或以失败 / 取消结束的任务保存到本地 meta 存储（unfinished_jobs.json），
+ *   宽限期后才成功结束的任务不保存；下次启动时
+ *   get_unfinished_jobs 列出，resolve_unfinished_jobs 按当前配置重新安装或放弃
+ * - restart：与 shutdown_app 相同的等待 / 保存流程，结束后重新启动应用（WebView 异常恢复使用）
+ */
+
+use super::meta_store::MetaStore;
+use crate::platform::ActiveInstallJob;
+use tauri::Manager;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+/// 未完成任务存储（schemaVersion=1）
+static UNFINISHED_JOBS: MetaStore<UnfinishedJobs> = MetaStore::new("unfinished_jobs", 1);
+
+/// 已开始退出流程（再次关闭窗口不重复处理；退出本身触发的关闭直接放行）
+static SHUTTING_DOWN: AtomicBool = AtomicBool::new(false);
+
+/// 请求取消后等待任务结束的最长时间
+const CANCEL_WAIT: Duration = Duration::from_secs(10);
+
+/// 轮询任务状态的间隔
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 退出时未完成的安装任务
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfinishedJob {
+    #[serde(flatten)]
+    pub job: ActiveInstallJob,
+    /// "cancelled"（已响应取消）| "terminated"（取消未生效，随进程退出被终止）
+    pub reason: String,
+    pub interrupted_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnfinishedJobs {
+    pub jobs: Vec<UnfinishedJob>,
+}
+
+/// shutdown_requested 事件内容
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownRequest {
+    pub jobs: Vec<ActiveInstallJob>,
+    pub grace_secs: u64,
+}
+
+/// 重新安装未完成任务的结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResumedJob {
+    pub printer_name: String,
+    pub success: bool,
+    pub job_id: Option<String>,
+    pub message: String,
+}
+
+/// 等待全部任务结束（超时返回 false）
+async fn wait_until_idle(timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if crate::platform::active_install_job_count() == 0 {
+            return true;
+        }
+        if Instant::now() >= deadline {
+            return false;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+/// 由取消时的任务、仍未结束的任务与已结束任务的结果得出需要保存的未完成任务
+///
+/// 测试模式的任务与最终安装成功的任务不保存；已结束但查不到结果的任务按已取消保存
+fn unfinished_jobs(
+    cancelled: Vec<ActiveInstallJob>,
+    still_active: &HashSet<String>,
+    outcome: impl Fn(&str) -> Option<bool>,
+    now: i64,
+) -> Vec<UnfinishedJob> {
+    cancelled
+        .into_iter()
+        .filter(|job| !job.dry_run)
+        .filter_map(|job| {
+            let reason = if still_active.contains(&job.job_id) {
+                "terminated"
+            } else if outcome(&job.job_id) == Some(true) {
+                return None;
+            } else {
+                "cancelled"
+            };
+            Some(UnfinishedJob { reason: reason.to_string(), job, interrupted_at: now })
+        })
+        .collect()
+}
+
+/// 等待 / 取消进行中的任务，返回未完成的任务
+async fn drain_jobs(grace: Duration) -> Vec<UnfinishedJob> {
+    if wait_until_idle(grace).await {
+        eprintln!("[Shutdown] all install jobs finished");
+        return Vec::new();
+    }
+    let remaining = crate::platform::active_install_jobs();
+    let cancelled = crate::platform::cancel_all_install_jobs();
+    eprintln!("[Shutdown] grace period elapsed, cancel requested jobs={}", cancelled);
+    wait_until_idle(CANCEL_WAIT).await;
+    let still_active: HashSet<String> = crate::platform::active_install_jobs().into_iter().map(|j| j.job_id).collect();
+    unfinished_jobs(
+        remaining,
+        &still_active,
+        crate::platform::install_job_outcome,
+        chrono::Utc::now().timestamp_millis(),
+    )
+}
+
+/// 等待 / 取消任务并保存未完成任务
+async fn drain_and_save(cancel_now: bool) {
+    SHUTTING_DOWN.store(true, Ordering::SeqCst);
+    let grace = if cancel_now {
+        Duration::ZERO
+    } else {
+        Duration::from_secs(super::user_settings::current().shutdown_grace_secs)
+    };
+    eprintln!(
+        "[Shutdown] draining jobs={} grace_secs={}",
+        crate::platform::active_install_job_count(),
+        grace.as_secs()
+    );
+    let unfinished = drain_jobs(grace).await;
+    if !unfinished.is_empty() {
+        let count = unfinished.len();
+        let saved = tokio::task::spawn_blocking(move || {
+            UNFINISHED_JOBS.update(|state| {
+                state.jobs.retain(|old| !unfinished.iter().any(|new| new.job.printer_name == old.job.printer_name));
+                state.jobs.extend(unfinished);
+            })
+        })
+        .await;
+        match saved {
+            Ok(Ok(())) => eprintln!("[Shutdown] saved unfinished jobs={}", count),
+            Ok(Err(e)) => eprintln!("[Shutdown] 保存未完成任务失败: {}", e),
+            Err(e) => eprintln!("[Shutdown] 保存未完成任务任务异常: {}", e),
+        }
+    }
+}
+
+/// 等待 / 取消任务、保存未完成任务后退出应用
+///
+/// # 参数
+/// - `cancel_now`: true 时不等待，立即取消进行中的任务
+pub async fn shutdown(app: tauri::AppHandle, cancel_now: bool) {
+    drain_and_save(cancel_now).await;
+    app.exit(0);
+}
+
+/// 等待 / 取消任务、保存未完成任务后重新启动应用（WebView 无法恢复时使用）
+pub async fn restart(app: tauri::AppHandle) {
+    drain_and_save(false).await;
+    eprintln!("[Shutdown] restarting application");
+    tauri::api::process::restart(&app.env());
+}
+
+/// 主窗口关闭请求
+///
+/// # 返回
+/// - `true`: 有进行中的安装任务，应阻止本次关闭（由退出流程稍后退出）
+/// - `false`: 可以直接关闭
+pub fn handle_close_requested(app: &tauri::AppHandle) -> bool {
+    if SHUTTING_DOWN.load(Ordering::SeqCst) {
+        return false;
+    }
+    let jobs = crate::platform::active_install_jobs();
+    if jobs.is_empty() {
+        return false;
+    }
+    eprintln!("[Shutdown] close requested with running jobs={}", jobs.len());
+    if super::interaction_mode::is_non_interactive() {
+        tauri::async_runtime::spawn(shutdown(app.clone(), false));
+    } else {
+        super::events::emit_shutdown_requested(
+            app,
+            &ShutdownRequest {
+                jobs,
+                grace_secs: super::user_settings::current().shutdown_grace_secs,
+            },
+        );
+    }
+    true
+}
+
+/// 上次退出时未完成的安装任务
+pub async fn get_unfinished_jobs() -> Result<Vec<UnfinishedJob>, String> {
+    tokio::task::spawn_blocking(|| UNFINISHED_JOBS.load().map(|state| state.jobs))
+        .await
+        .map_err(|e| format!("读取未完成任务异常: {}", e))?
+}
+
+/// 处理上次未完成的安装任务
+///
+/// # 参数
+/// - `resume`: true 时按当前配置依次重新安装，false 时放弃
+///
+/// # 返回
+/// - `Ok(Vec<ResumedJob>)`: 重新安装结果（放弃时为空）
+/// - `Err(String)`: 读取或清除存储失败
+pub async fn resolve_unfinished_jobs(app: &tauri::AppHandle, resume: bool) -> Result<Vec<ResumedJob>, String> {
+    let jobs = tokio::task::spawn_blocking(|| UNFINISHED_JOBS.update(|state| std::mem::take(&mut state.jobs)))
+        .await
+        .map_err(|e| format!("清除未完成任务异常: {}", e))??;
+    if !resume || jobs.is_empty() {
+        eprintln!("[Shutdown] unfinished jobs dismissed count={}", jobs.len());
+        return Ok(Vec::new());
+    }
+
+    let (config, _) = tokio::task::spawn_blocking(crate::load_local_config)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))??;
+    let mut results = Vec::new();
+    for unfinished in jobs {
+        let name = unfinished.job.printer_name;
+        let Some(printer) = super::printer_watch::find_config_printer(&config, &name) else {
+            results.push(ResumedJob {
+                printer_name: name,
+                success: false,
+                job_id: None,
+                message: "打印机已不在配置中，已跳过".to_string(),
+            });
+            continue;
+        };
+        let policy = printer.options.driver_install_policy.clone().unwrap_or_else(|| "always".to_string());
+        let result = crate::install_printer(
+            app.clone(),
+            printer.name.clone(),
+            printer.path.clone(),
+            printer.driver_key.clone(),
+            None,
+            printer.model.clone(),
+            Some(policy),
+            None,
+            Some(false),
+            // 中断的任务可能已创建了同名队列（端口尚未改绑），继续安装时沿用该队列
+            Some(super::queue_conflict::QueueConflictStrategy::Overwrite.as_str().to_string()),
+        )
+        .await;
+        results.push(match result {
+            Ok(r) => ResumedJob { printer_name: name, success: r.success, job_id: Some(r.job_id), message: r.message },
+            Err(e) => ResumedJob { printer_name: name, success: false, job_id: None, message: e },
+        });
+    }
+    eprintln!(
+        "[Shutdown] resumed unfinished jobs={} succeeded={}",
+        results.len(),
+        results.iter().filter(|r| r.success).count()
+    );
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_unfinished_jobs_and_marks_ones_that_ignored_cancel() {
+        let job = |id: &str, dry_run: bool| ActiveInstallJob {
+            job_id: id.to_string(),
+            printer_name: format!("打印机 {}", id),
+            path: "10.0.0.1".to_string(),
+            driver_key: Some("HP".to_string()),
+            dry_run,
+            started_at: 1,
+        };
+        let still_active: HashSet<String> = ["b".to_string()].into_iter().collect();
+        let outcome = |id: &str| match id {
+            "a" => Some(false),
+            "d" => Some(true),
+            _ => None,
+        };
+        let jobs = vec![job("a", false), job("b", false), job("c", true), job("d", false), job("e", false)];
+        let unfinished = unfinished_jobs(jobs, &still_active, outcome, 42);
+
+        let summary: Vec<(&str, &str)> = unfinished.iter().map(|u| (u.job.job_id.as_str(), u.reason.as_str())).collect();
+        assert_eq!(summary, vec![("a", "cancelled"), ("b", "terminated"), ("e", "cancelled")]);
+        assert_eq!(unfinished[0].interrupted_at, 42);
+    }
+}
@@ -0,0 +1,147 @@
+/**
+ * SNMP 耗材查询服务
+ * 职责：
+ * - 通过 SNMPv2c 遍历打印机 Printer-MIB 的 prtMarkerSuppliesTable（UDP 161），汇总墨粉 / 墨水 / 硒鼓余量
+ * - 供前端在安装完成后与状态视图中提示"墨粉不足"（余量 ≤ LOW_SUPPLY_PERCENT%）
+ * - 报文构造与解析见 eprinty_core::snmp；共享打印机（\\server\queue）的主机是打印服务器而不是设备，不支持查询
+ */
+
+use crate::platform::printer_address::PrinterAddress;
+use crate::utils::snmp::{self, PrinterSupply, SnmpValue};
+use serde::Serialize;
+use std::time::Duration;
+
+/// 未指定团体名时使用的默认值
+const DEFAULT_COMMUNITY: &str = "public";
+
+/// 单次请求的应答超时
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 单次请求超时后的重试次数
+const REQUEST_RETRIES: usize = 1;
+
+/// 地址解析超时
+const RESOLVE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// 遍历的最大轮数（每轮每列前进一行，防止设备返回异常 OID 时无限遍历）
+const MAX_WALK_ROUNDS: usize = 64;
+
+/// 耗材查询结果
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrinterSuppliesReport {
+    pub host: String,
+    pub supplies: Vec<PrinterSupply>,
+    /// 存在余量不足的耗材
+    pub any_low: bool,
+}
+
+/// 发送一次 GetNext 请求并等待对应的应答（超时重试）
+async fn get_next(
+    socket: &tokio::net::UdpSocket,
+    community: &str,
+    request_id: i32,
+    oids: &[Vec<u32>],
+) -> Result<Vec<(Vec<u32>, SnmpValue)>, String> {
+    let request = snmp::build_get_next(community, request_id, oids);
+    let mut buf = vec![0u8; 65535];
+    for _ in 0..=REQUEST_RETRIES {
+        socket.send(&request).await.map_err(|e| format!("发送 SNMP 请求失败: {}", e))?;
+        let deadline = tokio::time::Instant::now() + REQUEST_TIMEOUT;
+        loop {
+            let received = match tokio::time::timeout_at(deadline, socket.recv(&mut buf)).await {
+                Ok(received) => received.map_err(|e| format!("接收 SNMP 应答失败: {}", e))?,
+                Err(_) => break,
+            };
+            // 忽略结构损坏或属于之前请求的迟到应答
+            let Ok(response) = snmp::parse_response(&buf[..received]) else {
+                continue;
+            };
+            if response.request_id != request_id {
+                continue;
+            }
+            if response.error_status != 0 {
+                return Err(format!("打印机返回 SNMP 错误（error-status={}）", response.error_status));
+            }
+            return Ok(response.varbinds);
+        }
+    }
+    Err(format!(
+        "SNMP 请求超时（{} 秒，已重试 {} 次）：请确认打印机已启用 SNMP 且团体名正确",
+        REQUEST_TIMEOUT.as_secs(),
+        REQUEST_RETRIES
+    ))
+}
+
+/// 并行遍历多列（每列返回到离开本列前缀为止）
+async fn walk_columns(socket: &tokio::net::UdpSocket, community: &str, columns: &[Vec<u32>]) -> Result<Vec<(Vec<u32>, SnmpValue)>, String> {
+    let mut cursors: Vec<Option<Vec<u32>>> = columns.iter().cloned().map(Some).collect();
+    let mut collected = Vec::new();
+    // 起始请求 ID 取时间低位，避免与上一次查询的迟到应答混淆
+    let mut request_id = (chrono::Utc::now().timestamp_subsec_nanos() & 0x7FFF_FFFF) as i32;
+    for _ in 0..MAX_WALK_ROUNDS {
+        let active: Vec<usize> = (0..columns.len()).filter(|i| cursors[*i].is_some()).collect();
+        if active.is_empty() {
+            break;
+        }
+        let oids: Vec<Vec<u32>> = active.iter().filter_map(|i| cursors[*i].clone()).collect();
+        request_id = request_id.wrapping_add(1);
+        let varbinds = get_next(socket, community, request_id, &oids).await?;
+        for (slot, column_index) in active.iter().enumerate() {
+            let next = varbinds.get(slot).filter(|(oid, value)| {
+                *value != SnmpValue::EndOfMib && oid.starts_with(&columns[*column_index]) && oid.len() > columns[*column_index].len()
+            });
+            match next {
+                Some((oid, value)) => {
+                    cursors[*column_index] = Some(oid.clone());
+                    collected.push((oid.clone(), value.clone()));
+                }
+                None => cursors[*column_index] = None,
+            }
+        }
+    }
+    Ok(collected)
+}
+
+/// 查询打印机耗材余量
+///
+/// # 参数
+/// - `ip`: 打印机地址（IP、主机名或设备 URI，与配置中的 path 写法一致）
+/// - `community`: SNMP 团体名（None = "public"）
+///
+/// # 返回
+/// - `Ok(PrinterSuppliesReport)`: 耗材列表（设备未实现耗材表时为空）
+/// - `Err(String)`: 地址无效、共享打印机或 SNMP 无应答
+pub async fn get_printer_supplies(ip: &str, community: Option<&str>) -> Result<PrinterSuppliesReport, String> {
+    let address = PrinterAddress::parse(ip)?;
+    if matches!(address, PrinterAddress::SmbShare { .. }) {
+        return Err("共享打印机（\\\\server\\queue）不支持直接查询耗材，请使用打印机本身的 IP 地址".to_string());
+    }
+    let community = community.map(str::trim).filter(|c| !c.is_empty()).unwrap_or(DEFAULT_COMMUNITY);
+    let host = address.host_address();
+
+    let lookup = tokio::net::lookup_host((host.as_str(), snmp::SNMP_PORT));
+    let target = tokio::time::timeout(RESOLVE_TIMEOUT, lookup)
+        .await
+        .map_err(|_| format!("解析打印机地址 {} 超时（{} 秒）", host, RESOLVE_TIMEOUT.as_secs()))?
+        .map_err(|e| format!("无法解析打印机地址 {}: {}", host, e))?
+        .next()
+        .ok_or_else(|| format!("打印机地址 {} 没有可用的 DNS 记录", host))?;
+    let bind_addr = if target.is_ipv4() { "0.0.0.0:0" } else { "[::]:0" };
+    let socket = tokio::net::UdpSocket::bind(bind_addr)
+        .await
+        .map_err(|e| format!("创建 UDP 套接字失败: {}", e))?;
+    socket.connect(target).await.map_err(|e| format!("连接 {} 失败: {}", target, e))?;
+
+    let columns: Vec<Vec<u32>> = snmp::SUPPLY_COLUMNS.iter().map(|c| snmp::column_oid(*c)).collect();
+    let varbinds = walk_columns(&socket, community, &columns).await?;
+    let supplies = snmp::supplies_from_varbinds(&varbinds);
+    let any_low = supplies.iter().any(|s| s.low);
+    eprintln!(
+        "[SnmpService] supplies host=\"{}\" count={} low={}",
+        host,
+        supplies.len(),
+        supplies.iter().filter(|s| s.low).map(|s| s.description.as_str()).collect::<Vec<_>>().join(",")
+    );
+    Ok(PrinterSuppliesReport { host, supplies, any_low })
+}
@@ -0,0 +1,374 @@
+/**
+ * 应用更新下载服务
+ * 职责：
+ * - download_update 只从白名单主机下载：内置更新服务器（版本配置地址所在主机）+ 配置 updatePolicy.allowedHosts；
+ *   重定向到白名单以外的主机同样拒绝
+ * - 只允许 https，TLS 最低 1.2
+ * - 版本配置必须提供 SHA256 校验值（checksum），且下载地址与版本配置中的 update_url 一致；
+ *   下载内容先写入 .part 文件，SHA256 校验通过后才重命名为 .exe，校验失败时删除
+ * - 下载过程中发送 update_download_progress 事件（字节数、百分比、速度、剩余时间）；
+ *   下载中断时保留 .part 文件，重试时从断点继续（Range 请求，服务器不支持时从头下载）
+ */
+
+use super::events;
+use futures_util::StreamExt;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+/// 最多跟随的重定向次数
+const MAX_REDIRECTS: usize = 5;
+
+/// 进度事件的最短发送间隔
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(300);
+
+/// 计算下载速度的滑动窗口
+const SPEED_WINDOW: Duration = Duration::from_secs(5);
+
+/// update_download_progress 事件
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateDownloadProgress {
+    pub job_id: String,
+    /// "running" | "success" | "failed"
+    pub state: String,
+    /// 已下载字节（含断点续传前已下载的部分）
+    pub downloaded_bytes: u64,
+    /// 文件总大小（服务器未提供 Content-Length 时为 None）
+    pub total_bytes: Option<u64>,
+    pub percent: Option<f64>,
+    /// 最近 SPEED_WINDOW 内的平均速度
+    pub bytes_per_sec: u64,
+    /// 预计剩余秒数（总大小未知或速度为 0 时为 None）
+    pub eta_secs: Option<u64>,
+    /// 断点续传的起始字节（0 = 从头下载）
+    pub resumed_from: u64,
+    pub message: String,
+}
+
+/// 下载进度统计（速度按滑动窗口计算，避免瞬时波动）
+struct DownloadProgressTracker {
+    job_id: String,
+    resumed_from: u64,
+    total: Option<u64>,
+    downloaded: u64,
+    samples: VecDeque<(Instant, u64)>,
+}
+
+impl DownloadProgressTracker {
+    fn new(job_id: String, resumed_from: u64, total: Option<u64>, now: Instant) -> Self {
+        let mut samples = VecDeque::new();
+        samples.push_back((now, resumed_from));
+        DownloadProgressTracker { job_id, resumed_from, total, downloaded: resumed_from, samples }
+    }
+
+    /// 记录当前已下载字节数
+    fn record(&mut self, downloaded: u64, now: Instant) {
+        self.downloaded = downloaded;
+        self.samples.push_back((now, downloaded));
+        while self.samples.len() > 2 && self.samples.get(1).is_some_and(|(at, _)| now.duration_since(*at) > SPEED_WINDOW) {
+            self.samples.pop_front();
+        }
+    }
+
+    fn bytes_per_sec(&self) -> u64 {
+        match (self.samples.front(), self.samples.back()) {
+            (Some((start, from)), Some((end, to))) => {
+                let secs = end.duration_since(*start).as_secs_f64();
+                if secs > 0.0 { ((to - from) as f64 / secs) as u64 } else { 0 }
+            }
+            _ => 0,
+        }
+    }
+
+    fn snapshot(&self, state: &str, message: String) -> UpdateDownloadProgress {
+        let speed = self.bytes_per_sec();
+        let percent = self.total.filter(|t| *t > 0).map(|t| (self.downloaded as f64 * 100.0 / t as f64).min(100.0));
+        let eta_secs = match self.total {
+            Some(total) if speed > 0 => Some(total.saturating_sub(self.downloaded).div_ceil(speed)),
+            _ => None,
+        };
+        UpdateDownloadProgress {
+            job_id: self.job_id.clone(),
+            state: state.to_string(),
+            downloaded_bytes: self.downloaded,
+            total_bytes: self.total,
+            percent,
+            bytes_per_sec: speed,
+            eta_secs,
+            resumed_from: self.resumed_from,
+            message,
+        }
+    }
+}
+
+/// 校验 allowedHosts 条目（主机名，可用 "*." 前缀匹配子域名，不含协议 / 端口 / 路径）
+///
+/// # 参数
+/// - `host`: 配置中的主机条目
+///
+/// # 返回
+/// - `Ok(())`: 条目有效
+/// - `Err(String)`: 条目为空或格式错误
+pub fn validate_allowed_host(host: &str) -> Result<(), String> {
+    let name = host.trim().strip_prefix("*.").unwrap_or(host.trim());
+    let valid = !name.is_empty()
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+        && !name.starts_with('.')
+        && !name.ends_with('.');
+    if !valid {
+        return Err(format!("\"{}\" 无效，必须为主机名（如 cdn.example.com 或 *.example.com）", host));
+    }
+    Ok(())
+}
+
+/// 主机是否在白名单中（不区分大小写；"*.example.com" 匹配 example.com 的任意子域名，不含 example.com 本身）
+fn host_allowed(host: &str, allowed: &[String]) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    allowed.iter().any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        match entry.strip_prefix("*.") {
+            Some(suffix) => host.ends_with(&format!(".{}", suffix)),
+            None => host == entry,
+        }
+    })
+}
+
+/// 下载主机白名单（内置更新服务器 + 配置 updatePolicy.allowedHosts；配置读取失败时只用内置主机）
+fn allowed_hosts() -> Vec<String> {
+    let mut hosts: Vec<String> = url::Url::parse(crate::VERSION_CONFIG_REMOTE_URL)
+        .ok()
+        .and_then(|u| u.host_str().map(str::to_string))
+        .into_iter()
+        .collect();
+    if let Ok((config, _)) = crate::load_local_config() {
+        if let Some(policy) = config.update_policy {
+            hosts.extend(policy.allowed_hosts);
+        }
+    }
+    hosts
+}
+
+/// 检查下载地址：https + 白名单主机
+fn check_download_url(update_url: &str, allowed: &[String]) -> Result<url::Url, String> {
+    let parsed = url::Url::parse(update_url).map_err(|e| format!("更新下载地址无效: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err(format!("更新下载地址必须为 https: {}", update_url));
+    }
+    let host = parsed.host_str().unwrap_or_default();
+    if !host_allowed(host, allowed) {
+        return Err(format!(
+            "更新下载主机 \"{}\" 不在允许列表中（允许：{}）。如需使用其他下载服务器，请在配置 updatePolicy.allowedHosts 中添加",
+            host,
+            allowed.join(", ")
+        ));
+    }
+    Ok(parsed)
+}
+
+/// 从版本配置中取出期望的 SHA256（必须提供，且与下载地址对应）
+fn expected_sha256(version_config: &crate::VersionConfig, update_url: &str) -> Result<String, String> {
+    if version_config.update_url.as_deref().map(str::trim) != Some(update_url.trim()) {
+        return Err("更新下载地址与版本配置中的 update_url 不一致，已拒绝下载".to_string());
+    }
+    let checksum = version_config
+        .checksum
+        .as_ref()
+        .ok_or_else(|| "版本配置缺少 checksum，无法校验更新文件，已拒绝下载".to_string())?;
+    if !checksum.algorithm.eq_ignore_ascii_case("sha256") {
+        return Err(format!("不支持的 checksum.algorithm=\"{}\"（仅支持 sha256）", checksum.algorithm));
+    }
+    let value = checksum.value.trim().to_ascii_lowercase();
+    if value.len() != 64 || !value.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err("版本配置中的 checksum.value 不是有效的 SHA256（64 位十六进制）".to_string());
+    }
+    Ok(value)
+}
+
+/// 发送一次下载请求（resume_from > 0 时携带 Range 头）
+async fn request_download(client: &reqwest::Client, url: &url::Url, resume_from: u64) -> Result<reqwest::Response, String> {
+    let mut request = client.get(url.clone());
+    if resume_from > 0 {
+        request = request.header(reqwest::header::RANGE, format!("bytes={}-", resume_from));
+    }
+    request.send().await.map_err(|e| format!("下载更新文件失败: {}", e))
+}
+
+/// 对已下载的部分计算 SHA256（断点续传时继续累加）
+async fn hash_partial(path: &Path, hasher: &mut Sha256) -> Result<(), String> {
+    let mut file = tokio::fs::File::open(path).await.map_err(|e| format!("读取已下载的部分失败: {}", e))?;
+    let mut buf = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file.read(&mut buf).await.map_err(|e| format!("读取已下载的部分失败: {}", e))?;
+        if read == 0 {
+            return Ok(());
+        }
+        hasher.update(&buf[..read]);
+    }
+}
+
+/// 下载更新文件并校验 SHA256，校验通过后写成 .exe
+///
+/// 下载过程中发送 update_download_progress 事件（已下载字节、百分比、速度、剩余时间）；
+/// 中断时保留 .part 文件，再次调用时用 Range 请求从断点继续
+///
+/// # 参数
+/// - `app`: 用于发送进度事件
+/// - `update_url`: 前端传入的下载地址（必须与版本配置中的 update_url 一致）
+///
+/// # 返回
+/// - `Ok(PathBuf)`: 已校验并写入的更新文件路径
+/// - `Err(String)`: 地址 / 主机不允许、缺少校验值、下载失败或校验不一致（校验不一致时丢弃已下载的部分）
+pub async fn download_update(app: &tauri::AppHandle, update_url: &str) -> Result<PathBuf, String> {
+    let allowed = tokio::task::spawn_blocking(allowed_hosts)
+        .await
+        .map_err(|e| format!("读取本地配置任务异常: {}", e))?;
+    let parsed = check_download_url(update_url, &allowed)?;
+
+    let version_config = crate::fetch_version_config().await?;
+    let expected = expected_sha256(&version_config, update_url)?;
+
+    // 文件名按期望的 SHA256 区分版本：不同版本的部分下载不会被误续传
+    let final_path = std::env::temp_dir().join(format!("easyPrinter_update_{}.exe", &expected[..16]));
+    let partial_path = final_path.with_extension("exe.part");
+    let job_id = format!("update_{}", chrono::Utc::now().timestamp_millis());
+
+    let redirect_hosts = allowed.clone();
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(crate::HTTP_TIMEOUT_DOWNLOAD_SECS))
+        .https_only(true)
+        .min_tls_version(reqwest::tls::Version::TLS_1_2)
+        .redirect(reqwest::redirect::Policy::custom(move |attempt| {
+            let host = attempt.url().host_str().unwrap_or_default().to_string();
+            if attempt.previous().len() > MAX_REDIRECTS {
+                attempt.error(format!("重定向次数超过 {} 次", MAX_REDIRECTS))
+            } else if !host_allowed(&host, &redirect_hosts) {
+                attempt.error(format!("重定向到不允许的主机 \"{}\"", host))
+            } else {
+                attempt.follow()
+            }
+        }))
+        .build()
+        .map_err(|e| format!("创建HTTP客户端失败: {}", e))?;
+
+    let mut resume_from = tokio::fs::metadata(&partial_path).await.map(|m| m.len()).unwrap_or(0);
+    let mut response = request_download(&client, &parsed, resume_from).await?;
+    if response.status() == reqwest::StatusCode::RANGE_NOT_SATISFIABLE && resume_from > 0 {
+        // 已下载的部分与服务器上的文件不符（文件已更换或部分文件损坏），重新下载
+        eprintln!("[UpdateDownload] Range 不可用 resume_from={}，重新下载", resume_from);
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        resume_from = 0;
+        response = request_download(&client, &parsed, 0).await?;
+    }
+    if !response.status().is_success() {
+        return Err(format!("下载失败: {}", response.status()));
+    }
+    // 服务器忽略 Range（返回 200）时从头下载
+    if response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+        resume_from = 0;
+    }
+
+    let mut hasher = Sha256::new();
+    if resume_from > 0 {
+        hash_partial(&partial_path, &mut hasher).await?;
+        eprintln!("[UpdateDownload] 断点续传 resume_from={} path=\"{}\"", resume_from, partial_path.display());
+    }
+    let total = response.content_length().map(|len| len + resume_from);
+    let mut file = tokio::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(resume_from > 0)
+        .truncate(resume_from == 0)
+        .open(&partial_path)
+        .await
+        .map_err(|e| format!("创建更新文件失败: {}", e))?;
+
+    let mut progress = DownloadProgressTracker::new(job_id, resume_from, total, Instant::now());
+    events::emit_update_download_progress(app, &progress.snapshot("running", "开始下载更新文件".to_string()));
+    let mut stream = response.bytes_stream();
+    let mut last_emit = Instant::now();
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                let _ = file.flush().await;
+                let message = format!(
+                    "下载中断（已下载 {} MB，重试时将从断点继续）: {}",
+                    progress.downloaded / 1024 / 1024,
+                    e
+                );
+                events::emit_update_download_progress(app, &progress.snapshot("failed", message.clone()));
+                return Err(message);
+            }
+        };
+        file.write_all(&chunk).await.map_err(|e| format!("写入更新文件失败: {}", e))?;
+        hasher.update(&chunk);
+        let now = Instant::now();
+        progress.record(progress.downloaded + chunk.len() as u64, now);
+        if now.duration_since(last_emit) >= PROGRESS_INTERVAL {
+            last_emit = now;
+            let message = format!("已下载 {} / {} MB", progress.downloaded / 1024 / 1024, total.map(|t| t / 1024 / 1024).unwrap_or(0));
+            events::emit_update_download_progress(app, &progress.snapshot("running", message));
+        }
+    }
+    file.flush().await.map_err(|e| format!("写入更新文件失败: {}", e))?;
+    file.sync_all().await.map_err(|e| format!("写入更新文件失败: {}", e))?;
+    drop(file);
+
+    let actual = format!("{:x}", hasher.finalize());
+    if actual != expected {
+        eprintln!("[UpdateDownload] SHA256 不一致 expected={} actual={} size={}", expected, actual, progress.downloaded);
+        let _ = tokio::fs::remove_file(&partial_path).await;
+        let message = format!("更新文件校验失败（SHA256 不一致），已丢弃。期望 {}，实际 {}", expected, actual);
+        events::emit_update_download_progress(app, &progress.snapshot("failed", message.clone()));
+        return Err(message);
+    }
+
+    tokio::fs::rename(&partial_path, &final_path)
+        .await
+        .map_err(|e| format!("写入更新文件失败: {}", e))?;
+    eprintln!("[UpdateDownload] 已下载并校验 size={} resumed_from={} path=\"{}\"", progress.downloaded, resume_from, final_path.display());
+    events::emit_update_download_progress(app, &progress.snapshot("success", "更新文件已下载并校验".to_string()));
+    Ok(final_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allows_only_listed_hosts_over_https() {
+        let allowed = vec!["p.edianyun.icu".to_string(), "*.example.com".to_string()];
+        assert!(check_download_url("https://p.edianyun.icu/ePrinty.exe", &allowed).is_ok());
+        assert!(check_download_url("https://CDN.example.com/ePrinty.exe", &allowed).is_ok());
+        assert!(check_download_url("https://example.com/ePrinty.exe", &allowed).is_err());
+        assert!(check_download_url("https://evil-example.com/ePrinty.exe", &allowed).is_err());
+        assert!(check_download_url("http://p.edianyun.icu/ePrinty.exe", &allowed).is_err());
+
+        assert!(validate_allowed_host("*.example.com").is_ok());
+        assert!(validate_allowed_host("https://example.com").is_err());
+        assert!(validate_allowed_host("").is_err());
+    }
+
+    #[test]
+    fn progress_reports_speed_and_eta_including_resumed_bytes() {
+        let start = Instant::now();
+        let mut tracker = DownloadProgressTracker::new("update_1".to_string(), 40, Some(100), start);
+        tracker.record(50, start + Duration::from_secs(1));
+        tracker.record(60, start + Duration::from_secs(2));
+
+        let progress = tracker.snapshot("running", String::new());
+        assert_eq!(progress.downloaded_bytes, 60);
+        assert_eq!(progress.percent, Some(60.0));
+        assert_eq!(progress.bytes_per_sec, 10);
+        assert_eq!(progress.eta_secs, Some(4));
+        assert_eq!(progress.resumed_from, 40);
+
+        // 超出滑动窗口的样本不再参与速度计算
+        tracker.record(90, start + Duration::from_secs(10));
+        assert_eq!(tracker.bytes_per_sec(), 3);
+    }
+}
@@ -0,0 +1,168 @@
+/**
+ * 应用更新灰度推送服务
+ * 职责：
+ * - 按 version_config.json 的 target_groups / rollout_percentage 判断本机是否在本次更新的推送范围内
+ * - target_groups：仅推送给 printer_config.json 根节点 rolloutGroups 与之有交集的机器（不区分大小写）
+ * - rollout_percentage：SHA256(机器指纹 | 目标版本) 取前 4 字节对 100 取模得到分桶，分桶小于比例时推送；
+ *   同一版本内分桶稳定，比例调大只会新增机器；不同版本重新分桶，避免总是同一批机器先升级
+ * - 两个条件同时配置时需同时满足；当前版本低于 min_supported_version 时不受灰度限制
+ *
+ * 有新版本但本机不在推送范围内时，check_version_update 返回 hasUpdate=false、rolloutStatus="not_offered"
+ */
+
+use sha2::{Digest, Sha256};
+
+/// 灰度判断结果
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RolloutDecision {
+    /// 是否向本机推送更新
+    pub offered: bool,
+    /// "up_to_date" | "offered" | "not_offered"
+    pub status: &'static str,
+    /// 本机分桶（0-99，仅按比例灰度时计算）
+    pub bucket: Option<u32>,
+}
+
+impl RolloutDecision {
+    /// 已是最新版本
+    pub fn up_to_date() -> Self {
+        RolloutDecision { offered: false, status: "up_to_date", bucket: None }
+    }
+
+    fn offered(offered: bool, bucket: Option<u32>) -> Self {
+        RolloutDecision {
+            offered,
+            status: if offered { "offered" } else { "not_offered" },
+            bucket,
+        }
+    }
+}
+
+/// 计算本机在指定版本灰度中的分桶（0-99）
+fn rollout_bucket(fingerprint: &str, version: &str) -> u32 {
+    let digest = Sha256::digest(format!("{}|{}", fingerprint, version.trim()).as_bytes());
+    u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) % 100
+}
+
+/// 本机分组是否命中 target_groups（未配置或为空表示不限分组）
+fn in_target_groups(target_groups: Option<&[String]>, machine_groups: &[String]) -> bool {
+    let targets: Vec<&str> = target_groups
+        .unwrap_or_default()
+        .iter()
+        .map(|g| g.trim())
+        .filter(|g| !g.is_empty())
+        .collect();
+    targets.is_empty()
+        || machine_groups
+            .iter()
+            .any(|g| targets.iter().any(|t| t.eq_ignore_ascii_case(g.trim())))
+}
+
+/// 读取本机所属分组（本地配置根节点 rolloutGroups，读取失败视为不属于任何分组）
+fn machine_groups() -> Vec<String> {
+    match crate::load_local_config() {
+        Ok((config, _)) => config.rollout_groups,
+        Err(e) => {
+            eprintln!("[UpdateRollout] 读取本地配置失败，视为不属于任何灰度分组: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// 判断是否向本机推送新版本（调用方已确认存在更高版本）
+///
+/// # 参数
+/// - `version_config`: 远程版本配置
+/// - `current_version`: 当前应用版本
+///
+/// # 返回
+/// - 灰度判断结果；无法计算机器指纹时按不在推送范围内处理
+pub async fn evaluate(version_config: &crate::VersionConfig, current_version: &str) -> RolloutDecision {
+    let latest_version = version_config.app_version.clone();
+
+    let below_min_supported = version_config
+        .min_supported_version
+        .as_deref()
+        .is_some_and(|min| crate::compare_versions(current_version, min));
+    if below_min_supported {
+        eprintln!(
+            "[UpdateRollout] 当前版本 {} 低于最低支持版本 {:?}，不受灰度限制",
+            current_version, version_config.min_supported_version
+        );
+        return RolloutDecision::offered(true, None);
+    }
+
+    let target_groups = version_config.target_groups.clone();
+    let percentage = version_config.rollout_percentage.map(|p| p.min(100));
+    let all_groups = target_groups.as_ref().map(|g| g.is_empty()).unwrap_or(true);
+    if all_groups && !matches!(percentage, Some(p) if p < 100) {
+        return RolloutDecision::offered(true, None);
+    }
+
+    // 读取本地配置与机器标识均为阻塞调用（Windows 下机器标识会启动 PowerShell）
+    let result = tokio::task::spawn_blocking(move || {
+        let groups = machine_groups();
+        if !in_target_groups(target_groups.as_deref(), &groups) {
+            eprintln!(
+                "[UpdateRollout] 版本 {} 仅推送分组 {:?}，本机分组 {:?}，暂不推送",
+                latest_version, target_groups, groups
+            );
+            return RolloutDecision::offered(false, None);
+        }
+
+        let percentage = match percentage.filter(|p| *p < 100) {
+            Some(p) => p,
+            None => return RolloutDecision::offered(true, None),
+        };
+        match super::fleet_service::current_machine_fingerprint() {
+            Ok(fingerprint) => {
+                let bucket = rollout_bucket(&fingerprint, &latest_version);
+                let offered = bucket < u32::from(percentage);
+                eprintln!(
+                    "[UpdateRollout] 版本 {} 灰度 {}%，本机分桶 {}，{}",
+                    latest_version,
+                    percentage,
+                    bucket,
+                    if offered { "推送" } else { "暂不推送" }
+                );
+                RolloutDecision::offered(offered, Some(bucket))
+            }
+            Err(e) => {
+                eprintln!("[UpdateRollout] 计算机器指纹失败，暂不推送灰度版本 {}: {}", latest_version, e);
+                RolloutDecision::offered(false, None)
+            }
+        }
+    })
+    .await;
+
+    result.unwrap_or_else(|e| {
+        eprintln!("[UpdateRollout] 灰度判断任务异常，暂不推送: {}", e);
+        RolloutDecision::offered(false, None)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_is_stable_per_version_and_roughly_uniform() {
+        assert_eq!(rollout_bucket("abc", "1.5.0"), rollout_bucket("abc", " 1.5.0 "));
+        assert!(rollout_bucket("abc", "1.5.0") < 100);
+
+        let in_cohort = (0..1000)
+            .filter(|i| rollout_bucket(&format!("machine-{}", i), "1.5.0") < 10)
+            .count();
+        assert!((50..=150).contains(&in_cohort), "10% 灰度命中 {} / 1000", in_cohort);
+    }
+
+    #[test]
+    fn target_groups_match_case_insensitively() {
+        let groups = vec!["Pilot".to_string()];
+        assert!(in_target_groups(None, &groups));
+        assert!(in_target_groups(Some(&[]), &[]));
+        assert!(in_target_groups(Some(&["pilot".to_string()]), &groups));
+        assert!(!in_target_groups(Some(&["beijing".to_string()]), &groups));
+        assert!(!in_target_groups(Some(&["pilot".to_string()]), &[]));
+    }
+}
@@ -0,0 +1,296 @@
+/**
+ * 用户设置服务（按用户持久化的偏好）
+ * 职责：
+ * - 设置保存在本地 meta 存储（settings.json，schemaVersion=1），未出现的字段取 schema 默认值
+ * - get_settings / update_settings：前端读取与按字段修改（只提交需要修改的字段，null 表示恢复默认值）
+ * - 修改成功后发送 settings_changed 事件（携带新设置与变更字段），并同步到各子系统：
+ *   远程配置 / 版本配置地址覆盖（远程配置可设置多个来源依次故障转移）、崩溃报告上传地址覆盖、排查用的调试开关（保留解压失败时的临时目录、保留测试页临时文件）
+ * - driverChannel：本机默认的驱动通道（稳定版 / 测试版），见 driver_channel
+ *
+ * 设置在进程内缓存，首次读取时加载；读取失败时使用默认值（不阻止启动）
+ */
+
+use super::config_sources::ConfigSource;
+use super::driver_channel::DriverChannel;
+use super::meta_store::MetaStore;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// 用户设置存储（schemaVersion=1）
+static SETTINGS_STORE: MetaStore<UserSettings> = MetaStore::new("settings", 1);
+
+/// 进程内缓存（None = 尚未加载）
+static CURRENT: Mutex<Option<UserSettings>> = Mutex::new(None);
+
+/// 支持的界面语言
+const VALID_LANGUAGES: [&str; 2] = ["zh-CN", "en-US"];
+
+/// 关闭窗口时默认等待安装任务的秒数
+const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 60;
+
+/// shutdownGraceSecs 上限
+const MAX_SHUTDOWN_GRACE_SECS: u64 = 600;
+
+/// configSources 最多条数
+const MAX_CONFIG_SOURCES: usize = 8;
+
+/// configSources[].timeoutSecs 上限
+const MAX_SOURCE_TIMEOUT_SECS: u64 = 60;
+
+/// 用户设置
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UserSettings {
+    /// 界面语言："zh-CN" | "en-US"
+    pub language: String,
+    /// 同意发送诊断数据：崩溃报告不再逐次询问，直接发送
+    pub telemetry_opt_in: bool,
+    /// 是否显示配置更新等提示
+    pub notifications_enabled: bool,
+    /// 覆盖远程打印机配置地址（None = 使用内置地址）
+    pub config_url: Option<String>,
+    /// 按优先级排列的远程打印机配置来源（非空时优先于 configUrl，依次故障转移）
+    pub config_sources: Vec<ConfigSource>,
+    /// 覆盖远程版本配置地址（None = 使用内置地址）
+    pub version_config_url: Option<String>,
+    /// 覆盖崩溃报告上传地址（None = 使用配置中的 crashReporting.endpoint）
+    pub crash_report_endpoint: Option<String>,
+    /// 排查用：保留驱动包解压失败时的临时目录（替代旧的 EPRINTY_KEEP_STAGING）
+    pub keep_staging: bool,
+    /// 排查用：保留测试页临时文件（替代旧的 EPRINTY_DEBUG_KEEP_TESTPAGE_FILE）
+    pub keep_test_page_file: bool,
+    /// 关闭窗口时等待进行中的安装任务完成的最长秒数（超时后取消；非交互模式直接按此等待）
+    pub shutdown_grace_secs: u64,
+    /// 本机驱动通道："stable" | "beta"（beta 时配置了 betaDriverKey 的打印机安装测试版驱动；可按打印机单独切换）
+    pub driver_channel: DriverChannel,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        Self {
+            language: "zh-CN".to_string(),
+            telemetry_opt_in: false,
+            notifications_enabled: true,
+            config_url: None,
+            config_sources: Vec::new(),
+            version_config_url: None,
+            crash_report_endpoint: None,
+            keep_staging: false,
+            keep_test_page_file: false,
+            shutdown_grace_secs: DEFAULT_SHUTDOWN_GRACE_SECS,
+            driver_channel: DriverChannel::Stable,
+        }
+    }
+}
+
+/// update_settings 结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsUpdate {
+    pub settings: UserSettings,
+    /// 实际发生变化的字段（camelCase）
+    pub changed: Vec<String>,
+}
+
+/// 校验设置取值
+fn validate(settings: &UserSettings) -> Result<(), String> {
+    if !VALID_LANGUAGES.contains(&settings.language.as_str()) {
+        return Err(format!(
+            "language=\"{}\" 无效，可选值：{}",
+            settings.language,
+            VALID_LANGUAGES.join(" / ")
+        ));
+    }
+    if settings.shutdown_grace_secs > MAX_SHUTDOWN_GRACE_SECS {
+        return Err(format!(
+            "shutdownGraceSecs={} 无效，应为 0-{}",
+            settings.shutdown_grace_secs, MAX_SHUTDOWN_GRACE_SECS
+        ));
+    }
+    if settings.config_sources.len() > MAX_CONFIG_SOURCES {
+        return Err(format!(
+            "configSources 最多 {} 个，当前 {} 个",
+            MAX_CONFIG_SOURCES,
+            settings.config_sources.len()
+        ));
+    }
+    for (index, source) in settings.config_sources.iter().enumerate() {
+        let valid = url::Url::parse(&source.url)
+            .map(|u| u.scheme() == "http" || u.scheme() == "https")
+            .unwrap_or(false);
+        if !valid {
+            return Err(format!("configSources[{}].url=\"{}\" 无效，必须为 http/https 地址", index, source.url));
+        }
+        if let Some(secs) = source.timeout_secs {
+            if secs == 0 || secs > MAX_SOURCE_TIMEOUT_SECS {
+                return Err(format!(
+                    "configSources[{}].timeoutSecs={} 无效，应为 1-{}",
+                    index, secs, MAX_SOURCE_TIMEOUT_SECS
+                ));
+            }
+        }
+    }
+    let endpoints = [
+        ("configUrl", &settings.config_url),
+        ("versionConfigUrl", &settings.version_config_url),
+        ("crashReportEndpoint", &settings.crash_report_endpoint),
+    ];
+    for (field, value) in endpoints {
+        if let Some(url) = value {
+            let valid = url::Url::parse(url)
+                .map(|u| u.scheme() == "http" || u.scheme() == "https")
+                .unwrap_or(false);
+            if !valid {
+                return Err(format!("{}=\"{}\" 无效，必须为 http/https 地址", field, url));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// 把修改应用到当前设置
+///
+/// # 参数
+/// - `current`: 当前设置
+/// - `patch`: 要修改的字段（JSON 对象；值为 null 时恢复默认值，空字符串地址视为清除覆盖）
+///
+/// # 返回
+/// - `Ok((UserSettings, Vec<String>))`: 新设置与实际变化的字段
+/// - `Err(String)`: 未知字段、类型错误或取值无效
+fn apply_patch(current: &UserSettings, patch: &serde_json::Value) -> Result<(UserSettings, Vec<String>), String> {
+    let patch = patch.as_object().ok_or_else(|| "设置修改必须是 JSON 对象".to_string())?;
+    let defaults = serde_json::to_value(UserSettings::default()).map_err(|e| format!("序列化默认设置失败: {}", e))?;
+    let mut merged = serde_json::to_value(current).map_err(|e| format!("序列化设置失败: {}", e))?;
+
+    for (key, value) in patch {
+        let default_value = defaults.get(key).ok_or_else(|| format!("未知的设置项: {}", key))?;
+        let value = match value {
+            serde_json::Value::Null => default_value.clone(),
+            serde_json::Value::String(s) if s.trim().is_empty() && default_value.is_null() => serde_json::Value::Null,
+            serde_json::Value::String(s) => serde_json::Value::String(s.trim().to_string()),
+            other => other.clone(),
+        };
+        merged[key.as_str()] = value;
+    }
+
+    let updated: UserSettings = serde_json::from_value(merged.clone()).map_err(|e| format!("设置取值类型错误: {}", e))?;
+    validate(&updated)?;
+
+    let before = serde_json::to_value(current).map_err(|e| format!("序列化设置失败: {}", e))?;
+    let changed = patch
+        .keys()
+        .filter(|key| before.get(key.as_str()) != merged.get(key.as_str()))
+        .cloned()
+        .collect();
+    Ok((updated, changed))
+}
+
+/// 把设置同步到平台层的调试开关
+fn apply_to_subsystems(settings: &UserSettings) {
+    if let Err(e) = crate::platform::set_diagnostic_options(settings.keep_staging, settings.keep_test_page_file) {
+        eprintln!("[UserSettings] 同步调试开关失败: {}", e);
+    }
+}
+
+/// 当前设置（首次调用时从磁盘加载并同步到各子系统）
+pub fn current() -> UserSettings {
+    let mut cached = CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    if let Some(settings) = cached.as_ref() {
+        return settings.clone();
+    }
+    let settings = SETTINGS_STORE.load().unwrap_or_else(|e| {
+        eprintln!("[UserSettings] 读取设置失败，使用默认值: {}", e);
+        UserSettings::default()
+    });
+    apply_to_subsystems(&settings);
+    *cached = Some(settings.clone());
+    settings
+}
+
+/// 丢弃进程内缓存并重新从磁盘加载（导入应用状态后调用）
+pub fn reload() -> UserSettings {
+    *CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = None;
+    current()
+}
+
+/// 修改设置并持久化（阻塞调用：持有 meta 文件锁）
+///
+/// # 参数
+/// - `patch`: 要修改的字段（见 apply_patch）
+///
+/// # 返回
+/// - `Ok(SettingsUpdate)`: 新设置与变化的字段
+/// - `Err(String)`: 取值无效或写入失败（设置保持不变）
+pub fn update(patch: serde_json::Value) -> Result<SettingsUpdate, String> {
+    let (settings, changed) = SETTINGS_STORE.update(|stored| {
+        let (updated, changed) = apply_patch(stored, &patch)?;
+        *stored = updated.clone();
+        Ok::<_, String>((updated, changed))
+    })??;
+
+    apply_to_subsystems(&settings);
+    *CURRENT.lock().unwrap_or_else(|poisoned| poisoned.into_inner()) = Some(settings.clone());
+    eprintln!("[UserSettings] updated changed={:?}", changed);
+    Ok(SettingsUpdate { settings, changed })
+}
+
+/// 远程打印机配置的基准地址（extends 相对地址、版本索引以它为基准）
+///
+/// 本进程已从某个来源拉取过配置且该来源仍在来源列表中时为该来源，否则为首选来源
+pub fn config_url() -> String {
+    let sources = config_sources();
+    super::config_sources::served_url()
+        .filter(|url| sources.iter().any(|source| source.url == *url))
+        .unwrap_or_else(|| sources[0].url.clone())
+}
+
+/// 远程打印机配置来源（按优先级排列，至少一个）
+///
+/// configSources 非空时使用它；否则只有 configUrl 覆盖地址或内置地址一个来源
+pub fn config_sources() -> Vec<ConfigSource> {
+    let settings = current();
+    if !settings.config_sources.is_empty() {
+        return settings.config_sources;
+    }
+    vec![ConfigSource {
+        url: settings.config_url.unwrap_or_else(|| crate::CONFIG_REMOTE_URL.to_string()),
+        timeout_secs: None,
+    }]
+}
+
+/// 远程版本配置地址（设置覆盖优先）
+pub fn version_config_url() -> String {
+    current().version_config_url.unwrap_or_else(|| crate::VERSION_CONFIG_REMOTE_URL.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patch_updates_known_fields_and_resets_nulls() {
+        let current = UserSettings {
+            keep_staging: true,
+            ..UserSettings::default()
+        };
+
+        let (updated, changed) = apply_patch(
+            &current,
+            &serde_json::json!({ "configUrl": " https://example.com/printer_config.json ", "keepStaging": null, "language": "zh-CN" }),
+        )
+        .unwrap();
+        assert_eq!(updated.config_url.as_deref(), Some("https://example.com/printer_config.json"));
+        assert!(!updated.keep_staging);
+        assert_eq!(changed, vec!["configUrl".to_string(), "keepStaging".to_string()]);
+
+        let (cleared, _) = apply_patch(&updated, &serde_json::json!({ "configUrl": "" })).unwrap();
+        assert_eq!(cleared.config_url, None);
+
+        assert!(apply_patch(&current, &serde_json::json!({ "unknown": true })).is_err());
+        assert!(apply_patch(&current, &serde_json::json!({ "language": "fr" })).is_err());
+        assert!(apply_patch(&current, &serde_json::json!({ "configUrl": "ftp://x" })).is_err());
+        assert!(apply_patch(&current, &serde_json::json!({ "telemetryOptIn": "yes" })).is_err());
+        assert!(apply_patch(&current, &serde_json::json!({ "driverChannel": "nightly" })).is_err());
+        assert!(apply_patch(&current, &serde_json::json!({ "configSources": [{ "url": "https://a", "timeoutSecs": 0 }] })).is_err());
+    }
+}
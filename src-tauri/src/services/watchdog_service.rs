@@ -0,0 +1,38 @@
+/**
+ * 子进程看门狗服务
+ * 职责：
+ * - 启动时清理上次崩溃 / 强制退出遗留的子进程（powershell.exe 等）
+ * - 运行期间定期巡检，终止超过阈值仍未结束的子进程
+ */
+
+use std::time::Duration;
+
+/// 子进程最长存活时间（各执行封装的超时为 120 秒，这里留足余量）
+const CHILD_MAX_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// 巡检间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+async fn sweep_once(trigger: &str) {
+    match tokio::task::spawn_blocking(|| crate::platform::sweep_orphan_processes(CHILD_MAX_AGE)).await {
+        Ok(Ok(killed)) => {
+            if killed > 0 {
+                eprintln!("[Watchdog] sweep trigger={} killed={}", trigger, killed);
+            }
+        }
+        Ok(Err(e)) => eprintln!("[Watchdog] sweep trigger={} failed: {}", trigger, e),
+        Err(e) => eprintln!("[Watchdog] sweep trigger={} task panicked: {}", trigger, e),
+    }
+}
+
+/// 看门狗主循环（在后台任务中运行，所有错误只记录日志）
+pub async fn run_watchdog() {
+    sweep_once("startup").await;
+    let mut interval = tokio::time::interval(SWEEP_INTERVAL);
+    // 第一次 tick 立即返回，跳过（启动时已巡检）
+    interval.tick().await;
+    loop {
+        interval.tick().await;
+        sweep_once("interval").await;
+    }
+}
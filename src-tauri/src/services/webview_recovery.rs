@@ -0,0 +1,239 @@
+/**
+ * WebView 异常恢复服务
+ * 职责：
+ * - 启动时为主窗口注册 WebView 进程监控（WebView2 运行时在后台更新、渲染进程崩溃时界面会白屏，
+ *   而安装任务仍在后台运行）
+ * - 渲染进程退出 / 无响应：自动重新加载页面；RELOAD_WINDOW 内连续失败超过 MAX_RELOADS 次按无法恢复处理
+ * - 浏览器主进程退出（当前窗口无法恢复）：原生对话框询问后等待安装任务结束并重新启动应用
+ *   （未完成的任务由 shutdown_service 保存，重启后提示重新安装）；非交互模式不询问直接重启
+ * - 每次异常连同当时进行中的安装任务保存到本地 meta 存储（webview_recovery.json），
+ *   恢复后的页面通过 get_webview_recovery 读取并提示用户
+ */
+
+use super::meta_store::MetaStore;
+use crate::platform::{ActiveInstallJob, WebviewFailure};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::Manager;
+
+/// 异常记录存储（schemaVersion=1）
+static RECOVERY: MetaStore<RecoveryState> = MetaStore::new("webview_recovery", 1);
+
+/// 最近的自动重新加载时间（用于判断是否反复失败）
+static RECENT_RELOADS: Mutex<Vec<Instant>> = Mutex::new(Vec::new());
+
+/// 已进入重启流程（后续失败事件不再重复弹框）
+static RESTARTING: AtomicBool = AtomicBool::new(false);
+
+/// 统计连续失败的时间窗口
+const RELOAD_WINDOW: Duration = Duration::from_secs(5 * 60);
+
+/// 时间窗口内最多自动重新加载的次数
+const MAX_RELOADS: usize = 3;
+
+/// 保留的异常记录条数
+const MAX_EVENTS: usize = 20;
+
+/// 恢复方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RecoveryAction {
+    /// 重新加载页面
+    Reload,
+    /// 等待安装任务后重新启动应用
+    Restart,
+    /// 用户拒绝重启，等待安装任务后退出
+    Exit,
+}
+
+/// 一次 WebView 异常
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryEvent {
+    pub failure: WebviewFailure,
+    pub action: RecoveryAction,
+    /// 异常发生时进行中的安装任务
+    pub jobs: Vec<ActiveInstallJob>,
+    /// 发生时间（Unix 毫秒）
+    pub occurred_at: i64,
+    /// 已由页面读取并提示
+    #[serde(default)]
+    pub acknowledged: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoveryState {
+    pub events: Vec<RecoveryEvent>,
+}
+
+/// 根据失败类型与时间窗口内已重新加载的次数决定恢复方式（None 表示无需处理）
+fn decide_action(failure: WebviewFailure, recent_reloads: usize) -> Option<RecoveryAction> {
+    match failure {
+        WebviewFailure::Other(_) => None,
+        WebviewFailure::BrowserExited => Some(RecoveryAction::Restart),
+        WebviewFailure::RendererExited | WebviewFailure::RendererUnresponsive => {
+            if recent_reloads >= MAX_RELOADS {
+                Some(RecoveryAction::Restart)
+            } else {
+                Some(RecoveryAction::Reload)
+            }
+        }
+    }
+}
+
+/// 清理时间窗口外的记录并返回窗口内已重新加载的次数
+fn recent_reload_count(now: Instant) -> usize {
+    let mut reloads = RECENT_RELOADS.lock().unwrap_or_else(|e| e.into_inner());
+    reloads.retain(|at| now.duration_since(*at) < RELOAD_WINDOW);
+    reloads.len()
+}
+
+fn note_reload(now: Instant) {
+    RECENT_RELOADS.lock().unwrap_or_else(|e| e.into_inner()).push(now);
+}
+
+/// 保存异常记录（失败只记录日志）
+async fn record_event(event: RecoveryEvent) {
+    let saved = tokio::task::spawn_blocking(move || {
+        RECOVERY.update(|state| {
+            state.events.push(event);
+            let overflow = state.events.len().saturating_sub(MAX_EVENTS);
+            state.events.drain(..overflow);
+        })
+    })
+    .await;
+    match saved {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => eprintln!("[WebviewRecovery] 保存异常记录失败: {}", e),
+        Err(e) => eprintln!("[WebviewRecovery] 保存异常记录任务异常: {}", e),
+    }
+}
+
+fn restart_prompt(jobs: &[ActiveInstallJob]) -> String {
+    let mut message = "ePrinty 界面进程已意外退出（可能是 WebView2 运行时在后台更新），当前窗口无法继续使用。\n\n".to_string();
+    if !jobs.is_empty() {
+        let names: Vec<&str> = jobs.iter().map(|j| j.printer_name.as_str()).collect();
+        message.push_str(&format!(
+            "以下打印机仍在后台安装：{}。\n将等待安装完成（最多 {} 秒），超时未完成的安装在重启后可重新安装。\n\n",
+            names.join("、"),
+            super::user_settings::current().shutdown_grace_secs
+        ));
+    }
+    message.push_str("是否重新启动 ePrinty？\n（选择\"否\"将直接退出）");
+    message
+}
+
+/// 等待安装任务后重启 / 退出
+async fn restart_or_exit(app: tauri::AppHandle, failure: WebviewFailure, jobs: Vec<ActiveInstallJob>) {
+    if RESTARTING.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let confirmed = if super::interaction_mode::is_non_interactive() {
+        true
+    } else {
+        let prompt = restart_prompt(&jobs);
+        tokio::task::spawn_blocking(move || crate::platform::confirm_native_dialog(&prompt))
+            .await
+            .unwrap_or(true)
+    };
+    let action = if confirmed { RecoveryAction::Restart } else { RecoveryAction::Exit };
+    eprintln!("[WebviewRecovery] failure={:?} action={:?} jobs={}", failure, action, jobs.len());
+    record_event(RecoveryEvent {
+        failure,
+        action,
+        jobs,
+        occurred_at: chrono::Utc::now().timestamp_millis(),
+        acknowledged: false,
+    })
+    .await;
+    if confirmed {
+        super::shutdown_service::restart(app).await;
+    } else {
+        super::shutdown_service::shutdown(app, false).await;
+    }
+}
+
+async fn handle_failure(app: tauri::AppHandle, failure: WebviewFailure) {
+    let now = Instant::now();
+    let Some(action) = decide_action(failure, recent_reload_count(now)) else {
+        return;
+    };
+    let jobs = crate::platform::active_install_jobs();
+    if action == RecoveryAction::Restart {
+        restart_or_exit(app, failure, jobs).await;
+        return;
+    }
+
+    // 先保存记录再重新加载：新页面加载后立即读取 get_webview_recovery
+    note_reload(now);
+    eprintln!("[WebviewRecovery] failure={:?} action=reload jobs={}", failure, jobs.len());
+    record_event(RecoveryEvent {
+        failure,
+        action,
+        jobs: jobs.clone(),
+        occurred_at: chrono::Utc::now().timestamp_millis(),
+        acknowledged: false,
+    })
+    .await;
+    let reloaded = match app.get_window("main") {
+        Some(window) => crate::platform::reload_webview(&window),
+        None => Err("主窗口不存在".to_string()),
+    };
+    if let Err(e) = reloaded {
+        eprintln!("[WebviewRecovery] 自动重新加载失败: {}", e);
+        restart_or_exit(app, failure, jobs).await;
+    }
+}
+
+/// 为主窗口注册 WebView 进程监控（启动时调用，失败只记录日志）
+pub fn install(app: &tauri::AppHandle) {
+    let Some(window) = app.get_window("main") else {
+        eprintln!("[WebviewRecovery] 主窗口不存在，跳过 WebView 进程监控");
+        return;
+    };
+    let app_handle = app.clone();
+    let watched = crate::platform::watch_webview_process(&window, move |failure| {
+        // 回调在主线程执行：重新加载 / 弹框都放到后台任务中
+        tauri::async_runtime::spawn(handle_failure(app_handle.clone(), failure));
+    });
+    if let Err(e) = watched {
+        eprintln!("[WebviewRecovery] 注册 WebView 进程监控失败: {}", e);
+    }
+}
+
+/// 读取尚未提示的最近一次 WebView 异常，并标记为已提示
+///
+/// # 返回
+/// - `Ok(Some(RecoveryEvent))`: 页面恢复后应提示的异常
+/// - `Ok(None)`: 没有未提示的异常
+/// - `Err(String)`: 读取或写入存储失败
+pub async fn get_webview_recovery() -> Result<Option<RecoveryEvent>, String> {
+    tokio::task::spawn_blocking(|| {
+        RECOVERY.update(|state| {
+            let latest = state.events.iter().rev().find(|e| !e.acknowledged)?.clone();
+            for event in state.events.iter_mut() {
+                event.acknowledged = true;
+            }
+            Some(latest)
+        })
+    })
+    .await
+    .map_err(|e| format!("读取界面异常记录任务异常: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reloads_renderer_failures_until_they_repeat() {
+        assert_eq!(decide_action(WebviewFailure::Other(7), 0), None);
+        assert_eq!(decide_action(WebviewFailure::BrowserExited, 0), Some(RecoveryAction::Restart));
+        assert_eq!(decide_action(WebviewFailure::RendererExited, 0), Some(RecoveryAction::Reload));
+        assert_eq!(decide_action(WebviewFailure::RendererUnresponsive, MAX_RELOADS - 1), Some(RecoveryAction::Reload));
+        assert_eq!(decide_action(WebviewFailure::RendererExited, MAX_RELOADS), Some(RecoveryAction::Restart));
+    }
+}
@@ -1,3 +1,4 @@
 // 工具模块
+// INF 解析、文本编码、SNMP 报文、驱动包打包 / 解压 / 增量补丁与外部命令记录位于 eprinty-core，这里保持原有的 crate::utils::* 路径
 
-pub mod zip_extract;
+pub use eprinty_core::{command_trace, encoding, inf_parse, snmp, zip_extract, zip_pack, zip_patch};
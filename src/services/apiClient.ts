@@ -0,0 +1,32 @@
+/**
+ * 统一返回结构调用封装
+ * 职责：
+ * - invokeApi: 调用后端 api_* 命令（返回 { ok, data, error }），解包为原命令的返回形式
+ */
+
+import { invoke } from '@tauri-apps/api/tauri'
+
+export interface ApiError {
+  code: string
+  message: string
+  details?: any
+}
+
+export interface ApiResponse<T> {
+  ok: boolean
+  data: T | null
+  error: ApiError | null
+}
+
+/**
+ * 调用 api_<command> 并解包
+ * - 载荷存在时返回载荷（包括 success=false 的安装 / 删除结果，调用方按原字段判断）
+ * - 命令本身失败时抛出错误消息（与原命令抛出的字符串一致）
+ */
+export async function invokeApi<T = any>(command: string, args?: Record<string, unknown>): Promise<T> {
+  const response = await invoke<ApiResponse<T>>(`api_${command}`, args)
+  if (response.data !== null && response.data !== undefined) {
+    return response.data
+  }
+  throw response.error?.message ?? `${command} 失败`
+}
@@ -3,7 +3,7 @@
  * 统一管理安装请求的参数校验、调用和结果处理
  */
 
-import { invoke } from '@tauri-apps/api/tauri'
+import { invokeApi } from './apiClient'
 
 /**
  * 安装请求参数
@@ -18,6 +18,7 @@ export interface InstallRequest {
   driverInstallPolicy?: string
   installMode: string
   dryRun?: boolean
+  queueConflict?: 'fail' | 'overwrite' | 'rename-new'  // 同名队列指向其他地址时的处理（未传时交互模式为 overwrite）
 }
 
 /**
@@ -32,6 +33,56 @@ export interface InstallResponse {
   stdout?: string
   stderr?: string
   details?: any
+  // 参数校验失败时的字段级错误（按 code 本地化提示）
+  validationErrors?: InstallFieldError[]
+  // autoRetry 换策略重试时的每次尝试记录（仅 Windows）
+  attempts?: InstallAttempt[]
+  // 安装后校验是否全部通过（仅 Windows 执行校验，未执行时为 false）
+  verified?: boolean
+  verification?: InstallVerification
+}
+
+/**
+ * 安装尝试记录（后端 autoRetry）
+ */
+export interface InstallAttempt {
+  attempt: number
+  installMode: string
+  driverInstallPolicy: string
+  success: boolean
+  errorCode?: string | null
+  message: string
+  retryReason?: string | null
+}
+
+/**
+ * 安装后校验详情（后端 install_verify；各 *Matches / probeOk 为 null 表示未校验）
+ */
+export interface InstallVerification {
+  verified: boolean
+  queueExists: boolean
+  portName?: string | null
+  portHost?: string | null
+  expectedHost?: string | null
+  portMatches?: boolean | null
+  driverName?: string | null
+  expectedDriverNames: string[]
+  driverMatches?: boolean | null
+  probe?: 'zero_page' | 'pjl_status' | null
+  probeOk?: boolean | null
+  detail: string
+}
+
+/**
+ * 安装参数字段级错误（后端 install_input 校验）
+ */
+export interface InstallFieldError {
+  field: 'name' | 'path' | 'model'
+  code: string
+  message: string
+  maxLength?: number
+  lengthUnit?: 'utf16' | 'bytes'
+  character?: string
 }
 
 /**
@@ -155,7 +206,8 @@ export async function submitInstall(req: InstallRequest): Promise<InstallRespons
       model: req.model || null,
       driverInstallPolicy: req.driverInstallPolicy || 'always',
       installMode: req.installMode,
-      dryRun: finalDryRun
+      dryRun: finalDryRun,
+      queueConflict: req.queueConflict || null
     }
     
     // 双保险日志：记录最终传给后端的 dryRun 值
@@ -163,10 +215,10 @@ export async function submitInstall(req: InstallRequest): Promise<InstallRespons
       console.warn('[InstallService][Safety] dryRun=true 将被传给后端（请确认这是调试模式）')
     }
     
-    console.log('[InstallService] invoke install_printer', payload)
+    console.log('[InstallService] invoke api_install_printer', payload)
     
     // 3. 调用后端
-    const result = await invoke('install_printer', payload) as any
+    const result = await invokeApi('install_printer', payload)
     
     console.log('[InstallService] install_printer result', {
       success: result.success,
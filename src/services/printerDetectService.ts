@@ -26,6 +26,10 @@ export interface PrinterDetectEntry {
   displayName?: string
   deviceUri?: string
   platform?: string
+  // 是否由本工具安装（仅 managed 队列提供删除 / 修复）
+  managed?: boolean
+  // 对应的配置打印机名称
+  configKey?: string | null
 }
 
 /**